@@ -4,15 +4,27 @@
 //! integrating with the soul-audio-desktop backend/device system.
 
 use serde::{Deserialize, Serialize};
+use soul_audio::analysis::{spectrum_band_frequencies, AudioAnalysis};
+use soul_audio::channels::DownmixSettings;
 use soul_audio_desktop::{
     backend, device, AudioBackend, AudioDeviceInfo, BackendInfo, DeviceCapabilities,
     ExclusiveConfig, LatencyInfo, SupportedBitDepth,
 };
+use soul_playback::PlaybackRate;
 use tauri::State;
 
 use crate::app_state::AppState;
 use crate::playback::PlaybackManager;
 
+/// Settings key for the output dither mode
+const SETTING_DITHER_MODE: &str = "audio.dither_mode";
+
+/// Settings key for the surround downmix coefficients
+const SETTING_DOWNMIX: &str = "audio.downmix";
+
+/// Settings key for the default playback speed and pitch
+const SETTING_PLAYBACK_RATE: &str = "audio.playback_rate";
+
 /// Frontend-compatible backend info
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -33,6 +45,7 @@ impl From<BackendInfo> for FrontendBackendInfo {
             AudioBackend::Asio => "asio",
             #[cfg(any(target_os = "linux", target_os = "macos"))]
             AudioBackend::Jack => "jack",
+            AudioBackend::Virtual => "virtual",
         };
 
         Self {
@@ -155,6 +168,7 @@ impl From<AudioDeviceInfo> for FrontendDeviceInfo {
             AudioBackend::Asio => "asio",
             #[cfg(any(target_os = "linux", target_os = "macos"))]
             AudioBackend::Jack => "jack",
+            AudioBackend::Virtual => "virtual",
         };
 
         Self {
@@ -170,14 +184,27 @@ impl From<AudioDeviceInfo> for FrontendDeviceInfo {
     }
 }
 
+/// Convert AudioBackend enum to its frontend/settings string
+pub(crate) fn backend_to_str(backend: AudioBackend) -> &'static str {
+    match backend {
+        AudioBackend::Default => "default",
+        #[cfg(target_os = "windows")]
+        AudioBackend::Asio => "asio",
+        #[cfg(any(target_os = "linux", target_os = "macos"))]
+        AudioBackend::Jack => "jack",
+        AudioBackend::Virtual => "virtual",
+    }
+}
+
 /// Parse backend string to AudioBackend enum
-fn parse_backend(backend_str: &str) -> Result<AudioBackend, String> {
+pub(crate) fn parse_backend(backend_str: &str) -> Result<AudioBackend, String> {
     match backend_str {
         "default" => Ok(AudioBackend::Default),
         #[cfg(target_os = "windows")]
         "asio" => Ok(AudioBackend::Asio),
         #[cfg(any(target_os = "linux", target_os = "macos"))]
         "jack" => Ok(AudioBackend::Jack),
+        "virtual" => Ok(AudioBackend::Virtual),
         _ => Err(format!("Unknown backend: {}", backend_str)),
     }
 }
@@ -247,6 +274,23 @@ pub async fn get_audio_devices(backend_str: String) -> Result<Vec<FrontendDevice
     Ok(frontend_devices)
 }
 
+/// Get the input (recording) devices for a backend, for measurement microphones
+#[tauri::command]
+pub async fn get_audio_input_devices(
+    backend_str: String,
+) -> Result<Vec<FrontendDeviceInfo>, String> {
+    let backend = parse_backend(&backend_str)?;
+    let devices = device::list_input_devices(backend).map_err(|e| e.to_string())?;
+
+    eprintln!(
+        "[audio_settings] Found {} input devices for {}",
+        devices.len(),
+        backend_str
+    );
+
+    Ok(devices.into_iter().map(FrontendDeviceInfo::from).collect())
+}
+
 /// Set the audio output device
 ///
 /// This will switch the audio device during playback if possible.
@@ -371,13 +415,7 @@ pub async fn get_current_audio_device(
     // Get the actual sample rate from the playback system (what we're outputting at)
     let active_sample_rate = playback.get_current_sample_rate();
 
-    let backend_str = match backend {
-        AudioBackend::Default => "default",
-        #[cfg(target_os = "windows")]
-        AudioBackend::Asio => "asio",
-        #[cfg(any(target_os = "linux", target_os = "macos"))]
-        AudioBackend::Jack => "jack",
-    };
+    let backend_str = backend_to_str(backend);
 
     // Try to get device info by listing all devices and finding the matching one
     let (channels, is_default) = match device::list_devices(backend) {
@@ -510,6 +548,12 @@ mod tests {
         assert!(result.unwrap_err().contains("Unknown backend"));
     }
 
+    #[test]
+    fn test_backend_to_str_round_trips() {
+        let backend = parse_backend(backend_to_str(AudioBackend::Default)).unwrap();
+        assert_eq!(backend, AudioBackend::Default);
+    }
+
     #[cfg(target_os = "windows")]
     #[test]
     fn test_parse_backend_asio() {
@@ -524,6 +568,13 @@ mod tests {
         assert_eq!(backend, AudioBackend::Jack);
     }
 
+    #[test]
+    fn test_parse_backend_virtual() {
+        let backend = parse_backend("virtual").unwrap();
+        assert_eq!(backend, AudioBackend::Virtual);
+        assert_eq!(backend_to_str(backend), "virtual");
+    }
+
     #[tokio::test]
     async fn test_get_backends() {
         let backends = get_audio_backends().await.unwrap();
@@ -1399,6 +1450,351 @@ pub async fn get_resampling_settings(
     })
 }
 
+// ===== Dither =====
+
+/// Set output dither mode
+///
+/// Modes:
+/// - "none": No dither (round to nearest)
+/// - "tpdf": Flat TPDF dither (default)
+/// - "lipshitz": Noise-shaped, 5-tap Lipshitz curve
+/// - "f-weighted": Noise-shaped, 9-tap F-weighted curve
+/// - "e-weighted": Noise-shaped, 9-tap E-weighted curve (most aggressive)
+///
+/// Applies immediately when the output device uses an integer format.
+/// Noise shaping is used at 44.1/48 kHz; other rates fall back to TPDF.
+#[tauri::command]
+pub async fn set_dither_mode(
+    mode: String,
+    playback: State<'_, PlaybackManager>,
+    app_state: State<'_, AppState>,
+) -> Result<(), String> {
+    eprintln!("[audio_settings] Setting dither mode: {}", mode);
+
+    playback.set_dither_mode(&mode)?;
+
+    soul_storage::settings::set_setting(
+        &app_state.pool,
+        &app_state.user_id,
+        SETTING_DITHER_MODE,
+        &serde_json::json!(mode),
+    )
+    .await
+    .map_err(|e| format!("Failed to save dither mode: {}", e))?;
+
+    Ok(())
+}
+
+/// Get current output dither mode
+#[tauri::command]
+pub async fn get_dither_mode(playback: State<'_, PlaybackManager>) -> Result<String, String> {
+    Ok(playback.get_dither_mode())
+}
+
+/// Restore the saved dither mode on startup
+pub async fn initialize_dither_mode(
+    playback: &PlaybackManager,
+    app_state: &AppState,
+) -> Result<(), String> {
+    let saved_mode = soul_storage::settings::get_setting(
+        &app_state.pool,
+        &app_state.user_id,
+        SETTING_DITHER_MODE,
+    )
+    .await
+    .map_err(|e| format!("Failed to load dither mode setting: {}", e))?;
+
+    if let Some(mode) = saved_mode.as_ref().and_then(|v| v.as_str()) {
+        match playback.set_dither_mode(mode) {
+            Ok(()) => eprintln!("[audio_settings] Dither mode restored to: {}", mode),
+            Err(e) => eprintln!("[audio_settings] Ignoring saved dither mode: {}", e),
+        }
+    }
+
+    Ok(())
+}
+
+// ===== Channel Layout =====
+
+/// Frontend-compatible downmix coefficients
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrontendDownmixSettings {
+    pub center_gain: f32,
+    pub surround_gain: f32,
+    pub lfe_gain: f32,
+    pub normalize: bool,
+}
+
+impl From<DownmixSettings> for FrontendDownmixSettings {
+    fn from(settings: DownmixSettings) -> Self {
+        Self {
+            center_gain: settings.center_gain,
+            surround_gain: settings.surround_gain,
+            lfe_gain: settings.lfe_gain,
+            normalize: settings.normalize,
+        }
+    }
+}
+
+impl From<FrontendDownmixSettings> for DownmixSettings {
+    fn from(settings: FrontendDownmixSettings) -> Self {
+        Self {
+            center_gain: settings.center_gain,
+            surround_gain: settings.surround_gain,
+            lfe_gain: settings.lfe_gain,
+            normalize: settings.normalize,
+        }
+    }
+}
+
+/// Get the channel count of the current output stream (2 = stereo, 6 = 5.1, 8 = 7.1)
+#[tauri::command]
+pub async fn get_output_channels(playback: State<'_, PlaybackManager>) -> Result<u16, String> {
+    Ok(playback.get_output_channels())
+}
+
+/// Set the coefficients used to fold surround files down to fewer channels
+///
+/// Defaults to ITU-R BS.775 (-3 dB center and surrounds, LFE dropped).
+/// Applies to tracks loaded after the call.
+#[tauri::command]
+pub async fn set_downmix_settings(
+    settings: FrontendDownmixSettings,
+    playback: State<'_, PlaybackManager>,
+    app_state: State<'_, AppState>,
+) -> Result<(), String> {
+    eprintln!("[audio_settings] Setting downmix: {:?}", settings);
+
+    playback.set_downmix_settings(settings.clone().into())?;
+
+    let value = serde_json::to_value(&settings)
+        .map_err(|e| format!("Failed to serialize downmix settings: {}", e))?;
+    soul_storage::settings::set_setting(
+        &app_state.pool,
+        &app_state.user_id,
+        SETTING_DOWNMIX,
+        &value,
+    )
+    .await
+    .map_err(|e| format!("Failed to save downmix settings: {}", e))?;
+
+    Ok(())
+}
+
+/// Get current downmix coefficients
+#[tauri::command]
+pub async fn get_downmix_settings(
+    playback: State<'_, PlaybackManager>,
+) -> Result<FrontendDownmixSettings, String> {
+    Ok(playback.get_downmix_settings().into())
+}
+
+/// Restore the saved downmix coefficients on startup
+pub async fn initialize_downmix_settings(
+    playback: &PlaybackManager,
+    app_state: &AppState,
+) -> Result<(), String> {
+    let saved =
+        soul_storage::settings::get_setting(&app_state.pool, &app_state.user_id, SETTING_DOWNMIX)
+            .await
+            .map_err(|e| format!("Failed to load downmix setting: {}", e))?;
+
+    if let Some(settings) =
+        saved.and_then(|v| serde_json::from_value::<FrontendDownmixSettings>(v).ok())
+    {
+        match playback.set_downmix_settings(settings.into()) {
+            Ok(()) => eprintln!("[audio_settings] Downmix settings restored"),
+            Err(e) => eprintln!("[audio_settings] Ignoring saved downmix settings: {}", e),
+        }
+    }
+
+    Ok(())
+}
+
+// ===== Playback Speed =====
+
+/// Frontend-compatible playback speed and pitch
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrontendPlaybackRate {
+    /// Speed multiplier (0.5-3.0), pitch preserved
+    pub speed: f32,
+    /// Pitch shift in semitones (-12 to +12)
+    #[serde(default)]
+    pub pitch_semitones: f32,
+}
+
+impl From<PlaybackRate> for FrontendPlaybackRate {
+    fn from(rate: PlaybackRate) -> Self {
+        Self {
+            speed: rate.speed,
+            pitch_semitones: rate.pitch_semitones,
+        }
+    }
+}
+
+impl From<FrontendPlaybackRate> for PlaybackRate {
+    fn from(rate: FrontendPlaybackRate) -> Self {
+        PlaybackRate::new(rate.speed, rate.pitch_semitones)
+    }
+}
+
+/// Set the default playback speed and pitch
+///
+/// Applies immediately to tracks without their own rate. Values are clamped
+/// to 0.5-3.0x and +/-12 semitones.
+#[tauri::command]
+pub async fn set_playback_rate(
+    rate: FrontendPlaybackRate,
+    playback: State<'_, PlaybackManager>,
+    app_state: State<'_, AppState>,
+) -> Result<FrontendPlaybackRate, String> {
+    eprintln!("[audio_settings] Setting playback rate: {:?}", rate);
+
+    let rate = PlaybackRate::from(rate);
+    playback.set_playback_rate(rate);
+
+    let value = serde_json::to_value(FrontendPlaybackRate::from(rate))
+        .map_err(|e| format!("Failed to serialize playback rate: {}", e))?;
+    soul_storage::settings::set_setting(
+        &app_state.pool,
+        &app_state.user_id,
+        SETTING_PLAYBACK_RATE,
+        &value,
+    )
+    .await
+    .map_err(|e| format!("Failed to save playback rate: {}", e))?;
+
+    Ok(rate.into())
+}
+
+/// Get the default playback speed and pitch
+#[tauri::command]
+pub async fn get_playback_rate(
+    playback: State<'_, PlaybackManager>,
+) -> Result<FrontendPlaybackRate, String> {
+    Ok(playback.get_playback_rate().into())
+}
+
+/// Set the playback speed and pitch of the current track only
+///
+/// Pass null to return the track to the default rate. Not persisted.
+#[tauri::command]
+pub async fn set_current_track_playback_rate(
+    rate: Option<FrontendPlaybackRate>,
+    playback: State<'_, PlaybackManager>,
+) -> Result<(), String> {
+    eprintln!(
+        "[audio_settings] Setting current track playback rate: {:?}",
+        rate
+    );
+    playback.set_current_track_playback_rate(rate.map(PlaybackRate::from));
+    Ok(())
+}
+
+/// Get the playback speed and pitch in effect for the current track
+#[tauri::command]
+pub async fn get_current_playback_rate(
+    playback: State<'_, PlaybackManager>,
+) -> Result<FrontendPlaybackRate, String> {
+    Ok(playback.get_current_playback_rate().into())
+}
+
+/// Restore the saved default playback rate on startup
+pub async fn initialize_playback_rate(
+    playback: &PlaybackManager,
+    app_state: &AppState,
+) -> Result<(), String> {
+    let saved = soul_storage::settings::get_setting(
+        &app_state.pool,
+        &app_state.user_id,
+        SETTING_PLAYBACK_RATE,
+    )
+    .await
+    .map_err(|e| format!("Failed to load playback rate setting: {}", e))?;
+
+    if let Some(rate) = saved.and_then(|v| serde_json::from_value::<FrontendPlaybackRate>(v).ok()) {
+        playback.set_playback_rate(rate.into());
+        eprintln!("[audio_settings] Playback rate restored: {:?}", rate);
+    }
+
+    Ok(())
+}
+
+// ===== Audio Analysis =====
+
+/// Level, loudness and spectrum snapshot for meters and visualisers
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrontendAudioAnalysis {
+    /// Increments with every snapshot; unchanged means nothing new to draw
+    pub sequence: u64,
+    /// Peak level per output channel in dBFS
+    pub peak_db: Vec<f32>,
+    /// RMS level per output channel in dBFS
+    pub rms_db: Vec<f32>,
+    /// Momentary loudness (400ms) in LUFS, None when silent
+    pub momentary_lufs: Option<f32>,
+    /// Short-term loudness (3s) in LUFS, None when silent
+    pub short_term_lufs: Option<f32>,
+    /// Spectrum magnitude per log-spaced band in dBFS
+    pub spectrum_db: Vec<f32>,
+}
+
+impl From<AudioAnalysis> for FrontendAudioAnalysis {
+    fn from(analysis: AudioAnalysis) -> Self {
+        let channels = analysis.channels as usize;
+        let finite = |lufs: f32| lufs.is_finite().then_some(lufs);
+        Self {
+            sequence: analysis.sequence,
+            peak_db: (0..channels).map(|ch| analysis.peak_dbfs(ch)).collect(),
+            rms_db: (0..channels).map(|ch| analysis.rms_dbfs(ch)).collect(),
+            momentary_lufs: finite(analysis.momentary_lufs),
+            short_term_lufs: finite(analysis.short_term_lufs),
+            spectrum_db: analysis.spectrum_db.to_vec(),
+        }
+    }
+}
+
+/// Enable or disable the level/spectrum analysis tap
+///
+/// Analysis costs a little CPU on the audio thread, so the UI should only
+/// enable it while a meter or visualiser is visible.
+#[tauri::command]
+pub async fn set_audio_analysis_enabled(
+    #[allow(unused_variables)] playback: State<'_, PlaybackManager>,
+    #[allow(unused_variables)] enabled: bool,
+) -> Result<(), String> {
+    #[cfg(feature = "effects")]
+    {
+        playback.set_analysis_enabled(enabled);
+        Ok(())
+    }
+
+    #[cfg(not(feature = "effects"))]
+    {
+        Err("Effects feature not enabled".to_string())
+    }
+}
+
+/// Get the latest analysis snapshot (poll at display rate)
+#[tauri::command]
+pub async fn get_audio_analysis(
+    playback: State<'_, PlaybackManager>,
+) -> Result<Option<FrontendAudioAnalysis>, String> {
+    Ok(playback.get_audio_analysis().map(Into::into))
+}
+
+/// Get the center frequency of each spectrum band for the current output rate
+#[tauri::command]
+pub async fn get_spectrum_band_frequencies(
+    playback: State<'_, PlaybackManager>,
+) -> Result<Vec<f32>, String> {
+    let sample_rate = playback.get_current_sample_rate();
+    Ok(spectrum_band_frequencies(sample_rate).to_vec())
+}
+
 // ===== Headroom Management =====
 
 /// Headroom mode for frontend
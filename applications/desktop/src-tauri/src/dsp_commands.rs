@@ -4,14 +4,25 @@
 //! during playback. Effects are processed in series before upsampling.
 //!
 //! The DSP chain is persisted to the database and restored on app startup.
+//! Saved chain presets can be bound to output devices; the bound preset is
+//! loaded whenever playback moves to that device.
+//!
+//! Tracks, albums and genres can carry their own preset and gain offset.
+//! On every track change these are resolved with precedence
+//! track > album > genre > device profile > global chain.
 
 use crate::app_state::AppState;
+use crate::audio_settings::{backend_to_str, parse_backend};
 use crate::playback::PlaybackManager;
 use serde::{Deserialize, Serialize};
 use soul_audio::effects::{
-    CompressorSettings, CrossfeedPreset, CrossfeedSettings, EqBand, GraphicEqPreset,
-    LimiterSettings, StereoSettings,
+    AudioEffect, Compressor, CompressorSettings, ConvolutionEngine, Crossfeed, CrossfeedPreset,
+    CrossfeedSettings, DynamicEq, DynamicEqBand, EqBand, EqPhaseMode, EqPreset, FilterAlignment,
+    FilterSlope, FilterType, GraphicEq, GraphicEqBands, GraphicEqPreset, Limiter, LimiterSettings,
+    MultibandCompressor, MultibandCompressorSettings, ParametricEq, StereoEnhancer, StereoSettings,
 };
+use soul_audio_desktop::AudioBackend;
+use soul_storage::dsp_overrides::{DspOverride, OverrideScope, ResolvedOverride};
 use sqlx::SqlitePool;
 use tauri::State;
 
@@ -23,9 +34,21 @@ pub const DSP_CHAIN_SETTING_KEY: &str = "audio.dsp_chain";
 #[serde(tag = "type", rename_all = "camelCase")]
 pub enum EffectType {
     #[serde(rename = "eq")]
-    Eq { bands: Vec<EqBandData> },
+    Eq {
+        bands: Vec<EqBandData>,
+        /// Preamp from an imported preset (reserved as headroom, not applied as gain)
+        #[serde(default, rename = "preampDb")]
+        preamp_db: f32,
+        /// Run as a linear-phase FIR (adds latency)
+        #[serde(default, rename = "linearPhase")]
+        linear_phase: bool,
+    },
     #[serde(rename = "compressor")]
     Compressor { settings: CompressorData },
+    #[serde(rename = "multiband_compressor")]
+    MultibandCompressor { settings: MultibandCompressorData },
+    #[serde(rename = "dynamic_eq")]
+    DynamicEq { bands: Vec<DynamicEqBandData> },
     #[serde(rename = "limiter")]
     Limiter { settings: LimiterData },
     #[serde(rename = "crossfeed")]
@@ -38,6 +61,83 @@ pub enum EffectType {
     Convolution { settings: ConvolutionData },
 }
 
+impl EffectType {
+    /// Type ID of this effect in the [`soul_audio::pipeline::EffectRegistry`]
+    pub fn registry_type_id(&self) -> &'static str {
+        match self {
+            Self::Eq { .. } => "parametric_eq",
+            Self::Compressor { .. } => "compressor",
+            Self::MultibandCompressor { .. } => "multiband_compressor",
+            Self::DynamicEq { .. } => "dynamic_eq",
+            Self::Limiter { .. } => "limiter",
+            Self::Crossfeed { .. } => "crossfeed",
+            Self::Stereo { .. } => "stereo_enhancer",
+            Self::GraphicEq { .. } => "graphic_eq",
+            Self::Convolution { .. } => "convolution",
+        }
+    }
+}
+
+/// Phase mode for an EQ's `linearPhase` flag
+pub fn eq_phase_mode(linear_phase: bool) -> EqPhaseMode {
+    if linear_phase {
+        EqPhaseMode::LinearPhase
+    } else {
+        EqPhaseMode::MinimumPhase
+    }
+}
+
+/// EQ band filter shape for frontend
+///
+/// Names follow the parametric EQ editor (`bell`, `lowShelf`, ...).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EqFilterTypeData {
+    LowShelf,
+    /// Chains saved before the editor names were used say "peaking"
+    #[default]
+    #[serde(rename = "bell", alias = "peaking")]
+    Peaking,
+    HighShelf,
+    HighPass,
+    LowPass,
+    Notch,
+    BandPass,
+    AllPass,
+    TiltShelf,
+}
+
+/// High-pass/low-pass alignment for frontend
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EqFilterAlignmentData {
+    #[default]
+    Butterworth,
+    LinkwitzRiley,
+}
+
+impl From<FilterAlignment> for EqFilterAlignmentData {
+    fn from(alignment: FilterAlignment) -> Self {
+        match alignment {
+            FilterAlignment::Butterworth => Self::Butterworth,
+            FilterAlignment::LinkwitzRiley => Self::LinkwitzRiley,
+        }
+    }
+}
+
+impl From<EqFilterAlignmentData> for FilterAlignment {
+    fn from(data: EqFilterAlignmentData) -> Self {
+        match data {
+            EqFilterAlignmentData::Butterworth => Self::Butterworth,
+            EqFilterAlignmentData::LinkwitzRiley => Self::LinkwitzRiley,
+        }
+    }
+}
+
+fn default_filter_slope() -> u32 {
+    FilterSlope::default().db_per_octave()
+}
+
 /// EQ band data for frontend
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -45,21 +145,66 @@ pub struct EqBandData {
     pub frequency: f32,
     pub gain: f32,
     pub q: f32,
+    /// Older saved chains have no filter type; they were all peaking
+    #[serde(default)]
+    pub filter_type: EqFilterTypeData,
+    /// High-pass/low-pass alignment (ignored by other filter types)
+    #[serde(default)]
+    pub alignment: EqFilterAlignmentData,
+    /// High-pass/low-pass slope in dB/octave: 12, 24 or 48
+    #[serde(default = "default_filter_slope")]
+    pub slope: u32,
 }
 
 impl From<EqBand> for EqBandData {
     fn from(band: EqBand) -> Self {
+        let filter_type = match band.filter_type() {
+            FilterType::LowShelf => EqFilterTypeData::LowShelf,
+            FilterType::Peaking => EqFilterTypeData::Peaking,
+            FilterType::HighShelf => EqFilterTypeData::HighShelf,
+            FilterType::HighPass { .. } => EqFilterTypeData::HighPass,
+            FilterType::LowPass { .. } => EqFilterTypeData::LowPass,
+            FilterType::Notch => EqFilterTypeData::Notch,
+            FilterType::BandPass => EqFilterTypeData::BandPass,
+            FilterType::AllPass => EqFilterTypeData::AllPass,
+            FilterType::TiltShelf => EqFilterTypeData::TiltShelf,
+        };
+        let (alignment, slope) = match band.filter_type() {
+            FilterType::HighPass { alignment, slope }
+            | FilterType::LowPass { alignment, slope } => (alignment, slope),
+            _ => Default::default(),
+        };
+
         Self {
             frequency: band.frequency,
             gain: band.gain_db(),
             q: band.q(),
+            filter_type,
+            alignment: alignment.into(),
+            slope: slope.db_per_octave(),
         }
     }
 }
 
 impl From<EqBandData> for EqBand {
     fn from(data: EqBandData) -> Self {
-        EqBand::new(data.frequency, data.gain, data.q)
+        let alignment = data.alignment.into();
+        // Unknown slopes fall back to 12 dB/oct rather than rejecting the chain
+        let slope = FilterSlope::from_db_per_octave(data.slope).unwrap_or_default();
+
+        let filter_type = match data.filter_type {
+            EqFilterTypeData::LowShelf => FilterType::LowShelf,
+            EqFilterTypeData::Peaking => FilterType::Peaking,
+            EqFilterTypeData::HighShelf => FilterType::HighShelf,
+            EqFilterTypeData::HighPass => FilterType::HighPass { alignment, slope },
+            EqFilterTypeData::LowPass => FilterType::LowPass { alignment, slope },
+            EqFilterTypeData::Notch => FilterType::Notch,
+            EqFilterTypeData::BandPass => FilterType::BandPass,
+            EqFilterTypeData::AllPass => FilterType::AllPass,
+            EqFilterTypeData::TiltShelf => FilterType::TiltShelf,
+        };
+
+        EqBand::with_filter_type(filter_type, data.frequency, data.gain, data.q)
     }
 }
 
@@ -101,6 +246,78 @@ impl From<CompressorData> for CompressorSettings {
     }
 }
 
+/// Multiband compressor settings for frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultibandCompressorData {
+    /// Crossover frequencies in Hz, ascending (one fewer than bands)
+    pub crossovers_hz: Vec<f32>,
+    /// Compressor settings per band, from lowest to highest
+    pub bands: Vec<CompressorData>,
+}
+
+impl From<MultibandCompressorSettings> for MultibandCompressorData {
+    fn from(settings: MultibandCompressorSettings) -> Self {
+        Self {
+            crossovers_hz: settings.crossovers_hz,
+            bands: settings.bands.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<MultibandCompressorData> for MultibandCompressorSettings {
+    fn from(data: MultibandCompressorData) -> Self {
+        MultibandCompressorSettings {
+            crossovers_hz: data.crossovers_hz,
+            bands: data.bands.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// Dynamic EQ band for frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DynamicEqBandData {
+    pub frequency: f32,
+    pub q: f32,
+    pub threshold_db: f32,
+    pub ratio: f32,
+    /// Maximum gain change: negative cuts the band when loud, positive boosts it
+    pub range_db: f32,
+    pub attack_ms: f32,
+    pub release_ms: f32,
+}
+
+impl From<DynamicEqBand> for DynamicEqBandData {
+    fn from(band: DynamicEqBand) -> Self {
+        Self {
+            frequency: band.frequency,
+            q: band.q,
+            threshold_db: band.threshold_db,
+            ratio: band.ratio,
+            range_db: band.range_db,
+            attack_ms: band.attack_ms,
+            release_ms: band.release_ms,
+        }
+    }
+}
+
+impl From<DynamicEqBandData> for DynamicEqBand {
+    fn from(data: DynamicEqBandData) -> Self {
+        let mut band = DynamicEqBand {
+            frequency: data.frequency,
+            q: data.q,
+            threshold_db: data.threshold_db,
+            ratio: data.ratio,
+            range_db: data.range_db,
+            attack_ms: data.attack_ms,
+            release_ms: data.release_ms,
+        };
+        band.validate();
+        band
+    }
+}
+
 /// Limiter settings for frontend
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -207,6 +424,9 @@ pub struct GraphicEqData {
     pub preset: String,
     pub band_count: u8,
     pub gains: Vec<f32>,
+    /// Run as a linear-phase FIR (adds latency)
+    #[serde(default)]
+    pub linear_phase: bool,
 }
 
 impl GraphicEqData {
@@ -216,6 +436,7 @@ impl GraphicEqData {
             preset: preset.name().to_string(),
             band_count: 10,
             gains,
+            linear_phase: false,
         }
     }
 
@@ -224,6 +445,7 @@ impl GraphicEqData {
             preset: "Flat".to_string(),
             band_count: 10,
             gains: vec![0.0; 10],
+            linear_phase: false,
         }
     }
 
@@ -232,6 +454,7 @@ impl GraphicEqData {
             preset: "Flat".to_string(),
             band_count: 31,
             gains: vec![0.0; 31],
+            linear_phase: false,
         }
     }
 }
@@ -242,6 +465,9 @@ impl GraphicEqData {
 pub struct ConvolutionData {
     /// Path to impulse response file (WAV/FLAC)
     pub ir_file_path: String,
+    /// Extra IR files measured at other sample rates (the stream rate picks one)
+    #[serde(default)]
+    pub ir_set_paths: Vec<String>,
     /// Wet/dry mix: 0.0 (fully dry) to 1.0 (fully wet)
     pub wet_dry_mix: f32,
     /// Pre-delay in milliseconds (0-100ms)
@@ -254,6 +480,7 @@ impl Default for ConvolutionData {
     fn default() -> Self {
         Self {
             ir_file_path: String::new(),
+            ir_set_paths: Vec::new(),
             wet_dry_mix: 0.3,
             pre_delay_ms: 0.0,
             decay: 1.0,
@@ -277,6 +504,152 @@ pub struct EffectSlotState {
     pub enabled: bool,
 }
 
+impl EffectSlotState {
+    /// Build the audio effect for this slot
+    ///
+    /// `sample_rate` picks (and resamples) the convolution IR up front;
+    /// 0 leaves that to the first processed buffer.
+    pub fn build_effect(&self, sample_rate: u32) -> Box<dyn AudioEffect> {
+        let mut effect: Box<dyn AudioEffect> = match &self.effect {
+            EffectType::Eq {
+                bands,
+                linear_phase,
+                ..
+            } => {
+                let mut eq = ParametricEq::new();
+                eq.set_bands(bands.iter().map(|b| b.clone().into()).collect());
+                eq.set_phase_mode(eq_phase_mode(*linear_phase));
+                Box::new(eq)
+            }
+            EffectType::Compressor { settings } => {
+                Box::new(Compressor::with_settings(settings.clone().into()))
+            }
+            EffectType::MultibandCompressor { settings } => {
+                Box::new(MultibandCompressor::with_settings(settings.clone().into()))
+            }
+            EffectType::DynamicEq { bands } => Box::new(DynamicEq::with_bands(
+                bands.iter().map(|b| b.clone().into()).collect(),
+            )),
+            EffectType::Limiter { settings } => {
+                Box::new(Limiter::with_settings(settings.clone().into()))
+            }
+            EffectType::Crossfeed { settings } => {
+                let preset = match settings.preset.as_str() {
+                    "natural" => CrossfeedPreset::Natural,
+                    "relaxed" => CrossfeedPreset::Relaxed,
+                    "meier" => CrossfeedPreset::Meier,
+                    _ => CrossfeedPreset::Custom,
+                };
+
+                let crossfeed_settings = if preset == CrossfeedPreset::Custom {
+                    CrossfeedSettings::custom(settings.level_db, settings.cutoff_hz)
+                } else {
+                    CrossfeedSettings::from_preset(preset)
+                };
+
+                Box::new(Crossfeed::with_settings(crossfeed_settings))
+            }
+            EffectType::Stereo { settings } => {
+                let stereo_settings = StereoSettings {
+                    width: settings.width,
+                    mid_gain_db: settings.mid_gain_db,
+                    side_gain_db: settings.side_gain_db,
+                    balance: settings.balance,
+                };
+
+                Box::new(StereoEnhancer::with_settings(stereo_settings))
+            }
+            EffectType::GraphicEq { settings } => {
+                let mut graphic_eq = if settings.band_count == 31 {
+                    GraphicEq::new(GraphicEqBands::ThirtyOne)
+                } else {
+                    GraphicEq::new_10_band()
+                };
+
+                // Apply gains if we have the right number
+                if settings.band_count == 10 && settings.gains.len() == 10 {
+                    if let Ok(gains) = settings.gains.clone().try_into() {
+                        graphic_eq.set_gains_10(gains);
+                    }
+                } else {
+                    // For 31-band or custom, set each band individually
+                    for (i, &gain) in settings.gains.iter().enumerate() {
+                        graphic_eq.set_band_gain(i, gain);
+                    }
+                }
+
+                graphic_eq.set_phase_mode(eq_phase_mode(settings.linear_phase));
+                Box::new(graphic_eq)
+            }
+            EffectType::Convolution { settings } => {
+                let mut conv = ConvolutionEngine::new();
+
+                // Load IR (plus any per-sample-rate variants) if provided
+                if !settings.ir_file_path.is_empty() {
+                    let paths: Vec<&String> = std::iter::once(&settings.ir_file_path)
+                        .chain(settings.ir_set_paths.iter())
+                        .collect();
+                    match conv.load_from_wavs(&paths) {
+                        Ok(()) => {
+                            conv.set_dry_wet_mix(settings.wet_dry_mix);
+                            // Pick/resample the IR now rather than on the audio thread
+                            if sample_rate > 0 {
+                                if let Err(e) = conv.set_sample_rate(sample_rate) {
+                                    eprintln!("[build_effect] No IR for {} Hz: {}", sample_rate, e);
+                                }
+                            }
+                            // Note: pre_delay_ms and decay are UI-only for now
+                            // The ConvolutionEngine applies full IR as-is
+                            eprintln!("[build_effect] Loaded IR: {}", settings.ir_file_path);
+                        }
+                        Err(e) => {
+                            eprintln!(
+                                "[build_effect] Failed to load IR file '{}': {}",
+                                settings.ir_file_path, e
+                            );
+                            // Keep the engine but it won't process anything
+                        }
+                    }
+                }
+
+                Box::new(conv)
+            }
+        };
+
+        effect.set_enabled(self.enabled);
+        effect
+    }
+}
+
+/// Headroom the enabled EQs of a chain can boost by, in dB
+///
+/// An imported AutoEQ/APO preamp tells us how much its filter set boosts;
+/// upward dynamic EQ bands can boost by their full range.
+pub fn eq_boost_db(slots: &[Option<EffectSlotState>; 4]) -> f32 {
+    slots
+        .iter()
+        .flatten()
+        .filter(|slot| slot.enabled)
+        .filter_map(|slot| match &slot.effect {
+            EffectType::Eq { preamp_db, .. } => Some(-preamp_db),
+            EffectType::DynamicEq { bands } => {
+                Some(bands.iter().map(|b| b.range_db).fold(0.0_f32, f32::max))
+            }
+            _ => None,
+        })
+        .fold(0.0_f32, f32::max)
+}
+
+/// DSP override preset currently replacing the user's chain
+///
+/// `base_slots` holds the chain to return to (device profile / global
+/// chain) once playback reaches a track without an override preset.
+#[derive(Debug, Clone)]
+pub struct ActiveDspOverride {
+    pub preset_id: i64,
+    pub base_slots: [Option<EffectSlotState>; 4],
+}
+
 /// Persisted DSP chain data structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -417,9 +790,17 @@ pub async fn restore_dsp_chain_from_database(
 }
 
 /// Helper to persist DSP chain after modification using PlaybackManager and AppState
+///
+/// Skipped while a track override preset is playing, so the override
+/// doesn't become the user's chain.
 async fn persist_current_chain(playback: &PlaybackManager, app_state: &AppState) {
     #[cfg(feature = "effects")]
     {
+        if matches!(playback.with_dsp_override(|o| o.is_some()), Ok(true)) {
+            eprintln!("[persist_current_chain] DSP override active, not saving chain");
+            return;
+        }
+
         match playback.get_effect_slots() {
             Ok(slots) => {
                 persist_dsp_chain(&app_state.pool, &app_state.user_id, &slots).await;
@@ -443,6 +824,8 @@ pub async fn get_available_effects() -> Result<Vec<String>, String> {
         "eq".to_string(),
         "graphic_eq".to_string(),
         "compressor".to_string(),
+        "multiband_compressor".to_string(),
+        "dynamic_eq".to_string(),
         "limiter".to_string(),
         "crossfeed".to_string(),
         "stereo".to_string(),
@@ -450,6 +833,25 @@ pub async fn get_available_effects() -> Result<Vec<String>, String> {
     ])
 }
 
+/// Get the effects the current output device bypasses
+///
+/// Stereo-only effects (EQs, crossfeed, convolution) don't run on surround
+/// devices; the UI lists them so they don't stop working silently.
+#[tauri::command]
+pub async fn get_bypassed_effects(
+    #[allow(unused_variables)] playback: State<'_, PlaybackManager>,
+) -> Result<Vec<String>, String> {
+    #[cfg(feature = "effects")]
+    {
+        playback.bypassed_effects()
+    }
+
+    #[cfg(not(feature = "effects"))]
+    {
+        Ok(Vec::new())
+    }
+}
+
 /// Get current DSP chain configuration
 #[tauri::command]
 pub async fn get_dsp_chain(
@@ -694,61 +1096,25 @@ pub async fn get_eq_presets() -> Result<Vec<(String, Vec<EqBandData>)>, String>
         (
             "Flat".to_string(),
             vec![
-                EqBandData {
-                    frequency: 100.0,
-                    gain: 0.0,
-                    q: 1.0,
-                },
-                EqBandData {
-                    frequency: 1000.0,
-                    gain: 0.0,
-                    q: 1.0,
-                },
-                EqBandData {
-                    frequency: 10000.0,
-                    gain: 0.0,
-                    q: 1.0,
-                },
+                EqBand::peaking(100.0, 0.0, 1.0).into(),
+                EqBand::peaking(1000.0, 0.0, 1.0).into(),
+                EqBand::peaking(10000.0, 0.0, 1.0).into(),
             ],
         ),
         (
             "Bass Boost".to_string(),
             vec![
-                EqBandData {
-                    frequency: 60.0,
-                    gain: 6.0,
-                    q: 1.0,
-                },
-                EqBandData {
-                    frequency: 200.0,
-                    gain: 3.0,
-                    q: 1.0,
-                },
-                EqBandData {
-                    frequency: 1000.0,
-                    gain: 0.0,
-                    q: 1.0,
-                },
+                EqBand::peaking(60.0, 6.0, 1.0).into(),
+                EqBand::peaking(200.0, 3.0, 1.0).into(),
+                EqBand::peaking(1000.0, 0.0, 1.0).into(),
             ],
         ),
         (
             "Treble Boost".to_string(),
             vec![
-                EqBandData {
-                    frequency: 1000.0,
-                    gain: 0.0,
-                    q: 1.0,
-                },
-                EqBandData {
-                    frequency: 5000.0,
-                    gain: 3.0,
-                    q: 1.0,
-                },
-                EqBandData {
-                    frequency: 12000.0,
-                    gain: 6.0,
-                    q: 1.0,
-                },
+                EqBand::peaking(1000.0, 0.0, 1.0).into(),
+                EqBand::peaking(5000.0, 3.0, 1.0).into(),
+                EqBand::peaking(12000.0, 6.0, 1.0).into(),
             ],
         ),
     ])
@@ -770,6 +1136,48 @@ pub async fn get_compressor_presets() -> Result<Vec<(String, CompressorData)>, S
     ])
 }
 
+/// Get multiband compressor presets
+#[tauri::command]
+pub async fn get_multiband_compressor_presets(
+) -> Result<Vec<(String, MultibandCompressorData)>, String> {
+    Ok(vec![
+        (
+            "3 Band".to_string(),
+            MultibandCompressorSettings::three_band().into(),
+        ),
+        (
+            "4 Band".to_string(),
+            MultibandCompressorSettings::four_band().into(),
+        ),
+        (
+            "5 Band".to_string(),
+            MultibandCompressorSettings::five_band().into(),
+        ),
+    ])
+}
+
+/// Get dynamic EQ presets
+#[tauri::command]
+pub async fn get_dynamic_eq_presets() -> Result<Vec<(String, Vec<DynamicEqBandData>)>, String> {
+    Ok(vec![
+        (
+            "De-esser".to_string(),
+            vec![DynamicEqBand::de_esser().into()],
+        ),
+        (
+            "Bass Tamer".to_string(),
+            vec![DynamicEqBand::bass_tamer().into()],
+        ),
+        (
+            "De-esser + Bass Tamer".to_string(),
+            vec![
+                DynamicEqBand::bass_tamer().into(),
+                DynamicEqBand::de_esser().into(),
+            ],
+        ),
+    ])
+}
+
 /// Get limiter presets
 #[tauri::command]
 pub async fn get_limiter_presets() -> Result<Vec<(String, LimiterData)>, String> {
@@ -1005,6 +1413,21 @@ pub async fn load_dsp_chain_preset(
         .parse()
         .map_err(|e| format!("Invalid user ID: {}", e))?;
 
+    let effect_chain = fetch_preset_chain(&app_state.pool, user_id, preset_id).await?;
+
+    // Loading a preset by hand replaces whatever override is playing
+    #[cfg(feature = "effects")]
+    playback.update_dsp_override(|o| *o = None)?;
+
+    apply_effect_chain(&playback, &app_state, effect_chain).await
+}
+
+/// Fetch the effect chain of a saved DSP chain preset
+pub async fn fetch_preset_chain(
+    pool: &SqlitePool,
+    user_id: i64,
+    preset_id: i64,
+) -> Result<Vec<EffectType>, String> {
     let preset = sqlx::query!(
         r#"
         SELECT effect_chain FROM dsp_presets WHERE id = ? AND user_id = ?
@@ -1012,22 +1435,506 @@ pub async fn load_dsp_chain_preset(
         preset_id,
         user_id
     )
-    .fetch_optional(&*app_state.pool)
+    .fetch_optional(pool)
     .await
     .map_err(|e| format!("Failed to fetch preset: {}", e))?
     .ok_or("Preset not found")?;
 
-    let effect_chain: Vec<EffectType> = serde_json::from_str(&preset.effect_chain)
-        .map_err(|e| format!("Failed to parse effect chain: {}", e))?;
+    serde_json::from_str(&preset.effect_chain)
+        .map_err(|e| format!("Failed to parse effect chain: {}", e))
+}
 
-    // Clear existing chain (pass app_state for persistence)
-    clear_dsp_chain(playback.clone(), app_state.clone()).await?;
+/// Lay out a preset's effects over the four slots (all enabled)
+pub fn chain_to_slots(
+    effect_chain: Vec<EffectType>,
+) -> Result<[Option<EffectSlotState>; 4], String> {
+    if effect_chain.len() > 4 {
+        return Err(format!(
+            "Preset has {} effects, the chain holds at most 4",
+            effect_chain.len()
+        ));
+    }
 
-    // Add each effect to its slot (already persists after each add)
-    for (slot_index, effect) in effect_chain.iter().enumerate() {
-        add_effect_to_chain(playback.clone(), app_state.clone(), slot_index, effect.clone())
-            .await?;
+    let mut slots: [Option<EffectSlotState>; 4] = Default::default();
+    for (slot, effect) in slots.iter_mut().zip(effect_chain) {
+        *slot = Some(EffectSlotState {
+            effect,
+            enabled: true,
+        });
+    }
+    Ok(slots)
+}
+
+/// Replace the whole effect chain (one effect per slot, all enabled) and persist it
+///
+/// While a track override preset is playing, only the chain to return to
+/// afterwards is replaced.
+async fn apply_effect_chain(
+    #[allow(unused_variables)] playback: &PlaybackManager,
+    #[allow(unused_variables)] app_state: &AppState,
+    effect_chain: Vec<EffectType>,
+) -> Result<(), String> {
+    #[allow(unused_variables)]
+    let slots = chain_to_slots(effect_chain)?;
+
+    #[cfg(feature = "effects")]
+    {
+        let deferred = playback.update_dsp_override(|o| match o {
+            Some(active) => {
+                active.base_slots = slots.clone();
+                true
+            }
+            None => false,
+        })?;
+
+        if deferred {
+            persist_dsp_chain(&app_state.pool, &app_state.user_id, &slots).await;
+        } else {
+            playback.set_effect_slots(slots)?;
+            persist_current_chain(playback, app_state).await;
+        }
+    }
+
+    #[cfg(not(feature = "effects"))]
+    {
+        eprintln!("[apply_effect_chain] Effects feature not enabled");
     }
 
     Ok(())
 }
+
+// ===== Per-Device DSP Profiles =====
+
+/// DSP chain preset bound to an output device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceDspProfile {
+    /// Backend string ("default", "asio", "jack")
+    pub backend: String,
+    pub device_name: String,
+    pub preset_id: i64,
+}
+
+impl From<soul_storage::dsp_profiles::DeviceProfileBinding> for DeviceDspProfile {
+    fn from(binding: soul_storage::dsp_profiles::DeviceProfileBinding) -> Self {
+        Self {
+            backend: binding.backend,
+            device_name: binding.device_name,
+            preset_id: binding.preset_id,
+        }
+    }
+}
+
+/// Device profile bindings plus the fallback profile for unbound devices
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceDspProfiles {
+    pub bindings: Vec<DeviceDspProfile>,
+    pub default_preset_id: Option<i64>,
+}
+
+/// Load the DSP profile bound to an output device
+///
+/// Called after every device switch (manual or following the system
+/// default) and once on startup. Falls back to the default profile for
+/// unbound devices; with neither set the current chain is left alone.
+pub async fn apply_device_dsp_profile(
+    playback: &PlaybackManager,
+    app_state: &AppState,
+    backend: AudioBackend,
+    device_name: &str,
+) -> Result<bool, String> {
+    let backend_str = backend_to_str(backend);
+
+    let preset_id = soul_storage::dsp_profiles::resolve_profile(
+        &app_state.pool,
+        &app_state.user_id,
+        backend_str,
+        device_name,
+    )
+    .await
+    .map_err(|e| format!("Failed to resolve DSP profile: {}", e))?;
+
+    let Some(preset_id) = preset_id else {
+        eprintln!(
+            "[apply_device_dsp_profile] No DSP profile for {} ({}), keeping current chain",
+            device_name, backend_str
+        );
+        return Ok(false);
+    };
+
+    let user_id: i64 = app_state
+        .user_id
+        .parse()
+        .map_err(|e| format!("Invalid user ID: {}", e))?;
+
+    let effect_chain = fetch_preset_chain(&app_state.pool, user_id, preset_id).await?;
+    apply_effect_chain(playback, app_state, effect_chain).await?;
+
+    eprintln!(
+        "[apply_device_dsp_profile] Loaded preset {} for {} ({})",
+        preset_id, device_name, backend_str
+    );
+    Ok(true)
+}
+
+/// Get the DSP profiles bound to output devices
+#[tauri::command]
+pub async fn get_device_dsp_profiles(
+    app_state: State<'_, AppState>,
+) -> Result<DeviceDspProfiles, String> {
+    let bindings =
+        soul_storage::dsp_profiles::list_device_bindings(&app_state.pool, &app_state.user_id)
+            .await
+            .map_err(|e| format!("Failed to load device profiles: {}", e))?;
+
+    let default_preset_id =
+        soul_storage::dsp_profiles::get_default_profile(&app_state.pool, &app_state.user_id)
+            .await
+            .map_err(|e| format!("Failed to load default profile: {}", e))?;
+
+    Ok(DeviceDspProfiles {
+        bindings: bindings.into_iter().map(DeviceDspProfile::from).collect(),
+        default_preset_id,
+    })
+}
+
+/// Bind a DSP chain preset to an output device (None removes the binding)
+///
+/// If the device is the current output, its profile is applied right away.
+#[tauri::command]
+pub async fn set_device_dsp_profile(
+    backend_str: String,
+    device_name: String,
+    preset_id: Option<i64>,
+    playback: State<'_, PlaybackManager>,
+    app_state: State<'_, AppState>,
+) -> Result<(), String> {
+    let backend = parse_backend(&backend_str)?;
+
+    match preset_id {
+        Some(preset_id) => soul_storage::dsp_profiles::bind_device(
+            &app_state.pool,
+            &app_state.user_id,
+            &backend_str,
+            &device_name,
+            preset_id,
+        )
+        .await
+        .map_err(|e| format!("Failed to bind device profile: {}", e))?,
+        None => soul_storage::dsp_profiles::unbind_device(
+            &app_state.pool,
+            &app_state.user_id,
+            &backend_str,
+            &device_name,
+        )
+        .await
+        .map_err(|e| format!("Failed to unbind device profile: {}", e))?,
+    }
+
+    if playback.get_current_backend() == backend && playback.get_current_device() == device_name {
+        apply_device_dsp_profile(&playback, &app_state, backend, &device_name).await?;
+    }
+
+    Ok(())
+}
+
+/// Set the fallback DSP profile for devices without a binding (None clears it)
+///
+/// If the current output has no binding of its own, the profile is applied right away.
+#[tauri::command]
+pub async fn set_default_dsp_profile(
+    preset_id: Option<i64>,
+    playback: State<'_, PlaybackManager>,
+    app_state: State<'_, AppState>,
+) -> Result<(), String> {
+    soul_storage::dsp_profiles::set_default_profile(&app_state.pool, &app_state.user_id, preset_id)
+        .await
+        .map_err(|e| format!("Failed to set default profile: {}", e))?;
+
+    let backend = playback.get_current_backend();
+    let device_name = playback.get_current_device();
+    let resolved = soul_storage::dsp_profiles::resolve_profile(
+        &app_state.pool,
+        &app_state.user_id,
+        backend_to_str(backend),
+        &device_name,
+    )
+    .await
+    .map_err(|e| format!("Failed to resolve DSP profile: {}", e))?;
+
+    if preset_id.is_some() && resolved == preset_id {
+        apply_device_dsp_profile(&playback, &app_state, backend, &device_name).await?;
+    }
+
+    Ok(())
+}
+
+// ===== Per-Track / Album / Genre Overrides =====
+
+/// DSP preset and/or gain offset attached to a track, album or genre
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DspOverrideEntry {
+    /// "track", "album" or "genre"
+    pub scope: String,
+    pub target_id: i64,
+    pub preset_id: Option<i64>,
+    pub gain_db: Option<f64>,
+}
+
+impl From<DspOverride> for DspOverrideEntry {
+    fn from(entry: DspOverride) -> Self {
+        Self {
+            scope: entry.scope.as_str().to_string(),
+            target_id: entry.target_id,
+            preset_id: entry.preset_id,
+            gain_db: entry.gain_db,
+        }
+    }
+}
+
+fn parse_override_scope(scope: &str) -> Result<OverrideScope, String> {
+    OverrideScope::from_str(scope).ok_or_else(|| format!("Unknown override scope: {}", scope))
+}
+
+/// Gain offset and DSP preset resolved for a track
+///
+/// The preset with the highest precedence (track > album > genre) replaces
+/// the chain for as long as it applies; without one, the device profile /
+/// global chain comes back.
+#[derive(Debug, Clone, Default)]
+pub struct TrackDspOverride {
+    pub gain_db: f64,
+    /// Preset id and its chain laid out over the slots
+    pub preset: Option<(i64, [Option<EffectSlotState>; 4])>,
+}
+
+/// Resolve the gain offset and DSP preset of a track
+///
+/// Called when a track is loaded, so that its chain can be built before
+/// the transition to it.
+pub async fn resolve_track_dsp_override(
+    app_state: &AppState,
+    track_id: &str,
+) -> Result<TrackDspOverride, String> {
+    // Tracks outside the library (e.g. opened files) have no overrides
+    let resolved = match track_id.parse::<i64>() {
+        Ok(track_id) => soul_storage::dsp_overrides::resolve_for_track(
+            &app_state.pool,
+            &app_state.user_id,
+            track_id,
+        )
+        .await
+        .map_err(|e| format!("Failed to resolve DSP overrides: {}", e))?,
+        Err(_) => ResolvedOverride::default(),
+    };
+
+    #[cfg(feature = "effects")]
+    let preset = match resolved.preset_id {
+        Some(preset_id) => {
+            let user_id: i64 = app_state
+                .user_id
+                .parse()
+                .map_err(|e| format!("Invalid user ID: {}", e))?;
+            let slots =
+                chain_to_slots(fetch_preset_chain(&app_state.pool, user_id, preset_id).await?)?;
+            eprintln!(
+                "[resolve_track_dsp_override] Track {}: preset {} ({:?} override)",
+                track_id, preset_id, resolved.preset_scope
+            );
+            Some((preset_id, slots))
+        }
+        None => None,
+    };
+
+    #[cfg(not(feature = "effects"))]
+    let preset = None;
+
+    Ok(TrackDspOverride {
+        gain_db: resolved.gain_db.unwrap_or(0.0),
+        preset,
+    })
+}
+
+/// Re-apply overrides for the current and the prepared next track after they were edited
+async fn reapply_current_track_overrides(
+    playback: &PlaybackManager,
+    app_state: &AppState,
+) -> Result<(), String> {
+    if let Some(track) = playback.get_current_track() {
+        let resolved = resolve_track_dsp_override(app_state, &track.id).await?;
+        playback.apply_track_dsp_override(&resolved)?;
+    }
+    if let Some(track_id) = playback.next_track_dsp_id() {
+        let resolved = resolve_track_dsp_override(app_state, &track_id).await?;
+        playback.update_next_track_dsp_override(&track_id, resolved);
+    }
+    Ok(())
+}
+
+/// Get all DSP/gain overrides
+#[tauri::command]
+pub async fn get_dsp_overrides(
+    app_state: State<'_, AppState>,
+) -> Result<Vec<DspOverrideEntry>, String> {
+    let overrides =
+        soul_storage::dsp_overrides::list_overrides(&app_state.pool, &app_state.user_id)
+            .await
+            .map_err(|e| format!("Failed to load DSP overrides: {}", e))?;
+
+    Ok(overrides.into_iter().map(DspOverrideEntry::from).collect())
+}
+
+/// Attach a DSP preset and/or gain offset to a track, album or genre
+///
+/// With neither a preset nor a gain offset the override is removed. The
+/// current track picks up the change right away.
+#[tauri::command]
+pub async fn set_dsp_override(
+    scope: String,
+    target_id: i64,
+    preset_id: Option<i64>,
+    gain_db: Option<f64>,
+    playback: State<'_, PlaybackManager>,
+    app_state: State<'_, AppState>,
+) -> Result<(), String> {
+    let scope = parse_override_scope(&scope)?;
+
+    if preset_id.is_none() && gain_db.is_none() {
+        soul_storage::dsp_overrides::delete_override(
+            &app_state.pool,
+            &app_state.user_id,
+            scope,
+            target_id,
+        )
+        .await
+        .map_err(|e| format!("Failed to delete DSP override: {}", e))?;
+    } else {
+        soul_storage::dsp_overrides::set_override(
+            &app_state.pool,
+            &app_state.user_id,
+            &DspOverride {
+                scope,
+                target_id,
+                preset_id,
+                gain_db,
+            },
+        )
+        .await
+        .map_err(|e| format!("Failed to save DSP override: {}", e))?;
+    }
+
+    reapply_current_track_overrides(&playback, &app_state).await
+}
+
+/// Remove the override of a track, album or genre
+#[tauri::command]
+pub async fn delete_dsp_override(
+    scope: String,
+    target_id: i64,
+    playback: State<'_, PlaybackManager>,
+    app_state: State<'_, AppState>,
+) -> Result<(), String> {
+    let scope = parse_override_scope(&scope)?;
+
+    soul_storage::dsp_overrides::delete_override(
+        &app_state.pool,
+        &app_state.user_id,
+        scope,
+        target_id,
+    )
+    .await
+    .map_err(|e| format!("Failed to delete DSP override: {}", e))?;
+
+    reapply_current_track_overrides(&playback, &app_state).await
+}
+
+// ===== EQ Preset Import/Export =====
+
+/// Import an Equalizer APO / AutoEQ / REW filter file as a DSP chain preset
+///
+/// The filters become a single parametric EQ effect; the file's preamp is
+/// stored alongside and reserved as headroom when the preset is loaded.
+/// Returns the id of the saved preset.
+#[tauri::command]
+pub async fn import_eq_preset(
+    file_path: String,
+    name: Option<String>,
+    app_state: State<'_, crate::app_state::AppState>,
+) -> Result<i64, String> {
+    let path = std::path::Path::new(&file_path);
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read EQ preset file: {}", e))?;
+
+    let format = EqPreset::detect_format(&text);
+    let preset = EqPreset::parse(&text).map_err(|e| format!("Failed to parse EQ preset: {}", e))?;
+
+    let name = name.unwrap_or_else(|| {
+        path.file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "Imported EQ".to_string())
+    });
+    let description = format!(
+        "Imported from {} ({} bands, preamp {:.1} dB)",
+        match format {
+            soul_audio::effects::EqPresetFormat::EqualizerApo => "Equalizer APO",
+            soul_audio::effects::EqPresetFormat::Rew => "REW",
+        },
+        preset.bands.len(),
+        preset.preamp_db
+    );
+
+    let effect = EffectType::Eq {
+        bands: preset.bands.into_iter().map(EqBandData::from).collect(),
+        preamp_db: preset.preamp_db,
+        linear_phase: false,
+    };
+
+    eprintln!("[import_eq_preset] Importing '{}' from {}", name, file_path);
+    save_dsp_chain_preset(name, Some(description), vec![effect], app_state).await
+}
+
+/// Export the parametric EQ in a chain slot to an Equalizer APO `ParametricEQ.txt`
+#[tauri::command]
+pub async fn export_eq_preset(
+    #[allow(unused_variables)] playback: State<'_, PlaybackManager>,
+    slot_index: usize,
+    #[allow(unused_variables)] file_path: String,
+) -> Result<(), String> {
+    if slot_index >= 4 {
+        return Err("Slot index must be 0-3".to_string());
+    }
+
+    #[cfg(feature = "effects")]
+    {
+        let slots = playback.get_effect_slots()?;
+        let Some(EffectSlotState {
+            effect: EffectType::Eq {
+                bands, preamp_db, ..
+            },
+            ..
+        }) = &slots[slot_index]
+        else {
+            return Err(format!("No parametric EQ at slot {}", slot_index));
+        };
+
+        let preset = EqPreset {
+            preamp_db: *preamp_db,
+            bands: bands.iter().cloned().map(EqBand::from).collect(),
+        };
+
+        std::fs::write(&file_path, preset.to_apo_string())
+            .map_err(|e| format!("Failed to write EQ preset file: {}", e))?;
+        eprintln!(
+            "[export_eq_preset] Slot {} exported to {}",
+            slot_index, file_path
+        );
+        Ok(())
+    }
+
+    #[cfg(not(feature = "effects"))]
+    {
+        Err("Effects feature not enabled".to_string())
+    }
+}
@@ -0,0 +1,376 @@
+//! Offline export of tracks through the DSP chain
+//!
+//! Renders tracks to WAV or FLAC files with a saved DSP chain preset (or
+//! the chain currently playing), optional loudness normalization,
+//! resampling and dither. Runs in the background, much faster than real
+//! time, and reports progress through events:
+//!
+//! - `export:progress` while a track renders
+//! - `export:track-complete` after each track (with the output path or error)
+//! - `export:finished` when the batch is done or cancelled
+
+use crate::app_state::AppState;
+use crate::dsp_commands::{self, EffectSlotState};
+use crate::playback::PlaybackManager;
+use serde::{Deserialize, Serialize};
+use soul_audio::dither::DitherMode;
+use soul_audio::effects::EffectChain;
+use soul_audio::render::{self, OutputBitDepth, OutputFormat, RenderSettings};
+use soul_core::types::TrackId;
+use soul_loudness::NormalizationMode;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, State};
+
+/// Export worker state
+pub struct ExportWorker {
+    /// Whether an export batch is running
+    running: AtomicBool,
+    /// Cancellation flag, checked between tracks
+    cancel: AtomicBool,
+}
+
+impl ExportWorker {
+    pub fn new() -> Self {
+        Self {
+            running: AtomicBool::new(false),
+            cancel: AtomicBool::new(false),
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    pub fn request_cancel(&self) {
+        self.cancel.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::SeqCst)
+    }
+}
+
+impl Default for ExportWorker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Export options from the frontend
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportOptions {
+    /// "wav" or "flac"
+    pub format: String,
+    /// 16, 24 or 32 (32 = float, WAV only)
+    pub bit_depth: u16,
+    /// Output sample rate (None = keep each track's rate)
+    pub sample_rate: Option<u32>,
+    /// Normalization mode ("disabled", "replaygain_track", "ebu_r128", ...)
+    pub normalization: Option<String>,
+    /// Dither mode ("none", "tpdf", "lipshitz", ...)
+    pub dither: Option<String>,
+    /// DSP chain preset (None = the chain currently playing)
+    pub preset_id: Option<i64>,
+}
+
+impl ExportOptions {
+    fn to_settings(&self) -> Result<RenderSettings, String> {
+        let format: OutputFormat = self.format.parse()?;
+        let bit_depth = OutputBitDepth::from_bits(self.bit_depth)
+            .ok_or_else(|| format!("Unsupported bit depth: {}", self.bit_depth))?;
+        let normalization = match &self.normalization {
+            Some(mode) => NormalizationMode::from_str(mode)
+                .ok_or_else(|| format!("Invalid normalization mode: {}", mode))?,
+            None => NormalizationMode::Disabled,
+        };
+        let dither = match &self.dither {
+            Some(mode) => mode.parse::<DitherMode>()?,
+            None => DitherMode::Tpdf,
+        };
+
+        let settings = RenderSettings {
+            format,
+            bit_depth,
+            sample_rate: self.sample_rate,
+            normalization,
+            dither,
+            ..Default::default()
+        };
+        settings.validate().map_err(|e| e.to_string())?;
+        Ok(settings)
+    }
+}
+
+/// Progress of the track being rendered
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportProgress {
+    pub track_id: i64,
+    /// Position of the track in the batch (0-based)
+    pub index: usize,
+    pub total: usize,
+    /// Progress of this track (0.0 - 1.0)
+    pub progress: f32,
+}
+
+/// Outcome of one exported track
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportTrackResult {
+    pub track_id: i64,
+    pub output_path: Option<String>,
+    pub error: Option<String>,
+    /// Render speed as a multiple of real time
+    pub speed: Option<f64>,
+}
+
+/// Outcome of the whole batch
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportSummary {
+    pub completed: usize,
+    pub failed: usize,
+    pub cancelled: bool,
+}
+
+/// Export tracks to an output directory in the background
+#[tauri::command]
+pub async fn export_tracks(
+    track_ids: Vec<i64>,
+    output_dir: String,
+    options: ExportOptions,
+    app: AppHandle,
+    state: State<'_, AppState>,
+    #[allow(unused_variables)] playback: State<'_, PlaybackManager>,
+    worker: State<'_, Arc<ExportWorker>>,
+) -> Result<(), String> {
+    if worker.is_running() {
+        return Err("An export is already in progress".to_string());
+    }
+    if track_ids.is_empty() {
+        return Err("No tracks to export".to_string());
+    }
+
+    let mut settings = options.to_settings()?;
+
+    let slots = match options.preset_id {
+        Some(preset_id) => {
+            let user_id: i64 = state
+                .user_id
+                .parse()
+                .map_err(|e| format!("Invalid user ID: {}", e))?;
+            let chain = dsp_commands::fetch_preset_chain(&state.pool, user_id, preset_id).await?;
+            dsp_commands::chain_to_slots(chain)?
+        }
+        #[cfg(feature = "effects")]
+        None => playback.get_effect_slots()?,
+        #[cfg(not(feature = "effects"))]
+        None => Default::default(),
+    };
+    // Same headroom playback reserves for EQ boosts
+    settings.preamp_db = -(dsp_commands::eq_boost_db(&slots) as f64);
+
+    let output_dir = PathBuf::from(output_dir);
+    std::fs::create_dir_all(&output_dir)
+        .map_err(|e| format!("Failed to create output directory: {}", e))?;
+
+    worker.cancel.store(false, Ordering::SeqCst);
+    worker.running.store(true, Ordering::SeqCst);
+
+    let pool = (*state.pool).clone();
+    let worker_clone = Arc::clone(&worker);
+
+    tokio::spawn(async move {
+        let summary = run_export(
+            &app,
+            &pool,
+            &worker_clone,
+            &track_ids,
+            &output_dir,
+            &slots,
+            settings,
+        )
+        .await;
+
+        worker_clone.running.store(false, Ordering::SeqCst);
+        eprintln!(
+            "[export_tracks] Finished: {} exported, {} failed, cancelled: {}",
+            summary.completed, summary.failed, summary.cancelled
+        );
+        let _ = app.emit("export:finished", summary);
+    });
+
+    Ok(())
+}
+
+/// Stop the running export after the current track
+#[tauri::command]
+pub async fn cancel_export(worker: State<'_, Arc<ExportWorker>>) -> Result<(), String> {
+    if worker.is_running() {
+        worker.request_cancel();
+    }
+    Ok(())
+}
+
+/// Whether an export is running
+#[tauri::command]
+pub async fn is_export_running(worker: State<'_, Arc<ExportWorker>>) -> Result<bool, String> {
+    Ok(worker.is_running())
+}
+
+async fn run_export(
+    app: &AppHandle,
+    pool: &sqlx::SqlitePool,
+    worker: &ExportWorker,
+    track_ids: &[i64],
+    output_dir: &Path,
+    slots: &[Option<EffectSlotState>; 4],
+    settings: RenderSettings,
+) -> ExportSummary {
+    let mut summary = ExportSummary {
+        completed: 0,
+        failed: 0,
+        cancelled: false,
+    };
+
+    for (index, &track_id) in track_ids.iter().enumerate() {
+        if worker.is_cancelled() {
+            summary.cancelled = true;
+            break;
+        }
+
+        let result = export_track(
+            app,
+            pool,
+            track_id,
+            index,
+            track_ids.len(),
+            output_dir,
+            slots,
+            settings,
+        )
+        .await;
+
+        let event = match result {
+            Ok((path, speed)) => {
+                summary.completed += 1;
+                ExportTrackResult {
+                    track_id,
+                    output_path: Some(path.to_string_lossy().into_owned()),
+                    error: None,
+                    speed: Some(speed),
+                }
+            }
+            Err(e) => {
+                eprintln!("[run_export] Track {} failed: {}", track_id, e);
+                summary.failed += 1;
+                ExportTrackResult {
+                    track_id,
+                    output_path: None,
+                    error: Some(e),
+                    speed: None,
+                }
+            }
+        };
+        let _ = app.emit("export:track-complete", event);
+    }
+
+    summary
+}
+
+/// Render one track, returns the output path and render speed
+#[allow(clippy::too_many_arguments)]
+async fn export_track(
+    app: &AppHandle,
+    pool: &sqlx::SqlitePool,
+    track_id: i64,
+    index: usize,
+    total: usize,
+    output_dir: &Path,
+    slots: &[Option<EffectSlotState>; 4],
+    settings: RenderSettings,
+) -> Result<(PathBuf, f64), String> {
+    let track = soul_storage::tracks::get_by_id(pool, TrackId::new(track_id.to_string()))
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Track {} not found", track_id))?;
+
+    let input = track
+        .availability
+        .iter()
+        .find_map(|avail| {
+            if matches!(
+                avail.status,
+                soul_core::types::AvailabilityStatus::LocalFile
+                    | soul_core::types::AvailabilityStatus::Cached
+            ) {
+                avail.local_file_path.clone()
+            } else {
+                None
+            }
+        })
+        .map(PathBuf::from)
+        .ok_or_else(|| format!("No local file found for track {}", track_id))?;
+
+    let output = unique_output_path(output_dir, &input, settings.format);
+    let slots = slots.clone();
+    let app = app.clone();
+
+    tokio::task::spawn_blocking(move || {
+        // A fresh chain per track; convolution IRs are picked for the output rate
+        let mut chain = EffectChain::new();
+        let sample_rate = settings.sample_rate.unwrap_or(0);
+        for slot in slots.iter().flatten() {
+            chain.add_effect(slot.build_effect(sample_rate));
+        }
+
+        // Only emit when the whole percentage changes
+        let mut last_percent = None;
+        let report =
+            render::render_file_with_progress(&input, &output, &mut chain, &settings, |p| {
+                let percent = (p * 100.0) as u32;
+                if last_percent != Some(percent) {
+                    last_percent = Some(percent);
+                    let _ = app.emit(
+                        "export:progress",
+                        ExportProgress {
+                            track_id,
+                            index,
+                            total,
+                            progress: p,
+                        },
+                    );
+                }
+            })
+            .map_err(|e| e.to_string())?;
+
+        eprintln!(
+            "[export_track] {} -> {} ({:.0}x real time)",
+            input.display(),
+            output.display(),
+            report.speed()
+        );
+        Ok((output, report.speed()))
+    })
+    .await
+    .map_err(|e| format!("Export task failed: {}", e))?
+}
+
+/// `<dir>/<source stem>.<ext>`, numbered if that file exists
+fn unique_output_path(dir: &Path, input: &Path, format: OutputFormat) -> PathBuf {
+    let stem = input
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "track".to_string());
+
+    let mut path = dir.join(format!("{}.{}", stem, format.as_str()));
+    let mut n = 2;
+    while path.exists() {
+        path = dir.join(format!("{} ({}).{}", stem, n, format.as_str()));
+        n += 1;
+    }
+    path
+}
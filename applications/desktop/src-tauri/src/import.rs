@@ -422,7 +422,7 @@ impl ImportManager {
         );
 
         // Scan directory first
-        let scanner = soul_importer::scanner::FileScanner::new();
+        let scanner = soul_importer::scanner::FileScanner::new().include_cue_sheets(true);
         let files = scanner.scan_directory(&directory).map_err(|e| {
             eprintln!("[ImportManager::import_directory] Scan error: {}", e);
             e.to_string()
@@ -391,6 +391,8 @@ pub fn preview_path_template(template: String) -> String {
         musicbrainz_recording_id: None,
         composer: Some("Roger Waters".to_string()),
         album_art: None,
+        bpm: None,
+        musical_key: None,
     };
 
     let source_path = std::path::Path::new("/example/source.flac");
@@ -1,13 +1,16 @@
 //! Loudness analysis Tauri commands
 //!
 //! Provides commands for analyzing audio tracks for loudness normalization
-//! (ReplayGain 2.0 and EBU R128), managing analysis queue, and retrieving
-//! loudness metadata.
+//! (ReplayGain 2.0, EBU R128 and TT dynamic range), managing analysis queue,
+//! and retrieving loudness metadata.
 
 use crate::app_state::AppState;
 use crate::playback::PlaybackManager;
 use serde::{Deserialize, Serialize};
-use soul_loudness::{LoudnessAnalyzer, LoudnessInfo, NormalizationMode, ReplayGainCalculator};
+use soul_loudness::{
+    album_dynamic_range, CueDetector, CuePoints, LoudnessAnalyzer, LoudnessInfo, NormalizationMode,
+    ReplayGainCalculator,
+};
 use std::path::Path;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
@@ -39,6 +42,12 @@ pub struct FrontendLoudnessInfo {
     pub lufs_range: Option<f64>,
     /// True peak in dBFS
     pub true_peak_dbfs: Option<f64>,
+    /// TT dynamic range in dB
+    pub dr_track: Option<f64>,
+    /// Album dynamic range in dB
+    pub dr_album: Option<f64>,
+    /// Peak-to-loudness ratio in dB
+    pub plr: Option<f64>,
     /// Whether the track has been analyzed
     pub is_analyzed: bool,
 }
@@ -54,11 +63,36 @@ impl From<soul_storage::loudness::TrackLoudness> for FrontendLoudnessInfo {
             lufs_integrated: l.lufs_integrated,
             lufs_range: l.lufs_range,
             true_peak_dbfs: l.true_peak_dbfs,
+            dr_track: l.dr_track,
+            dr_album: l.dr_album,
+            plr: l.plr,
             is_analyzed: l.is_analyzed(),
         }
     }
 }
 
+/// Dynamic range of a track for frontend consumption
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrontendDynamicRange {
+    pub track_id: i64,
+    /// TT dynamic range in dB (shown rounded, e.g. "DR6")
+    pub dr_track: f64,
+    pub dr_album: Option<f64>,
+    pub plr: Option<f64>,
+}
+
+impl From<soul_storage::loudness::TrackDynamicRange> for FrontendDynamicRange {
+    fn from(d: soul_storage::loudness::TrackDynamicRange) -> Self {
+        Self {
+            track_id: d.track_id,
+            dr_track: d.dr_track,
+            dr_album: d.dr_album,
+            plr: d.plr,
+        }
+    }
+}
+
 /// Analysis queue statistics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -109,7 +143,10 @@ impl AnalysisWorker {
 }
 
 /// Loudness analysis version string
-const ANALYSIS_VERSION: &str = "1.0.0-ebur128";
+const ANALYSIS_VERSION: &str = "1.2.0-ebur128-dr-cue";
+
+/// Default DR threshold for finding brickwalled masters
+const DEFAULT_MAX_DYNAMIC_RANGE: f64 = 7.0;
 
 /// Get loudness information for a track
 #[tauri::command]
@@ -156,7 +193,7 @@ pub async fn analyze_track(
         .ok_or_else(|| format!("No local file found for track {}", track_id))?;
 
     // Analyze the file
-    let loudness_info = analyze_audio_file(&file_path).await?;
+    let (loudness_info, cue_points) = analyze_audio_file(&file_path).await?;
 
     // Calculate ReplayGain
     let rg_calculator = ReplayGainCalculator::new();
@@ -179,6 +216,9 @@ pub async fn analyze_track(
         lufs_integrated: Some(loudness_info.integrated_lufs),
         lufs_range: Some(loudness_info.loudness_range_lu),
         true_peak_dbfs: Some(loudness_info.true_peak_dbfs),
+        dr_track: loudness_info.dynamic_range_db,
+        dr_album: None,
+        plr: Some(loudness_info.peak_to_loudness_ratio()),
         analyzed_at: None,
         version: None,
     };
@@ -192,6 +232,11 @@ pub async fn analyze_track(
     )
     .await
     .map_err(|e| e.to_string())?;
+    store_cue_points(&state.pool, track_id, cue_points).await?;
+
+    if let Some(album_id) = track.album_id {
+        refresh_album_dynamic_range(&state.pool, album_id).await?;
+    }
 
     // Emit event for UI update
     let _ = app.emit("loudness-analysis-complete", track_id);
@@ -199,6 +244,26 @@ pub async fn analyze_track(
     Ok(FrontendLoudnessInfo::from(track_loudness))
 }
 
+/// Get analyzed tracks at or below a dynamic range, least dynamic first
+///
+/// Defaults to DR7, below which masters are usually brickwalled.
+#[tauri::command]
+pub async fn get_low_dynamic_range_tracks(
+    max_dr: Option<f64>,
+    limit: Option<i32>,
+    state: State<'_, AppState>,
+) -> Result<Vec<FrontendDynamicRange>, String> {
+    let tracks = soul_storage::loudness::get_tracks_by_dynamic_range(
+        &state.pool,
+        max_dr.unwrap_or(DEFAULT_MAX_DYNAMIC_RANGE),
+        limit.unwrap_or(500),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(tracks.into_iter().map(FrontendDynamicRange::from).collect())
+}
+
 /// Queue a track for background analysis
 #[tauri::command]
 pub async fn queue_track_analysis(
@@ -383,8 +448,8 @@ pub async fn clear_completed_analysis(state: State<'_, AppState>) -> Result<i64,
 
 // Helper functions
 
-/// Analyze an audio file and return loudness information
-async fn analyze_audio_file(file_path: &str) -> Result<LoudnessInfo, String> {
+/// Analyze an audio file and return loudness information and cue points
+async fn analyze_audio_file(file_path: &str) -> Result<(LoudnessInfo, Option<CuePoints>), String> {
     let path = Path::new(file_path);
 
     if !path.exists() {
@@ -439,13 +504,14 @@ async fn analyze_audio_file(file_path: &str) -> Result<LoudnessInfo, String> {
 
         // Create decoder
         let dec_opts = DecoderOptions::default();
-        let mut decoder = symphonia::default::get_codecs()
+        let mut decoder = soul_audio::codecs::registry()
             .make(&track.codec_params, &dec_opts)
             .map_err(|e| format!("Failed to create decoder: {}", e))?;
 
         // Create loudness analyzer
         let mut analyzer = LoudnessAnalyzer::new(sample_rate, channels)
             .map_err(|e| format!("Failed to create analyzer: {}", e))?;
+        let mut cue_detector = CueDetector::new(sample_rate, channels);
 
         // Decode and analyze
         let mut sample_buf: Option<SampleBuffer<f32>> = None;
@@ -490,17 +556,60 @@ async fn analyze_audio_file(file_path: &str) -> Result<LoudnessInfo, String> {
             if let Err(e) = analyzer.add_frames(buf.samples()) {
                 eprintln!("[analyze_audio_file] Analysis error: {}", e);
             }
+            cue_detector.add_frames(buf.samples());
         }
 
         // Finalize analysis
-        analyzer
+        let loudness_info = analyzer
             .finalize()
-            .map_err(|e| format!("Analysis failed: {}", e))
+            .map_err(|e| format!("Analysis failed: {}", e))?;
+        Ok((loudness_info, cue_detector.finalize()))
     })
     .await
     .map_err(|e| format!("Task error: {}", e))?
 }
 
+/// Store the cue points of a track
+///
+/// CUE sheet virtual tracks are analysed as their whole file, so the cue
+/// points don't apply to them and are cleared instead.
+async fn store_cue_points(
+    pool: &sqlx::SqlitePool,
+    track_id: i64,
+    cue_points: Option<CuePoints>,
+) -> Result<(), String> {
+    let is_virtual = soul_storage::cue_tracks::get_range(pool, track_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .is_some();
+
+    let cue_points = match cue_points {
+        Some(c) if !is_virtual => Some(soul_storage::loudness::TrackCuePoints {
+            cue_in: c.cue_in_secs,
+            cue_out: c.cue_out_secs,
+            fade_out: c.fade_out_secs,
+        }),
+        _ => None,
+    };
+    soul_storage::loudness::update_track_cue_points(pool, track_id, cue_points.as_ref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Recompute an album's DR from its analyzed tracks
+async fn refresh_album_dynamic_range(pool: &sqlx::SqlitePool, album_id: i64) -> Result<(), String> {
+    let track_drs = soul_storage::loudness::get_album_track_dynamic_ranges(pool, album_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if let Some(dr_album) = album_dynamic_range(&track_drs) {
+        soul_storage::loudness::update_album_dynamic_range(pool, album_id, dr_album)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
 /// Background analysis worker loop
 async fn run_analysis_worker(
     pool: sqlx::SqlitePool,
@@ -583,7 +692,7 @@ async fn run_analysis_worker(
 
         // Analyze
         match analyze_audio_file(&file_path).await {
-            Ok(loudness_info) => {
+            Ok((loudness_info, cue_points)) => {
                 // Calculate ReplayGain
                 let rg_calculator = ReplayGainCalculator::new();
                 let track_gain = rg_calculator.track_gain(&loudness_info);
@@ -602,6 +711,9 @@ async fn run_analysis_worker(
                     lufs_integrated: Some(loudness_info.integrated_lufs),
                     lufs_range: Some(loudness_info.loudness_range_lu),
                     true_peak_dbfs: Some(loudness_info.true_peak_dbfs),
+                    dr_track: loudness_info.dynamic_range_db,
+                    dr_album: None,
+                    plr: Some(loudness_info.peak_to_loudness_ratio()),
                     analyzed_at: None,
                     version: None,
                 };
@@ -621,6 +733,15 @@ async fn run_analysis_worker(
                             .await;
                     continue;
                 }
+                if let Err(e) = store_cue_points(&pool, item.track_id, cue_points).await {
+                    eprintln!("[analysis_worker] Failed to store cue points: {}", e);
+                }
+
+                if let Some(album_id) = track.album_id {
+                    if let Err(e) = refresh_album_dynamic_range(&pool, album_id).await {
+                        eprintln!("[analysis_worker] Failed to update album DR: {}", e);
+                    }
+                }
 
                 // Mark completed
                 let _ = soul_storage::loudness::mark_queue_completed(&pool, item.id).await;
@@ -639,6 +760,7 @@ async fn run_analysis_worker(
                         "trackTitle": track.title,
                         "lufsIntegrated": loudness_info.integrated_lufs,
                         "replaygainGain": track_gain.gain_db,
+                        "drTrack": loudness_info.dynamic_range_db,
                     }),
                 );
 
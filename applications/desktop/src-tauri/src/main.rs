@@ -6,16 +6,19 @@ mod artwork;
 mod audio_settings;
 mod deep_link;
 mod dsp_commands;
+mod export;
 mod fingerprint;
 mod import;
 mod library_settings;
 mod loudness;
+mod measurement;
 mod playback;
 mod playback_context;
 mod shortcuts;
 mod sources;
 mod splash;
 mod sync;
+mod tempo_key;
 // mod tray; // Temporarily disabled - Tauri 2.0 API change
 mod updater;
 mod window_state_manager;
@@ -181,6 +184,46 @@ impl TrackData {
                 .unwrap_or(Duration::from_secs(0)),
             track_number: self.track_number,
             source: soul_playback::TrackSource::Single,
+            ..Default::default()
+        }
+    }
+}
+
+/// Look up the file range of a CUE sheet virtual track (None = whole file)
+async fn cue_track_range(
+    pool: &sqlx::SqlitePool,
+    track_id: &str,
+) -> Option<soul_playback::TrackRange> {
+    let id: i64 = track_id.parse().ok()?;
+    match soul_storage::cue_tracks::get_range(pool, id).await {
+        Ok(range) => range.map(|r| soul_playback::TrackRange::new(r.start(), r.end())),
+        Err(e) => {
+            eprintln!("[cue_track_range] Failed to load range for track {}: {}", id, e);
+            None
+        }
+    }
+}
+
+/// Look up the analysed cue points of a track (None = not analysed yet)
+async fn track_cue_points(
+    pool: &sqlx::SqlitePool,
+    track_id: &str,
+) -> Option<soul_playback::CuePoints> {
+    use std::time::Duration;
+
+    let id: i64 = track_id.parse().ok()?;
+    match soul_storage::loudness::get_track_cue_points(pool, id).await {
+        Ok(cues) => cues.map(|c| soul_playback::CuePoints {
+            cue_in: Duration::from_secs_f64(c.cue_in.max(0.0)),
+            cue_out: Duration::from_secs_f64(c.cue_out.max(0.0)),
+            fade_out: c.fade_out.map(|f| Duration::from_secs_f64(f.max(0.0))),
+        }),
+        Err(e) => {
+            eprintln!(
+                "[track_cue_points] Failed to load cue points for track {}: {}",
+                id, e
+            );
+            None
         }
     }
 }
@@ -195,9 +238,12 @@ async fn play_track(
     duration_seconds: Option<f64>,
     track_number: Option<u32>,
     playback: State<'_, PlaybackManager>,
+    state: State<'_, AppState>,
 ) -> Result<(), String> {
     use std::time::Duration;
 
+    let range = cue_track_range(&state.pool, &track_id).await;
+    let cue_points = track_cue_points(&state.pool, &track_id).await;
     let track = soul_playback::QueueTrack {
         id: track_id,
         path: PathBuf::from(file_path),
@@ -209,6 +255,9 @@ async fn play_track(
             .unwrap_or(Duration::from_secs(0)),
         track_number,
         source: soul_playback::TrackSource::Single,
+        range,
+        cue_points,
+        ..Default::default()
     };
 
     playback.play_track(track)
@@ -219,6 +268,7 @@ async fn play_queue(
     queue: Vec<TrackData>,
     start_index: usize,
     playback: State<'_, PlaybackManager>,
+    state: State<'_, AppState>,
 ) -> Result<(), String> {
     eprintln!(
         "[play_queue] Called with {} tracks, start_index: {}",
@@ -242,11 +292,15 @@ async fn play_queue(
         );
     }
 
-    // Convert to QueueTrack format
-    let tracks: Vec<soul_playback::QueueTrack> = queue
-        .iter()
-        .map(|track_data| track_data.to_queue_track())
-        .collect();
+    // Convert to QueueTrack format (CUE sheet tracks play a range of their file,
+    // analysed tracks carry cue points for crossfades)
+    let mut tracks: Vec<soul_playback::QueueTrack> = Vec::with_capacity(queue.len());
+    for track_data in &queue {
+        let mut track = track_data.to_queue_track();
+        track.range = cue_track_range(&state.pool, &track.id).await;
+        track.cue_points = track_cue_points(&state.pool, &track.id).await;
+        tracks.push(track);
+    }
 
     eprintln!(
         "[play_queue] Loading {} tracks as playlist (source queue)",
@@ -1024,6 +1078,8 @@ fn handle_file_associations(app: AppHandle, files: Vec<PathBuf>) {
                             | "aif"
                             | "ape"
                             | "wv"
+                            | "dsf"
+                            | "dff"
                     )
                 })
                 .unwrap_or(false)
@@ -1386,6 +1442,45 @@ fn main() {
                     }
                 }
 
+                // Restore saved output dither mode (if any)
+                {
+                    let app_state_for_init = app_handle.state::<AppState>();
+                    if let Err(e) = audio_settings::initialize_dither_mode(
+                        &playback_manager,
+                        &app_state_for_init,
+                    )
+                    .await
+                    {
+                        eprintln!("[main] Warning: Failed to restore dither mode: {}", e);
+                    }
+                }
+
+                // Restore saved surround downmix coefficients (if any)
+                {
+                    let app_state_for_init = app_handle.state::<AppState>();
+                    if let Err(e) = audio_settings::initialize_downmix_settings(
+                        &playback_manager,
+                        &app_state_for_init,
+                    )
+                    .await
+                    {
+                        eprintln!("[main] Warning: Failed to restore downmix settings: {}", e);
+                    }
+                }
+
+                // Restore saved default playback speed/pitch (if any)
+                {
+                    let app_state_for_init = app_handle.state::<AppState>();
+                    if let Err(e) = audio_settings::initialize_playback_rate(
+                        &playback_manager,
+                        &app_state_for_init,
+                    )
+                    .await
+                    {
+                        eprintln!("[main] Warning: Failed to restore playback rate: {}", e);
+                    }
+                }
+
                 // Restore saved DSP effect chain
                 {
                     let app_state_for_init = app_handle.state::<AppState>();
@@ -1397,6 +1492,21 @@ fn main() {
                     .await;
                 }
 
+                // Load the DSP profile bound to the output device (if any)
+                {
+                    let app_state_for_init = app_handle.state::<AppState>();
+                    if let Err(e) = dsp_commands::apply_device_dsp_profile(
+                        &playback_manager,
+                        &app_state_for_init,
+                        playback_manager.get_current_backend(),
+                        &playback_manager.get_current_device(),
+                    )
+                    .await
+                    {
+                        eprintln!("[main] Warning: Failed to apply device DSP profile: {}", e);
+                    }
+                }
+
                 app_handle.manage(playback_manager);
 
                 emit_init_progress(&app_handle, "Initializing loudness analyzer...", 55).await;
@@ -1448,6 +1558,12 @@ fn main() {
                 let fingerprint_worker = std::sync::Arc::new(fingerprint::FingerprintWorker::new());
                 app_handle.manage(fingerprint_worker);
 
+                // Initialize tempo/key worker
+                app_handle.manage(std::sync::Arc::new(tempo_key::TempoKeyWorker::new()));
+
+                // Initialize export worker
+                app_handle.manage(std::sync::Arc::new(export::ExportWorker::new()));
+
                 emit_init_progress(&app_handle, "Setting up system tray...", 70).await;
 
                 // Setup system tray (temporarily disabled - Tauri 2.0 API change)
@@ -1530,6 +1646,7 @@ fn main() {
             audio_settings::get_audio_backends,
             audio_settings::get_audio_devices,
             audio_settings::get_audio_devices_with_capabilities,
+            audio_settings::get_audio_input_devices,
             audio_settings::get_current_audio_device,
             audio_settings::get_device_capabilities,
             audio_settings::set_audio_device,
@@ -1560,6 +1677,22 @@ fn main() {
             audio_settings::get_resampling_backend,
             audio_settings::set_resampling_settings,
             audio_settings::get_resampling_settings,
+            // Output dither
+            audio_settings::set_dither_mode,
+            audio_settings::get_dither_mode,
+            // Channel layout / downmix
+            audio_settings::get_output_channels,
+            audio_settings::set_downmix_settings,
+            audio_settings::get_downmix_settings,
+            // Playback speed / pitch
+            audio_settings::set_playback_rate,
+            audio_settings::get_playback_rate,
+            audio_settings::set_current_track_playback_rate,
+            audio_settings::get_current_playback_rate,
+            // Level meters / spectrum analyzer
+            audio_settings::set_audio_analysis_enabled,
+            audio_settings::get_audio_analysis,
+            audio_settings::get_spectrum_band_frequencies,
             // Headroom management
             audio_settings::get_headroom_settings,
             audio_settings::set_headroom_mode,
@@ -1569,6 +1702,7 @@ fn main() {
             // DSP effects chain
             dsp_commands::get_available_effects,
             dsp_commands::get_dsp_chain,
+            dsp_commands::get_bypassed_effects,
             dsp_commands::add_effect_to_chain,
             dsp_commands::remove_effect_from_chain,
             dsp_commands::toggle_effect,
@@ -1576,6 +1710,8 @@ fn main() {
             dsp_commands::clear_dsp_chain,
             dsp_commands::get_eq_presets,
             dsp_commands::get_compressor_presets,
+            dsp_commands::get_multiband_compressor_presets,
+            dsp_commands::get_dynamic_eq_presets,
             dsp_commands::get_limiter_presets,
             dsp_commands::get_crossfeed_presets,
             dsp_commands::get_stereo_presets,
@@ -1584,6 +1720,14 @@ fn main() {
             dsp_commands::save_dsp_chain_preset,
             dsp_commands::delete_dsp_chain_preset,
             dsp_commands::load_dsp_chain_preset,
+            dsp_commands::get_device_dsp_profiles,
+            dsp_commands::set_device_dsp_profile,
+            dsp_commands::set_default_dsp_profile,
+            dsp_commands::get_dsp_overrides,
+            dsp_commands::set_dsp_override,
+            dsp_commands::delete_dsp_override,
+            dsp_commands::import_eq_preset,
+            dsp_commands::export_eq_preset,
             // Library management
             get_all_tracks,
             get_track_by_id,
@@ -1658,6 +1802,23 @@ fn main() {
             fingerprint::clear_failed_fingerprints,
             fingerprint::compare_fingerprints,
             fingerprint::find_duplicates,
+            // Tempo/key detection
+            tempo_key::get_tempo_key_status,
+            tempo_key::start_tempo_key_analysis,
+            tempo_key::stop_tempo_key_analysis,
+            tempo_key::queue_tempo_key_analysis,
+            tempo_key::queue_all_tempo_key_analysis,
+            tempo_key::retry_failed_tempo_key_analysis,
+            tempo_key::clear_failed_tempo_key_analysis,
+            tempo_key::get_track_tempo_key,
+            tempo_key::find_tracks_by_tempo_key,
+            tempo_key::write_tempo_key_tags,
+            // Offline export
+            export::export_tracks,
+            export::cancel_export,
+            export::is_export_running,
+            // Room/headphone measurement
+            measurement::run_measurement,
             // Settings
             get_user_settings,
             set_user_setting,
@@ -1680,6 +1841,7 @@ fn main() {
             // Loudness analysis
             loudness::get_track_loudness,
             loudness::analyze_track,
+            loudness::get_low_dynamic_range_tracks,
             loudness::queue_track_analysis,
             loudness::queue_all_unanalyzed,
             loudness::get_analysis_queue_stats,
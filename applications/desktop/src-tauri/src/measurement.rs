@@ -0,0 +1,138 @@
+//! Room and headphone measurement
+//!
+//! Plays a log sweep on an output device while recording a microphone,
+//! deconvolves the recording into an impulse response and optionally
+//! designs a correction filter. Both are saved as WAV files that load
+//! straight into the convolution slot.
+
+use crate::audio_settings::parse_backend;
+use serde::{Deserialize, Serialize};
+use soul_audio::measurement::{
+    correction_filter, CorrectionSettings, LogSweep, SweepSettings, TargetCurve,
+};
+use soul_audio_desktop::measurement::{record_sweep, MeasurementConfig};
+use std::path::PathBuf;
+
+/// Correction filter options from the frontend
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorrectionOptions {
+    pub max_boost_db: f64,
+    pub max_cut_db: f64,
+    pub low_hz: f64,
+    pub high_hz: f64,
+    /// Target slope (0 = flat)
+    pub tilt_db_per_octave: f64,
+}
+
+/// Measurement options from the frontend
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MeasurementOptions {
+    pub backend: String,
+    /// Output device (None = default)
+    pub output_device: Option<String>,
+    /// Input device (None = default)
+    pub input_device: Option<String>,
+    /// Output channels to play the sweep on (empty = all)
+    pub output_channels: Vec<usize>,
+    /// Input channels to record (1 or 2)
+    pub input_channels: Vec<usize>,
+    /// Sample rate for both devices (default 48 kHz)
+    pub sample_rate: Option<u32>,
+    /// Sweep length in seconds (default 10)
+    pub duration_secs: Option<f64>,
+    /// Sweep level in dBFS (default -6)
+    pub level_db: Option<f64>,
+    /// Impulse response length in milliseconds (default 500)
+    pub ir_length_ms: Option<u32>,
+    /// Directory the WAV files are written to
+    pub output_dir: String,
+    /// File name stem, e.g. "living_room_left"
+    pub name: String,
+    /// Also design a correction filter
+    pub correction: Option<CorrectionOptions>,
+}
+
+/// Files written by a measurement
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MeasurementResult {
+    pub ir_path: String,
+    pub correction_path: Option<String>,
+    pub sample_rate: u32,
+}
+
+/// Measure an impulse response (and optionally a correction filter)
+///
+/// Takes the length of the sweep plus a couple of seconds.
+#[tauri::command]
+pub async fn run_measurement(options: MeasurementOptions) -> Result<MeasurementResult, String> {
+    let defaults = SweepSettings::default();
+    let sweep_settings = SweepSettings {
+        sample_rate: options.sample_rate.unwrap_or(defaults.sample_rate),
+        duration_secs: options.duration_secs.unwrap_or(defaults.duration_secs),
+        level_db: options.level_db.unwrap_or(defaults.level_db),
+        ..defaults
+    };
+    let sweep = LogSweep::new(sweep_settings).map_err(|e| e.to_string())?;
+
+    let config = MeasurementConfig {
+        backend: parse_backend(&options.backend)?,
+        output_device: options.output_device.clone(),
+        input_device: options.input_device.clone(),
+        output_channels: options.output_channels.clone(),
+        input_channels: options.input_channels.clone(),
+    };
+
+    let output_dir = PathBuf::from(&options.output_dir);
+    std::fs::create_dir_all(&output_dir)
+        .map_err(|e| format!("Failed to create output directory: {}", e))?;
+
+    eprintln!(
+        "[run_measurement] Measuring '{}' at {} Hz",
+        options.name, sweep_settings.sample_rate
+    );
+
+    tokio::task::spawn_blocking(move || {
+        let recording = record_sweep(&config, &sweep).map_err(|e| e.to_string())?;
+
+        let ir_frames = (options.ir_length_ms.unwrap_or(500) as u64
+            * sweep_settings.sample_rate as u64
+            / 1000) as usize;
+        let ir = recording
+            .deconvolve(&sweep, ir_frames)
+            .map_err(|e| e.to_string())?;
+        let ir_path = output_dir.join(format!("{}.wav", options.name));
+        ir.write_wav(&ir_path).map_err(|e| e.to_string())?;
+
+        let correction_path = match &options.correction {
+            Some(correction) => {
+                let settings = CorrectionSettings {
+                    max_boost_db: correction.max_boost_db,
+                    max_cut_db: correction.max_cut_db,
+                    low_hz: correction.low_hz,
+                    high_hz: correction.high_hz,
+                    ..Default::default()
+                };
+                let target = TargetCurve::tilt(correction.tilt_db_per_octave);
+                let filter =
+                    correction_filter(&ir, &target, &settings).map_err(|e| e.to_string())?;
+
+                let path = output_dir.join(format!("{}_correction.wav", options.name));
+                filter.write_wav(&path).map_err(|e| e.to_string())?;
+                Some(path.to_string_lossy().into_owned())
+            }
+            None => None,
+        };
+
+        eprintln!("[run_measurement] Saved {}", ir_path.display());
+        Ok(MeasurementResult {
+            ir_path: ir_path.to_string_lossy().into_owned(),
+            correction_path,
+            sample_rate: sweep_settings.sample_rate,
+        })
+    })
+    .await
+    .map_err(|e| format!("Measurement task failed: {}", e))?
+}
@@ -4,14 +4,16 @@
 //! a clean interface for Tauri commands and event emission.
 
 use serde::Serialize;
+use soul_audio::analysis::AudioAnalysis;
+use soul_audio::channels::DownmixSettings;
 use soul_audio_desktop::{
-    DesktopPlayback, ExclusiveConfig, LatencyInfo, PlaybackCommand, PlaybackEvent,
+    DesktopPlayback, ExclusiveConfig, LatencyInfo, LoadRequest, PlaybackCommand, PlaybackEvent,
 };
-use soul_playback::{PlaybackConfig, QueueTrack, RepeatMode, ShuffleMode};
+use soul_playback::{PlaybackConfig, PlaybackRate, QueueTrack, RepeatMode, ShuffleMode, TrackDsp};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager};
 
 /// Track info for frontend events (with duration in seconds)
 #[derive(Debug, Clone, Serialize)]
@@ -38,6 +40,15 @@ impl From<&QueueTrack> for FrontendTrackEvent {
     }
 }
 
+/// Overrides resolved for loaded tracks that haven't started playing yet
+#[derive(Default)]
+struct PendingTrackDsp {
+    /// Track loaded directly, which starts as soon as it is loaded
+    current: Option<(String, crate::dsp_commands::TrackDspOverride)>,
+    /// Preloaded next track, which switches at the transition
+    next: Option<(String, crate::dsp_commands::TrackDspOverride)>,
+}
+
 /// Playback manager for Tauri application
 ///
 /// Wraps DesktopPlayback and handles event emission to frontend.
@@ -46,6 +57,9 @@ pub struct PlaybackManager {
     app_handle: AppHandle,
     #[cfg(feature = "effects")]
     effect_slots: Arc<Mutex<[Option<crate::dsp_commands::EffectSlotState>; 4]>>,
+    #[cfg(feature = "effects")]
+    dsp_override: Arc<Mutex<Option<crate::dsp_commands::ActiveDspOverride>>>,
+    pending_track_dsp: Arc<Mutex<PendingTrackDsp>>,
 }
 
 impl PlaybackManager {
@@ -56,6 +70,16 @@ impl PlaybackManager {
 
         // Create desktop playback system
         let playback = DesktopPlayback::new(config).map_err(|e| e.to_string())?;
+
+        // Track overrides are resolved as tracks load, so a preloaded track
+        // switches chain and gain where the transition to it starts
+        {
+            let app_handle = app_handle.clone();
+            playback.set_track_dsp_resolver(Some(Arc::new(move |request: &LoadRequest| {
+                Self::resolve_track_dsp(&app_handle, request)
+            })));
+        }
+
         let playback = Arc::new(Mutex::new(playback));
 
         // Start event emission thread
@@ -73,13 +97,16 @@ impl PlaybackManager {
             app_handle,
             #[cfg(feature = "effects")]
             effect_slots: Arc::new(Mutex::new([None, None, None, None])),
+            #[cfg(feature = "effects")]
+            dsp_override: Arc::new(Mutex::new(None)),
+            pending_track_dsp: Arc::new(Mutex::new(PendingTrackDsp::default())),
         })
     }
 
     /// Event emission loop that runs in background thread
     ///
     /// Polls for playback events and emits them to the frontend via Tauri events.
-    /// Also polls for device sample rate changes periodically.
+    /// Also polls for device sample rate and system default device changes periodically.
     fn event_emission_loop(playback: Arc<Mutex<DesktopPlayback>>, app_handle: AppHandle) {
         let mut last_position_emit = std::time::Instant::now();
         let mut last_sample_rate_check = std::time::Instant::now();
@@ -108,6 +135,9 @@ impl PlaybackManager {
                         } else {
                             eprintln!("[playback] Track changed: None");
                         }
+                        if let Some(t) = track {
+                            Self::commit_track_overrides(&app_handle, &t.id);
+                        }
                         app_handle.emit("playback:track-changed", frontend_track)
                     }
                     PlaybackEvent::PositionUpdated(position) => {
@@ -128,6 +158,23 @@ impl PlaybackManager {
                             }),
                         )
                     }
+                    PlaybackEvent::DeviceChanged {
+                        backend,
+                        device_name,
+                    } => {
+                        eprintln!(
+                            "[playback] Output device changed: {} ({:?})",
+                            device_name, backend
+                        );
+                        Self::apply_device_profile(&app_handle, *backend, device_name.clone());
+                        app_handle.emit(
+                            "playback:device-changed",
+                            serde_json::json!({
+                                "backend": crate::audio_settings::backend_to_str(*backend),
+                                "deviceName": device_name
+                            }),
+                        )
+                    }
                     PlaybackEvent::CrossfadeStarted {
                         from_track_id,
                         to_track_id,
@@ -198,6 +245,18 @@ impl PlaybackManager {
                         eprintln!("[playback] Failed to check sample rate: {}", e);
                     }
                 }
+
+                // Follow the system default device when no device was picked explicitly
+                // (emits DeviceChanged, which loads the new device's DSP profile)
+                match pb.check_default_device() {
+                    Ok(true) => {
+                        eprintln!("[playback] System default device changed, stream moved");
+                    }
+                    Ok(false) => {}
+                    Err(e) => {
+                        eprintln!("[playback] Failed to check default device: {}", e);
+                    }
+                }
                 drop(pb);
                 last_sample_rate_check = std::time::Instant::now();
             }
@@ -207,6 +266,104 @@ impl PlaybackManager {
         }
     }
 
+    /// Load the DSP profile bound to a device in the background
+    ///
+    /// Does nothing until the playback manager is registered with Tauri
+    /// (startup applies the profile itself once the saved chain is restored).
+    fn apply_device_profile(
+        app_handle: &AppHandle,
+        backend: soul_audio_desktop::AudioBackend,
+        device_name: String,
+    ) {
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            let (Some(playback), Some(app_state)) = (
+                app_handle.try_state::<PlaybackManager>(),
+                app_handle.try_state::<crate::app_state::AppState>(),
+            ) else {
+                return;
+            };
+
+            match crate::dsp_commands::apply_device_dsp_profile(
+                &playback,
+                &app_state,
+                backend,
+                &device_name,
+            )
+            .await
+            {
+                Ok(true) => {
+                    let _ = app_handle.emit("dsp:profile-applied", &device_name);
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    eprintln!("[playback] Failed to apply DSP profile: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Resolve the DSP/gain overrides of a track being loaded
+    ///
+    /// Runs on the track loader thread. The chain is built here; the
+    /// playback engine switches to it (and the gain offset) where the track
+    /// starts: right away for direct loads, at the start of the crossfade
+    /// or the gapless boundary for preloaded tracks.
+    fn resolve_track_dsp(app_handle: &AppHandle, request: &LoadRequest) -> Option<TrackDsp> {
+        let playback = app_handle.try_state::<PlaybackManager>()?;
+        let app_state = app_handle.try_state::<crate::app_state::AppState>()?;
+
+        let resolved = match tauri::async_runtime::block_on(
+            crate::dsp_commands::resolve_track_dsp_override(&app_state, &request.track.id),
+        ) {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                eprintln!("[playback] Failed to resolve DSP overrides: {}", e);
+                return None;
+            }
+        };
+
+        // A preloaded track follows the current one, which may have started
+        // before its TrackChanged was handled
+        if request.is_preload {
+            if let Some(current) = playback.get_current_track() {
+                Self::commit_track_overrides(app_handle, &current.id);
+            }
+        }
+
+        let dsp = playback.track_dsp(&resolved, request.target_sample_rate);
+
+        let mut pending = playback.pending_track_dsp.lock().ok()?;
+        let entry = Some((request.track.id.clone(), resolved));
+        if request.is_preload {
+            pending.next = entry;
+        } else {
+            pending.current = entry;
+        }
+
+        Some(dsp)
+    }
+
+    /// Record the DSP/gain overrides of a track that started playing
+    ///
+    /// The playback engine already switched its chain and gain offset; this
+    /// brings the slot and override state in line.
+    fn commit_track_overrides(app_handle: &AppHandle, track_id: &str) {
+        let Some(playback) = app_handle.try_state::<PlaybackManager>() else {
+            return;
+        };
+
+        match playback.commit_track_dsp(track_id) {
+            Ok(true) => {
+                let _ = app_handle.emit("dsp:profile-applied", track_id);
+            }
+            Ok(false) => {}
+            Err(e) => {
+                eprintln!("[playback] Failed to record DSP overrides: {}", e);
+            }
+        }
+    }
+
     /// Play a track from local file
     ///
     /// # Arguments
@@ -338,6 +495,12 @@ impl PlaybackManager {
         playback.has_previous()
     }
 
+    /// Get the current track
+    pub fn get_current_track(&self) -> Option<QueueTrack> {
+        let playback = self.playback.lock().unwrap();
+        playback.get_current_track()
+    }
+
     /// Get current playback state
     pub fn get_state(&self) -> soul_playback::PlaybackState {
         let playback = self.playback.lock().unwrap();
@@ -477,6 +640,262 @@ impl PlaybackManager {
         self.rebuild_effect_chain()
     }
 
+    /// Replace all four slots at once
+    ///
+    /// When every slot keeps its effect type (and enabled state) and the
+    /// type supports in-place updates in the `EffectRegistry`, parameters
+    /// are updated in place so filter state survives (e.g. when the override
+    /// preset of the playing track is switched). Otherwise the chain is
+    /// rebuilt once.
+    #[cfg(feature = "effects")]
+    pub fn set_effect_slots(
+        &self,
+        new_slots: [Option<crate::dsp_commands::EffectSlotState>; 4],
+    ) -> Result<(), String> {
+        let registry = soul_audio::pipeline::EffectRegistry::with_builtin_effects();
+
+        let in_place = {
+            let slots = self.effect_slots.lock().map_err(|e| e.to_string())?;
+            slots
+                .iter()
+                .zip(new_slots.iter())
+                .all(|(old, new)| match (old, new) {
+                    (None, None) => true,
+                    (Some(old), Some(new)) => {
+                        let type_id = new.effect.registry_type_id();
+                        old.enabled == new.enabled
+                            && old.effect.registry_type_id() == type_id
+                            && registry.supports_in_place_update(type_id)
+                            && same_band_layout(&old.effect, &new.effect)
+                    }
+                    _ => false,
+                })
+        };
+
+        if in_place {
+            let mut all_updated = true;
+            for (slot_index, slot) in new_slots.iter().enumerate() {
+                if let Some(slot) = slot {
+                    all_updated &=
+                        self.update_effect_parameters_in_place(slot_index, &slot.effect)?;
+                }
+            }
+            if all_updated {
+                return Ok(());
+            }
+        }
+
+        {
+            let mut slots = self.effect_slots.lock().map_err(|e| e.to_string())?;
+            *slots = new_slots;
+        }
+        self.rebuild_effect_chain()
+    }
+
+    /// Access the DSP override state (preset replacing the user's chain)
+    #[cfg(feature = "effects")]
+    pub fn with_dsp_override<F, R>(&self, f: F) -> Result<R, String>
+    where
+        F: FnOnce(&mut Option<crate::dsp_commands::ActiveDspOverride>) -> R,
+    {
+        let mut state = self.dsp_override.lock().map_err(|e| e.to_string())?;
+        Ok(f(&mut state))
+    }
+
+    /// Change the DSP override state
+    ///
+    /// Also rebuilds the chain prepared for the next track, which depends on
+    /// the override it follows.
+    #[cfg(feature = "effects")]
+    pub fn update_dsp_override<F, R>(&self, f: F) -> Result<R, String>
+    where
+        F: FnOnce(&mut Option<crate::dsp_commands::ActiveDspOverride>) -> R,
+    {
+        let result = self.with_dsp_override(f)?;
+        self.refresh_next_track_dsp();
+        Ok(result)
+    }
+
+    /// Slots a track with `resolved` overrides switches to
+    ///
+    /// None keeps the playing chain: the track has the preset that's already
+    /// playing, or neither has one.
+    #[cfg(feature = "effects")]
+    fn switch_slots(
+        &self,
+        resolved: &crate::dsp_commands::TrackDspOverride,
+    ) -> Result<Option<[Option<crate::dsp_commands::EffectSlotState>; 4]>, String> {
+        let active = self.dsp_override.lock().map_err(|e| e.to_string())?;
+        Ok(match (&resolved.preset, active.as_ref()) {
+            (Some((preset_id, _)), Some(playing)) if playing.preset_id == *preset_id => None,
+            (Some((_, slots)), _) => Some(slots.clone()),
+            (None, Some(playing)) => Some(playing.base_slots.clone()),
+            (None, None) => None,
+        })
+    }
+
+    /// Gain and effects for a track with `resolved` overrides
+    ///
+    /// A chain is only built when the track changes which preset plays.
+    #[cfg(feature = "effects")]
+    fn track_dsp(
+        &self,
+        resolved: &crate::dsp_commands::TrackDspOverride,
+        sample_rate: u32,
+    ) -> TrackDsp {
+        let slots = self.switch_slots(resolved).unwrap_or_else(|e| {
+            eprintln!("[playback] Failed to read DSP override state: {}", e);
+            None
+        });
+
+        TrackDsp {
+            gain_offset_db: resolved.gain_db,
+            effect_chain: slots.map(|slots| Self::build_effect_chain(&slots, sample_rate)),
+        }
+    }
+
+    /// Gain for a track with `resolved` overrides
+    #[cfg(not(feature = "effects"))]
+    fn track_dsp(
+        &self,
+        resolved: &crate::dsp_commands::TrackDspOverride,
+        _sample_rate: u32,
+    ) -> TrackDsp {
+        TrackDsp {
+            gain_offset_db: resolved.gain_db,
+        }
+    }
+
+    /// Bring the slot and override state in line with a track's overrides
+    ///
+    /// With `apply_chain` the effect chain is updated as well (in place where
+    /// possible); otherwise the playback engine has already switched it.
+    /// Returns whether the playing preset changed.
+    #[cfg(feature = "effects")]
+    fn switch_track_dsp(
+        &self,
+        resolved: &crate::dsp_commands::TrackDspOverride,
+        apply_chain: bool,
+    ) -> Result<bool, String> {
+        let current = self.get_effect_slots()?;
+
+        let slots = {
+            let mut active = self.dsp_override.lock().map_err(|e| e.to_string())?;
+            match (&resolved.preset, active.take()) {
+                (Some((preset_id, _)), Some(playing)) if playing.preset_id == *preset_id => {
+                    *active = Some(playing);
+                    return Ok(false);
+                }
+                (Some((preset_id, slots)), playing) => {
+                    // Keep the chain we return to once the override ends
+                    *active = Some(crate::dsp_commands::ActiveDspOverride {
+                        preset_id: *preset_id,
+                        base_slots: playing.map_or(current, |playing| playing.base_slots),
+                    });
+                    slots.clone()
+                }
+                (None, Some(playing)) => playing.base_slots,
+                (None, None) => return Ok(false),
+            }
+        };
+
+        if apply_chain {
+            self.set_effect_slots(slots)?;
+        } else {
+            let mut effect_slots = self.effect_slots.lock().map_err(|e| e.to_string())?;
+            *effect_slots = slots;
+            self.sync_eq_headroom(&effect_slots);
+        }
+        Ok(true)
+    }
+
+    #[cfg(not(feature = "effects"))]
+    fn switch_track_dsp(
+        &self,
+        _resolved: &crate::dsp_commands::TrackDspOverride,
+        _apply_chain: bool,
+    ) -> Result<bool, String> {
+        Ok(false)
+    }
+
+    /// Record the overrides of a track that started playing
+    ///
+    /// Uses the overrides resolved when the track was loaded. Returns whether
+    /// the playing preset changed.
+    pub fn commit_track_dsp(&self, track_id: &str) -> Result<bool, String> {
+        let resolved = {
+            let mut pending = self.pending_track_dsp.lock().map_err(|e| e.to_string())?;
+            let take = |entry: &mut Option<(String, crate::dsp_commands::TrackDspOverride)>| {
+                if entry.as_ref().is_some_and(|(id, _)| id == track_id) {
+                    entry.take().map(|(_, resolved)| resolved)
+                } else {
+                    None
+                }
+            };
+            let current = take(&mut pending.current);
+            let next = take(&mut pending.next);
+            current.or(next)
+        };
+
+        match resolved {
+            Some(resolved) => self.switch_track_dsp(&resolved, false),
+            None => Ok(false),
+        }
+    }
+
+    /// Apply a track's overrides to the playing track right away
+    ///
+    /// For overrides edited while the track plays. Returns whether the
+    /// playing preset changed.
+    pub fn apply_track_dsp_override(
+        &self,
+        resolved: &crate::dsp_commands::TrackDspOverride,
+    ) -> Result<bool, String> {
+        self.set_gain_offset_db(resolved.gain_db);
+        let changed = self.switch_track_dsp(resolved, true)?;
+        if changed {
+            self.refresh_next_track_dsp();
+        }
+        Ok(changed)
+    }
+
+    /// ID of the preloaded next track whose overrides are pending
+    pub fn next_track_dsp_id(&self) -> Option<String> {
+        let pending = self.pending_track_dsp.lock().ok()?;
+        pending.next.as_ref().map(|(track_id, _)| track_id.clone())
+    }
+
+    /// Replace the overrides resolved for the preloaded next track
+    pub fn update_next_track_dsp_override(
+        &self,
+        track_id: &str,
+        resolved: crate::dsp_commands::TrackDspOverride,
+    ) {
+        if let Ok(mut pending) = self.pending_track_dsp.lock() {
+            match &mut pending.next {
+                Some((id, entry)) if id == track_id => *entry = resolved,
+                _ => return,
+            }
+        }
+        self.refresh_next_track_dsp();
+    }
+
+    /// Rebuild the gain and effects prepared for the next track
+    fn refresh_next_track_dsp(&self) {
+        let next = self
+            .pending_track_dsp
+            .lock()
+            .ok()
+            .and_then(|pending| pending.next.clone());
+
+        if let Some((track_id, resolved)) = next {
+            let dsp = self.track_dsp(&resolved, self.get_current_sample_rate());
+            if let Ok(playback) = self.playback.lock() {
+                playback.set_next_track_dsp(&track_id, dsp);
+            }
+        }
+    }
+
     /// Update effect parameters in-place WITHOUT rebuilding the chain
     ///
     /// This preserves filter states and prevents audio artifacts (sizzle/pops)
@@ -487,10 +906,10 @@ impl PlaybackManager {
         slot_index: usize,
         effect: &crate::dsp_commands::EffectType,
     ) -> Result<bool, String> {
-        use crate::dsp_commands::EffectType;
+        use crate::dsp_commands::{eq_phase_mode, EffectType};
         use soul_audio::effects::{
-            Compressor, Crossfeed, CrossfeedPreset, GraphicEq, Limiter, ParametricEq,
-            StereoEnhancer,
+            Compressor, Crossfeed, CrossfeedPreset, DynamicEq, GraphicEq, Limiter,
+            MultibandCompressor, ParametricEq, StereoEnhancer,
         };
 
         if slot_index >= 4 {
@@ -500,9 +919,14 @@ impl PlaybackManager {
         // Try to update in-place
         let updated = self.with_effect_chain(|chain| {
             match effect {
-                EffectType::Eq { bands } => {
+                EffectType::Eq {
+                    bands,
+                    linear_phase,
+                    ..
+                } => {
                     if let Some(eq) = chain.get_effect_as_mut::<ParametricEq>(slot_index) {
                         eq.set_bands(bands.iter().map(|b| b.clone().into()).collect());
+                        eq.set_phase_mode(eq_phase_mode(*linear_phase));
                         true
                     } else {
                         false
@@ -513,6 +937,7 @@ impl PlaybackManager {
                         for (i, &gain) in settings.gains.iter().enumerate() {
                             geq.set_band_gain(i, gain);
                         }
+                        geq.set_phase_mode(eq_phase_mode(settings.linear_phase));
                         true
                     } else {
                         false
@@ -536,6 +961,22 @@ impl PlaybackManager {
                         false
                     }
                 }
+                EffectType::MultibandCompressor { settings } => {
+                    if let Some(comp) = chain.get_effect_as_mut::<MultibandCompressor>(slot_index) {
+                        comp.set_settings(settings.clone().into());
+                        true
+                    } else {
+                        false
+                    }
+                }
+                EffectType::DynamicEq { bands } => {
+                    if let Some(eq) = chain.get_effect_as_mut::<DynamicEq>(slot_index) {
+                        eq.set_bands(bands.iter().map(|b| b.clone().into()).collect());
+                        true
+                    } else {
+                        false
+                    }
+                }
                 EffectType::Stereo { settings } => {
                     if let Some(stereo) = chain.get_effect_as_mut::<StereoEnhancer>(slot_index) {
                         stereo.set_width(settings.width);
@@ -580,133 +1021,56 @@ impl PlaybackManager {
             if let Some(ref mut slot_state) = slots[slot_index] {
                 slot_state.effect = effect.clone();
             }
+            self.sync_eq_headroom(&slots);
         }
 
         Ok(updated)
     }
 
+    /// Build an effect chain from slot state, prepared for `sample_rate`
+    #[cfg(feature = "effects")]
+    fn build_effect_chain(
+        slots: &[Option<crate::dsp_commands::EffectSlotState>; 4],
+        sample_rate: u32,
+    ) -> soul_audio::effects::EffectChain {
+        let mut chain = soul_audio::effects::EffectChain::new();
+        for slot_state in slots.iter().flatten() {
+            chain.add_effect(slot_state.build_effect(sample_rate));
+        }
+        chain.set_sample_rate(sample_rate);
+        chain
+    }
+
     /// Rebuild the entire effect chain from current slot state
     #[cfg(feature = "effects")]
     fn rebuild_effect_chain(&self) -> Result<(), String> {
-        use crate::dsp_commands::EffectType;
-        use soul_audio::effects::{
-            AudioEffect, Compressor, ConvolutionEngine, Crossfeed, CrossfeedPreset,
-            CrossfeedSettings, GraphicEq, GraphicEqBands, Limiter, ParametricEq, StereoEnhancer,
-            StereoSettings,
-        };
-
         let slots = self.effect_slots.lock().map_err(|e| e.to_string())?;
+        let sample_rate = self.get_current_sample_rate();
 
         self.with_effect_chain(|chain| {
             // Clear existing effects
             chain.clear();
 
             // Add effects from slots
-            for slot in slots.iter() {
-                if let Some(slot_state) = slot {
-                    let effect: Box<dyn soul_audio::effects::AudioEffect> = match &slot_state.effect
-                    {
-                        EffectType::Eq { bands } => {
-                            let mut eq = ParametricEq::new();
-                            eq.set_bands(bands.iter().map(|b| b.clone().into()).collect());
-                            eq.set_enabled(slot_state.enabled);
-                            Box::new(eq)
-                        }
-                        EffectType::Compressor { settings } => {
-                            let mut comp = Compressor::with_settings(settings.clone().into());
-                            comp.set_enabled(slot_state.enabled);
-                            Box::new(comp)
-                        }
-                        EffectType::Limiter { settings } => {
-                            let mut lim = Limiter::with_settings(settings.clone().into());
-                            lim.set_enabled(slot_state.enabled);
-                            Box::new(lim)
-                        }
-                        EffectType::Crossfeed { settings } => {
-                            let preset = match settings.preset.as_str() {
-                                "natural" => CrossfeedPreset::Natural,
-                                "relaxed" => CrossfeedPreset::Relaxed,
-                                "meier" => CrossfeedPreset::Meier,
-                                _ => CrossfeedPreset::Custom,
-                            };
-
-                            let crossfeed_settings = if preset == CrossfeedPreset::Custom {
-                                CrossfeedSettings::custom(settings.level_db, settings.cutoff_hz)
-                            } else {
-                                CrossfeedSettings::from_preset(preset)
-                            };
-
-                            let mut crossfeed = Crossfeed::with_settings(crossfeed_settings);
-                            crossfeed.set_enabled(slot_state.enabled);
-                            Box::new(crossfeed)
-                        }
-                        EffectType::Stereo { settings } => {
-                            let stereo_settings = StereoSettings {
-                                width: settings.width,
-                                mid_gain_db: settings.mid_gain_db,
-                                side_gain_db: settings.side_gain_db,
-                                balance: settings.balance,
-                            };
-
-                            let mut stereo = StereoEnhancer::with_settings(stereo_settings);
-                            stereo.set_enabled(slot_state.enabled);
-                            Box::new(stereo)
-                        }
-                        EffectType::GraphicEq { settings } => {
-                            let mut graphic_eq = if settings.band_count == 31 {
-                                GraphicEq::new(GraphicEqBands::ThirtyOne)
-                            } else {
-                                GraphicEq::new_10_band()
-                            };
-
-                            // Apply gains if we have the right number
-                            if settings.band_count == 10 && settings.gains.len() == 10 {
-                                if let Ok(gains) = settings.gains.clone().try_into() {
-                                    graphic_eq.set_gains_10(gains);
-                                }
-                            } else {
-                                // For 31-band or custom, set each band individually
-                                for (i, &gain) in settings.gains.iter().enumerate() {
-                                    graphic_eq.set_band_gain(i, gain);
-                                }
-                            }
-
-                            graphic_eq.set_enabled(slot_state.enabled);
-                            Box::new(graphic_eq)
-                        }
-                        EffectType::Convolution { settings } => {
-                            let mut conv = ConvolutionEngine::new();
-
-                            // Load IR from file path if provided
-                            if !settings.ir_file_path.is_empty() {
-                                match conv.load_from_wav(&settings.ir_file_path) {
-                                    Ok(()) => {
-                                        conv.set_dry_wet_mix(settings.wet_dry_mix);
-                                        // Note: pre_delay_ms and decay are UI-only for now
-                                        // The ConvolutionEngine applies full IR as-is
-                                        eprintln!(
-                                            "[rebuild_effect_chain] Loaded IR: {}",
-                                            settings.ir_file_path
-                                        );
-                                    }
-                                    Err(e) => {
-                                        eprintln!(
-                                            "[rebuild_effect_chain] Failed to load IR file '{}': {}",
-                                            settings.ir_file_path, e
-                                        );
-                                        // Keep the engine but it won't process anything
-                                    }
-                                }
-                            }
-
-                            conv.set_enabled(slot_state.enabled);
-                            Box::new(conv)
-                        }
-                    };
-                    chain.add_effect(effect);
-                }
+            for slot_state in slots.iter().flatten() {
+                chain.add_effect(slot_state.build_effect(sample_rate));
             }
-        })
+        })?;
+
+        self.sync_eq_headroom(&slots);
+        Ok(())
+    }
+
+    /// Reserve headroom for imported EQ presets
+    ///
+    /// An imported AutoEQ/APO preamp tells us how much the filter set can
+    /// boost; it's handed to the headroom manager as the EQ boost so Auto
+    /// mode attenuates by that amount before the chain.
+    #[cfg(feature = "effects")]
+    fn sync_eq_headroom(&self, slots: &[Option<crate::dsp_commands::EffectSlotState>; 4]) {
+        let boost_db = crate::dsp_commands::eq_boost_db(slots);
+
+        self.set_headroom_eq_boost_db(boost_db as f64);
     }
 
     /// Access the effect chain for configuration
@@ -719,6 +1083,13 @@ impl PlaybackManager {
         Ok(playback.with_effect_chain(f))
     }
 
+    /// Names of the enabled effects bypassed on the current output device
+    #[cfg(feature = "effects")]
+    pub fn bypassed_effects(&self) -> Result<Vec<String>, String> {
+        let playback = self.playback.lock().map_err(|e| e.to_string())?;
+        Ok(playback.bypassed_effects())
+    }
+
     // ===== Volume Leveling =====
 
     /// Set volume leveling mode (ReplayGain track/album, EBU R128, etc.)
@@ -763,6 +1134,18 @@ impl PlaybackManager {
         playback.set_prevent_clipping(prevent);
     }
 
+    /// Set the gain offset for the current track (from DSP overrides)
+    pub fn set_gain_offset_db(&self, gain_db: f64) {
+        let playback = self.playback.lock().unwrap();
+        playback.set_gain_offset_db(gain_db);
+    }
+
+    /// Get the gain offset for the current track
+    pub fn get_gain_offset_db(&self) -> f64 {
+        let playback = self.playback.lock().unwrap();
+        playback.get_gain_offset_db()
+    }
+
     // ===== Exclusive Mode / Bit-Perfect Output =====
 
     /// Get current latency information
@@ -835,6 +1218,34 @@ impl PlaybackManager {
         playback.get_crossfade_curve()
     }
 
+    // ===========================================================================
+    // Playback Speed
+    // ===========================================================================
+
+    /// Set the default playback speed and pitch
+    pub fn set_playback_rate(&self, rate: PlaybackRate) {
+        let playback = self.playback.lock().unwrap();
+        playback.set_playback_rate(rate);
+    }
+
+    /// Get the default playback speed and pitch
+    pub fn get_playback_rate(&self) -> PlaybackRate {
+        let playback = self.playback.lock().unwrap();
+        playback.get_playback_rate()
+    }
+
+    /// Override the playback speed and pitch of the current track (None = default)
+    pub fn set_current_track_playback_rate(&self, rate: Option<PlaybackRate>) {
+        let playback = self.playback.lock().unwrap();
+        playback.set_current_track_playback_rate(rate);
+    }
+
+    /// Get the playback speed and pitch in effect for the current track
+    pub fn get_current_playback_rate(&self) -> PlaybackRate {
+        let playback = self.playback.lock().unwrap();
+        playback.get_current_playback_rate()
+    }
+
     // ===========================================================================
     // Resampling Settings
     // ===========================================================================
@@ -897,6 +1308,65 @@ impl PlaybackManager {
         playback.get_resampling_backend()
     }
 
+    // ===== Dither Settings =====
+
+    /// Set the output dither mode ("none", "tpdf", "lipshitz", "f-weighted", "e-weighted")
+    ///
+    /// Applies immediately to 16/24-bit integer outputs.
+    pub fn set_dither_mode(&self, mode: &str) -> Result<(), String> {
+        let playback = self.playback.lock().unwrap();
+        playback.set_dither_mode(mode)
+    }
+
+    /// Get current output dither mode
+    pub fn get_dither_mode(&self) -> String {
+        let playback = self.playback.lock().unwrap();
+        playback.get_dither_mode()
+    }
+
+    // ===== Channel Layout =====
+
+    /// Get the channel count of the current output stream
+    pub fn get_output_channels(&self) -> u16 {
+        let playback = self.playback.lock().unwrap();
+        playback.get_output_channels()
+    }
+
+    /// Set the coefficients used to fold surround files down to fewer channels
+    ///
+    /// Applies to tracks loaded after the call.
+    pub fn set_downmix_settings(&self, settings: DownmixSettings) -> Result<(), String> {
+        let playback = self.playback.lock().unwrap();
+        playback.set_downmix_settings(settings)
+    }
+
+    /// Get the current downmix coefficients
+    pub fn get_downmix_settings(&self) -> DownmixSettings {
+        let playback = self.playback.lock().unwrap();
+        playback.get_downmix_settings()
+    }
+
+    // ===== Audio Analysis =====
+
+    /// Enable or disable the level/spectrum analysis tap
+    #[cfg(feature = "effects")]
+    pub fn set_analysis_enabled(&self, enabled: bool) {
+        let playback = self.playback.lock().unwrap();
+        playback.set_analysis_enabled(enabled);
+    }
+
+    /// Check if the analysis tap is enabled
+    pub fn is_analysis_enabled(&self) -> bool {
+        let playback = self.playback.lock().unwrap();
+        playback.is_analysis_enabled()
+    }
+
+    /// Get the latest level/loudness/spectrum snapshot
+    pub fn get_audio_analysis(&self) -> Option<AudioAnalysis> {
+        let playback = self.playback.lock().unwrap();
+        playback.get_audio_analysis()
+    }
+
     // ===== Headroom Management =====
 
     /// Set headroom management mode
@@ -947,3 +1417,21 @@ impl PlaybackManager {
         playback.get_headroom_attenuation_db()
     }
 }
+
+/// Whether an in-place update can turn `old` into `new`
+///
+/// A graphic EQ can't change its band count in place.
+#[cfg(feature = "effects")]
+fn same_band_layout(
+    old: &crate::dsp_commands::EffectType,
+    new: &crate::dsp_commands::EffectType,
+) -> bool {
+    use crate::dsp_commands::EffectType;
+
+    match (old, new) {
+        (EffectType::GraphicEq { settings: old }, EffectType::GraphicEq { settings: new }) => {
+            old.band_count == new.band_count
+        }
+        _ => true,
+    }
+}
@@ -0,0 +1,428 @@
+//! Background tempo and key detection
+//!
+//! Processes the tempo/key queue in the background, detecting the BPM and
+//! musical key of each track and storing them in the database. BPM and key
+//! tags read on import take precedence unless the worker is told to
+//! overwrite them, and detected values can optionally be written back to
+//! the files' tags.
+
+use crate::app_state::AppState;
+use serde::{Deserialize, Serialize};
+use soul_importer::metadata::KeyNotation;
+use soul_storage::tempo_key::{TempoKeyAnalysis, TempoKeyFilter, TrackTempoKey};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, State};
+use tokio::sync::Mutex;
+
+/// Tempo/key worker state
+pub struct TempoKeyWorker {
+    /// Whether the worker is currently running
+    running: AtomicBool,
+    /// Number of items processed in current session
+    processed_count: AtomicI64,
+    /// Last error message
+    last_error: Mutex<Option<String>>,
+    /// Cancellation token
+    cancel: AtomicBool,
+    /// Options for the current session
+    options: Mutex<TempoKeyOptions>,
+}
+
+impl TempoKeyWorker {
+    pub fn new() -> Self {
+        Self {
+            running: AtomicBool::new(false),
+            processed_count: AtomicI64::new(0),
+            last_error: Mutex::new(None),
+            cancel: AtomicBool::new(false),
+            options: Mutex::new(TempoKeyOptions::default()),
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    pub fn processed_count(&self) -> i64 {
+        self.processed_count.load(Ordering::SeqCst)
+    }
+
+    pub fn request_cancel(&self) {
+        self.cancel.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::SeqCst)
+    }
+
+    pub fn reset(&self) {
+        self.running.store(false, Ordering::SeqCst);
+        self.cancel.store(false, Ordering::SeqCst);
+        self.processed_count.store(0, Ordering::SeqCst);
+    }
+}
+
+impl Default for TempoKeyWorker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Options for a tempo/key analysis session
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TempoKeyOptions {
+    /// Replace BPM/key read from tags with detected values
+    pub overwrite_tags: bool,
+    /// Write detected values back to the files' tags
+    pub write_tags: bool,
+    /// Notation used for the key when writing tags
+    pub key_notation: KeyNotation,
+}
+
+/// Tempo/key analysis status for frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TempoKeyStatus {
+    pub is_running: bool,
+    pub pending_count: i64,
+    pub failed_count: i64,
+    pub processed_this_session: i64,
+    pub last_error: Option<String>,
+}
+
+/// Get tempo/key analysis status
+#[tauri::command]
+pub async fn get_tempo_key_status(
+    state: State<'_, AppState>,
+    worker: State<'_, Arc<TempoKeyWorker>>,
+) -> Result<TempoKeyStatus, String> {
+    let stats = soul_storage::tempo_key_queue::get_stats(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let last_error = worker.last_error.lock().await.clone();
+
+    Ok(TempoKeyStatus {
+        is_running: worker.is_running(),
+        pending_count: stats.pending,
+        failed_count: stats.failed,
+        processed_this_session: worker.processed_count(),
+        last_error,
+    })
+}
+
+/// Start the tempo/key analysis worker
+#[tauri::command]
+pub async fn start_tempo_key_analysis(
+    app: AppHandle,
+    options: Option<TempoKeyOptions>,
+    state: State<'_, AppState>,
+    worker: State<'_, Arc<TempoKeyWorker>>,
+) -> Result<(), String> {
+    if worker.is_running() {
+        return Err("Tempo/key analysis already in progress".to_string());
+    }
+
+    worker.reset();
+    worker.running.store(true, Ordering::SeqCst);
+    *worker.options.lock().await = options.unwrap_or_default();
+
+    let pool = (*state.pool).clone();
+    let worker_clone = Arc::clone(&worker);
+
+    // Spawn background task
+    tokio::spawn(async move {
+        run_tempo_key_worker(app, pool, worker_clone).await;
+    });
+
+    Ok(())
+}
+
+/// Stop the tempo/key analysis worker
+#[tauri::command]
+pub async fn stop_tempo_key_analysis(worker: State<'_, Arc<TempoKeyWorker>>) -> Result<(), String> {
+    if !worker.is_running() {
+        return Ok(());
+    }
+
+    worker.request_cancel();
+    Ok(())
+}
+
+/// Queue a track for tempo/key analysis
+#[tauri::command]
+pub async fn queue_tempo_key_analysis(
+    track_id: i64,
+    priority: Option<i32>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    soul_storage::tempo_key_queue::enqueue(&state.pool, track_id, priority.unwrap_or(0))
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Queue all tracks without a BPM or key
+#[tauri::command]
+pub async fn queue_all_tempo_key_analysis(state: State<'_, AppState>) -> Result<u64, String> {
+    soul_storage::tempo_key_queue::enqueue_unanalyzed(&state.pool, 0)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Retry failed tempo/key analyses
+#[tauri::command]
+pub async fn retry_failed_tempo_key_analysis(state: State<'_, AppState>) -> Result<u64, String> {
+    soul_storage::tempo_key_queue::retry_failed(&state.pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Clear failed tempo/key analyses
+#[tauri::command]
+pub async fn clear_failed_tempo_key_analysis(state: State<'_, AppState>) -> Result<u64, String> {
+    soul_storage::tempo_key_queue::clear_failed(&state.pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Get the BPM and key of a track
+#[tauri::command]
+pub async fn get_track_tempo_key(
+    track_id: i64,
+    state: State<'_, AppState>,
+) -> Result<Option<TrackTempoKey>, String> {
+    soul_storage::tempo_key::get_track_tempo_key(&state.pool, track_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Find tracks by BPM range and key
+#[tauri::command]
+pub async fn find_tracks_by_tempo_key(
+    filter: TempoKeyFilter,
+    state: State<'_, AppState>,
+) -> Result<Vec<TrackTempoKey>, String> {
+    soul_storage::tempo_key::find_tracks(&state.pool, &filter)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Write a track's stored BPM and key to its file's tags
+#[tauri::command]
+pub async fn write_tempo_key_tags(
+    track_id: i64,
+    key_notation: Option<KeyNotation>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let values = soul_storage::tempo_key::get_track_tempo_key(&state.pool, track_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Track {} not found", track_id))?;
+
+    let is_virtual = soul_storage::cue_tracks::get_range(&state.pool, track_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .is_some();
+    if is_virtual {
+        return Err(format!("Track {} is part of a CUE sheet", track_id));
+    }
+
+    let file_path = track_file_path(&state.pool, track_id).await?;
+    write_tags(&file_path, &values, key_notation.unwrap_or_default()).await
+}
+
+/// Background worker that processes the tempo/key queue
+async fn run_tempo_key_worker(app: AppHandle, pool: sqlx::SqlitePool, worker: Arc<TempoKeyWorker>) {
+    tracing::info!("Tempo/key worker started");
+    let options = *worker.options.lock().await;
+
+    // Emit initial status
+    let _ = app.emit("tempo-key-started", ());
+
+    loop {
+        // Check for cancellation
+        if worker.is_cancelled() {
+            tracing::info!("Tempo/key worker cancelled");
+            break;
+        }
+
+        // Get next item to process
+        let item = match soul_storage::tempo_key_queue::get_next(&pool).await {
+            Ok(Some(item)) => item,
+            Ok(None) => {
+                // No more items, worker is done
+                tracing::info!("Tempo/key queue empty, worker stopping");
+                break;
+            }
+            Err(e) => {
+                tracing::error!("Failed to get tempo/key queue item: {}", e);
+                *worker.last_error.lock().await = Some(e.to_string());
+                // Wait a bit before retrying
+                tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+                continue;
+            }
+        };
+
+        match process_tempo_key(&pool, item.track_id, options).await {
+            Ok(()) => {
+                // Remove from queue
+                let _ = soul_storage::tempo_key_queue::complete(&pool, item.id).await;
+                worker.processed_count.fetch_add(1, Ordering::SeqCst);
+
+                // Emit progress event
+                let pending = soul_storage::tempo_key_queue::pending_count(&pool)
+                    .await
+                    .unwrap_or(0);
+                let _ = app.emit(
+                    "tempo-key-progress",
+                    TempoKeyProgressEvent {
+                        processed: worker.processed_count(),
+                        pending,
+                    },
+                );
+            }
+            Err(e) => {
+                tracing::warn!("Failed to detect tempo/key of {}: {}", item.track_id, e);
+                let _ = soul_storage::tempo_key_queue::fail(&pool, item.id, &e).await;
+                *worker.last_error.lock().await = Some(e);
+            }
+        }
+
+        // Small delay to avoid overwhelming the system
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    }
+
+    worker.running.store(false, Ordering::SeqCst);
+
+    // Emit completion event
+    let _ = app.emit(
+        "tempo-key-complete",
+        TempoKeyCompleteEvent {
+            processed: worker.processed_count(),
+        },
+    );
+
+    tracing::info!(
+        "Tempo/key worker stopped. Processed {} items.",
+        worker.processed_count()
+    );
+}
+
+/// Detect and store the tempo and key of a single track
+async fn process_tempo_key(
+    pool: &sqlx::SqlitePool,
+    track_id: i64,
+    options: TempoKeyOptions,
+) -> Result<(), String> {
+    let file_path = track_file_path(pool, track_id).await?;
+
+    // CUE sheet tracks share a file; only analyze their own range
+    let range = soul_storage::cue_tracks::get_range(pool, track_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let (start, end) = range
+        .as_ref()
+        .map_or((std::time::Duration::ZERO, None), |r| (r.start(), r.end()));
+
+    // Run detection in blocking task to avoid blocking async runtime
+    let file_path_owned = file_path.clone();
+    let analysis = tokio::task::spawn_blocking(move || {
+        soul_audio::musical::analyze_file_range(Path::new(&file_path_owned), start, end)
+    })
+    .await
+    .map_err(|e| format!("Tempo/key task failed: {}", e))?
+    .map_err(|e| format!("Tempo/key error: {}", e))?;
+
+    let analysis = TempoKeyAnalysis {
+        bpm: analysis.tempo.map(|t| (t.bpm, t.confidence)),
+        musical_key: analysis.key.map(|k| (k.key.camelot(), k.confidence)),
+    };
+    soul_storage::tempo_key::update_from_analysis(
+        pool,
+        track_id,
+        &analysis,
+        options.overwrite_tags,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // Tags of a shared CUE file don't belong to any single track
+    if options.write_tags && range.is_none() {
+        let stored = soul_storage::tempo_key::get_track_tempo_key(pool, track_id)
+            .await
+            .map_err(|e| e.to_string())?;
+        if let Some(values) = stored {
+            write_tags(&file_path, &values, options.key_notation).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Write stored BPM and key to a file's tags
+async fn write_tags(
+    file_path: &str,
+    values: &TrackTempoKey,
+    key_notation: KeyNotation,
+) -> Result<(), String> {
+    if values.bpm.is_none() && values.musical_key.is_none() {
+        return Ok(());
+    }
+
+    let file_path = file_path.to_string();
+    let bpm = values.bpm;
+    let musical_key = values.musical_key.clone();
+    tokio::task::spawn_blocking(move || {
+        soul_importer::metadata::write_tempo_key_tags(
+            Path::new(&file_path),
+            bpm,
+            musical_key.as_deref(),
+            key_notation,
+        )
+    })
+    .await
+    .map_err(|e| format!("Tag write task failed: {}", e))?
+    .map_err(|e| e.to_string())
+}
+
+/// Find the local file of a track
+async fn track_file_path(pool: &sqlx::SqlitePool, track_id: i64) -> Result<String, String> {
+    let track =
+        soul_storage::tracks::get_by_id(pool, soul_core::types::TrackId::new(track_id.to_string()))
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Track {} not found", track_id))?;
+
+    track
+        .availability
+        .iter()
+        .find_map(|avail| {
+            if matches!(
+                avail.status,
+                soul_core::types::AvailabilityStatus::LocalFile
+                    | soul_core::types::AvailabilityStatus::Cached
+            ) {
+                avail.local_file_path.clone()
+            } else {
+                None
+            }
+        })
+        .ok_or_else(|| format!("No local file found for track {}", track_id))
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TempoKeyProgressEvent {
+    processed: i64,
+    pending: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TempoKeyCompleteEvent {
+    processed: i64,
+}
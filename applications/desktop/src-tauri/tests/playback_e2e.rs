@@ -308,6 +308,7 @@ fn test_e2e_queue_management() {
             duration: Duration::from_secs(180),
             track_number: Some(i),
             source: TrackSource::Single,
+            ..Default::default()
         })
         .collect();
 
@@ -367,6 +368,7 @@ fn test_e2e_navigation_commands() {
             duration: Duration::from_secs(180),
             track_number: Some(i),
             source: TrackSource::Single,
+            ..Default::default()
         };
         manager.add_to_queue(track).unwrap();
     }
@@ -419,6 +421,7 @@ fn test_e2e_complete_user_session() {
                 id: "playlist1".to_string(),
                 name: "Test Playlist".to_string(),
             },
+            ..Default::default()
         };
         manager.add_to_queue(track).unwrap();
         std::thread::sleep(Duration::from_millis(5));
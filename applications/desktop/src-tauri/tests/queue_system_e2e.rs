@@ -103,6 +103,7 @@ fn create_test_track(id: &str, title: &str, artist: &str) -> QueueTrack {
         duration: Duration::from_secs(180),
         track_number: Some(id.parse().unwrap_or(1)),
         source: TrackSource::Single,
+        ..Default::default()
     }
 }
 
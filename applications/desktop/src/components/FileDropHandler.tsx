@@ -45,7 +45,7 @@ export function FileDropHandler({ children }: FileDropHandlerProps) {
 
   // Helper to process file paths and show dialog (or auto-handle based on settings)
   const processFilePaths = async (paths: string[]) => {
-    const audioExtensions = ['mp3', 'flac', 'wav', 'ogg', 'oga', 'm4a', 'mp4', 'aac', 'opus', 'wma', 'aiff', 'aif', 'ape', 'wv'];
+    const audioExtensions = ['mp3', 'flac', 'wav', 'ogg', 'oga', 'm4a', 'mp4', 'aac', 'opus', 'wma', 'aiff', 'aif', 'ape', 'wv', 'dsf', 'dff'];
 
     try {
       const files: DroppedFile[] = [];
@@ -325,7 +325,7 @@ export function ImportDialog({ open, onClose }: ImportDialogProps) {
       // Use Tauri command to open file dialog
       const files = await invoke<string[] | null>('open_file_dialog', {
         multiple: true,
-        filters: [{ name: 'Audio Files', extensions: ['mp3', 'flac', 'ogg', 'wav', 'aac', 'm4a', 'opus'] }]
+        filters: [{ name: 'Audio Files', extensions: ['mp3', 'flac', 'ogg', 'wav', 'aac', 'm4a', 'opus', 'dsf', 'dff', 'cue'] }]
       });
 
       console.log('File dialog result:', files);
@@ -13,7 +13,9 @@ use axum::{
 };
 use serde::Deserialize;
 use soul_core::{storage::StorageContext, types::TrackId};
+use std::io::SeekFrom;
 use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
 use tokio_util::io::ReaderStream;
 
 #[derive(Debug, Deserialize)]
@@ -80,11 +82,12 @@ pub async fn stream_track(
 
         if let Some((start, end)) = parse_range(range_str, file_size) {
             // Open file and seek to position
-            let file = File::open(&file_path).await?;
-            let reader = ReaderStream::new(file);
+            let mut file = File::open(&file_path).await?;
+            file.seek(SeekFrom::Start(start)).await?;
 
             // Create range response
             let content_length = end - start + 1;
+            let reader = ReaderStream::new(file.take(content_length));
             let body = Body::from_stream(reader);
 
             let response = Response::builder()
@@ -144,10 +144,10 @@ export function DeviceSelector() {
 
     loadCurrentDevice();
 
-    // Listen for sample rate changes from the backend
-    // This fires when the device sample rate changes externally
+    // Listen for sample rate and device changes from the backend
+    // Sample rate changes fire when the device is reconfigured externally
     // (e.g., via ASIO control panel or Windows sound settings)
-    let unlistenFn: (() => void) | undefined;
+    let unlistenFns: (() => void)[] = [];
     let mounted = true;
 
     const setupListener = async () => {
@@ -167,7 +167,14 @@ export function DeviceSelector() {
           }
         });
 
-        unlistenFn = unlisten;
+        // The output moved to another device (e.g. the system default changed)
+        const unlistenDevice = await listen<{ backend: string; deviceName: string }>('playback:device-changed', (event) => {
+          if (!mounted) return;
+          console.log('[DeviceSelector] Device changed:', event.payload.deviceName, `(${event.payload.backend})`);
+          loadCurrentDevice();
+        });
+
+        unlistenFns = [unlisten, unlistenDevice];
       } catch (error) {
         // Tauri not available (browser mode), ignore
         console.log('[DeviceSelector] Tauri event listener not available');
@@ -178,9 +185,7 @@ export function DeviceSelector() {
 
     return () => {
       mounted = false;
-      if (unlistenFn) {
-        unlistenFn();
-      }
+      unlistenFns.forEach((unlisten) => unlisten());
     };
   }, [isBrowserDemo, loadCurrentDevice, loadDevicesCallback]);
 
@@ -15,6 +15,7 @@ import {
   ConvolutionEditor,
   defaultConvolutionSettings,
 } from './effects';
+import type { FilterAlignment, FilterSlope, FilterType } from './effects/ParametricEqEditor';
 
 // Types matching backend
 export interface EffectSlot {
@@ -24,8 +25,10 @@ export interface EffectSlot {
 }
 
 export type EffectType =
-  | { type: 'eq'; bands: EqBand[] }
+  | { type: 'eq'; bands: EqBand[]; preampDb?: number; linearPhase?: boolean }
   | { type: 'compressor'; settings: CompressorSettings }
+  | { type: 'multiband_compressor'; settings: MultibandCompressorSettings }
+  | { type: 'dynamic_eq'; bands: DynamicEqBand[] }
   | { type: 'limiter'; settings: LimiterSettings }
   | { type: 'crossfeed'; settings: CrossfeedSettings }
   | { type: 'stereo'; settings: StereoSettings }
@@ -36,6 +39,9 @@ export interface EqBand {
   frequency: number;
   gain: number;
   q: number;
+  filterType?: FilterType;
+  alignment?: FilterAlignment;
+  slope?: FilterSlope;
 }
 
 export interface CompressorSettings {
@@ -47,6 +53,21 @@ export interface CompressorSettings {
   makeupGainDb: number;
 }
 
+export interface MultibandCompressorSettings {
+  crossoversHz: number[];
+  bands: CompressorSettings[];
+}
+
+export interface DynamicEqBand {
+  frequency: number;
+  q: number;
+  thresholdDb: number;
+  ratio: number;
+  rangeDb: number;
+  attackMs: number;
+  releaseMs: number;
+}
+
 export interface LimiterSettings {
   thresholdDb: number;
   releaseMs: number;
@@ -69,10 +90,12 @@ export interface GraphicEqSettings {
   preset: string;
   bandCount: number;
   gains: number[];
+  linearPhase?: boolean;
 }
 
 export interface ConvolutionSettings {
   irFilePath: string;
+  irSetPaths?: string[];
   wetDryMix: number;
   preDelayMs: number;
   decay: number;
@@ -82,7 +105,16 @@ interface DspConfigProps {
   onChainChange?: () => void;
 }
 
-type EffectTypeKey = 'eq' | 'graphic_eq' | 'compressor' | 'limiter' | 'crossfeed' | 'stereo' | 'convolution';
+type EffectTypeKey =
+  | 'eq'
+  | 'graphic_eq'
+  | 'compressor'
+  | 'multiband_compressor'
+  | 'dynamic_eq'
+  | 'limiter'
+  | 'crossfeed'
+  | 'stereo'
+  | 'convolution';
 
 interface EffectInfo {
   key: EffectTypeKey;
@@ -114,6 +146,20 @@ const EFFECT_INFO: EffectInfo[] = [
     icon: <Gauge className="w-5 h-5" />,
     category: 'dynamics',
   },
+  {
+    key: 'multiband_compressor',
+    name: 'Multiband Compressor',
+    description: '3 to 5 band compression with Linkwitz-Riley crossovers',
+    icon: <Gauge className="w-5 h-5" />,
+    category: 'dynamics',
+  },
+  {
+    key: 'dynamic_eq',
+    name: 'Dynamic EQ',
+    description: 'Level-dependent EQ bands, e.g. de-essing or bass taming',
+    icon: <SlidersHorizontal className="w-5 h-5" />,
+    category: 'dynamics',
+  },
   {
     key: 'limiter',
     name: 'Limiter',
@@ -152,11 +198,66 @@ export function DspConfig({ onChainChange }: DspConfigProps) {
   const [editingSlot, setEditingSlot] = useState<number | null>(null);
   const [notification, setNotification] = useState<{ type: 'success' | 'error'; message: string } | null>(null);
   const [showClearDialog, setShowClearDialog] = useState(false);
+  const [bypassedEffects, setBypassedEffects] = useState<string[]>([]);
 
   useEffect(() => {
     loadChain();
   }, []);
 
+  // Stereo-only effects don't run on surround devices; list them
+  const loadBypassedEffects = useCallback(async () => {
+    try {
+      setBypassedEffects(await invoke<string[]>('get_bypassed_effects'));
+    } catch {
+      setBypassedEffects([]);
+    }
+  }, []);
+
+  useEffect(() => {
+    loadBypassedEffects();
+  }, [chain, loadBypassedEffects]);
+
+  useEffect(() => {
+    let unlisten: (() => void) | undefined;
+    let mounted = true;
+
+    import('@tauri-apps/api/event')
+      .then(({ listen }) => listen('playback:device-changed', () => mounted && loadBypassedEffects()))
+      .then((fn) => {
+        unlisten = fn;
+        if (!mounted) fn();
+      })
+      .catch(() => {
+        // Tauri not available (browser mode), ignore
+      });
+
+    return () => {
+      mounted = false;
+      unlisten?.();
+    };
+  }, [loadBypassedEffects]);
+
+  // Reload when switching devices loads a bound DSP profile
+  useEffect(() => {
+    let unlisten: (() => void) | undefined;
+    let mounted = true;
+
+    import('@tauri-apps/api/event')
+      .then(({ listen }) => listen('dsp:profile-applied', () => mounted && loadChain()))
+      .then((fn) => {
+        unlisten = fn;
+        if (!mounted) fn();
+      })
+      .catch(() => {
+        // Tauri not available (browser mode), ignore
+      });
+
+    return () => {
+      mounted = false;
+      unlisten?.();
+    };
+  }, []);
+
   // Auto-hide notifications
   useEffect(() => {
     if (notification) {
@@ -221,6 +322,18 @@ export function DspConfig({ onChainChange }: DspConfigProps) {
             },
           };
           break;
+        case 'multiband_compressor': {
+          const presets = await invoke<[string, MultibandCompressorSettings][]>(
+            'get_multiband_compressor_presets'
+          );
+          effect = { type: 'multiband_compressor', settings: presets[0][1] };
+          break;
+        }
+        case 'dynamic_eq': {
+          const presets = await invoke<[string, DynamicEqBand[]][]>('get_dynamic_eq_presets');
+          effect = { type: 'dynamic_eq', bands: presets[0][1] };
+          break;
+        }
         case 'limiter':
           effect = {
             type: 'limiter',
@@ -343,22 +456,45 @@ export function DspConfig({ onChainChange }: DspConfigProps) {
     if (!slot.effect) return null;
 
     switch (slot.effect.type) {
-      case 'eq':
+      case 'eq': {
+        const eq = slot.effect;
         return (
-          <ParametricEqEditor
-            bands={slot.effect.bands}
-            onBandsChange={(bands) => handleEffectChange(slot.index, { type: 'eq', bands })}
-            slotIndex={slot.index}
-          />
+          <>
+            <LinearPhaseToggle
+              checked={eq.linearPhase ?? false}
+              onChange={(linearPhase) => handleEffectChange(slot.index, { ...eq, linearPhase })}
+            />
+            <ParametricEqEditor
+              bands={eq.bands}
+              onBandsChange={(bands) => handleEffectChange(slot.index, { ...eq, bands })}
+              slotIndex={slot.index}
+            />
+          </>
         );
-      case 'graphic_eq':
+      }
+      case 'graphic_eq': {
+        const current = slot.effect.settings;
         return (
-          <GraphicEqEditor
-            settings={slot.effect.settings}
-            onSettingsChange={(settings) => handleEffectChange(slot.index, { type: 'graphic_eq', settings })}
-            slotIndex={slot.index}
-          />
+          <>
+            <LinearPhaseToggle
+              checked={current.linearPhase ?? false}
+              onChange={(linearPhase) =>
+                handleEffectChange(slot.index, { type: 'graphic_eq', settings: { ...current, linearPhase } })
+              }
+            />
+            <GraphicEqEditor
+              settings={current}
+              onSettingsChange={(settings) =>
+                handleEffectChange(slot.index, {
+                  type: 'graphic_eq',
+                  settings: { ...settings, linearPhase: current.linearPhase },
+                })
+              }
+              slotIndex={slot.index}
+            />
+          </>
         );
+      }
       case 'compressor':
         return (
           <CompressorEditor
@@ -463,6 +599,22 @@ export function DspConfig({ onChainChange }: DspConfigProps) {
         variant="destructive"
       />
 
+      {/* Effects the output device's channel layout bypasses */}
+      {bypassedEffects.length > 0 && (
+        <div
+          data-testid="bypassed-effects-warning"
+          className="p-3 rounded-lg border flex items-start gap-3 bg-amber-50 border-amber-200 text-amber-900 dark:bg-amber-950 dark:border-amber-800 dark:text-amber-100"
+        >
+          <AlertCircle className="w-5 h-5 flex-shrink-0" />
+          <span className="text-sm">
+            {t('dsp.bypassedOnSurround', {
+              defaultValue: 'Not applied on this surround output (stereo only): {{effects}}',
+              effects: bypassedEffects.join(', '),
+            })}
+          </span>
+        </div>
+      )}
+
       {/* Effect Slots */}
       <div className="space-y-3">
         {chain.map((slot) => (
@@ -646,6 +798,22 @@ export function DspConfig({ onChainChange }: DspConfigProps) {
   );
 }
 
+function LinearPhaseToggle({ checked, onChange }: { checked: boolean; onChange: (checked: boolean) => void }) {
+  return (
+    <label className="flex items-center gap-2 cursor-pointer mb-3">
+      <input
+        type="checkbox"
+        checked={checked}
+        onChange={(e) => onChange(e.target.checked)}
+        className="w-4 h-4"
+      />
+      <span className="text-xs text-muted-foreground">
+        Linear phase (no phase shift, adds ~100 ms latency)
+      </span>
+    </label>
+  );
+}
+
 function getEffectName(type: string): string {
   const info = EFFECT_INFO.find(e => e.key === type);
   return info?.name || type;
@@ -664,6 +832,10 @@ function getEffectDescription(effect: EffectType): string {
       return `${effect.settings.bandCount}-band, ${effect.settings.preset} preset`;
     case 'compressor':
       return `${effect.settings.ratio}:1 ratio, ${effect.settings.thresholdDb}dB threshold`;
+    case 'multiband_compressor':
+      return `${effect.settings.bands.length} bands`;
+    case 'dynamic_eq':
+      return `${effect.bands.length} dynamic ${effect.bands.length === 1 ? 'band' : 'bands'}`;
     case 'limiter':
       return `${effect.settings.thresholdDb}dB threshold, ${effect.settings.releaseMs}ms release`;
     case 'crossfeed':
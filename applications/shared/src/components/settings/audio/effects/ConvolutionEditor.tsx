@@ -23,6 +23,8 @@ import {
 export interface ConvolutionSettings {
   /** Path to impulse response file */
   irFilePath: string;
+  /** Extra IR files at other sample rates (the stream rate picks one) */
+  irSetPaths?: string[];
   /** Wet/dry mix: 0.0 (dry) to 1.0 (wet) */
   wetDryMix: number;
   /** Pre-delay in milliseconds: 0 to 100ms */
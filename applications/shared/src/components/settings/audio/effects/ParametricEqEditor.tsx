@@ -17,10 +17,28 @@ export interface EqBand {
   gain: number;       // dB (-24 to +24)
   q: number;          // 0.1 to 10
   filterType?: FilterType;
+  alignment?: FilterAlignment; // lowPass/highPass only
+  slope?: FilterSlope;         // lowPass/highPass only, dB/octave
   enabled?: boolean;
 }
 
-export type FilterType = 'bell' | 'lowShelf' | 'highShelf' | 'lowPass' | 'highPass';
+export type FilterType =
+  | 'bell'
+  | 'lowShelf'
+  | 'highShelf'
+  | 'lowPass'
+  | 'highPass'
+  | 'notch'
+  | 'bandPass'
+  | 'allPass'
+  | 'tiltShelf';
+
+export type FilterAlignment = 'butterworth' | 'linkwitzRiley';
+
+export type FilterSlope = 12 | 24 | 48;
+
+// Filter types whose gain has no effect
+const GAINLESS_FILTER_TYPES: FilterType[] = ['lowPass', 'highPass', 'notch', 'bandPass', 'allPass'];
 
 export interface ParametricEqEditorProps {
   bands: EqBand[];
@@ -154,8 +172,14 @@ const FILTER_TYPE_LABELS: Record<FilterType, string> = {
   highShelf: 'filterTypes.highShelf',
   lowPass: 'filterTypes.lowPass',
   highPass: 'filterTypes.highPass',
+  notch: 'filterTypes.notch',
+  bandPass: 'filterTypes.bandPass',
+  allPass: 'filterTypes.allPass',
+  tiltShelf: 'filterTypes.tiltShelf',
 };
 
+const FILTER_SLOPES: FilterSlope[] = [12, 24, 48];
+
 // Calculate EQ curve points
 function calculateEqCurve(
   bands: EqBand[],
@@ -179,7 +203,9 @@ function calculateEqCurve(
         band.frequency,
         band.gain,
         band.q,
-        band.filterType || 'bell'
+        band.filterType || 'bell',
+        band.slope ?? 12,
+        band.alignment ?? 'butterworth'
       );
       totalGain += contribution;
     }
@@ -199,7 +225,9 @@ function calculateBandContribution(
   centerFreq: number,
   gain: number,
   q: number,
-  filterType: FilterType
+  filterType: FilterType,
+  slope: FilterSlope = 12,
+  alignment: FilterAlignment = 'butterworth'
 ): number {
   const freqRatio = freq / centerFreq;
   const logRatio = Math.log2(freqRatio);
@@ -221,23 +249,44 @@ function calculateBandContribution(
       const transition = 1 / (1 + Math.exp(-q * 2 * logRatio));
       return gain * transition;
     }
-    case 'lowPass': {
-      // Low pass approximation
-      if (freq <= centerFreq) return 0;
-      const rolloff = Math.pow(centerFreq / freq, q * 2);
-      return -24 * (1 - rolloff);
+    case 'lowPass':
+      return passFilterResponse(freqRatio, slope, alignment);
+    case 'highPass':
+      return passFilterResponse(1 / freqRatio, slope, alignment);
+    case 'notch': {
+      // Analog notch: |H|^2 = (1-x^2)^2 / ((1-x^2)^2 + (x/Q)^2)
+      const d = 1 - freqRatio * freqRatio;
+      const b = freqRatio / q;
+      return 10 * Math.log10((d * d + 1e-12) / (d * d + b * b));
+    }
+    case 'bandPass': {
+      // Analog band-pass, 0 dB at center
+      const d = 1 - freqRatio * freqRatio;
+      const b = freqRatio / q;
+      return 10 * Math.log10((b * b) / (d * d + b * b));
     }
-    case 'highPass': {
-      // High pass approximation
-      if (freq >= centerFreq) return 0;
-      const rolloff = Math.pow(freq / centerFreq, q * 2);
-      return -24 * (1 - rolloff);
+    case 'allPass':
+      return 0;
+    case 'tiltShelf': {
+      // -gain/2 below the pivot, +gain/2 above
+      const transition = 1 / (1 + Math.exp(-q * 2 * logRatio));
+      return gain * (transition - 0.5);
     }
     default:
       return 0;
   }
 }
 
+// Low-pass magnitude in dB; x is frequency / cutoff (invert it for high-pass)
+function passFilterResponse(x: number, slope: FilterSlope, alignment: FilterAlignment): number {
+  const order = slope / 6;
+  if (alignment === 'linkwitzRiley') {
+    // Butterworth of half the order, squared
+    return -20 * Math.log10(1 + Math.pow(x, order));
+  }
+  return -10 * Math.log10(1 + Math.pow(x, 2 * order));
+}
+
 export function ParametricEqEditor({
   bands,
   onBandsChange,
@@ -264,6 +313,8 @@ export function ParametricEqEditor({
       gain: band.gain,
       q: band.q,
       filterType: band.filterType || 'bell' as FilterType,
+      alignment: band.alignment,
+      slope: band.slope,
       enabled: band.enabled !== false,
     }));
   }, [bands]);
@@ -702,6 +753,35 @@ export function ParametricEqEditor({
                   ))}
                 </select>
 
+                {/* Slope and alignment (pass filters only) */}
+                {(band.filterType === 'lowPass' || band.filterType === 'highPass') && (
+                  <>
+                    <select
+                      data-testid={`eq-slope-${index}`}
+                      value={band.slope ?? 12}
+                      onChange={(e) => updateBand(index, { slope: Number(e.target.value) as FilterSlope })}
+                      onClick={(e) => e.stopPropagation()}
+                      className="px-2 py-1 text-sm bg-background border border-border rounded focus:outline-none focus:ring-1 focus:ring-primary"
+                    >
+                      {FILTER_SLOPES.map((slope) => (
+                        <option key={slope} value={slope}>
+                          {slope} dB/oct
+                        </option>
+                      ))}
+                    </select>
+                    <select
+                      data-testid={`eq-alignment-${index}`}
+                      value={band.alignment ?? 'butterworth'}
+                      onChange={(e) => updateBand(index, { alignment: e.target.value as FilterAlignment })}
+                      onClick={(e) => e.stopPropagation()}
+                      className="px-2 py-1 text-sm bg-background border border-border rounded focus:outline-none focus:ring-1 focus:ring-primary"
+                    >
+                      <option value="butterworth">{t('filterTypes.butterworth', 'Butterworth')}</option>
+                      <option value="linkwitzRiley">{t('filterTypes.linkwitzRiley', 'Linkwitz-Riley')}</option>
+                    </select>
+                  </>
+                )}
+
                 {/* Frequency input */}
                 <div className="flex items-center gap-1">
                   <input
@@ -728,7 +808,8 @@ export function ParametricEqEditor({
                     value={band.gain}
                     onChange={(e) => updateBand(index, { gain: Number(e.target.value) })}
                     onClick={(e) => e.stopPropagation()}
-                    className="flex-1 accent-primary"
+                    disabled={GAINLESS_FILTER_TYPES.includes(band.filterType)}
+                    className="flex-1 accent-primary disabled:opacity-30"
                     min={MIN_GAIN}
                     max={MAX_GAIN}
                     step={0.5}
@@ -33,6 +33,6 @@ export type { EffectType, EffectSlot, CrossfeedSettings as DspCrossfeedSettings,
 export type { LimiterSettings, LimiterEditorProps } from './audio/effects/LimiterEditor';
 export type { CrossfeedSettings as CrossfeedEditorSettings, CrossfeedEditorProps } from './audio/effects/CrossfeedEditor';
 export type { GraphicEqSettings as GraphicEqEditorSettings, GraphicEqEditorProps } from './audio/effects/GraphicEqEditor';
-export type { EqBand, FilterType, ParametricEqEditorProps } from './audio/effects/ParametricEqEditor';
+export type { EqBand, FilterAlignment, FilterSlope, FilterType, ParametricEqEditorProps } from './audio/effects/ParametricEqEditor';
 export type { ConvolutionSettings, ConvolutionEditorProps } from './audio/effects/ConvolutionEditor';
 export type { CompressorSettings, CompressorEditorProps } from './audio/effects/CompressorEditor';
@@ -16,6 +16,10 @@ pub enum ArtworkError {
     #[error("Metadata error: {0}")]
     Lofty(#[from] lofty::error::LoftyError),
 
+    /// Error reading a file through the soul-audio decoder registry
+    #[error("Metadata error: {0}")]
+    Audio(#[from] soul_audio::AudioError),
+
     /// No artwork found in file
     #[error("No artwork found in file")]
     NoArtwork,
@@ -90,6 +90,16 @@ impl ArtworkExtractor {
             return Err(ArtworkError::FileNotFound(path.to_path_buf()));
         }
 
+        // Formats soul-audio decodes itself (WavPack, Monkey's Audio,
+        // Musepack) are read through its registry, like the importer does
+        let registry = soul_audio::formats::registry();
+        if registry
+            .find_by_extension(path)
+            .is_some_and(|format| format.name != soul_audio::formats::SYMPHONIA)
+        {
+            return Self::extract_with_registry(path);
+        }
+
         // Read the file with lofty
         let tagged_file = lofty::read_from_path(path)?;
 
@@ -118,8 +128,32 @@ impl ArtworkExtractor {
             return Ok(None);
         };
 
-        // Check size limit
-        let data = picture.data();
+        // Get MIME type (default to "image/jpeg" if not specified)
+        let mime_type = picture
+            .mime_type()
+            .map(|m| m.as_str().to_string())
+            .unwrap_or_else(|| "image/jpeg".to_string());
+
+        Self::checked_artwork(path, picture.data(), mime_type).map(Some)
+    }
+
+    /// Extract artwork through the soul-audio decoder registry
+    fn extract_with_registry(path: &Path) -> Result<Option<ArtworkData>> {
+        let metadata = soul_audio::formats::registry().extract_metadata(path)?;
+        let Some(art) = metadata.primary_album_art() else {
+            return Ok(None);
+        };
+
+        let mime_type = if art.mime_type.is_empty() {
+            "image/jpeg".to_string()
+        } else {
+            art.mime_type.clone()
+        };
+        Self::checked_artwork(path, &art.data, mime_type).map(Some)
+    }
+
+    /// Artwork data, if within the size limit
+    fn checked_artwork(path: &Path, data: &[u8], mime_type: String) -> Result<ArtworkData> {
         if data.len() > MAX_ARTWORK_SIZE {
             eprintln!(
                 "Warning: Artwork in {} is too large ({} bytes, max {} bytes), skipping",
@@ -130,13 +164,7 @@ impl ArtworkExtractor {
             return Err(ArtworkError::TooLarge(data.len(), MAX_ARTWORK_SIZE));
         }
 
-        // Get MIME type (default to "image/jpeg" if not specified)
-        let mime_type = picture
-            .mime_type()
-            .map(|m| m.as_str().to_string())
-            .unwrap_or_else(|| "image/jpeg".to_string());
-
-        Ok(Some(ArtworkData::new(data.to_vec(), mime_type)))
+        Ok(ArtworkData::new(data.to_vec(), mime_type))
     }
 }
 
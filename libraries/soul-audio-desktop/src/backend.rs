@@ -1,7 +1,7 @@
 // soul-audio-desktop/src/backend.rs
 //
 // Audio backend selection and management for multi-driver support
-// (WASAPI, ASIO, JACK, CoreAudio, ALSA)
+// (WASAPI, ASIO, JACK, CoreAudio, ALSA, plus virtual devices)
 
 use cpal::traits::HostTrait;
 use serde::{Deserialize, Serialize};
@@ -21,6 +21,13 @@ pub enum AudioBackend {
     /// JACK Audio Connection Kit - Professional routing, low-latency
     #[cfg(feature = "jack")]
     Jack,
+
+    /// Virtual devices (null sink, WAV file) - no sound hardware needed
+    ///
+    /// For headless rendering and tests; see `crate::virtual_output`. Not
+    /// listed by `list_available_backends`, since users don't pick it as a
+    /// playback backend.
+    Virtual,
 }
 
 impl AudioBackend {
@@ -50,6 +57,8 @@ impl AudioBackend {
 
             #[cfg(feature = "jack")]
             Self::Jack => "JACK",
+
+            Self::Virtual => "Virtual",
         }
     }
 
@@ -79,10 +88,14 @@ impl AudioBackend {
 
             #[cfg(feature = "jack")]
             Self::Jack => "Professional audio routing (cross-application, low-latency)",
+
+            Self::Virtual => "Null sink or WAV file recording (no sound hardware)",
         }
     }
 
     /// Convert backend to CPAL host
+    ///
+    /// Fails for `Virtual`, whose devices are not CPAL devices.
     pub fn to_cpal_host(&self) -> Result<cpal::Host, BackendError> {
         match self {
             Self::Default => Ok(cpal::default_host()),
@@ -102,12 +115,14 @@ impl AudioBackend {
                 cpal::host_from_id(host_id)
                     .map_err(|_| BackendError::BackendUnavailable(self.name()))
             }
+
+            Self::Virtual => Err(BackendError::NoCpalHost(self.name())),
         }
     }
 
     /// Check if backend is available on current system
     pub fn is_available(&self) -> bool {
-        self.to_cpal_host().is_ok()
+        *self == Self::Virtual || self.to_cpal_host().is_ok()
     }
 }
 
@@ -190,6 +205,7 @@ pub fn get_backend_info() -> Vec<BackendInfo> {
                     AudioBackend::Asio => 1, // Indicate at least one device exists
                     #[cfg(feature = "jack")]
                     AudioBackend::Jack => 1, // Indicate at least one device exists
+                    AudioBackend::Virtual => crate::virtual_output::list_devices().len(),
                 }
             } else {
                 0
@@ -218,6 +234,10 @@ pub enum BackendError {
     #[error("No audio backends available")]
     NoBackendsAvailable,
 
+    /// Backend has no CPAL host (virtual devices)
+    #[error("Audio backend '{0}' is not a CPAL host")]
+    NoCpalHost(&'static str),
+
     /// CPAL error
     #[error("CPAL error: {0}")]
     CpalError(String),
@@ -299,6 +319,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_virtual_backend() {
+        let backend = AudioBackend::Virtual;
+        assert_eq!(backend.name(), "Virtual");
+        assert!(backend.is_available());
+        assert!(matches!(
+            backend.to_cpal_host(),
+            Err(BackendError::NoCpalHost("Virtual"))
+        ));
+        assert!(!list_available_backends().contains(&backend));
+    }
+
     #[cfg(all(target_os = "windows", feature = "asio"))]
     #[test]
     fn test_asio_backend() {
@@ -279,6 +279,12 @@ pub fn list_devices_with_capabilities(
     backend: AudioBackend,
     include_capabilities: bool,
 ) -> Result<Vec<AudioDeviceInfo>, DeviceError> {
+    if backend == AudioBackend::Virtual {
+        return Ok(crate::virtual_output::list_device_infos(
+            include_capabilities,
+        ));
+    }
+
     let host = backend
         .to_cpal_host()
         .map_err(|_| DeviceError::BackendUnavailable(backend.name()))?;
@@ -355,11 +361,34 @@ pub fn get_default_device(backend: AudioBackend) -> Result<AudioDeviceInfo, Devi
     get_default_device_with_capabilities(backend, false)
 }
 
+/// Get the name of the default output device for a backend
+///
+/// Cheaper than [`get_default_device`] (no config queries), for polling
+/// whether the system default has changed.
+pub fn get_default_device_name(backend: AudioBackend) -> Result<String, DeviceError> {
+    if backend == AudioBackend::Virtual {
+        return Ok(crate::virtual_output::default_device().name);
+    }
+
+    let host = backend
+        .to_cpal_host()
+        .map_err(|_| DeviceError::BackendUnavailable(backend.name()))?;
+
+    host.default_output_device()
+        .ok_or(DeviceError::NoDeviceFound)?
+        .name()
+        .map_err(|e| DeviceError::DeviceInfoFailed(e.to_string()))
+}
+
 /// Get information about the default output device with optional capability detection
 pub fn get_default_device_with_capabilities(
     backend: AudioBackend,
     include_capabilities: bool,
 ) -> Result<AudioDeviceInfo, DeviceError> {
+    if backend == AudioBackend::Virtual {
+        return Ok(crate::virtual_output::default_device().info(true, include_capabilities));
+    }
+
     let host = backend
         .to_cpal_host()
         .map_err(|_| DeviceError::BackendUnavailable(backend.name()))?;
@@ -410,6 +439,10 @@ pub fn get_device_capabilities(
     backend: AudioBackend,
     device_name: &str,
 ) -> Result<DeviceCapabilities, DeviceError> {
+    if backend == AudioBackend::Virtual {
+        return Ok(crate::virtual_output::find_device(device_name)?.capabilities());
+    }
+
     let device = find_device_by_name(backend, device_name)?;
     Ok(detect_device_capabilities(&device, backend))
 }
@@ -439,6 +472,81 @@ pub fn find_device_by_name(
     Err(DeviceError::DeviceNotFound(device_name.to_string()))
 }
 
+/// Enumerate the input (recording) devices for a backend
+///
+/// Used for measurement microphones; `channels` and the sample rates
+/// describe the device's input side. Capabilities are not queried.
+pub fn list_input_devices(backend: AudioBackend) -> Result<Vec<AudioDeviceInfo>, DeviceError> {
+    let host = backend
+        .to_cpal_host()
+        .map_err(|_| DeviceError::BackendUnavailable(backend.name()))?;
+
+    let default_name = host.default_input_device().and_then(|d| d.name().ok());
+
+    let devices = host
+        .input_devices()
+        .map_err(|e| DeviceError::EnumerationFailed(e.to_string()))?;
+
+    let mut device_list: Vec<AudioDeviceInfo> = devices
+        .filter_map(|device| {
+            let name = device.name().ok()?;
+            let config = device.default_input_config().ok()?;
+            let sample_rate_range =
+                device
+                    .supported_input_configs()
+                    .ok()
+                    .and_then(|mut configs| {
+                        configs
+                            .next()
+                            .map(|config| (config.min_sample_rate(), config.max_sample_rate()))
+                    });
+
+            Some(AudioDeviceInfo {
+                is_default: Some(&name) == default_name.as_ref(),
+                name,
+                backend,
+                sample_rate: config.sample_rate(),
+                channels: config.channels(),
+                sample_rate_range,
+                capabilities: None,
+            })
+        })
+        .collect();
+
+    // Sort: default first, then alphabetically
+    device_list.sort_by(|a, b| match (a.is_default, b.is_default) {
+        (true, false) => std::cmp::Ordering::Less,
+        (false, true) => std::cmp::Ordering::Greater,
+        _ => a.name.cmp(&b.name),
+    });
+
+    Ok(device_list)
+}
+
+/// Find an input (recording) device by name within a backend
+pub fn find_input_device_by_name(
+    backend: AudioBackend,
+    device_name: &str,
+) -> Result<cpal::Device, DeviceError> {
+    let host = backend
+        .to_cpal_host()
+        .map_err(|_| DeviceError::BackendUnavailable(backend.name()))?;
+
+    let devices = host
+        .input_devices()
+        .map_err(|e| DeviceError::EnumerationFailed(e.to_string()))?;
+
+    for device in devices {
+        if let Ok(name) = device.name() {
+            if name == device_name {
+                return Ok(device);
+            }
+        }
+    }
+
+    Err(DeviceError::DeviceNotFound(device_name.to_string()))
+}
+
 /// Device-related errors
 #[derive(Debug, Error)]
 pub enum DeviceError {
@@ -454,6 +562,10 @@ pub enum DeviceError {
     #[error("No audio output devices found")]
     NoDeviceFound,
 
+    /// No input devices found
+    #[error("No audio input devices found")]
+    NoInputDeviceFound,
+
     /// Device with specified name not found
     #[error("Audio device '{0}' not found")]
     DeviceNotFound(String),
@@ -461,6 +573,10 @@ pub enum DeviceError {
     /// Failed to get device information
     #[error("Failed to get device information: {0}")]
     DeviceInfoFailed(String),
+
+    /// Device configuration is not usable
+    #[error("Invalid device configuration: {0}")]
+    InvalidConfig(String),
 }
 
 impl From<BackendError> for DeviceError {
@@ -707,6 +823,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_virtual_backend_devices() {
+        let backend = AudioBackend::Virtual;
+        let devices = list_devices(backend).expect("Virtual devices are always listed");
+        assert!(devices
+            .iter()
+            .any(|d| d.name == crate::virtual_output::NULL_DEVICE_NAME));
+        assert!(devices[0].is_default, "Default device should be first");
+        assert_eq!(devices.iter().filter(|d| d.is_default).count(), 1);
+
+        let caps = get_device_capabilities(backend, crate::virtual_output::NULL_DEVICE_NAME)
+            .expect("Null device should have capabilities");
+        assert_eq!(caps.bit_depths, vec![SupportedBitDepth::Float32]);
+
+        assert!(matches!(
+            get_device_capabilities(backend, "No Such Virtual Device"),
+            Err(DeviceError::DeviceNotFound(_))
+        ));
+    }
+
     #[test]
     fn test_standard_sample_rates() {
         // Verify standard sample rates are defined correctly
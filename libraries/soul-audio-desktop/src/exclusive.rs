@@ -21,6 +21,8 @@ fn cpal_sample_rate_to_u32(sr: cpal::SampleRate) -> u32 {
     sr
 }
 use serde::{Deserialize, Serialize};
+use soul_audio::channels::{ChannelLayout, ChannelMatrix, DownmixSettings};
+use soul_audio::dither::{DitherMode, StereoDither};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
@@ -205,6 +207,39 @@ impl AudioData {
         }
     }
 
+    /// Create from interleaved stereo f32 samples, dithering integer targets
+    ///
+    /// Same layout as [`AudioData::from_f32`] (24-bit values right-aligned in
+    /// `Int32`), but quantization goes through `dither` so its mode (none,
+    /// TPDF or noise-shaped) applies.
+    pub fn from_f32_with_dither(
+        samples: &[f32],
+        target: SupportedBitDepth,
+        dither: &mut StereoDither,
+    ) -> Self {
+        match target {
+            SupportedBitDepth::Int16 => {
+                let mut converted = vec![0i16; samples.len()];
+                dither.process_stereo_to_i16(samples, &mut converted);
+                Self::Int16(converted)
+            }
+            SupportedBitDepth::Int24 | SupportedBitDepth::Int32 => {
+                let mut converted = vec![0i32; samples.len()];
+                dither.process_stereo_to_i32(samples, &mut converted);
+                if target == SupportedBitDepth::Int24 {
+                    // Round the 24-bit dithered value down into the low 24 bits
+                    for sample in &mut converted {
+                        *sample = ((*sample as i64 + 128) >> 8).min(8_388_607) as i32;
+                    }
+                }
+                Self::Int32(converted)
+            }
+            SupportedBitDepth::Float32 | SupportedBitDepth::Float64 => {
+                Self::Float32(samples.to_vec())
+            }
+        }
+    }
+
     /// Get length in samples
     pub fn len(&self) -> usize {
         match self {
@@ -260,12 +295,18 @@ pub struct ExclusiveOutput {
     config: ExclusiveConfig,
     /// Actual sample rate being used
     sample_rate: u32,
+    /// Channel count of the stream
+    channels: u16,
     /// Shared state
     state: Arc<ExclusiveState>,
     /// Audio thread handle
     _audio_thread: Option<JoinHandle<()>>,
     /// Latency information
     latency: LatencyInfo,
+    /// Dither used by `play_f32` for integer formats
+    dither: Mutex<StereoDither>,
+    /// Coefficients used by `play_f32_with_channels` to fold down channels
+    downmix: Mutex<DownmixSettings>,
 }
 
 impl ExclusiveOutput {
@@ -290,6 +331,7 @@ impl ExclusiveOutput {
         let (stream_config, sample_format, buffer_size) = Self::find_best_config(&device, &config)?;
 
         let sample_rate = stream_config.sample_rate;
+        let channels = stream_config.channels;
 
         // Calculate latency
         let buffer_samples = match buffer_size {
@@ -324,9 +366,15 @@ impl ExclusiveOutput {
             command_tx,
             config,
             sample_rate,
+            channels,
             state,
             _audio_thread: Some(audio_thread),
             latency,
+            dither: Mutex::new(StereoDither::with_mode(
+                DitherMode::default().for_channels(channels),
+                sample_rate,
+            )),
+            downmix: Mutex::new(DownmixSettings::default()),
         })
     }
 
@@ -776,11 +824,64 @@ impl ExclusiveOutput {
     }
 
     /// Play f32 samples with automatic conversion to target format
+    ///
+    /// Samples are interleaved stereo. Integer formats are dithered using the
+    /// current dither mode.
     pub fn play_f32(&self, samples: &[f32]) -> Result<()> {
-        let data = AudioData::from_f32(samples, self.config.bit_depth);
+        self.play_f32_with_channels(samples, 2)
+    }
+
+    /// Play interleaved f32 samples with `channels` channels
+    ///
+    /// Samples are remixed to the stream's channel count if they differ
+    /// (using the current downmix settings), then converted like `play_f32`.
+    pub fn play_f32_with_channels(&self, samples: &[f32], channels: u16) -> Result<()> {
+        let remixed;
+        let samples = if channels == self.channels {
+            samples
+        } else {
+            let matrix = ChannelMatrix::new(
+                ChannelLayout::from_channel_count(channels),
+                ChannelLayout::from_channel_count(self.channels),
+                &self.downmix.lock().unwrap(),
+            );
+            let frames = samples.len() / channels.max(1) as usize;
+            let mut output = vec![0.0; frames * self.channels as usize];
+            matrix.process(samples, &mut output);
+            remixed = output;
+            &remixed[..]
+        };
+
+        let data = {
+            let mut dither = self.dither.lock().unwrap();
+            AudioData::from_f32_with_dither(samples, self.config.bit_depth, &mut dither)
+        };
         self.play(data)
     }
 
+    /// Set the dither mode used when converting to integer formats
+    ///
+    /// Noise-shaped modes fall back to flat TPDF on non-stereo streams.
+    pub fn set_dither_mode(&self, mode: DitherMode) {
+        *self.dither.lock().unwrap() =
+            StereoDither::with_mode(mode.for_channels(self.channels), self.sample_rate);
+    }
+
+    /// Get the current dither mode
+    pub fn dither_mode(&self) -> DitherMode {
+        self.dither.lock().unwrap().mode()
+    }
+
+    /// Set the coefficients used to fold down channels in `play_f32_with_channels`
+    pub fn set_downmix_settings(&self, settings: DownmixSettings) {
+        *self.downmix.lock().unwrap() = settings;
+    }
+
+    /// Get the current downmix coefficients
+    pub fn downmix_settings(&self) -> DownmixSettings {
+        *self.downmix.lock().unwrap()
+    }
+
     /// Pause playback
     pub fn pause(&self) -> Result<()> {
         self.command_tx
@@ -827,6 +928,11 @@ impl ExclusiveOutput {
         self.sample_rate
     }
 
+    /// Get the channel count of the stream
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
     /// Get configuration
     pub fn config(&self) -> &ExclusiveConfig {
         &self.config
@@ -925,6 +1031,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_audio_data_dithered_conversion() {
+        use soul_audio::dither::NoiseShape;
+
+        let samples = vec![0.5f32; 2000];
+
+        // No dither matches plain rounding
+        let mut none = StereoDither::with_mode(DitherMode::None, 44100);
+        match AudioData::from_f32_with_dither(&samples, SupportedBitDepth::Int16, &mut none) {
+            AudioData::Int16(v) => assert!(v.iter().all(|&s| s == 16384)),
+            _ => panic!("Expected Int16"),
+        }
+
+        // Shaped dither stays within a few LSB of the input
+        let mut shaped = StereoDither::with_mode(DitherMode::Shaped(NoiseShape::Lipshitz), 44100);
+        match AudioData::from_f32_with_dither(&samples, SupportedBitDepth::Int16, &mut shaped) {
+            AudioData::Int16(v) => {
+                assert!(v.iter().all(|&s| (s as i32 - 16384).abs() < 64));
+                let mean = v.iter().map(|&s| s as f64).sum::<f64>() / v.len() as f64;
+                assert!((mean - 16383.5).abs() < 1.0);
+            }
+            _ => panic!("Expected Int16"),
+        }
+
+        // 24-bit output keeps the right-aligned layout of from_f32, with
+        // TPDF noise of ±1 LSB
+        let mut tpdf = StereoDither::new();
+        match AudioData::from_f32_with_dither(&samples, SupportedBitDepth::Int24, &mut tpdf) {
+            AudioData::Int32(v) => {
+                assert!(v.iter().all(|&s| (s - 4_194_304).abs() <= 2));
+            }
+            _ => panic!("Expected Int32"),
+        }
+    }
+
     #[test]
     fn test_latency_info() {
         let info = LatencyInfo {
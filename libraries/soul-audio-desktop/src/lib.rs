@@ -9,6 +9,8 @@
 //! - Automatic sample rate conversion
 //! - Volume control
 //! - Playback controls (play, pause, resume, stop)
+//! - Sweep measurement (play on an output, record from an input device)
+//! - Virtual output devices (null sink, WAV recording) for headless playback
 //!
 //! # Example
 //!
@@ -42,10 +44,12 @@ pub mod backend;
 pub mod device;
 mod error;
 pub mod exclusive;
+pub mod measurement;
 mod output;
 pub mod playback;
 pub mod sources;
 pub mod track_loader;
+pub mod virtual_output;
 
 pub use backend::{AudioBackend, BackendError, BackendInfo};
 pub use device::{
@@ -57,5 +61,9 @@ pub use error::{AudioError, AudioOutputError, Result};
 pub use exclusive::{AudioData, ExclusiveConfig, ExclusiveOutput, LatencyInfo};
 pub use output::{CpalOutput, ResamplingQuality};
 pub use playback::{DesktopPlayback, PlaybackCommand, PlaybackEvent, ResamplingSettings, SampleRateMode};
-pub use sources::{LocalAudioSource, StreamingAudioSource};
-pub use track_loader::{LoadRequest, LoadResult, TrackLoader};
+pub use sources::{
+    DecoderAudioSource, DsdAudioSource, DsdOutputMode, HttpMediaSource, LocalAudioSource,
+    StreamingAudioSource,
+};
+pub use track_loader::{LoadRequest, LoadResult, TrackDspResolver, TrackLoader};
+pub use virtual_output::{VirtualClock, VirtualDevice, VirtualSink, VirtualStream};
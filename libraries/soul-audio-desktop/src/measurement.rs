@@ -0,0 +1,389 @@
+//! Live sweep measurement
+//!
+//! Plays a [`LogSweep`] on an output device while recording a microphone
+//! from an input device, both through CPAL at the sweep's sample rate. The
+//! recording is deconvolved with [`LogSweep::deconvolve`] into an impulse
+//! response.
+//!
+//! The output and input should be the same interface (or share a word
+//! clock): the deconvolution tolerates any latency between them, but not
+//! drift between two independent clocks.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use soul_audio::measurement::{LogSweep, SweepSettings};
+//! use soul_audio_desktop::measurement::{record_sweep, MeasurementConfig};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let sweep = LogSweep::new(SweepSettings::default())?;
+//! let config = MeasurementConfig {
+//!     output_channels: vec![0], // left speaker
+//!     ..Default::default()
+//! };
+//!
+//! let recording = record_sweep(&config, &sweep)?;
+//! let ir = recording.deconvolve(&sweep, 48000)?;
+//! ir.write_wav("/tmp/left_speaker.wav")?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::backend::AudioBackend;
+use crate::device;
+use crate::error::{AudioError, Result};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, Sample, SizedSample};
+use soul_audio::effects::ImpulseResponse;
+use soul_audio::measurement::{self, LogSweep};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Extra time recorded after the sweep and tail, for output and input latency
+const LATENCY_MARGIN: Duration = Duration::from_millis(500);
+
+/// How long playback may overrun the sweep length before giving up
+const PLAYBACK_TIMEOUT_MARGIN: Duration = Duration::from_secs(5);
+
+/// Devices and channels to measure with
+#[derive(Debug, Clone)]
+pub struct MeasurementConfig {
+    /// Backend both devices belong to
+    pub backend: AudioBackend,
+    /// Output device (None = system default)
+    pub output_device: Option<String>,
+    /// Input device (None = system default)
+    pub input_device: Option<String>,
+    /// Output channels the sweep is played on, the rest stay silent
+    /// (empty = all channels)
+    pub output_channels: Vec<usize>,
+    /// Input channels to record, 1 or 2 (e.g. two microphones of a binaural head)
+    pub input_channels: Vec<usize>,
+}
+
+impl Default for MeasurementConfig {
+    fn default() -> Self {
+        Self {
+            backend: AudioBackend::Default,
+            output_device: None,
+            input_device: None,
+            output_channels: Vec::new(),
+            input_channels: vec![0],
+        }
+    }
+}
+
+/// A recorded sweep
+#[derive(Debug, Clone)]
+pub struct Recording {
+    /// Interleaved samples of the selected input channels
+    pub samples: Vec<f32>,
+    /// Number of channels (1 or 2)
+    pub channels: usize,
+    /// Sample rate (Hz)
+    pub sample_rate: u32,
+}
+
+impl Recording {
+    /// Deconvolve into an impulse response of `ir_frames` frames
+    pub fn deconvolve(
+        &self,
+        sweep: &LogSweep,
+        ir_frames: usize,
+    ) -> std::result::Result<ImpulseResponse, measurement::MeasurementError> {
+        sweep.deconvolve(&self.samples, self.channels, self.sample_rate, ir_frames)
+    }
+}
+
+/// Play a sweep and record it
+///
+/// Blocks for the length of the sweep and its tail, plus a margin for
+/// device latency.
+pub fn record_sweep(config: &MeasurementConfig, sweep: &LogSweep) -> Result<Recording> {
+    if !matches!(config.input_channels.len(), 1 | 2) {
+        return Err(AudioError::DeviceError(format!(
+            "Record 1 or 2 input channels, not {}",
+            config.input_channels.len()
+        )));
+    }
+
+    let sample_rate = sweep.settings().sample_rate;
+    let host = config
+        .backend
+        .to_cpal_host()
+        .map_err(|e| AudioError::DeviceError(e.to_string()))?;
+
+    let output = match &config.output_device {
+        Some(name) => device::find_device_by_name(config.backend, name)
+            .map_err(|e| AudioError::DeviceError(e.to_string()))?,
+        None => host
+            .default_output_device()
+            .ok_or(AudioError::DeviceNotFound)?,
+    };
+    let input = match &config.input_device {
+        Some(name) => device::find_input_device_by_name(config.backend, name)
+            .map_err(|e| AudioError::DeviceError(e.to_string()))?,
+        None => host
+            .default_input_device()
+            .ok_or(AudioError::DeviceNotFound)?,
+    };
+
+    let output_config = pick_config(
+        output
+            .supported_output_configs()
+            .map_err(|e| AudioError::CpalError(e.to_string()))?,
+        sample_rate,
+        config.output_channels.iter().max().map_or(1, |&c| c + 1),
+    )
+    .ok_or_else(|| {
+        AudioError::UnsupportedFormat(format!(
+            "Output device can't play {} Hz on the requested channels",
+            sample_rate
+        ))
+    })?;
+    let input_config = pick_config(
+        input
+            .supported_input_configs()
+            .map_err(|e| AudioError::CpalError(e.to_string()))?,
+        sample_rate,
+        config.input_channels.iter().max().map_or(1, |&c| c + 1),
+    )
+    .ok_or_else(|| {
+        AudioError::UnsupportedFormat(format!(
+            "Input device can't record {} Hz on the requested channels",
+            sample_rate
+        ))
+    })?;
+
+    eprintln!(
+        "[Measurement] Output: {} ch {:?}, input: {} ch {:?}, {} Hz",
+        output_config.channels(),
+        output_config.sample_format(),
+        input_config.channels(),
+        input_config.sample_format(),
+        sample_rate
+    );
+
+    let signal = sweep.playback_signal();
+    let sweep_duration = Duration::from_secs_f64(signal.len() as f64 / sample_rate as f64);
+    let record_frames = signal.len()
+        + (LATENCY_MARGIN.as_secs_f64() * sample_rate as f64).ceil() as usize
+        + sample_rate as usize / 10;
+
+    // Allocated up front so the input callback never allocates
+    let recorded = Arc::new(Mutex::new(Vec::with_capacity(
+        record_frames * config.input_channels.len(),
+    )));
+    let finished = Arc::new(AtomicBool::new(false));
+
+    let input_stream = match input_config.sample_format() {
+        cpal::SampleFormat::F32 => build_input::<f32>(
+            &input,
+            &input_config.config(),
+            &config.input_channels,
+            record_frames,
+            Arc::clone(&recorded),
+        ),
+        cpal::SampleFormat::I32 => build_input::<i32>(
+            &input,
+            &input_config.config(),
+            &config.input_channels,
+            record_frames,
+            Arc::clone(&recorded),
+        ),
+        cpal::SampleFormat::I16 => build_input::<i16>(
+            &input,
+            &input_config.config(),
+            &config.input_channels,
+            record_frames,
+            Arc::clone(&recorded),
+        ),
+        format => Err(AudioError::UnsupportedFormat(format!("{:?}", format))),
+    }?;
+
+    let output_stream = match output_config.sample_format() {
+        cpal::SampleFormat::F32 => build_output::<f32>(
+            &output,
+            &output_config.config(),
+            &config.output_channels,
+            signal,
+            Arc::clone(&finished),
+        ),
+        cpal::SampleFormat::I32 => build_output::<i32>(
+            &output,
+            &output_config.config(),
+            &config.output_channels,
+            signal,
+            Arc::clone(&finished),
+        ),
+        cpal::SampleFormat::I16 => build_output::<i16>(
+            &output,
+            &output_config.config(),
+            &config.output_channels,
+            signal,
+            Arc::clone(&finished),
+        ),
+        format => Err(AudioError::UnsupportedFormat(format!("{:?}", format))),
+    }?;
+
+    // Record from before the sweep starts
+    input_stream.play()?;
+    output_stream.play()?;
+
+    let deadline = Instant::now() + sweep_duration + PLAYBACK_TIMEOUT_MARGIN;
+    while !finished.load(Ordering::Acquire) {
+        if Instant::now() > deadline {
+            return Err(AudioError::PlaybackError(
+                "Output stream stopped before the sweep finished".to_string(),
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    std::thread::sleep(LATENCY_MARGIN);
+
+    drop(output_stream);
+    drop(input_stream);
+
+    let samples = std::mem::take(&mut *recorded.lock().unwrap());
+    eprintln!(
+        "[Measurement] Recorded {} frames",
+        samples.len() / config.input_channels.len()
+    );
+
+    Ok(Recording {
+        samples,
+        channels: config.input_channels.len(),
+        sample_rate,
+    })
+}
+
+/// A config at the sample rate with enough channels, preferring f32 > i32 > i16
+fn pick_config(
+    configs: impl Iterator<Item = cpal::SupportedStreamConfigRange>,
+    sample_rate: u32,
+    min_channels: usize,
+) -> Option<cpal::SupportedStreamConfig> {
+    configs
+        .filter(|c| usize::from(c.channels()) >= min_channels)
+        .filter_map(|c| c.try_with_sample_rate(sample_rate))
+        .max_by_key(|c| match c.sample_format() {
+            cpal::SampleFormat::F32 => 3,
+            cpal::SampleFormat::I32 => 2,
+            cpal::SampleFormat::I16 => 1,
+            _ => 0,
+        })
+}
+
+/// Record the selected channels until `max_frames` are captured
+fn build_input<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    channels: &[usize],
+    max_frames: usize,
+    recorded: Arc<Mutex<Vec<f32>>>,
+) -> Result<cpal::Stream>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let device_channels = usize::from(config.channels);
+    let channels = channels.to_vec();
+    let max_samples = max_frames * channels.len();
+
+    Ok(device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            // Only contended after the stream is dropped
+            let Ok(mut recorded) = recorded.try_lock() else {
+                return;
+            };
+            for frame in data.chunks_exact(device_channels) {
+                if recorded.len() >= max_samples {
+                    return;
+                }
+                recorded.extend(channels.iter().map(|&c| frame[c].to_sample::<f32>()));
+            }
+        },
+        |err| eprintln!("[Measurement] Input stream error: {}", err),
+        None,
+    )?)
+}
+
+/// Play a mono signal on the selected channels, then silence
+fn build_output<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    channels: &[usize],
+    signal: Vec<f32>,
+    finished: Arc<AtomicBool>,
+) -> Result<cpal::Stream>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    let device_channels = usize::from(config.channels);
+    let mut enabled = vec![channels.is_empty(); device_channels];
+    for &c in channels {
+        enabled[c] = true;
+    }
+    let mut position = 0;
+
+    Ok(device.build_output_stream(
+        config,
+        move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+            for frame in data.chunks_exact_mut(device_channels) {
+                let sample = signal.get(position).copied().unwrap_or(0.0);
+                position += 1;
+                for (out, &on) in frame.iter_mut().zip(&enabled) {
+                    *out = T::from_sample(if on { sample } else { 0.0 });
+                }
+            }
+            if position >= signal.len() {
+                finished.store(true, Ordering::Release);
+            }
+        },
+        |err| eprintln!("[Measurement] Output stream error: {}", err),
+        None,
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejects_input_channel_count() {
+        let sweep = LogSweep::new(Default::default()).unwrap();
+        let config = MeasurementConfig {
+            input_channels: vec![0, 1, 2],
+            ..Default::default()
+        };
+        assert!(matches!(
+            record_sweep(&config, &sweep),
+            Err(AudioError::DeviceError(_))
+        ));
+    }
+
+    #[test]
+    fn test_recording_deconvolves() {
+        let sweep = LogSweep::new(soul_audio::measurement::SweepSettings {
+            duration_secs: 1.0,
+            tail_secs: 0.2,
+            ..Default::default()
+        })
+        .unwrap();
+
+        // A loopback cable with 100 frames of latency
+        let mut samples = vec![0.0; 100];
+        samples.extend(sweep.playback_signal());
+        let recording = Recording {
+            samples,
+            channels: 1,
+            sample_rate: 48000,
+        };
+
+        let ir = recording.deconvolve(&sweep, 1024).unwrap();
+        assert_eq!(ir.frames(), 1024);
+        assert!(ir.samples[96] > 0.7);
+    }
+}
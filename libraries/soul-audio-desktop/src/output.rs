@@ -3,6 +3,7 @@ use crate::error::{AudioOutputError, Result};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{Device, Stream, StreamConfig};
 use crossbeam_channel::{bounded, Receiver, Sender};
+use soul_audio::channels::{ChannelLayout, ChannelMatrix, DownmixSettings};
 use soul_core::{AudioBuffer, AudioOutput};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
@@ -123,6 +124,10 @@ pub struct CpalOutput {
     command_tx: Sender<AudioCommand>,
     /// Sample rate of the output device
     sample_rate: u32,
+    /// Channel count of the output stream
+    channels: u16,
+    /// Coefficients for folding buffers down to fewer channels
+    downmix: DownmixSettings,
     /// Shared state for volume tracking
     state: Arc<AudioState>,
     /// Handle to the audio thread (optional, for joining on drop)
@@ -161,6 +166,7 @@ impl CpalOutput {
     ) -> Result<Self> {
         let state = Arc::new(AudioState::new());
         let (command_tx, command_rx) = bounded::<AudioCommand>(32);
+        let channels = config.channels;
 
         // Spawn audio thread
         let state_clone = Arc::clone(&state);
@@ -171,6 +177,8 @@ impl CpalOutput {
         Ok(Self {
             command_tx,
             sample_rate,
+            channels,
+            downmix: DownmixSettings::default(),
             state,
             _audio_thread: Some(audio_thread),
             resampling_quality: ResamplingQuality::default(),
@@ -190,6 +198,33 @@ impl CpalOutput {
         self.resampling_quality
     }
 
+    /// Get the channel count of the output stream
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// Set the coefficients used when a buffer has more channels than the device
+    pub fn set_downmix_settings(&mut self, settings: DownmixSettings) {
+        self.downmix = settings;
+    }
+
+    /// Get the current downmix coefficients
+    pub fn downmix_settings(&self) -> DownmixSettings {
+        self.downmix
+    }
+
+    /// Remix interleaved samples to the output channel count
+    fn remix(samples: &[f32], from: u16, to: u16, settings: &DownmixSettings) -> Vec<f32> {
+        let matrix = ChannelMatrix::new(
+            ChannelLayout::from_channel_count(from),
+            ChannelLayout::from_channel_count(to),
+            settings,
+        );
+        let mut output = vec![0.0; samples.len() / from.max(1) as usize * to as usize];
+        matrix.process(samples, &mut output);
+        output
+    }
+
     /// Audio thread main loop
     ///
     /// This function runs in a dedicated thread and owns the CPAL Stream.
@@ -410,6 +445,18 @@ impl AudioOutput for CpalOutput {
             Self::resample_buffer(buffer, self.sample_rate, self.resampling_quality)?
         };
 
+        // Remix if the buffer's layout doesn't match the device (e.g. 5.1 on stereo)
+        let samples = if buffer.format.channels == self.channels {
+            samples
+        } else {
+            Self::remix(
+                &samples,
+                buffer.format.channels,
+                self.channels,
+                &self.downmix,
+            )
+        };
+
         // Send play command to audio thread
         self.command_tx
             .send(AudioCommand::Play {
@@ -494,6 +541,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn remix_surround_to_stereo() {
+        // One 5.1 frame: L R C LFE Ls Rs
+        let frame = [0.5, 0.0, 0.5, 1.0, 0.0, 0.0];
+        let stereo = CpalOutput::remix(&frame, 6, 2, &DownmixSettings::default());
+        assert_eq!(stereo.len(), 2);
+        assert!(stereo[0] > stereo[1], "Left should carry the front left signal");
+        assert!(stereo[1] > 0.0, "Center should reach the right channel");
+    }
+
     #[test]
     fn volume_control() {
         let Ok(mut output) = CpalOutput::new() else {
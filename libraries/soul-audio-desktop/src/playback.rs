@@ -1,17 +1,23 @@
 //! Desktop playback integration
 //!
 //! Combines `PlaybackManager` with CPAL audio output for desktop playback.
+//! Devices of the virtual backend (null sink, WAV file) run the same audio
+//! callbacks on a render thread instead of a CPAL stream.
 
 use cpal::{
     traits::{DeviceTrait, HostTrait, StreamTrait},
     Device, Stream, StreamConfig,
 };
 use crossbeam_channel::{bounded, Receiver, Sender};
-use soul_playback::{PlaybackConfig, PlaybackManager, QueueTrack};
+use soul_playback::{PlaybackConfig, PlaybackManager, QueueTrack, TrackDsp};
 use std::sync::{Arc, Mutex};
 
+use crate::device::SupportedBitDepth;
 use crate::error::Result;
-use std::sync::atomic::{AtomicU64, Ordering};
+use soul_audio::analysis::{AnalysisReader, AudioAnalysis};
+use soul_audio::channels::DownmixSettings;
+use soul_audio::dither::{DitherMode, StereoDither};
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
 
 /// Global counter for I32 (ASIO) callbacks - used for diagnostics
 /// This is updated by audio_callback_i32 and read by send_command for debugging
@@ -166,6 +172,25 @@ impl Drop for CallbackDropGuard {
     }
 }
 
+/// An open output: a CPAL stream or a virtual device's render thread
+enum OutputStream {
+    Cpal(Stream),
+    Virtual(crate::virtual_output::VirtualStream),
+}
+
+impl OutputStream {
+    /// Stop calling the audio callback
+    fn pause(&self) -> Result<()> {
+        match self {
+            Self::Cpal(stream) => Ok(stream.pause()?),
+            Self::Virtual(stream) => {
+                stream.pause();
+                Ok(())
+            }
+        }
+    }
+}
+
 /// Commands sent to playback thread
 #[derive(Debug, Clone)]
 pub enum PlaybackCommand {
@@ -243,6 +268,14 @@ pub enum PlaybackEvent {
     /// Device sample rate changed (old_rate, new_rate)
     SampleRateChanged(u32, u32),
 
+    /// Output device changed (manual switch or system default device change)
+    DeviceChanged {
+        /// Backend of the new device
+        backend: crate::AudioBackend,
+        /// Name of the new device
+        device_name: String,
+    },
+
     /// Crossfade started between two tracks
     CrossfadeStarted {
         /// ID of the outgoing track
@@ -429,8 +462,8 @@ pub struct DesktopPlayback {
     /// Event sender (for creating new streams)
     event_tx: Sender<PlaybackEvent>,
 
-    /// CPAL audio stream
-    stream: Arc<Mutex<Option<Stream>>>,
+    /// Output stream (CPAL or virtual device)
+    stream: Arc<Mutex<Option<OutputStream>>>,
 
     /// Playback manager (shared with audio thread)
     manager: Arc<Mutex<PlaybackManager>>,
@@ -441,6 +474,10 @@ pub struct DesktopPlayback {
     /// Current device name
     current_device: Arc<Mutex<String>>,
 
+    /// Whether the output follows the system default device
+    /// (set when the device was opened without an explicit name)
+    follow_default_device: bool,
+
     /// Current stream sample rate (what we're actually outputting at)
     current_sample_rate: Arc<std::sync::atomic::AtomicU32>,
 
@@ -449,6 +486,14 @@ pub struct DesktopPlayback {
 
     /// Background track loader (keeps disk I/O off audio thread)
     track_loader: Arc<crate::track_loader::TrackLoader>,
+
+    /// Dither mode for integer output, as an index into `DitherMode::ALL`
+    /// (atomic so the audio callback can read it without locking)
+    dither_mode: Arc<AtomicU8>,
+
+    /// Reader for the level/spectrum analysis tap (None when disabled)
+    /// Kept outside the manager so polling never contends with the audio thread
+    analysis_reader: Mutex<Option<AnalysisReader>>,
 }
 
 // SAFETY: DesktopPlayback is safe to send between threads because:
@@ -456,6 +501,7 @@ pub struct DesktopPlayback {
 // - manager is Arc<Mutex<>>, which is Send + Sync
 // - _stream is CPAL's Stream, which internally uses thread-safe primitives
 //   (the PhantomData<*mut ()> is just a marker, not actually unsafe)
+//   Virtual device streams are Send + Sync on their own
 #[allow(unsafe_code)]
 unsafe impl Send for DesktopPlayback {}
 
@@ -490,6 +536,7 @@ impl DesktopPlayback {
         backend: crate::AudioBackend,
         device_name: Option<String>,
     ) -> Result<Self> {
+        let follow_default_device = device_name.is_none();
         let manager = Arc::new(Mutex::new(PlaybackManager::new(config)));
 
         let (command_tx, command_rx) = bounded(32);
@@ -497,8 +544,11 @@ impl DesktopPlayback {
 
         // Create background track loader FIRST - keeps disk I/O off audio thread
         let track_loader = Arc::new(crate::track_loader::TrackLoader::new());
+        let dither_mode = Arc::new(AtomicU8::new(
+            Self::dither_mode_index(DitherMode::default()),
+        ));
 
-        // Create CPAL stream with specified device (passes track_loader to callbacks)
+        // Create output stream with specified device (passes track_loader to callbacks)
         let (stream, actual_device_name, sample_rate) = Self::create_audio_stream(
             manager.clone(),
             command_rx.clone(),
@@ -506,6 +556,7 @@ impl DesktopPlayback {
             backend,
             device_name,
             track_loader.clone(),
+            dither_mode.clone(),
         )?;
 
         let stream = Arc::new(Mutex::new(Some(stream)));
@@ -522,15 +573,21 @@ impl DesktopPlayback {
             manager,
             current_backend,
             current_device,
+            follow_default_device,
             current_sample_rate,
             resampling_settings,
             track_loader,
+            dither_mode,
+            analysis_reader: Mutex::new(None),
         })
     }
 
-    /// Create CPAL audio stream
+    /// Create the output stream for a device
     ///
-    /// Returns (Stream, device_name, sample_rate)
+    /// Virtual devices get a render thread running the same callbacks as a
+    /// CPAL stream of their format.
+    ///
+    /// Returns (OutputStream, device_name, sample_rate)
     fn create_audio_stream(
         manager: Arc<Mutex<PlaybackManager>>,
         command_rx: Receiver<PlaybackCommand>,
@@ -538,7 +595,19 @@ impl DesktopPlayback {
         backend: crate::AudioBackend,
         device_name: Option<String>,
         track_loader: Arc<crate::track_loader::TrackLoader>,
-    ) -> Result<(Stream, String, u32)> {
+        dither_mode: Arc<AtomicU8>,
+    ) -> Result<(OutputStream, String, u32)> {
+        if backend == crate::AudioBackend::Virtual {
+            return Self::create_virtual_stream(
+                manager,
+                command_rx,
+                event_tx,
+                device_name,
+                track_loader,
+                dither_mode,
+            );
+        }
+
         let host = backend
             .to_cpal_host()
             .map_err(|_| crate::error::AudioError::DeviceNotFound)?;
@@ -561,12 +630,19 @@ impl DesktopPlayback {
         let sample_rate = config.sample_rate;
         let channels = config.channels;
 
-        // Set sample rate and channel count in manager
-        {
-            let mut mgr = manager.lock().unwrap();
-            mgr.set_sample_rate(sample_rate);
-            mgr.set_output_channels(channels);
-        }
+        // DoP needs a stereo integer/float stream the DAC can lock onto;
+        // 16-bit output can't carry the 24-bit DoP words
+        let capabilities = crate::device::detect_device_capabilities(&device, backend);
+        let dsd_passthrough = capabilities.supports_dsd
+            && channels == 2
+            && sample_format != cpal::SampleFormat::I16;
+        Self::apply_output_format(
+            &manager,
+            &track_loader,
+            sample_rate,
+            channels,
+            dsd_passthrough,
+        );
 
         eprintln!("[CPAL] Building output stream with config: sample_rate={}, channels={}, buffer_size={:?}, format={:?}",
             config.sample_rate, config.channels, config.buffer_size, sample_format);
@@ -574,85 +650,37 @@ impl DesktopPlayback {
         // Build stream with the appropriate sample format
         let stream = match sample_format {
             cpal::SampleFormat::F32 => {
-                let manager_clone = manager.clone();
-                let track_loader_clone = track_loader.clone();
-                // Per-stream callback counter for logging
-                let mut callback_count: u32 = 0;
-                let stream_id = std::time::Instant::now();
-                // Create stream start envelope for click-free startup (30ms fade)
-                let mut stream_envelope = StreamStartEnvelope::new(sample_rate, channels);
-                eprintln!(
-                    "[CPAL] Creating F32 stream callback (stream_id: {:?})",
-                    stream_id
+                let mut callback = Self::f32_stream_callback(
+                    manager,
+                    command_rx,
+                    event_tx,
+                    track_loader,
+                    sample_rate,
+                    channels,
                 );
                 device.build_output_stream(
                     &config,
-                    move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
-                        callback_count += 1;
-                        Self::audio_callback_f32(
-                            data,
-                            manager_clone.clone(),
-                            &command_rx,
-                            &event_tx,
-                            &track_loader_clone,
-                            callback_count,
-                            stream_id,
-                        );
-                        // Apply stream start envelope to prevent DAC pop
-                        stream_envelope.process(data);
-                    },
+                    move |data: &mut [f32], _: &cpal::OutputCallbackInfo| callback(data),
                     |err| eprintln!("[CPAL] Audio stream error callback: {}", err),
                     None,
                 )?
             }
             cpal::SampleFormat::I32 => {
-                let manager_clone = manager.clone();
-                let track_loader_clone = track_loader.clone();
-                // Pre-allocate conversion buffer to avoid allocation in audio callback
-                // Use a reasonable default size that will be resized if needed
-                let mut f32_buffer: Vec<f32> = Vec::with_capacity(4096);
-                // Per-stream callback counter for logging
-                let mut callback_count: u32 = 0;
-                let stream_id = std::time::Instant::now();
-                // Create stream start envelope for click-free startup (30ms fade)
-                let mut stream_envelope = StreamStartEnvelope::new(sample_rate, channels);
-                eprintln!(
-                    "[CPAL] Creating I32 stream callback (stream_id: {:?})",
-                    stream_id
-                );
-
                 // Clone event_tx for the error callback
                 let error_event_tx = event_tx.clone();
-
-                // Create drop guard to detect when callback is dropped
-                let drop_guard = CallbackDropGuard {
-                    stream_id,
-                    sample_format: "I32",
-                };
-
-                // Create TPDF dither for high-quality F32→I32 conversion
-                let mut dither = soul_audio::dither::StereoDither::new();
+                let mut callback = Self::i32_stream_callback(
+                    manager,
+                    command_rx,
+                    event_tx,
+                    track_loader,
+                    dither_mode,
+                    sample_rate,
+                    channels,
+                );
 
                 device.build_output_stream(
                     &config,
-                    move |data: &mut [i32], _: &cpal::OutputCallbackInfo| {
-                        // Keep drop guard alive - when this closure is dropped, drop_guard is dropped
-                        let _ = &drop_guard;
-                        callback_count += 1;
-                        Self::audio_callback_i32(
-                            data,
-                            manager_clone.clone(),
-                            &command_rx,
-                            &event_tx,
-                            &track_loader_clone,
-                            &mut f32_buffer,
-                            &mut dither,
-                            callback_count,
-                            stream_id,
-                        );
-                        // Apply stream start envelope to prevent DAC pop
-                        stream_envelope.process_i32(data);
-                    },
+                    move |data: &mut [i32], _: &cpal::OutputCallbackInfo| callback(data),
                     move |err| {
                         eprintln!("[CPAL] !!! AUDIO STREAM ERROR CALLBACK !!!");
                         eprintln!("[CPAL]   Error: {}", err);
@@ -664,41 +692,18 @@ impl DesktopPlayback {
                 )?
             }
             cpal::SampleFormat::I16 => {
-                let manager_clone = manager.clone();
-                let track_loader_clone = track_loader.clone();
-                // Pre-allocate conversion buffer to avoid allocation in audio callback
-                let mut f32_buffer: Vec<f32> = Vec::with_capacity(4096);
-                // Per-stream callback counter for logging
-                let mut callback_count: u32 = 0;
-                let stream_id = std::time::Instant::now();
-                // Create stream start envelope for click-free startup (30ms fade)
-                let mut stream_envelope = StreamStartEnvelope::new(sample_rate, channels);
-                eprintln!(
-                    "[CPAL] Creating I16 stream callback (stream_id: {:?})",
-                    stream_id
+                let mut callback = Self::i16_stream_callback(
+                    manager,
+                    command_rx,
+                    event_tx,
+                    track_loader,
+                    dither_mode,
+                    sample_rate,
+                    channels,
                 );
-                // Create TPDF dither for high-quality F32→I16 conversion
-                // This is especially important for 16-bit output where quantization is audible
-                let mut dither = soul_audio::dither::StereoDither::new();
-
                 device.build_output_stream(
                     &config,
-                    move |data: &mut [i16], _: &cpal::OutputCallbackInfo| {
-                        callback_count += 1;
-                        Self::audio_callback_i16(
-                            data,
-                            manager_clone.clone(),
-                            &command_rx,
-                            &event_tx,
-                            &track_loader_clone,
-                            &mut f32_buffer,
-                            &mut dither,
-                            callback_count,
-                            stream_id,
-                        );
-                        // Apply stream start envelope to prevent DAC pop
-                        stream_envelope.process_i16(data);
-                    },
+                    move |data: &mut [i16], _: &cpal::OutputCallbackInfo| callback(data),
                     |err| eprintln!("[CPAL] Audio stream error callback: {}", err),
                     None,
                 )?
@@ -743,7 +748,262 @@ impl DesktopPlayback {
         eprintln!("[CPAL] ==========================================");
         eprintln!("[CPAL] Audio callbacks should start momentarily...");
 
-        Ok((stream, actual_device_name, sample_rate))
+        Ok((OutputStream::Cpal(stream), actual_device_name, sample_rate))
+    }
+
+    /// Open a virtual output device (null sink or WAV file)
+    ///
+    /// Returns (OutputStream, device_name, sample_rate)
+    fn create_virtual_stream(
+        manager: Arc<Mutex<PlaybackManager>>,
+        command_rx: Receiver<PlaybackCommand>,
+        event_tx: Sender<PlaybackEvent>,
+        device_name: Option<String>,
+        track_loader: Arc<crate::track_loader::TrackLoader>,
+        dither_mode: Arc<AtomicU8>,
+    ) -> Result<(OutputStream, String, u32)> {
+        let device = match device_name {
+            Some(name) => crate::virtual_output::find_device(&name)
+                .map_err(|e| crate::error::AudioError::DeviceError(e.to_string()))?,
+            None => crate::virtual_output::default_device(),
+        };
+
+        let sample_rate = device.sample_rate;
+        let channels = device.channels;
+
+        // Same DoP rule as for hardware devices
+        let dsd_passthrough = device.capabilities().supports_dsd
+            && channels == 2
+            && device.bit_depth != SupportedBitDepth::Int16;
+        Self::apply_output_format(
+            &manager,
+            &track_loader,
+            sample_rate,
+            channels,
+            dsd_passthrough,
+        );
+
+        eprintln!(
+            "[Virtual] Opening '{}': {} Hz, {} channels, {}, {} frames, {:?} clock, sink {:?}",
+            device.name,
+            sample_rate,
+            channels,
+            device.bit_depth.display_name(),
+            device.buffer_frames,
+            device.clock,
+            device.sink
+        );
+
+        let stream = match device.bit_depth {
+            SupportedBitDepth::Float32 => crate::virtual_output::VirtualStream::build(
+                &device,
+                Self::f32_stream_callback(
+                    manager,
+                    command_rx,
+                    event_tx,
+                    track_loader,
+                    sample_rate,
+                    channels,
+                ),
+            )?,
+            SupportedBitDepth::Int24 | SupportedBitDepth::Int32 => {
+                crate::virtual_output::VirtualStream::build(
+                    &device,
+                    Self::i32_stream_callback(
+                        manager,
+                        command_rx,
+                        event_tx,
+                        track_loader,
+                        dither_mode,
+                        sample_rate,
+                        channels,
+                    ),
+                )?
+            }
+            SupportedBitDepth::Int16 => crate::virtual_output::VirtualStream::build(
+                &device,
+                Self::i16_stream_callback(
+                    manager,
+                    command_rx,
+                    event_tx,
+                    track_loader,
+                    dither_mode,
+                    sample_rate,
+                    channels,
+                ),
+            )?,
+            SupportedBitDepth::Float64 => {
+                return Err(crate::error::AudioError::UnsupportedFormat(
+                    "64-bit float virtual output".to_string(),
+                ));
+            }
+        };
+        stream.play();
+
+        Ok((OutputStream::Virtual(stream), device.name, sample_rate))
+    }
+
+    /// Configure the manager and track loader for a new output stream
+    fn apply_output_format(
+        manager: &Mutex<PlaybackManager>,
+        track_loader: &crate::track_loader::TrackLoader,
+        sample_rate: u32,
+        channels: u16,
+        dsd_passthrough: bool,
+    ) {
+        // Set sample rate and channel count in manager
+        {
+            let mut mgr = manager.lock().unwrap();
+            mgr.set_sample_rate(sample_rate);
+            mgr.set_output_channels(channels);
+        }
+
+        track_loader.set_dsd_passthrough(dsd_passthrough);
+        // Local files are remixed to the device's channel layout on load
+        track_loader.set_output_channels(channels);
+        eprintln!(
+            "[CPAL] DSD output: {}",
+            if dsd_passthrough { "DoP" } else { "PCM conversion" }
+        );
+    }
+
+    /// Build the data callback of an f32 stream
+    fn f32_stream_callback(
+        manager: Arc<Mutex<PlaybackManager>>,
+        command_rx: Receiver<PlaybackCommand>,
+        event_tx: Sender<PlaybackEvent>,
+        track_loader: Arc<crate::track_loader::TrackLoader>,
+        sample_rate: u32,
+        channels: u16,
+    ) -> impl FnMut(&mut [f32]) + Send + 'static {
+        // Per-stream callback counter for logging
+        let mut callback_count: u32 = 0;
+        let stream_id = std::time::Instant::now();
+        // Create stream start envelope for click-free startup (30ms fade)
+        let mut stream_envelope = StreamStartEnvelope::new(sample_rate, channels);
+        eprintln!(
+            "[CPAL] Creating F32 stream callback (stream_id: {:?})",
+            stream_id
+        );
+
+        move |data: &mut [f32]| {
+            callback_count += 1;
+            Self::audio_callback_f32(
+                data,
+                manager.clone(),
+                &command_rx,
+                &event_tx,
+                &track_loader,
+                callback_count,
+                stream_id,
+            );
+            // Apply stream start envelope to prevent DAC pop
+            stream_envelope.process(data);
+        }
+    }
+
+    /// Build the data callback of an i32 stream
+    fn i32_stream_callback(
+        manager: Arc<Mutex<PlaybackManager>>,
+        command_rx: Receiver<PlaybackCommand>,
+        event_tx: Sender<PlaybackEvent>,
+        track_loader: Arc<crate::track_loader::TrackLoader>,
+        dither_mode: Arc<AtomicU8>,
+        sample_rate: u32,
+        channels: u16,
+    ) -> impl FnMut(&mut [i32]) + Send + 'static {
+        // Pre-allocate conversion buffer to avoid allocation in audio callback
+        // Use a reasonable default size that will be resized if needed
+        let mut f32_buffer: Vec<f32> = Vec::with_capacity(4096);
+        // Per-stream callback counter for logging
+        let mut callback_count: u32 = 0;
+        let stream_id = std::time::Instant::now();
+        // Create stream start envelope for click-free startup (30ms fade)
+        let mut stream_envelope = StreamStartEnvelope::new(sample_rate, channels);
+        eprintln!(
+            "[CPAL] Creating I32 stream callback (stream_id: {:?})",
+            stream_id
+        );
+
+        // Create drop guard to detect when callback is dropped
+        let drop_guard = CallbackDropGuard {
+            stream_id,
+            sample_format: "I32",
+        };
+
+        // Create dither for high-quality F32→I32 conversion (mode is user-selectable)
+        let mut dither = StereoDither::with_mode(
+            Self::load_dither_mode(&dither_mode).for_channels(channels),
+            sample_rate,
+        );
+
+        move |data: &mut [i32]| {
+            // Keep drop guard alive - when this closure is dropped, drop_guard is dropped
+            let _ = &drop_guard;
+            callback_count += 1;
+            Self::update_dither(&mut dither, &dither_mode, sample_rate, channels);
+            Self::audio_callback_i32(
+                data,
+                manager.clone(),
+                &command_rx,
+                &event_tx,
+                &track_loader,
+                &mut f32_buffer,
+                &mut dither,
+                callback_count,
+                stream_id,
+            );
+            // Apply stream start envelope to prevent DAC pop
+            stream_envelope.process_i32(data);
+        }
+    }
+
+    /// Build the data callback of an i16 stream
+    fn i16_stream_callback(
+        manager: Arc<Mutex<PlaybackManager>>,
+        command_rx: Receiver<PlaybackCommand>,
+        event_tx: Sender<PlaybackEvent>,
+        track_loader: Arc<crate::track_loader::TrackLoader>,
+        dither_mode: Arc<AtomicU8>,
+        sample_rate: u32,
+        channels: u16,
+    ) -> impl FnMut(&mut [i16]) + Send + 'static {
+        // Pre-allocate conversion buffer to avoid allocation in audio callback
+        let mut f32_buffer: Vec<f32> = Vec::with_capacity(4096);
+        // Per-stream callback counter for logging
+        let mut callback_count: u32 = 0;
+        let stream_id = std::time::Instant::now();
+        // Create stream start envelope for click-free startup (30ms fade)
+        let mut stream_envelope = StreamStartEnvelope::new(sample_rate, channels);
+        eprintln!(
+            "[CPAL] Creating I16 stream callback (stream_id: {:?})",
+            stream_id
+        );
+        // Create dither for high-quality F32→I16 conversion
+        // This is especially important for 16-bit output where quantization is audible,
+        // and where noise shaping pays off most
+        let mut dither = StereoDither::with_mode(
+            Self::load_dither_mode(&dither_mode).for_channels(channels),
+            sample_rate,
+        );
+
+        move |data: &mut [i16]| {
+            callback_count += 1;
+            Self::update_dither(&mut dither, &dither_mode, sample_rate, channels);
+            Self::audio_callback_i16(
+                data,
+                manager.clone(),
+                &command_rx,
+                &event_tx,
+                &track_loader,
+                &mut f32_buffer,
+                &mut dither,
+                callback_count,
+                stream_id,
+            );
+            // Apply stream start envelope to prevent DAC pop
+            stream_envelope.process_i16(data);
+        }
     }
 
     /// Get stream configuration
@@ -792,7 +1052,10 @@ impl DesktopPlayback {
         }
 
         // Find a config that matches the device's actual sample rate
-        // Prefer stereo, then prefer f32 > i32 > i16
+        // Prefer the device's own channel count (stereo, 5.1, 7.1, ...) so
+        // surround speaker setups get native multichannel output,
+        // then prefer f32 > i32 > i16
+        let device_channels = default_config.channels();
         let matching_config = supported_configs
             .iter()
             .filter(|c| {
@@ -800,7 +1063,7 @@ impl DesktopPlayback {
                 c.min_sample_rate() <= actual_sample_rate
                     && c.max_sample_rate() >= actual_sample_rate
             })
-            .filter(|c| c.channels() == 2) // Prefer stereo
+            .filter(|c| c.channels() == device_channels)
             .max_by_key(|c| {
                 // Prefer f32 > i32 > i16
                 match c.sample_format() {
@@ -930,6 +1193,11 @@ impl DesktopPlayback {
         event_tx: &Sender<PlaybackEvent>,
     ) {
         if let Some(track) = mgr.get_current_track().cloned() {
+            // Already requested (by Play/Next or an earlier callback)
+            if track_loader.is_load_pending() {
+                return;
+            }
+
             let target_sample_rate = mgr.get_sample_rate();
             let request = crate::track_loader::LoadRequest {
                 path: track.path.clone(),
@@ -1025,6 +1293,10 @@ impl DesktopPlayback {
                     Self::load_next_track(&mut mgr, track_loader, event_tx);
                 }
             }
+            Err(soul_playback::PlaybackError::QueueEmpty) => {
+                data.fill(0.0);
+                Self::finish_queue(&mut mgr, event_tx);
+            }
             Err(e) => {
                 // Error processing audio - fill with silence
                 data.fill(0.0);
@@ -1039,7 +1311,7 @@ impl DesktopPlayback {
     /// Audio callback for i32 sample format (ASIO)
     ///
     /// Uses a pre-allocated f32 buffer to avoid allocation in the real-time audio thread.
-    /// Uses TPDF dithering for high-quality F32→I32 conversion.
+    /// Uses the configured dither mode (TPDF by default) for F32→I32 conversion.
     fn audio_callback_i32(
         data: &mut [i32],
         manager: Arc<Mutex<PlaybackManager>>,
@@ -1047,7 +1319,7 @@ impl DesktopPlayback {
         event_tx: &Sender<PlaybackEvent>,
         track_loader: &Arc<crate::track_loader::TrackLoader>,
         f32_buffer: &mut Vec<f32>,
-        dither: &mut soul_audio::dither::StereoDither,
+        dither: &mut StereoDither,
         callback_count: u32,
         stream_id: std::time::Instant,
     ) {
@@ -1132,9 +1404,20 @@ impl DesktopPlayback {
                     Self::load_next_track(&mut mgr, track_loader, event_tx);
                 }
 
-                // Convert f32 [-1.0, 1.0] to i32 with TPDF dithering
-                // Dithering reduces quantization noise for higher quality audio
-                dither.process_stereo_to_i32(f32_slice, data);
+                if mgr.is_bitstream() {
+                    // DoP words must reach the DAC bit-exact: no dither
+                    for (out, &sample) in data.iter_mut().zip(f32_slice.iter()) {
+                        *out = (sample as f64 * 2_147_483_648.0) as i32;
+                    }
+                } else {
+                    // Convert f32 [-1.0, 1.0] to i32 with dithering
+                    // Dithering reduces quantization noise for higher quality audio
+                    dither.process_stereo_to_i32(f32_slice, data);
+                }
+            }
+            Err(soul_playback::PlaybackError::QueueEmpty) => {
+                data.fill(0);
+                Self::finish_queue(&mut mgr, event_tx);
             }
             Err(e) => {
                 // Error processing audio - fill with silence
@@ -1150,7 +1433,7 @@ impl DesktopPlayback {
     /// Audio callback for i16 sample format
     ///
     /// Uses a pre-allocated f32 buffer to avoid allocation in the real-time audio thread.
-    /// Uses TPDF dithering for high-quality F32→I16 conversion.
+    /// Uses the configured dither mode (TPDF by default) for F32→I16 conversion.
     fn audio_callback_i16(
         data: &mut [i16],
         manager: Arc<Mutex<PlaybackManager>>,
@@ -1158,7 +1441,7 @@ impl DesktopPlayback {
         event_tx: &Sender<PlaybackEvent>,
         track_loader: &Arc<crate::track_loader::TrackLoader>,
         f32_buffer: &mut Vec<f32>,
-        dither: &mut soul_audio::dither::StereoDither,
+        dither: &mut StereoDither,
         callback_count: u32,
         stream_id: std::time::Instant,
     ) {
@@ -1223,10 +1506,14 @@ impl DesktopPlayback {
                     Self::load_next_track(&mut mgr, track_loader, event_tx);
                 }
 
-                // Convert f32 [-1.0, 1.0] to i16 with TPDF dithering
+                // Convert f32 [-1.0, 1.0] to i16 with dithering
                 // Dithering is essential for 16-bit audio quality
                 dither.process_stereo_to_i16(f32_slice, data);
             }
+            Err(soul_playback::PlaybackError::QueueEmpty) => {
+                data.fill(0);
+                Self::finish_queue(&mut mgr, event_tx);
+            }
             Err(e) => {
                 // Error processing audio - fill with silence
                 data.fill(0);
@@ -1238,6 +1525,40 @@ impl DesktopPlayback {
         }
     }
 
+    /// Position of a dither mode in `DitherMode::ALL`
+    fn dither_mode_index(mode: DitherMode) -> u8 {
+        DitherMode::ALL
+            .iter()
+            .position(|m| *m == mode)
+            .unwrap_or_default() as u8
+    }
+
+    /// Read the shared dither mode
+    fn load_dither_mode(dither_mode: &AtomicU8) -> DitherMode {
+        DitherMode::ALL
+            .get(dither_mode.load(Ordering::Relaxed) as usize)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Rebuild the callback's dither if the mode was changed
+    ///
+    /// Called at the top of each integer-format callback. `StereoDither` holds
+    /// no heap data, so rebuilding it is real-time safe. Noise shaping is
+    /// only used on stereo streams (see `DitherMode::for_channels`).
+    #[inline]
+    fn update_dither(
+        dither: &mut StereoDither,
+        dither_mode: &AtomicU8,
+        sample_rate: u32,
+        channels: u16,
+    ) {
+        let mode = Self::load_dither_mode(dither_mode).for_channels(channels);
+        if dither.mode() != mode {
+            *dither = StereoDither::with_mode(mode, sample_rate);
+        }
+    }
+
     /// Forward events from PlaybackManager to the desktop event channel
     ///
     /// This drains events from the manager (e.g., crossfade progress, track changes at 50%)
@@ -1333,6 +1654,16 @@ impl DesktopPlayback {
         }
     }
 
+    /// Stop after the last track (called from audio callbacks)
+    ///
+    /// `process_audio` reports the end of the queue as `QueueEmpty` when the
+    /// last track finishes and there is nothing to advance to.
+    fn finish_queue(mgr: &mut PlaybackManager, event_tx: &Sender<PlaybackEvent>) {
+        mgr.stop();
+        Self::forward_manager_events(mgr, event_tx);
+        let _ = event_tx.try_send(PlaybackEvent::TrackChanged(None));
+    }
+
     /// Poll for ready track loads from the background loader (non-blocking)
     ///
     /// This is called from the audio callback to check if any track loads have completed.
@@ -1342,6 +1673,12 @@ impl DesktopPlayback {
         track_loader: &Arc<crate::track_loader::TrackLoader>,
         event_tx: &Sender<PlaybackEvent>,
     ) {
+        // Effect chains replaced at the last track switch are freed off the audio thread
+        #[cfg(feature = "effects")]
+        if let Some(chain) = mgr.take_retired_chain() {
+            track_loader.retire_chain(chain);
+        }
+
         while let Some(result) = track_loader.poll_ready() {
             if let Some(source) = result.source {
                 if result.is_preload {
@@ -1350,13 +1687,19 @@ impl DesktopPlayback {
                         "[poll_track_loader] Next track ready for crossfade: {}",
                         result.track.title
                     );
-                    mgr.set_next_source(source, result.track);
+                    match result.dsp {
+                        Some(dsp) => mgr.set_next_source_with_dsp(source, result.track, dsp),
+                        None => mgr.set_next_source(source, result.track),
+                    }
                 } else {
                     // Current track loaded (initial load or track change)
                     eprintln!(
                         "[poll_track_loader] Track loaded: {}",
                         result.track.title
                     );
+                    if let Some(dsp) = result.dsp {
+                        mgr.apply_track_dsp(dsp);
+                    }
                     mgr.set_audio_source(source);
                     let _ = event_tx.try_send(PlaybackEvent::StateChanged(mgr.get_state()));
                     let _ = event_tx.try_send(PlaybackEvent::TrackChanged(Some(result.track)));
@@ -1788,6 +2131,7 @@ impl DesktopPlayback {
             backend,
             device_name.clone(),
             self.track_loader.clone(),
+            self.dither_mode.clone(),
         )?;
 
         // Check if sample rate changed
@@ -1864,10 +2208,15 @@ impl DesktopPlayback {
         );
 
         // Update current backend and device
-        {
-            *self.current_backend.lock().unwrap() = backend;
-            *self.current_device.lock().unwrap() = actual_device_name.clone();
-        }
+        let device_changed = {
+            let mut current_backend = self.current_backend.lock().unwrap();
+            let mut current_device = self.current_device.lock().unwrap();
+            let changed = *current_backend != backend || *current_device != actual_device_name;
+            *current_backend = backend;
+            *current_device = actual_device_name.clone();
+            changed
+        };
+        self.follow_default_device = device_name.is_none();
 
         // Check callbacks after updating backend
         let callbacks_after_backend = GLOBAL_I32_CALLBACK_COUNTER.load(Ordering::Relaxed);
@@ -1893,10 +2242,13 @@ impl DesktopPlayback {
                 mgr.get_sample_rate()
             };
 
-            match crate::sources::local::LocalAudioSource::new(&track.path, target_sample_rate) {
+            match self
+                .track_loader
+                .open_source(&track.path, target_sample_rate, track.range)
+            {
                 Ok(source) => {
                     let mut mgr = self.manager.lock().unwrap();
-                    mgr.set_audio_source(Box::new(source));
+                    mgr.set_audio_source(source);
                     eprintln!(
                         "[DesktopPlayback] Audio source reloaded with sample rate: {}",
                         target_sample_rate
@@ -1955,6 +2307,15 @@ impl DesktopPlayback {
             }
         }
 
+        // Let listeners apply per-device settings (e.g. DSP profiles)
+        // Stream refreshes on the same device don't count as a change
+        if device_changed {
+            let _ = self.event_tx.try_send(PlaybackEvent::DeviceChanged {
+                backend,
+                device_name: actual_device_name,
+            });
+        }
+
         // Final callback check before returning
         let callbacks_at_end = GLOBAL_I32_CALLBACK_COUNTER.load(Ordering::Relaxed);
         eprintln!(
@@ -1992,6 +2353,12 @@ impl DesktopPlayback {
         let backend = *self.current_backend.lock().unwrap();
         let device_name = self.current_device.lock().unwrap().clone();
 
+        if backend == crate::AudioBackend::Virtual {
+            return crate::virtual_output::find_device(&device_name)
+                .map(|device| device.sample_rate)
+                .map_err(|e| crate::error::AudioError::DeviceError(e.to_string()));
+        }
+
         let device = crate::device::find_device_by_name(backend, &device_name)
             .map_err(|e| crate::error::AudioError::DeviceError(e.to_string()))?;
 
@@ -2041,10 +2408,10 @@ impl DesktopPlayback {
 
         // Sample rate has changed - need to recreate the stream
         let backend = *self.current_backend.lock().unwrap();
-        let device_name = self.current_device.lock().unwrap().clone();
+        let device_name = self.reopen_device_name();
 
         // switch_device will handle everything: stream recreation, source reload, position preservation
-        self.switch_device(backend, Some(device_name))?;
+        self.switch_device(backend, device_name)?;
 
         Ok(true)
     }
@@ -2059,8 +2426,64 @@ impl DesktopPlayback {
     /// * `Err(_)` - Failed to refresh stream
     pub fn refresh_stream(&mut self) -> Result<()> {
         let backend = *self.current_backend.lock().unwrap();
-        let device_name = self.current_device.lock().unwrap().clone();
-        self.switch_device(backend, Some(device_name))
+        let device_name = self.reopen_device_name();
+        self.switch_device(backend, device_name)
+    }
+
+    /// Whether the output follows the system default device
+    ///
+    /// True when the current device was opened without an explicit name.
+    pub fn is_following_default_device(&self) -> bool {
+        self.follow_default_device
+    }
+
+    /// Check if the system default device has changed and follow it
+    ///
+    /// This method should be called periodically (alongside
+    /// [`check_and_update_sample_rate`](Self::check_and_update_sample_rate)).
+    /// It only acts when the output follows the default device; an explicitly
+    /// selected device is never switched away from.
+    ///
+    /// If the default changed, the stream is moved to the new default device
+    /// and a `DeviceChanged` event is emitted.
+    ///
+    /// # Returns
+    /// * `Ok(true)` - Default device changed and playback moved to it
+    /// * `Ok(false)` - Not following the default, or default unchanged
+    /// * `Err(_)` - Failed to query the default device or switch to it
+    pub fn check_default_device(&mut self) -> Result<bool> {
+        if !self.follow_default_device {
+            return Ok(false);
+        }
+
+        let backend = *self.current_backend.lock().unwrap();
+        let default_name = crate::device::get_default_device_name(backend)
+            .map_err(|e| crate::error::AudioError::DeviceError(e.to_string()))?;
+
+        if *self.current_device.lock().unwrap() == default_name {
+            return Ok(false);
+        }
+
+        eprintln!(
+            "[DesktopPlayback] System default device changed: {} -> {}",
+            self.current_device.lock().unwrap(),
+            default_name
+        );
+
+        self.switch_device(backend, None)?;
+
+        Ok(true)
+    }
+
+    /// Device name to reopen the current output with
+    ///
+    /// `None` keeps following the system default device.
+    fn reopen_device_name(&self) -> Option<String> {
+        if self.follow_default_device {
+            None
+        } else {
+            Some(self.current_device.lock().unwrap().clone())
+        }
     }
 
     /// Get mutable reference to effect chain (for configuring DSP effects)
@@ -2090,6 +2513,23 @@ impl DesktopPlayback {
         f(manager.effect_chain_mut())
     }
 
+    /// Names of the enabled effects bypassed on the current output device
+    ///
+    /// Stereo-only effects (EQs, crossfeed, convolution) don't run when the
+    /// device has a surround layout.
+    #[cfg(feature = "effects")]
+    pub fn bypassed_effects(&self) -> Vec<String> {
+        let mut manager = self.manager.lock().unwrap();
+        let layout =
+            soul_audio::channels::ChannelLayout::from_channel_count(manager.get_output_channels());
+        manager
+            .effect_chain_mut()
+            .bypassed_effects(layout)
+            .into_iter()
+            .map(String::from)
+            .collect()
+    }
+
     // ===== Volume Leveling =====
 
     /// Set volume leveling mode (ReplayGain track/album, EBU R128, etc.)
@@ -2148,16 +2588,59 @@ impl DesktopPlayback {
         manager.set_prevent_clipping(prevent);
     }
 
+    /// Set the gain offset in dB for the current track
+    ///
+    /// Used for per-track/album/genre overrides; ramped to avoid clicks.
+    pub fn set_gain_offset_db(&self, gain_db: f64) {
+        let mut manager = self.manager.lock().unwrap();
+        manager.set_gain_offset_db(gain_db);
+    }
+
+    /// Get the gain offset in dB
+    pub fn get_gain_offset_db(&self) -> f64 {
+        let manager = self.manager.lock().unwrap();
+        manager.get_gain_offset_db()
+    }
+
+    /// Set the resolver for the gain and effects of each loaded track
+    ///
+    /// Runs on the track loader thread. Preloaded tracks switch to their
+    /// DSP where the transition to them starts, directly loaded tracks
+    /// as they start.
+    pub fn set_track_dsp_resolver(&self, resolver: Option<crate::track_loader::TrackDspResolver>) {
+        self.track_loader.set_dsp_resolver(resolver);
+    }
+
+    /// Replace the gain and effects prepared for the next track
+    ///
+    /// Returns false if `track_id` isn't the prepared next track or the
+    /// transition to it has already started.
+    pub fn set_next_track_dsp(&self, track_id: &str, dsp: TrackDsp) -> bool {
+        let mut manager = self.manager.lock().unwrap();
+        manager.set_next_track_dsp(track_id, dsp)
+    }
+
+    /// Apply gain and effects to the current track right away
+    pub fn apply_track_dsp(&self, dsp: TrackDsp) {
+        let mut manager = self.manager.lock().unwrap();
+        manager.apply_track_dsp(dsp);
+    }
+
     // ===== Exclusive Mode / Bit-Perfect Output =====
 
     /// Get current latency information
     ///
     /// Returns buffer size, latency in milliseconds, and exclusive mode status.
+    /// The total includes the delay of the DSP effects (e.g. linear-phase EQ).
     pub fn get_latency_info(&self) -> crate::LatencyInfo {
         // Get current buffer size from stream config
         // This is an estimate based on typical buffer sizes
         let sample_rate = self.current_sample_rate.load(Ordering::SeqCst);
-        let buffer_samples = 512u32; // Default estimate
+        let buffer_samples = match &*self.stream.lock().unwrap() {
+            // Virtual devices have a known, fixed buffer size
+            Some(OutputStream::Virtual(stream)) => stream.device().buffer_frames,
+            _ => 512u32, // Default estimate
+        };
 
         let buffer_ms = if sample_rate > 0 {
             buffer_samples as f32 / sample_rate as f32 * 1000.0
@@ -2165,10 +2648,17 @@ impl DesktopPlayback {
             11.6 // ~512 samples at 44100
         };
 
+        let effect_latency = self.manager.lock().unwrap().get_effect_latency();
+        let effects_ms = if sample_rate > 0 {
+            effect_latency as f32 / sample_rate as f32 * 1000.0
+        } else {
+            0.0
+        };
+
         crate::LatencyInfo {
             buffer_samples,
             buffer_ms,
-            total_ms: buffer_ms + 5.0, // Add DAC latency estimate
+            total_ms: buffer_ms + effects_ms + 5.0, // Add DAC latency estimate
             exclusive: false,          // Currently not tracking exclusive mode state
         }
     }
@@ -2295,6 +2785,37 @@ impl DesktopPlayback {
         manager.set_crossfade_on_skip(on_skip);
     }
 
+    // ===== Playback Speed =====
+
+    /// Set the default playback speed and pitch
+    ///
+    /// Speed changes keep the original pitch. Tracks queued with their own
+    /// `playback_rate` keep it.
+    pub fn set_playback_rate(&self, rate: soul_playback::PlaybackRate) {
+        let mut manager = self.manager.lock().unwrap();
+        manager.set_playback_rate(rate);
+    }
+
+    /// Get the default playback speed and pitch
+    pub fn get_playback_rate(&self) -> soul_playback::PlaybackRate {
+        let manager = self.manager.lock().unwrap();
+        manager.get_playback_rate()
+    }
+
+    /// Override the playback speed and pitch of the current track
+    ///
+    /// `None` returns the track to the default rate.
+    pub fn set_current_track_playback_rate(&self, rate: Option<soul_playback::PlaybackRate>) {
+        let mut manager = self.manager.lock().unwrap();
+        manager.set_current_track_playback_rate(rate);
+    }
+
+    /// Get the playback speed and pitch in effect for the current track
+    pub fn get_current_playback_rate(&self) -> soul_playback::PlaybackRate {
+        let manager = self.manager.lock().unwrap();
+        manager.get_current_playback_rate()
+    }
+
     // ===========================================================================
     // Resampling Settings
     // ===========================================================================
@@ -2408,6 +2929,96 @@ impl DesktopPlayback {
         settings.clone()
     }
 
+    // ===========================================================================
+    // Dither Settings
+    // ===========================================================================
+
+    /// Set the dither mode used when output is converted to i16/i32
+    ///
+    /// Modes:
+    /// - "none": round to nearest (no dither)
+    /// - "tpdf": flat TPDF dither (default)
+    /// - "lipshitz", "f-weighted", "e-weighted": noise-shaped TPDF; shaping
+    ///   applies to stereo output at 44.1/48 kHz, anything else gets flat TPDF
+    ///
+    /// Takes effect immediately. Float outputs and DoP passthrough are never dithered.
+    pub fn set_dither_mode(&self, mode: &str) -> std::result::Result<(), String> {
+        let mode: DitherMode = mode.parse()?;
+        self.dither_mode
+            .store(Self::dither_mode_index(mode), Ordering::Relaxed);
+        eprintln!("[DesktopPlayback] Dither mode set to '{}'", mode);
+        Ok(())
+    }
+
+    /// Get current dither mode
+    pub fn get_dither_mode(&self) -> String {
+        Self::load_dither_mode(&self.dither_mode).to_string()
+    }
+
+    // ===========================================================================
+    // Channel Layout
+    // ===========================================================================
+
+    /// Get the channel count of the current output stream
+    pub fn get_output_channels(&self) -> u16 {
+        self.track_loader.output_channels()
+    }
+
+    /// Set the coefficients used to fold surround files down to fewer channels
+    ///
+    /// Applies to tracks loaded after the call (e.g. 5.1 files on a stereo
+    /// device). Defaults to ITU-R BS.775.
+    pub fn set_downmix_settings(
+        &self,
+        settings: DownmixSettings,
+    ) -> std::result::Result<(), String> {
+        settings.validate()?;
+        self.track_loader.set_downmix_settings(settings);
+        eprintln!("[DesktopPlayback] Downmix settings: {:?}", settings);
+        Ok(())
+    }
+
+    /// Get the coefficients used to fold surround files down
+    pub fn get_downmix_settings(&self) -> DownmixSettings {
+        self.track_loader.downmix_settings()
+    }
+
+    // ===========================================================================
+    // Audio Analysis
+    // ===========================================================================
+
+    /// Enable or disable the level/spectrum analysis tap
+    ///
+    /// Meters the post-effects signal; results are published ~30 times per
+    /// second and read with `get_audio_analysis`.
+    #[cfg(feature = "effects")]
+    pub fn set_analysis_enabled(&self, enabled: bool) {
+        let reader = {
+            let mut manager = self.manager.lock().unwrap();
+            manager.set_analysis_enabled(enabled);
+            manager.analysis_reader()
+        };
+        *self.analysis_reader.lock().unwrap() = reader;
+    }
+
+    /// Check if the analysis tap is enabled
+    pub fn is_analysis_enabled(&self) -> bool {
+        self.analysis_reader.lock().unwrap().is_some()
+    }
+
+    /// Get the latest level/loudness/spectrum snapshot
+    ///
+    /// Lock-free with respect to the audio thread, so it can be polled at
+    /// display rate. Returns None when analysis is disabled or nothing has
+    /// played yet.
+    pub fn get_audio_analysis(&self) -> Option<AudioAnalysis> {
+        self.analysis_reader
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(AnalysisReader::latest)
+    }
+
     // ===========================================================================
     // Headroom Management
     // ===========================================================================
@@ -2594,6 +3205,46 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_explicit_device_stops_following_default() {
+        let result = DesktopPlayback::new(PlaybackConfig::default());
+
+        match result {
+            Ok(mut playback) => {
+                // Opened without a device name: follows the system default
+                assert!(playback.is_following_default_device());
+
+                // Selecting the same device by name pins it
+                let device = playback.get_current_device();
+                if playback
+                    .switch_device(crate::AudioBackend::Default, Some(device))
+                    .is_ok()
+                {
+                    assert!(!playback.is_following_default_device());
+                    assert!(!playback.check_default_device().unwrap());
+                }
+
+                // A sample rate refresh keeps the pinned device
+                if playback.refresh_stream().is_ok() {
+                    assert!(!playback.is_following_default_device());
+                }
+
+                if playback
+                    .switch_device(crate::AudioBackend::Default, None)
+                    .is_ok()
+                {
+                    assert!(playback.is_following_default_device());
+                }
+            }
+            Err(e) => {
+                eprintln!(
+                    "Note: Audio device not available in test environment: {}",
+                    e
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_switch_device_invalid_device() {
         let result = DesktopPlayback::new(PlaybackConfig::default());
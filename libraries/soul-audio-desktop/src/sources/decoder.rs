@@ -0,0 +1,509 @@
+//! Audio source for formats decoded outside Symphonia
+//!
+//! Plays the files `soul_audio`'s decoder registry opens with its own
+//! decoders: WavPack, Monkey's Audio and (with the `musepack` feature)
+//! Musepack. Registry decoders output interleaved `f32` in the file's own
+//! channel layout, which is remixed to the output channel count (like
+//! `LocalAudioSource`, with a BS.775 downmix by default) and resampled to the
+//! device rate.
+//!
+//! The decoder thread, shared buffer and seek handling mirror
+//! `LocalAudioSource`; track ranges (CUE sheets, which are common with
+//! single-file APE and WavPack rips) work the same way as for DSD files.
+
+use super::local::{DecoderCommand, LocalAudioSource, SharedState};
+use crossbeam_channel::{bounded, Receiver, Sender};
+use rubato::{Resampler, SincFixedIn};
+use soul_audio::channels::{ChannelLayout, ChannelMatrix, DownmixSettings};
+use soul_core::AudioDecoder;
+use soul_playback::{AudioSource, PlaybackError, Result, TrackRange};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Size of output buffer in seconds
+const BUFFER_SIZE_SECONDS: usize = 5;
+
+/// Frames requested from the decoder per iteration
+const DECODE_CHUNK_FRAMES: usize = 4096;
+
+/// Frames per resampler chunk (same as `LocalAudioSource`)
+const RESAMPLER_CHUNK_FRAMES: usize = 1024;
+
+/// Audio source for files opened through the decoder registry
+///
+/// Decodes in a background thread; `read_samples()` only copies from the
+/// pre-filled buffer.
+pub struct DecoderAudioSource {
+    path: PathBuf,
+    /// Output sample rate
+    sample_rate: u32,
+    /// Sample rate of the file
+    source_sample_rate: u32,
+    /// Channel count of the file
+    source_channels: u16,
+    /// Channel count delivered by `read_samples`
+    output_channels: u16,
+
+    shared: Arc<Mutex<SharedState>>,
+    command_tx: Sender<DecoderCommand>,
+    _decoder_thread: JoinHandle<()>,
+
+    total_duration: Duration,
+    range: Option<TrackRange>,
+}
+
+impl DecoderAudioSource {
+    /// Whether a file is played by this source rather than Symphonia or DSD
+    pub fn handles(path: &Path) -> bool {
+        soul_audio::formats::registry()
+            .find(path)
+            .is_some_and(|format| format.name != soul_audio::formats::SYMPHONIA)
+    }
+
+    /// Open a file for stereo playback
+    ///
+    /// # Arguments
+    /// * `path` - Path to the audio file
+    /// * `target_sample_rate` - Output sample rate of the device
+    /// * `range` - Part of the file to play (None = whole file)
+    pub fn new(
+        path: impl AsRef<Path>,
+        target_sample_rate: u32,
+        range: Option<TrackRange>,
+    ) -> Result<Self> {
+        Self::with_layout(
+            path,
+            target_sample_rate,
+            range,
+            2,
+            DownmixSettings::default(),
+        )
+    }
+
+    /// Open a file for playback with a specific number of output channels
+    ///
+    /// The file's channel layout is remixed to `output_channels` in the
+    /// decoder thread, as `LocalAudioSource::with_layout` does.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the audio file
+    /// * `target_sample_rate` - Output sample rate of the device
+    /// * `range` - Part of the file to play (None = whole file)
+    /// * `output_channels` - Channel count of the output device
+    /// * `downmix` - Coefficients used when folding down channels
+    pub fn with_layout(
+        path: impl AsRef<Path>,
+        target_sample_rate: u32,
+        range: Option<TrackRange>,
+        output_channels: u16,
+        downmix: DownmixSettings,
+    ) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let output_channels = output_channels.max(1);
+
+        let mut decoder = soul_audio::formats::registry()
+            .decoder_for(&path)
+            .map_err(|e| PlaybackError::AudioSource(format!("Failed to open file: {}", e)))?;
+        let metadata = decoder
+            .open(&path)
+            .map_err(|e| PlaybackError::AudioSource(format!("Failed to open file: {}", e)))?;
+
+        let file_duration = metadata.duration.unwrap_or(Duration::ZERO);
+        let total_duration = match range {
+            Some(range) => {
+                if range.start >= file_duration {
+                    return Err(PlaybackError::AudioSource(format!(
+                        "Track range starts at {:?}, past the end of the file ({:?})",
+                        range.start, file_duration
+                    )));
+                }
+                range.length(file_duration)
+            }
+            None => file_duration,
+        };
+
+        let resampler = if metadata.sample_rate == target_sample_rate {
+            None
+        } else {
+            Some(
+                LocalAudioSource::create_resampler(
+                    metadata.sample_rate,
+                    target_sample_rate,
+                    output_channels as usize,
+                    RESAMPLER_CHUNK_FRAMES,
+                )
+                .map_err(PlaybackError::AudioSource)?,
+            )
+        };
+
+        let output_buffer_capacity =
+            BUFFER_SIZE_SECONDS * target_sample_rate as usize * output_channels as usize;
+        let shared = Arc::new(Mutex::new(SharedState {
+            output_buffer: VecDeque::with_capacity(output_buffer_capacity),
+            samples_read: 0,
+            is_eof: false,
+            seek_pending: false,
+        }));
+
+        let (command_tx, command_rx) = bounded::<DecoderCommand>(4);
+
+        let source_sample_rate = metadata.sample_rate;
+        let shared_clone = shared.clone();
+        let decoder_thread = thread::Builder::new()
+            .name(format!(
+                "decoder-{}",
+                path.file_name().unwrap_or_default().to_string_lossy()
+            ))
+            .spawn(move || {
+                Self::decoder_thread_main(
+                    decoder,
+                    resampler,
+                    source_sample_rate,
+                    target_sample_rate,
+                    output_buffer_capacity,
+                    range,
+                    output_channels,
+                    downmix,
+                    shared_clone,
+                    command_rx,
+                );
+            })
+            .map_err(|e| {
+                PlaybackError::AudioSource(format!("Failed to spawn decoder thread: {}", e))
+            })?;
+
+        // Wait for some initial data (up to 500ms) so the source is immediately playable
+        for _ in 0..50 {
+            let state = shared.lock().unwrap();
+            if state.output_buffer.len() >= 4800 || state.is_eof {
+                break;
+            }
+            drop(state);
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        Ok(Self {
+            path,
+            sample_rate: target_sample_rate,
+            source_sample_rate,
+            source_channels: metadata.channels,
+            output_channels,
+            shared,
+            command_tx,
+            _decoder_thread: decoder_thread,
+            total_duration,
+            range,
+        })
+    }
+
+    /// Background decoder thread main function
+    fn decoder_thread_main(
+        mut decoder: Box<dyn AudioDecoder>,
+        mut resampler: Option<SincFixedIn<f32>>,
+        source_sample_rate: u32,
+        target_sample_rate: u32,
+        output_buffer_capacity: usize,
+        range: Option<TrackRange>,
+        output_channels: u16,
+        downmix: DownmixSettings,
+        shared: Arc<Mutex<SharedState>>,
+        command_rx: Receiver<DecoderCommand>,
+    ) {
+        let channels = output_channels as usize;
+        let range_start = range.map_or(Duration::ZERO, |r| r.start);
+        let end_frame = range.and_then(|r| r.end_frame(source_sample_rate));
+
+        // Absolute position in the file, in source frames
+        let mut frame = 0u64;
+        if range_start > Duration::ZERO {
+            match decoder.seek(range_start) {
+                Ok(actual) => {
+                    frame = (actual.as_secs_f64() * source_sample_rate as f64).round() as u64
+                }
+                Err(e) => eprintln!("[Decoder] Seek to range start failed: {}", e),
+            }
+        }
+
+        // Remix from the file layout to the output layout, rebuilt if a
+        // chunk arrives with a different channel count
+        let output_layout = ChannelLayout::from_channel_count(output_channels);
+        let mut matrix = ChannelMatrix::new(output_layout, output_layout, &downmix);
+
+        let mut input_buffer: VecDeque<f32> =
+            VecDeque::with_capacity(RESAMPLER_CHUNK_FRAMES * channels * 4);
+        let mut is_eof = false;
+
+        loop {
+            match command_rx.try_recv() {
+                Ok(DecoderCommand::Stop) => break,
+                Ok(DecoderCommand::Seek(position)) => {
+                    match decoder.seek(range_start + position) {
+                        Ok(actual) => {
+                            frame =
+                                (actual.as_secs_f64() * source_sample_rate as f64).round() as u64;
+                            input_buffer.clear();
+                            if let Some(r) = &mut resampler {
+                                r.reset();
+                            }
+                            is_eof = false;
+
+                            let mut state = shared.lock().unwrap();
+                            state.output_buffer.clear();
+                            state.samples_read = (actual.saturating_sub(range_start).as_secs_f64()
+                                * target_sample_rate as f64
+                                * channels as f64)
+                                as usize;
+                            state.is_eof = false;
+                        }
+                        Err(e) => eprintln!("[Decoder] Seek failed: {}", e),
+                    }
+                    shared.lock().unwrap().seek_pending = false;
+                }
+                Err(crossbeam_channel::TryRecvError::Empty) => {}
+                Err(crossbeam_channel::TryRecvError::Disconnected) => {
+                    eprintln!("[Decoder] Command channel disconnected, exiting");
+                    break;
+                }
+            }
+
+            let buffer_len = shared.lock().unwrap().output_buffer.len();
+            if buffer_len >= output_buffer_capacity / 2 {
+                thread::sleep(Duration::from_millis(10));
+                continue;
+            }
+
+            if is_eof {
+                thread::sleep(Duration::from_millis(50));
+                continue;
+            }
+
+            let remaining = end_frame.map_or(u64::MAX, |end| end.saturating_sub(frame));
+            let wanted = (DECODE_CHUNK_FRAMES as u64).min(remaining) as usize;
+            let chunk = if wanted == 0 {
+                Ok(None)
+            } else {
+                decoder.decode_chunk(wanted)
+            };
+
+            let (mut samples, decoded_channels) = match chunk {
+                Ok(Some(chunk)) => (chunk.samples, chunk.format.channels.max(1)),
+                Ok(None) => (Vec::new(), output_channels),
+                Err(e) => {
+                    eprintln!("[Decoder] Decode error: {}", e);
+                    (Vec::new(), output_channels)
+                }
+            };
+
+            if samples.is_empty() {
+                is_eof = true;
+                if resampler.is_some() && !input_buffer.is_empty() {
+                    LocalAudioSource::flush_resampler_static(
+                        &mut input_buffer,
+                        &mut resampler,
+                        channels,
+                        RESAMPLER_CHUNK_FRAMES,
+                        &shared,
+                    );
+                }
+                shared.lock().unwrap().is_eof = true;
+                continue;
+            }
+
+            samples.truncate(wanted * decoded_channels as usize);
+            let chunk_frames = samples.len() / decoded_channels as usize;
+            frame += chunk_frames as u64;
+
+            // Remix to the output channel count
+            if matrix.input().channel_count() != decoded_channels {
+                let input_layout = ChannelLayout::from_channel_count(decoded_channels);
+                eprintln!("[Decoder] Remixing {} -> {}", input_layout, output_layout);
+                matrix = ChannelMatrix::new(input_layout, output_layout, &downmix);
+            }
+            if !matrix.is_identity() {
+                let mut remixed = vec![0.0; chunk_frames * channels];
+                let frames = matrix.process(&samples, &mut remixed);
+                remixed.truncate(frames * channels);
+                samples = remixed;
+            }
+
+            if resampler.is_some() {
+                input_buffer.extend(samples);
+                LocalAudioSource::process_resampling_static(
+                    &mut input_buffer,
+                    &mut resampler,
+                    channels,
+                    RESAMPLER_CHUNK_FRAMES,
+                    output_buffer_capacity,
+                    &shared,
+                );
+            } else {
+                shared.lock().unwrap().output_buffer.extend(samples);
+            }
+        }
+    }
+
+    /// Get file path
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get output sample rate
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Get the file's sample rate
+    pub fn source_sample_rate(&self) -> u32 {
+        self.source_sample_rate
+    }
+
+    /// Get number of channels in the audio file
+    pub fn source_channels(&self) -> u16 {
+        self.source_channels
+    }
+
+    /// Get the part of the file being played (None = whole file)
+    pub fn range(&self) -> Option<TrackRange> {
+        self.range
+    }
+}
+
+impl AudioSource for DecoderAudioSource {
+    fn read_samples(&mut self, output: &mut [f32]) -> Result<usize> {
+        let mut state = self.shared.lock().unwrap();
+
+        let available = state.output_buffer.len().min(output.len());
+        for (dst, src) in output
+            .iter_mut()
+            .zip(state.output_buffer.drain(..available))
+        {
+            *dst = src;
+        }
+        state.samples_read += available;
+
+        if available < output.len() {
+            output[available..].fill(0.0);
+        }
+
+        Ok(available)
+    }
+
+    fn seek(&mut self, position: Duration) -> Result<()> {
+        if position > self.total_duration {
+            return Err(PlaybackError::InvalidSeekPosition(position));
+        }
+
+        self.shared.lock().unwrap().seek_pending = true;
+
+        self.command_tx
+            .send(DecoderCommand::Seek(position))
+            .map_err(|e| PlaybackError::AudioSource(format!("Failed to send seek command: {}", e)))
+    }
+
+    fn duration(&self) -> Duration {
+        self.total_duration
+    }
+
+    fn position(&self) -> Duration {
+        let state = self.shared.lock().unwrap();
+        let frames = state.samples_read / self.output_channels as usize;
+        Duration::from_secs_f64(frames as f64 / self.sample_rate as f64)
+    }
+
+    fn channels(&self) -> u16 {
+        self.output_channels
+    }
+
+    fn is_finished(&self) -> bool {
+        let state = self.shared.lock().unwrap();
+        state.is_eof && !state.seek_pending && state.output_buffer.is_empty()
+    }
+}
+
+impl Drop for DecoderAudioSource {
+    fn drop(&mut self) {
+        let _ = self.command_tx.send(DecoderCommand::Stop);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soul_audio::test_utils::generate_pcm_test_signal;
+    use soul_audio::test_utils::wavpack::{write_wavpack, WavPackOptions};
+
+    #[test]
+    fn decoder_source_implements_audio_source() {
+        fn assert_audio_source<T: AudioSource>() {}
+        assert_audio_source::<DecoderAudioSource>();
+    }
+
+    #[test]
+    fn handles_registry_formats_only() {
+        assert!(DecoderAudioSource::handles(Path::new("/nonexistent/a.wv")));
+        assert!(DecoderAudioSource::handles(Path::new("/nonexistent/a.ape")));
+        assert!(!DecoderAudioSource::handles(Path::new(
+            "/nonexistent/a.flac"
+        )));
+        assert!(!DecoderAudioSource::handles(Path::new(
+            "/nonexistent/a.dsf"
+        )));
+    }
+
+    #[test]
+    fn missing_file_is_an_error() {
+        let result = DecoderAudioSource::new("/nonexistent/file.wv", 44100, None);
+        assert!(result.is_err());
+    }
+
+    /// Read everything the decoder thread produces
+    fn read_all(source: &mut DecoderAudioSource) -> Vec<f32> {
+        let mut samples = Vec::new();
+        let mut buffer = vec![0.0f32; 4096];
+        while !source.is_finished() {
+            let n = source.read_samples(&mut buffer).unwrap();
+            if n == 0 {
+                thread::sleep(Duration::from_millis(1));
+            }
+            samples.extend_from_slice(&buffer[..n]);
+        }
+        samples
+    }
+
+    #[test]
+    fn surround_files_play_natively_or_downmixed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("surround.wv");
+        let input = generate_pcm_test_signal(4410, 6, 16);
+        let options = WavPackOptions {
+            channels: 6,
+            ..Default::default()
+        };
+        write_wavpack(&path, &input, &options).unwrap();
+        let expected: Vec<f32> = input.iter().map(|&s| s as f32 / 32768.0).collect();
+
+        // A 5.1 device gets the file's channels untouched
+        let mut source =
+            DecoderAudioSource::with_layout(&path, 44100, None, 6, DownmixSettings::default())
+                .unwrap();
+        assert_eq!(source.source_channels(), 6);
+        assert_eq!(source.channels(), 6);
+        assert_eq!(read_all(&mut source), expected);
+
+        // A stereo device gets the BS.775 downmix
+        let matrix = ChannelMatrix::new(
+            ChannelLayout::from_channel_count(6),
+            ChannelLayout::from_channel_count(2),
+            &DownmixSettings::default(),
+        );
+        let mut downmixed = vec![0.0; 4410 * 2];
+        matrix.process(&expected, &mut downmixed);
+
+        let mut source = DecoderAudioSource::new(&path, 44100, None).unwrap();
+        assert_eq!(source.channels(), 2);
+        assert_eq!(read_all(&mut source), downmixed);
+    }
+}
@@ -0,0 +1,521 @@
+//! DSD (DSF / DSDIFF) audio source
+//!
+//! Plays DSD files in one of two modes, chosen when the source is created:
+//!
+//! - **DoP passthrough**: the 1-bit stream is packed into DSD-over-PCM words
+//!   by `DopEncoder` and sent to the DAC at the DoP carrier rate (176.4 kHz
+//!   for DSD64). The source reports itself as a bitstream, so
+//!   `PlaybackManager` and the output callback leave the samples untouched.
+//! - **PCM conversion**: the stream is decimated to 88.2/96 kHz PCM by
+//!   `DsdToPcm`, downmixed to stereo and resampled to the device rate, then
+//!   goes through the normal effect chain like any other track.
+//!
+//! The decoder thread, shared buffer and seek handling mirror
+//! `LocalAudioSource`.
+
+use super::local::{DecoderCommand, LocalAudioSource, SharedState};
+use crossbeam_channel::{bounded, Receiver, Sender};
+use rubato::SincFixedIn;
+use soul_audio::dsd::{downmix_to_stereo, DopEncoder, DsdFile, DsdToPcm};
+use soul_playback::{AudioSource, PlaybackError, Result, TrackRange};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Size of output buffer in seconds
+const BUFFER_SIZE_SECONDS: usize = 5;
+
+/// DSD bytes read per channel per iteration
+const READ_CHUNK_BYTES: usize = 4096;
+
+/// Frames per resampler chunk (same as `LocalAudioSource`)
+const RESAMPLER_CHUNK_FRAMES: usize = 1024;
+
+/// Output is always interleaved stereo
+const OUTPUT_CHANNELS: usize = 2;
+
+/// DSD idle pattern, used to pad the final DoP frame
+const DSD_SILENCE: u8 = 0x69;
+
+/// How DSD is delivered to the output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DsdOutputMode {
+    /// Bit-perfect DSD over PCM (requires a DoP-capable DAC)
+    Dop,
+    /// Converted to PCM and processed like any other track
+    Pcm,
+}
+
+/// Converts DSD bytes read from the file into output samples
+enum Renderer {
+    Dop {
+        encoder: DopEncoder,
+        /// Interleaved [L0 L1 R0 R1] scratch buffer for the encoder
+        interleaved: Vec<u8>,
+    },
+    Pcm(Box<PcmRenderer>),
+}
+
+/// DSD to PCM conversion state
+struct PcmRenderer {
+    decimator: DsdToPcm,
+    resampler: Option<SincFixedIn<f32>>,
+    /// Decimated samples waiting for the resampler
+    input_buffer: VecDeque<f32>,
+    /// Scratch buffer for decimator output
+    pcm: Vec<f32>,
+}
+
+impl Renderer {
+    /// Bytes per channel that map to a whole number of output frames
+    fn alignment(&self) -> u64 {
+        match self {
+            Renderer::Dop { .. } => 2,
+            Renderer::Pcm(pcm) => pcm.decimator.bytes_per_frame() as u64,
+        }
+    }
+
+    fn reset(&mut self) {
+        match self {
+            Renderer::Dop { encoder, .. } => encoder.reset(),
+            Renderer::Pcm(pcm) => {
+                pcm.decimator.reset();
+                pcm.input_buffer.clear();
+                if let Some(r) = &mut pcm.resampler {
+                    rubato::Resampler::reset(r);
+                }
+            }
+        }
+    }
+
+    /// Render one chunk of per-channel DSD bytes into the output buffer
+    fn render(
+        &mut self,
+        channels: &[Vec<u8>],
+        output_buffer_capacity: usize,
+        shared: &Arc<Mutex<SharedState>>,
+    ) {
+        match self {
+            Renderer::Dop {
+                encoder,
+                interleaved,
+            } => {
+                // DoP mode is only chosen for stereo files
+                let len = channels[0].len();
+                interleaved.clear();
+                for i in (0..len).step_by(2) {
+                    for channel in channels.iter().take(OUTPUT_CHANNELS) {
+                        interleaved.push(channel[i]);
+                        interleaved.push(channel.get(i + 1).copied().unwrap_or(DSD_SILENCE));
+                    }
+                }
+                let samples = encoder.encode_f32(interleaved);
+                shared.lock().unwrap().output_buffer.extend(samples);
+            }
+            Renderer::Pcm(pcm) => {
+                let mut decimated = std::mem::take(&mut pcm.pcm);
+                decimated.clear();
+                let slices: Vec<&[u8]> = channels.iter().map(|c| c.as_slice()).collect();
+                pcm.decimator.process(&slices, &mut decimated);
+                pcm.push(&decimated, output_buffer_capacity, shared);
+                pcm.pcm = decimated;
+            }
+        }
+    }
+
+    /// Flush any buffered audio at the end of the stream
+    fn finish(&mut self, output_buffer_capacity: usize, shared: &Arc<Mutex<SharedState>>) {
+        if let Renderer::Pcm(pcm) = self {
+            let mut decimated = std::mem::take(&mut pcm.pcm);
+            decimated.clear();
+            pcm.decimator.flush(&mut decimated);
+            pcm.push(&decimated, output_buffer_capacity, shared);
+            pcm.pcm = decimated;
+
+            LocalAudioSource::flush_resampler_static(
+                &mut pcm.input_buffer,
+                &mut pcm.resampler,
+                OUTPUT_CHANNELS,
+                RESAMPLER_CHUNK_FRAMES,
+                shared,
+            );
+        }
+    }
+}
+
+impl PcmRenderer {
+    /// Downmix decimated PCM to stereo and resample it into the output buffer
+    fn push(
+        &mut self,
+        decimated: &[f32],
+        output_buffer_capacity: usize,
+        shared: &Arc<Mutex<SharedState>>,
+    ) {
+        let stereo = downmix_to_stereo(decimated, self.decimator.channels());
+        if self.resampler.is_none() {
+            shared.lock().unwrap().output_buffer.extend(stereo);
+            return;
+        }
+
+        self.input_buffer.extend(stereo);
+        LocalAudioSource::process_resampling_static(
+            &mut self.input_buffer,
+            &mut self.resampler,
+            OUTPUT_CHANNELS,
+            RESAMPLER_CHUNK_FRAMES,
+            output_buffer_capacity,
+            shared,
+        );
+    }
+}
+
+/// Audio source for DSF and DSDIFF files
+///
+/// Decodes in a background thread; `read_samples()` only copies from the
+/// pre-filled buffer. See the module docs for the two output modes.
+pub struct DsdAudioSource {
+    path: PathBuf,
+    mode: DsdOutputMode,
+    /// Output sample rate (DoP carrier rate in DoP mode)
+    sample_rate: u32,
+    /// DSD sample rate of the file
+    dsd_rate: u32,
+
+    shared: Arc<Mutex<SharedState>>,
+    command_tx: Sender<DecoderCommand>,
+    _decoder_thread: JoinHandle<()>,
+
+    total_duration: Duration,
+    range: Option<TrackRange>,
+}
+
+impl DsdAudioSource {
+    /// Open a DSD file for playback
+    ///
+    /// # Arguments
+    /// * `path` - Path to the `.dsf` / `.dff` file
+    /// * `target_sample_rate` - Output sample rate of the device
+    /// * `range` - Part of the file to play (None = whole file)
+    /// * `allow_dop` - Whether the device accepts DoP
+    ///
+    /// DoP is used only when allowed, the file is stereo and the device runs
+    /// at the DoP carrier rate for the file's DSD rate; otherwise the file
+    /// is converted to PCM.
+    pub fn new(
+        path: impl AsRef<Path>,
+        target_sample_rate: u32,
+        range: Option<TrackRange>,
+        allow_dop: bool,
+    ) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+
+        let file = DsdFile::open(&path)
+            .map_err(|e| PlaybackError::AudioSource(format!("Failed to open DSD file: {}", e)))?;
+        let info = *file.info();
+
+        let dop_rate = info.dop().map(|dop| dop.pcm_rate_hz);
+        let mode = if allow_dop && info.channels == 2 && dop_rate == Some(target_sample_rate) {
+            DsdOutputMode::Dop
+        } else {
+            DsdOutputMode::Pcm
+        };
+
+        let renderer = match mode {
+            DsdOutputMode::Dop => Renderer::Dop {
+                encoder: DopEncoder::new(OUTPUT_CHANNELS),
+                interleaved: Vec::with_capacity(READ_CHUNK_BYTES * OUTPUT_CHANNELS),
+            },
+            DsdOutputMode::Pcm => {
+                let decimator = DsdToPcm::for_dsd_rate(info.sample_rate, info.channels)
+                    .map_err(|e| PlaybackError::AudioSource(e.to_string()))?;
+                let resampler = if decimator.pcm_rate() == target_sample_rate {
+                    None
+                } else {
+                    Some(
+                        LocalAudioSource::create_resampler(
+                            decimator.pcm_rate(),
+                            target_sample_rate,
+                            OUTPUT_CHANNELS,
+                            RESAMPLER_CHUNK_FRAMES,
+                        )
+                        .map_err(PlaybackError::AudioSource)?,
+                    )
+                };
+                Renderer::Pcm(Box::new(PcmRenderer {
+                    decimator,
+                    resampler,
+                    input_buffer: VecDeque::new(),
+                    pcm: Vec::new(),
+                }))
+            }
+        };
+
+        let file_duration = info.duration();
+        let total_duration = match range {
+            Some(range) => {
+                if range.start >= file_duration {
+                    return Err(PlaybackError::AudioSource(format!(
+                        "Track range starts at {:?}, past the end of the file ({:?})",
+                        range.start, file_duration
+                    )));
+                }
+                range.length(file_duration)
+            }
+            None => file_duration,
+        };
+
+        eprintln!("[DsdAudioSource] File info:");
+        eprintln!("  - Path: {}", path.display());
+        eprintln!("  - DSD rate: {} Hz", info.sample_rate);
+        eprintln!("  - Channels: {}", info.channels);
+        eprintln!("  - Output: {:?} at {} Hz", mode, target_sample_rate);
+
+        let output_buffer_capacity =
+            BUFFER_SIZE_SECONDS * target_sample_rate as usize * OUTPUT_CHANNELS;
+        let shared = Arc::new(Mutex::new(SharedState {
+            output_buffer: VecDeque::with_capacity(output_buffer_capacity),
+            samples_read: 0,
+            is_eof: false,
+            seek_pending: false,
+        }));
+
+        let (command_tx, command_rx) = bounded::<DecoderCommand>(4);
+
+        let shared_clone = shared.clone();
+        let decoder_thread = thread::Builder::new()
+            .name(format!("dsd-{}", path.file_name().unwrap_or_default().to_string_lossy()))
+            .spawn(move || {
+                Self::decoder_thread_main(
+                    file,
+                    renderer,
+                    target_sample_rate,
+                    output_buffer_capacity,
+                    range,
+                    shared_clone,
+                    command_rx,
+                );
+            })
+            .map_err(|e| PlaybackError::AudioSource(format!("Failed to spawn decoder thread: {}", e)))?;
+
+        // Wait for some initial data (up to 500ms) so the source is immediately playable
+        for _ in 0..50 {
+            let state = shared.lock().unwrap();
+            if state.output_buffer.len() >= 4800 || state.is_eof {
+                break;
+            }
+            drop(state);
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        Ok(Self {
+            path,
+            mode,
+            sample_rate: target_sample_rate,
+            dsd_rate: info.sample_rate,
+            shared,
+            command_tx,
+            _decoder_thread: decoder_thread,
+            total_duration,
+            range,
+        })
+    }
+
+    /// Background decoder thread main function
+    fn decoder_thread_main(
+        mut file: DsdFile,
+        mut renderer: Renderer,
+        target_sample_rate: u32,
+        output_buffer_capacity: usize,
+        range: Option<TrackRange>,
+        shared: Arc<Mutex<SharedState>>,
+        command_rx: Receiver<DecoderCommand>,
+    ) {
+        let info = *file.info();
+        let alignment = renderer.alignment();
+
+        // Range boundaries in bytes per channel, aligned to whole output frames
+        let to_byte = |frame: u64| frame / 8 / alignment * alignment;
+        let range_start = range
+            .map(|r| to_byte(r.start_frame(info.sample_rate)))
+            .unwrap_or(0);
+        let end_byte = range
+            .and_then(|r| r.end_frame(info.sample_rate))
+            .map(to_byte)
+            .unwrap_or(u64::MAX)
+            .min(info.bytes_per_channel());
+
+        if range_start > 0 {
+            if let Err(e) = file.seek(range_start) {
+                eprintln!("[DsdDecoder] Seek to range start failed: {}", e);
+            }
+        }
+
+        let mut buffers: Vec<Vec<u8>> = vec![Vec::with_capacity(READ_CHUNK_BYTES); info.channels];
+        let mut is_eof = false;
+
+        loop {
+            match command_rx.try_recv() {
+                Ok(DecoderCommand::Stop) => break,
+                Ok(DecoderCommand::Seek(position)) => {
+                    let offset =
+                        to_byte((position.as_secs_f64() * info.sample_rate as f64) as u64);
+                    match file.seek(range_start + offset) {
+                        Ok(()) => {
+                            renderer.reset();
+                            is_eof = false;
+
+                            let mut state = shared.lock().unwrap();
+                            state.output_buffer.clear();
+                            state.samples_read = (position.as_secs_f64()
+                                * target_sample_rate as f64
+                                * OUTPUT_CHANNELS as f64)
+                                as usize;
+                            state.is_eof = false;
+                        }
+                        Err(e) => eprintln!("[DsdDecoder] Seek failed: {}", e),
+                    }
+                    shared.lock().unwrap().seek_pending = false;
+                }
+                Err(crossbeam_channel::TryRecvError::Empty) => {}
+                Err(crossbeam_channel::TryRecvError::Disconnected) => {
+                    eprintln!("[DsdDecoder] Command channel disconnected, exiting");
+                    break;
+                }
+            }
+
+            let buffer_len = shared.lock().unwrap().output_buffer.len();
+            if buffer_len >= output_buffer_capacity / 2 {
+                thread::sleep(Duration::from_millis(10));
+                continue;
+            }
+
+            if is_eof {
+                thread::sleep(Duration::from_millis(50));
+                continue;
+            }
+
+            let remaining = end_byte.saturating_sub(file.position());
+            let wanted = (READ_CHUNK_BYTES as u64).min(remaining) as usize;
+
+            for buffer in &mut buffers {
+                buffer.clear();
+            }
+            let read = match file.read(&mut buffers, wanted) {
+                Ok(read) => read,
+                Err(e) => {
+                    eprintln!("[DsdDecoder] Read error: {}", e);
+                    0
+                }
+            };
+
+            if read > 0 {
+                renderer.render(&buffers, output_buffer_capacity, &shared);
+            }
+
+            if read < READ_CHUNK_BYTES {
+                is_eof = true;
+                renderer.finish(output_buffer_capacity, &shared);
+                shared.lock().unwrap().is_eof = true;
+            }
+        }
+    }
+
+    /// Get file path
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get output mode (DoP or PCM)
+    pub fn mode(&self) -> DsdOutputMode {
+        self.mode
+    }
+
+    /// Get output sample rate
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Get the file's DSD sample rate
+    pub fn dsd_rate(&self) -> u32 {
+        self.dsd_rate
+    }
+
+    /// Get the part of the file being played (None = whole file)
+    pub fn range(&self) -> Option<TrackRange> {
+        self.range
+    }
+}
+
+impl AudioSource for DsdAudioSource {
+    fn read_samples(&mut self, output: &mut [f32]) -> Result<usize> {
+        let mut state = self.shared.lock().unwrap();
+
+        let available = state.output_buffer.len().min(output.len());
+        for (dst, src) in output.iter_mut().zip(state.output_buffer.drain(..available)) {
+            *dst = src;
+        }
+        state.samples_read += available;
+
+        if available < output.len() {
+            output[available..].fill(0.0);
+        }
+
+        Ok(available)
+    }
+
+    fn seek(&mut self, position: Duration) -> Result<()> {
+        if position > self.total_duration {
+            return Err(PlaybackError::InvalidSeekPosition(position));
+        }
+
+        self.shared.lock().unwrap().seek_pending = true;
+
+        self.command_tx
+            .send(DecoderCommand::Seek(position))
+            .map_err(|e| PlaybackError::AudioSource(format!("Failed to send seek command: {}", e)))
+    }
+
+    fn duration(&self) -> Duration {
+        self.total_duration
+    }
+
+    fn position(&self) -> Duration {
+        let state = self.shared.lock().unwrap();
+        let frames = state.samples_read / OUTPUT_CHANNELS;
+        Duration::from_secs_f64(frames as f64 / self.sample_rate as f64)
+    }
+
+    fn is_finished(&self) -> bool {
+        let state = self.shared.lock().unwrap();
+        state.is_eof && !state.seek_pending && state.output_buffer.is_empty()
+    }
+
+    fn is_bitstream(&self) -> bool {
+        self.mode == DsdOutputMode::Dop
+    }
+}
+
+impl Drop for DsdAudioSource {
+    fn drop(&mut self) {
+        let _ = self.command_tx.send(DecoderCommand::Stop);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dsd_source_implements_audio_source() {
+        fn assert_audio_source<T: AudioSource>() {}
+        assert_audio_source::<DsdAudioSource>();
+    }
+
+    #[test]
+    fn missing_file_is_an_error() {
+        let result = DsdAudioSource::new("/nonexistent/file.dsf", 44100, None, false);
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,270 @@
+//! HTTP media source for decoding remote audio files
+//!
+//! Adapts an HTTP response body to Symphonia's `MediaSource` so that
+//! compressed files served by `soul-server` (FLAC, MP3, Ogg, ...) can be
+//! probed and decoded exactly like local files.
+//!
+//! Seeking is implemented with HTTP Range requests: a seek drops the current
+//! response and the next read issues `Range: bytes=<offset>-`. Short forward
+//! seeks (common while Symphonia probes a container) are served by skipping
+//! bytes on the open connection instead of reconnecting.
+
+use reqwest::blocking::{Client, Response};
+use reqwest::header::{ACCEPT_RANGES, CONTENT_RANGE, RANGE};
+use reqwest::StatusCode;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::time::Duration;
+use symphonia::core::io::MediaSource;
+
+/// Forward seeks up to this many bytes are served by reading and discarding
+/// from the open response rather than issuing a new Range request.
+const MAX_SKIP_BYTES: u64 = 64 * 1024;
+
+/// Timeout for establishing a connection to the server
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Seekable HTTP byte stream implementing Symphonia's `MediaSource`
+pub struct HttpMediaSource {
+    /// HTTP client (keeps the connection pool alive across Range requests)
+    client: Client,
+
+    /// URL of the remote file
+    url: String,
+
+    /// Currently open response body (None after a seek until the next read)
+    response: Option<Response>,
+
+    /// Current byte offset in the remote file
+    position: u64,
+
+    /// Total size of the remote file, if the server reported it
+    content_length: Option<u64>,
+
+    /// Whether the server advertised `Accept-Ranges: bytes`
+    supports_ranges: bool,
+}
+
+impl HttpMediaSource {
+    /// Open a remote file
+    ///
+    /// Issues the initial GET request and reads the file size and Range
+    /// support from the response headers. The response body is kept open
+    /// and consumed by subsequent reads.
+    pub fn open(url: impl Into<String>) -> io::Result<Self> {
+        let url = url.into();
+
+        // No overall timeout: the body of a long track is read over minutes
+        let client = Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .timeout(None)
+            .build()
+            .map_err(to_io_error)?;
+
+        let response = Self::request(&client, &url, 0)?;
+
+        let content_length = response.content_length();
+        let supports_ranges = response
+            .headers()
+            .get(ACCEPT_RANGES)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.eq_ignore_ascii_case("bytes"));
+
+        Ok(Self {
+            client,
+            url,
+            response: Some(response),
+            position: 0,
+            content_length,
+            supports_ranges,
+        })
+    }
+
+    /// Send a GET request starting at `offset`
+    ///
+    /// Requests with a non-zero offset must be answered with
+    /// `206 Partial Content` starting at exactly that offset.
+    fn request(client: &Client, url: &str, offset: u64) -> io::Result<Response> {
+        let mut request = client.get(url);
+        if offset > 0 {
+            request = request.header(RANGE, format!("bytes={}-", offset));
+        }
+
+        let response = request.send().map_err(to_io_error)?;
+        let status = response.status();
+
+        if offset == 0 {
+            if !status.is_success() {
+                return Err(io::Error::other(format!("HTTP error: {}", status)));
+            }
+            return Ok(response);
+        }
+
+        if status != StatusCode::PARTIAL_CONTENT {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("Server did not honour Range request (HTTP {})", status),
+            ));
+        }
+
+        // Guard against servers that answer 206 but start somewhere else
+        let range_start = response
+            .headers()
+            .get(CONTENT_RANGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse_content_range_start);
+        if range_start.is_some_and(|start| start != offset) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Range response starts at {:?}, expected {}",
+                    range_start, offset
+                ),
+            ));
+        }
+
+        Ok(response)
+    }
+
+    /// URL of the remote file
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Current byte offset
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Total size of the remote file, if known
+    pub fn content_length(&self) -> Option<u64> {
+        self.content_length
+    }
+
+    /// Whether the server supports Range requests
+    pub fn supports_ranges(&self) -> bool {
+        self.supports_ranges
+    }
+
+    /// Read and discard `count` bytes from the open response
+    fn skip_forward(&mut self, count: u64) -> io::Result<()> {
+        let Some(response) = self.response.as_mut() else {
+            return Ok(());
+        };
+
+        let skipped = io::copy(&mut response.by_ref().take(count), &mut io::sink())?;
+        if skipped < count {
+            // Body ended early; reconnect on next read
+            self.response = None;
+        }
+        Ok(())
+    }
+}
+
+impl Read for HttpMediaSource {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        if self
+            .content_length
+            .is_some_and(|len| self.position >= len)
+        {
+            return Ok(0);
+        }
+
+        if self.response.is_none() {
+            self.response = Some(Self::request(&self.client, &self.url, self.position)?);
+        }
+
+        let read = self
+            .response
+            .as_mut()
+            .map(|r| r.read(buf))
+            .unwrap_or(Ok(0))?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for HttpMediaSource {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+            SeekFrom::End(delta) => {
+                let len = self.content_length.ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::Unsupported,
+                        "Cannot seek from end: content length unknown",
+                    )
+                })?;
+                len.checked_add_signed(delta)
+            }
+        }
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Invalid seek position"))?;
+
+        if target == self.position {
+            return Ok(target);
+        }
+
+        if target > self.position
+            && target - self.position <= MAX_SKIP_BYTES
+            && self.response.is_some()
+        {
+            self.skip_forward(target - self.position)?;
+        } else {
+            if !self.supports_ranges {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "Server does not support Range requests",
+                ));
+            }
+            // Reconnect lazily at the new offset on the next read
+            self.response = None;
+        }
+
+        self.position = target;
+        Ok(target)
+    }
+}
+
+impl MediaSource for HttpMediaSource {
+    fn is_seekable(&self) -> bool {
+        self.supports_ranges && self.content_length.is_some()
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        self.content_length
+    }
+}
+
+/// Parse the start offset from a `Content-Range: bytes start-end/total` header
+fn parse_content_range_start(value: &str) -> Option<u64> {
+    let range = value.trim().strip_prefix("bytes")?.trim_start();
+    let (start, _) = range.split_once('-')?;
+    start.trim().parse().ok()
+}
+
+fn to_io_error(err: reqwest::Error) -> io::Error {
+    io::Error::other(err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_content_range() {
+        assert_eq!(parse_content_range_start("bytes 100-199/1000"), Some(100));
+        assert_eq!(parse_content_range_start("bytes 0-999/*"), Some(0));
+        assert_eq!(parse_content_range_start("bytes */1000"), None);
+        assert_eq!(parse_content_range_start("items 1-2/3"), None);
+    }
+
+    #[test]
+    fn open_unreachable_url_fails() {
+        let result = HttpMediaSource::open("http://127.0.0.1:9/nonexistent.flac");
+        assert!(result.is_err());
+    }
+}
@@ -8,7 +8,8 @@
 //! ## Supported Formats
 //! - **Containers**: MP3, FLAC, OGG, WAV, AAC, OPUS, M4A, etc.
 //! - **Sample types**: All Symphonia formats (F32, F64, S8, S16, S24, S32, U8, U16, U24, U32)
-//! - **Channel layouts**: Mono (duplicated to stereo), Stereo, Multi-channel (mixed to stereo)
+//! - **Channel layouts**: Mono, Stereo, Quad, 5.1, 7.1 - remixed to the output layout
+//!   (ITU-R BS.775 downmix by default, see [`soul_audio::channels`])
 //!
 //! ## Architecture
 //!
@@ -37,12 +38,18 @@
 //! 3. **Format Conversion** (`convert_to_f32_interleaved`):
 //!    - Handles all Symphonia sample formats
 //!    - Normalizes to [-1.0, 1.0] range
+//!
+//! 4. **Channel Remixing** (`ChannelMatrix`):
+//!    - Maps the file's channel layout to the output channel count
+//!    - Runs before resampling, so everything downstream sees output channels
 
 use crossbeam_channel::{bounded, Receiver, Sender};
 use rubato::{
     Resampler, SincFixedIn, SincInterpolationParameters, SincInterpolationType, WindowFunction,
 };
-use soul_playback::{AudioSource, PlaybackError, Result};
+use soul_audio::channels::{ChannelLayout, ChannelMatrix, DownmixSettings};
+use soul_audio::encoder_delay::{self, DelayTrimmer, EncoderDelay};
+use soul_playback::{AudioSource, PlaybackError, Result, TrackRange};
 use std::collections::VecDeque;
 use std::fs::File;
 use std::path::{Path, PathBuf};
@@ -66,7 +73,7 @@ const MIN_BUFFER_SAMPLES: usize = 4800;
 
 /// Commands sent to the decoder thread
 #[derive(Debug)]
-enum DecoderCommand {
+pub(super) enum DecoderCommand {
     /// Seek to a position
     Seek(Duration),
     /// Stop decoding and exit thread
@@ -74,15 +81,15 @@ enum DecoderCommand {
 }
 
 /// Shared state between audio thread and decoder thread
-struct SharedState {
+pub(super) struct SharedState {
     /// Ring buffer for resampled samples (at TARGET rate, ready for output)
-    output_buffer: VecDeque<f32>,
+    pub(super) output_buffer: VecDeque<f32>,
     /// Total samples read by audio callback (at target rate)
-    samples_read: usize,
+    pub(super) samples_read: usize,
     /// Whether decoder has reached end of file
-    is_eof: bool,
+    pub(super) is_eof: bool,
     /// Whether a seek is pending (decoder will reset)
-    seek_pending: bool,
+    pub(super) seek_pending: bool,
 }
 
 /// Audio source for local files with background decoder thread
@@ -97,7 +104,8 @@ pub struct LocalAudioSource {
     path: PathBuf,
     source_sample_rate: u32, // Sample rate of the audio file
     target_sample_rate: u32, // Target output sample rate
-    channels: u16,
+    source_channels: u16,    // Channel count of the audio file
+    output_channels: u16,    // Channel count delivered by read_samples
 
     // Shared state with decoder thread (protected by mutex)
     shared: Arc<Mutex<SharedState>>,
@@ -110,6 +118,9 @@ pub struct LocalAudioSource {
     // Position tracking
     total_duration: Duration,
     needs_resampling: bool,
+
+    /// Sub-range of the file being played (None = whole file)
+    range: Option<TrackRange>,
 }
 
 impl LocalAudioSource {
@@ -126,7 +137,54 @@ impl LocalAudioSource {
     /// * `Ok(source)` - Audio source ready for streaming playback
     /// * `Err(_)` - Failed to open or probe file
     pub fn new(path: impl AsRef<Path>, target_sample_rate: u32) -> Result<Self> {
+        Self::with_range(path, target_sample_rate, None)
+    }
+
+    /// Create a source that plays only part of a file
+    ///
+    /// Used for virtual tracks from CUE sheets. Playback starts and stops at
+    /// the exact sample boundaries of `range`, and duration, position and
+    /// seeking are all relative to the start of the range.
+    ///
+    /// # Arguments
+    /// * `path` - Path to audio file
+    /// * `target_sample_rate` - Target output sample rate (e.g., 44100, 48000)
+    /// * `range` - Part of the file to play (None = whole file)
+    pub fn with_range(
+        path: impl AsRef<Path>,
+        target_sample_rate: u32,
+        range: Option<TrackRange>,
+    ) -> Result<Self> {
+        Self::with_layout(
+            path,
+            target_sample_rate,
+            range,
+            2,
+            DownmixSettings::default(),
+        )
+    }
+
+    /// Create a source that delivers a specific number of output channels
+    ///
+    /// The file's channel layout is remixed to `output_channels` in the
+    /// decoder thread: surround files are downmixed with `downmix` on stereo
+    /// devices and play natively on 5.1/7.1 devices.
+    ///
+    /// # Arguments
+    /// * `path` - Path to audio file
+    /// * `target_sample_rate` - Target output sample rate (e.g., 44100, 48000)
+    /// * `range` - Part of the file to play (None = whole file)
+    /// * `output_channels` - Channel count of the output device
+    /// * `downmix` - Coefficients used when folding down channels
+    pub fn with_layout(
+        path: impl AsRef<Path>,
+        target_sample_rate: u32,
+        range: Option<TrackRange>,
+        output_channels: u16,
+        downmix: DownmixSettings,
+    ) -> Result<Self> {
         let path = path.as_ref().to_path_buf();
+        let output_channels = output_channels.max(1);
 
         // Open the file and probe it to get metadata
         let file = File::open(&path)
@@ -139,7 +197,7 @@ impl LocalAudioSource {
             hint.with_extension(ext);
         }
 
-        let probed = symphonia::default::get_probe()
+        let mut probed = symphonia::default::get_probe()
             .format(
                 &hint,
                 mss,
@@ -148,6 +206,7 @@ impl LocalAudioSource {
             )
             .map_err(|e| PlaybackError::AudioSource(format!("Failed to probe file: {}", e)))?;
 
+        let delay = EncoderDelay::from_probe(&mut probed);
         let format_reader = probed.format;
 
         let track = format_reader
@@ -162,24 +221,54 @@ impl LocalAudioSource {
             .time_base
             .unwrap_or(TimeBase::new(1, sample_rate));
 
-        let total_duration = track
+        // Encoder delay and padding are trimmed in the decoder thread, so
+        // durations and positions are on the trimmed timeline
+        let trimmer = delay
+            .has_padding()
+            .then(|| DelayTrimmer::new(delay, track.codec_params.n_frames.unwrap_or(u64::MAX)));
+
+        let file_duration = track
             .codec_params
             .n_frames
+            .map(|frames| trimmer.as_ref().map_or(frames, DelayTrimmer::valid_samples))
             .map(|frames| Duration::from_secs_f64(frames as f64 / sample_rate as f64))
             .unwrap_or(Duration::MAX);
 
+        let total_duration = match range {
+            Some(range) => {
+                if range.start >= file_duration {
+                    return Err(PlaybackError::AudioSource(format!(
+                        "Track range starts at {:?}, past the end of the file ({:?})",
+                        range.start, file_duration
+                    )));
+                }
+                range.length(file_duration)
+            }
+            None => file_duration,
+        };
+
         let needs_resampling = sample_rate != target_sample_rate;
 
         eprintln!("[LocalAudioSource] File info:");
         eprintln!("  - Path: {}", path.display());
         eprintln!("  - Source sample rate: {} Hz", sample_rate);
         eprintln!("  - Target sample rate: {} Hz", target_sample_rate);
-        eprintln!("  - Channels: {}", channels);
+        eprintln!("  - Channels: {} (output: {})", channels, output_channels);
         eprintln!("  - Needs resampling: {}", needs_resampling);
+        if let Some(range) = range {
+            eprintln!("  - Range: {:?} - {:?}", range.start, range.end);
+        }
+        if let Some(ref trimmer) = trimmer {
+            eprintln!(
+                "  - Encoder delay: {} / padding: {} samples",
+                trimmer.delay().start_padding,
+                trimmer.delay().end_padding
+            );
+        }
 
-        // Calculate buffer capacity (5 seconds of stereo audio at target sample rate)
+        // Calculate buffer capacity (5 seconds of output audio at target sample rate)
         let output_buffer_capacity =
-            (BUFFER_SIZE_SECONDS * target_sample_rate as usize) * channels as usize;
+            (BUFFER_SIZE_SECONDS * target_sample_rate as usize) * output_channels as usize;
 
         // Create shared state
         let shared = Arc::new(Mutex::new(SharedState {
@@ -204,10 +293,13 @@ impl LocalAudioSource {
                     path_clone,
                     sample_rate,
                     target_sample_rate,
-                    channels,
+                    output_channels,
+                    downmix,
                     track_id,
                     time_base,
                     output_buffer_capacity,
+                    range,
+                    trimmer,
                     shared_clone,
                     command_rx,
                 );
@@ -235,13 +327,15 @@ impl LocalAudioSource {
             path,
             source_sample_rate: sample_rate,
             target_sample_rate,
-            channels,
+            source_channels: channels,
+            output_channels,
             shared,
             output_buffer_capacity,
             command_tx,
             _decoder_thread: decoder_thread,
             total_duration,
             needs_resampling,
+            range,
         })
     }
 
@@ -249,14 +343,27 @@ impl LocalAudioSource {
     ///
     /// Continuously decodes packets and fills the output buffer.
     /// Handles seek commands and stops when requested.
+    ///
+    /// Encoder delay and padding are dropped first (when `trimmer` is set),
+    /// so ranges, seeks and positions all refer to the trimmed timeline.
+    ///
+    /// When a range is given, decoded packets are trimmed to the exact range
+    /// boundaries and seek positions are offset by the range start.
+    ///
+    /// Decoded audio is remixed to `channels` (the output channel count)
+    /// before resampling.
+    #[allow(clippy::too_many_arguments)]
     fn decoder_thread_main(
         path: PathBuf,
         source_sample_rate: u32,
         target_sample_rate: u32,
         channels: u16,
+        downmix: DownmixSettings,
         track_id: u32,
         time_base: TimeBase,
         output_buffer_capacity: usize,
+        range: Option<TrackRange>,
+        mut trimmer: Option<DelayTrimmer>,
         shared: Arc<Mutex<SharedState>>,
         command_rx: Receiver<DecoderCommand>,
     ) {
@@ -298,8 +405,9 @@ impl LocalAudioSource {
                 return;
             }
         };
+        let preroll = encoder_delay::seek_preroll(&track.codec_params);
 
-        let mut decoder = match symphonia::default::get_codecs()
+        let mut decoder = match soul_audio::codecs::registry()
             .make(&track.codec_params, &DecoderOptions::default())
         {
             Ok(d) => d,
@@ -314,22 +422,11 @@ impl LocalAudioSource {
         let resampler_chunk_frames = 1024;
 
         let mut resampler = if needs_resampling {
-            let params = SincInterpolationParameters {
-                sinc_len: 256,
-                f_cutoff: 0.95,
-                interpolation: SincInterpolationType::Linear,
-                oversampling_factor: 256,
-                window: WindowFunction::BlackmanHarris2,
-            };
-
-            let resample_ratio = target_sample_rate as f64 / source_sample_rate as f64;
-
-            match SincFixedIn::<f32>::new(
-                resample_ratio,
-                2.0,
-                params,
-                resampler_chunk_frames,
+            match Self::create_resampler(
+                source_sample_rate,
+                target_sample_rate,
                 channels as usize,
+                resampler_chunk_frames,
             ) {
                 Ok(r) => {
                     let delay = r.output_delay();
@@ -357,10 +454,45 @@ impl LocalAudioSource {
             .unwrap_or(0);
         let mut samples_to_skip = resampler_delay_samples;
 
+        // Remix from the file layout to the output layout, rebuilt if a
+        // decoded packet reports a different channel count
+        let output_layout = ChannelLayout::from_channel_count(channels);
+        let mut matrix = ChannelMatrix::new(output_layout, output_layout, &downmix);
+
         // Input buffer for accumulating samples before resampling
         let mut input_buffer: VecDeque<f32> = VecDeque::with_capacity(resampler_chunk_frames * channels as usize * 4);
         let mut is_eof = false;
 
+        // Range boundaries in source frames; decoded frames before `skip_until`
+        // (range start or seek target) are dropped, as is everything from `end_frame`.
+        //
+        // Seeks land on the sync point before the target, far enough ahead for
+        // the decoder to warm up, so playback resumes on the exact sample.
+        let range_start_frame = range.map(|r| r.start_frame(source_sample_rate)).unwrap_or(0);
+        let end_frame = range.and_then(|r| r.end_frame(source_sample_rate));
+        let mut skip_until = range_start_frame;
+        let start_padding = trimmer
+            .as_ref()
+            .map_or(0, |trimmer| u64::from(trimmer.delay().start_padding));
+
+        if range_start_frame > 0 {
+            if let Err(e) = format_reader.seek(
+                symphonia::core::formats::SeekMode::Accurate,
+                symphonia::core::formats::SeekTo::TimeStamp {
+                    ts: Self::seek_timestamp(
+                        range_start_frame + start_padding,
+                        preroll,
+                        time_base,
+                        source_sample_rate,
+                    ),
+                    track_id,
+                },
+            ) {
+                // Fall back to decoding from the beginning and discarding up to the start
+                eprintln!("[DecoderThread] Seek to range start failed: {}", e);
+            }
+        }
+
         eprintln!("[DecoderThread] Decoder thread ready, starting decode loop");
 
         loop {
@@ -373,34 +505,55 @@ impl LocalAudioSource {
                 Ok(DecoderCommand::Seek(position)) => {
                     eprintln!("[DecoderThread] Seek command: {:?}", position);
 
-                    // Perform seek
-                    let seek_ts = time_base.calc_timestamp(position.into());
-                    if let Err(e) = format_reader.seek(
+                    // Perform seek (positions are relative to the range start)
+                    let target_frame = range_start_frame
+                        + (position.as_secs_f64() * source_sample_rate as f64).round() as u64;
+                    let seek_ts = Self::seek_timestamp(
+                        target_frame + start_padding,
+                        preroll,
+                        time_base,
+                        source_sample_rate,
+                    );
+                    match format_reader.seek(
                         symphonia::core::formats::SeekMode::Accurate,
                         symphonia::core::formats::SeekTo::TimeStamp {
                             ts: seek_ts,
                             track_id,
                         },
                     ) {
-                        eprintln!("[DecoderThread] Seek failed: {}", e);
-                    } else {
-                        decoder.reset();
-                        input_buffer.clear();
-                        if let Some(ref mut r) = resampler {
-                            r.reset();
-                            // Reset delay skip counter after resampler reset
-                            samples_to_skip = resampler_delay_samples;
+                        Err(e) => eprintln!("[DecoderThread] Seek failed: {}", e),
+                        Ok(seeked) => {
+                            // If the demuxer landed past the target, resume from
+                            // where it actually is
+                            let landed = Self::timestamp_to_frame(
+                                seeked.actual_ts,
+                                time_base,
+                                source_sample_rate,
+                            )
+                            .saturating_sub(start_padding);
+                            let target_frame = target_frame.max(landed);
+                            let seconds = (target_frame - range_start_frame) as f64
+                                / source_sample_rate as f64;
+
+                            decoder.reset();
+                            input_buffer.clear();
+                            if let Some(ref mut r) = resampler {
+                                r.reset();
+                                // Reset delay skip counter after resampler reset
+                                samples_to_skip = resampler_delay_samples;
+                            }
+                            is_eof = false;
+                            skip_until = target_frame;
+
+                            // Clear output buffer and update position (whole frames)
+                            let mut state = shared.lock().unwrap();
+                            state.output_buffer.clear();
+                            state.samples_read = (seconds * target_sample_rate as f64).round()
+                                as usize
+                                * channels as usize;
+                            state.is_eof = false;
+                            state.seek_pending = false;
                         }
-                        is_eof = false;
-
-                        // Clear output buffer and update position
-                        let mut state = shared.lock().unwrap();
-                        state.output_buffer.clear();
-                        state.samples_read = (position.as_secs_f64()
-                            * target_sample_rate as f64
-                            * channels as f64) as usize;
-                        state.is_eof = false;
-                        state.seek_pending = false;
                     }
                 }
                 Err(crossbeam_channel::TryRecvError::Empty) => {
@@ -466,6 +619,27 @@ impl LocalAudioSource {
                 continue;
             }
 
+            // Packet position on the trimmed timeline
+            let raw_start = Self::timestamp_to_frame(packet.ts(), time_base, source_sample_rate);
+            let packet_start = match trimmer {
+                Some(ref mut trimmer) => {
+                    trimmer.set_raw_position(raw_start);
+                    raw_start.saturating_sub(u64::from(trimmer.delay().start_padding))
+                }
+                None => raw_start,
+            };
+            if end_frame.is_some_and(|end| packet_start >= end) {
+                Self::finish_range(
+                    &mut is_eof,
+                    &mut input_buffer,
+                    &mut resampler,
+                    channels as usize,
+                    resampler_chunk_frames,
+                    &shared,
+                );
+                continue;
+            }
+
             // Decode the packet
             let decoded = match decoder.decode(&packet) {
                 Ok(d) => d,
@@ -474,9 +648,12 @@ impl LocalAudioSource {
                     continue;
                 }
             };
+            let mut packet_frames = decoded.frames() as u64;
+
+            let decoded_channels = decoded.spec().channels.count() as u16;
 
             // Convert to f32 samples
-            let samples = match Self::convert_to_f32_interleaved(decoded, channels) {
+            let samples = match Self::convert_to_f32_interleaved(decoded) {
                 Ok(s) => s,
                 Err(e) => {
                     eprintln!("[DecoderThread] Conversion error: {}", e);
@@ -484,6 +661,47 @@ impl LocalAudioSource {
                 }
             };
 
+            // Remix to the output channel count
+            if matrix.input().channel_count() != decoded_channels {
+                let input_layout = ChannelLayout::from_channel_count(decoded_channels);
+                eprintln!(
+                    "[DecoderThread] Remixing {} -> {}",
+                    input_layout, output_layout
+                );
+                matrix = ChannelMatrix::new(input_layout, output_layout, &downmix);
+            }
+            let mut samples = if matrix.is_identity() {
+                samples
+            } else {
+                let mut remixed = vec![0.0; packet_frames as usize * channels as usize];
+                let frames = matrix.process(&samples, &mut remixed);
+                remixed.truncate(frames * channels as usize);
+                remixed
+            };
+
+            // Drop encoder delay and padding
+            if let Some(ref mut trimmer) = trimmer {
+                let keep = trimmer.trim(packet_frames as usize);
+                if packet_frames > 0 && keep.len() as u64 != packet_frames {
+                    let samples_per_frame = samples.len() / packet_frames as usize;
+                    samples.truncate(keep.end * samples_per_frame);
+                    samples.drain(..keep.start * samples_per_frame);
+                }
+                packet_frames = keep.len() as u64;
+            }
+
+            // Trim to the range start / seek target and the range end
+            let keep_from = skip_until.saturating_sub(packet_start).min(packet_frames);
+            let keep_to = end_frame
+                .map(|end| end.saturating_sub(packet_start).min(packet_frames))
+                .unwrap_or(packet_frames);
+            let reached_end = keep_to < packet_frames;
+            if packet_frames > 0 && (keep_from > 0 || reached_end) {
+                let samples_per_frame = samples.len() / packet_frames as usize;
+                samples.truncate(keep_to as usize * samples_per_frame);
+                samples.drain(..(keep_from.min(keep_to) as usize * samples_per_frame));
+            }
+
             if needs_resampling {
                 // Add samples to input buffer
                 for sample in samples {
@@ -509,13 +727,81 @@ impl LocalAudioSource {
                     }
                 }
             }
+
+            if reached_end {
+                Self::finish_range(
+                    &mut is_eof,
+                    &mut input_buffer,
+                    &mut resampler,
+                    channels as usize,
+                    resampler_chunk_frames,
+                    &shared,
+                );
+            }
         }
 
         eprintln!("[DecoderThread] Decoder thread exiting");
     }
 
+    /// Timestamp to seek to for playing from `file_frame` (a frame of the
+    /// decoded file, before trimming)
+    ///
+    /// Lands `preroll` frames early so the decoder has warmed up by the
+    /// time it reaches the frame; rounds down so it never lands late.
+    fn seek_timestamp(file_frame: u64, preroll: u64, time_base: TimeBase, sample_rate: u32) -> u64 {
+        (u128::from(file_frame.saturating_sub(preroll)) * u128::from(time_base.denom)
+            / (u128::from(time_base.numer) * u128::from(sample_rate))) as u64
+    }
+
+    /// Convert a packet timestamp to a frame position at the source sample rate
+    fn timestamp_to_frame(ts: u64, time_base: TimeBase, sample_rate: u32) -> u64 {
+        (u128::from(ts) * u128::from(time_base.numer) * u128::from(sample_rate)
+            / u128::from(time_base.denom)) as u64
+    }
+
+    /// Mark the end of a track range as end of stream
+    fn finish_range(
+        is_eof: &mut bool,
+        input_buffer: &mut VecDeque<f32>,
+        resampler: &mut Option<SincFixedIn<f32>>,
+        channels: usize,
+        chunk_frames: usize,
+        shared: &Arc<Mutex<SharedState>>,
+    ) {
+        *is_eof = true;
+        Self::flush_resampler_static(input_buffer, resampler, channels, chunk_frames, shared);
+
+        let mut state = shared.lock().unwrap();
+        state.is_eof = true;
+        eprintln!("[DecoderThread] Reached end of track range");
+    }
+
+    /// Create the sinc resampler used by decoder threads
+    ///
+    /// Shared with `StreamingAudioSource` so local and remote tracks are
+    /// converted to the device rate with identical quality.
+    pub(super) fn create_resampler(
+        source_sample_rate: u32,
+        target_sample_rate: u32,
+        channels: usize,
+        chunk_frames: usize,
+    ) -> std::result::Result<SincFixedIn<f32>, String> {
+        let params = SincInterpolationParameters {
+            sinc_len: 256,
+            f_cutoff: 0.95,
+            interpolation: SincInterpolationType::Linear,
+            oversampling_factor: 256,
+            window: WindowFunction::BlackmanHarris2,
+        };
+
+        let resample_ratio = target_sample_rate as f64 / source_sample_rate as f64;
+
+        SincFixedIn::<f32>::new(resample_ratio, 2.0, params, chunk_frames, channels)
+            .map_err(|e| e.to_string())
+    }
+
     /// Static version of process_resampling for use in decoder thread
-    fn process_resampling_static(
+    pub(super) fn process_resampling_static(
         input_buffer: &mut VecDeque<f32>,
         resampler: &mut Option<SincFixedIn<f32>>,
         channels: usize,
@@ -561,7 +847,7 @@ impl LocalAudioSource {
     }
 
     /// Static version of flush_resampler for use in decoder thread
-    fn flush_resampler_static(
+    pub(super) fn flush_resampler_static(
         input_buffer: &mut VecDeque<f32>,
         resampler: &mut Option<SincFixedIn<f32>>,
         channels: usize,
@@ -617,34 +903,26 @@ impl LocalAudioSource {
         }
     }
 
-    /// Generic helper to interleave planar audio buffer to stereo f32
+    /// Generic helper to interleave planar audio buffer to f32
     ///
     /// Takes any planar buffer type and a normalization function,
-    /// converts to interleaved stereo f32 format with mono->stereo duplication.
+    /// converts to interleaved f32 format keeping every channel of the buffer.
     ///
     /// # Type Parameters
     /// * `T` - Sample type (i8, i16, i32, u8, u16, u32, f32, f64, etc.)
     /// * `F` - Normalization function: T -> f32 in range [-1.0, 1.0]
-    fn interleave_to_stereo_f32<T, F>(
-        buf: &symphonia::core::audio::AudioBuffer<T>,
-        normalize: F,
-    ) -> Vec<f32>
+    fn interleave_to_f32<T, F>(buf: &symphonia::core::audio::AudioBuffer<T>, normalize: F) -> Vec<f32>
     where
         T: symphonia::core::sample::Sample,
         F: Fn(T) -> f32,
     {
         let channels = buf.spec().channels.count();
         let frames = buf.frames();
-        let mut output = Vec::with_capacity(frames * 2);
+        let mut output = Vec::with_capacity(frames * channels);
 
         for frame_idx in 0..frames {
-            // Left channel
-            output.push(normalize(buf.chan(0)[frame_idx]));
-            // Right channel (duplicate left if mono)
-            if channels > 1 {
-                output.push(normalize(buf.chan(1)[frame_idx]));
-            } else {
-                output.push(normalize(buf.chan(0)[frame_idx]));
+            for ch in 0..channels {
+                output.push(normalize(buf.chan(ch)[frame_idx]));
             }
         }
 
@@ -658,48 +936,45 @@ impl LocalAudioSource {
     /// - Signed int: S8, S16, S24, S32
     /// - Unsigned int: U8, U16, U24, U32
     ///
-    /// All formats are normalized to [-1.0, 1.0] and converted to stereo.
-    fn convert_to_f32_interleaved(
-        decoded: AudioBufferRef,
-        _target_channels: u16,
-    ) -> Result<Vec<f32>> {
+    /// All formats are normalized to [-1.0, 1.0], keeping the buffer's channels.
+    fn convert_to_f32_interleaved(decoded: AudioBufferRef) -> Result<Vec<f32>> {
         let output = match decoded {
             // Float formats - clamp to [-1.0, 1.0] to handle intersample peaks
             AudioBufferRef::F32(buf) => {
-                Self::interleave_to_stereo_f32(&buf, |s| s.clamp(-1.0, 1.0))
+                Self::interleave_to_f32(&buf, |s| s.clamp(-1.0, 1.0))
             }
             AudioBufferRef::F64(buf) => {
-                Self::interleave_to_stereo_f32(&buf, |s| (s as f32).clamp(-1.0, 1.0))
+                Self::interleave_to_f32(&buf, |s| (s as f32).clamp(-1.0, 1.0))
             }
 
             // Signed integer formats - use symmetric scaling (divide by 2^(N-1))
             // This ensures -1.0 to 1.0 range is symmetric
-            AudioBufferRef::S8(buf) => Self::interleave_to_stereo_f32(&buf, |s| s as f32 / 128.0),
+            AudioBufferRef::S8(buf) => Self::interleave_to_f32(&buf, |s| s as f32 / 128.0),
             AudioBufferRef::S16(buf) => {
-                Self::interleave_to_stereo_f32(&buf, |s| s as f32 / 32768.0)
+                Self::interleave_to_f32(&buf, |s| s as f32 / 32768.0)
             }
             AudioBufferRef::S24(buf) => {
-                Self::interleave_to_stereo_f32(&buf, |s| s.inner() as f32 / 8388608.0)
+                Self::interleave_to_f32(&buf, |s| s.inner() as f32 / 8388608.0)
             }
             AudioBufferRef::S32(buf) => {
-                Self::interleave_to_stereo_f32(&buf, |s| s as f32 / 2147483648.0)
+                Self::interleave_to_f32(&buf, |s| s as f32 / 2147483648.0)
             }
 
             // Unsigned integer formats - normalize and center around 0
             AudioBufferRef::U8(buf) => {
-                Self::interleave_to_stereo_f32(&buf, |s| (s as f32 / u8::MAX as f32) * 2.0 - 1.0)
+                Self::interleave_to_f32(&buf, |s| (s as f32 / u8::MAX as f32) * 2.0 - 1.0)
             }
             AudioBufferRef::U16(buf) => {
-                Self::interleave_to_stereo_f32(&buf, |s| (s as f32 / u16::MAX as f32) * 2.0 - 1.0)
+                Self::interleave_to_f32(&buf, |s| (s as f32 / u16::MAX as f32) * 2.0 - 1.0)
             }
             AudioBufferRef::U24(buf) => {
                 // U24 range: 0 to 16777215 (2^24 - 1)
-                Self::interleave_to_stereo_f32(&buf, |s| {
+                Self::interleave_to_f32(&buf, |s| {
                     (s.inner() as f32 / 16777215.0) * 2.0 - 1.0
                 })
             }
             AudioBufferRef::U32(buf) => {
-                Self::interleave_to_stereo_f32(&buf, |s| (s as f32 / u32::MAX as f32) * 2.0 - 1.0)
+                Self::interleave_to_f32(&buf, |s| (s as f32 / u32::MAX as f32) * 2.0 - 1.0)
             }
         };
 
@@ -721,9 +996,14 @@ impl LocalAudioSource {
         self.source_sample_rate
     }
 
-    /// Get number of channels
-    pub fn channels(&self) -> u16 {
-        self.channels
+    /// Get number of channels in the audio file
+    pub fn source_channels(&self) -> u16 {
+        self.source_channels
+    }
+
+    /// Get the part of the file being played (None = whole file)
+    pub fn range(&self) -> Option<TrackRange> {
+        self.range
     }
 }
 
@@ -779,10 +1059,14 @@ impl AudioSource for LocalAudioSource {
     fn position(&self) -> Duration {
         // Calculate position based on samples read (at target sample rate)
         let state = self.shared.lock().unwrap();
-        let frames = state.samples_read / self.channels as usize;
+        let frames = state.samples_read / self.output_channels as usize;
         Duration::from_secs_f64(frames as f64 / self.target_sample_rate as f64)
     }
 
+    fn channels(&self) -> u16 {
+        self.output_channels
+    }
+
     fn is_finished(&self) -> bool {
         let state = self.shared.lock().unwrap();
         state.is_eof && state.output_buffer.is_empty()
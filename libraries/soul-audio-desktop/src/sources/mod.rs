@@ -1,7 +1,13 @@
 //! Audio source implementations for desktop
 
+pub mod decoder;
+pub mod dsd;
+pub mod http;
 pub mod local;
 pub mod streaming;
 
+pub use decoder::DecoderAudioSource;
+pub use dsd::{DsdAudioSource, DsdOutputMode};
+pub use http::HttpMediaSource;
 pub use local::LocalAudioSource;
 pub use streaming::StreamingAudioSource;
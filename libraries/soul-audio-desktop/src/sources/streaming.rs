@@ -1,286 +1,368 @@
 //! Streaming audio source from server
-
+//!
+//! Plays the files served by `soul-server`'s `/stream/:track_id` endpoint.
+//! The HTTP body is fed into `SymphoniaDecoder` through [`HttpMediaSource`],
+//! so any container/codec that plays locally (FLAC, MP3, Ogg, WAV, AAC)
+//! also plays remotely.
+//!
+//! ## Architecture
+//!
+//! Mirrors `LocalAudioSource`: a background decoder thread downloads,
+//! decodes and resamples to the device rate, filling a shared output buffer.
+//! `read_samples()` only copies from that buffer and never touches the network.
+//!
+//! Seeking sends a command to the decoder thread, which seeks the decoder;
+//! the decoder in turn seeks the `HttpMediaSource`, which reconnects with an
+//! HTTP Range request at the new byte offset.
+
+use super::http::HttpMediaSource;
+use super::local::{DecoderCommand, LocalAudioSource, SharedState};
 use crossbeam_channel::{bounded, Receiver, Sender};
+use rubato::{Resampler, SincFixedIn};
+use soul_audio::SymphoniaDecoder;
+use soul_core::AudioDecoder;
 use soul_playback::{AudioSource, PlaybackError, Result};
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc, Mutex,
-};
-use std::thread;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
 use std::time::Duration;
 
-const CHUNK_SIZE: usize = 8192; // Samples per chunk (4096 frames for stereo)
-const BUFFER_CHUNKS: usize = 16; // Buffer up to 16 chunks
+/// Size of output buffer in seconds (more than local files to absorb network jitter)
+const BUFFER_SIZE_SECONDS: usize = 10;
+
+/// Frames requested from the decoder per iteration
+const DECODE_CHUNK_FRAMES: usize = 4096;
+
+/// Frames per resampler chunk (same as `LocalAudioSource`)
+const RESAMPLER_CHUNK_FRAMES: usize = 1024;
+
+/// The decoder always outputs interleaved stereo
+const DECODED_CHANNELS: usize = 2;
+
+/// Stream properties discovered by the decoder thread
+#[derive(Debug, Default)]
+struct StreamInfo {
+    /// Sample rate of the remote file (once probed)
+    source_sample_rate: Option<u32>,
+    /// Duration reported by the container (once probed)
+    duration: Option<Duration>,
+    /// Last error from the decoder thread
+    error: Option<String>,
+}
 
 /// Audio source that streams from server
 ///
-/// Downloads audio chunks in background while playback continues.
+/// Downloads and decodes audio in background while playback continues.
 /// Uses buffering to handle network latency.
 pub struct StreamingAudioSource {
     /// URL of the audio stream
     url: String,
 
-    /// Sample rate
+    /// Output sample rate (audio is resampled to this rate)
     sample_rate: u32,
 
     /// Number of channels
     channels: u16,
 
-    /// Total duration
+    /// Total duration from track metadata (refined once the stream is probed)
     duration: Duration,
 
-    /// Current read position in samples
-    position: usize,
-
-    /// Buffer of decoded samples (shared with download thread)
-    buffer: Arc<Mutex<Vec<f32>>>,
+    /// Shared output buffer and position (same layout as `LocalAudioSource`)
+    shared: Arc<Mutex<SharedState>>,
 
-    /// Channel to receive new chunks from download thread
-    chunk_receiver: Receiver<Vec<f32>>,
+    /// Stream properties and errors reported by the decoder thread
+    info: Arc<Mutex<StreamInfo>>,
 
-    /// Flag to signal download thread to stop
-    stop_signal: Arc<AtomicBool>,
+    /// Channel to send commands to the decoder thread
+    command_tx: Sender<DecoderCommand>,
 
-    /// Handle to background download thread
-    _download_thread: Option<thread::JoinHandle<()>>,
-
-    /// Whether stream has finished
-    finished: bool,
-
-    /// Whether we've encountered an error
-    error: Arc<Mutex<Option<String>>>,
+    /// Handle to background decoder thread
+    _decoder_thread: Option<JoinHandle<()>>,
 }
 
 impl StreamingAudioSource {
     /// Create a new streaming audio source
     ///
-    /// Starts background download thread that fetches audio chunks from the server.
+    /// Starts a background thread that downloads, decodes and resamples the
+    /// remote file. Returns immediately; connection errors are reported by
+    /// the first `read_samples()` call.
     ///
     /// # Arguments
     /// * `url` - URL of the audio stream endpoint
-    /// * `sample_rate` - Sample rate of the audio
-    /// * `channels` - Number of audio channels (1=mono, 2=stereo)
-    /// * `duration` - Total duration of the track
+    /// * `sample_rate` - Output sample rate (the stream is resampled to this rate)
+    /// * `channels` - Number of output channels (decoded audio is interleaved stereo)
+    /// * `duration` - Total duration of the track (from track metadata)
     ///
     /// # Returns
     /// * `Ok(source)` - Streaming source ready for playback
     /// * `Err(_)` - Failed to initialize stream
     pub fn new(url: String, sample_rate: u32, channels: u16, duration: Duration) -> Result<Self> {
-        let (chunk_sender, chunk_receiver) = bounded(BUFFER_CHUNKS);
-        let stop_signal = Arc::new(AtomicBool::new(false));
-        let error = Arc::new(Mutex::new(None));
-
-        // Start background download thread
-        let download_url = url.clone();
-        let stop_signal_clone = Arc::clone(&stop_signal);
-        let error_clone = Arc::clone(&error);
-        let download_thread = thread::spawn(move || {
-            Self::download_stream(download_url, chunk_sender, stop_signal_clone, error_clone);
-        });
+        let output_buffer_capacity = BUFFER_SIZE_SECONDS * sample_rate as usize * DECODED_CHANNELS;
+
+        let shared = Arc::new(Mutex::new(SharedState {
+            output_buffer: VecDeque::with_capacity(output_buffer_capacity),
+            samples_read: 0,
+            is_eof: false,
+            seek_pending: false,
+        }));
+        let info = Arc::new(Mutex::new(StreamInfo::default()));
+
+        let (command_tx, command_rx) = bounded::<DecoderCommand>(4);
+
+        let thread_url = url.clone();
+        let shared_clone = Arc::clone(&shared);
+        let info_clone = Arc::clone(&info);
+        let decoder_thread = thread::Builder::new()
+            .name("stream-decoder".to_string())
+            .spawn(move || {
+                Self::decoder_thread_main(
+                    thread_url,
+                    sample_rate,
+                    output_buffer_capacity,
+                    shared_clone,
+                    info_clone,
+                    command_rx,
+                );
+            })
+            .map_err(|e| {
+                PlaybackError::AudioSource(format!("Failed to spawn stream decoder thread: {}", e))
+            })?;
 
         Ok(Self {
             url,
             sample_rate,
             channels,
             duration,
-            position: 0,
-            buffer: Arc::new(Mutex::new(Vec::with_capacity(CHUNK_SIZE * BUFFER_CHUNKS))),
-            chunk_receiver,
-            stop_signal,
-            _download_thread: Some(download_thread),
-            finished: false,
-            error,
+            shared,
+            info,
+            command_tx,
+            _decoder_thread: Some(decoder_thread),
         })
     }
 
-    /// Background thread to download and decode stream
+    /// Background decoder thread main function
     ///
-    /// This runs in a separate thread and downloads audio chunks from the server,
-    /// sending them to the main playback thread via channel.
-    fn download_stream(
+    /// Opens the HTTP stream, probes it, then continuously decodes and
+    /// resamples into the shared output buffer. Handles seek and stop commands.
+    fn decoder_thread_main(
         url: String,
-        sender: Sender<Vec<f32>>,
-        stop_signal: Arc<AtomicBool>,
-        error: Arc<Mutex<Option<String>>>,
+        target_sample_rate: u32,
+        output_buffer_capacity: usize,
+        shared: Arc<Mutex<SharedState>>,
+        info: Arc<Mutex<StreamInfo>>,
+        command_rx: Receiver<DecoderCommand>,
     ) {
-        // Create a tokio runtime for async HTTP operations
-        let runtime = match tokio::runtime::Runtime::new() {
-            Ok(rt) => rt,
+        let fail = |message: String| {
+            eprintln!("[StreamDecoder] {}", message);
+            info.lock().unwrap().error = Some(message);
+            shared.lock().unwrap().is_eof = true;
+        };
+
+        let media_source = match HttpMediaSource::open(url.as_str()) {
+            Ok(source) => source,
             Err(e) => {
-                *error.lock().unwrap() = Some(format!("Failed to create runtime: {}", e));
+                fail(format!("Failed to open stream: {}", e));
                 return;
             }
         };
 
-        // Run async download in the runtime
-        runtime.block_on(async {
-            if let Err(e) = Self::download_stream_async(&url, &sender, &stop_signal).await {
-                *error.lock().unwrap() = Some(format!("Streaming error: {}", e));
+        let mut decoder = SymphoniaDecoder::new();
+        let metadata = match decoder.open_source(Box::new(media_source), extension_hint(&url)) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                fail(format!("Failed to probe stream: {}", e));
+                return;
             }
-        });
-    }
-
-    /// Async implementation of stream downloading
-    async fn download_stream_async(
-        url: &str,
-        sender: &Sender<Vec<f32>>,
-        stop_signal: &Arc<AtomicBool>,
-    ) -> std::result::Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // Create HTTP client
-        let client = reqwest::Client::new();
-
-        // Start streaming request
-        let mut response = client.get(url).send().await?;
+        };
 
-        if !response.status().is_success() {
-            return Err(format!("HTTP error: {}", response.status()).into());
+        {
+            let mut info = info.lock().unwrap();
+            info.source_sample_rate = Some(metadata.sample_rate);
+            info.duration = metadata.duration;
         }
 
-        // Buffer for reading bytes
-        let mut byte_buffer = vec![0u8; CHUNK_SIZE * 4]; // f32 = 4 bytes
-
-        // Stream chunks
-        while !stop_signal.load(Ordering::Relaxed) {
-            // Read chunk of bytes
-            let bytes_read = match response.chunk().await? {
-                Some(chunk) => {
-                    let len = chunk.len().min(byte_buffer.len());
-                    byte_buffer[..len].copy_from_slice(&chunk[..len]);
-                    len
+        let needs_resampling = metadata.sample_rate != target_sample_rate;
+        let mut resampler: Option<SincFixedIn<f32>> = if needs_resampling {
+            match LocalAudioSource::create_resampler(
+                metadata.sample_rate,
+                target_sample_rate,
+                DECODED_CHANNELS,
+                RESAMPLER_CHUNK_FRAMES,
+            ) {
+                Ok(r) => Some(r),
+                Err(e) => {
+                    fail(format!("Failed to create resampler: {}", e));
+                    return;
                 }
-                None => break, // End of stream
-            };
-
-            if bytes_read == 0 {
-                break;
             }
+        } else {
+            None
+        };
 
-            // Convert bytes to f32 samples (assuming little-endian f32)
-            let sample_count = bytes_read / 4;
-            let mut samples = Vec::with_capacity(sample_count);
-
-            for i in 0..sample_count {
-                let offset = i * 4;
-                if offset + 4 <= bytes_read {
-                    let bytes = [
-                        byte_buffer[offset],
-                        byte_buffer[offset + 1],
-                        byte_buffer[offset + 2],
-                        byte_buffer[offset + 3],
-                    ];
-                    samples.push(f32::from_le_bytes(bytes));
+        let mut input_buffer: VecDeque<f32> =
+            VecDeque::with_capacity(RESAMPLER_CHUNK_FRAMES * DECODED_CHANNELS * 4);
+        let mut is_eof = false;
+
+        loop {
+            match command_rx.try_recv() {
+                Ok(DecoderCommand::Stop) => break,
+                Ok(DecoderCommand::Seek(position)) => {
+                    match decoder.seek(position) {
+                        Ok(actual) => {
+                            input_buffer.clear();
+                            if let Some(ref mut r) = resampler {
+                                r.reset();
+                            }
+                            is_eof = false;
+
+                            let mut state = shared.lock().unwrap();
+                            state.output_buffer.clear();
+                            state.samples_read = (actual.as_secs_f64()
+                                * target_sample_rate as f64
+                                * DECODED_CHANNELS as f64)
+                                as usize;
+                            state.is_eof = false;
+                        }
+                        Err(e) => {
+                            eprintln!("[StreamDecoder] Seek failed: {}", e);
+                            info.lock().unwrap().error = Some(format!("Seek failed: {}", e));
+                        }
+                    }
+                    shared.lock().unwrap().seek_pending = false;
                 }
+                Err(crossbeam_channel::TryRecvError::Empty) => {}
+                Err(crossbeam_channel::TryRecvError::Disconnected) => break,
             }
 
-            // Send samples to playback thread
-            if sender.send(samples).is_err() {
-                break; // Receiver dropped
+            let buffer_len = shared.lock().unwrap().output_buffer.len();
+            if buffer_len >= output_buffer_capacity / 2 {
+                thread::sleep(Duration::from_millis(10));
+                continue;
             }
-        }
 
-        Ok(())
-    }
+            if is_eof {
+                thread::sleep(Duration::from_millis(50));
+                continue;
+            }
 
-    /// Fill internal buffer from received chunks
-    ///
-    /// Pulls all available chunks from the channel into the shared buffer.
-    fn fill_buffer(&mut self) -> Result<()> {
-        let mut buffer = self.buffer.lock().unwrap();
+            let chunk = match decoder.decode_chunk(DECODE_CHUNK_FRAMES) {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    // Network errors end the stream; keep what is buffered
+                    fail(format!("Decode error: {}", e));
+                    is_eof = true;
+                    continue;
+                }
+            };
 
-        // Try to receive all available chunks
-        while let Ok(chunk) = self.chunk_receiver.try_recv() {
-            buffer.extend(chunk);
-        }
+            let Some(chunk) = chunk else {
+                is_eof = true;
+                if needs_resampling && !input_buffer.is_empty() {
+                    LocalAudioSource::flush_resampler_static(
+                        &mut input_buffer,
+                        &mut resampler,
+                        DECODED_CHANNELS,
+                        RESAMPLER_CHUNK_FRAMES,
+                        &shared,
+                    );
+                }
+                shared.lock().unwrap().is_eof = true;
+                continue;
+            };
 
-        // Check for errors from download thread
-        if let Some(err_msg) = self.error.lock().unwrap().take() {
-            return Err(PlaybackError::AudioSource(err_msg));
+            if needs_resampling {
+                input_buffer.extend(chunk.samples);
+                LocalAudioSource::process_resampling_static(
+                    &mut input_buffer,
+                    &mut resampler,
+                    DECODED_CHANNELS,
+                    RESAMPLER_CHUNK_FRAMES,
+                    output_buffer_capacity,
+                    &shared,
+                );
+            } else {
+                shared.lock().unwrap().output_buffer.extend(chunk.samples);
+            }
         }
-
-        Ok(())
     }
 
-    /// Get current buffer length
-    fn buffer_len(&self) -> usize {
-        self.buffer.lock().unwrap().len()
+    /// Take the last error reported by the decoder thread
+    fn take_error(&self) -> Option<String> {
+        self.info.lock().unwrap().error.take()
     }
 }
 
 impl AudioSource for StreamingAudioSource {
     fn read_samples(&mut self, output: &mut [f32]) -> Result<usize> {
-        // Fill internal buffer from network
-        self.fill_buffer()?;
-
-        let mut buffer = self.buffer.lock().unwrap();
-
-        // Calculate how many samples we can read
-        let available = buffer.len().saturating_sub(self.position);
-
-        if available == 0 {
-            // Check if stream is finished
-            drop(buffer); // Release lock before checking channel
-            if self.chunk_receiver.is_empty() {
-                self.finished = true;
-                return Ok(0);
-            }
-
-            // Buffer underrun - return silence and hope more data arrives soon
+        if let Some(err_msg) = self.take_error() {
             output.fill(0.0);
-            return Ok(0);
+            return Err(PlaybackError::AudioSource(format!("Streaming error: {}", err_msg)));
         }
 
-        let to_read = available.min(output.len());
-
-        // Copy from buffer
-        output[..to_read].copy_from_slice(&buffer[self.position..self.position + to_read]);
+        let mut state = self.shared.lock().unwrap();
 
-        // Update position
-        self.position += to_read;
+        let available = state.output_buffer.len().min(output.len());
+        for (dst, src) in output.iter_mut().zip(state.output_buffer.drain(..available)) {
+            *dst = src;
+        }
+        state.samples_read += available;
 
-        // Remove consumed samples from buffer to prevent unbounded growth
-        if self.position > CHUNK_SIZE * 4 {
-            buffer.drain(0..self.position);
-            self.position = 0;
+        // Buffer underrun (or end of stream) - fill remainder with silence
+        if available < output.len() {
+            output[available..].fill(0.0);
         }
 
-        Ok(to_read)
+        Ok(available)
     }
 
-    fn seek(&mut self, _position: Duration) -> Result<()> {
-        // Seeking in streaming is complex and would require:
-        // 1. Stopping current download thread
-        // 2. Requesting new stream from specific position
-        // 3. Clearing buffer
-        // 4. Starting download from new position
-        //
-        // This is not implemented in MVP - streaming is assumed to be
-        // sequential playback only.
-        Err(PlaybackError::InvalidOperation(
-            "Seeking not supported for streaming sources".to_string(),
-        ))
+    fn seek(&mut self, position: Duration) -> Result<()> {
+        if position > self.duration() {
+            return Err(PlaybackError::InvalidSeekPosition(position));
+        }
+
+        self.shared.lock().unwrap().seek_pending = true;
+
+        self.command_tx
+            .send(DecoderCommand::Seek(position))
+            .map_err(|e| PlaybackError::AudioSource(format!("Failed to send seek command: {}", e)))
     }
 
     fn duration(&self) -> Duration {
-        self.duration
+        self.info
+            .lock()
+            .unwrap()
+            .duration
+            .unwrap_or(self.duration)
     }
 
     fn position(&self) -> Duration {
-        // Calculate position from samples read
-        let frames = self.position / self.channels as usize;
+        let state = self.shared.lock().unwrap();
+        let frames = state.samples_read / DECODED_CHANNELS;
         Duration::from_secs_f64(frames as f64 / self.sample_rate as f64)
     }
 
     fn is_finished(&self) -> bool {
-        self.finished
+        let state = self.shared.lock().unwrap();
+        state.is_eof && !state.seek_pending && state.output_buffer.is_empty()
     }
 }
 
 impl StreamingAudioSource {
-    /// Get sample rate
+    /// Get URL of the stream
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Get output sample rate
     pub fn sample_rate(&self) -> u32 {
         self.sample_rate
     }
 
+    /// Get sample rate of the remote file (None until the stream is probed)
+    pub fn source_sample_rate(&self) -> Option<u32> {
+        self.info.lock().unwrap().source_sample_rate
+    }
+
     /// Get number of channels
     pub fn channels(&self) -> u16 {
         self.channels
@@ -289,11 +371,19 @@ impl StreamingAudioSource {
 
 impl Drop for StreamingAudioSource {
     fn drop(&mut self) {
-        // Signal download thread to stop
-        self.stop_signal.store(true, Ordering::Relaxed);
+        // Signal decoder thread to stop
+        let _ = self.command_tx.send(DecoderCommand::Stop);
     }
 }
 
+/// Derive a probe hint from the file extension in a URL, if any
+fn extension_hint(url: &str) -> Option<&str> {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let file_name = path.rsplit('/').next()?;
+    let (_, ext) = file_name.rsplit_once('.')?;
+    (!ext.is_empty() && ext.len() <= 5).then_some(ext)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -319,4 +409,12 @@ mod tests {
         assert_eq!(source.sample_rate(), 44100);
         assert_eq!(source.channels(), 2);
     }
+
+    #[test]
+    fn extension_hint_from_url() {
+        assert_eq!(extension_hint("http://host/music/song.flac"), Some("flac"));
+        assert_eq!(extension_hint("http://host/a.mp3?quality=high"), Some("mp3"));
+        assert_eq!(extension_hint("http://host/api/stream/track1"), None);
+        assert_eq!(extension_hint("http://host/stream/track1?x=a.b"), None);
+    }
 }
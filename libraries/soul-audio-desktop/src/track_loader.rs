@@ -11,18 +11,31 @@
 //!        │                              │
 //!        │  request_load(path)          │
 //!        │─────────────────────────────>│
-//!        │                              │ LocalAudioSource::new()
+//!        │                              │ open_local_source()
 //!        │                              │ (disk I/O, 5-100ms)
 //!        │                              │
 //!        │  poll_ready() -> Some(src)   │
 //!        │<─────────────────────────────│
 //!        │                              │
 //! ```
-
+//!
+//! DSD files (`.dsf` / `.dff`) are opened as [`DsdAudioSource`]; WavPack,
+//! Monkey's Audio and Musepack files, which `soul_audio`'s decoder registry
+//! decodes without Symphonia, as [`DecoderAudioSource`]; everything else
+//! goes through Symphonia via [`LocalAudioSource`], remixed to the output
+//! device's channel count.
+
+use crate::sources::decoder::DecoderAudioSource;
+use crate::sources::dsd::DsdAudioSource;
 use crate::sources::local::LocalAudioSource;
 use crossbeam_channel::{bounded, Receiver, Sender, TryRecvError};
-use soul_playback::{AudioSource, QueueTrack};
-use std::path::PathBuf;
+use soul_audio::channels::DownmixSettings;
+#[cfg(feature = "effects")]
+use soul_audio::effects::EffectChain;
+use soul_audio::DsdFile;
+use soul_playback::{AudioSource, QueueTrack, TrackDsp, TrackRange};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU16, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
 
@@ -39,6 +52,13 @@ pub struct LoadRequest {
     pub is_preload: bool,
 }
 
+/// Resolves the gain offset and effect chain a track plays with
+///
+/// Called on the loader thread once the track's source is open, so chains
+/// are built there rather than at the transition. `None` leaves the
+/// playing DSP as it is.
+pub type TrackDspResolver = Arc<dyn Fn(&LoadRequest) -> Option<TrackDsp> + Send + Sync>;
+
 /// Result of loading a track
 pub struct LoadResult {
     /// The loaded audio source (if successful)
@@ -49,6 +69,8 @@ pub struct LoadResult {
     pub error: Option<String>,
     /// Whether this was a preload request
     pub is_preload: bool,
+    /// Gain and effects for the track (None = keep the playing ones)
+    pub dsp: Option<TrackDsp>,
 }
 
 /// Background track loader
@@ -64,6 +86,59 @@ pub struct TrackLoader {
     _thread_handle: JoinHandle<()>,
     /// Flag to signal shutdown
     shutdown: Arc<Mutex<bool>>,
+    /// Whether the output device accepts DoP (DSD over PCM)
+    dsd_passthrough: Arc<AtomicBool>,
+    /// Channel count of the output device
+    output_channels: Arc<AtomicU16>,
+    /// Coefficients for folding surround files down to fewer channels
+    downmix: Arc<Mutex<DownmixSettings>>,
+    /// Current-track loads requested but not yet polled
+    pending_loads: AtomicUsize,
+    /// Resolver for per-track gain and effects
+    dsp_resolver: Arc<Mutex<Option<TrackDspResolver>>>,
+    /// Effect chains to free on the loader thread
+    #[cfg(feature = "effects")]
+    retired_tx: Sender<EffectChain>,
+}
+
+/// Open a local file as an audio source
+///
+/// DSD files are sent as DoP when `dsd_passthrough` is set and the device
+/// runs at the matching DoP rate; otherwise they are converted to PCM.
+/// Formats the decoder registry handles outside Symphonia go through their
+/// registry decoder; all other formats are decoded with Symphonia. Both are
+/// remixed to `output_channels` using `downmix`. DSD sources are always
+/// stereo.
+pub fn open_local_source(
+    path: &Path,
+    target_sample_rate: u32,
+    range: Option<TrackRange>,
+    dsd_passthrough: bool,
+    output_channels: u16,
+    downmix: DownmixSettings,
+) -> soul_playback::Result<Box<dyn AudioSource>> {
+    if DsdFile::is_dsd_path(path) {
+        let source = DsdAudioSource::new(path, target_sample_rate, range, dsd_passthrough)?;
+        Ok(Box::new(source))
+    } else if DecoderAudioSource::handles(path) {
+        let source = DecoderAudioSource::with_layout(
+            path,
+            target_sample_rate,
+            range,
+            output_channels,
+            downmix,
+        )?;
+        Ok(Box::new(source))
+    } else {
+        let source = LocalAudioSource::with_layout(
+            path,
+            target_sample_rate,
+            range,
+            output_channels,
+            downmix,
+        )?;
+        Ok(Box::new(source))
+    }
 }
 
 impl TrackLoader {
@@ -71,13 +146,33 @@ impl TrackLoader {
     pub fn new() -> Self {
         let (request_tx, request_rx) = bounded::<LoadRequest>(4);
         let (result_tx, result_rx) = bounded::<LoadResult>(4);
+        #[cfg(feature = "effects")]
+        let (retired_tx, retired_rx) = bounded::<EffectChain>(4);
         let shutdown = Arc::new(Mutex::new(false));
         let shutdown_clone = shutdown.clone();
+        let dsd_passthrough = Arc::new(AtomicBool::new(false));
+        let dsd_passthrough_clone = dsd_passthrough.clone();
+        let output_channels = Arc::new(AtomicU16::new(2));
+        let output_channels_clone = output_channels.clone();
+        let downmix = Arc::new(Mutex::new(DownmixSettings::default()));
+        let downmix_clone = downmix.clone();
+        let dsp_resolver: Arc<Mutex<Option<TrackDspResolver>>> = Arc::new(Mutex::new(None));
+        let dsp_resolver_clone = dsp_resolver.clone();
 
         let thread_handle = thread::Builder::new()
             .name("track-loader".to_string())
             .spawn(move || {
-                Self::loader_thread(request_rx, result_tx, shutdown_clone);
+                Self::loader_thread(
+                    request_rx,
+                    result_tx,
+                    shutdown_clone,
+                    dsd_passthrough_clone,
+                    output_channels_clone,
+                    downmix_clone,
+                    dsp_resolver_clone,
+                    #[cfg(feature = "effects")]
+                    retired_rx,
+                );
             })
             .expect("Failed to spawn track loader thread");
 
@@ -86,15 +181,102 @@ impl TrackLoader {
             result_rx,
             _thread_handle: thread_handle,
             shutdown,
+            dsd_passthrough,
+            output_channels,
+            downmix,
+            pending_loads: AtomicUsize::new(0),
+            dsp_resolver,
+            #[cfg(feature = "effects")]
+            retired_tx,
         }
     }
 
+    /// Enable or disable DoP output for DSD files
+    ///
+    /// Set from the output device's capabilities whenever the stream is
+    /// (re)created. Affects tracks loaded after the call.
+    pub fn set_dsd_passthrough(&self, enabled: bool) {
+        self.dsd_passthrough.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Check whether DSD files are sent as DoP
+    pub fn dsd_passthrough(&self) -> bool {
+        self.dsd_passthrough.load(Ordering::Relaxed)
+    }
+
+    /// Set the channel count that loaded sources deliver
+    ///
+    /// Set from the output stream configuration whenever the stream is
+    /// (re)created. Affects tracks loaded after the call.
+    pub fn set_output_channels(&self, channels: u16) {
+        self.output_channels.store(channels.max(1), Ordering::Relaxed);
+    }
+
+    /// Get the channel count that loaded sources deliver
+    pub fn output_channels(&self) -> u16 {
+        self.output_channels.load(Ordering::Relaxed)
+    }
+
+    /// Set the downmix coefficients for surround files
+    ///
+    /// Affects tracks loaded after the call.
+    pub fn set_downmix_settings(&self, settings: DownmixSettings) {
+        *self.downmix.lock().unwrap() = settings;
+    }
+
+    /// Get the downmix coefficients for surround files
+    pub fn downmix_settings(&self) -> DownmixSettings {
+        *self.downmix.lock().unwrap()
+    }
+
+    /// Set the resolver for the gain and effects of loaded tracks
+    ///
+    /// Affects tracks loaded after the call.
+    pub fn set_dsp_resolver(&self, resolver: Option<TrackDspResolver>) {
+        *self.dsp_resolver.lock().unwrap() = resolver;
+    }
+
+    /// Free an effect chain on the loader thread (non-blocking)
+    ///
+    /// For chains replaced on the audio thread. Dropped in place if the
+    /// queue is full.
+    #[cfg(feature = "effects")]
+    pub fn retire_chain(&self, chain: EffectChain) {
+        let _ = self.retired_tx.try_send(chain);
+    }
+
+    /// Open a local file synchronously with the loader's DSD and channel settings
+    ///
+    /// Used when a source must be replaced immediately (e.g. after a device
+    /// switch) rather than through the background queue.
+    pub fn open_source(
+        &self,
+        path: &Path,
+        target_sample_rate: u32,
+        range: Option<TrackRange>,
+    ) -> soul_playback::Result<Box<dyn AudioSource>> {
+        open_local_source(
+            path,
+            target_sample_rate,
+            range,
+            self.dsd_passthrough(),
+            self.output_channels(),
+            self.downmix_settings(),
+        )
+    }
+
     /// Request loading a track (non-blocking)
     ///
     /// Returns true if the request was queued, false if the queue is full.
     pub fn request_load(&self, request: LoadRequest) -> bool {
+        let is_preload = request.is_preload;
         match self.request_tx.try_send(request) {
-            Ok(()) => true,
+            Ok(()) => {
+                if !is_preload {
+                    self.pending_loads.fetch_add(1, Ordering::Relaxed);
+                }
+                true
+            }
             Err(crossbeam_channel::TrySendError::Full(_)) => {
                 eprintln!("[TrackLoader] Load request queue full, dropping request");
                 false
@@ -111,7 +293,12 @@ impl TrackLoader {
     /// Returns Some(result) if a track has finished loading, None otherwise.
     pub fn poll_ready(&self) -> Option<LoadResult> {
         match self.result_rx.try_recv() {
-            Ok(result) => Some(result),
+            Ok(result) => {
+                if !result.is_preload {
+                    self.pending_loads.fetch_sub(1, Ordering::Relaxed);
+                }
+                Some(result)
+            }
             Err(TryRecvError::Empty) => None,
             Err(TryRecvError::Disconnected) => {
                 eprintln!("[TrackLoader] Result channel disconnected");
@@ -127,6 +314,14 @@ impl TrackLoader {
         !self.request_tx.is_empty() || !self.result_rx.is_empty()
     }
 
+    /// Check if a current-track load has been requested but not yet polled
+    ///
+    /// Audio callbacks keep seeing the Loading state until the result
+    /// arrives; this keeps them from requesting the same track again.
+    pub fn is_load_pending(&self) -> bool {
+        self.pending_loads.load(Ordering::Relaxed) > 0
+    }
+
     /// Shutdown the loader thread
     pub fn shutdown(&self) {
         *self.shutdown.lock().unwrap() = true;
@@ -139,6 +334,11 @@ impl TrackLoader {
         request_rx: Receiver<LoadRequest>,
         result_tx: Sender<LoadResult>,
         shutdown: Arc<Mutex<bool>>,
+        dsd_passthrough: Arc<AtomicBool>,
+        output_channels: Arc<AtomicU16>,
+        downmix: Arc<Mutex<DownmixSettings>>,
+        dsp_resolver: Arc<Mutex<Option<TrackDspResolver>>>,
+        #[cfg(feature = "effects")] retired_rx: Receiver<EffectChain>,
     ) {
         eprintln!("[TrackLoader] Background thread started");
 
@@ -149,6 +349,10 @@ impl TrackLoader {
                 break;
             }
 
+            // Free retired effect chains
+            #[cfg(feature = "effects")]
+            while retired_rx.try_recv().is_ok() {}
+
             // Wait for a load request (with timeout to allow shutdown checks)
             match request_rx.recv_timeout(std::time::Duration::from_millis(100)) {
                 Ok(request) => {
@@ -159,8 +363,14 @@ impl TrackLoader {
                     );
 
                     // This is the slow part - disk I/O!
-                    let result = match LocalAudioSource::new(&request.path, request.target_sample_rate)
-                    {
+                    let result = match open_local_source(
+                        &request.path,
+                        request.target_sample_rate,
+                        request.track.range,
+                        dsd_passthrough.load(Ordering::Relaxed),
+                        output_channels.load(Ordering::Relaxed),
+                        *downmix.lock().unwrap(),
+                    ) {
                         Ok(source) => {
                             let duration = start.elapsed();
                             eprintln!(
@@ -168,11 +378,14 @@ impl TrackLoader {
                                 request.track.title,
                                 duration.as_millis()
                             );
+                            let resolver = dsp_resolver.lock().unwrap().clone();
+                            let dsp = resolver.and_then(|resolve| resolve(&request));
                             LoadResult {
-                                source: Some(Box::new(source)),
+                                source: Some(source),
                                 track: request.track,
                                 error: None,
                                 is_preload: request.is_preload,
+                                dsp,
                             }
                         }
                         Err(e) => {
@@ -185,6 +398,7 @@ impl TrackLoader {
                                 track: request.track,
                                 error: Some(e.to_string()),
                                 is_preload: request.is_preload,
+                                dsp: None,
                             }
                         }
                     };
@@ -282,6 +496,7 @@ mod tests {
                 path: wav_path,
                 track_number: None,
                 source: soul_playback::TrackSource::Single,
+                ..Default::default()
             },
             target_sample_rate: 44100,
             is_preload: false,
@@ -321,6 +536,7 @@ mod tests {
                 path: missing_path,
                 track_number: None,
                 source: soul_playback::TrackSource::Single,
+                ..Default::default()
             },
             target_sample_rate: 44100,
             is_preload: false,
@@ -343,6 +559,62 @@ mod tests {
         assert!(result.error.is_some(), "Should have error message");
     }
 
+    #[test]
+    fn test_track_loader_pending_load() {
+        let temp_dir = TempDir::new().unwrap();
+        let wav_path = temp_dir.path().join("test.wav");
+        generate_test_wav(&wav_path).unwrap();
+
+        let loader = TrackLoader::new();
+        assert!(!loader.is_load_pending());
+
+        let request = |is_preload| LoadRequest {
+            path: wav_path.clone(),
+            track: QueueTrack {
+                id: "pending".to_string(),
+                path: wav_path.clone(),
+                ..Default::default()
+            },
+            target_sample_rate: 44100,
+            is_preload,
+        };
+
+        // Preloads don't block loading the current track
+        assert!(loader.request_load(request(true)));
+        assert!(!loader.is_load_pending());
+
+        assert!(loader.request_load(request(false)));
+        assert!(loader.is_load_pending());
+
+        let mut polled = 0;
+        for _ in 0..100 {
+            if loader.poll_ready().is_some() {
+                polled += 1;
+                if polled == 2 {
+                    break;
+                }
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(polled, 2);
+        assert!(!loader.is_load_pending());
+    }
+
+    #[test]
+    fn test_track_loader_output_channels() {
+        let temp_dir = TempDir::new().unwrap();
+        let wav_path = temp_dir.path().join("test.wav");
+        generate_test_wav(&wav_path).unwrap();
+
+        let loader = TrackLoader::new();
+        assert_eq!(loader.output_channels(), 2);
+
+        // Stereo file upmixed for a 5.1 device
+        loader.set_output_channels(6);
+        let source = loader.open_source(&wav_path, 44100, None).unwrap();
+        assert_eq!(source.channels(), 6);
+    }
+
     #[test]
     fn test_track_loader_non_blocking() {
         let loader = TrackLoader::new();
@@ -0,0 +1,759 @@
+//! Virtual output devices: null sink and WAV file recording
+//!
+//! Devices of `AudioBackend::Virtual` need no sound hardware. Each open
+//! device is a render thread that pulls buffers from the same callbacks a
+//! CPAL stream would call and either discards them (null sink) or writes
+//! them to a WAV file. A recording therefore holds exactly the samples a DAC
+//! would receive: after effects, volume, dither and integer conversion.
+//!
+//! Devices live in a process-wide registry and are addressed by name like
+//! hardware devices, so `DesktopPlayback::switch_device`, sample rate change
+//! detection and default device following work unchanged. The registry
+//! always contains [`NULL_DEVICE_NAME`], which is the default device until
+//! another one is made the default.
+
+use crate::backend::AudioBackend;
+use crate::device::{
+    AudioDeviceInfo, DeviceCapabilities, DeviceError, SupportedBitDepth, DSD_RATES,
+};
+use crate::error::{AudioError, Result};
+use serde::{Deserialize, Serialize};
+use std::io::{Seek, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Name of the built-in null sink device
+pub const NULL_DEVICE_NAME: &str = "Null Output";
+
+/// How often a paused render thread checks whether to resume
+const PAUSED_POLL_INTERVAL: Duration = Duration::from_millis(2);
+
+/// When a virtual device renders its buffers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VirtualClock {
+    /// One buffer per buffer period, like a sound card
+    #[default]
+    RealTime,
+    /// Buffers back to back, as fast as the callback returns them
+    ///
+    /// Decoders still run in their own threads, so a source that falls
+    /// behind plays silence just as it would on a real device.
+    Unpaced,
+}
+
+/// Where a virtual device's samples go
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VirtualSink {
+    /// Discard every buffer
+    Null,
+    /// Write a WAV file in the device's format
+    ///
+    /// The file is rewritten each time the device is opened (including
+    /// when a stream is recreated after a device switch or a sample rate
+    /// change) and finalized when the stream is dropped.
+    Wav(PathBuf),
+}
+
+/// A virtual output device
+///
+/// Built with [`null`](Self::null) or [`wav`](Self::wav) and the `with_*`
+/// methods, then added with [`register_device`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct VirtualDevice {
+    /// Device name, unique within the virtual backend
+    pub name: String,
+
+    /// Where rendered samples go
+    pub sink: VirtualSink,
+
+    /// Output sample rate (Hz)
+    pub sample_rate: u32,
+
+    /// Number of output channels
+    pub channels: u16,
+
+    /// Sample format of the stream; selects the same callback (and dither)
+    /// as a CPAL stream of that format. 64-bit float is not supported.
+    pub bit_depth: SupportedBitDepth,
+
+    /// Frames per callback
+    pub buffer_frames: u32,
+
+    /// Render pacing
+    pub clock: VirtualClock,
+}
+
+impl VirtualDevice {
+    /// A null sink: 48 kHz stereo, 32-bit float, 512-frame buffers, real time
+    pub fn null(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            sink: VirtualSink::Null,
+            sample_rate: 48000,
+            channels: 2,
+            bit_depth: SupportedBitDepth::Float32,
+            buffer_frames: 512,
+            clock: VirtualClock::RealTime,
+        }
+    }
+
+    /// A WAV file recorder with the same defaults as [`null`](Self::null)
+    pub fn wav(name: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            sink: VirtualSink::Wav(path.into()),
+            ..Self::null(name)
+        }
+    }
+
+    /// Set output sample rate
+    #[must_use]
+    pub fn with_sample_rate(mut self, sample_rate: u32) -> Self {
+        self.sample_rate = sample_rate;
+        self
+    }
+
+    /// Set number of output channels
+    #[must_use]
+    pub fn with_channels(mut self, channels: u16) -> Self {
+        self.channels = channels;
+        self
+    }
+
+    /// Set sample format
+    #[must_use]
+    pub fn with_bit_depth(mut self, bit_depth: SupportedBitDepth) -> Self {
+        self.bit_depth = bit_depth;
+        self
+    }
+
+    /// Set frames per callback
+    #[must_use]
+    pub fn with_buffer_frames(mut self, frames: u32) -> Self {
+        self.buffer_frames = frames;
+        self
+    }
+
+    /// Set render pacing
+    #[must_use]
+    pub fn with_clock(mut self, clock: VirtualClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Capabilities reported for this device
+    ///
+    /// A virtual device supports exactly its configured rate and format. `DoP`
+    /// is available when the rate can carry DSD64 or higher (176.4 kHz
+    /// multiples), the same rule as for hardware devices.
+    pub fn capabilities(&self) -> DeviceCapabilities {
+        let supports_dsd = self.sample_rate >= 176_400 && self.sample_rate % 44_100 == 0;
+        let dsd_rates = if supports_dsd {
+            DSD_RATES
+                .iter()
+                .map(|&(rate, _)| rate)
+                .filter(|rate| rate / 16 <= self.sample_rate)
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        DeviceCapabilities {
+            sample_rates: vec![self.sample_rate],
+            bit_depths: vec![self.bit_depth],
+            max_channels: self.channels,
+            supports_exclusive: false,
+            supports_dsd,
+            dsd_rates,
+            min_buffer_frames: Some(self.buffer_frames),
+            max_buffer_frames: Some(self.buffer_frames),
+            has_hardware_volume: false,
+        }
+    }
+
+    /// Device information in the form hardware devices are listed in
+    pub fn info(&self, is_default: bool, include_capabilities: bool) -> AudioDeviceInfo {
+        AudioDeviceInfo {
+            name: self.name.clone(),
+            backend: AudioBackend::Virtual,
+            is_default,
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+            sample_rate_range: Some((self.sample_rate, self.sample_rate)),
+            capabilities: include_capabilities.then(|| self.capabilities()),
+        }
+    }
+
+    fn validate(&self) -> std::result::Result<(), DeviceError> {
+        let invalid = |reason: &str| {
+            Err(DeviceError::InvalidConfig(format!(
+                "virtual device '{}': {}",
+                self.name, reason
+            )))
+        };
+
+        if self.name.is_empty() {
+            return invalid("name is empty");
+        }
+        if self.sample_rate == 0 {
+            return invalid("sample rate is 0");
+        }
+        if self.channels == 0 {
+            return invalid("channel count is 0");
+        }
+        if self.buffer_frames == 0 {
+            return invalid("buffer size is 0");
+        }
+        if self.bit_depth == SupportedBitDepth::Float64 {
+            return invalid("64-bit float output is not supported");
+        }
+        Ok(())
+    }
+}
+
+// ===== Device registry =====
+
+struct Registry {
+    devices: Vec<VirtualDevice>,
+    /// Name of the default device (None = the null device)
+    default: Option<String>,
+}
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+    devices: Vec::new(),
+    default: None,
+});
+
+/// Add a virtual device, replacing any device with the same name
+///
+/// Replacing an open device takes effect when the stream is next created;
+/// changing its sample rate is picked up by
+/// `DesktopPlayback::check_and_update_sample_rate` like a rate change in a
+/// driver's control panel. The built-in null device can be replaced too.
+pub fn register_device(device: VirtualDevice) -> std::result::Result<(), DeviceError> {
+    device.validate()?;
+
+    let mut registry = REGISTRY.lock().unwrap();
+    if let Some(existing) = registry.devices.iter_mut().find(|d| d.name == device.name) {
+        *existing = device;
+    } else {
+        registry.devices.push(device);
+    }
+    Ok(())
+}
+
+/// Remove a registered device
+///
+/// Returns false if no device had that name. If it was the default device,
+/// the null device becomes the default again.
+pub fn remove_device(name: &str) -> bool {
+    let mut registry = REGISTRY.lock().unwrap();
+    let count = registry.devices.len();
+    registry.devices.retain(|d| d.name != name);
+    if registry.default.as_deref() == Some(name) {
+        registry.default = None;
+    }
+    registry.devices.len() != count
+}
+
+/// Make a device the default of the virtual backend
+///
+/// Playback that follows the default device moves to it on the next
+/// `DesktopPlayback::check_default_device`.
+pub fn set_default_device(name: &str) -> std::result::Result<(), DeviceError> {
+    find_device(name)?;
+    REGISTRY.lock().unwrap().default = Some(name.to_string());
+    Ok(())
+}
+
+/// All virtual devices, the null device first
+pub fn list_devices() -> Vec<VirtualDevice> {
+    let registry = REGISTRY.lock().unwrap();
+    let mut devices = Vec::with_capacity(registry.devices.len() + 1);
+    if !registry.devices.iter().any(|d| d.name == NULL_DEVICE_NAME) {
+        devices.push(VirtualDevice::null(NULL_DEVICE_NAME));
+    }
+    devices.extend(registry.devices.iter().cloned());
+    devices
+}
+
+/// Find a virtual device by name
+pub fn find_device(name: &str) -> std::result::Result<VirtualDevice, DeviceError> {
+    list_devices()
+        .into_iter()
+        .find(|d| d.name == name)
+        .ok_or_else(|| DeviceError::DeviceNotFound(name.to_string()))
+}
+
+/// The default virtual device
+pub fn default_device() -> VirtualDevice {
+    let default = REGISTRY.lock().unwrap().default.clone();
+    default
+        .and_then(|name| find_device(&name).ok())
+        .unwrap_or_else(|| find_device(NULL_DEVICE_NAME).expect("null device is always listed"))
+}
+
+/// Information about all virtual devices, the default first
+pub fn list_device_infos(include_capabilities: bool) -> Vec<AudioDeviceInfo> {
+    let default_name = default_device().name;
+    let mut infos: Vec<AudioDeviceInfo> = list_devices()
+        .iter()
+        .map(|d| d.info(d.name == default_name, include_capabilities))
+        .collect();
+    infos.sort_by_key(|info| !info.is_default);
+    infos
+}
+
+// ===== Rendering =====
+
+/// Sample types a virtual device renders
+pub trait VirtualSample: Copy + Default + Send + 'static {
+    /// Whether a stream of `bit_depth` uses buffers of this type
+    fn carries(bit_depth: SupportedBitDepth) -> bool;
+
+    /// Write one sample to a WAV file of `bit_depth`
+    fn write_wav<W: Write + Seek>(
+        self,
+        writer: &mut hound::WavWriter<W>,
+        bit_depth: SupportedBitDepth,
+    ) -> hound::Result<()>;
+}
+
+impl VirtualSample for f32 {
+    fn carries(bit_depth: SupportedBitDepth) -> bool {
+        bit_depth == SupportedBitDepth::Float32
+    }
+
+    fn write_wav<W: Write + Seek>(
+        self,
+        writer: &mut hound::WavWriter<W>,
+        _bit_depth: SupportedBitDepth,
+    ) -> hound::Result<()> {
+        writer.write_sample(self)
+    }
+}
+
+impl VirtualSample for i32 {
+    fn carries(bit_depth: SupportedBitDepth) -> bool {
+        matches!(
+            bit_depth,
+            SupportedBitDepth::Int24 | SupportedBitDepth::Int32
+        )
+    }
+
+    /// 24-bit streams are left-aligned in 32-bit words, as CPAL's I32
+    /// format; a 24-bit DAC only sees the top three bytes.
+    fn write_wav<W: Write + Seek>(
+        self,
+        writer: &mut hound::WavWriter<W>,
+        bit_depth: SupportedBitDepth,
+    ) -> hound::Result<()> {
+        if bit_depth == SupportedBitDepth::Int24 {
+            writer.write_sample(self >> 8)
+        } else {
+            writer.write_sample(self)
+        }
+    }
+}
+
+impl VirtualSample for i16 {
+    fn carries(bit_depth: SupportedBitDepth) -> bool {
+        bit_depth == SupportedBitDepth::Int16
+    }
+
+    fn write_wav<W: Write + Seek>(
+        self,
+        writer: &mut hound::WavWriter<W>,
+        _bit_depth: SupportedBitDepth,
+    ) -> hound::Result<()> {
+        writer.write_sample(self)
+    }
+}
+
+/// WAV format of a device
+fn wav_spec(device: &VirtualDevice) -> hound::WavSpec {
+    let (bits_per_sample, sample_format) = match device.bit_depth {
+        SupportedBitDepth::Float32 => (32, hound::SampleFormat::Float),
+        depth => (u16::from(depth.bits()), hound::SampleFormat::Int),
+    };
+    hound::WavSpec {
+        channels: device.channels,
+        sample_rate: device.sample_rate,
+        bits_per_sample,
+        sample_format,
+    }
+}
+
+/// A running virtual device
+///
+/// The counterpart of a CPAL stream: created paused, started with
+/// [`play`](Self::play). Dropping it stops the render thread and finalizes
+/// the WAV file.
+pub struct VirtualStream {
+    device: VirtualDevice,
+    playing: Arc<AtomicBool>,
+    stop: Arc<AtomicBool>,
+    frames_rendered: Arc<AtomicU64>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl VirtualStream {
+    /// Open a device and start its (paused) render thread
+    ///
+    /// `callback` fills one buffer of `buffer_frames * channels` interleaved
+    /// samples per call, like a CPAL data callback. Its sample type must
+    /// match the device's bit depth.
+    pub fn build<T, F>(device: &VirtualDevice, callback: F) -> Result<Self>
+    where
+        T: VirtualSample,
+        F: FnMut(&mut [T]) + Send + 'static,
+    {
+        device
+            .validate()
+            .map_err(|e| AudioError::DeviceError(e.to_string()))?;
+        if !T::carries(device.bit_depth) {
+            return Err(AudioError::UnsupportedFormat(format!(
+                "{} callback for a {} device",
+                std::any::type_name::<T>(),
+                device.bit_depth.display_name()
+            )));
+        }
+
+        let writer = match &device.sink {
+            VirtualSink::Null => None,
+            VirtualSink::Wav(path) => Some(
+                hound::WavWriter::create(path, wav_spec(device)).map_err(|e| {
+                    AudioError::DeviceError(format!("Failed to create {}: {}", path.display(), e))
+                })?,
+            ),
+        };
+
+        let playing = Arc::new(AtomicBool::new(false));
+        let stop = Arc::new(AtomicBool::new(false));
+        let frames_rendered = Arc::new(AtomicU64::new(0));
+
+        let thread = {
+            let device = device.clone();
+            let playing = playing.clone();
+            let stop = stop.clone();
+            let frames_rendered = frames_rendered.clone();
+            thread::Builder::new()
+                .name(format!("virtual-output-{}", device.name))
+                .spawn(move || {
+                    Self::render_thread_main(
+                        &device,
+                        callback,
+                        writer,
+                        &playing,
+                        &stop,
+                        &frames_rendered,
+                    );
+                })
+                .map_err(|e| {
+                    AudioError::DeviceError(format!("Failed to spawn render thread: {}", e))
+                })?
+        };
+
+        Ok(Self {
+            device: device.clone(),
+            playing,
+            stop,
+            frames_rendered,
+            thread: Some(thread),
+        })
+    }
+
+    fn render_thread_main<T, F>(
+        device: &VirtualDevice,
+        mut callback: F,
+        mut writer: Option<hound::WavWriter<std::io::BufWriter<std::fs::File>>>,
+        playing: &AtomicBool,
+        stop: &AtomicBool,
+        frames_rendered: &AtomicU64,
+    ) where
+        T: VirtualSample,
+        F: FnMut(&mut [T]),
+    {
+        let frames = device.buffer_frames as usize;
+        let mut buffer = vec![T::default(); frames * device.channels as usize];
+
+        // Real-time pacing: buffers are due at fixed intervals from the
+        // moment playback (re)started
+        let mut clock_start = Instant::now();
+        let mut frames_since_start = 0u64;
+
+        while !stop.load(Ordering::Acquire) {
+            if !playing.load(Ordering::Acquire) {
+                thread::sleep(PAUSED_POLL_INTERVAL);
+                clock_start = Instant::now();
+                frames_since_start = 0;
+                continue;
+            }
+
+            callback(&mut buffer);
+
+            if let Some(wav) = &mut writer {
+                let written = buffer
+                    .iter()
+                    .try_for_each(|&sample| sample.write_wav(wav, device.bit_depth));
+                if let Err(e) = written {
+                    eprintln!(
+                        "[Virtual] Failed to write to '{}', discarding further output: {}",
+                        device.name, e
+                    );
+                    writer = None;
+                }
+            }
+
+            frames_rendered.fetch_add(frames as u64, Ordering::Relaxed);
+            frames_since_start += frames as u64;
+
+            match device.clock {
+                VirtualClock::RealTime => {
+                    let due = clock_start
+                        + Duration::from_secs_f64(
+                            frames_since_start as f64 / device.sample_rate as f64,
+                        );
+                    let now = Instant::now();
+                    if due > now {
+                        thread::sleep(due - now);
+                    }
+                }
+                // Let decoder and loader threads run between buffers
+                VirtualClock::Unpaced => thread::yield_now(),
+            }
+        }
+
+        if let Some(wav) = writer {
+            if let Err(e) = wav.finalize() {
+                eprintln!("[Virtual] Failed to finalize '{}': {}", device.name, e);
+            }
+        }
+    }
+
+    /// Start or resume rendering
+    pub fn play(&self) {
+        self.playing.store(true, Ordering::Release);
+    }
+
+    /// Pause rendering (nothing is written while paused)
+    pub fn pause(&self) {
+        self.playing.store(false, Ordering::Release);
+    }
+
+    /// Whether the stream is rendering
+    pub fn is_playing(&self) -> bool {
+        self.playing.load(Ordering::Acquire)
+    }
+
+    /// Frames rendered since the stream was built
+    pub fn frames_rendered(&self) -> u64 {
+        self.frames_rendered.load(Ordering::Relaxed)
+    }
+
+    /// The device this stream renders for
+    pub fn device(&self) -> &VirtualDevice {
+        &self.device
+    }
+}
+
+impl Drop for VirtualStream {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wait_for_frames(stream: &VirtualStream, frames: u64) {
+        let start = Instant::now();
+        while stream.frames_rendered() < frames {
+            assert!(
+                start.elapsed() < Duration::from_secs(5),
+                "render thread stalled at {} frames",
+                stream.frames_rendered()
+            );
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn test_null_device_is_always_listed() {
+        assert!(list_devices().iter().any(|d| d.name == NULL_DEVICE_NAME));
+        assert_eq!(
+            find_device(NULL_DEVICE_NAME).unwrap().sink,
+            VirtualSink::Null
+        );
+    }
+
+    #[test]
+    fn test_register_replaces_and_removes() {
+        let name = "registry test device";
+        register_device(VirtualDevice::null(name).with_sample_rate(44100)).unwrap();
+        register_device(VirtualDevice::null(name).with_sample_rate(96000)).unwrap();
+
+        let matching: Vec<_> = list_devices()
+            .into_iter()
+            .filter(|d| d.name == name)
+            .collect();
+        assert_eq!(matching.len(), 1);
+        assert_eq!(matching[0].sample_rate, 96000);
+
+        assert!(remove_device(name));
+        assert!(!remove_device(name));
+        assert!(matches!(
+            find_device(name),
+            Err(DeviceError::DeviceNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_register_rejects_invalid_devices() {
+        for device in [
+            VirtualDevice::null(""),
+            VirtualDevice::null("invalid").with_sample_rate(0),
+            VirtualDevice::null("invalid").with_channels(0),
+            VirtualDevice::null("invalid").with_buffer_frames(0),
+            VirtualDevice::null("invalid").with_bit_depth(SupportedBitDepth::Float64),
+        ] {
+            assert!(matches!(
+                register_device(device),
+                Err(DeviceError::InvalidConfig(_))
+            ));
+        }
+        assert!(find_device("invalid").is_err());
+    }
+
+    #[test]
+    fn test_capabilities() {
+        let device = VirtualDevice::null("caps")
+            .with_sample_rate(44100)
+            .with_bit_depth(SupportedBitDepth::Int24)
+            .with_buffer_frames(256);
+        let caps = device.capabilities();
+        assert_eq!(caps.sample_rates, vec![44100]);
+        assert_eq!(caps.bit_depths, vec![SupportedBitDepth::Int24]);
+        assert_eq!(caps.min_buffer_frames, Some(256));
+        assert!(!caps.supports_dsd);
+
+        let dop = device.with_sample_rate(352_800).capabilities();
+        assert!(dop.supports_dsd);
+        assert_eq!(dop.dsd_rates, vec![2_822_400, 5_644_800]);
+    }
+
+    #[test]
+    fn test_callback_type_must_match_bit_depth() {
+        let device = VirtualDevice::null("mismatch").with_bit_depth(SupportedBitDepth::Int16);
+        let result = VirtualStream::build(&device, |_: &mut [f32]| {});
+        assert!(matches!(result, Err(AudioError::UnsupportedFormat(_))));
+    }
+
+    #[test]
+    fn test_stream_starts_paused() {
+        let device = VirtualDevice::null("paused").with_clock(VirtualClock::Unpaced);
+        let stream = VirtualStream::build(&device, |_: &mut [f32]| {}).unwrap();
+        thread::sleep(Duration::from_millis(20));
+        assert!(!stream.is_playing());
+        assert_eq!(stream.frames_rendered(), 0);
+
+        stream.play();
+        wait_for_frames(&stream, 512);
+        stream.pause();
+        // A buffer in flight may still complete
+        thread::sleep(Duration::from_millis(20));
+        let frames = stream.frames_rendered();
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(stream.frames_rendered(), frames);
+    }
+
+    #[test]
+    fn test_real_time_clock_paces_buffers() {
+        let device = VirtualDevice::null("real time")
+            .with_sample_rate(48000)
+            .with_buffer_frames(480);
+        let stream = VirtualStream::build(&device, |_: &mut [f32]| {}).unwrap();
+
+        let start = Instant::now();
+        stream.play();
+        wait_for_frames(&stream, 9600);
+        let elapsed = start.elapsed();
+
+        // 20 buffers of 10 ms; the first is rendered immediately
+        assert!(
+            elapsed >= Duration::from_millis(185),
+            "rendered 200 ms of audio in {:?}",
+            elapsed
+        );
+    }
+
+    #[test]
+    fn test_wav_sink_records_16_bit() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.wav");
+        let device = VirtualDevice::wav("wav 16", &path)
+            .with_sample_rate(44100)
+            .with_bit_depth(SupportedBitDepth::Int16)
+            .with_buffer_frames(100)
+            .with_clock(VirtualClock::Unpaced);
+
+        let mut next = 0i16;
+        let stream = VirtualStream::build(&device, move |data: &mut [i16]| {
+            for sample in data {
+                *sample = next;
+                next = next.wrapping_add(1);
+            }
+        })
+        .unwrap();
+        stream.play();
+        wait_for_frames(&stream, 1000);
+        drop(stream);
+
+        let mut reader = hound::WavReader::open(&path).unwrap();
+        let spec = reader.spec();
+        assert_eq!(spec.sample_rate, 44100);
+        assert_eq!(spec.bits_per_sample, 16);
+        assert_eq!(spec.channels, 2);
+
+        let samples: Vec<i16> = reader.samples().map(|s| s.unwrap()).collect();
+        assert!(samples.len() >= 2000);
+        assert_eq!(samples.len() % 200, 0, "whole buffers only");
+        assert!(samples.iter().enumerate().all(|(i, &s)| s == i as i16));
+    }
+
+    #[test]
+    fn test_wav_sink_records_top_24_bits() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out24.wav");
+        let device = VirtualDevice::wav("wav 24", &path)
+            .with_bit_depth(SupportedBitDepth::Int24)
+            .with_channels(1)
+            .with_clock(VirtualClock::Unpaced);
+
+        let stream = VirtualStream::build(&device, |data: &mut [i32]| {
+            data.fill(0x1234_56ff);
+        })
+        .unwrap();
+        stream.play();
+        wait_for_frames(&stream, 512);
+        drop(stream);
+
+        let mut reader = hound::WavReader::open(&path).unwrap();
+        assert_eq!(reader.spec().bits_per_sample, 24);
+        assert!(reader.samples::<i32>().all(|s| s.unwrap() == 0x12_3456));
+    }
+
+    #[test]
+    fn test_wav_sink_create_error() {
+        let device = VirtualDevice::wav("bad path", "/nonexistent/dir/out.wav");
+        let result = VirtualStream::build(&device, |_: &mut [f32]| {});
+        assert!(matches!(result, Err(AudioError::DeviceError(_))));
+    }
+}
@@ -1,9 +1,9 @@
-//! Integration tests for `LocalAudioSource` and `StreamingAudioSource`
+//! Integration tests for `LocalAudioSource`, `DsdAudioSource` and `StreamingAudioSource`
 //!
 //! These tests verify real behavior with actual audio data.
 
-use soul_audio_desktop::{LocalAudioSource, StreamingAudioSource};
-use soul_playback::AudioSource;
+use soul_audio_desktop::{DsdAudioSource, DsdOutputMode, LocalAudioSource, StreamingAudioSource};
+use soul_playback::{AudioSource, TrackRange};
 use std::fs::File;
 use std::io::Write;
 use std::path::PathBuf;
@@ -291,6 +291,205 @@ fn test_local_source_reset_functionality() {
     assert!(!source.is_finished(), "Should not be finished after reset");
 }
 
+/// Generate a stereo WAV whose sample values encode their frame index
+///
+/// Frame `i` holds `i / 32768.0` on both channels, so decoded samples reveal
+/// exactly which frame of the file they came from.
+fn generate_frame_index_wav(path: &PathBuf, frames: u32) {
+    let spec = hound::WavSpec {
+        channels: 2,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(path, spec).unwrap();
+    for i in 0..frames {
+        writer.write_sample(i as i16).unwrap();
+        writer.write_sample(i as i16).unwrap();
+    }
+    writer.finalize().unwrap();
+}
+
+/// Read a source until it reports finished
+fn read_to_end(source: &mut dyn AudioSource) -> Vec<f32> {
+    let mut samples = Vec::new();
+    let mut buffer = vec![0.0f32; 4096];
+    let deadline = std::time::Instant::now() + Duration::from_secs(10);
+
+    while !source.is_finished() && std::time::Instant::now() < deadline {
+        let read = source.read_samples(&mut buffer).unwrap();
+        samples.extend_from_slice(&buffer[..read]);
+        if read == 0 {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    samples
+}
+
+/// Frame index encoded in a sample by `generate_frame_index_wav`
+fn frame_index(sample: f32) -> u32 {
+    (sample * 32768.0).round() as u32
+}
+
+#[test]
+fn test_local_source_range_plays_exact_samples() {
+    let temp_dir = TempDir::new().unwrap();
+    let wav_path = temp_dir.path().join("album.wav");
+    generate_frame_index_wav(&wav_path, 30000);
+
+    // CUE times 00:00:10 - 00:00:40 (CD frames) = samples 5880 - 23520 at 44.1 kHz
+    let range = TrackRange::new(
+        Duration::from_nanos(10 * 1_000_000_000 / 75),
+        Some(Duration::from_nanos(40 * 1_000_000_000 / 75)),
+    );
+    let mut source = LocalAudioSource::with_range(&wav_path, 44100, Some(range)).unwrap();
+
+    assert_eq!(source.duration(), Duration::from_nanos(30 * 1_000_000_000 / 75));
+
+    let samples = read_to_end(&mut source);
+    assert_eq!(samples.len(), (23520 - 5880) * 2, "Range should play exactly its frames");
+    assert_eq!(frame_index(samples[0]), 5880);
+    assert_eq!(frame_index(samples[samples.len() - 1]), 23519);
+    assert!(source.is_finished());
+}
+
+#[test]
+fn test_local_source_range_seek_is_relative_to_start() {
+    let temp_dir = TempDir::new().unwrap();
+    let wav_path = temp_dir.path().join("album.wav");
+    generate_frame_index_wav(&wav_path, 30000);
+
+    let range = TrackRange::new(Duration::from_nanos(10 * 1_000_000_000 / 75), None);
+    let mut source = LocalAudioSource::with_range(&wav_path, 44100, Some(range)).unwrap();
+
+    // Open-ended range runs to the end of the file
+    assert_eq!(
+        source.duration(),
+        Duration::from_secs_f64(30000.0 / 44100.0) - range.start
+    );
+
+    // 100ms into the track = frame 5880 + 4410 of the file
+    source.seek(Duration::from_millis(100)).unwrap();
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    while (source.position().as_secs_f64() - 0.1).abs() > 0.001 {
+        assert!(std::time::Instant::now() < deadline, "Seek was not applied");
+        std::thread::sleep(Duration::from_millis(5));
+    }
+
+    let samples = read_to_end(&mut source);
+    assert_eq!(frame_index(samples[0]), 5880 + 4410);
+    assert_eq!(frame_index(samples[samples.len() - 1]), 29999);
+    assert_eq!(samples.len(), (30000 - 5880 - 4410) * 2);
+}
+
+#[test]
+fn test_local_source_range_past_end_fails() {
+    let temp_dir = TempDir::new().unwrap();
+    let wav_path = temp_dir.path().join("album.wav");
+    generate_frame_index_wav(&wav_path, 4410);
+
+    let range = TrackRange::new(Duration::from_secs(1), None);
+    assert!(LocalAudioSource::with_range(&wav_path, 44100, Some(range)).is_err());
+}
+
+// ===== DsdAudioSource Integration Tests =====
+
+/// Write a stereo DSD64 DSF file from per-channel MSB-first bytes
+fn generate_test_dsf(path: &PathBuf, channels: &[Vec<u8>]) {
+    const BLOCK: usize = 4096;
+    let bytes_per_channel = channels[0].len();
+    let blocks = bytes_per_channel.div_ceil(BLOCK);
+    let data_len = (blocks * BLOCK * channels.len()) as u64;
+
+    let mut out = Vec::new();
+    out.extend_from_slice(b"DSD ");
+    out.extend_from_slice(&28u64.to_le_bytes());
+    out.extend_from_slice(&(28 + 52 + 12 + data_len).to_le_bytes());
+    out.extend_from_slice(&0u64.to_le_bytes()); // no metadata
+
+    out.extend_from_slice(b"fmt ");
+    out.extend_from_slice(&52u64.to_le_bytes());
+    out.extend_from_slice(&1u32.to_le_bytes()); // version
+    out.extend_from_slice(&0u32.to_le_bytes()); // DSD raw
+    out.extend_from_slice(&2u32.to_le_bytes()); // stereo
+    out.extend_from_slice(&(channels.len() as u32).to_le_bytes());
+    out.extend_from_slice(&2_822_400u32.to_le_bytes());
+    out.extend_from_slice(&1u32.to_le_bytes()); // 1 bit, LSB first
+    out.extend_from_slice(&(bytes_per_channel as u64 * 8).to_le_bytes());
+    out.extend_from_slice(&(BLOCK as u32).to_le_bytes());
+    out.extend_from_slice(&0u32.to_le_bytes());
+
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&(12 + data_len).to_le_bytes());
+    for block in 0..blocks {
+        for channel in channels {
+            for i in block * BLOCK..(block + 1) * BLOCK {
+                out.push(channel.get(i).map(|b| b.reverse_bits()).unwrap_or(0));
+            }
+        }
+    }
+
+    std::fs::write(path, out).unwrap();
+}
+
+#[test]
+fn test_dsd_source_dop_is_bit_exact() {
+    let temp_dir = TempDir::new().unwrap();
+    let dsf_path = temp_dir.path().join("track.dsf");
+    // 50ms of DSD64
+    let left: Vec<u8> = (0..17640).map(|i| (i % 251) as u8).collect();
+    let right: Vec<u8> = (0..17640).map(|i| (i % 241) as u8 ^ 0xFF).collect();
+    generate_test_dsf(&dsf_path, &[left.clone(), right.clone()]);
+
+    let mut source = DsdAudioSource::new(&dsf_path, 176_400, None, true).unwrap();
+    assert_eq!(source.mode(), DsdOutputMode::Dop);
+    assert!(source.is_bitstream());
+
+    let samples = read_to_end(&mut source);
+    assert_eq!(samples.len(), 17640 / 2 * 2, "One DoP frame per two DSD bytes");
+
+    for (frame, pair) in samples.chunks_exact(2).enumerate() {
+        let marker = if frame % 2 == 0 { 0x05 } else { 0xFA };
+        for (ch, (&sample, bytes)) in pair.iter().zip([&left, &right]).enumerate() {
+            let word = (sample as f64 * 2_147_483_648.0) as i32 as u32;
+            let expected = (marker << 24)
+                | (u32::from(bytes[frame * 2]) << 16)
+                | (u32::from(bytes[frame * 2 + 1]) << 8);
+            assert_eq!(word, expected, "frame {} channel {}", frame, ch);
+        }
+    }
+}
+
+#[test]
+fn test_dsd_source_falls_back_to_pcm() {
+    let temp_dir = TempDir::new().unwrap();
+    let dsf_path = temp_dir.path().join("silence.dsf");
+    // 100ms of DSD idle pattern
+    let silence = vec![0x69u8; 35280];
+    generate_test_dsf(&dsf_path, &[silence.clone(), silence]);
+
+    // DoP not allowed by the device
+    let mut source = DsdAudioSource::new(&dsf_path, 44100, None, false).unwrap();
+    assert_eq!(source.mode(), DsdOutputMode::Pcm);
+    assert!(!source.is_bitstream());
+    assert_eq!(source.duration(), Duration::from_millis(100));
+
+    let samples = read_to_end(&mut source);
+    // 88.2kHz PCM resampled to 44.1kHz; the resampler delay trims the tail
+    let frames = samples.len() / 2;
+    assert!(
+        (4300..=4410).contains(&frames),
+        "Expected ~4410 frames at 44.1kHz, got {}",
+        frames
+    );
+    assert!(samples.iter().all(|s| s.abs() < 0.01), "Idle pattern should decode to silence");
+
+    // DoP allowed, but the device isn't running at the DoP rate
+    let source = DsdAudioSource::new(&dsf_path, 48000, None, true).unwrap();
+    assert_eq!(source.mode(), DsdOutputMode::Pcm);
+}
+
 // ===== StreamingAudioSource Integration Tests =====
 
 #[test]
@@ -325,7 +524,7 @@ fn test_streaming_source_initial_state() {
 }
 
 #[test]
-fn test_streaming_source_seek_not_supported() {
+fn test_streaming_source_seek_beyond_duration_rejected() {
     let mut source = StreamingAudioSource::new(
         "http://localhost:8080/stream".to_string(),
         44100,
@@ -334,11 +533,12 @@ fn test_streaming_source_seek_not_supported() {
     )
     .unwrap();
 
-    // Seeking should return error
-    let result = source.seek(Duration::from_secs(30));
+    // Seeking past the end should return error
+    // (in-range seeks are covered against a real server in streaming_http_test.rs)
+    let result = source.seek(Duration::from_secs(120));
     assert!(
         result.is_err(),
-        "Streaming source should not support seeking"
+        "Seeking beyond duration should be rejected"
     );
 }
 
@@ -19,6 +19,7 @@ fn create_test_track(id: &str, title: &str, duration_secs: u64) -> QueueTrack {
         duration: Duration::from_secs(duration_secs),
         track_number: Some(1),
         source: TrackSource::Single,
+        ..Default::default()
     }
 }
 
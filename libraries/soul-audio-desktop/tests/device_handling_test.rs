@@ -935,6 +935,7 @@ mod device_switching_tests {
             duration: Duration::from_secs(10),
             track_number: None,
             source: TrackSource::Single,
+            ..Default::default()
         };
 
         // Add track and start playback
@@ -0,0 +1,203 @@
+//! Gapless MP3 playback tests
+//!
+//! Plays an album of MP3 tracks whose LAME tags declare encoder delay and
+//! padding through `PlaybackManager` and checks that the delay and padding
+//! are trimmed: the tracks join without silence in between and play for
+//! exactly their declared length.
+
+use soul_audio::test_utils::mp3::{write_gapless_mp3, MP3_SAMPLE_RATE};
+use soul_audio_desktop::LocalAudioSource;
+use soul_playback::{
+    AudioSource, PlaybackConfig, PlaybackError, PlaybackManager, QueueTrack, TrackSource,
+};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+/// Frames of signal per track (about 0.5s each)
+const TONE_FRAMES: usize = 20;
+
+/// Near-silence threshold (the tone never gets this quiet)
+const SILENCE_THRESHOLD: f32 = 1e-4;
+
+/// Runs of this many silent frames count as an inserted gap
+const MIN_GAP_FRAMES: usize = 4;
+
+fn create_track(id: &str, path: &Path) -> QueueTrack {
+    QueueTrack {
+        id: id.to_string(),
+        path: path.to_path_buf(),
+        title: format!("Track {}", id),
+        artist: "Test Artist".to_string(),
+        album: Some("Gapless Album".to_string()),
+        duration: Duration::from_secs(1),
+        track_number: id.parse().ok(),
+        source: TrackSource::Single,
+        ..Default::default()
+    }
+}
+
+/// Write the album, returning the track paths and playable frames per track
+fn write_album(dir: &TempDir, tracks: usize) -> (Vec<PathBuf>, u64) {
+    let mut frames = 0;
+    let paths = (1..=tracks)
+        .map(|n| {
+            let path = dir.path().join(format!("{:02}.mp3", n));
+            frames = write_gapless_mp3(&path, TONE_FRAMES).expect("Failed to write MP3");
+            path
+        })
+        .collect();
+    (paths, frames)
+}
+
+/// Decode a whole track through `LocalAudioSource`
+///
+/// The decoder thread fills the buffer in the background, so an empty read
+/// only means the end of the track once the source reports it is finished.
+fn decode_track(path: &Path) -> DecodedTrack {
+    let mut source = LocalAudioSource::new(path, MP3_SAMPLE_RATE).expect("Failed to open MP3");
+    let deadline = Instant::now() + Duration::from_secs(10);
+
+    let mut samples = Vec::new();
+    let mut buffer = vec![0.0f32; 4096];
+    while !source.is_finished() {
+        let n = source.read_samples(&mut buffer).unwrap();
+        if n == 0 {
+            assert!(
+                Instant::now() < deadline,
+                "Timed out decoding {}",
+                path.display()
+            );
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        samples.extend_from_slice(&buffer[..n]);
+    }
+
+    DecodedTrack {
+        samples,
+        position: 0,
+        duration: source.duration(),
+    }
+}
+
+/// A decoded track played back from memory
+///
+/// Lets `PlaybackManager` run faster than the decoder threads without
+/// mistaking a momentarily empty buffer for the end of a track.
+struct DecodedTrack {
+    samples: Vec<f32>,
+    position: usize,
+    duration: Duration,
+}
+
+impl AudioSource for DecodedTrack {
+    fn read_samples(&mut self, buffer: &mut [f32]) -> soul_playback::Result<usize> {
+        let n = buffer.len().min(self.samples.len() - self.position);
+        buffer[..n].copy_from_slice(&self.samples[self.position..self.position + n]);
+        self.position += n;
+        Ok(n)
+    }
+
+    fn seek(&mut self, position: Duration) -> soul_playback::Result<()> {
+        let frame = (position.as_secs_f64() * MP3_SAMPLE_RATE as f64) as usize;
+        self.position = (frame * 2).min(self.samples.len());
+        Ok(())
+    }
+
+    fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    fn position(&self) -> Duration {
+        Duration::from_secs_f64((self.position / 2) as f64 / MP3_SAMPLE_RATE as f64)
+    }
+
+    fn is_finished(&self) -> bool {
+        self.position == self.samples.len()
+    }
+}
+
+/// Longest run of silent stereo frames in `samples`
+fn longest_silence(samples: &[f32]) -> usize {
+    let mut longest = 0;
+    let mut run = 0;
+    for frame in samples.chunks_exact(2) {
+        if frame.iter().all(|s| s.abs() <= SILENCE_THRESHOLD) {
+            run += 1;
+            longest = longest.max(run);
+        } else {
+            run = 0;
+        }
+    }
+    longest
+}
+
+#[test]
+fn test_local_source_trims_encoder_delay_and_padding() {
+    let dir = TempDir::new().unwrap();
+    let (paths, frames) = write_album(&dir, 1);
+
+    let track = decode_track(&paths[0]);
+    let expected = Duration::from_secs_f64(frames as f64 / MP3_SAMPLE_RATE as f64);
+    assert!(
+        track.duration.abs_diff(expected) < Duration::from_micros(100),
+        "Duration should exclude delay and padding: {:?} vs {:?}",
+        track.duration,
+        expected
+    );
+
+    let decoded = &track.samples;
+    assert_eq!(decoded.len() as u64, frames * 2);
+    assert!(longest_silence(decoded) < MIN_GAP_FRAMES);
+}
+
+#[test]
+fn test_gapless_mp3_album_has_no_silence_at_track_boundaries() {
+    let dir = TempDir::new().unwrap();
+    let (paths, frames) = write_album(&dir, 3);
+
+    let mut manager = PlaybackManager::new(PlaybackConfig::default());
+    manager.set_sample_rate(MP3_SAMPLE_RATE);
+    manager.set_volume(100);
+    manager.set_audio_source(Box::new(decode_track(&paths[0])));
+
+    // Collect everything the manager writes
+    let mut queued = 1;
+    let mut output = Vec::new();
+    let mut buffer = vec![0.0f32; 1024];
+    for _ in 0..10_000 {
+        if !manager.has_next_source() && queued < paths.len() {
+            let track = create_track(&(queued + 1).to_string(), &paths[queued]);
+            manager.set_next_source(Box::new(decode_track(&paths[queued])), track);
+            queued += 1;
+        }
+
+        // Ending the last track leaves nothing queued to play next
+        let n = match manager.process_audio(&mut buffer) {
+            Ok(0) | Err(PlaybackError::QueueEmpty) => break,
+            result => result.unwrap(),
+        };
+        output.extend_from_slice(&buffer[..n]);
+    }
+    assert_eq!(queued, paths.len());
+
+    // Every track plays exactly its trimmed length, back to back
+    let album_samples = (paths.len() as u64 * frames * 2) as usize;
+    assert!(output.len() >= album_samples);
+    assert!(output[album_samples - 2..album_samples]
+        .iter()
+        .any(|s| s.abs() > SILENCE_THRESHOLD));
+    assert!(output[album_samples..].iter().all(|&s| s == 0.0));
+
+    for boundary in 1..paths.len() as u64 {
+        let at = (boundary * frames) as usize * 2;
+        let window = &output[at - 4096..at + 4096];
+        let silence = longest_silence(window);
+        assert!(
+            silence < MIN_GAP_FRAMES,
+            "Found {} silent frames at the start of track {}",
+            silence,
+            boundary + 1
+        );
+    }
+}
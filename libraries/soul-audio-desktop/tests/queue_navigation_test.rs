@@ -18,6 +18,7 @@ fn create_track(id: &str, title: &str) -> QueueTrack {
         duration: Duration::from_secs(180),
         track_number: Some(1),
         source: TrackSource::Single,
+        ..Default::default()
     }
 }
 
@@ -3,6 +3,7 @@
 //! Tests for packet-based decoding with ring buffer.
 //! Verifies memory bounds, on-demand loading, and performance.
 
+use soul_audio::test_utils::mp3::{write_gapless_mp3, MP3_SAMPLE_RATE};
 use soul_audio_desktop::LocalAudioSource;
 use soul_playback::AudioSource;
 use std::fs::File;
@@ -256,6 +257,46 @@ fn test_multiple_seeks_maintain_accuracy() {
     }
 }
 
+/// Read a source until it runs dry, after letting the decoder thread catch up
+fn read_to_end(source: &mut LocalAudioSource) -> Vec<f32> {
+    std::thread::sleep(Duration::from_millis(300));
+
+    let mut samples = Vec::new();
+    let mut buffer = vec![0.0f32; 4096];
+    loop {
+        let read = source.read_samples(&mut buffer).unwrap();
+        if read == 0 {
+            break;
+        }
+        samples.extend_from_slice(&buffer[..read]);
+    }
+    samples
+}
+
+#[test]
+fn test_seek_is_sample_accurate_in_mp3() {
+    let temp_dir = TempDir::new().unwrap();
+    let mp3_path = temp_dir.path().join("test.mp3");
+    write_gapless_mp3(&mp3_path, 40).unwrap();
+
+    let mut source = LocalAudioSource::new(&mp3_path, MP3_SAMPLE_RATE).unwrap();
+    let full = read_to_end(&mut source);
+
+    // Targets between MP3 frame boundaries (1152 samples)
+    for frame in [1usize, 5000, 23456] {
+        let target = Duration::from_secs_f64(frame as f64 / MP3_SAMPLE_RATE as f64);
+        source.seek(target).unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+
+        let position = source.position().as_secs_f64() * MP3_SAMPLE_RATE as f64;
+        assert_eq!(position.round() as usize, frame);
+
+        let rest = read_to_end(&mut source);
+        assert_eq!(rest.len(), full.len() - frame * 2);
+        assert_eq!(rest, full[frame * 2..], "Audio after seeking to {}", frame);
+    }
+}
+
 #[test]
 fn test_seek_past_end_fails() {
     let temp_dir = TempDir::new().unwrap();
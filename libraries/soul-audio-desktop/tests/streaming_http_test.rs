@@ -0,0 +1,291 @@
+//! Streaming source integration tests against a local HTTP server
+//!
+//! Spins up a minimal HTTP/1.1 server (with Range support, like `soul-server`'s
+//! `/stream/:track_id`) serving a generated WAV file, then verifies that
+//! `StreamingAudioSource` decodes, resamples and seeks the remote file.
+
+use soul_audio_desktop::{HttpMediaSource, StreamingAudioSource};
+use soul_playback::AudioSource;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const SAMPLE_RATE: u32 = 44100;
+const DURATION_SECS: f64 = 3.0;
+const RAMP_PEAK: f64 = 0.9;
+
+/// Generate a stereo 16-bit WAV whose left channel is a slow ramp
+///
+/// The ramp makes every position identifiable from its sample value:
+/// `value = RAMP_PEAK * t / DURATION_SECS`.
+fn generate_ramp_wav() -> Vec<u8> {
+    let spec = hound::WavSpec {
+        channels: 2,
+        sample_rate: SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+
+    let mut cursor = std::io::Cursor::new(Vec::new());
+    {
+        let mut writer = hound::WavWriter::new(&mut cursor, spec).unwrap();
+        let total_frames = (SAMPLE_RATE as f64 * DURATION_SECS) as usize;
+        for i in 0..total_frames {
+            let ramp = RAMP_PEAK * i as f64 / total_frames as f64;
+            let sample = (ramp * 32767.0) as i16;
+            writer.write_sample(sample).unwrap();
+            writer.write_sample(-sample).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+    cursor.into_inner()
+}
+
+/// Expected left-channel value at a position
+fn expected_ramp_value(position: Duration) -> f32 {
+    (RAMP_PEAK * position.as_secs_f64() / DURATION_SECS) as f32
+}
+
+/// Minimal HTTP file server with Range support
+struct TestServer {
+    url: String,
+    /// Range start offsets requested by clients (None = full request)
+    requests: Arc<Mutex<Vec<Option<u64>>>>,
+}
+
+impl TestServer {
+    fn start(body: Vec<u8>, path: &str) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}{}", listener.local_addr().unwrap(), path);
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let body = Arc::new(body);
+
+        let requests_clone = Arc::clone(&requests);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { break };
+                let body = Arc::clone(&body);
+                let requests = Arc::clone(&requests_clone);
+                thread::spawn(move || Self::handle(stream, &body, &requests));
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    fn handle(mut stream: TcpStream, body: &[u8], requests: &Mutex<Vec<Option<u64>>>) {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut range_start = None;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                return;
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some(value) = line
+                .to_ascii_lowercase()
+                .strip_prefix("range: bytes=")
+                .map(str::to_string)
+            {
+                range_start = value.split('-').next().and_then(|s| s.parse::<u64>().ok());
+            }
+        }
+        requests.lock().unwrap().push(range_start);
+
+        let total = body.len() as u64;
+        let header = match range_start {
+            Some(start) => format!(
+                "HTTP/1.1 206 Partial Content\r\nContent-Type: audio/wav\r\nAccept-Ranges: bytes\r\n\
+                 Content-Length: {}\r\nContent-Range: bytes {}-{}/{}\r\nConnection: close\r\n\r\n",
+                total - start,
+                start,
+                total - 1,
+                total
+            ),
+            None => format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: audio/wav\r\nAccept-Ranges: bytes\r\n\
+                 Content-Length: {}\r\nConnection: close\r\n\r\n",
+                total
+            ),
+        };
+
+        let start = range_start.unwrap_or(0) as usize;
+        // Client may disconnect mid-body after a seek; ignore write errors
+        let _ = stream.write_all(header.as_bytes());
+        let _ = stream.write_all(&body[start..]);
+    }
+
+    fn range_requests(&self) -> Vec<u64> {
+        self.requests.lock().unwrap().iter().flatten().copied().collect()
+    }
+}
+
+/// Read from a source until `samples` are collected or the stream finishes
+fn read_samples_blocking(source: &mut StreamingAudioSource, samples: usize) -> Vec<f32> {
+    let mut collected = Vec::with_capacity(samples);
+    let mut buffer = vec![0.0f32; 2048];
+    let deadline = Instant::now() + Duration::from_secs(10);
+
+    while collected.len() < samples && Instant::now() < deadline {
+        let wanted = (samples - collected.len()).min(buffer.len());
+        let read = source.read_samples(&mut buffer[..wanted]).unwrap();
+        collected.extend_from_slice(&buffer[..read]);
+        if read == 0 {
+            if source.is_finished() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    collected
+}
+
+#[test]
+fn test_http_media_source_reads_and_seeks_with_range() {
+    let body = generate_ramp_wav();
+    let server = TestServer::start(body.clone(), "/stream/track.wav");
+
+    let mut source = HttpMediaSource::open(server.url.as_str()).unwrap();
+    assert_eq!(source.content_length(), Some(body.len() as u64));
+    assert!(source.supports_ranges());
+
+    let mut head = [0u8; 12];
+    source.read_exact(&mut head).unwrap();
+    assert_eq!(&head, &body[..12]);
+
+    // Large forward seek triggers a Range request
+    let offset = body.len() as u64 / 2;
+    source.seek(SeekFrom::Start(offset)).unwrap();
+    let mut chunk = [0u8; 64];
+    source.read_exact(&mut chunk).unwrap();
+    assert_eq!(&chunk[..], &body[offset as usize..offset as usize + 64]);
+    assert!(server.range_requests().contains(&offset));
+
+    // Backward seek also reconnects
+    source.seek(SeekFrom::Start(100)).unwrap();
+    source.read_exact(&mut chunk).unwrap();
+    assert_eq!(&chunk[..], &body[100..164]);
+}
+
+#[test]
+fn test_streaming_source_decodes_container_format() {
+    let server = TestServer::start(generate_ramp_wav(), "/stream/track.wav");
+
+    let mut source = StreamingAudioSource::new(
+        server.url.clone(),
+        SAMPLE_RATE,
+        2,
+        Duration::from_secs_f64(DURATION_SECS),
+    )
+    .unwrap();
+
+    let expected_samples = (SAMPLE_RATE as f64 * DURATION_SECS) as usize * 2;
+    let samples = read_samples_blocking(&mut source, expected_samples + 4096);
+
+    assert_eq!(samples.len(), expected_samples, "Should decode the entire file");
+    assert!(source.is_finished());
+    assert_eq!(source.source_sample_rate(), Some(SAMPLE_RATE));
+
+    // Decoded container samples, not raw bytes reinterpreted as f32
+    let mid_frame = expected_samples / 4;
+    let expected = expected_ramp_value(Duration::from_secs_f64(DURATION_SECS / 2.0));
+    assert!((samples[mid_frame * 2] - expected).abs() < 0.01);
+    assert!((samples[mid_frame * 2 + 1] + expected).abs() < 0.01);
+}
+
+#[test]
+fn test_streaming_source_seek_uses_range_request() {
+    let server = TestServer::start(generate_ramp_wav(), "/stream/track.wav");
+
+    let mut source = StreamingAudioSource::new(
+        server.url.clone(),
+        SAMPLE_RATE,
+        2,
+        Duration::from_secs_f64(DURATION_SECS),
+    )
+    .unwrap();
+
+    // Let the initial buffer fill, then seek
+    let _ = read_samples_blocking(&mut source, 4096);
+    let target = Duration::from_secs(2);
+    source.seek(target).unwrap();
+
+    // Wait for the decoder thread to apply the seek
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while (source.position().as_secs_f64() - target.as_secs_f64()).abs() > 0.05 {
+        assert!(Instant::now() < deadline, "Seek was not applied");
+        thread::sleep(Duration::from_millis(5));
+    }
+
+    let samples = read_samples_blocking(&mut source, 1024);
+    assert!(!samples.is_empty());
+    let expected = expected_ramp_value(target);
+    assert!(
+        (samples[0] - expected).abs() < 0.01,
+        "After seek expected ~{:.4}, got {:.4}",
+        expected,
+        samples[0]
+    );
+
+    assert!(
+        !server.range_requests().is_empty(),
+        "Seek should have issued an HTTP Range request"
+    );
+}
+
+#[test]
+fn test_streaming_source_resamples_to_device_rate() {
+    let server = TestServer::start(generate_ramp_wav(), "/stream/track.wav");
+
+    let target_rate = 48000;
+    let mut source = StreamingAudioSource::new(
+        server.url.clone(),
+        target_rate,
+        2,
+        Duration::from_secs_f64(DURATION_SECS),
+    )
+    .unwrap();
+
+    let expected_samples = (target_rate as f64 * DURATION_SECS) as usize * 2;
+    let samples = read_samples_blocking(&mut source, expected_samples * 2);
+
+    let tolerance = (target_rate as usize / 10) * 2; // 100ms
+    assert!(
+        samples.len().abs_diff(expected_samples) < tolerance,
+        "Expected ~{} samples at {} Hz, got {}",
+        expected_samples,
+        target_rate,
+        samples.len()
+    );
+    assert_eq!(source.sample_rate(), target_rate);
+}
+
+#[test]
+fn test_streaming_source_reports_connection_error() {
+    let mut source = StreamingAudioSource::new(
+        "http://127.0.0.1:9/stream/missing.flac".to_string(),
+        SAMPLE_RATE,
+        2,
+        Duration::from_secs(60),
+    )
+    .unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut buffer = vec![0.0f32; 1024];
+    loop {
+        match source.read_samples(&mut buffer) {
+            Err(_) => break,
+            Ok(_) => {
+                assert!(Instant::now() < deadline, "Connection error was not reported");
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+    }
+    assert!(buffer.iter().all(|&s| s == 0.0));
+}
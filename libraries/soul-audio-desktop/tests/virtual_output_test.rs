@@ -0,0 +1,315 @@
+//! Headless playback through virtual output devices
+//!
+//! Runs `DesktopPlayback` end to end on the virtual backend: tracks are
+//! decoded, processed and rendered to WAV files or the null sink without a
+//! sound card, and device switches and sample rate changes behave as they
+//! do with hardware devices.
+//!
+//! The virtual device registry is process-wide, so every test registers
+//! devices under its own names.
+
+use soul_audio_desktop::virtual_output::{self, NULL_DEVICE_NAME};
+use soul_audio_desktop::{
+    AudioBackend, DesktopPlayback, PlaybackCommand, PlaybackEvent, SupportedBitDepth, VirtualClock,
+    VirtualDevice,
+};
+use soul_playback::{PlaybackConfig, QueueTrack, TrackSource};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+/// Write a stereo 16-bit 440 Hz tone at half scale
+fn write_tone_wav(path: &Path, sample_rate: u32, duration_secs: f64) {
+    let spec = hound::WavSpec {
+        channels: 2,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(path, spec).unwrap();
+    let frames = (sample_rate as f64 * duration_secs) as usize;
+    for i in 0..frames {
+        let t = i as f64 / sample_rate as f64;
+        let sample = ((2.0 * std::f64::consts::PI * 440.0 * t).sin() * 16384.0) as i16;
+        writer.write_sample(sample).unwrap();
+        writer.write_sample(sample).unwrap();
+    }
+    writer.finalize().unwrap();
+}
+
+fn create_track(id: &str, path: &Path, duration_secs: u64) -> QueueTrack {
+    QueueTrack {
+        id: id.to_string(),
+        path: path.to_path_buf(),
+        title: format!("Track {}", id),
+        artist: "Test Artist".to_string(),
+        album: None,
+        duration: Duration::from_secs(duration_secs),
+        track_number: None,
+        source: TrackSource::Single,
+        ..Default::default()
+    }
+}
+
+fn full_volume() -> PlaybackConfig {
+    PlaybackConfig {
+        volume: 100,
+        ..Default::default()
+    }
+}
+
+/// Drain events until one matches, failing after `timeout`
+fn wait_for_event(
+    playback: &DesktopPlayback,
+    timeout: Duration,
+    matches: impl Fn(&PlaybackEvent) -> bool,
+) -> Vec<PlaybackEvent> {
+    let start = Instant::now();
+    let mut events = Vec::new();
+    loop {
+        while let Some(event) = playback.try_recv_event() {
+            let found = matches(&event);
+            events.push(event);
+            if found {
+                return events;
+            }
+        }
+        assert!(
+            start.elapsed() < timeout,
+            "Timed out waiting for event; got {:?}",
+            events
+        );
+        std::thread::sleep(Duration::from_millis(5));
+    }
+}
+
+/// Drain events for a while (keeps the bounded event channel from filling)
+fn drain_events_for(playback: &DesktopPlayback, duration: Duration) -> Vec<PlaybackEvent> {
+    let start = Instant::now();
+    let mut events = Vec::new();
+    while start.elapsed() < duration {
+        while let Some(event) = playback.try_recv_event() {
+            events.push(event);
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    events
+}
+
+/// Frames with any non-zero sample
+fn audible_frames<T: Copy + Default + PartialEq>(samples: &[T], channels: usize) -> usize {
+    samples
+        .chunks_exact(channels)
+        .filter(|frame| frame.iter().any(|&s| s != T::default()))
+        .count()
+}
+
+#[test]
+fn test_renders_track_to_wav_as_fast_as_possible() {
+    let dir = TempDir::new().unwrap();
+    let track_path = dir.path().join("tone.wav");
+    let output_path = dir.path().join("render.wav");
+    write_tone_wav(&track_path, 44100, 1.0);
+
+    let name = "render 16-bit unpaced";
+    virtual_output::register_device(
+        VirtualDevice::wav(name, &output_path)
+            .with_sample_rate(44100)
+            .with_bit_depth(SupportedBitDepth::Int16)
+            .with_buffer_frames(1024)
+            .with_clock(VirtualClock::Unpaced),
+    )
+    .unwrap();
+
+    let playback =
+        DesktopPlayback::new_with_device(full_volume(), AudioBackend::Virtual, Some(name.into()))
+            .expect("Virtual output needs no sound hardware");
+    assert_eq!(playback.get_current_backend(), AudioBackend::Virtual);
+    assert_eq!(playback.get_current_device(), name);
+    assert_eq!(playback.get_current_sample_rate(), 44100);
+    assert_eq!(playback.get_latency_info().buffer_samples, 1024);
+
+    // Without dither, silence renders as exact zeros
+    playback.set_dither_mode("none").unwrap();
+    playback
+        .send_command(PlaybackCommand::AddToQueue(create_track(
+            "1",
+            &track_path,
+            1,
+        )))
+        .unwrap();
+    playback.send_command(PlaybackCommand::Play).unwrap();
+
+    // The end of the queue is reported as no current track
+    let start = Instant::now();
+    wait_for_event(&playback, Duration::from_secs(10), |event| {
+        matches!(event, PlaybackEvent::TrackChanged(None))
+    });
+    assert!(
+        start.elapsed() < Duration::from_secs(1),
+        "Unpaced rendering should beat real time, took {:?}",
+        start.elapsed()
+    );
+
+    // Dropping the playback finalizes the file
+    drop(playback);
+
+    let mut reader = hound::WavReader::open(&output_path).unwrap();
+    let spec = reader.spec();
+    assert_eq!(spec.sample_rate, 44100);
+    assert_eq!(spec.bits_per_sample, 16);
+    assert_eq!(spec.channels, 2);
+
+    let samples: Vec<i16> = reader.samples().map(|s| s.unwrap()).collect();
+    let audible = audible_frames(&samples, 2);
+    assert!(
+        audible.abs_diff(44100) < 441,
+        "Expected one second of tone, got {} audible frames",
+        audible
+    );
+    let peak = samples.iter().map(|s| s.unsigned_abs()).max().unwrap();
+    assert!(peak > 8000, "Tone should reach the output, peak {}", peak);
+
+    virtual_output::remove_device(name);
+}
+
+#[test]
+fn test_device_switch_changes_sample_rate_and_format() {
+    let dir = TempDir::new().unwrap();
+    let track_path = dir.path().join("tone.wav");
+    let first_path = dir.path().join("first.wav");
+    let second_path = dir.path().join("second.wav");
+    write_tone_wav(&track_path, 44100, 3.0);
+
+    let first = "switch from 44.1k float";
+    let second = "switch to 48k 24-bit";
+    virtual_output::register_device(
+        VirtualDevice::wav(first, &first_path)
+            .with_sample_rate(44100)
+            .with_buffer_frames(441),
+    )
+    .unwrap();
+    virtual_output::register_device(
+        VirtualDevice::wav(second, &second_path)
+            .with_sample_rate(48000)
+            .with_bit_depth(SupportedBitDepth::Int24)
+            .with_buffer_frames(480),
+    )
+    .unwrap();
+
+    let mut playback =
+        DesktopPlayback::new_with_device(full_volume(), AudioBackend::Virtual, Some(first.into()))
+            .unwrap();
+    playback
+        .send_command(PlaybackCommand::AddToQueue(create_track(
+            "1",
+            &track_path,
+            3,
+        )))
+        .unwrap();
+    playback.send_command(PlaybackCommand::Play).unwrap();
+    wait_for_event(&playback, Duration::from_secs(5), |event| {
+        matches!(event, PlaybackEvent::TrackChanged(Some(_)))
+    });
+    drain_events_for(&playback, Duration::from_millis(300));
+
+    playback
+        .switch_device(AudioBackend::Virtual, Some(second.into()))
+        .unwrap();
+    assert_eq!(playback.get_current_device(), second);
+    assert_eq!(playback.get_current_sample_rate(), 48000);
+    assert!(!playback.is_following_default_device());
+
+    let events = drain_events_for(&playback, Duration::from_millis(300));
+    assert!(events
+        .iter()
+        .any(|e| matches!(e, PlaybackEvent::SampleRateChanged(44100, 48000))));
+    assert!(events.iter().any(|e| matches!(
+        e,
+        PlaybackEvent::DeviceChanged {
+            backend: AudioBackend::Virtual,
+            device_name,
+        } if device_name == second
+    )));
+    assert_eq!(playback.get_state(), soul_playback::PlaybackState::Playing);
+    drop(playback);
+
+    let mut reader = hound::WavReader::open(&first_path).unwrap();
+    assert_eq!(reader.spec().sample_rate, 44100);
+    assert_eq!(reader.spec().sample_format, hound::SampleFormat::Float);
+    let samples: Vec<f32> = reader.samples().map(|s| s.unwrap()).collect();
+    assert!(audible_frames(&samples, 2) > 4410, "First device played");
+
+    let mut reader = hound::WavReader::open(&second_path).unwrap();
+    assert_eq!(reader.spec().sample_rate, 48000);
+    assert_eq!(reader.spec().bits_per_sample, 24);
+    let samples: Vec<i32> = reader.samples().map(|s| s.unwrap()).collect();
+    let peak = samples.iter().map(|s| s.unsigned_abs()).max().unwrap();
+    assert!(peak > 1 << 21, "Second device played, peak {}", peak);
+
+    virtual_output::remove_device(first);
+    virtual_output::remove_device(second);
+}
+
+#[test]
+fn test_detects_device_sample_rate_change() {
+    let name = "rate change";
+    virtual_output::register_device(VirtualDevice::null(name).with_sample_rate(44100)).unwrap();
+
+    let mut playback = DesktopPlayback::new_with_device(
+        PlaybackConfig::default(),
+        AudioBackend::Virtual,
+        Some(name.into()),
+    )
+    .unwrap();
+    assert_eq!(playback.query_device_sample_rate().unwrap(), 44100);
+    assert!(!playback.check_and_update_sample_rate().unwrap());
+
+    // Like changing the rate in a driver's control panel
+    virtual_output::register_device(VirtualDevice::null(name).with_sample_rate(96000)).unwrap();
+    assert!(playback.check_and_update_sample_rate().unwrap());
+    assert_eq!(playback.get_current_sample_rate(), 96000);
+    assert_eq!(playback.get_current_device(), name);
+    assert!(!playback.check_and_update_sample_rate().unwrap());
+
+    let events = drain_events_for(&playback, Duration::from_millis(50));
+    assert!(events
+        .iter()
+        .any(|e| matches!(e, PlaybackEvent::SampleRateChanged(44100, 96000))));
+
+    virtual_output::remove_device(name);
+}
+
+/// The only test that changes the virtual backend's default device
+#[test]
+fn test_follows_default_virtual_device() {
+    let mut playback =
+        DesktopPlayback::new_with_device(PlaybackConfig::default(), AudioBackend::Virtual, None)
+            .unwrap();
+    assert_eq!(playback.get_current_device(), NULL_DEVICE_NAME);
+    assert!(playback.is_following_default_device());
+    assert!(!playback.check_default_device().unwrap());
+
+    let name = "new default";
+    virtual_output::register_device(VirtualDevice::null(name).with_sample_rate(88200)).unwrap();
+    virtual_output::set_default_device(name).unwrap();
+    assert!(playback.check_default_device().unwrap());
+    assert_eq!(playback.get_current_device(), name);
+    assert_eq!(playback.get_current_sample_rate(), 88200);
+    assert!(playback.is_following_default_device());
+
+    // Removing the default falls back to the null device
+    assert!(virtual_output::remove_device(name));
+    assert!(playback.check_default_device().unwrap());
+    assert_eq!(playback.get_current_device(), NULL_DEVICE_NAME);
+}
+
+#[test]
+fn test_unknown_virtual_device_is_an_error() {
+    let result = DesktopPlayback::new_with_device(
+        PlaybackConfig::default(),
+        AudioBackend::Virtual,
+        Some("no such virtual device".into()),
+    );
+    assert!(result.is_err());
+}
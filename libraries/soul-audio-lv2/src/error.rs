@@ -0,0 +1,42 @@
+/// LV2 host errors
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Result type for LV2 operations
+pub type Result<T> = std::result::Result<T, Lv2Error>;
+
+/// LV2 host errors
+#[derive(Debug, Error)]
+pub enum Lv2Error {
+    /// Failed to read a bundle file
+    #[error("Failed to read {path}: {message}")]
+    BundleRead { path: PathBuf, message: String },
+
+    /// Invalid Turtle in a bundle file
+    #[error("Invalid Turtle in {path}: {message}")]
+    Parse { path: PathBuf, message: String },
+
+    /// Invalid plugin description
+    #[error("Invalid description of {uri}: {message}")]
+    InvalidPlugin { uri: String, message: String },
+
+    /// No plugin with this URI is installed
+    #[error("LV2 plugin not found: {0}")]
+    PluginNotFound(String),
+
+    /// The plugin needs something this host doesn't provide
+    #[error("Unsupported LV2 plugin {uri}: {reason}")]
+    Unsupported { uri: String, reason: String },
+
+    /// Failed to load the plugin binary
+    #[error("Failed to load plugin binary {path}: {message}")]
+    LoadError { path: PathBuf, message: String },
+
+    /// The binary doesn't contain the plugin
+    #[error("Plugin {0} not found in its binary")]
+    DescriptorNotFound(String),
+
+    /// The plugin refused to instantiate
+    #[error("Failed to instantiate {uri} at {sample_rate} Hz")]
+    InstantiateFailed { uri: String, sample_rate: u32 },
+}
@@ -0,0 +1,186 @@
+//! LV2 C ABI
+//!
+//! Mirrors `lv2/core/lv2.h` and `lv2/urid/urid.h`. The types are public so
+//! plugins compiled into the host binary can be wrapped with
+//! [`Lv2Plugin::from_descriptor`](crate::Lv2Plugin::from_descriptor).
+
+#![allow(unsafe_code)]
+
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::sync::{Mutex, OnceLock};
+
+/// Opaque plugin instance
+pub type Lv2Handle = *mut c_void;
+
+/// A host feature passed to `instantiate`
+#[repr(C)]
+pub struct Lv2Feature {
+    pub uri: *const c_char,
+    pub data: *mut c_void,
+}
+
+/// Plugin descriptor, as returned by `lv2_descriptor()`
+#[repr(C)]
+pub struct Lv2Descriptor {
+    pub uri: *const c_char,
+    pub instantiate: Option<
+        unsafe extern "C" fn(
+            descriptor: *const Lv2Descriptor,
+            sample_rate: f64,
+            bundle_path: *const c_char,
+            features: *const *const Lv2Feature,
+        ) -> Lv2Handle,
+    >,
+    pub connect_port:
+        Option<unsafe extern "C" fn(instance: Lv2Handle, port: u32, data: *mut c_void)>,
+    pub activate: Option<unsafe extern "C" fn(instance: Lv2Handle)>,
+    pub run: Option<unsafe extern "C" fn(instance: Lv2Handle, sample_count: u32)>,
+    pub deactivate: Option<unsafe extern "C" fn(instance: Lv2Handle)>,
+    pub cleanup: Option<unsafe extern "C" fn(instance: Lv2Handle)>,
+    pub extension_data: Option<unsafe extern "C" fn(uri: *const c_char) -> *const c_void>,
+}
+
+// Descriptors are immutable static data in the plugin binary
+unsafe impl Sync for Lv2Descriptor {}
+
+/// Signature of the `lv2_descriptor` entry point
+pub type Lv2DescriptorFn = unsafe extern "C" fn(index: u32) -> *const Lv2Descriptor;
+
+/// Name of the entry point exported by plugin binaries
+pub const DESCRIPTOR_SYMBOL: &[u8] = b"lv2_descriptor\0";
+
+/// URI of the URID map feature
+pub const URID_MAP_URI: &[u8] = b"http://lv2plug.in/ns/ext/urid#map\0";
+
+/// URI of the URID unmap feature
+pub const URID_UNMAP_URI: &[u8] = b"http://lv2plug.in/ns/ext/urid#unmap\0";
+
+type UridMapHandle = *mut c_void;
+
+#[repr(C)]
+struct Lv2UridMap {
+    handle: UridMapHandle,
+    map: unsafe extern "C" fn(handle: UridMapHandle, uri: *const c_char) -> u32,
+}
+
+#[repr(C)]
+struct Lv2UridUnmap {
+    handle: UridMapHandle,
+    unmap: unsafe extern "C" fn(handle: UridMapHandle, urid: u32) -> *const c_char,
+}
+
+/// Process-wide URI <-> integer mapping
+///
+/// URIDs start at 1 (0 means "no URID") and never change for the lifetime
+/// of the process, as the URID extension requires.
+struct UridTable {
+    uris: Mutex<Vec<CString>>,
+}
+
+fn urid_table() -> &'static UridTable {
+    static TABLE: OnceLock<UridTable> = OnceLock::new();
+    TABLE.get_or_init(|| UridTable {
+        uris: Mutex::new(Vec::new()),
+    })
+}
+
+unsafe extern "C" fn urid_map(_handle: UridMapHandle, uri: *const c_char) -> u32 {
+    if uri.is_null() {
+        return 0;
+    }
+    let uri = CStr::from_ptr(uri);
+    let Ok(mut uris) = urid_table().uris.lock() else {
+        return 0;
+    };
+    if let Some(position) = uris.iter().position(|u| u.as_c_str() == uri) {
+        return position as u32 + 1;
+    }
+    uris.push(uri.to_owned());
+    uris.len() as u32
+}
+
+unsafe extern "C" fn urid_unmap(_handle: UridMapHandle, urid: u32) -> *const c_char {
+    let Ok(uris) = urid_table().uris.lock() else {
+        return std::ptr::null();
+    };
+    // The CString's heap buffer stays put when the Vec grows
+    match (urid as usize).checked_sub(1).and_then(|i| uris.get(i)) {
+        Some(uri) => uri.as_ptr(),
+        None => std::ptr::null(),
+    }
+}
+
+/// Features offered to every plugin instance
+///
+/// Boxed so the pointers handed to the plugin stay valid when the owner
+/// moves. Must outlive the instances created with it.
+pub(crate) struct HostFeatures {
+    map: Lv2UridMap,
+    unmap: Lv2UridUnmap,
+    features: [Lv2Feature; 2],
+    list: [*const Lv2Feature; 3],
+}
+
+impl HostFeatures {
+    #[allow(clippy::unnecessary_box_returns)]
+    pub(crate) fn new() -> Box<Self> {
+        let mut host = Box::new(Self {
+            map: Lv2UridMap {
+                handle: std::ptr::null_mut(),
+                map: urid_map,
+            },
+            unmap: Lv2UridUnmap {
+                handle: std::ptr::null_mut(),
+                unmap: urid_unmap,
+            },
+            features: [
+                Lv2Feature {
+                    uri: URID_MAP_URI.as_ptr().cast(),
+                    data: std::ptr::null_mut(),
+                },
+                Lv2Feature {
+                    uri: URID_UNMAP_URI.as_ptr().cast(),
+                    data: std::ptr::null_mut(),
+                },
+            ],
+            list: [std::ptr::null(); 3],
+        });
+
+        host.features[0].data = std::ptr::addr_of_mut!(host.map).cast();
+        host.features[1].data = std::ptr::addr_of_mut!(host.unmap).cast();
+        host.list[0] = std::ptr::addr_of!(host.features[0]);
+        host.list[1] = std::ptr::addr_of!(host.features[1]);
+        host
+    }
+
+    /// Null-terminated feature list for `instantiate`
+    pub(crate) fn as_ptr(&self) -> *const *const Lv2Feature {
+        self.list.as_ptr()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_urid_map_round_trip() {
+        let host = HostFeatures::new();
+        let map = unsafe { &*(host.features[0].data as *const Lv2UridMap) };
+        let unmap = unsafe { &*(host.features[1].data as *const Lv2UridUnmap) };
+
+        let a = unsafe { (map.map)(map.handle, b"urn:soul:test:a\0".as_ptr().cast()) };
+        let b = unsafe { (map.map)(map.handle, b"urn:soul:test:b\0".as_ptr().cast()) };
+        assert_ne!(a, 0);
+        assert_ne!(a, b);
+        assert_eq!(
+            unsafe { (map.map)(map.handle, b"urn:soul:test:a\0".as_ptr().cast()) },
+            a
+        );
+
+        let uri = unsafe { CStr::from_ptr((unmap.unmap)(unmap.handle, b)) };
+        assert_eq!(uri.to_str(), Ok("urn:soul:test:b"));
+        assert!(unsafe { (unmap.unmap)(unmap.handle, 0) }.is_null());
+        assert!(host.list[2].is_null());
+    }
+}
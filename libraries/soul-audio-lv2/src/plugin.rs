@@ -0,0 +1,527 @@
+//! Plugin instances
+//!
+//! [`Lv2Plugin`] wraps an instantiated plugin as an [`AudioEffect`] and a
+//! [`PipelineComponent`], so it can sit in an `EffectChain` or be created
+//! through the `EffectRegistry` like the built-in effects.
+//!
+//! The pipeline is stereo. Stereo plugins run as a single instance, mono
+//! plugins as two (one per channel), and mono-to-stereo plugins get the
+//! mono sum of the input.
+
+#![allow(unsafe_code)]
+
+use crate::error::{Lv2Error, Result};
+use crate::ffi::{HostFeatures, Lv2Descriptor, Lv2DescriptorFn, Lv2Handle, DESCRIPTOR_SYMBOL};
+use crate::world::{Lv2PluginInfo, Lv2PortDirection, Lv2PortKind};
+use serde::{Deserialize, Serialize};
+use soul_audio::effects::AudioEffect;
+use soul_audio::pipeline::{PipelineComponent, PipelineComponentInfo};
+use std::any::Any;
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::ffi::{CStr, CString};
+
+/// Largest block passed to the plugin's `run()`
+///
+/// Longer buffers are processed in several runs.
+pub const MAX_BLOCK_FRAMES: usize = 1024;
+
+/// Serialisable plugin settings, used for presets
+///
+/// Controls are keyed by port symbol, which (unlike port indices) plugins
+/// keep stable across versions. Symbols the plugin doesn't know are
+/// ignored, missing ones keep their current value.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Lv2Params {
+    /// URI of the plugin these settings belong to
+    pub plugin_uri: String,
+    /// Control input values by port symbol
+    #[serde(default)]
+    pub controls: BTreeMap<String, f32>,
+}
+
+impl Lv2Params {
+    /// Settings for a plugin, with every control at its current value
+    pub fn new(plugin_uri: impl Into<String>) -> Self {
+        Self {
+            plugin_uri: plugin_uri.into(),
+            controls: BTreeMap::new(),
+        }
+    }
+
+    /// Set a control value
+    #[must_use]
+    pub fn with_control(mut self, symbol: impl Into<String>, value: f32) -> Self {
+        self.controls.insert(symbol.into(), value);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IoLayout {
+    /// One mono instance per channel
+    DualMono,
+    /// Mono sum in, stereo out
+    MonoToStereo,
+    /// Stereo in, stereo out
+    Stereo,
+}
+
+/// Port memory shared with the plugin
+///
+/// The plugin holds raw pointers into it, so values are only ever accessed
+/// through `Cell`s and the boxed slice never moves or resizes.
+struct PortBuffer(Box<[Cell<f32>]>);
+
+impl PortBuffer {
+    fn new(len: usize) -> Self {
+        Self((0..len).map(|_| Cell::new(0.0)).collect())
+    }
+
+    fn ptr(&self, index: usize) -> *mut f32 {
+        self.0[index].as_ptr()
+    }
+}
+
+struct Instance {
+    handle: Lv2Handle,
+    audio_inputs: Vec<PortBuffer>,
+    audio_outputs: Vec<PortBuffer>,
+    /// Control outputs, indexed by port index
+    control_outputs: PortBuffer,
+}
+
+/// A running LV2 plugin
+pub struct Lv2Plugin {
+    info: Lv2PluginInfo,
+    descriptor: *const Lv2Descriptor,
+    instances: Vec<Instance>,
+    /// Control inputs, indexed by port index, shared by all instances
+    controls: PortBuffer,
+    layout: IoLayout,
+    sample_rate: u32,
+    enabled: bool,
+    active: bool,
+    features: Box<HostFeatures>,
+    /// Keeps the plugin binary loaded; dropped after the instances
+    library: Option<libloading::Library>,
+}
+
+// Instances are only touched through `&mut self`. LV2 allows instantiation
+// and audio-class calls from any thread as long as they aren't concurrent.
+unsafe impl Send for Lv2Plugin {}
+
+impl Lv2Plugin {
+    /// Load a plugin's binary and instantiate it
+    ///
+    /// The plugin only processes audio at `sample_rate`; buffers at other
+    /// rates pass through unchanged, so recreate the plugin when the output
+    /// rate changes.
+    pub fn instantiate(info: &Lv2PluginInfo, sample_rate: u32) -> Result<Self> {
+        if let Some(reason) = info.unsupported_reason() {
+            return Err(Lv2Error::Unsupported {
+                uri: info.uri.clone(),
+                reason,
+            });
+        }
+
+        let load_error = |message: String| Lv2Error::LoadError {
+            path: info.binary.clone(),
+            message,
+        };
+        let library = unsafe { libloading::Library::new(&info.binary) }
+            .map_err(|e| load_error(e.to_string()))?;
+        let descriptor = unsafe {
+            let entry = library
+                .get::<Lv2DescriptorFn>(DESCRIPTOR_SYMBOL)
+                .map_err(|e| load_error(e.to_string()))?;
+            find_descriptor(*entry, &info.uri)
+        }
+        .ok_or_else(|| Lv2Error::DescriptorNotFound(info.uri.clone()))?;
+
+        unsafe { Self::create(info, descriptor, sample_rate, Some(library)) }
+    }
+
+    /// Instantiate a plugin from a descriptor that is already loaded
+    ///
+    /// For plugins compiled into the host binary.
+    ///
+    /// # Safety
+    /// `descriptor` must be a valid LV2 descriptor implementing the plugin
+    /// described by `info`, with ports matching `info.ports`.
+    pub unsafe fn from_descriptor(
+        info: &Lv2PluginInfo,
+        descriptor: &'static Lv2Descriptor,
+        sample_rate: u32,
+    ) -> Result<Self> {
+        if let Some(reason) = info.unsupported_reason() {
+            return Err(Lv2Error::Unsupported {
+                uri: info.uri.clone(),
+                reason,
+            });
+        }
+        Self::create(info, descriptor, sample_rate, None)
+    }
+
+    unsafe fn create(
+        info: &Lv2PluginInfo,
+        descriptor: *const Lv2Descriptor,
+        sample_rate: u32,
+        library: Option<libloading::Library>,
+    ) -> Result<Self> {
+        let failed = || Lv2Error::InstantiateFailed {
+            uri: info.uri.clone(),
+            sample_rate,
+        };
+
+        let audio_inputs = info.audio_ports(Lv2PortDirection::Input).count();
+        let audio_outputs = info.audio_ports(Lv2PortDirection::Output).count();
+        let layout = match (audio_inputs, audio_outputs) {
+            (1, 1) => IoLayout::DualMono,
+            (1, 2) => IoLayout::MonoToStereo,
+            _ => IoLayout::Stereo,
+        };
+
+        // LV2 bundle paths end with a separator
+        let bundle_path =
+            CString::new(format!("{}/", info.bundle_path.display())).map_err(|_| failed())?;
+
+        let controls = PortBuffer::new(info.ports.len());
+        for port in info.parameters() {
+            controls.0[port.index as usize].set(port.initial_value());
+        }
+
+        let mut plugin = Self {
+            info: info.clone(),
+            descriptor,
+            instances: Vec::new(),
+            controls,
+            layout,
+            sample_rate,
+            enabled: true,
+            active: false,
+            features: HostFeatures::new(),
+            library,
+        };
+
+        let desc = &*descriptor;
+        let (Some(instantiate), Some(connect_port)) = (desc.instantiate, desc.connect_port) else {
+            return Err(failed());
+        };
+
+        let instance_count = if layout == IoLayout::DualMono { 2 } else { 1 };
+        for _ in 0..instance_count {
+            let handle = instantiate(
+                descriptor,
+                f64::from(sample_rate),
+                bundle_path.as_ptr(),
+                plugin.features.as_ptr(),
+            );
+            if handle.is_null() {
+                // Earlier instances are cleaned up when `plugin` drops
+                return Err(failed());
+            }
+
+            let instance = Instance {
+                handle,
+                audio_inputs: (0..audio_inputs)
+                    .map(|_| PortBuffer::new(MAX_BLOCK_FRAMES))
+                    .collect(),
+                audio_outputs: (0..audio_outputs)
+                    .map(|_| PortBuffer::new(MAX_BLOCK_FRAMES))
+                    .collect(),
+                control_outputs: PortBuffer::new(info.ports.len()),
+            };
+
+            let (mut next_input, mut next_output) = (0, 0);
+            for port in &info.ports {
+                let index = port.index as usize;
+                let data = match (port.kind, port.direction) {
+                    (Lv2PortKind::Audio, Lv2PortDirection::Input) => {
+                        next_input += 1;
+                        instance.audio_inputs[next_input - 1].ptr(0)
+                    }
+                    (Lv2PortKind::Audio, Lv2PortDirection::Output) => {
+                        next_output += 1;
+                        instance.audio_outputs[next_output - 1].ptr(0)
+                    }
+                    (Lv2PortKind::Control, Lv2PortDirection::Input) => plugin.controls.ptr(index),
+                    (Lv2PortKind::Control, Lv2PortDirection::Output) => {
+                        instance.control_outputs.ptr(index)
+                    }
+                    (Lv2PortKind::Other, _) => std::ptr::null_mut(),
+                };
+                connect_port(handle, port.index, data.cast());
+            }
+
+            plugin.instances.push(instance);
+        }
+
+        plugin.activate();
+        Ok(plugin)
+    }
+
+    /// Description of the hosted plugin
+    pub fn plugin_info(&self) -> &Lv2PluginInfo {
+        &self.info
+    }
+
+    /// Sample rate the plugin was instantiated at
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Current value of a control input
+    pub fn control(&self, symbol: &str) -> Option<f32> {
+        let port = self.info.port(symbol).filter(|p| p.is_parameter())?;
+        Some(self.controls.0[port.index as usize].get())
+    }
+
+    /// Last value of a control output (meters, latency, ...)
+    pub fn output(&self, symbol: &str) -> Option<f32> {
+        let port = self.info.port(symbol).filter(|p| {
+            p.kind == Lv2PortKind::Control && p.direction == Lv2PortDirection::Output
+        })?;
+        let instance = self.instances.first()?;
+        Some(instance.control_outputs.0[port.index as usize].get())
+    }
+
+    /// Set a control input, clamped to the port's range
+    ///
+    /// Returns false if the plugin has no such control.
+    pub fn set_control(&mut self, symbol: &str, value: f32) -> bool {
+        match self.info.port(symbol).filter(|p| p.is_parameter()) {
+            Some(port) => {
+                self.controls.0[port.index as usize].set(port.clamp(value));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Current settings of every control input
+    pub fn params(&self) -> Lv2Params {
+        Lv2Params {
+            plugin_uri: self.info.uri.clone(),
+            controls: self
+                .info
+                .parameters()
+                .map(|p| (p.symbol.clone(), self.controls.0[p.index as usize].get()))
+                .collect(),
+        }
+    }
+
+    /// Apply saved settings
+    ///
+    /// Returns false (and changes nothing) if they belong to another plugin.
+    pub fn apply_parameters(&mut self, params: &Lv2Params) -> bool {
+        if params.plugin_uri != self.info.uri {
+            return false;
+        }
+        for (symbol, value) in &params.controls {
+            self.set_control(symbol, *value);
+        }
+        true
+    }
+
+    fn descriptor(&self) -> &Lv2Descriptor {
+        unsafe { &*self.descriptor }
+    }
+
+    fn activate(&mut self) {
+        if self.active {
+            return;
+        }
+        let desc = self.descriptor();
+        for instance in &self.instances {
+            unsafe {
+                if let Some(activate) = desc.activate {
+                    activate(instance.handle);
+                }
+                // An empty run lets the plugin report its latency
+                if let Some(run) = desc.run {
+                    run(instance.handle, 0);
+                }
+            }
+        }
+        self.active = true;
+    }
+
+    fn deactivate(&mut self) {
+        if !self.active {
+            return;
+        }
+        if let Some(deactivate) = self.descriptor().deactivate {
+            for instance in &self.instances {
+                unsafe { deactivate(instance.handle) };
+            }
+        }
+        self.active = false;
+    }
+
+    fn run_block(
+        &self,
+        chunk: &mut [f32],
+        frames: usize,
+        run: unsafe extern "C" fn(Lv2Handle, u32),
+    ) {
+        match self.layout {
+            IoLayout::Stereo => {
+                let instance = &self.instances[0];
+                for (frame, samples) in chunk.chunks_exact(2).enumerate() {
+                    instance.audio_inputs[0].0[frame].set(samples[0]);
+                    instance.audio_inputs[1].0[frame].set(samples[1]);
+                }
+                unsafe { run(instance.handle, frames as u32) };
+                for (frame, samples) in chunk.chunks_exact_mut(2).enumerate() {
+                    samples[0] = instance.audio_outputs[0].0[frame].get();
+                    samples[1] = instance.audio_outputs[1].0[frame].get();
+                }
+            }
+            IoLayout::MonoToStereo => {
+                let instance = &self.instances[0];
+                for (frame, samples) in chunk.chunks_exact(2).enumerate() {
+                    instance.audio_inputs[0].0[frame].set((samples[0] + samples[1]) * 0.5);
+                }
+                unsafe { run(instance.handle, frames as u32) };
+                for (frame, samples) in chunk.chunks_exact_mut(2).enumerate() {
+                    samples[0] = instance.audio_outputs[0].0[frame].get();
+                    samples[1] = instance.audio_outputs[1].0[frame].get();
+                }
+            }
+            IoLayout::DualMono => {
+                for (channel, instance) in self.instances.iter().enumerate() {
+                    for (frame, samples) in chunk.chunks_exact(2).enumerate() {
+                        instance.audio_inputs[0].0[frame].set(samples[channel]);
+                    }
+                    unsafe { run(instance.handle, frames as u32) };
+                    for (frame, samples) in chunk.chunks_exact_mut(2).enumerate() {
+                        samples[channel] = instance.audio_outputs[0].0[frame].get();
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Lv2Plugin {
+    fn drop(&mut self) {
+        self.deactivate();
+        if let Some(cleanup) = self.descriptor().cleanup {
+            for instance in self.instances.drain(..) {
+                unsafe { cleanup(instance.handle) };
+            }
+        }
+    }
+}
+
+unsafe fn find_descriptor(entry: Lv2DescriptorFn, uri: &str) -> Option<*const Lv2Descriptor> {
+    for index in 0.. {
+        let descriptor = entry(index);
+        if descriptor.is_null() {
+            return None;
+        }
+        let descriptor_uri = (*descriptor).uri;
+        if !descriptor_uri.is_null() && CStr::from_ptr(descriptor_uri).to_str() == Ok(uri) {
+            return Some(descriptor);
+        }
+    }
+    None
+}
+
+impl AudioEffect for Lv2Plugin {
+    fn process(&mut self, buffer: &mut [f32], sample_rate: u32) {
+        if !self.enabled || !self.active || sample_rate != self.sample_rate {
+            return;
+        }
+        let Some(run) = self.descriptor().run else {
+            return;
+        };
+
+        for chunk in buffer.chunks_mut(MAX_BLOCK_FRAMES * 2) {
+            let frames = chunk.len() / 2;
+            if frames > 0 {
+                self.run_block(&mut chunk[..frames * 2], frames, run);
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        // Re-activating clears the plugin's internal state
+        self.deactivate();
+        self.activate();
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn name(&self) -> &str {
+        &self.info.name
+    }
+
+    fn latency_samples(&self) -> usize {
+        self.info
+            .latency_port()
+            .and_then(|port| self.output(&port.symbol))
+            .filter(|latency| latency.is_finite() && *latency > 0.0)
+            .map_or(0, |latency| latency.round() as usize)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl PipelineComponent for Lv2Plugin {
+    fn process(&mut self, buffer: &mut [f32], sample_rate: u32) {
+        AudioEffect::process(self, buffer, sample_rate);
+    }
+
+    fn reset(&mut self) {
+        AudioEffect::reset(self);
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        AudioEffect::set_enabled(self, enabled);
+    }
+
+    fn is_enabled(&self) -> bool {
+        AudioEffect::is_enabled(self)
+    }
+
+    fn latency_samples(&self) -> usize {
+        AudioEffect::latency_samples(self)
+    }
+
+    fn info(&self) -> PipelineComponentInfo {
+        PipelineComponentInfo {
+            type_id: crate::LV2_EFFECT_TYPE,
+            display_name: "LV2 Plugin",
+            description: "Externally installed LV2 plugin",
+            supports_in_place_update: true,
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn update_parameters(&mut self, params: &dyn Any) -> bool {
+        params
+            .downcast_ref::<Lv2Params>()
+            .is_some_and(|p| self.apply_parameters(p))
+    }
+}
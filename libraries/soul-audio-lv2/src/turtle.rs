@@ -0,0 +1,610 @@
+//! Minimal Turtle reader for LV2 bundle metadata
+//!
+//! LV2 describes plugins in Turtle (`manifest.ttl` and the files it points
+//! to with `rdfs:seeAlso`). This reader covers the subset those files use:
+//! `@prefix`/`@base` directives, IRIs, prefixed names, blank node property
+//! lists, collections and literals. Datatypes and language tags are
+//! dropped; literals keep their lexical form.
+
+use std::collections::HashMap;
+
+/// Well-known prefixes, usable even when a file doesn't declare them
+const DEFAULT_PREFIXES: &[(&str, &str)] = &[
+    ("rdf", "http://www.w3.org/1999/02/22-rdf-syntax-ns#"),
+    ("rdfs", "http://www.w3.org/2000/01/rdf-schema#"),
+    ("lv2", "http://lv2plug.in/ns/lv2core#"),
+    ("doap", "http://usefulinc.com/ns/doap#"),
+    ("atom", "http://lv2plug.in/ns/ext/atom#"),
+    ("urid", "http://lv2plug.in/ns/ext/urid#"),
+];
+
+pub(crate) const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const RDF_FIRST: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#first";
+const RDF_REST: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#rest";
+const RDF_NIL: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#nil";
+
+/// An RDF term
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum Node {
+    Iri(String),
+    Blank(u32),
+    Literal(String),
+}
+
+impl Node {
+    pub(crate) fn as_iri(&self) -> Option<&str> {
+        match self {
+            Node::Iri(iri) => Some(iri),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_literal(&self) -> Option<&str> {
+        match self {
+            Node::Literal(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// A subject-predicate-object statement
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Triple {
+    pub subject: Node,
+    pub predicate: String,
+    pub object: Node,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Iri(String),
+    PrefixedName(String, String),
+    BlankLabel(String),
+    Literal(String),
+    A,
+    Prefix,
+    Base,
+    Dot,
+    Semicolon,
+    Comma,
+    OpenBracket,
+    CloseBracket,
+    OpenParen,
+    CloseParen,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '#' => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '<' => {
+                let end = chars[i..]
+                    .iter()
+                    .position(|&c| c == '>')
+                    .ok_or("unterminated IRI")?;
+                tokens.push(Token::Iri(chars[i + 1..i + end].iter().collect()));
+                i += end + 1;
+            }
+            '"' | '\'' => {
+                let long = chars[i..].starts_with(&[c, c, c]);
+                let quote_len = if long { 3 } else { 1 };
+                i += quote_len;
+                let mut value = String::new();
+                loop {
+                    let Some(&ch) = chars.get(i) else {
+                        return Err("unterminated string".to_string());
+                    };
+                    if ch == '\\' {
+                        let escaped = chars.get(i + 1).ok_or("unterminated escape")?;
+                        value.push(match escaped {
+                            'n' => '\n',
+                            't' => '\t',
+                            'r' => '\r',
+                            other => *other,
+                        });
+                        i += 2;
+                    } else if ch == c && (!long || chars[i..].starts_with(&[c, c, c])) {
+                        i += quote_len;
+                        break;
+                    } else {
+                        value.push(ch);
+                        i += 1;
+                    }
+                }
+                tokens.push(Token::Literal(value));
+
+                // Drop language tags and datatypes
+                if chars.get(i) == Some(&'@') {
+                    i += 1;
+                    while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '-') {
+                        i += 1;
+                    }
+                } else if chars[i..].starts_with(&['^', '^']) {
+                    i += 2;
+                    if chars.get(i) == Some(&'<') {
+                        let end = chars[i..]
+                            .iter()
+                            .position(|&c| c == '>')
+                            .ok_or("unterminated IRI")?;
+                        i += end + 1;
+                    } else {
+                        while i < chars.len() && is_name_char(chars[i]) {
+                            i += 1;
+                        }
+                    }
+                }
+            }
+            '.' if !chars.get(i + 1).is_some_and(|c| c.is_ascii_digit()) => {
+                tokens.push(Token::Dot);
+                i += 1;
+            }
+            ';' => {
+                tokens.push(Token::Semicolon);
+                i += 1;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            '[' => {
+                tokens.push(Token::OpenBracket);
+                i += 1;
+            }
+            ']' => {
+                tokens.push(Token::CloseBracket);
+                i += 1;
+            }
+            '(' => {
+                tokens.push(Token::OpenParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::CloseParen);
+                i += 1;
+            }
+            '@' => {
+                let start = i + 1;
+                i = start;
+                while i < chars.len() && chars[i].is_alphabetic() {
+                    i += 1;
+                }
+                let directive: String = chars[start..i].iter().collect();
+                tokens.push(match directive.as_str() {
+                    "prefix" => Token::Prefix,
+                    "base" => Token::Base,
+                    other => return Err(format!("unknown directive @{}", other)),
+                });
+            }
+            c if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' => {
+                let start = i;
+                i += 1;
+                while i < chars.len()
+                    && (chars[i].is_ascii_digit()
+                        || matches!(chars[i], 'e' | 'E' | '-' | '+')
+                        || (chars[i] == '.'
+                            && chars.get(i + 1).is_some_and(|c| c.is_ascii_digit())))
+                {
+                    i += 1;
+                }
+                tokens.push(Token::Literal(chars[start..i].iter().collect()));
+            }
+            _ => {
+                let start = i;
+                while i < chars.len() && (is_name_char(chars[i]) || chars[i] == ':') {
+                    i += 1;
+                }
+                // A trailing dot ends the statement, not the name
+                while i > start + 1 && chars[i - 1] == '.' {
+                    i -= 1;
+                }
+                if i == start {
+                    return Err(format!("unexpected character '{}'", c));
+                }
+                let word: String = chars[start..i].iter().collect();
+                tokens.push(match word.as_str() {
+                    "a" => Token::A,
+                    "true" | "false" => Token::Literal(word),
+                    _ if word.eq_ignore_ascii_case("PREFIX") => Token::Prefix,
+                    _ if word.eq_ignore_ascii_case("BASE") => Token::Base,
+                    _ => match word.split_once(':') {
+                        Some(("_", label)) => Token::BlankLabel(label.to_string()),
+                        Some((prefix, local)) => {
+                            Token::PrefixedName(prefix.to_string(), local.to_string())
+                        }
+                        None => return Err(format!("unexpected word '{}'", word)),
+                    },
+                });
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.')
+}
+
+/// Turtle parser producing triples
+///
+/// Blank node numbers keep counting across [`Parser::parse`] calls, so the
+/// triples of several files can be merged.
+#[derive(Debug, Default)]
+pub(crate) struct Parser {
+    next_blank: u32,
+}
+
+struct Document<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    base: String,
+    prefixes: HashMap<String, String>,
+    blank_labels: HashMap<String, u32>,
+    triples: Vec<Triple>,
+}
+
+impl Parser {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a document, resolving relative IRIs against `base`
+    pub(crate) fn parse(&mut self, input: &str, base: &str) -> Result<Vec<Triple>, String> {
+        let tokens = tokenize(input)?;
+        let mut doc = Document {
+            tokens: &tokens,
+            pos: 0,
+            base: base.to_string(),
+            prefixes: DEFAULT_PREFIXES
+                .iter()
+                .map(|&(p, iri)| (p.to_string(), iri.to_string()))
+                .collect(),
+            blank_labels: HashMap::new(),
+            triples: Vec::new(),
+        };
+
+        while doc.pos < tokens.len() {
+            self.statement(&mut doc)?;
+        }
+        Ok(doc.triples)
+    }
+
+    fn blank(&mut self) -> Node {
+        self.next_blank += 1;
+        Node::Blank(self.next_blank)
+    }
+
+    fn statement(&mut self, doc: &mut Document) -> Result<(), String> {
+        match doc.next()? {
+            Token::Prefix => {
+                let Token::PrefixedName(prefix, local) = doc.next()? else {
+                    return Err("expected prefix name".to_string());
+                };
+                if !local.is_empty() {
+                    return Err(format!("invalid prefix name {}:{}", prefix, local));
+                }
+                let Token::Iri(iri) = doc.next()? else {
+                    return Err("expected prefix IRI".to_string());
+                };
+                let iri = doc.resolve(&iri);
+                doc.prefixes.insert(prefix, iri);
+                doc.skip_dot();
+            }
+            Token::Base => {
+                let Token::Iri(iri) = doc.next()? else {
+                    return Err("expected base IRI".to_string());
+                };
+                doc.base = doc.resolve(&iri);
+                doc.skip_dot();
+            }
+            Token::OpenBracket => {
+                let subject = self.blank_property_list(doc)?;
+                if doc.peek() != Some(&Token::Dot) {
+                    self.predicate_object_list(doc, &subject)?;
+                }
+                doc.expect(Token::Dot)?;
+            }
+            token => {
+                let subject = self.term(doc, token)?;
+                self.predicate_object_list(doc, &subject)?;
+                doc.expect(Token::Dot)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn predicate_object_list(&mut self, doc: &mut Document, subject: &Node) -> Result<(), String> {
+        loop {
+            let predicate = match doc.next()? {
+                Token::A => RDF_TYPE.to_string(),
+                token => match self.term(doc, token)? {
+                    Node::Iri(iri) => iri,
+                    _ => return Err("predicate must be an IRI".to_string()),
+                },
+            };
+
+            loop {
+                let object = self.object(doc)?;
+                doc.triples.push(Triple {
+                    subject: subject.clone(),
+                    predicate: predicate.clone(),
+                    object,
+                });
+                if doc.peek() == Some(&Token::Comma) {
+                    doc.pos += 1;
+                } else {
+                    break;
+                }
+            }
+
+            // One or more semicolons, optionally ending the list
+            if doc.peek() != Some(&Token::Semicolon) {
+                return Ok(());
+            }
+            while doc.peek() == Some(&Token::Semicolon) {
+                doc.pos += 1;
+            }
+            if matches!(doc.peek(), Some(Token::Dot | Token::CloseBracket) | None) {
+                return Ok(());
+            }
+        }
+    }
+
+    fn object(&mut self, doc: &mut Document) -> Result<Node, String> {
+        match doc.next()? {
+            Token::OpenBracket => self.blank_property_list(doc),
+            Token::OpenParen => self.collection(doc),
+            token => self.term(doc, token),
+        }
+    }
+
+    /// `[ ... ]` after the opening bracket
+    fn blank_property_list(&mut self, doc: &mut Document) -> Result<Node, String> {
+        let node = self.blank();
+        if doc.peek() != Some(&Token::CloseBracket) {
+            self.predicate_object_list(doc, &node)?;
+        }
+        doc.expect(Token::CloseBracket)?;
+        Ok(node)
+    }
+
+    /// `( ... )` after the opening parenthesis
+    fn collection(&mut self, doc: &mut Document) -> Result<Node, String> {
+        let mut items = Vec::new();
+        while doc.peek() != Some(&Token::CloseParen) {
+            items.push(self.object(doc)?);
+        }
+        doc.pos += 1;
+
+        let mut list = Node::Iri(RDF_NIL.to_string());
+        for item in items.into_iter().rev() {
+            let cell = self.blank();
+            doc.triples.push(Triple {
+                subject: cell.clone(),
+                predicate: RDF_FIRST.to_string(),
+                object: item,
+            });
+            doc.triples.push(Triple {
+                subject: cell.clone(),
+                predicate: RDF_REST.to_string(),
+                object: list,
+            });
+            list = cell;
+        }
+        Ok(list)
+    }
+
+    fn term(&mut self, doc: &mut Document, token: Token) -> Result<Node, String> {
+        match token {
+            Token::Iri(iri) => Ok(Node::Iri(doc.resolve(&iri))),
+            Token::PrefixedName(prefix, local) => {
+                let namespace = doc
+                    .prefixes
+                    .get(&prefix)
+                    .ok_or_else(|| format!("undeclared prefix '{}'", prefix))?;
+                Ok(Node::Iri(format!("{}{}", namespace, local)))
+            }
+            Token::BlankLabel(label) => {
+                if let Some(&id) = doc.blank_labels.get(&label) {
+                    return Ok(Node::Blank(id));
+                }
+                let node = self.blank();
+                if let Node::Blank(id) = node {
+                    doc.blank_labels.insert(label, id);
+                }
+                Ok(node)
+            }
+            Token::Literal(value) => Ok(Node::Literal(value)),
+            other => Err(format!("unexpected {:?}", other)),
+        }
+    }
+}
+
+impl Document<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<Token, String> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or("unexpected end of document")?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        let token = self.next()?;
+        if token == expected {
+            Ok(())
+        } else {
+            Err(format!("expected {:?}, found {:?}", expected, token))
+        }
+    }
+
+    /// `@prefix` ends with a dot, SPARQL-style `PREFIX` doesn't
+    fn skip_dot(&mut self) {
+        if self.peek() == Some(&Token::Dot) {
+            self.pos += 1;
+        }
+    }
+
+    fn resolve(&self, iri: &str) -> String {
+        resolve_iri(&self.base, iri)
+    }
+}
+
+/// Resolve a possibly relative IRI against a base
+pub(crate) fn resolve_iri(base: &str, iri: &str) -> String {
+    let has_scheme = iri
+        .find(':')
+        .is_some_and(|colon| !iri[..colon].contains('/') && colon > 0);
+    if has_scheme {
+        return iri.to_string();
+    }
+    if let Some(fragment) = iri.strip_prefix('#') {
+        let base = base.split('#').next().unwrap_or(base);
+        return format!("{}#{}", base, fragment);
+    }
+    let directory = match base.rfind('/') {
+        Some(slash) => &base[..=slash],
+        None => base,
+    };
+    format!("{}{}", directory, iri)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LV2: &str = "http://lv2plug.in/ns/lv2core#";
+
+    fn parse(input: &str) -> Vec<Triple> {
+        Parser::new()
+            .parse(input, "file:///usr/lib/lv2/amp.lv2/manifest.ttl")
+            .unwrap()
+    }
+
+    fn objects<'a>(triples: &'a [Triple], subject: &Node, predicate: &str) -> Vec<&'a Node> {
+        triples
+            .iter()
+            .filter(|t| &t.subject == subject && t.predicate == predicate)
+            .map(|t| &t.object)
+            .collect()
+    }
+
+    #[test]
+    fn test_manifest() {
+        let triples = parse(
+            r#"
+            @prefix lv2:  <http://lv2plug.in/ns/lv2core#> .
+            @prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+
+            # A plugin
+            <http://example.org/amp>
+                a lv2:Plugin ;
+                lv2:binary <amp.so> ;
+                rdfs:seeAlso <amp.ttl> .
+            "#,
+        );
+
+        let plugin = Node::Iri("http://example.org/amp".to_string());
+        assert_eq!(
+            objects(&triples, &plugin, RDF_TYPE),
+            vec![&Node::Iri(format!("{}Plugin", LV2))]
+        );
+        assert_eq!(
+            objects(&triples, &plugin, &format!("{}binary", LV2)),
+            vec![&Node::Iri("file:///usr/lib/lv2/amp.lv2/amp.so".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_blank_nodes_and_literals() {
+        let triples = parse(
+            r#"
+            PREFIX ex: <http://example.org/>
+            ex:amp lv2:port [
+                a lv2:InputPort , lv2:ControlPort ;
+                lv2:index 0 ;
+                lv2:symbol "gain" ;
+                lv2:name """Gain
+(dB)"""@en ;
+                lv2:default -6.5 ;
+                lv2:maximum 2.4E1 ;
+                lv2:minimum "-90"^^<http://www.w3.org/2001/XMLSchema#float> ;
+            ] , [
+                lv2:index 1 ;
+                lv2:symbol 'out'
+            ] ;
+                ex:enabled true .
+            "#,
+        );
+
+        let amp = Node::Iri("http://example.org/amp".to_string());
+        let ports = objects(&triples, &amp, &format!("{}port", LV2));
+        assert_eq!(ports.len(), 2);
+        assert_ne!(ports[0], ports[1]);
+
+        let literal = |port: &Node, name: &str| -> String {
+            objects(&triples, port, &format!("{}{}", LV2, name))[0]
+                .as_literal()
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(literal(ports[0], "symbol"), "gain");
+        assert_eq!(literal(ports[0], "name"), "Gain\n(dB)");
+        assert_eq!(literal(ports[0], "default"), "-6.5");
+        assert_eq!(literal(ports[0], "maximum"), "2.4E1");
+        assert_eq!(literal(ports[0], "minimum"), "-90");
+        assert_eq!(literal(ports[1], "symbol"), "out");
+        assert_eq!(objects(&triples, ports[0], RDF_TYPE).len(), 2);
+
+        let enabled = objects(&triples, &amp, "http://example.org/enabled");
+        assert_eq!(enabled, vec![&Node::Literal("true".to_string())]);
+    }
+
+    #[test]
+    fn test_collections() {
+        let triples = parse("<#list> <#items> ( 1 2 ) .");
+        let list = &objects(
+            &triples,
+            &Node::Iri("file:///usr/lib/lv2/amp.lv2/manifest.ttl#list".to_string()),
+            "file:///usr/lib/lv2/amp.lv2/manifest.ttl#items",
+        )[0];
+        let first = objects(&triples, list, RDF_FIRST);
+        assert_eq!(first, vec![&Node::Literal("1".to_string())]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut parser = Parser::new();
+        assert!(parser.parse("<a> <b> \"open", "file:///").is_err());
+        assert!(parser.parse("nope:thing <b> <c> .", "file:///").is_err());
+        assert!(parser.parse("<a> <b> <c>", "file:///").is_err());
+    }
+
+    #[test]
+    fn test_resolve_iri() {
+        let base = "file:///lv2/amp.lv2/manifest.ttl";
+        assert_eq!(resolve_iri(base, "amp.so"), "file:///lv2/amp.lv2/amp.so");
+        assert_eq!(resolve_iri(base, "urn:amp"), "urn:amp");
+        assert_eq!(
+            resolve_iri(base, "#x"),
+            "file:///lv2/amp.lv2/manifest.ttl#x"
+        );
+        assert_eq!(resolve_iri(base, "http://a/b"), "http://a/b");
+    }
+}
@@ -0,0 +1,602 @@
+//! Plugin discovery
+//!
+//! Installed plugins live in bundles (`*.lv2` directories) on the LV2 search
+//! path. Each bundle's `manifest.ttl` lists its plugins and points to the
+//! files describing them in detail.
+
+use crate::error::{Lv2Error, Result};
+use crate::turtle::{Node, Parser, Triple, RDF_TYPE};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+const LV2: &str = "http://lv2plug.in/ns/lv2core#";
+const RDFS_SEE_ALSO: &str = "http://www.w3.org/2000/01/rdf-schema#seeAlso";
+const DOAP_NAME: &str = "http://usefulinc.com/ns/doap#name";
+const URID_MAP: &str = "http://lv2plug.in/ns/ext/urid#map";
+const URID_UNMAP: &str = "http://lv2plug.in/ns/ext/urid#unmap";
+
+/// Features the host provides or that need nothing from it
+const SUPPORTED_FEATURES: &[&str] = &[
+    URID_MAP,
+    URID_UNMAP,
+    "http://lv2plug.in/ns/lv2core#isLive",
+    "http://lv2plug.in/ns/lv2core#inPlaceBroken",
+    "http://lv2plug.in/ns/lv2core#hardRTCapable",
+];
+
+/// Default search path when `LV2_PATH` isn't set
+const DEFAULT_PATHS: &[&str] = &[
+    "/usr/local/lib/lv2",
+    "/usr/lib/lv2",
+    "/usr/lib64/lv2",
+    "/usr/lib/x86_64-linux-gnu/lv2",
+    "/usr/lib/aarch64-linux-gnu/lv2",
+];
+
+/// Kind of data a port carries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Lv2PortKind {
+    /// Audio samples
+    Audio,
+    /// A single float per block
+    Control,
+    /// Anything else (CV, atom sequences, ...)
+    Other,
+}
+
+/// Direction of a port, seen from the plugin
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Lv2PortDirection {
+    Input,
+    Output,
+}
+
+/// A plugin port
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Lv2Port {
+    /// Port index, as passed to `connect_port`
+    pub index: u32,
+    /// Identifier, unique within the plugin (used for presets)
+    pub symbol: String,
+    /// Human-readable name
+    pub name: String,
+    pub kind: Lv2PortKind,
+    pub direction: Lv2PortDirection,
+    /// Default value (control ports)
+    pub default: Option<f32>,
+    /// Minimum value (control ports)
+    pub minimum: Option<f32>,
+    /// Maximum value (control ports)
+    pub maximum: Option<f32>,
+    /// Output port reporting the plugin latency in frames
+    pub reports_latency: bool,
+    /// On/off switch (0 or 1)
+    pub toggled: bool,
+    /// Only integer values are meaningful
+    pub integer: bool,
+    /// The port may be left unconnected
+    pub optional: bool,
+}
+
+impl Lv2Port {
+    /// Whether this is a control input (a plugin parameter)
+    pub fn is_parameter(&self) -> bool {
+        self.kind == Lv2PortKind::Control && self.direction == Lv2PortDirection::Input
+    }
+
+    /// Initial value: the default, or the minimum, clamped to the range
+    pub fn initial_value(&self) -> f32 {
+        self.clamp(self.default.or(self.minimum).unwrap_or(0.0))
+    }
+
+    /// Clamp a value to the port's range
+    pub fn clamp(&self, value: f32) -> f32 {
+        let mut value = value;
+        if let Some(min) = self.minimum {
+            value = value.max(min);
+        }
+        if let Some(max) = self.maximum {
+            value = value.min(max);
+        }
+        if self.toggled {
+            value = if value > 0.0 { 1.0 } else { 0.0 };
+        } else if self.integer {
+            value = value.round();
+        }
+        value
+    }
+}
+
+/// An installed plugin
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Lv2PluginInfo {
+    /// Plugin URI (its unique identifier)
+    pub uri: String,
+    /// Human-readable name
+    pub name: String,
+    /// Bundle directory
+    pub bundle_path: PathBuf,
+    /// Shared library implementing the plugin
+    pub binary: PathBuf,
+    /// Ports, ordered by index
+    pub ports: Vec<Lv2Port>,
+    /// Features the plugin can't run without
+    pub required_features: Vec<String>,
+}
+
+impl Lv2PluginInfo {
+    /// Ports carrying audio in the given direction
+    pub fn audio_ports(&self, direction: Lv2PortDirection) -> impl Iterator<Item = &Lv2Port> {
+        self.ports
+            .iter()
+            .filter(move |p| p.kind == Lv2PortKind::Audio && p.direction == direction)
+    }
+
+    /// Control inputs (the plugin's parameters)
+    pub fn parameters(&self) -> impl Iterator<Item = &Lv2Port> {
+        self.ports.iter().filter(|p| p.is_parameter())
+    }
+
+    /// Look up a port by symbol
+    pub fn port(&self, symbol: &str) -> Option<&Lv2Port> {
+        self.ports.iter().find(|p| p.symbol == symbol)
+    }
+
+    /// Output port the plugin reports its latency on
+    pub fn latency_port(&self) -> Option<&Lv2Port> {
+        self.ports.iter().find(|p| {
+            p.reports_latency
+                && p.kind == Lv2PortKind::Control
+                && p.direction == Lv2PortDirection::Output
+        })
+    }
+
+    /// Why this host can't run the plugin, if it can't
+    ///
+    /// Supported plugins need no features beyond URID mapping, only have
+    /// audio and control ports (other kinds must be optional), and are
+    /// mono (1 in, 1 out), mono-to-stereo (1 in, 2 out) or stereo (2 in,
+    /// 2 out).
+    pub fn unsupported_reason(&self) -> Option<String> {
+        if let Some(feature) = self
+            .required_features
+            .iter()
+            .find(|f| !SUPPORTED_FEATURES.contains(&f.as_str()))
+        {
+            return Some(format!("requires feature {}", feature));
+        }
+
+        if let Some(port) = self
+            .ports
+            .iter()
+            .find(|p| p.kind == Lv2PortKind::Other && !p.optional)
+        {
+            return Some(format!("unsupported port type for '{}'", port.symbol));
+        }
+
+        let inputs = self.audio_ports(Lv2PortDirection::Input).count();
+        let outputs = self.audio_ports(Lv2PortDirection::Output).count();
+        match (inputs, outputs) {
+            (1, 1 | 2) | (2, 2) => None,
+            _ => Some(format!(
+                "{} audio inputs and {} outputs (needs 1/1, 1/2 or 2/2)",
+                inputs, outputs
+            )),
+        }
+    }
+
+    /// Whether this host can run the plugin
+    pub fn is_supported(&self) -> bool {
+        self.unsupported_reason().is_none()
+    }
+}
+
+/// Set of installed plugins
+#[derive(Debug, Clone, Default)]
+pub struct Lv2World {
+    plugins: Vec<Lv2PluginInfo>,
+}
+
+impl Lv2World {
+    /// Create an empty world
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load all plugins on the search path
+    ///
+    /// Bundles that fail to load are skipped.
+    pub fn load_default() -> Self {
+        let mut world = Self::new();
+        world.load_paths(&Self::search_path());
+        world
+    }
+
+    /// Directories searched for bundles
+    ///
+    /// `LV2_PATH` if set, otherwise `~/.lv2` and the usual system
+    /// directories.
+    pub fn search_path() -> Vec<PathBuf> {
+        if let Some(path) = std::env::var_os("LV2_PATH") {
+            return std::env::split_paths(&path).collect();
+        }
+
+        let mut paths = Vec::new();
+        if let Some(home) = std::env::var_os("HOME") {
+            paths.push(PathBuf::from(home).join(".lv2"));
+        }
+        paths.extend(DEFAULT_PATHS.iter().map(PathBuf::from));
+        paths
+    }
+
+    /// Load every bundle in the given directories
+    ///
+    /// Returns the number of plugins added. Missing directories and broken
+    /// bundles are skipped.
+    pub fn load_paths(&mut self, paths: &[PathBuf]) -> usize {
+        let mut added = 0;
+        for dir in paths {
+            let Ok(entries) = std::fs::read_dir(dir) else {
+                continue;
+            };
+            let mut bundles: Vec<PathBuf> = entries
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| p.is_dir() && p.join("manifest.ttl").is_file())
+                .collect();
+            bundles.sort();
+
+            for bundle in bundles {
+                added += self.load_bundle(&bundle).unwrap_or(0);
+            }
+        }
+        added
+    }
+
+    /// Load the plugins of one bundle
+    ///
+    /// Returns the number of plugins added. A plugin already loaded from
+    /// an earlier bundle is kept, so earlier search path entries win.
+    pub fn load_bundle(&mut self, bundle: &Path) -> Result<usize> {
+        let mut added = 0;
+        for plugin in read_bundle(bundle)? {
+            if self.plugin(&plugin.uri).is_none() {
+                self.plugins.push(plugin);
+                added += 1;
+            }
+        }
+        Ok(added)
+    }
+
+    /// All loaded plugins
+    pub fn plugins(&self) -> &[Lv2PluginInfo] {
+        &self.plugins
+    }
+
+    /// Look up a plugin by URI
+    pub fn plugin(&self, uri: &str) -> Option<&Lv2PluginInfo> {
+        self.plugins.iter().find(|p| p.uri == uri)
+    }
+}
+
+/// Triples indexed by subject
+struct Graph {
+    statements: HashMap<Node, Vec<(String, Node)>>,
+}
+
+impl Graph {
+    fn new(triples: Vec<Triple>) -> Self {
+        let mut statements: HashMap<Node, Vec<(String, Node)>> = HashMap::new();
+        for t in triples {
+            statements
+                .entry(t.subject)
+                .or_default()
+                .push((t.predicate, t.object));
+        }
+        Self { statements }
+    }
+
+    fn objects<'a, 'p>(
+        &'a self,
+        subject: &Node,
+        predicate: &'p str,
+    ) -> impl Iterator<Item = &'a Node> + 'p
+    where
+        'a: 'p,
+    {
+        self.statements
+            .get(subject)
+            .into_iter()
+            .flatten()
+            .filter(move |(p, _)| p == predicate)
+            .map(|(_, o)| o)
+    }
+
+    fn object(&self, subject: &Node, predicate: &str) -> Option<&Node> {
+        self.objects(subject, predicate).next()
+    }
+
+    fn literal(&self, subject: &Node, predicate: &str) -> Option<&str> {
+        self.objects(subject, predicate).find_map(Node::as_literal)
+    }
+
+    fn number(&self, subject: &Node, predicate: &str) -> Option<f32> {
+        self.literal(subject, predicate)?.parse().ok()
+    }
+
+    fn has(&self, subject: &Node, predicate: &str, object: &str) -> bool {
+        self.objects(subject, predicate)
+            .any(|o| o.as_iri() == Some(object))
+    }
+}
+
+fn read_bundle(bundle: &Path) -> Result<Vec<Lv2PluginInfo>> {
+    let bundle = bundle.canonicalize().map_err(|e| Lv2Error::BundleRead {
+        path: bundle.to_path_buf(),
+        message: e.to_string(),
+    })?;
+
+    let mut parser = Parser::new();
+    let manifest = bundle.join("manifest.ttl");
+    let mut triples = parse_file(&mut parser, &manifest)?;
+
+    let plugin_class = format!("{}Plugin", LV2);
+    let plugins: Vec<Node> = triples
+        .iter()
+        .filter(|t| t.predicate == RDF_TYPE && t.object.as_iri() == Some(&plugin_class))
+        .map(|t| t.subject.clone())
+        .collect();
+
+    // Pull in the files the manifest points to for its plugins
+    let mut loaded = HashSet::from([manifest]);
+    let see_also: Vec<PathBuf> = triples
+        .iter()
+        .filter(|t| t.predicate == RDFS_SEE_ALSO && plugins.contains(&t.subject))
+        .filter_map(|t| t.object.as_iri().and_then(file_iri_to_path))
+        .collect();
+    for path in see_also {
+        if loaded.insert(path.clone()) {
+            triples.extend(parse_file(&mut parser, &path)?);
+        }
+    }
+
+    let graph = Graph::new(triples);
+    plugins
+        .iter()
+        .filter_map(|plugin| match plugin {
+            Node::Iri(uri) => Some(plugin_info(&graph, plugin, uri, &bundle)),
+            _ => None,
+        })
+        .collect()
+}
+
+fn parse_file(parser: &mut Parser, path: &Path) -> Result<Vec<Triple>> {
+    let text = std::fs::read_to_string(path).map_err(|e| Lv2Error::BundleRead {
+        path: path.to_path_buf(),
+        message: e.to_string(),
+    })?;
+    parser
+        .parse(&text, &path_to_file_iri(path))
+        .map_err(|message| Lv2Error::Parse {
+            path: path.to_path_buf(),
+            message,
+        })
+}
+
+fn plugin_info(graph: &Graph, plugin: &Node, uri: &str, bundle: &Path) -> Result<Lv2PluginInfo> {
+    let invalid = |message: &str| Lv2Error::InvalidPlugin {
+        uri: uri.to_string(),
+        message: message.to_string(),
+    };
+
+    let binary = graph
+        .object(plugin, &format!("{}binary", LV2))
+        .and_then(Node::as_iri)
+        .and_then(file_iri_to_path)
+        .ok_or_else(|| invalid("no binary"))?;
+
+    let mut ports = Vec::new();
+    for port in graph.objects(plugin, &format!("{}port", LV2)) {
+        ports.push(port_info(graph, port).ok_or_else(|| invalid("incomplete port"))?);
+    }
+    ports.sort_by_key(|p| p.index);
+    if ports.iter().enumerate().any(|(i, p)| p.index as usize != i) {
+        return Err(invalid("port indices are not contiguous"));
+    }
+
+    let required_features = graph
+        .objects(plugin, &format!("{}requiredFeature", LV2))
+        .filter_map(Node::as_iri)
+        .map(str::to_string)
+        .collect();
+
+    Ok(Lv2PluginInfo {
+        uri: uri.to_string(),
+        name: graph.literal(plugin, DOAP_NAME).unwrap_or(uri).to_string(),
+        bundle_path: bundle.to_path_buf(),
+        binary,
+        ports,
+        required_features,
+    })
+}
+
+fn port_info(graph: &Graph, port: &Node) -> Option<Lv2Port> {
+    let lv2 = |name: &str| format!("{}{}", LV2, name);
+    let is_a = |class: &str| graph.has(port, RDF_TYPE, &lv2(class));
+    let has_property = |property: &str| graph.has(port, &lv2("portProperty"), &lv2(property));
+
+    let kind = if is_a("AudioPort") {
+        Lv2PortKind::Audio
+    } else if is_a("ControlPort") {
+        Lv2PortKind::Control
+    } else {
+        Lv2PortKind::Other
+    };
+    let direction = if is_a("InputPort") {
+        Lv2PortDirection::Input
+    } else if is_a("OutputPort") {
+        Lv2PortDirection::Output
+    } else {
+        return None;
+    };
+
+    let symbol = graph.literal(port, &lv2("symbol"))?.to_string();
+    Some(Lv2Port {
+        index: graph.literal(port, &lv2("index"))?.parse().ok()?,
+        name: graph
+            .literal(port, &lv2("name"))
+            .unwrap_or(&symbol)
+            .to_string(),
+        symbol,
+        kind,
+        direction,
+        default: graph.number(port, &lv2("default")),
+        minimum: graph.number(port, &lv2("minimum")),
+        maximum: graph.number(port, &lv2("maximum")),
+        reports_latency: has_property("reportsLatency")
+            || graph.has(port, &lv2("designation"), &lv2("latency")),
+        toggled: has_property("toggled"),
+        integer: has_property("integer"),
+        optional: has_property("connectionOptional"),
+    })
+}
+
+fn path_to_file_iri(path: &Path) -> String {
+    let mut iri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                iri.push(byte as char);
+            }
+            _ => {
+                let _ = write!(iri, "%{:02X}", byte);
+            }
+        }
+    }
+    iri
+}
+
+fn file_iri_to_path(iri: &str) -> Option<PathBuf> {
+    let path = iri.strip_prefix("file://")?;
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(byte) = path
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    Some(PathBuf::from(String::from_utf8(decoded).ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn port(kind: Lv2PortKind, direction: Lv2PortDirection, index: u32) -> Lv2Port {
+        Lv2Port {
+            index,
+            symbol: format!("p{}", index),
+            name: format!("Port {}", index),
+            kind,
+            direction,
+            default: None,
+            minimum: None,
+            maximum: None,
+            reports_latency: false,
+            toggled: false,
+            integer: false,
+            optional: false,
+        }
+    }
+
+    fn plugin(ports: Vec<Lv2Port>) -> Lv2PluginInfo {
+        Lv2PluginInfo {
+            uri: "urn:test".to_string(),
+            name: "Test".to_string(),
+            bundle_path: PathBuf::from("/lv2/test.lv2"),
+            binary: PathBuf::from("/lv2/test.lv2/test.so"),
+            ports,
+            required_features: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_file_iri_round_trip() {
+        let path = Path::new("/home/me/.lv2/My Amp (x64).lv2/amp.so");
+        let iri = path_to_file_iri(path);
+        assert_eq!(iri, "file:///home/me/.lv2/My%20Amp%20%28x64%29.lv2/amp.so");
+        assert_eq!(file_iri_to_path(&iri).as_deref(), Some(path));
+        assert_eq!(file_iri_to_path("http://example.org/amp"), None);
+    }
+
+    #[test]
+    fn test_clamp() {
+        let mut gain = port(Lv2PortKind::Control, Lv2PortDirection::Input, 0);
+        gain.minimum = Some(-90.0);
+        gain.maximum = Some(24.0);
+        gain.default = Some(0.0);
+        assert_eq!(gain.clamp(30.0), 24.0);
+        assert_eq!(gain.clamp(-100.0), -90.0);
+        assert_eq!(gain.initial_value(), 0.0);
+
+        gain.integer = true;
+        assert_eq!(gain.clamp(2.6), 3.0);
+
+        gain.toggled = true;
+        gain.minimum = Some(0.0);
+        gain.maximum = Some(1.0);
+        assert_eq!(gain.clamp(0.7), 1.0);
+        assert_eq!(gain.clamp(0.0), 0.0);
+    }
+
+    #[test]
+    fn test_supported_layouts() {
+        use Lv2PortDirection::{Input, Output};
+        use Lv2PortKind::{Audio, Control, Other};
+
+        let stereo = plugin(vec![
+            port(Audio, Input, 0),
+            port(Audio, Input, 1),
+            port(Audio, Output, 2),
+            port(Audio, Output, 3),
+            port(Control, Input, 4),
+        ]);
+        assert!(stereo.is_supported());
+        assert_eq!(stereo.parameters().count(), 1);
+
+        let generator = plugin(vec![port(Audio, Output, 0)]);
+        assert!(generator
+            .unsupported_reason()
+            .unwrap()
+            .contains("0 audio inputs"));
+
+        let mut midi = plugin(vec![
+            port(Audio, Input, 0),
+            port(Audio, Output, 1),
+            port(Other, Input, 2),
+        ]);
+        assert!(!midi.is_supported());
+        midi.ports[2].optional = true;
+        assert!(midi.is_supported());
+
+        midi.required_features = vec!["http://lv2plug.in/ns/ext/worker#schedule".to_string()];
+        assert!(midi
+            .unsupported_reason()
+            .unwrap()
+            .contains("worker#schedule"));
+    }
+}
@@ -0,0 +1,493 @@
+//! LV2 host tests
+//!
+//! The test bundle in `tests/fixtures` describes the plugins; their code is
+//! implemented below against the LV2 C ABI and hosted with
+//! `Lv2Plugin::from_descriptor`. On Linux, `tests/plugin` is also compiled
+//! into a copy of the bundle, which is loaded like an installed plugin.
+
+#![allow(unsafe_code)]
+
+use soul_audio::effects::{AudioEffect, EffectChain};
+use soul_audio::pipeline::{EffectRegistry, PipelineComponent};
+use soul_audio_lv2::ffi::{Lv2Descriptor, Lv2Feature, Lv2Handle, URID_MAP_URI};
+use soul_audio_lv2::{
+    register_lv2_effects, Lv2Error, Lv2Params, Lv2Plugin, Lv2PluginInfo, Lv2PortDirection,
+    Lv2PortKind, Lv2World, LV2_EFFECT_TYPE, MAX_BLOCK_FRAMES,
+};
+use std::ffi::{c_char, c_void, CStr};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+const STEREO_AMP: &str = "urn:soul-player:test:stereo-amp";
+const MONO_AMP: &str = "urn:soul-player:test:mono-amp";
+const WORKER: &str = "urn:soul-player:test:worker";
+
+const REPORTED_LATENCY: f32 = 64.0;
+const HALF_GAIN_DB: f32 = -6.0206;
+
+// ===== Test plugins =====
+//
+// Stereo amp ports: 0 gain, 1-2 audio in, 3-4 audio out, 5 latency
+// Mono amp ports:   0 audio in, 1 audio out, 2 gain, 3 mute
+
+struct Amp {
+    /// Port buffers, indexed by port index
+    ports: [*mut f32; 6],
+    stereo: bool,
+}
+
+static ACTIVATIONS: AtomicUsize = AtomicUsize::new(0);
+
+static STEREO_DESCRIPTOR: Lv2Descriptor = descriptor(b"urn:soul-player:test:stereo-amp\0");
+static MONO_DESCRIPTOR: Lv2Descriptor = descriptor(b"urn:soul-player:test:mono-amp\0");
+
+const fn descriptor(uri: &'static [u8]) -> Lv2Descriptor {
+    Lv2Descriptor {
+        uri: uri.as_ptr().cast(),
+        instantiate: Some(instantiate),
+        connect_port: Some(connect_port),
+        activate: Some(activate),
+        run: Some(run),
+        deactivate: None,
+        cleanup: Some(cleanup),
+        extension_data: None,
+    }
+}
+
+unsafe fn has_feature(features: *const *const Lv2Feature, uri: &[u8]) -> bool {
+    let uri = CStr::from_bytes_with_nul(uri).unwrap();
+    let mut feature = features;
+    while !(*feature).is_null() {
+        if CStr::from_ptr((**feature).uri) == uri {
+            return true;
+        }
+        feature = feature.add(1);
+    }
+    false
+}
+
+unsafe extern "C" fn instantiate(
+    descriptor: *const Lv2Descriptor,
+    _sample_rate: f64,
+    bundle_path: *const c_char,
+    features: *const *const Lv2Feature,
+) -> Lv2Handle {
+    // Bundle paths end with a separator, and the URID map is always offered
+    let bundle_path = CStr::from_ptr(bundle_path).to_string_lossy();
+    if !bundle_path.ends_with("soul-test-amp.lv2/") || !has_feature(features, URID_MAP_URI) {
+        return std::ptr::null_mut();
+    }
+
+    let amp = Box::new(Amp {
+        ports: [std::ptr::null_mut(); 6],
+        stereo: std::ptr::eq(descriptor, &STEREO_DESCRIPTOR),
+    });
+    Box::into_raw(amp).cast()
+}
+
+unsafe extern "C" fn connect_port(instance: Lv2Handle, port: u32, data: *mut c_void) {
+    let amp = &mut *instance.cast::<Amp>();
+    amp.ports[port as usize] = data.cast();
+}
+
+unsafe extern "C" fn activate(_instance: Lv2Handle) {
+    ACTIVATIONS.fetch_add(1, Ordering::SeqCst);
+}
+
+unsafe extern "C" fn run(instance: Lv2Handle, sample_count: u32) {
+    let amp = &*instance.cast::<Amp>();
+    let frames = sample_count as usize;
+
+    if amp.stereo {
+        *amp.ports[5] = REPORTED_LATENCY;
+        let gain = 10.0_f32.powf(*amp.ports[0] / 20.0);
+        for (input, output) in [(1, 3), (2, 4)] {
+            let input = std::slice::from_raw_parts(amp.ports[input], frames);
+            let output = std::slice::from_raw_parts_mut(amp.ports[output], frames);
+            for (out, sample) in output.iter_mut().zip(input) {
+                *out = sample * gain;
+            }
+        }
+    } else {
+        let muted = *amp.ports[3] > 0.5;
+        let gain = if muted {
+            0.0
+        } else {
+            10.0_f32.powf(*amp.ports[2] / 20.0)
+        };
+        let input = std::slice::from_raw_parts(amp.ports[0], frames);
+        let output = std::slice::from_raw_parts_mut(amp.ports[1], frames);
+        for (out, sample) in output.iter_mut().zip(input) {
+            *out = sample * gain;
+        }
+    }
+}
+
+unsafe extern "C" fn cleanup(instance: Lv2Handle) {
+    drop(Box::from_raw(instance.cast::<Amp>()));
+}
+
+// ===== Helpers =====
+
+fn fixtures() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+}
+
+fn world() -> Lv2World {
+    let mut world = Lv2World::new();
+    world
+        .load_bundle(&fixtures().join("soul-test-amp.lv2"))
+        .unwrap();
+    world
+}
+
+fn info(uri: &str) -> Lv2PluginInfo {
+    world().plugin(uri).unwrap().clone()
+}
+
+fn stereo_amp() -> Lv2Plugin {
+    unsafe { Lv2Plugin::from_descriptor(&info(STEREO_AMP), &STEREO_DESCRIPTOR, 48000) }.unwrap()
+}
+
+fn mono_amp() -> Lv2Plugin {
+    unsafe { Lv2Plugin::from_descriptor(&info(MONO_AMP), &MONO_DESCRIPTOR, 48000) }.unwrap()
+}
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!(
+        (actual - expected).abs() < 1e-4,
+        "expected {}, got {}",
+        expected,
+        actual
+    );
+}
+
+// ===== Discovery =====
+
+#[test]
+fn test_enumerates_bundle() {
+    let world = world();
+    assert_eq!(world.plugins().len(), 3);
+
+    let stereo = world.plugin(STEREO_AMP).unwrap();
+    assert_eq!(stereo.name, "Soul Test Stereo Amp");
+    assert!(stereo
+        .binary
+        .ends_with("soul-test-amp.lv2/soul-test-amp.so"));
+    assert!(stereo.is_supported());
+    assert_eq!(stereo.ports.len(), 6);
+    assert_eq!(stereo.audio_ports(Lv2PortDirection::Input).count(), 2);
+    assert_eq!(stereo.audio_ports(Lv2PortDirection::Output).count(), 2);
+
+    let gain = stereo.port("gain").unwrap();
+    assert_eq!(gain.index, 0);
+    assert_eq!(gain.kind, Lv2PortKind::Control);
+    assert!(gain.is_parameter());
+    assert_eq!(gain.default, Some(0.0));
+    assert_eq!(gain.minimum, Some(-90.0));
+    assert_eq!(gain.maximum, Some(24.0));
+
+    let latency = stereo.latency_port().unwrap();
+    assert_eq!(latency.symbol, "latency");
+    assert!(latency.integer);
+
+    let mono = world.plugin(MONO_AMP).unwrap();
+    assert!(mono.is_supported());
+    let symbols: Vec<&str> = mono.parameters().map(|p| p.symbol.as_str()).collect();
+    assert_eq!(symbols, vec!["gain", "mute"]);
+    assert!(mono.port("mute").unwrap().toggled);
+    assert!(mono.latency_port().is_none());
+
+    let worker = world.plugin(WORKER).unwrap();
+    assert!(worker
+        .unsupported_reason()
+        .unwrap()
+        .contains("worker#schedule"));
+}
+
+#[test]
+fn test_load_paths_skips_duplicates() {
+    let mut world = Lv2World::new();
+    let paths = vec![fixtures(), fixtures().join("missing")];
+    assert_eq!(world.load_paths(&paths), 3);
+    assert_eq!(world.load_paths(&paths), 0);
+    assert_eq!(world.plugins().len(), 3);
+
+    let missing = world.load_bundle(&fixtures().join("missing.lv2"));
+    assert!(matches!(missing, Err(Lv2Error::BundleRead { .. })));
+}
+
+#[test]
+fn test_instantiate_checks_plugin() {
+    let world = world();
+
+    let unsupported = Lv2Plugin::instantiate(world.plugin(WORKER).unwrap(), 48000);
+    assert!(matches!(unsupported, Err(Lv2Error::Unsupported { .. })));
+
+    // The fixture bundle ships no binary
+    let missing = Lv2Plugin::instantiate(world.plugin(STEREO_AMP).unwrap(), 48000);
+    assert!(matches!(missing, Err(Lv2Error::LoadError { .. })));
+}
+
+// ===== Processing =====
+
+#[test]
+fn test_stereo_gain() {
+    let mut plugin = stereo_amp();
+    assert_eq!(AudioEffect::name(&plugin), "Soul Test Stereo Amp");
+
+    // Default gain is 0 dB
+    let mut buffer = vec![0.5, -0.25, 0.1, 0.2];
+    AudioEffect::process(&mut plugin, &mut buffer, 48000);
+    assert_eq!(buffer, vec![0.5, -0.25, 0.1, 0.2]);
+
+    assert!(plugin.set_control("gain", HALF_GAIN_DB));
+    AudioEffect::process(&mut plugin, &mut buffer, 48000);
+    for (sample, expected) in buffer.iter().zip([0.25, -0.125, 0.05, 0.1]) {
+        assert_close(*sample, expected);
+    }
+}
+
+#[test]
+fn test_dual_mono() {
+    let mut plugin = mono_amp();
+
+    // Default gain is -6 dB, applied to each channel separately
+    let mut buffer = vec![1.0, 0.0, 0.0, -1.0];
+    AudioEffect::process(&mut plugin, &mut buffer, 48000);
+    let gain = 10.0_f32.powf(-6.0 / 20.0);
+    assert_close(buffer[0], gain);
+    assert_close(buffer[1], 0.0);
+    assert_close(buffer[2], 0.0);
+    assert_close(buffer[3], -gain);
+
+    assert!(plugin.set_control("mute", 0.8));
+    assert_eq!(plugin.control("mute"), Some(1.0));
+    let mut buffer = vec![1.0, 1.0];
+    AudioEffect::process(&mut plugin, &mut buffer, 48000);
+    assert_eq!(buffer, vec![0.0, 0.0]);
+}
+
+#[test]
+fn test_long_buffers_are_split() {
+    let mut plugin = stereo_amp();
+    plugin.set_control("gain", HALF_GAIN_DB);
+
+    let frames = MAX_BLOCK_FRAMES * 2 + 100;
+    let mut buffer = vec![1.0; frames * 2];
+    AudioEffect::process(&mut plugin, &mut buffer, 48000);
+    for sample in &buffer {
+        assert_close(*sample, 0.5);
+    }
+}
+
+#[test]
+fn test_passthrough_when_disabled_or_wrong_rate() {
+    let mut plugin = stereo_amp();
+    plugin.set_control("gain", HALF_GAIN_DB);
+
+    let mut buffer = vec![1.0, 1.0];
+    AudioEffect::process(&mut plugin, &mut buffer, 44100);
+    assert_eq!(buffer, vec![1.0, 1.0]);
+
+    AudioEffect::set_enabled(&mut plugin, false);
+    assert!(!AudioEffect::is_enabled(&plugin));
+    AudioEffect::process(&mut plugin, &mut buffer, 48000);
+    assert_eq!(buffer, vec![1.0, 1.0]);
+}
+
+#[test]
+fn test_reset_reactivates() {
+    let mut plugin = stereo_amp();
+    let before = ACTIVATIONS.load(Ordering::SeqCst);
+    AudioEffect::reset(&mut plugin);
+    assert!(ACTIVATIONS.load(Ordering::SeqCst) > before);
+
+    let mut buffer = vec![0.5, 0.5];
+    AudioEffect::process(&mut plugin, &mut buffer, 48000);
+    assert_eq!(buffer, vec![0.5, 0.5]);
+}
+
+// ===== Latency =====
+
+#[test]
+fn test_latency_reporting() {
+    // Reported right after instantiation
+    let plugin = stereo_amp();
+    assert_eq!(AudioEffect::latency_samples(&plugin), 64);
+    assert_eq!(plugin.output("latency"), Some(REPORTED_LATENCY));
+
+    let mono = mono_amp();
+    assert_eq!(AudioEffect::latency_samples(&mono), 0);
+
+    let mut chain = EffectChain::new();
+    chain.add_effect(Box::new(plugin));
+    chain.add_effect(Box::new(mono));
+    assert_eq!(chain.latency_samples(), 64);
+}
+
+// ===== Parameters =====
+
+#[test]
+fn test_params_round_trip() {
+    let mut plugin = mono_amp();
+    plugin.set_control("gain", 3.0);
+    plugin.set_control("mute", 1.0);
+
+    let params = plugin.params();
+    assert_eq!(params.plugin_uri, MONO_AMP);
+    assert_eq!(params.controls.len(), 2);
+
+    let json = serde_json::to_string(&params).unwrap();
+    let restored: Lv2Params = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, params);
+
+    let mut other = mono_amp();
+    assert!(other.apply_parameters(&restored));
+    assert_eq!(other.params(), params);
+}
+
+#[test]
+fn test_apply_parameters() {
+    let mut plugin = mono_amp();
+
+    // Out-of-range values are clamped, unknown symbols ignored
+    let params = Lv2Params::new(MONO_AMP)
+        .with_control("gain", 100.0)
+        .with_control("removed_in_v2", 1.0);
+    assert!(plugin.apply_parameters(&params));
+    assert_eq!(plugin.control("gain"), Some(24.0));
+    assert_eq!(plugin.control("mute"), Some(0.0));
+    assert_eq!(plugin.control("removed_in_v2"), None);
+
+    // Settings for another plugin are rejected
+    let foreign = Lv2Params::new(STEREO_AMP).with_control("gain", -12.0);
+    assert!(!plugin.apply_parameters(&foreign));
+    assert_eq!(plugin.control("gain"), Some(24.0));
+
+    // Outputs aren't parameters
+    assert!(!plugin.set_control("out", 1.0));
+
+    // Presets saved without controls deserialize to defaults
+    let bare: Lv2Params = serde_json::from_str(r#"{"plugin_uri":"urn:x"}"#).unwrap();
+    assert!(bare.controls.is_empty());
+}
+
+// ===== Pipeline =====
+
+#[test]
+fn test_pipeline_component() {
+    let mut component: Box<dyn PipelineComponent> = Box::new(stereo_amp());
+    assert_eq!(component.info().type_id, LV2_EFFECT_TYPE);
+    assert!(component.info().supports_in_place_update);
+    assert_eq!(component.latency_samples(), 64);
+
+    let params = Lv2Params::new(STEREO_AMP).with_control("gain", HALF_GAIN_DB);
+    assert!(component.update_parameters(&params));
+    assert!(!component.update_parameters(&Lv2Params::new(MONO_AMP)));
+    assert!(!component.update_parameters(&0.5_f32));
+
+    let mut buffer = vec![1.0, 1.0];
+    component.process(&mut buffer, 48000);
+    assert_close(buffer[0], 0.5);
+
+    let plugin = component.as_any().downcast_ref::<Lv2Plugin>().unwrap();
+    assert_close(plugin.control("gain").unwrap(), HALF_GAIN_DB);
+}
+
+#[test]
+fn test_registry_updates_in_place() {
+    let mut registry = EffectRegistry::with_builtin_effects();
+    register_lv2_effects(&mut registry, Arc::new(world()), 48000);
+
+    // Created from the binary, which the fixture doesn't have
+    assert!(registry
+        .create(LV2_EFFECT_TYPE, &Lv2Params::new(STEREO_AMP))
+        .is_none());
+
+    let mut component: Box<dyn PipelineComponent> = Box::new(mono_amp());
+    let params = Lv2Params::new(MONO_AMP).with_control("gain", 0.0);
+    assert!(registry.update_in_place(LV2_EFFECT_TYPE, component.as_mut(), &params));
+
+    let mut buffer = vec![0.75, -0.75];
+    component.process(&mut buffer, 48000);
+    assert_close(buffer[0], 0.75);
+    assert_close(buffer[1], -0.75);
+}
+
+// ===== Plugin binary =====
+
+/// Copy of the fixture bundle with `tests/plugin` compiled into it
+///
+/// Built once per test run, with the `rustc` from `RUSTC` or the `PATH`.
+#[cfg(target_os = "linux")]
+fn built_bundle() -> &'static std::path::Path {
+    use std::process::Command;
+    use std::sync::OnceLock;
+
+    static BUNDLE: OnceLock<PathBuf> = OnceLock::new();
+    BUNDLE.get_or_init(|| {
+        let bundle = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("soul-test-amp.lv2");
+        std::fs::create_dir_all(&bundle).unwrap();
+        for file in ["manifest.ttl", "amp.ttl"] {
+            let source = fixtures().join("soul-test-amp.lv2").join(file);
+            std::fs::copy(source, bundle.join(file)).unwrap();
+        }
+
+        let rustc = std::env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+        let status = Command::new(rustc)
+            .args(["--edition", "2021", "--crate-type", "cdylib"])
+            .args(["--crate-name", "soul_test_amp", "-o"])
+            .arg(bundle.join("soul-test-amp.so"))
+            .arg(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/plugin/soul_test_amp.rs"))
+            .status()
+            .expect("failed to run rustc");
+        assert!(status.success(), "building the test plugin failed");
+        bundle
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn built_world() -> Lv2World {
+    let mut world = Lv2World::new();
+    world.load_bundle(built_bundle()).unwrap();
+    world
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_instantiate_from_binary() {
+    let world = built_world();
+    let info = world.plugin(STEREO_AMP).unwrap();
+    assert!(info.binary.is_file());
+
+    let mut plugin = Lv2Plugin::instantiate(info, 48000).unwrap();
+    assert_eq!(AudioEffect::latency_samples(&plugin), 64);
+    plugin.set_control("gain", HALF_GAIN_DB);
+
+    let mut buffer = vec![1.0, -0.5];
+    AudioEffect::process(&mut plugin, &mut buffer, 48000);
+    assert_close(buffer[0], 0.5);
+    assert_close(buffer[1], -0.25);
+
+    // Both plugins come from the same binary
+    let mono = Lv2Plugin::instantiate(world.plugin(MONO_AMP).unwrap(), 48000).unwrap();
+    assert_eq!(mono.control("gain"), Some(-6.0));
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_registry_creates_from_binary() {
+    let mut registry = EffectRegistry::with_builtin_effects();
+    register_lv2_effects(&mut registry, Arc::new(built_world()), 48000);
+
+    let params = Lv2Params::new(MONO_AMP).with_control("mute", 1.0);
+    let mut component = registry.create(LV2_EFFECT_TYPE, &params).unwrap();
+    let mut buffer = vec![0.75, -0.75];
+    component.process(&mut buffer, 48000);
+    assert_eq!(buffer, vec![0.0, 0.0]);
+
+    let plugin = component.as_any().downcast_ref::<Lv2Plugin>().unwrap();
+    assert_eq!(plugin.params().plugin_uri, MONO_AMP);
+}
@@ -0,0 +1,145 @@
+//! Binary of the test bundle in `tests/fixtures/soul-test-amp.lv2`
+//!
+//! Compiled into `soul-test-amp.so` by `lv2_host_test.rs`, so the tests can
+//! load a real plugin through `lv2_descriptor`. Implements the stereo and
+//! mono amps the same way as the in-process plugins in `lv2_host_test.rs`.
+//! Standalone on purpose: it's built with plain `rustc`, without crates.
+
+use std::ffi::{c_char, c_void, CStr};
+
+type Lv2Handle = *mut c_void;
+
+#[repr(C)]
+pub struct Lv2Feature {
+    uri: *const c_char,
+    data: *mut c_void,
+}
+
+#[repr(C)]
+pub struct Lv2Descriptor {
+    uri: *const c_char,
+    instantiate: Option<
+        unsafe extern "C" fn(
+            *const Lv2Descriptor,
+            f64,
+            *const c_char,
+            *const *const Lv2Feature,
+        ) -> Lv2Handle,
+    >,
+    connect_port: Option<unsafe extern "C" fn(Lv2Handle, u32, *mut c_void)>,
+    activate: Option<unsafe extern "C" fn(Lv2Handle)>,
+    run: Option<unsafe extern "C" fn(Lv2Handle, u32)>,
+    deactivate: Option<unsafe extern "C" fn(Lv2Handle)>,
+    cleanup: Option<unsafe extern "C" fn(Lv2Handle)>,
+    extension_data: Option<unsafe extern "C" fn(*const c_char) -> *const c_void>,
+}
+
+unsafe impl Sync for Lv2Descriptor {}
+
+const URID_MAP_URI: &[u8] = b"http://lv2plug.in/ns/ext/urid#map\0";
+const REPORTED_LATENCY: f32 = 64.0;
+
+// Stereo amp ports: 0 gain, 1-2 audio in, 3-4 audio out, 5 latency
+// Mono amp ports:   0 audio in, 1 audio out, 2 gain, 3 mute
+
+struct Amp {
+    /// Port buffers, indexed by port index
+    ports: [*mut f32; 6],
+    stereo: bool,
+}
+
+static STEREO_DESCRIPTOR: Lv2Descriptor = descriptor(b"urn:soul-player:test:stereo-amp\0");
+static MONO_DESCRIPTOR: Lv2Descriptor = descriptor(b"urn:soul-player:test:mono-amp\0");
+
+const fn descriptor(uri: &'static [u8]) -> Lv2Descriptor {
+    Lv2Descriptor {
+        uri: uri.as_ptr().cast(),
+        instantiate: Some(instantiate),
+        connect_port: Some(connect_port),
+        activate: None,
+        run: Some(run),
+        deactivate: None,
+        cleanup: Some(cleanup),
+        extension_data: None,
+    }
+}
+
+/// Plugin entry point
+///
+/// The worker plugin in the manifest is left out: hosts can't instantiate
+/// it, so they never ask for it.
+#[no_mangle]
+pub extern "C" fn lv2_descriptor(index: u32) -> *const Lv2Descriptor {
+    match index {
+        0 => &STEREO_DESCRIPTOR,
+        1 => &MONO_DESCRIPTOR,
+        _ => std::ptr::null(),
+    }
+}
+
+unsafe fn has_feature(features: *const *const Lv2Feature, uri: &[u8]) -> bool {
+    let uri = CStr::from_bytes_with_nul(uri).unwrap();
+    let mut feature = features;
+    while !(*feature).is_null() {
+        if CStr::from_ptr((**feature).uri) == uri {
+            return true;
+        }
+        feature = feature.add(1);
+    }
+    false
+}
+
+unsafe extern "C" fn instantiate(
+    descriptor: *const Lv2Descriptor,
+    _sample_rate: f64,
+    bundle_path: *const c_char,
+    features: *const *const Lv2Feature,
+) -> Lv2Handle {
+    let bundle_path = CStr::from_ptr(bundle_path).to_string_lossy();
+    if !bundle_path.ends_with("soul-test-amp.lv2/") || !has_feature(features, URID_MAP_URI) {
+        return std::ptr::null_mut();
+    }
+
+    let amp = Box::new(Amp {
+        ports: [std::ptr::null_mut(); 6],
+        stereo: std::ptr::eq(descriptor, &STEREO_DESCRIPTOR),
+    });
+    Box::into_raw(amp).cast()
+}
+
+unsafe extern "C" fn connect_port(instance: Lv2Handle, port: u32, data: *mut c_void) {
+    let amp = &mut *instance.cast::<Amp>();
+    amp.ports[port as usize] = data.cast();
+}
+
+unsafe extern "C" fn run(instance: Lv2Handle, sample_count: u32) {
+    let amp = &*instance.cast::<Amp>();
+    let frames = sample_count as usize;
+
+    if amp.stereo {
+        *amp.ports[5] = REPORTED_LATENCY;
+        let gain = 10.0_f32.powf(*amp.ports[0] / 20.0);
+        for (input, output) in [(1, 3), (2, 4)] {
+            let input = std::slice::from_raw_parts(amp.ports[input], frames);
+            let output = std::slice::from_raw_parts_mut(amp.ports[output], frames);
+            for (out, sample) in output.iter_mut().zip(input) {
+                *out = sample * gain;
+            }
+        }
+    } else {
+        let gain = if *amp.ports[3] > 0.5 {
+            0.0
+        } else {
+            10.0_f32.powf(*amp.ports[2] / 20.0)
+        };
+        let input = std::slice::from_raw_parts(amp.ports[0], frames);
+        let output = std::slice::from_raw_parts_mut(amp.ports[1], frames);
+        for (out, sample) in output.iter_mut().zip(input) {
+            *out = sample * gain;
+        }
+    }
+}
+
+unsafe extern "C" fn cleanup(instance: Lv2Handle) {
+    drop(Box::from_raw(instance.cast::<Amp>()));
+}
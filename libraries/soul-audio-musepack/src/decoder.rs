@@ -0,0 +1,258 @@
+//! Streaming Musepack decoder on top of libmpcdec
+
+#![allow(unsafe_code)]
+
+use crate::error::{MusepackError, Result};
+use crate::ffi::{self, MpcDemux, MpcFrameInfo, MpcReader, MpcStreamInfo, STATUS_OK};
+use soul_core::{AudioBuffer, AudioDecoder, AudioFormat, AudioMetadata, SampleRate};
+use std::ffi::CString;
+use std::path::Path;
+use std::time::Duration;
+
+/// File extensions of Musepack files
+pub const EXTENSIONS: &[&str] = &["mpc", "mp+", "mpp"];
+
+/// An open libmpcdec demuxer
+struct Stream {
+    library: &'static ffi::Library,
+    /// Boxed: the demuxer keeps a pointer to the reader
+    reader: Box<MpcReader>,
+    demux: *mut MpcDemux,
+    sample_rate: u32,
+    channels: usize,
+    /// Samples per channel, without the encoder delay
+    total_samples: u64,
+    /// Decoded samples not yet returned
+    pending: Vec<f32>,
+    /// Frame buffer handed to the library
+    frame: Vec<f32>,
+    position_samples: u64,
+    finished: bool,
+}
+
+// The demuxer is only used through `&mut self`; libmpcdec has no thread
+// affinity.
+unsafe impl Send for Stream {}
+
+impl Stream {
+    fn open(path: &Path) -> Result<Self> {
+        let library = ffi::library()?;
+        let open_error = |message: &str| MusepackError::Open {
+            path: path.to_path_buf(),
+            message: message.to_string(),
+        };
+        let c_path = CString::new(path.to_string_lossy().as_bytes())
+            .map_err(|_| open_error("path contains a NUL byte"))?;
+
+        let mut reader = Box::<MpcReader>::default();
+        if unsafe { (library.reader_init_stdio)(&mut *reader, c_path.as_ptr()) } != STATUS_OK {
+            return Err(open_error("cannot read file"));
+        }
+        let demux = unsafe { (library.demux_init)(&mut *reader) };
+        if demux.is_null() {
+            unsafe { (library.reader_exit_stdio)(&mut *reader) };
+            return Err(open_error("not a Musepack stream"));
+        }
+
+        let mut info = MpcStreamInfo::default();
+        unsafe { (library.demux_get_info)(demux, &mut info) };
+
+        let stream = Self {
+            library,
+            reader,
+            demux,
+            sample_rate: info.sample_freq,
+            channels: info.channels as usize,
+            total_samples: info.samples.saturating_sub(info.beg_silence),
+            pending: Vec::new(),
+            frame: vec![0.0; ffi::DECODER_BUFFER_LENGTH],
+            position_samples: 0,
+            finished: false,
+        };
+        if stream.sample_rate == 0 || !matches!(stream.channels, 1 | 2) {
+            return Err(open_error("unsupported stream parameters"));
+        }
+        Ok(stream)
+    }
+
+    /// Decode the next frame into `pending`; false at the end of the stream
+    fn decode_frame(&mut self) -> Result<bool> {
+        let mut frame = MpcFrameInfo {
+            samples: 0,
+            bits: 0,
+            buffer: self.frame.as_mut_ptr(),
+            is_key_frame: 0,
+        };
+        let status = unsafe { (self.library.demux_decode)(self.demux, &mut frame) };
+        if status != STATUS_OK {
+            return Err(MusepackError::Decode(format!("status {}", status)));
+        }
+        if frame.bits == -1 {
+            return Ok(false);
+        }
+
+        let samples = (frame.samples as usize).min(ffi::DECODER_BUFFER_LENGTH / self.channels);
+        self.pending
+            .extend_from_slice(&self.frame[..samples * self.channels]);
+        Ok(true)
+    }
+
+    fn decode_chunk(&mut self, max_frames: usize) -> Result<Option<Vec<f32>>> {
+        let target_samples = max_frames * self.channels;
+        while !self.finished && self.pending.len() < target_samples {
+            if !self.decode_frame()? {
+                self.finished = true;
+            }
+        }
+        if self.pending.is_empty() {
+            return Ok(None);
+        }
+
+        let take = self.pending.len().min(target_samples);
+        let samples: Vec<f32> = self.pending.drain(..take).collect();
+        self.position_samples += (samples.len() / self.channels) as u64;
+        Ok(Some(samples))
+    }
+
+    fn seek(&mut self, position: Duration) -> Result<Duration> {
+        let target = ((position.as_secs_f64() * f64::from(self.sample_rate)).round() as u64)
+            .min(self.total_samples);
+        let status = unsafe { (self.library.demux_seek_sample)(self.demux, target) };
+        if status != STATUS_OK {
+            return Err(MusepackError::Decode(format!(
+                "seek failed with status {}",
+                status
+            )));
+        }
+
+        self.pending.clear();
+        self.finished = false;
+        self.position_samples = target;
+        Ok(self.position())
+    }
+
+    fn position(&self) -> Duration {
+        Duration::from_secs_f64(self.position_samples as f64 / f64::from(self.sample_rate))
+    }
+
+    fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.total_samples as f64 / f64::from(self.sample_rate))
+    }
+
+    fn format(&self) -> AudioFormat {
+        AudioFormat::new(SampleRate::new(self.sample_rate), self.channels as u16, 32)
+    }
+}
+
+impl Drop for Stream {
+    fn drop(&mut self) {
+        unsafe {
+            (self.library.demux_exit)(self.demux);
+            (self.library.reader_exit_stdio)(&mut *self.reader);
+        }
+    }
+}
+
+/// Musepack (SV7 and SV8) decoder
+///
+/// Decodes through libmpcdec, loaded when the first file is opened; opening
+/// fails with [`MusepackError::LibraryNotFound`] if it isn't installed.
+/// Output is interleaved `f32` in the file's channels (mono or stereo).
+#[derive(Default)]
+pub struct MusepackDecoder {
+    stream: Option<Stream>,
+}
+
+impl MusepackDecoder {
+    /// Create a new decoder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn stream(&mut self) -> Result<&mut Stream> {
+        self.stream.as_mut().ok_or(MusepackError::NotOpen)
+    }
+}
+
+impl AudioDecoder for MusepackDecoder {
+    fn decode(&mut self, path: &Path) -> soul_core::Result<AudioBuffer> {
+        let mut stream = Stream::open(path)?;
+        let format = stream.format();
+        let mut samples = Vec::new();
+        while let Some(chunk) = stream.decode_chunk(ffi::FRAME_LENGTH * 16)? {
+            samples.extend(chunk);
+        }
+        Ok(AudioBuffer::new(samples, format))
+    }
+
+    fn supports_format(&self, path: &Path) -> bool {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|ext| EXTENSIONS.iter().any(|e| ext.eq_ignore_ascii_case(e)))
+    }
+
+    fn open(&mut self, path: &Path) -> soul_core::Result<AudioMetadata> {
+        self.stream = None;
+        let stream = Stream::open(path)?;
+        let metadata = AudioMetadata {
+            sample_rate: stream.sample_rate,
+            channels: stream.channels as u16,
+            duration: Some(stream.duration()),
+            bits_per_sample: None,
+        };
+        self.stream = Some(stream);
+        Ok(metadata)
+    }
+
+    fn decode_chunk(&mut self, max_frames: usize) -> soul_core::Result<Option<AudioBuffer>> {
+        let stream = self.stream()?;
+        let format = stream.format();
+        Ok(stream
+            .decode_chunk(max_frames)?
+            .map(|samples| AudioBuffer::new(samples, format)))
+    }
+
+    fn seek(&mut self, position: Duration) -> soul_core::Result<Duration> {
+        Ok(self.stream()?.seek(position)?)
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        self.stream.as_ref().map(Stream::duration)
+    }
+
+    fn position(&self) -> Duration {
+        self.stream
+            .as_ref()
+            .map_or(Duration::ZERO, Stream::position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_supports_format() {
+        let decoder = MusepackDecoder::new();
+        assert!(decoder.supports_format(Path::new("/music/track.mpc")));
+        assert!(decoder.supports_format(Path::new("/music/track.MP+")));
+        assert!(!decoder.supports_format(Path::new("/music/track.mp3")));
+    }
+
+    #[test]
+    fn test_not_open() {
+        let mut decoder = MusepackDecoder::new();
+        assert!(decoder.decode_chunk(1024).is_err());
+        assert!(decoder.seek(Duration::from_secs(1)).is_err());
+        assert_eq!(decoder.duration(), None);
+        assert_eq!(decoder.position(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_open_missing_file() {
+        let mut decoder = MusepackDecoder::new();
+        let result = decoder.open(Path::new("/nonexistent/track.mpc"));
+        assert!(result.is_err());
+        assert!(decoder.duration().is_none());
+    }
+}
@@ -0,0 +1,32 @@
+/// Musepack decoder errors
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Result type for Musepack operations
+pub type Result<T> = std::result::Result<T, MusepackError>;
+
+/// Musepack decoder errors
+#[derive(Debug, Error)]
+pub enum MusepackError {
+    /// libmpcdec isn't installed or lacks a function we need
+    #[error("libmpcdec not available: {0}")]
+    LibraryNotFound(String),
+
+    /// The file couldn't be opened or isn't a Musepack stream
+    #[error("Failed to open Musepack file {path}: {message}")]
+    Open { path: PathBuf, message: String },
+
+    /// The stream is corrupt
+    #[error("Musepack decode error: {0}")]
+    Decode(String),
+
+    /// No file is open
+    #[error("No Musepack file open")]
+    NotOpen,
+}
+
+impl From<MusepackError> for soul_core::SoulError {
+    fn from(err: MusepackError) -> Self {
+        soul_core::SoulError::Audio(err.to_string())
+    }
+}
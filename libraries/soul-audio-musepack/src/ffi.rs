@@ -0,0 +1,186 @@
+//! libmpcdec (Musepack SV7/SV8 decoder) bindings
+//!
+//! The library is loaded at runtime so builds don't need it installed; only
+//! the handful of functions of the demuxer API are used. Types mirror
+//! `mpc/mpcdec.h` and `mpc/streaminfo.h` of libmpcdec 1.2+ (SV8) with the
+//! default float sample format.
+
+#![allow(unsafe_code)]
+
+use crate::error::{MusepackError, Result};
+use std::ffi::{c_char, c_void};
+use std::sync::OnceLock;
+
+/// Samples per channel in one Musepack frame
+pub const FRAME_LENGTH: usize = 36 * 32;
+
+/// Size of the buffer `mpc_demux_decode` writes to, in samples
+pub const DECODER_BUFFER_LENGTH: usize = 4 * FRAME_LENGTH;
+
+/// `mpc_status` success value
+pub const STATUS_OK: i32 = 0;
+
+/// File names tried when loading the library
+const LIBRARY_NAMES: &[&str] = &[
+    "libmpcdec.so.6",
+    "libmpcdec.so",
+    "libmpcdec.6.dylib",
+    "libmpcdec.dylib",
+    "mpcdec.dll",
+    "libmpcdec.dll",
+];
+
+/// Opaque demuxer handle
+#[repr(C)]
+pub struct MpcDemux {
+    _private: [u8; 0],
+}
+
+/// Reader callbacks; filled in by `mpc_reader_init_stdio`
+#[repr(C)]
+pub struct MpcReader {
+    pub read: Option<unsafe extern "C" fn(*mut MpcReader, *mut c_void, i32) -> i32>,
+    pub seek: Option<unsafe extern "C" fn(*mut MpcReader, i32) -> u8>,
+    pub tell: Option<unsafe extern "C" fn(*mut MpcReader) -> i32>,
+    pub get_size: Option<unsafe extern "C" fn(*mut MpcReader) -> i32>,
+    pub canseek: Option<unsafe extern "C" fn(*mut MpcReader) -> u8>,
+    pub data: *mut c_void,
+}
+
+impl Default for MpcReader {
+    fn default() -> Self {
+        Self {
+            read: None,
+            seek: None,
+            tell: None,
+            get_size: None,
+            canseek: None,
+            data: std::ptr::null_mut(),
+        }
+    }
+}
+
+/// One decoded frame
+#[repr(C)]
+pub struct MpcFrameInfo {
+    /// Samples per channel in the frame
+    pub samples: u32,
+    /// Bits consumed, -1 at the end of the stream
+    pub bits: i32,
+    /// Interleaved output, at least [`DECODER_BUFFER_LENGTH`] samples
+    pub buffer: *mut f32,
+    pub is_key_frame: u8,
+}
+
+/// Stream properties
+#[repr(C)]
+pub struct MpcStreamInfo {
+    pub sample_freq: u32,
+    pub channels: u32,
+    pub stream_version: u32,
+    pub bitrate: u32,
+    pub average_bitrate: f64,
+    pub max_band: u32,
+    pub ms: u8,
+    pub fast_seek: u8,
+    pub block_pwr: u32,
+    pub gain_title: u16,
+    pub gain_album: u16,
+    pub peak_album: u16,
+    pub peak_title: u16,
+    pub is_true_gapless: u32,
+    /// Samples in the stream, including the leading silence
+    pub samples: u64,
+    /// Encoder delay at the start of the stream
+    pub beg_silence: u64,
+    pub encoder_version: u32,
+    pub encoder: [c_char; 256],
+    pub pns: u8,
+    pub profile: f32,
+    pub profile_name: *const c_char,
+    pub header_position: i64,
+    pub tag_offset: i64,
+    pub total_file_length: i64,
+    /// Room for fields added by newer library versions
+    _reserved: [u8; 64],
+}
+
+impl Default for MpcStreamInfo {
+    fn default() -> Self {
+        // SAFETY: plain old data; all-zero is a valid value
+        unsafe { std::mem::zeroed() }
+    }
+}
+
+type ReaderInitStdio = unsafe extern "C" fn(*mut MpcReader, *const c_char) -> i32;
+type ReaderExitStdio = unsafe extern "C" fn(*mut MpcReader);
+type DemuxInit = unsafe extern "C" fn(*mut MpcReader) -> *mut MpcDemux;
+type DemuxExit = unsafe extern "C" fn(*mut MpcDemux);
+type DemuxGetInfo = unsafe extern "C" fn(*mut MpcDemux, *mut MpcStreamInfo);
+type DemuxDecode = unsafe extern "C" fn(*mut MpcDemux, *mut MpcFrameInfo) -> i32;
+type DemuxSeekSample = unsafe extern "C" fn(*mut MpcDemux, u64) -> i32;
+
+/// Entry points of a loaded libmpcdec
+pub struct Library {
+    pub reader_init_stdio: ReaderInitStdio,
+    pub reader_exit_stdio: ReaderExitStdio,
+    pub demux_init: DemuxInit,
+    pub demux_exit: DemuxExit,
+    pub demux_get_info: DemuxGetInfo,
+    pub demux_decode: DemuxDecode,
+    pub demux_seek_sample: DemuxSeekSample,
+    /// Keeps the library loaded for the function pointers above
+    _handle: libloading::Library,
+}
+
+impl Library {
+    fn load() -> Result<Self> {
+        let mut errors = Vec::new();
+        for name in LIBRARY_NAMES {
+            match unsafe { libloading::Library::new(name) } {
+                Ok(library) => return unsafe { Self::from_library(library) },
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+        Err(MusepackError::LibraryNotFound(errors.join("; ")))
+    }
+
+    unsafe fn from_library(library: libloading::Library) -> Result<Self> {
+        unsafe fn symbol<T: Copy>(library: &libloading::Library, name: &[u8]) -> Result<T> {
+            library
+                .get::<T>(name)
+                .map(|s| *s)
+                .map_err(|e| MusepackError::LibraryNotFound(e.to_string()))
+        }
+
+        Ok(Self {
+            reader_init_stdio: symbol(&library, b"mpc_reader_init_stdio\0")?,
+            reader_exit_stdio: symbol(&library, b"mpc_reader_exit_stdio\0")?,
+            demux_init: symbol(&library, b"mpc_demux_init\0")?,
+            demux_exit: symbol(&library, b"mpc_demux_exit\0")?,
+            demux_get_info: symbol(&library, b"mpc_demux_get_info\0")?,
+            demux_decode: symbol(&library, b"mpc_demux_decode\0")?,
+            demux_seek_sample: symbol(&library, b"mpc_demux_seek_sample\0")?,
+            _handle: library,
+        })
+    }
+}
+
+/// The process-wide libmpcdec, loaded on first use
+pub fn library() -> Result<&'static Library> {
+    static LIBRARY: OnceLock<std::result::Result<Library, String>> = OnceLock::new();
+    LIBRARY
+        .get_or_init(|| {
+            Library::load().map_err(|e| match e {
+                MusepackError::LibraryNotFound(message) => message,
+                other => other.to_string(),
+            })
+        })
+        .as_ref()
+        .map_err(|e| MusepackError::LibraryNotFound(e.clone()))
+}
+
+/// Whether libmpcdec can be loaded
+pub fn is_available() -> bool {
+    library().is_ok()
+}
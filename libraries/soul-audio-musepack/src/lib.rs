@@ -0,0 +1,37 @@
+//! Musepack decoding for Soul Player
+//!
+//! Decodes Musepack (`.mpc`) files, stream versions 7 and 8, through
+//! [libmpcdec](https://www.musepack.net). The library is loaded at runtime,
+//! so builds don't depend on it: without it installed, opening a file fails
+//! with [`MusepackError::LibraryNotFound`].
+//!
+//! `soul-audio` registers [`MusepackDecoder`] in its decoder registry with
+//! the `musepack` feature; tags and stream properties are read there without
+//! the library.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use soul_audio_musepack::MusepackDecoder;
+//! use soul_core::AudioDecoder;
+//! use std::path::Path;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut decoder = MusepackDecoder::new();
+//! let info = decoder.open(Path::new("archive/track.mpc"))?;
+//! println!("{} Hz, {:?}", info.sample_rate, info.duration);
+//!
+//! while let Some(chunk) = decoder.decode_chunk(4096)? {
+//!     println!("{} samples", chunk.samples.len());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+mod decoder;
+mod error;
+pub mod ffi;
+
+pub use decoder::{MusepackDecoder, EXTENSIONS};
+pub use error::{MusepackError, Result};
+pub use ffi::is_available;
@@ -0,0 +1,573 @@
+//! Real-time audio analysis for level meters and spectrum displays
+//!
+//! [`AnalysisTap`] sits in the playback pipeline and measures the audio that
+//! passes through it:
+//! - Peak and RMS level per channel
+//! - Momentary (400ms) and short-term (3s) loudness in LUFS
+//! - A magnitude spectrum reduced to [`SPECTRUM_BANDS`] log-spaced bands
+//!
+//! Processing never allocates. Results are published at a bounded rate
+//! (30 Hz by default) into shared atomics that an [`AnalysisReader`] on the
+//! UI thread can poll without taking any lock.
+//!
+//! # Example
+//!
+//! ```
+//! use soul_audio::analysis::AnalysisTap;
+//!
+//! let mut tap = AnalysisTap::new(44100, 2);
+//! let reader = tap.reader();
+//!
+//! // Audio thread
+//! let buffer = vec![0.5f32; 44100 * 2 / 10];
+//! tap.process(&buffer);
+//!
+//! // UI thread
+//! if let Some(levels) = reader.latest() {
+//!     println!("L peak: {:.1} dBFS", levels.peak_dbfs(0));
+//! }
+//! ```
+
+use crate::channels::MAX_CHANNELS;
+use rustfft::{num_complex::Complex, Fft, FftPlanner};
+use soul_loudness::LoudnessAnalyzer;
+use std::sync::atomic::{fence, AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Number of bands in the published spectrum
+pub const SPECTRUM_BANDS: usize = 64;
+
+/// Default publish rate in Hz
+pub const DEFAULT_PUBLISH_HZ: u32 = 30;
+
+/// FFT size for the spectrum (~46ms at 44.1kHz)
+const FFT_SIZE: usize = 2048;
+
+/// Lowest and highest frequency shown in the spectrum
+const SPECTRUM_MIN_HZ: f32 = 20.0;
+const SPECTRUM_MAX_HZ: f32 = 20000.0;
+
+/// Floor for dB conversions (silence)
+const SILENCE_DB: f32 = -120.0;
+
+/// One published analysis snapshot
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AudioAnalysis {
+    /// Increments with every publish; lets pollers skip unchanged snapshots
+    pub sequence: u64,
+    /// Number of valid entries in `peak` and `rms`
+    pub channels: u16,
+    /// Peak level per channel since the previous snapshot (linear)
+    pub peak: [f32; MAX_CHANNELS],
+    /// RMS level per channel since the previous snapshot (linear)
+    pub rms: [f32; MAX_CHANNELS],
+    /// Momentary loudness in LUFS (`-inf` when silent or unavailable)
+    pub momentary_lufs: f32,
+    /// Short-term loudness in LUFS (`-inf` when silent or unavailable)
+    pub short_term_lufs: f32,
+    /// Spectrum magnitude per band in dBFS, low to high frequency
+    pub spectrum_db: [f32; SPECTRUM_BANDS],
+}
+
+impl AudioAnalysis {
+    /// Peak level of a channel in dBFS
+    pub fn peak_dbfs(&self, channel: usize) -> f32 {
+        linear_to_db(self.peak.get(channel).copied().unwrap_or(0.0))
+    }
+
+    /// RMS level of a channel in dBFS
+    pub fn rms_dbfs(&self, channel: usize) -> f32 {
+        linear_to_db(self.rms.get(channel).copied().unwrap_or(0.0))
+    }
+}
+
+/// Center frequency of each spectrum band for a sample rate
+pub fn spectrum_band_frequencies(sample_rate: u32) -> [f32; SPECTRUM_BANDS] {
+    let (min, max) = spectrum_range(sample_rate);
+    std::array::from_fn(|band| {
+        let lo = band_edge(min, max, band);
+        let hi = band_edge(min, max, band + 1);
+        (lo * hi).sqrt()
+    })
+}
+
+/// Latest-value slot shared between the tap and its readers
+///
+/// A sequence lock over atomic fields: the single writer makes the sequence
+/// odd while updating, readers retry if it was odd or changed underneath them.
+struct SharedAnalysis {
+    sequence: AtomicU64,
+    channels: AtomicU32,
+    peak: [AtomicU32; MAX_CHANNELS],
+    rms: [AtomicU32; MAX_CHANNELS],
+    momentary_lufs: AtomicU32,
+    short_term_lufs: AtomicU32,
+    spectrum_db: [AtomicU32; SPECTRUM_BANDS],
+}
+
+impl SharedAnalysis {
+    fn new() -> Self {
+        Self {
+            sequence: AtomicU64::new(0),
+            channels: AtomicU32::new(0),
+            peak: std::array::from_fn(|_| AtomicU32::new(0)),
+            rms: std::array::from_fn(|_| AtomicU32::new(0)),
+            momentary_lufs: AtomicU32::new(f32::NEG_INFINITY.to_bits()),
+            short_term_lufs: AtomicU32::new(f32::NEG_INFINITY.to_bits()),
+            spectrum_db: std::array::from_fn(|_| AtomicU32::new(SILENCE_DB.to_bits())),
+        }
+    }
+
+    fn publish(&self, snapshot: &AudioAnalysis) {
+        self.sequence.fetch_add(1, Ordering::Relaxed);
+        fence(Ordering::Release);
+
+        self.channels
+            .store(snapshot.channels as u32, Ordering::Relaxed);
+        store_all(&self.peak, &snapshot.peak);
+        store_all(&self.rms, &snapshot.rms);
+        self.momentary_lufs
+            .store(snapshot.momentary_lufs.to_bits(), Ordering::Relaxed);
+        self.short_term_lufs
+            .store(snapshot.short_term_lufs.to_bits(), Ordering::Relaxed);
+        store_all(&self.spectrum_db, &snapshot.spectrum_db);
+
+        self.sequence.fetch_add(1, Ordering::Release);
+    }
+
+    fn load(&self) -> Option<AudioAnalysis> {
+        // The writer publishes at ~30 Hz, so a couple of retries is plenty
+        for _ in 0..4 {
+            let before = self.sequence.load(Ordering::Acquire);
+            if before == 0 {
+                return None;
+            }
+            if before & 1 == 1 {
+                std::hint::spin_loop();
+                continue;
+            }
+
+            let snapshot = AudioAnalysis {
+                sequence: before / 2,
+                channels: self.channels.load(Ordering::Relaxed) as u16,
+                peak: load_all(&self.peak),
+                rms: load_all(&self.rms),
+                momentary_lufs: f32::from_bits(self.momentary_lufs.load(Ordering::Relaxed)),
+                short_term_lufs: f32::from_bits(self.short_term_lufs.load(Ordering::Relaxed)),
+                spectrum_db: load_all(&self.spectrum_db),
+            };
+
+            fence(Ordering::Acquire);
+            if self.sequence.load(Ordering::Relaxed) == before {
+                return Some(snapshot);
+            }
+        }
+
+        None
+    }
+}
+
+fn store_all<const N: usize>(slots: &[AtomicU32; N], values: &[f32; N]) {
+    for (slot, value) in slots.iter().zip(values) {
+        slot.store(value.to_bits(), Ordering::Relaxed);
+    }
+}
+
+fn load_all<const N: usize>(slots: &[AtomicU32; N]) -> [f32; N] {
+    std::array::from_fn(|i| f32::from_bits(slots[i].load(Ordering::Relaxed)))
+}
+
+/// Lock-free handle for polling analysis results from another thread
+#[derive(Clone)]
+pub struct AnalysisReader {
+    shared: Arc<SharedAnalysis>,
+}
+
+impl AnalysisReader {
+    /// Get the most recent snapshot
+    ///
+    /// Returns `None` before the first publish, or in the unlikely case that
+    /// the writer kept updating during every read attempt.
+    pub fn latest(&self) -> Option<AudioAnalysis> {
+        self.shared.load()
+    }
+
+    /// Sequence number of the most recent snapshot (0 = none yet)
+    pub fn sequence(&self) -> u64 {
+        self.shared.sequence.load(Ordering::Acquire) / 2
+    }
+}
+
+impl std::fmt::Debug for AnalysisReader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AnalysisReader")
+            .field("sequence", &self.sequence())
+            .finish()
+    }
+}
+
+/// Level, loudness and spectrum analysis tap
+///
+/// Call [`process`](Self::process) with interleaved output samples from the
+/// audio thread. Format changes ([`set_format`](Self::set_format)) allocate
+/// and belong on the control thread.
+pub struct AnalysisTap {
+    shared: Arc<SharedAnalysis>,
+    sample_rate: u32,
+    channels: usize,
+
+    /// Frames between publishes
+    publish_interval: usize,
+    frames_since_publish: usize,
+
+    /// Level accumulators since the last publish
+    peak: [f32; MAX_CHANNELS],
+    sum_squares: [f64; MAX_CHANNELS],
+
+    /// Live EBU R128 meter (None if the format isn't supported by ebur128)
+    loudness: Option<LoudnessAnalyzer>,
+
+    /// Spectrum state
+    fft: Arc<dyn Fft<f32>>,
+    window: Vec<f32>,
+    window_gain: f32,
+    history: Vec<f32>,
+    history_pos: usize,
+    fft_buffer: Vec<Complex<f32>>,
+    fft_scratch: Vec<Complex<f32>>,
+    band_bins: [(usize, usize); SPECTRUM_BANDS],
+}
+
+impl AnalysisTap {
+    /// Create a tap for the given output format
+    pub fn new(sample_rate: u32, channels: u16) -> Self {
+        let fft = FftPlanner::new().plan_fft_forward(FFT_SIZE);
+        let scratch_len = fft.get_inplace_scratch_len();
+
+        // Hann window
+        let window: Vec<f32> = (0..FFT_SIZE)
+            .map(|i| {
+                let phase = 2.0 * std::f32::consts::PI * i as f32 / FFT_SIZE as f32;
+                0.5 - 0.5 * phase.cos()
+            })
+            .collect();
+        let window_gain = window.iter().sum::<f32>();
+
+        let mut tap = Self {
+            shared: Arc::new(SharedAnalysis::new()),
+            sample_rate: 0,
+            channels: 0,
+            publish_interval: 1,
+            frames_since_publish: 0,
+            peak: [0.0; MAX_CHANNELS],
+            sum_squares: [0.0; MAX_CHANNELS],
+            loudness: None,
+            fft,
+            window,
+            window_gain,
+            history: vec![0.0; FFT_SIZE],
+            history_pos: 0,
+            fft_buffer: vec![Complex::new(0.0, 0.0); FFT_SIZE],
+            fft_scratch: vec![Complex::new(0.0, 0.0); scratch_len],
+            band_bins: [(0, 0); SPECTRUM_BANDS],
+        };
+        tap.set_format(sample_rate, channels);
+        tap
+    }
+
+    /// Get a reader for polling results from another thread
+    pub fn reader(&self) -> AnalysisReader {
+        AnalysisReader {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+
+    /// Change the analysed format (allocates; call from the control thread)
+    pub fn set_format(&mut self, sample_rate: u32, channels: u16) {
+        let channels = (channels as usize).clamp(1, MAX_CHANNELS);
+        if sample_rate == self.sample_rate && channels == self.channels {
+            return;
+        }
+
+        self.sample_rate = sample_rate;
+        self.channels = channels;
+        self.loudness = LoudnessAnalyzer::live(sample_rate, channels as u32).ok();
+        self.set_publish_rate(DEFAULT_PUBLISH_HZ);
+        self.update_band_bins();
+        self.reset();
+    }
+
+    /// Set how often snapshots are published (clamped to 1-120 Hz)
+    pub fn set_publish_rate(&mut self, hz: u32) {
+        let hz = hz.clamp(1, 120);
+        self.publish_interval = (self.sample_rate / hz).max(1) as usize;
+    }
+
+    /// Clear accumulated levels, loudness and spectrum history
+    pub fn reset(&mut self) {
+        self.frames_since_publish = 0;
+        self.peak = [0.0; MAX_CHANNELS];
+        self.sum_squares = [0.0; MAX_CHANNELS];
+        self.history.fill(0.0);
+        self.history_pos = 0;
+        if let Some(loudness) = &mut self.loudness {
+            loudness.reset();
+        }
+    }
+
+    /// Get the analysed sample rate
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Get the analysed channel count
+    pub fn channels(&self) -> u16 {
+        self.channels as u16
+    }
+
+    /// Analyse a buffer of interleaved samples (real-time safe)
+    pub fn process(&mut self, samples: &[f32]) {
+        let channels = self.channels;
+        let mut frames = samples.chunks_exact(channels);
+
+        // Feed the loudness meter in publish-sized pieces so snapshots line up
+        let mut start = 0;
+        while start < samples.len() {
+            let remaining_frames = self.publish_interval - self.frames_since_publish;
+            let end = (start + remaining_frames * channels).min(samples.len());
+            let end = end - (end - start) % channels;
+            if end == start {
+                break;
+            }
+
+            for frame in frames.by_ref().take((end - start) / channels) {
+                let mut mono = 0.0;
+                for (ch, &sample) in frame.iter().enumerate() {
+                    let abs = sample.abs();
+                    if abs > self.peak[ch] {
+                        self.peak[ch] = abs;
+                    }
+                    self.sum_squares[ch] += (sample as f64) * (sample as f64);
+                    mono += sample;
+                }
+
+                self.history[self.history_pos] = mono / channels as f32;
+                self.history_pos = (self.history_pos + 1) % FFT_SIZE;
+            }
+
+            if let Some(loudness) = &mut self.loudness {
+                let _ = loudness.add_frames(&samples[start..end]);
+            }
+
+            self.frames_since_publish += (end - start) / channels;
+            if self.frames_since_publish >= self.publish_interval {
+                self.publish();
+            }
+
+            start = end;
+        }
+    }
+
+    /// Build a snapshot from the accumulators and hand it to readers
+    fn publish(&mut self) {
+        let frames = self.frames_since_publish.max(1) as f64;
+        let mut rms = [0.0; MAX_CHANNELS];
+        for (out, &sum) in rms.iter_mut().zip(&self.sum_squares).take(self.channels) {
+            *out = (sum / frames).sqrt() as f32;
+        }
+
+        let (momentary_lufs, short_term_lufs) = match &self.loudness {
+            Some(loudness) => (
+                loudness
+                    .momentary_loudness()
+                    .map_or(f32::NEG_INFINITY, |l| l as f32),
+                loudness
+                    .short_term_loudness()
+                    .map_or(f32::NEG_INFINITY, |l| l as f32),
+            ),
+            None => (f32::NEG_INFINITY, f32::NEG_INFINITY),
+        };
+
+        let snapshot = AudioAnalysis {
+            sequence: 0,
+            channels: self.channels as u16,
+            peak: self.peak,
+            rms,
+            momentary_lufs,
+            short_term_lufs,
+            spectrum_db: self.compute_spectrum(),
+        };
+        self.shared.publish(&snapshot);
+
+        self.frames_since_publish = 0;
+        self.peak = [0.0; MAX_CHANNELS];
+        self.sum_squares = [0.0; MAX_CHANNELS];
+    }
+
+    /// FFT the most recent FFT_SIZE mono samples and reduce to bands
+    fn compute_spectrum(&mut self) -> [f32; SPECTRUM_BANDS] {
+        // Unroll the ring oldest-first, windowed
+        for (i, (bin, &w)) in self.fft_buffer.iter_mut().zip(&self.window).enumerate() {
+            let sample = self.history[(self.history_pos + i) % FFT_SIZE];
+            *bin = Complex::new(sample * w, 0.0);
+        }
+
+        self.fft
+            .process_with_scratch(&mut self.fft_buffer, &mut self.fft_scratch);
+
+        // Single-sided amplitude, corrected for the window, so a full-scale
+        // sine reads ~0 dBFS in its band
+        let scale = 2.0 / self.window_gain;
+        let mut spectrum = [SILENCE_DB; SPECTRUM_BANDS];
+        for (out, &(lo, hi)) in spectrum.iter_mut().zip(&self.band_bins) {
+            let peak = self.fft_buffer[lo..hi]
+                .iter()
+                .map(|c| c.norm())
+                .fold(0.0_f32, f32::max);
+            *out = linear_to_db(peak * scale);
+        }
+        spectrum
+    }
+
+    /// Map each log-spaced band to a range of FFT bins
+    fn update_band_bins(&mut self) {
+        let (min, max) = spectrum_range(self.sample_rate);
+        let bin_hz = self.sample_rate.max(1) as f32 / FFT_SIZE as f32;
+        let nyquist_bin = FFT_SIZE / 2;
+
+        for (band, bins) in self.band_bins.iter_mut().enumerate() {
+            let lo = (band_edge(min, max, band) / bin_hz).floor() as usize;
+            let hi = (band_edge(min, max, band + 1) / bin_hz).ceil() as usize;
+            // Low bands can be narrower than one bin; always cover at least one
+            let lo = lo.clamp(1, nyquist_bin - 1);
+            let hi = hi.clamp(lo + 1, nyquist_bin);
+            *bins = (lo, hi);
+        }
+    }
+}
+
+fn spectrum_range(sample_rate: u32) -> (f32, f32) {
+    let max = SPECTRUM_MAX_HZ.min(sample_rate as f32 * 0.5);
+    (SPECTRUM_MIN_HZ.min(max * 0.5), max)
+}
+
+fn band_edge(min: f32, max: f32, edge: usize) -> f32 {
+    min * (max / min).powf(edge as f32 / SPECTRUM_BANDS as f32)
+}
+
+fn linear_to_db(value: f32) -> f32 {
+    if value > 0.0 {
+        (20.0 * value.log10()).max(SILENCE_DB)
+    } else {
+        SILENCE_DB
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(freq: f32, amplitude: f32, sample_rate: u32, frames: usize) -> Vec<f32> {
+        (0..frames)
+            .flat_map(|i| {
+                let s = amplitude
+                    * (2.0 * std::f32::consts::PI * freq * i as f32 / sample_rate as f32).sin();
+                [s, s]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn nothing_published_before_first_interval() {
+        let mut tap = AnalysisTap::new(48000, 2);
+        let reader = tap.reader();
+
+        tap.process(&[0.5; 64]);
+        assert!(reader.latest().is_none());
+        assert_eq!(reader.sequence(), 0);
+    }
+
+    #[test]
+    fn publishes_at_bounded_rate() {
+        let mut tap = AnalysisTap::new(48000, 2);
+        let reader = tap.reader();
+
+        // One second of audio at the default 30 Hz
+        tap.process(&sine(1000.0, 0.5, 48000, 48000));
+        assert_eq!(reader.sequence(), DEFAULT_PUBLISH_HZ as u64);
+    }
+
+    #[test]
+    fn measures_peak_and_rms() {
+        let mut tap = AnalysisTap::new(48000, 2);
+        let reader = tap.reader();
+
+        tap.process(&sine(1000.0, 0.5, 48000, 4800));
+        let levels = reader.latest().unwrap();
+
+        assert_eq!(levels.channels, 2);
+        assert!((levels.peak[0] - 0.5).abs() < 0.01);
+        // RMS of a sine is amplitude / sqrt(2)
+        assert!((levels.rms[1] - 0.5 / 2.0_f32.sqrt()).abs() < 0.01);
+        assert!((levels.peak_dbfs(0) - (-6.02)).abs() < 0.2);
+    }
+
+    #[test]
+    fn spectrum_peaks_at_tone_frequency() {
+        let mut tap = AnalysisTap::new(48000, 2);
+        let reader = tap.reader();
+
+        tap.process(&sine(1000.0, 0.5, 48000, 9600));
+        let levels = reader.latest().unwrap();
+
+        let loudest = levels
+            .spectrum_db
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(band, _)| band)
+            .unwrap();
+        let center = spectrum_band_frequencies(48000)[loudest];
+        assert!(
+            (800.0..1250.0).contains(&center),
+            "Loudest band at {} Hz",
+            center
+        );
+        // A -6 dBFS sine should read close to -6 dB in its band
+        assert!((levels.spectrum_db[loudest] - (-6.0)).abs() < 2.0);
+    }
+
+    #[test]
+    fn reports_momentary_loudness() {
+        let mut tap = AnalysisTap::new(48000, 2);
+        let reader = tap.reader();
+
+        tap.process(&sine(1000.0, 0.1, 48000, 48000));
+        let levels = reader.latest().unwrap();
+
+        // Mean square 0.005 per channel, K-weighting ~0 dB at 1 kHz:
+        // -0.691 + 10 * log10(2 * 0.005) + 0.691 = -20 LUFS
+        assert!(
+            (levels.momentary_lufs - (-20.0)).abs() < 0.5,
+            "Expected ~-20 LUFS, got {}",
+            levels.momentary_lufs
+        );
+    }
+
+    #[test]
+    fn set_format_handles_surround() {
+        let mut tap = AnalysisTap::new(48000, 2);
+        let reader = tap.reader();
+        tap.set_format(48000, 6);
+
+        let buffer: Vec<f32> = (0..4800 * 6)
+            .map(|i| if i % 6 == 2 { 0.25 } else { 0.0 })
+            .collect();
+        tap.process(&buffer);
+
+        let levels = reader.latest().unwrap();
+        assert_eq!(levels.channels, 6);
+        assert_eq!(levels.peak[2], 0.25);
+        assert_eq!(levels.peak[0], 0.0);
+    }
+}
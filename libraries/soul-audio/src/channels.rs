@@ -0,0 +1,549 @@
+//! Channel layouts and up/downmix matrices
+//!
+//! Decoders deliver audio in the file's own layout (mono, stereo, 5.1, ...).
+//! When the output device has a different channel count, a [`ChannelMatrix`]
+//! maps one layout onto the other. The default downmix follows
+//! ITU-R BS.775: center and surrounds are folded into the front pair at
+//! -3 dB and the LFE channel is dropped.
+//!
+//! Channel order follows WAVE / FLAC / SMPTE conventions:
+//!
+//! | Layout | Order                           |
+//! |--------|---------------------------------|
+//! | Mono   | C                               |
+//! | Stereo | L R                             |
+//! | Quad   | L R Ls Rs                       |
+//! | 5.1    | L R C LFE Ls Rs                 |
+//! | 7.1    | L R C LFE Lrs Rrs Ls Rs         |
+//!
+//! # Example
+//!
+//! ```
+//! use soul_audio::channels::{ChannelLayout, ChannelMatrix, DownmixSettings};
+//!
+//! let matrix = ChannelMatrix::new(
+//!     ChannelLayout::Surround51,
+//!     ChannelLayout::Stereo,
+//!     &DownmixSettings::default(),
+//! );
+//!
+//! let input = [0.5_f32, 0.5, 0.0, 0.0, 0.0, 0.0]; // One 5.1 frame
+//! let mut output = [0.0_f32; 2];
+//! assert_eq!(matrix.process(&input, &mut output), 1);
+//! ```
+
+use std::fmt;
+
+/// Highest channel count handled by the pipeline
+pub const MAX_CHANNELS: usize = 8;
+
+/// Loudspeaker position of a channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Speaker {
+    /// Front left
+    FrontLeft,
+    /// Front right
+    FrontRight,
+    /// Front center
+    Center,
+    /// Low-frequency effects
+    Lfe,
+    /// Side / surround left
+    SurroundLeft,
+    /// Side / surround right
+    SurroundRight,
+    /// Rear left (7.1 only)
+    RearLeft,
+    /// Rear right (7.1 only)
+    RearRight,
+}
+
+/// Interleaved channel layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelLayout {
+    /// One channel
+    Mono,
+    /// Left / right
+    Stereo,
+    /// Front pair plus surround pair
+    Quad,
+    /// 5.1 surround
+    Surround51,
+    /// 7.1 surround
+    Surround71,
+    /// Channels without known speaker positions
+    Discrete(u16),
+}
+
+impl ChannelLayout {
+    /// Pick the standard layout for a channel count
+    ///
+    /// Counts without a standard layout become [`ChannelLayout::Discrete`].
+    pub fn from_channel_count(channels: u16) -> Self {
+        match channels {
+            1 => Self::Mono,
+            2 => Self::Stereo,
+            4 => Self::Quad,
+            6 => Self::Surround51,
+            8 => Self::Surround71,
+            n => Self::Discrete(n),
+        }
+    }
+
+    /// Number of interleaved channels
+    pub fn channel_count(&self) -> u16 {
+        match self {
+            Self::Discrete(n) => *n,
+            _ => self.speakers().len() as u16,
+        }
+    }
+
+    /// Speaker positions in interleaved order (empty for discrete layouts)
+    pub fn speakers(&self) -> &'static [Speaker] {
+        use Speaker::{
+            Center, FrontLeft, FrontRight, Lfe, RearLeft, RearRight, SurroundLeft, SurroundRight,
+        };
+        match self {
+            Self::Mono => &[Center],
+            Self::Stereo => &[FrontLeft, FrontRight],
+            Self::Quad => &[FrontLeft, FrontRight, SurroundLeft, SurroundRight],
+            Self::Surround51 => &[
+                FrontLeft,
+                FrontRight,
+                Center,
+                Lfe,
+                SurroundLeft,
+                SurroundRight,
+            ],
+            Self::Surround71 => &[
+                FrontLeft,
+                FrontRight,
+                Center,
+                Lfe,
+                RearLeft,
+                RearRight,
+                SurroundLeft,
+                SurroundRight,
+            ],
+            Self::Discrete(_) => &[],
+        }
+    }
+
+    /// Index of a speaker in this layout
+    pub fn position_of(&self, speaker: Speaker) -> Option<usize> {
+        self.speakers().iter().position(|s| *s == speaker)
+    }
+}
+
+impl fmt::Display for ChannelLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Mono => f.write_str("mono"),
+            Self::Stereo => f.write_str("stereo"),
+            Self::Quad => f.write_str("quad"),
+            Self::Surround51 => f.write_str("5.1"),
+            Self::Surround71 => f.write_str("7.1"),
+            Self::Discrete(n) => write!(f, "{} channels", n),
+        }
+    }
+}
+
+/// Gains used when folding channels the output doesn't have
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DownmixSettings {
+    /// Gain of the center channel in each front channel
+    pub center_gain: f32,
+    /// Gain of surround/rear channels in the front channel on their side
+    pub surround_gain: f32,
+    /// Gain of the LFE channel in each front channel (0 drops it)
+    pub lfe_gain: f32,
+    /// Scale the matrix so no output channel can exceed full scale
+    pub normalize: bool,
+}
+
+impl DownmixSettings {
+    /// ITU-R BS.775 coefficients (-3 dB center/surround, no LFE)
+    pub fn itu_bs775() -> Self {
+        Self {
+            center_gain: std::f32::consts::FRAC_1_SQRT_2,
+            surround_gain: std::f32::consts::FRAC_1_SQRT_2,
+            lfe_gain: 0.0,
+            normalize: true,
+        }
+    }
+
+    /// Validate settings
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, gain) in [
+            ("Center", self.center_gain),
+            ("Surround", self.surround_gain),
+            ("LFE", self.lfe_gain),
+        ] {
+            if !(0.0..=2.0).contains(&gain) {
+                return Err(format!("{} gain must be between 0.0 and 2.0", name));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for DownmixSettings {
+    fn default() -> Self {
+        Self::itu_bs775()
+    }
+}
+
+/// Mixing matrix from one channel layout to another
+///
+/// Coefficients are stored row-major: one row per output channel, one
+/// column per input channel. [`ChannelMatrix::process`] does not allocate,
+/// so a matrix can be built up front and used on the audio thread.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelMatrix {
+    input: ChannelLayout,
+    output: ChannelLayout,
+    coefficients: Vec<f32>,
+}
+
+impl ChannelMatrix {
+    /// Build the default up/downmix matrix between two layouts
+    ///
+    /// - Channels present in both layouts are copied
+    /// - Missing center, LFE and surround channels are folded into the
+    ///   front pair using `settings`; missing rear channels go to the surrounds
+    /// - Mono sources are sent to both front channels
+    /// - Downmix to mono averages the stereo downmix
+    /// - Discrete layouts map channel `n` to channel `n`
+    pub fn new(input: ChannelLayout, output: ChannelLayout, settings: &DownmixSettings) -> Self {
+        let inputs = input.channel_count() as usize;
+        let outputs = output.channel_count() as usize;
+        let mut coefficients = vec![0.0; inputs * outputs];
+
+        let discrete = matches!(input, ChannelLayout::Discrete(_))
+            || matches!(output, ChannelLayout::Discrete(_));
+
+        if input == output || discrete {
+            for ch in 0..inputs.min(outputs) {
+                coefficients[ch * inputs + ch] = 1.0;
+            }
+        } else if output == ChannelLayout::Mono {
+            let stereo = Self::new(input, ChannelLayout::Stereo, settings);
+            for (ch, coeff) in coefficients.iter_mut().enumerate() {
+                *coeff = 0.5 * (stereo.coefficients[ch] + stereo.coefficients[inputs + ch]);
+            }
+        } else if input == ChannelLayout::Mono {
+            for speaker in [Speaker::FrontLeft, Speaker::FrontRight] {
+                if let Some(out) = output.position_of(speaker) {
+                    coefficients[out] = 1.0;
+                }
+            }
+        } else {
+            for (in_ch, speaker) in input.speakers().iter().enumerate() {
+                for (target, gain) in Self::route(*speaker, output, settings) {
+                    if let Some(out) = output.position_of(target) {
+                        coefficients[out * inputs + in_ch] += gain;
+                    }
+                }
+            }
+
+            if settings.normalize {
+                let max_row_sum = coefficients
+                    .chunks(inputs)
+                    .map(|row| row.iter().map(|c| c.abs()).sum::<f32>())
+                    .fold(0.0f32, f32::max);
+                if max_row_sum > 1.0 {
+                    for coeff in &mut coefficients {
+                        *coeff /= max_row_sum;
+                    }
+                }
+            }
+        }
+
+        Self {
+            input,
+            output,
+            coefficients,
+        }
+    }
+
+    /// Build a matrix from explicit coefficients
+    ///
+    /// # Arguments
+    /// * `coefficients` - Row-major gains, `output channels × input channels`
+    pub fn from_coefficients(
+        input: ChannelLayout,
+        output: ChannelLayout,
+        coefficients: Vec<f32>,
+    ) -> Result<Self, String> {
+        let expected = input.channel_count() as usize * output.channel_count() as usize;
+        if coefficients.len() != expected {
+            return Err(format!(
+                "Matrix from {} to {} needs {} coefficients, got {}",
+                input,
+                output,
+                expected,
+                coefficients.len()
+            ));
+        }
+        if coefficients.iter().any(|c| !c.is_finite()) {
+            return Err("Matrix coefficients must be finite".to_string());
+        }
+
+        Ok(Self {
+            input,
+            output,
+            coefficients,
+        })
+    }
+
+    /// Where a speaker missing from `output` is folded to
+    fn route(
+        speaker: Speaker,
+        output: ChannelLayout,
+        settings: &DownmixSettings,
+    ) -> Vec<(Speaker, f32)> {
+        use Speaker::{
+            Center, FrontLeft, FrontRight, Lfe, RearLeft, RearRight, SurroundLeft, SurroundRight,
+        };
+
+        if output.position_of(speaker).is_some() {
+            return vec![(speaker, 1.0)];
+        }
+
+        match speaker {
+            Center => vec![
+                (FrontLeft, settings.center_gain),
+                (FrontRight, settings.center_gain),
+            ],
+            Lfe => vec![
+                (FrontLeft, settings.lfe_gain),
+                (FrontRight, settings.lfe_gain),
+            ],
+            RearLeft if output.position_of(SurroundLeft).is_some() => vec![(SurroundLeft, 1.0)],
+            RearRight if output.position_of(SurroundRight).is_some() => {
+                vec![(SurroundRight, 1.0)]
+            }
+            SurroundLeft | RearLeft => vec![(FrontLeft, settings.surround_gain)],
+            SurroundRight | RearRight => vec![(FrontRight, settings.surround_gain)],
+            FrontLeft | FrontRight => Vec::new(),
+        }
+    }
+
+    /// Input layout
+    pub fn input(&self) -> ChannelLayout {
+        self.input
+    }
+
+    /// Output layout
+    pub fn output(&self) -> ChannelLayout {
+        self.output
+    }
+
+    /// Gain from `input_channel` to `output_channel`
+    pub fn coefficient(&self, output_channel: usize, input_channel: usize) -> f32 {
+        let inputs = self.input.channel_count() as usize;
+        self.coefficients
+            .get(output_channel * inputs + input_channel)
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// Check whether the matrix copies every channel unchanged
+    pub fn is_identity(&self) -> bool {
+        let inputs = self.input.channel_count() as usize;
+        inputs == self.output.channel_count() as usize
+            && self.coefficients.iter().enumerate().all(|(i, &c)| {
+                let expected = if i / inputs == i % inputs { 1.0 } else { 0.0 };
+                c == expected
+            })
+    }
+
+    /// Mix interleaved `input` frames into interleaved `output` frames
+    ///
+    /// Processes as many whole frames as fit in both buffers.
+    ///
+    /// # Returns
+    /// Number of frames written
+    ///
+    /// # Real-Time Safety
+    /// No allocations.
+    pub fn process(&self, input: &[f32], output: &mut [f32]) -> usize {
+        let inputs = self.input.channel_count() as usize;
+        let outputs = self.output.channel_count() as usize;
+        if inputs == 0 || outputs == 0 {
+            return 0;
+        }
+
+        let frames = (input.len() / inputs).min(output.len() / outputs);
+
+        for (in_frame, out_frame) in input
+            .chunks_exact(inputs)
+            .zip(output.chunks_exact_mut(outputs))
+            .take(frames)
+        {
+            for (out, row) in out_frame.iter_mut().zip(self.coefficients.chunks(inputs)) {
+                *out = row.iter().zip(in_frame).map(|(c, s)| c * s).sum();
+            }
+        }
+
+        frames
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mix(matrix: &ChannelMatrix, frame: &[f32]) -> Vec<f32> {
+        let mut output = vec![0.0; matrix.output().channel_count() as usize];
+        assert_eq!(matrix.process(frame, &mut output), 1);
+        output
+    }
+
+    #[test]
+    fn test_layout_from_channel_count() {
+        for layout in [
+            ChannelLayout::Mono,
+            ChannelLayout::Stereo,
+            ChannelLayout::Quad,
+            ChannelLayout::Surround51,
+            ChannelLayout::Surround71,
+        ] {
+            assert_eq!(
+                ChannelLayout::from_channel_count(layout.channel_count()),
+                layout
+            );
+        }
+        assert_eq!(
+            ChannelLayout::from_channel_count(3),
+            ChannelLayout::Discrete(3)
+        );
+        assert_eq!(ChannelLayout::Discrete(3).channel_count(), 3);
+    }
+
+    #[test]
+    fn test_identity_matrix() {
+        let matrix = ChannelMatrix::new(
+            ChannelLayout::Surround51,
+            ChannelLayout::Surround51,
+            &DownmixSettings::default(),
+        );
+        assert!(matrix.is_identity());
+
+        let frame = [0.1, 0.2, 0.3, 0.4, 0.5, 0.6];
+        assert_eq!(mix(&matrix, &frame), frame);
+    }
+
+    #[test]
+    fn test_bs775_downmix_coefficients() {
+        let settings = DownmixSettings {
+            normalize: false,
+            ..DownmixSettings::itu_bs775()
+        };
+        let matrix =
+            ChannelMatrix::new(ChannelLayout::Surround51, ChannelLayout::Stereo, &settings);
+        let g = std::f32::consts::FRAC_1_SQRT_2;
+
+        // Lo = L + 0.707 C + 0.707 Ls, LFE dropped
+        assert_eq!(matrix.coefficient(0, 0), 1.0);
+        assert_eq!(matrix.coefficient(0, 1), 0.0);
+        assert!((matrix.coefficient(0, 2) - g).abs() < 1e-6);
+        assert_eq!(matrix.coefficient(0, 3), 0.0);
+        assert!((matrix.coefficient(0, 4) - g).abs() < 1e-6);
+        assert_eq!(matrix.coefficient(0, 5), 0.0);
+
+        // Ro mirrors Lo
+        assert!((matrix.coefficient(1, 2) - g).abs() < 1e-6);
+        assert!((matrix.coefficient(1, 5) - g).abs() < 1e-6);
+        assert_eq!(matrix.coefficient(1, 4), 0.0);
+    }
+
+    #[test]
+    fn test_normalized_downmix_cannot_clip() {
+        let matrix = ChannelMatrix::new(
+            ChannelLayout::Surround71,
+            ChannelLayout::Stereo,
+            &DownmixSettings::default(),
+        );
+
+        let output = mix(&matrix, &[1.0; 8]);
+        for sample in output {
+            assert!(sample <= 1.0 + 1e-6, "Downmix clipped: {}", sample);
+        }
+    }
+
+    #[test]
+    fn test_downmix_to_mono() {
+        let matrix = ChannelMatrix::new(
+            ChannelLayout::Stereo,
+            ChannelLayout::Mono,
+            &DownmixSettings::default(),
+        );
+        assert_eq!(mix(&matrix, &[1.0, 0.0]), vec![0.5]);
+    }
+
+    #[test]
+    fn test_71_to_51_folds_rears_into_surrounds() {
+        let matrix = ChannelMatrix::new(
+            ChannelLayout::Surround71,
+            ChannelLayout::Surround51,
+            &DownmixSettings::default(),
+        );
+
+        // Rear left only
+        let output = mix(&matrix, &[0.0, 0.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.0]);
+        assert!(output[4] > 0.0, "Rear left should reach surround left");
+        assert_eq!(output[0], 0.0);
+        assert_eq!(output[5], 0.0);
+    }
+
+    #[test]
+    fn test_upmix_keeps_front_pair() {
+        let mono = ChannelMatrix::new(
+            ChannelLayout::Mono,
+            ChannelLayout::Surround51,
+            &DownmixSettings::default(),
+        );
+        assert_eq!(mix(&mono, &[0.5]), vec![0.5, 0.5, 0.0, 0.0, 0.0, 0.0]);
+
+        let stereo = ChannelMatrix::new(
+            ChannelLayout::Stereo,
+            ChannelLayout::Surround71,
+            &DownmixSettings::default(),
+        );
+        assert_eq!(
+            mix(&stereo, &[0.25, -0.25]),
+            vec![0.25, -0.25, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn test_custom_matrix() {
+        assert!(ChannelMatrix::from_coefficients(
+            ChannelLayout::Stereo,
+            ChannelLayout::Mono,
+            vec![1.0],
+        )
+        .is_err());
+
+        let swap = ChannelMatrix::from_coefficients(
+            ChannelLayout::Stereo,
+            ChannelLayout::Stereo,
+            vec![0.0, 1.0, 1.0, 0.0],
+        )
+        .unwrap();
+        assert!(!swap.is_identity());
+        assert_eq!(mix(&swap, &[1.0, 2.0]), vec![2.0, 1.0]);
+    }
+
+    #[test]
+    fn test_settings_validation() {
+        assert!(DownmixSettings::default().validate().is_ok());
+
+        let invalid = DownmixSettings {
+            lfe_gain: -1.0,
+            ..DownmixSettings::default()
+        };
+        assert!(invalid.validate().is_err());
+    }
+}
@@ -0,0 +1,49 @@
+//! Codec registry
+//!
+//! Symphonia's default registry plus the decoders this crate adds on top of
+//! it, currently Opus (with the `opus` feature). Anything that creates a
+//! decoder from probed codec parameters should use [`registry`] rather than
+//! `symphonia::default::get_codecs()`, or those formats won't play.
+
+pub mod opus;
+
+use std::sync::OnceLock;
+use symphonia::core::codecs::CodecRegistry;
+
+/// Whether this build can decode Opus (the `opus` feature)
+pub const OPUS_ENABLED: bool = cfg!(feature = "opus");
+
+/// The codec registry with every decoder enabled in this build
+pub fn registry() -> &'static CodecRegistry {
+    static REGISTRY: OnceLock<CodecRegistry> = OnceLock::new();
+
+    REGISTRY.get_or_init(|| {
+        let mut registry = CodecRegistry::new();
+        symphonia::default::register_enabled_codecs(&mut registry);
+
+        #[cfg(feature = "opus")]
+        registry.register_all::<opus::OpusDecoder>();
+
+        registry
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use symphonia::core::codecs::{CODEC_TYPE_FLAC, CODEC_TYPE_MP3, CODEC_TYPE_OPUS};
+
+    #[test]
+    fn test_registry_includes_default_codecs() {
+        assert!(registry().get_codec(CODEC_TYPE_MP3).is_some());
+        assert!(registry().get_codec(CODEC_TYPE_FLAC).is_some());
+    }
+
+    #[test]
+    fn test_registry_includes_opus_when_enabled() {
+        assert_eq!(
+            registry().get_codec(CODEC_TYPE_OPUS).is_some(),
+            OPUS_ENABLED
+        );
+    }
+}
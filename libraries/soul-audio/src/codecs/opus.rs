@@ -0,0 +1,289 @@
+//! Opus decoding
+//!
+//! Symphonia demuxes Ogg Opus (including the pre-skip, which it reports as
+//! the encoder delay) but has no Opus decoder. [`OpusDecoder`] decodes the
+//! packets with libopus, always at 48 kHz as RFC 7845 recommends, and
+//! applies the output gain from the identification header.
+//!
+//! Channel mapping family 0 (mono/stereo) and single-stream family 1 files
+//! are supported; multistream surround files are rejected as unsupported.
+
+/// Fields of the Opus identification header ("OpusHead", RFC 7845 §5.1)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpusHead {
+    /// Output channel count
+    pub channels: u8,
+    /// Samples (at 48 kHz) to discard from the start of the decoded stream
+    pub pre_skip: u16,
+    /// Sample rate of the original input (informational only)
+    pub input_sample_rate: u32,
+    /// Gain to apply to the decoded output, in Q7.8 dB
+    pub output_gain: i16,
+    /// Channel mapping family
+    pub mapping_family: u8,
+    /// Number of Opus streams in each packet
+    pub stream_count: u8,
+    /// Number of those streams that are coupled (stereo)
+    pub coupled_count: u8,
+}
+
+impl OpusHead {
+    /// Parse the identification header
+    ///
+    /// Symphonia's Ogg reader passes the whole header packet as the codec's
+    /// extra data. Returns `None` if it isn't a valid OpusHead.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() < 19 || &data[..8] != b"OpusHead" {
+            return None;
+        }
+
+        // Only a new major version (upper nibble) is incompatible
+        if data[8] >> 4 != 0 {
+            return None;
+        }
+
+        let channels = data[9];
+        let mapping_family = data[18];
+        let (stream_count, coupled_count) = if mapping_family == 0 {
+            if !(1..=2).contains(&channels) {
+                return None;
+            }
+            (1, channels - 1)
+        } else {
+            // Family 1+ adds a channel mapping table
+            if channels == 0 || data.len() < 21 + channels as usize {
+                return None;
+            }
+            (data[19], data[20])
+        };
+
+        Some(Self {
+            channels,
+            pre_skip: u16::from_le_bytes([data[10], data[11]]),
+            input_sample_rate: u32::from_le_bytes([data[12], data[13], data[14], data[15]]),
+            output_gain: i16::from_le_bytes([data[16], data[17]]),
+            mapping_family,
+            stream_count,
+            coupled_count,
+        })
+    }
+
+    /// Output gain in dB
+    pub fn output_gain_db(&self) -> f32 {
+        f32::from(self.output_gain) / 256.0
+    }
+
+    /// Whether every packet holds a single mono or stereo stream
+    pub fn is_single_stream(&self) -> bool {
+        self.stream_count == 1 && self.channels <= 2
+    }
+}
+
+#[cfg(feature = "opus")]
+pub use self::decoder::OpusDecoder;
+
+#[cfg(feature = "opus")]
+mod decoder {
+    use super::OpusHead;
+    use audiopus::coder::{Decoder as LibopusDecoder, GenericCtl};
+    use audiopus::{packet, Channels as OpusChannels, MutSignals, SampleRate};
+    use std::sync::{Mutex, PoisonError};
+    use symphonia::core::audio::{
+        AsAudioBufferRef, AudioBuffer, AudioBufferRef, Channels, Signal, SignalSpec,
+    };
+    use symphonia::core::codecs::{
+        CodecDescriptor, CodecParameters, Decoder, DecoderOptions, FinalizeResult, CODEC_TYPE_OPUS,
+    };
+    use symphonia::core::errors::{Error, Result};
+    use symphonia::core::formats::Packet;
+
+    /// Sample rate Opus is decoded at
+    const OPUS_SAMPLE_RATE: u32 = 48000;
+
+    /// Longest Opus packet: 120 ms at 48 kHz
+    const MAX_PACKET_FRAMES: usize = 5760;
+
+    /// Symphonia decoder for Opus packets, backed by libopus
+    pub struct OpusDecoder {
+        params: CodecParameters,
+        /// libopus decoders are `Send` but not `Sync`; only ever accessed
+        /// through `&mut self`, so the lock is never contended
+        decoder: Mutex<LibopusDecoder>,
+        channels: usize,
+        /// Interleaved output of libopus
+        interleaved: Vec<f32>,
+        buf: AudioBuffer<f32>,
+    }
+
+    impl OpusDecoder {
+        fn decode_inner(&mut self, packet: &Packet) -> Result<()> {
+            let input = packet::Packet::try_from(packet.buf())
+                .map_err(|_| Error::DecodeError("opus: empty packet"))?;
+            let output = MutSignals::try_from(&mut self.interleaved[..])
+                .map_err(|_| Error::DecodeError("opus: output buffer too large"))?;
+
+            let frames = self
+                .decoder
+                .get_mut()
+                .unwrap_or_else(PoisonError::into_inner)
+                .decode_float(Some(input), output, false)
+                .map_err(|_| Error::DecodeError("opus: invalid packet"))?;
+
+            self.buf.clear();
+            self.buf.render_reserved(Some(frames));
+            for ch in 0..self.channels {
+                let interleaved = self.interleaved.chunks_exact(self.channels);
+                for (sample, frame) in self.buf.chan_mut(ch).iter_mut().zip(interleaved) {
+                    *sample = frame[ch];
+                }
+            }
+
+            Ok(())
+        }
+    }
+
+    impl Decoder for OpusDecoder {
+        fn try_new(params: &CodecParameters, _options: &DecoderOptions) -> Result<Self> {
+            let head = params
+                .extra_data
+                .as_deref()
+                .and_then(OpusHead::parse)
+                .ok_or(Error::DecodeError(
+                    "opus: missing or invalid identification header",
+                ))?;
+
+            if !head.is_single_stream() {
+                return Err(Error::Unsupported(
+                    "opus: multistream files are not supported",
+                ));
+            }
+
+            let (opus_channels, spec_channels) = if head.channels == 1 {
+                (OpusChannels::Mono, Channels::FRONT_LEFT)
+            } else {
+                (
+                    OpusChannels::Stereo,
+                    Channels::FRONT_LEFT | Channels::FRONT_RIGHT,
+                )
+            };
+
+            let decoder = LibopusDecoder::new(SampleRate::Hz48000, opus_channels)
+                .map_err(|_| Error::Unsupported("opus: failed to create decoder"))?;
+
+            // The header gain is part of the stream: R128 gains are relative
+            // to the output with it applied
+            if head.output_gain != 0 {
+                decoder
+                    .set_gain(i32::from(head.output_gain))
+                    .map_err(|_| Error::DecodeError("opus: invalid output gain"))?;
+            }
+
+            let channels = head.channels as usize;
+            let spec = SignalSpec::new(OPUS_SAMPLE_RATE, spec_channels);
+
+            Ok(Self {
+                params: params.clone(),
+                decoder: Mutex::new(decoder),
+                channels,
+                interleaved: vec![0.0; MAX_PACKET_FRAMES * channels],
+                buf: AudioBuffer::new(MAX_PACKET_FRAMES as u64, spec),
+            })
+        }
+
+        fn supported_codecs() -> &'static [CodecDescriptor] {
+            &[CodecDescriptor {
+                codec: CODEC_TYPE_OPUS,
+                short_name: "opus",
+                long_name: "Opus (libopus)",
+                inst_func: |params, options| Ok(Box::new(OpusDecoder::try_new(params, options)?)),
+            }]
+        }
+
+        fn reset(&mut self) {
+            // The output gain survives a reset
+            let _ = self
+                .decoder
+                .get_mut()
+                .unwrap_or_else(PoisonError::into_inner)
+                .reset_state();
+        }
+
+        fn codec_params(&self) -> &CodecParameters {
+            &self.params
+        }
+
+        fn decode(&mut self, packet: &Packet) -> Result<AudioBufferRef<'_>> {
+            if let Err(e) = self.decode_inner(packet) {
+                self.buf.clear();
+                Err(e)
+            } else {
+                Ok(self.buf.as_audio_buffer_ref())
+            }
+        }
+
+        fn finalize(&mut self) -> FinalizeResult {
+            FinalizeResult::default()
+        }
+
+        fn last_decoded(&self) -> AudioBufferRef<'_> {
+            self.buf.as_audio_buffer_ref()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn head(channels: u8, pre_skip: u16, output_gain: i16, mapping_family: u8) -> Vec<u8> {
+        let mut data = b"OpusHead".to_vec();
+        data.push(1);
+        data.push(channels);
+        data.extend_from_slice(&pre_skip.to_le_bytes());
+        data.extend_from_slice(&44100u32.to_le_bytes());
+        data.extend_from_slice(&output_gain.to_le_bytes());
+        data.push(mapping_family);
+        data
+    }
+
+    #[test]
+    fn test_parse_stereo_head() {
+        let parsed = OpusHead::parse(&head(2, 312, -1536, 0)).unwrap();
+
+        assert_eq!(parsed.channels, 2);
+        assert_eq!(parsed.pre_skip, 312);
+        assert_eq!(parsed.input_sample_rate, 44100);
+        assert_eq!(parsed.output_gain, -1536);
+        assert_eq!(parsed.output_gain_db(), -6.0);
+        assert_eq!((parsed.stream_count, parsed.coupled_count), (1, 1));
+        assert!(parsed.is_single_stream());
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_heads() {
+        assert!(OpusHead::parse(b"OpusTags").is_none());
+        assert!(OpusHead::parse(&head(2, 312, 0, 0)[..18]).is_none());
+
+        // Family 0 is mono or stereo only
+        assert!(OpusHead::parse(&head(0, 312, 0, 0)).is_none());
+        assert!(OpusHead::parse(&head(6, 312, 0, 0)).is_none());
+
+        // Incompatible major version
+        let mut data = head(2, 312, 0, 0);
+        data[8] = 0x10;
+        assert!(OpusHead::parse(&data).is_none());
+
+        // Family 1 without its mapping table
+        assert!(OpusHead::parse(&head(6, 312, 0, 1)).is_none());
+    }
+
+    #[test]
+    fn test_parse_surround_head() {
+        let mut data = head(6, 312, 0, 1);
+        data.extend_from_slice(&[4, 2, 0, 4, 1, 2, 3, 5]);
+
+        let parsed = OpusHead::parse(&data).unwrap();
+        assert_eq!((parsed.stream_count, parsed.coupled_count), (4, 2));
+        assert!(!parsed.is_single_stream());
+    }
+}
@@ -1,4 +1,7 @@
 /// Audio decoder implementation using Symphonia
+use crate::codecs;
+use crate::dsd::{self, DsdFile, DsdToPcm};
+use crate::encoder_delay::{self, DelayTrimmer, EncoderDelay};
 use crate::error::{AudioError, Result};
 use crate::metadata::{self, AudioMetadata as FileMetadata};
 use soul_core::{
@@ -9,14 +12,19 @@ use std::time::Duration;
 use symphonia::core::audio::{AudioBufferRef, Signal};
 use symphonia::core::codecs::{Decoder, DecoderOptions};
 use symphonia::core::formats::{FormatOptions, FormatReader, SeekMode, SeekTo};
-use symphonia::core::io::MediaSourceStream;
+use symphonia::core::io::{MediaSource, MediaSourceStream};
 use symphonia::core::meta::MetadataOptions;
 use symphonia::core::probe::Hint;
-use symphonia::core::units::{Time, TimeBase};
+use symphonia::core::units::TimeBase;
+
+/// Seek attempts before accepting a landing point past the requested one
+const MAX_SEEK_ATTEMPTS: usize = 4;
 
 /// Audio decoder using Symphonia
 ///
-/// Supports: MP3, FLAC, OGG, WAV, AAC, OPUS
+/// Supports: MP3, FLAC, OGG, WAV, AAC, Opus (with the `opus` feature), and
+/// DSD files (DSF, DFF), which are converted to PCM (88.2/96 kHz) with
+/// [`DsdToPcm`]
 ///
 /// This decoder supports two modes:
 /// 1. **Full decode**: Use `decode()` to load entire file into memory
@@ -24,6 +32,8 @@ use symphonia::core::units::{Time, TimeBase};
 pub struct SymphoniaDecoder {
     /// Streaming state (when a file is open for streaming)
     stream_state: Option<StreamState>,
+    /// Streaming state when the open file is a DSD file
+    dsd_state: Option<DsdStreamState>,
 }
 
 /// Internal state for streaming decode
@@ -44,12 +54,119 @@ struct StreamState {
     position_samples: u64,
     /// Time base for position calculation
     time_base: Option<TimeBase>,
+    /// Decoded stereo samples not yet returned by `decode_chunk`
+    pending: Vec<f32>,
+    /// Trims encoder delay and padding (None if the file declares none)
+    trimmer: Option<DelayTrimmer>,
+    /// Decoded frames before this file position are dropped (between the
+    /// sync point a seek lands on and its target)
+    discard_until: u64,
+    /// Frames decoded before a seek target to warm up the decoder
+    preroll: u64,
+}
+
+/// Internal state for streaming decode of DSD files
+struct DsdStreamState {
+    /// DSF/DSDIFF reader
+    file: DsdFile,
+    /// DSD to PCM converter
+    decimator: DsdToPcm,
+    /// Scratch buffers for packed DSD bytes (one per channel)
+    dsd_buffers: Vec<Vec<u8>>,
+    /// Converted stereo samples not yet returned
+    pending: Vec<f32>,
+    /// Whether the reader is exhausted and the filter flushed
+    finished: bool,
+    /// Current position in PCM frames
+    position_samples: u64,
+}
+
+impl DsdStreamState {
+    /// Open a DSD file for conversion to PCM
+    fn open(path: &Path) -> Result<Self> {
+        let file = DsdFile::open(path)?;
+        let info = *file.info();
+        let decimator = DsdToPcm::for_dsd_rate(info.sample_rate, info.channels)?;
+
+        Ok(Self {
+            file,
+            decimator,
+            dsd_buffers: vec![Vec::new(); info.channels],
+            pending: Vec::new(),
+            finished: false,
+            position_samples: 0,
+        })
+    }
+
+    /// Decode up to `max_frames` stereo frames; `None` at end of stream
+    fn decode_chunk(&mut self, max_frames: usize) -> Result<Option<Vec<f32>>> {
+        let target_samples = max_frames * 2;
+        let channels = self.file.info().channels;
+        let bytes_per_frame = self.decimator.bytes_per_frame();
+
+        while self.pending.len() < target_samples && !self.finished {
+            for buf in &mut self.dsd_buffers {
+                buf.clear();
+            }
+            let read = self
+                .file
+                .read(&mut self.dsd_buffers, max_frames * bytes_per_frame)?;
+
+            let mut pcm = Vec::new();
+            if read == 0 {
+                self.decimator.flush(&mut pcm);
+                self.finished = true;
+            } else {
+                let input: Vec<&[u8]> = self.dsd_buffers.iter().map(|b| b.as_slice()).collect();
+                self.decimator.process(&input, &mut pcm);
+            }
+            self.pending.extend(dsd::downmix_to_stereo(&pcm, channels));
+        }
+
+        if self.pending.is_empty() {
+            return Ok(None);
+        }
+
+        let take = target_samples.min(self.pending.len());
+        let samples: Vec<f32> = self.pending.drain(..take).collect();
+        self.position_samples += (samples.len() / 2) as u64;
+        Ok(Some(samples))
+    }
+
+    /// Seek to a position, returning the actual (frame-aligned) position
+    fn seek(&mut self, position: Duration) -> Result<Duration> {
+        let info = *self.file.info();
+        let pcm_rate = self.decimator.pcm_rate();
+        let bytes_per_frame = self.decimator.bytes_per_frame() as u64;
+
+        let total_frames = info.bytes_per_channel() / bytes_per_frame;
+        let frame = ((position.as_secs_f64() * pcm_rate as f64) as u64).min(total_frames);
+
+        self.file.seek(frame * bytes_per_frame)?;
+        self.decimator.reset();
+        self.pending.clear();
+        self.finished = false;
+        self.position_samples = frame;
+
+        Ok(Duration::from_secs_f64(frame as f64 / pcm_rate as f64))
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.decimator.pcm_rate()
+    }
+
+    fn duration(&self) -> Duration {
+        self.file.info().duration()
+    }
 }
 
 impl SymphoniaDecoder {
     /// Create a new decoder
     pub fn new() -> Self {
-        Self { stream_state: None }
+        Self {
+            stream_state: None,
+            dsd_state: None,
+        }
     }
 
     /// Open a file and create stream state
@@ -63,17 +180,28 @@ impl SymphoniaDecoder {
         let file = std::fs::File::open(path)
             .map_err(|e| AudioError::Io(e))?;
 
+        Self::create_stream_state_from_source(
+            Box::new(file),
+            path.extension().and_then(|e| e.to_str()),
+        )
+    }
+
+    /// Probe an arbitrary media source and create stream state
+    fn create_stream_state_from_source(
+        source: Box<dyn MediaSource>,
+        extension: Option<&str>,
+    ) -> Result<StreamState> {
         // Create media source
-        let mss = MediaSourceStream::new(Box::new(file), Default::default());
+        let mss = MediaSourceStream::new(source, Default::default());
 
         // Create a hint to help the format registry guess the format
         let mut hint = Hint::new();
-        if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+        if let Some(ext) = extension {
             hint.with_extension(ext);
         }
 
         // Probe the media source
-        let probed = symphonia::default::get_probe()
+        let mut probed = symphonia::default::get_probe()
             .format(
                 &hint,
                 mss,
@@ -82,6 +210,7 @@ impl SymphoniaDecoder {
             )
             .map_err(|e| AudioError::Symphonia(format!("Failed to probe file: {}", e)))?;
 
+        let delay = EncoderDelay::from_probe(&mut probed);
         let format = probed.format;
 
         // Find the default track
@@ -94,16 +223,21 @@ impl SymphoniaDecoder {
         let channels = track.codec_params.channels.map(|c| c.count() as u16).unwrap_or(2);
         let track_id = track.id;
         let time_base = track.codec_params.time_base;
+        let trimmer = Self::delay_trimmer(delay, track.codec_params.n_frames);
+        let preroll = encoder_delay::seek_preroll(&track.codec_params);
 
-        // Calculate duration if possible
+        // Calculate duration if possible (without encoder delay and padding)
         let duration = if let Some(n_frames) = track.codec_params.n_frames {
-            Some(Duration::from_secs_f64(n_frames as f64 / sample_rate as f64))
+            let frames = trimmer
+                .as_ref()
+                .map_or(n_frames, DelayTrimmer::valid_samples);
+            Some(Duration::from_secs_f64(frames as f64 / sample_rate as f64))
         } else {
             None
         };
 
         // Create decoder
-        let decoder = symphonia::default::get_codecs()
+        let decoder = codecs::registry()
             .make(&track.codec_params, &DecoderOptions::default())
             .map_err(|e| AudioError::Symphonia(format!("Failed to create decoder: {}", e)))?;
 
@@ -116,9 +250,73 @@ impl SymphoniaDecoder {
             duration,
             position_samples: 0,
             time_base,
+            pending: Vec::new(),
+            trimmer,
+            discard_until: 0,
+            preroll,
         })
     }
 
+    /// Create a trimmer for the file's encoder delay, if it declares any
+    ///
+    /// `n_frames` is the decoded length including the padding; without it
+    /// only the start is trimmed.
+    fn delay_trimmer(delay: EncoderDelay, n_frames: Option<u64>) -> Option<DelayTrimmer> {
+        delay
+            .has_padding()
+            .then(|| DelayTrimmer::new(delay, n_frames.unwrap_or(u64::MAX)))
+    }
+
+    /// Drop encoder delay, padding and seek pre-roll from a decoded packet
+    ///
+    /// `samples` are the packet's interleaved stereo samples, starting at
+    /// `packet_ts` on the file's timeline. Frames before `discard_until`
+    /// (a file position) are dropped as well.
+    fn trim_packet(
+        trimmer: Option<&mut DelayTrimmer>,
+        packet_ts: u64,
+        time_base: Option<TimeBase>,
+        sample_rate: u32,
+        discard_until: u64,
+        samples: &mut Vec<f32>,
+    ) {
+        let packet_start = Self::timestamp_to_frame(packet_ts, time_base, sample_rate);
+        let frames = samples.len() / 2;
+        let keep = match trimmer {
+            Some(trimmer) => {
+                trimmer.set_raw_position(packet_start);
+                trimmer.trim(frames)
+            }
+            None => 0..frames,
+        };
+        let discard = discard_until.saturating_sub(packet_start) as usize;
+        samples.truncate(keep.end * 2);
+        samples.drain(..keep.start.max(discard).min(keep.end) * 2);
+    }
+
+    /// Convert a frame position to a timestamp, rounding down
+    fn frame_to_timestamp(frame: u64, time_base: Option<TimeBase>, sample_rate: u32) -> u64 {
+        match time_base {
+            Some(tb) => {
+                (u128::from(frame) * u128::from(tb.denom)
+                    / (u128::from(tb.numer) * u128::from(sample_rate))) as u64
+            }
+            None => frame,
+        }
+    }
+
+    /// Convert a packet timestamp to a frame position
+    fn timestamp_to_frame(ts: u64, time_base: Option<TimeBase>, sample_rate: u32) -> u64 {
+        match time_base {
+            Some(tb) => {
+                (u128::from(ts) * u128::from(tb.numer) * u128::from(sample_rate)
+                    / u128::from(tb.denom)) as u64
+            }
+            // Fallback: assume sample-based timestamp
+            None => ts,
+        }
+    }
+
     /// Convert Symphonia audio buffer to our `AudioBuffer` format
     ///
     /// Always outputs interleaved stereo f32 samples in the range [-1.0, 1.0].
@@ -346,11 +544,43 @@ impl SymphoniaDecoder {
     /// Close any open streaming session
     pub fn close(&mut self) {
         self.stream_state = None;
+        self.dsd_state = None;
     }
 
     /// Check if a file is currently open for streaming
     pub fn is_open(&self) -> bool {
-        self.stream_state.is_some()
+        self.stream_state.is_some() || self.dsd_state.is_some()
+    }
+
+    /// Open an arbitrary media source for streaming decode
+    ///
+    /// Works like [`AudioDecoderTrait::open`] but reads from any Symphonia
+    /// `MediaSource` (e.g. an HTTP body) instead of a local file. After this
+    /// call, `decode_chunk()`, `seek()` and `position()` behave exactly as for
+    /// files. Seeking requires the source to report `is_seekable()`.
+    ///
+    /// # Arguments
+    /// * `source` - Media source to decode
+    /// * `extension` - Optional file extension used as a probe hint (e.g. "flac")
+    pub fn open_source(
+        &mut self,
+        source: Box<dyn MediaSource>,
+        extension: Option<&str>,
+    ) -> Result<AudioMetadata> {
+        // Close any existing stream
+        self.close();
+
+        let state = Self::create_stream_state_from_source(source, extension)?;
+
+        let metadata = AudioMetadata {
+            sample_rate: state.sample_rate,
+            channels: state.channels,
+            duration: state.duration,
+            bits_per_sample: None,
+        };
+
+        self.stream_state = Some(state);
+        Ok(metadata)
     }
 
     /// Extract metadata from an audio file
@@ -384,6 +614,17 @@ impl AudioDecoderTrait for SymphoniaDecoder {
             return Err(AudioError::FileNotFound(path.display().to_string()).into());
         }
 
+        // DSD files are not handled by Symphonia
+        if DsdFile::is_dsd_path(path) {
+            let mut state = DsdStreamState::open(path)?;
+            let mut all_samples = Vec::new();
+            while let Some(chunk) = state.decode_chunk(16384)? {
+                all_samples.extend_from_slice(&chunk);
+            }
+            let format = AudioFormat::new(SampleRate::new(state.sample_rate()), 2, 32);
+            return Ok(AudioBuffer::new(all_samples, format));
+        }
+
         // Open the file
         let file =
             std::fs::File::open(path).map_err(|e| soul_core::SoulError::audio(e.to_string()))?;
@@ -398,7 +639,7 @@ impl AudioDecoderTrait for SymphoniaDecoder {
         }
 
         // Probe the media source
-        let probed = symphonia::default::get_probe()
+        let mut probed = symphonia::default::get_probe()
             .format(
                 &hint,
                 mss,
@@ -407,6 +648,7 @@ impl AudioDecoderTrait for SymphoniaDecoder {
             )
             .map_err(|e| soul_core::SoulError::audio(format!("Failed to probe file: {}", e)))?;
 
+        let delay = EncoderDelay::from_probe(&mut probed);
         let mut format = probed.format;
 
         // Find the default track
@@ -417,9 +659,11 @@ impl AudioDecoderTrait for SymphoniaDecoder {
         // Get sample rate and track ID before entering loop
         let sample_rate = track.codec_params.sample_rate.unwrap_or(44100);
         let track_id = track.id;
+        let time_base = track.codec_params.time_base;
+        let mut trimmer = Self::delay_trimmer(delay, track.codec_params.n_frames);
 
         // Create decoder
-        let mut decoder = symphonia::default::get_codecs()
+        let mut decoder = codecs::registry()
             .make(&track.codec_params, &DecoderOptions::default())
             .map_err(|e| soul_core::SoulError::audio(format!("Failed to create decoder: {}", e)))?;
 
@@ -454,7 +698,15 @@ impl AudioDecoderTrait for SymphoniaDecoder {
                 .map_err(|e| soul_core::SoulError::audio(format!("Decode error: {}", e)))?;
 
             // Convert and append to buffer (always outputs stereo)
-            let buffer = Self::convert_buffer(decoded, sample_rate)?;
+            let mut buffer = Self::convert_buffer(decoded, sample_rate)?;
+            Self::trim_packet(
+                trimmer.as_mut(),
+                packet.ts(),
+                time_base,
+                sample_rate,
+                0,
+                &mut buffer.samples,
+            );
             all_samples.extend_from_slice(&buffer.samples);
         }
 
@@ -466,10 +718,12 @@ impl AudioDecoderTrait for SymphoniaDecoder {
 
     fn supports_format(&self, path: &Path) -> bool {
         if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-            matches!(
-                ext.to_lowercase().as_str(),
-                "mp3" | "flac" | "ogg" | "opus" | "wav" | "m4a" | "aac"
-            )
+            match ext.to_lowercase().as_str() {
+                "mp3" | "flac" | "ogg" | "wav" | "m4a" | "aac" | "dsf" | "dff" => true,
+                // Opus streams need the libopus decoder
+                "opus" => cfg!(feature = "opus"),
+                _ => false,
+            }
         } else {
             false
         }
@@ -477,7 +731,19 @@ impl AudioDecoderTrait for SymphoniaDecoder {
 
     fn open(&mut self, path: &Path) -> soul_core::Result<AudioMetadata> {
         // Close any existing stream
-        self.stream_state = None;
+        self.close();
+
+        if DsdFile::is_dsd_path(path) {
+            let state = DsdStreamState::open(path)?;
+            let metadata = AudioMetadata {
+                sample_rate: state.sample_rate(),
+                channels: state.file.info().channels as u16,
+                duration: Some(state.duration()),
+                bits_per_sample: None,
+            };
+            self.dsd_state = Some(state);
+            return Ok(metadata);
+        }
 
         // Create new stream state
         let state = Self::create_stream_state(path)?;
@@ -494,16 +760,24 @@ impl AudioDecoderTrait for SymphoniaDecoder {
     }
 
     fn decode_chunk(&mut self, max_frames: usize) -> soul_core::Result<Option<AudioBuffer>> {
+        if let Some(ref mut dsd_state) = self.dsd_state {
+            let format = AudioFormat::new(SampleRate::new(dsd_state.sample_rate()), 2, 32);
+            return Ok(dsd_state
+                .decode_chunk(max_frames)?
+                .map(|samples| AudioBuffer::new(samples, format)));
+        }
+
         let state = self
             .stream_state
             .as_mut()
             .ok_or_else(|| AudioError::NoFileOpen)?;
 
-        // Try to decode packets until we have enough frames or reach EOF
-        let mut all_samples = Vec::new();
+        // Start from samples left over by the previous call, then decode
+        // packets until we have enough frames or reach EOF
+        let mut all_samples = std::mem::take(&mut state.pending);
         let target_samples = max_frames * 2; // Stereo output
 
-        loop {
+        while all_samples.len() < target_samples {
             // Get the next packet
             let packet = match state.format.next_packet() {
                 Ok(packet) => packet,
@@ -541,34 +815,41 @@ impl AudioDecoderTrait for SymphoniaDecoder {
                 }
             };
 
-            // Update position based on decoded frames
-            let frames_decoded = decoded.frames() as u64;
-            state.position_samples += frames_decoded;
-
-            // Convert to stereo f32
-            let buffer = Self::convert_buffer(decoded, state.sample_rate)?;
+            // Convert to stereo f32, without encoder delay, padding and
+            // seek pre-roll
+            let mut buffer = Self::convert_buffer(decoded, state.sample_rate)?;
+            Self::trim_packet(
+                state.trimmer.as_mut(),
+                packet.ts(),
+                state.time_base,
+                state.sample_rate,
+                state.discard_until,
+                &mut buffer.samples,
+            );
             all_samples.extend_from_slice(&buffer.samples);
-
-            // Check if we have enough samples
-            if all_samples.len() >= target_samples {
-                break;
-            }
         }
 
         if all_samples.is_empty() {
             return Ok(None);
         }
 
-        // Truncate to max_frames if we decoded more
+        // Keep anything beyond max_frames for the next call
         if all_samples.len() > target_samples {
-            all_samples.truncate(target_samples);
+            state.pending = all_samples.split_off(target_samples);
         }
 
+        // Position tracks frames handed out, not frames decoded
+        state.position_samples += (all_samples.len() / 2) as u64;
+
         let format = AudioFormat::new(SampleRate::new(state.sample_rate), 2, 32);
         Ok(Some(AudioBuffer::new(all_samples, format)))
     }
 
     fn seek(&mut self, position: Duration) -> soul_core::Result<Duration> {
+        if let Some(ref mut dsd_state) = self.dsd_state {
+            return Ok(dsd_state.seek(position)?);
+        }
+
         let state = self
             .stream_state
             .as_mut()
@@ -585,53 +866,76 @@ impl AudioDecoderTrait for SymphoniaDecoder {
             position
         };
 
-        // Convert duration to Time for Symphonia
-        let secs = clamped_position.as_secs();
-        let frac = (clamped_position.as_nanos() % 1_000_000_000) as f64 / 1_000_000_000.0;
-
-        let time = Time::new(secs, frac);
-
-        // Perform seek using accurate mode
-        let seek_result = state.format.seek(
-            SeekMode::Accurate,
-            SeekTo::Time {
-                time,
-                track_id: Some(state.track_id),
-            },
-        );
-
-        match seek_result {
-            Ok(seeked_to) => {
-                // Reset decoder state after seek
-                state.decoder.reset();
+        // Target frame on the (trimmed) timeline and in the file
+        let target_frame =
+            (clamped_position.as_secs_f64() * state.sample_rate as f64).round() as u64;
+        let start_padding = state
+            .trimmer
+            .as_ref()
+            .map_or(0, |trimmer| u64::from(trimmer.delay().start_padding));
+        let file_frame = target_frame + start_padding;
+
+        // Land on a sync point early enough for the decoder to warm up,
+        // then discard everything decoded before the target
+        let mut seek_frame = file_frame.saturating_sub(state.preroll);
+        let mut attempts = 0;
+        let landed = loop {
+            let ts = Self::frame_to_timestamp(seek_frame, state.time_base, state.sample_rate);
+            let seeked = state
+                .format
+                .seek(
+                    SeekMode::Accurate,
+                    SeekTo::TimeStamp {
+                        ts,
+                        track_id: state.track_id,
+                    },
+                )
+                .map_err(|e| match e {
+                    symphonia::core::errors::Error::SeekError(kind) => {
+                        AudioError::SeekError(format!("Seek failed: {:?}", kind))
+                    }
+                    e => AudioError::SeekError(format!("Seek error: {}", e)),
+                })?;
+            let landed =
+                Self::timestamp_to_frame(seeked.actual_ts, state.time_base, state.sample_rate);
+
+            // Demuxers that estimate byte offsets (e.g. VBR MP3 without a
+            // seek table) can overshoot: step back by the overshoot and retry
+            attempts += 1;
+            if landed <= seek_frame || seek_frame == 0 || attempts == MAX_SEEK_ATTEMPTS {
+                break landed;
+            }
+            seek_frame = seek_frame.saturating_sub(2 * (landed - seek_frame));
+        };
 
-                // Calculate actual position from timestamp
-                let actual_position = if let Some(tb) = state.time_base {
-                    let ts = seeked_to.actual_ts;
-                    Duration::from_secs_f64(ts as f64 * tb.numer as f64 / tb.denom as f64)
-                } else {
-                    // Fallback: assume sample-based timestamp
-                    Duration::from_secs_f64(seeked_to.actual_ts as f64 / state.sample_rate as f64)
-                };
+        // Reset decoder state after seek
+        state.decoder.reset();
+        state.pending.clear();
 
-                // Update position tracking
-                state.position_samples =
-                    (actual_position.as_secs_f64() * state.sample_rate as f64) as u64;
+        // If the target is still behind the landing point, playback resumes
+        // from where the demuxer actually is
+        let file_frame = file_frame.max(landed);
+        state.discard_until = file_frame;
+        state.position_samples = file_frame.saturating_sub(start_padding);
 
-                Ok(actual_position)
-            }
-            Err(symphonia::core::errors::Error::SeekError(kind)) => {
-                Err(AudioError::SeekError(format!("Seek failed: {:?}", kind)).into())
-            }
-            Err(e) => Err(AudioError::SeekError(format!("Seek error: {}", e)).into()),
-        }
+        Ok(Duration::from_secs_f64(
+            state.position_samples as f64 / state.sample_rate as f64,
+        ))
     }
 
     fn duration(&self) -> Option<Duration> {
+        if let Some(ref dsd_state) = self.dsd_state {
+            return Some(dsd_state.duration());
+        }
         self.stream_state.as_ref().and_then(|s| s.duration)
     }
 
     fn position(&self) -> Duration {
+        if let Some(ref dsd_state) = self.dsd_state {
+            return Duration::from_secs_f64(
+                dsd_state.position_samples as f64 / dsd_state.sample_rate() as f64,
+            );
+        }
         self.stream_state
             .as_ref()
             .map(|s| Duration::from_secs_f64(s.position_samples as f64 / s.sample_rate as f64))
@@ -642,6 +946,7 @@ impl AudioDecoderTrait for SymphoniaDecoder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::mp3::{write_gapless_mp3, write_vbr_mp3, MP3_SAMPLE_RATE};
 
     #[test]
     fn decoder_creation() {
@@ -656,6 +961,8 @@ mod tests {
         assert!(decoder.supports_format(Path::new("test.flac")));
         assert!(decoder.supports_format(Path::new("test.ogg")));
         assert!(decoder.supports_format(Path::new("test.wav")));
+        assert!(decoder.supports_format(Path::new("test.dsf")));
+        assert!(decoder.supports_format(Path::new("test.DFF")));
         assert!(!decoder.supports_format(Path::new("test.txt")));
     }
 
@@ -665,4 +972,201 @@ mod tests {
         let result = decoder.decode(Path::new("/nonexistent/file.mp3"));
         assert!(result.is_err());
     }
+
+    /// Longest run of silent frames in interleaved stereo samples
+    fn longest_silence(samples: &[f32]) -> usize {
+        let mut longest = 0;
+        let mut run = 0;
+        for frame in samples.chunks(2) {
+            if frame.iter().all(|s| s.abs() <= 1e-4) {
+                run += 1;
+                longest = longest.max(run);
+            } else {
+                run = 0;
+            }
+        }
+        longest
+    }
+
+    #[test]
+    fn decode_trims_encoder_delay_and_padding() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("gapless.mp3");
+        let samples = write_gapless_mp3(&path, 10).unwrap();
+
+        let mut decoder = SymphoniaDecoder::new();
+        let buffer = decoder.decode(&path).unwrap();
+
+        // Only the audio between delay and padding, which is never silent
+        assert_eq!(buffer.samples.len() as u64, samples * 2);
+        assert!(longest_silence(&buffer.samples) < 4);
+    }
+
+    #[test]
+    fn streaming_decode_and_seek_use_trimmed_timeline() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("gapless.mp3");
+        let samples = write_gapless_mp3(&path, 10).unwrap();
+        let full = SymphoniaDecoder::new().decode(&path).unwrap().samples;
+
+        let mut decoder = SymphoniaDecoder::new();
+        let metadata = decoder.open(&path).unwrap();
+        let duration = Duration::from_secs_f64(samples as f64 / MP3_SAMPLE_RATE as f64);
+        assert_eq!(metadata.duration, Some(duration));
+
+        let decode_rest = |decoder: &mut SymphoniaDecoder| {
+            let mut decoded = Vec::new();
+            while let Some(chunk) = decoder.decode_chunk(1000).unwrap() {
+                decoded.extend(chunk.samples);
+            }
+            decoded
+        };
+
+        // Streaming matches the full decode and ends at the duration
+        assert_eq!(decode_rest(&mut decoder), full);
+        assert_eq!(decoder.position(), duration);
+
+        // Seeking back to the start skips the encoder delay again
+        assert_eq!(decoder.seek(Duration::ZERO).unwrap(), Duration::ZERO);
+        assert_eq!(decode_rest(&mut decoder), full);
+
+        // Seeks land on the exact sample, between frame boundaries
+        let target = Duration::from_millis(150);
+        let actual = decoder.seek(target).unwrap();
+        let start = (target.as_secs_f64() * MP3_SAMPLE_RATE as f64).round() as usize;
+        assert_ne!(start % 1152, 0);
+        assert_eq!(
+            (actual.as_secs_f64() * MP3_SAMPLE_RATE as f64).round() as usize,
+            start
+        );
+        assert_eq!(decoder.position(), actual);
+
+        let rest = decode_rest(&mut decoder);
+        assert_eq!(rest.len() / 2 + start, samples as usize);
+        assert_eq!(rest, full[start * 2..]);
+    }
+
+    #[test]
+    fn seek_in_vbr_mp3_without_seek_table() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vbr.mp3");
+        write_vbr_mp3(&path, 60).unwrap();
+        let full = SymphoniaDecoder::new().decode(&path).unwrap().samples;
+
+        let mut decoder = SymphoniaDecoder::new();
+        decoder.open(&path).unwrap();
+
+        // Frames differ in size, so no byte offset maps linearly to a time
+        for start in [40_000usize, 1_500, 61_234, 3] {
+            let target = Duration::from_secs_f64(start as f64 / MP3_SAMPLE_RATE as f64);
+            let actual = decoder.seek(target).unwrap();
+            assert_eq!(
+                (actual.as_secs_f64() * MP3_SAMPLE_RATE as f64).round() as usize,
+                start
+            );
+            assert_eq!(decoder.position(), actual);
+
+            let chunk = decoder.decode_chunk(2048).unwrap().unwrap();
+            assert_eq!(
+                chunk.samples,
+                full[start * 2..(start + 2048) * 2],
+                "Audio after seeking to {}",
+                start
+            );
+        }
+    }
+
+    #[cfg(feature = "opus")]
+    mod opus {
+        use super::*;
+        use crate::test_utils::opus::{write_ogg_opus, OPUS_SAMPLE_RATE};
+
+        /// One second of a stereo 100 Hz - 2 kHz chirp at -6 dBFS
+        ///
+        /// Unlike a steady tone, a chirp only lines up with itself at one
+        /// offset.
+        fn chirp() -> Vec<f32> {
+            (0..OPUS_SAMPLE_RATE as usize)
+                .flat_map(|i| {
+                    let t = i as f32 / OPUS_SAMPLE_RATE as f32;
+                    let phase = 2.0 * std::f32::consts::PI * (100.0 * t + 950.0 * t * t);
+                    let s = 0.5 * phase.sin();
+                    [s, s]
+                })
+                .collect()
+        }
+
+        fn rms(samples: &[f32]) -> f32 {
+            (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+        }
+
+        #[test]
+        fn decode_trims_pre_skip_and_end_padding() {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("chirp.opus");
+            let input = chirp();
+            write_ogg_opus(&path, &input, 0).unwrap();
+
+            let buffer = SymphoniaDecoder::new().decode(&path).unwrap();
+
+            assert_eq!(buffer.format.sample_rate.as_hz(), OPUS_SAMPLE_RATE);
+            assert_eq!(buffer.samples.len(), input.len());
+
+            // Lossy, but in phase with the input once trimmed
+            let error: Vec<f32> = buffer
+                .samples
+                .iter()
+                .zip(&input)
+                .map(|(decoded, original)| decoded - original)
+                .collect();
+            assert!(rms(&error) < 0.05 * rms(&input));
+        }
+
+        #[test]
+        fn decode_applies_header_output_gain() {
+            let dir = tempfile::tempdir().unwrap();
+            let unity = dir.path().join("unity.opus");
+            let quiet = dir.path().join("quiet.opus");
+            write_ogg_opus(&unity, &chirp(), 0).unwrap();
+            write_ogg_opus(&quiet, &chirp(), -6 * 256).unwrap();
+
+            let unity = SymphoniaDecoder::new().decode(&unity).unwrap().samples;
+            let quiet = SymphoniaDecoder::new().decode(&quiet).unwrap().samples;
+
+            let ratio_db = 20.0 * (rms(&quiet) / rms(&unity)).log10();
+            assert!((ratio_db + 6.0).abs() < 0.1, "gain was {ratio_db} dB");
+        }
+
+        #[test]
+        fn streaming_decode_and_seek() {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("chirp.opus");
+            let input = chirp();
+            write_ogg_opus(&path, &input, 0).unwrap();
+            let full = SymphoniaDecoder::new().decode(&path).unwrap().samples;
+
+            let mut decoder = SymphoniaDecoder::new();
+            let metadata = decoder.open(&path).unwrap();
+            assert_eq!(metadata.duration, Some(Duration::from_secs(1)));
+
+            let target = Duration::from_millis(510);
+            assert_eq!(decoder.seek(target).unwrap(), target);
+
+            let mut rest = Vec::new();
+            while let Some(chunk) = decoder.decode_chunk(1000).unwrap() {
+                rest.extend(chunk.samples);
+            }
+
+            // The pre-roll settles the decoder, so the output closely
+            // matches the full decode from the target on
+            let start = (target.as_secs_f64() * OPUS_SAMPLE_RATE as f64) as usize * 2;
+            assert_eq!(rest.len(), full.len() - start);
+            let error: Vec<f32> = rest
+                .iter()
+                .zip(&full[start..])
+                .map(|(seeked, decoded)| seeked - decoded)
+                .collect();
+            assert!(rms(&error) < 0.05 * rms(&full));
+        }
+    }
 }
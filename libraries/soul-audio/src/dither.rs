@@ -4,6 +4,11 @@
 //! for converting floating-point audio to integer formats (I16, I32).
 //! TPDF is the industry standard for audio dithering.
 //!
+//! For 16-bit outputs, [`NoiseShapedDither`] adds error-feedback noise
+//! shaping on top of TPDF, moving the dither noise out of the 2-5 kHz
+//! region where hearing is most sensitive and into the top octave.
+//! [`StereoDither`] can run in any [`DitherMode`].
+//!
 //! # Why Dithering?
 //!
 //! When converting from high-precision formats (F32) to lower bit-depths (I16),
@@ -21,6 +26,26 @@
 //! let sample: f32 = 0.5;
 //! let i16_sample = dither.dither_to_i16(sample);
 //! ```
+//!
+//! Noise-shaped output:
+//!
+//! ```
+//! use soul_audio::dither::{DitherMode, NoiseShape, StereoDither};
+//!
+//! let mut dither = StereoDither::with_mode(DitherMode::Shaped(NoiseShape::FWeighted), 44100);
+//! let input = [0.25_f32, -0.25];
+//! let mut output = [0_i16; 2];
+//! dither.process_stereo_to_i16(&input, &mut output);
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Longest error-feedback filter used by [`NoiseShape`]
+const MAX_SHAPER_ORDER: usize = 9;
+
+/// Full-scale value for 24-bit quantization in [`StereoDither`]
+const I24_SCALE: f64 = 8_388_607.0;
 
 /// TPDF (Triangular Probability Density Function) dither
 ///
@@ -96,8 +121,15 @@ impl TpdfDither {
         let rand2 = self.prev_rand;
         self.prev_rand = rand1;
 
-        // Scale for 24-bit (8-bit shift for the 24->32 padding)
-        (rand1 - rand2) >> 8
+        // Range: [-1, 1] in 24-bit quantization steps, which are 256 apart
+        // in the 32-bit container
+        (rand1 - rand2) >> 16
+    }
+
+    /// Generate TPDF noise in LSB units (range ±1 LSB)
+    #[inline(always)]
+    fn tpdf_lsb(&mut self) -> f64 {
+        self.tpdf_noise_i16() as f64 / 65536.0
     }
 
     /// Convert F32 sample to I16 with TPDF dithering
@@ -204,25 +236,279 @@ impl Clone for TpdfDither {
     }
 }
 
+/// Noise shaping filter curves
+///
+/// Error-feedback coefficients published by Lipshitz, Wannamaker et al.
+/// They are designed for 44.1 kHz and keep their shape at 48 kHz (the
+/// curve moves up by ~9%). At other rates [`NoiseShaper`] falls back to
+/// plain TPDF.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoiseShape {
+    /// 5-tap Lipshitz filter, ~15 dB less noise below 5 kHz
+    Lipshitz,
+    /// 9-tap F-weighted filter (Wannamaker), follows the 15 phon curve
+    FWeighted,
+    /// 9-tap modified E-weighted filter (Wannamaker), most aggressive
+    EWeighted,
+}
+
+impl NoiseShape {
+    /// All available curves
+    pub const ALL: [NoiseShape; 3] = [Self::Lipshitz, Self::FWeighted, Self::EWeighted];
+
+    /// Error-feedback filter coefficients, newest error first
+    pub fn coefficients(&self) -> &'static [f64] {
+        match self {
+            Self::Lipshitz => &[2.033, -2.165, 1.959, -1.590, 0.6149],
+            Self::FWeighted => &[
+                2.412, -3.370, 3.937, -4.174, 3.353, -2.205, 1.281, -0.569, 0.0847,
+            ],
+            Self::EWeighted => &[
+                2.847, -4.685, 6.214, -7.184, 6.639, -5.032, 3.263, -1.632, 0.4191,
+            ],
+        }
+    }
+
+    /// Whether the curve is valid at this sample rate
+    pub fn supports_sample_rate(sample_rate: u32) -> bool {
+        matches!(sample_rate, 44_100 | 48_000)
+    }
+}
+
+/// Dither strategy for float to integer conversion
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DitherMode {
+    /// Round to nearest, no dither (only for testing or pre-dithered material)
+    None,
+    /// Flat TPDF dither
+    #[default]
+    Tpdf,
+    /// TPDF dither with error-feedback noise shaping
+    Shaped(NoiseShape),
+}
+
+impl DitherMode {
+    /// All modes, in the order shown to users
+    pub const ALL: [DitherMode; 5] = [
+        Self::None,
+        Self::Tpdf,
+        Self::Shaped(NoiseShape::Lipshitz),
+        Self::Shaped(NoiseShape::FWeighted),
+        Self::Shaped(NoiseShape::EWeighted),
+    ];
+
+    /// Stable identifier used in settings ("none", "tpdf", "lipshitz", ...)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Tpdf => "tpdf",
+            Self::Shaped(NoiseShape::Lipshitz) => "lipshitz",
+            Self::Shaped(NoiseShape::FWeighted) => "f-weighted",
+            Self::Shaped(NoiseShape::EWeighted) => "e-weighted",
+        }
+    }
+
+    /// Mode to use for an interleaved stream with `channels` channels
+    ///
+    /// [`StereoDither`] keeps one error-feedback state per stereo channel, so
+    /// noise shaping falls back to flat TPDF for any other channel count.
+    pub fn for_channels(self, channels: u16) -> Self {
+        match self {
+            Self::Shaped(_) if channels != 2 => Self::Tpdf,
+            mode => mode,
+        }
+    }
+}
+
+impl fmt::Display for DitherMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for DitherMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.as_str() == s)
+            .ok_or_else(|| {
+                let valid: Vec<&str> = Self::ALL.iter().map(DitherMode::as_str).collect();
+                format!(
+                    "Invalid dither mode '{}'. Must be one of: {}",
+                    s,
+                    valid.join(", ")
+                )
+            })
+    }
+}
+
+/// Error-feedback quantizer for one channel
+///
+/// Subtracts filtered past quantization errors from the input before
+/// rounding, so the total error spectrum follows `1 - H(z)`. Works in
+/// LSB units, so the same state serves any output bit depth.
+#[derive(Debug, Clone)]
+pub struct NoiseShaper {
+    coefficients: &'static [f64],
+    /// Past errors, newest first
+    history: [f64; MAX_SHAPER_ORDER],
+}
+
+impl NoiseShaper {
+    /// Create a shaper for the given curve and sample rate
+    ///
+    /// Unsupported sample rates get an empty filter (plain TPDF).
+    pub fn new(shape: NoiseShape, sample_rate: u32) -> Self {
+        if NoiseShape::supports_sample_rate(sample_rate) {
+            Self {
+                coefficients: shape.coefficients(),
+                history: [0.0; MAX_SHAPER_ORDER],
+            }
+        } else {
+            Self::flat()
+        }
+    }
+
+    /// Shaper without a filter (plain rounding of value + dither)
+    fn flat() -> Self {
+        Self {
+            coefficients: &[],
+            history: [0.0; MAX_SHAPER_ORDER],
+        }
+    }
+
+    /// Whether a shaping filter is active
+    pub fn is_active(&self) -> bool {
+        !self.coefficients.is_empty()
+    }
+
+    /// Quantize a value given in LSBs, adding `dither` (also in LSBs)
+    ///
+    /// Returns the rounded output; clamping to the target range is left to
+    /// the caller so clipping does not feed back into the filter.
+    #[inline]
+    pub fn quantize(&mut self, value: f64, dither: f64) -> f64 {
+        let order = self.coefficients.len();
+        let feedback: f64 = self
+            .coefficients
+            .iter()
+            .zip(&self.history)
+            .map(|(c, e)| c * e)
+            .sum();
+
+        let target = value - feedback;
+        let output = (target + dither).round();
+
+        if order > 0 {
+            self.history.copy_within(0..order - 1, 1);
+            self.history[0] = output - target;
+        }
+        output
+    }
+
+    /// Clear the error history
+    pub fn reset(&mut self) {
+        self.history = [0.0; MAX_SHAPER_ORDER];
+    }
+}
+
+/// Noise-shaped TPDF dither for a single channel
+///
+/// Intended for 16-bit output at 44.1/48 kHz, where shaping gains the
+/// most. For 24-bit output the noise is far below audibility either way.
+#[derive(Debug, Clone)]
+pub struct NoiseShapedDither {
+    tpdf: TpdfDither,
+    shaper: NoiseShaper,
+}
+
+impl NoiseShapedDither {
+    /// Create a noise-shaped dither for the given curve and sample rate
+    pub fn new(shape: NoiseShape, sample_rate: u32) -> Self {
+        Self {
+            tpdf: TpdfDither::new(),
+            shaper: NoiseShaper::new(shape, sample_rate),
+        }
+    }
+
+    /// Create with custom seed for reproducible results
+    pub fn with_seed(shape: NoiseShape, sample_rate: u32, seed: u32) -> Self {
+        Self {
+            tpdf: TpdfDither::with_seed(seed),
+            shaper: NoiseShaper::new(shape, sample_rate),
+        }
+    }
+
+    /// Convert F32 sample to I16 with shaped dither
+    #[inline]
+    pub fn dither_to_i16(&mut self, sample: f32) -> i16 {
+        let dither = self.tpdf.tpdf_lsb();
+        let output = self.shaper.quantize(sample as f64 * 32767.0, dither);
+        output.clamp(-32768.0, 32767.0) as i16
+    }
+
+    /// Convert F32 sample to I32 with shaped dither at 24-bit precision
+    ///
+    /// The result is quantized to 24 bits and left-aligned in the i32.
+    #[inline]
+    pub fn dither_to_i32(&mut self, sample: f32) -> i32 {
+        let dither = self.tpdf.tpdf_lsb();
+        let output = self.shaper.quantize(sample as f64 * I24_SCALE, dither);
+        (output.clamp(-I24_SCALE - 1.0, I24_SCALE) as i32) << 8
+    }
+
+    /// Reset dither and filter state
+    pub fn reset(&mut self) {
+        self.tpdf.reset();
+        self.shaper.reset();
+    }
+}
+
 /// Stereo dither for processing interleaved audio
 ///
 /// Uses independent dither states for L/R channels to avoid
 /// correlated noise that could cause phantom center artifacts.
 #[derive(Debug, Clone)]
 pub struct StereoDither {
+    mode: DitherMode,
     left: TpdfDither,
     right: TpdfDither,
+    /// Error-feedback state, used in [`DitherMode::Shaped`]
+    left_shaper: NoiseShaper,
+    right_shaper: NoiseShaper,
 }
 
 impl StereoDither {
-    /// Create a new stereo dither
+    /// Create a new stereo dither (flat TPDF)
     pub fn new() -> Self {
+        Self::with_mode(DitherMode::Tpdf, 0)
+    }
+
+    /// Create a stereo dither using the given mode
+    ///
+    /// `sample_rate` selects whether noise shaping is applied; see
+    /// [`NoiseShape`].
+    pub fn with_mode(mode: DitherMode, sample_rate: u32) -> Self {
+        let shaper = match mode {
+            DitherMode::Shaped(shape) => NoiseShaper::new(shape, sample_rate),
+            DitherMode::None | DitherMode::Tpdf => NoiseShaper::flat(),
+        };
         Self {
+            mode,
             left: TpdfDither::new(),
             right: TpdfDither::with_seed(0x5EED_u32), // Different seed for decorrelation
+            left_shaper: shaper.clone(),
+            right_shaper: shaper,
         }
     }
 
+    /// Current dither mode
+    pub fn mode(&self) -> DitherMode {
+        self.mode
+    }
+
     /// Process interleaved stereo F32 to I16
     ///
     /// # Arguments
@@ -232,20 +518,65 @@ impl StereoDither {
         debug_assert_eq!(input.len(), output.len());
         debug_assert_eq!(input.len() % 2, 0);
 
-        for i in (0..input.len()).step_by(2) {
-            output[i] = self.left.dither_to_i16(input[i]);
-            output[i + 1] = self.right.dither_to_i16(input[i + 1]);
+        match self.mode {
+            DitherMode::None => {
+                for (inp, out) in input.iter().zip(output.iter_mut()) {
+                    *out = (inp * 32767.0).round().clamp(-32768.0, 32767.0) as i16;
+                }
+            }
+            DitherMode::Tpdf => {
+                for i in (0..input.len()).step_by(2) {
+                    output[i] = self.left.dither_to_i16(input[i]);
+                    output[i + 1] = self.right.dither_to_i16(input[i + 1]);
+                }
+            }
+            DitherMode::Shaped(_) => {
+                for i in (0..input.len()).step_by(2) {
+                    let left = self
+                        .left_shaper
+                        .quantize(input[i] as f64 * 32767.0, self.left.tpdf_lsb());
+                    let right = self
+                        .right_shaper
+                        .quantize(input[i + 1] as f64 * 32767.0, self.right.tpdf_lsb());
+                    output[i] = left.clamp(-32768.0, 32767.0) as i16;
+                    output[i + 1] = right.clamp(-32768.0, 32767.0) as i16;
+                }
+            }
         }
     }
 
     /// Process interleaved stereo F32 to I32
+    ///
+    /// TPDF and shaped modes dither at 24-bit precision; shaped output is
+    /// quantized to 24 bits and left-aligned.
     pub fn process_stereo_to_i32(&mut self, input: &[f32], output: &mut [i32]) {
         debug_assert_eq!(input.len(), output.len());
         debug_assert_eq!(input.len() % 2, 0);
 
-        for i in (0..input.len()).step_by(2) {
-            output[i] = self.left.dither_to_i32(input[i]);
-            output[i + 1] = self.right.dither_to_i32(input[i + 1]);
+        match self.mode {
+            DitherMode::None => {
+                for (inp, out) in input.iter().zip(output.iter_mut()) {
+                    *out = TpdfDither::convert_to_i32_no_dither(*inp);
+                }
+            }
+            DitherMode::Tpdf => {
+                for i in (0..input.len()).step_by(2) {
+                    output[i] = self.left.dither_to_i32(input[i]);
+                    output[i + 1] = self.right.dither_to_i32(input[i + 1]);
+                }
+            }
+            DitherMode::Shaped(_) => {
+                for i in (0..input.len()).step_by(2) {
+                    let left = self
+                        .left_shaper
+                        .quantize(input[i] as f64 * I24_SCALE, self.left.tpdf_lsb());
+                    let right = self
+                        .right_shaper
+                        .quantize(input[i + 1] as f64 * I24_SCALE, self.right.tpdf_lsb());
+                    output[i] = (left.clamp(-I24_SCALE - 1.0, I24_SCALE) as i32) << 8;
+                    output[i + 1] = (right.clamp(-I24_SCALE - 1.0, I24_SCALE) as i32) << 8;
+                }
+            }
         }
     }
 
@@ -253,6 +584,8 @@ impl StereoDither {
     pub fn reset(&mut self) {
         self.left.reset();
         self.right.reset();
+        self.left_shaper.reset();
+        self.right_shaper.reset();
     }
 }
 
@@ -310,6 +643,18 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_dither_to_i32_noise_is_one_lsb() {
+        let mut dither = TpdfDither::new();
+        let expected = 0.25_f64 * 2147483647.0;
+
+        // ±1 LSB of 24-bit is ±256 in the 32-bit container
+        for _ in 0..10000 {
+            let result = dither.dither_to_i32(0.25);
+            assert!((result as f64 - expected).abs() <= 257.0);
+        }
+    }
+
     #[test]
     fn test_dither_reproducibility() {
         let mut dither1 = TpdfDither::with_seed(12345);
@@ -427,4 +772,82 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_dither_mode_strings() {
+        for mode in DitherMode::ALL {
+            assert_eq!(mode.as_str().parse::<DitherMode>(), Ok(mode));
+        }
+        assert!("shaped".parse::<DitherMode>().is_err());
+        assert_eq!(DitherMode::default(), DitherMode::Tpdf);
+    }
+
+    #[test]
+    fn test_dither_mode_for_channels() {
+        let shaped = DitherMode::Shaped(NoiseShape::Lipshitz);
+        assert_eq!(shaped.for_channels(2), shaped);
+        assert_eq!(shaped.for_channels(6), DitherMode::Tpdf);
+        assert_eq!(DitherMode::None.for_channels(8), DitherMode::None);
+    }
+
+    #[test]
+    fn test_no_dither_rounds() {
+        let mut stereo = StereoDither::with_mode(DitherMode::None, 44100);
+        let input = [0.5_f32, -0.5, 0.0, 1.0];
+        let mut output = [0_i16; 4];
+
+        stereo.process_stereo_to_i16(&input, &mut output);
+        assert_eq!(output, [16384, -16384, 0, 32767]);
+    }
+
+    #[test]
+    fn test_noise_shaper_rate_support() {
+        assert!(NoiseShaper::new(NoiseShape::Lipshitz, 44100).is_active());
+        assert!(NoiseShaper::new(NoiseShape::FWeighted, 48000).is_active());
+        assert!(!NoiseShaper::new(NoiseShape::EWeighted, 96000).is_active());
+    }
+
+    #[test]
+    fn test_shaped_dither_tracks_input() {
+        for shape in NoiseShape::ALL {
+            let mut dither = NoiseShapedDither::with_seed(shape, 44100, 777);
+            let mut sum: i64 = 0;
+
+            for _ in 0..100_000 {
+                sum += dither.dither_to_i16(0.25) as i64;
+            }
+
+            // Shaping moves noise in frequency but must not add DC
+            let mean = sum as f64 / 100_000.0;
+            assert!(
+                (mean - 0.25 * 32767.0).abs() < 0.5,
+                "{:?}: mean {} drifted",
+                shape,
+                mean
+            );
+        }
+    }
+
+    #[test]
+    fn test_shaped_dither_stays_stable_when_clipping() {
+        let mut dither = NoiseShapedDither::new(NoiseShape::EWeighted, 44100);
+
+        for _ in 0..1000 {
+            assert_eq!(dither.dither_to_i16(1.5), i16::MAX);
+        }
+        // Error history must not have blown up while clipped
+        let samples: Vec<i16> = (0..1000).map(|_| dither.dither_to_i16(0.0)).collect();
+        assert!(samples.iter().all(|s| s.abs() < 100));
+    }
+
+    #[test]
+    fn test_shaped_i32_is_24bit() {
+        let mut dither = NoiseShapedDither::new(NoiseShape::FWeighted, 48000);
+
+        for i in 0..1000 {
+            let sample = (i as f32 * 0.01).sin() * 0.5;
+            let output = dither.dither_to_i32(sample);
+            assert_eq!(output & 0xFF, 0, "Low byte must be zero");
+        }
+    }
 }
@@ -42,6 +42,20 @@ impl DsdFormat {
         44100 * self.multiplier()
     }
 
+    /// Look up the format for a DSD sample rate in Hz
+    ///
+    /// Returns `None` for rates that are not a standard DSD rate.
+    pub fn from_sample_rate(rate: u32) -> Option<Self> {
+        [
+            DsdFormat::Dsd64,
+            DsdFormat::Dsd128,
+            DsdFormat::Dsd256,
+            DsdFormat::Dsd512,
+        ]
+        .into_iter()
+        .find(|format| format.sample_rate() == rate)
+    }
+
     /// Get the DSD sample rate in MHz
     pub fn sample_rate_mhz(&self) -> f64 {
         self.sample_rate() as f64 / 1_000_000.0
@@ -343,6 +357,13 @@ mod tests {
         assert_eq!(DsdFormat::Dsd256.sample_rate(), 11_289_600);
     }
 
+    #[test]
+    fn test_dsd_format_from_sample_rate() {
+        assert_eq!(DsdFormat::from_sample_rate(2_822_400), Some(DsdFormat::Dsd64));
+        assert_eq!(DsdFormat::from_sample_rate(22_579_200), Some(DsdFormat::Dsd512));
+        assert_eq!(DsdFormat::from_sample_rate(3_072_000), None);
+    }
+
     #[test]
     fn test_dsd_format_display() {
         assert_eq!(DsdFormat::Dsd64.display_name(), "DSD64 (2.8 MHz)");
@@ -0,0 +1,342 @@
+//! DSD to PCM conversion
+//!
+//! Converts a 1-bit DSD stream to PCM with a single-stage linear-phase FIR
+//! decimator. The filter is a Blackman-Harris windowed sinc whose length
+//! grows with the decimation ratio (16 output periods), giving ~90 dB of
+//! stopband rejection of the shaped DSD noise above the passband.
+//!
+//! Because the input is 1-bit, the filter is evaluated with byte lookup
+//! tables: each group of 8 taps is precomputed for all 256 byte values, so
+//! one output sample costs `taps / 8` table lookups instead of `taps`
+//! multiplications.
+//!
+//! The output is time-aligned with the input (output frame `n` is centered on
+//! DSD sample `n * ratio`), so positions and seeks map exactly between the
+//! DSD and PCM domains.
+//!
+//! # Level
+//!
+//! A stream of all ones maps to +1.0 (unity DC gain). SACD material is
+//! mastered with 0 dB at 50% modulation, i.e. -6 dBFS here, which leaves
+//! headroom for the +3 dB peaks the format allows.
+
+use crate::error::{AudioError, Result};
+
+/// Preferred PCM output rates: 2x the base rate of the 44.1k and 48k DSD families
+const PCM_RATES: [u32; 2] = [88_200, 96_000];
+
+/// Byte pattern representing DSD silence (equal number of ones and zeros)
+const DSD_SILENCE: u8 = 0x69;
+
+/// Filter length in output periods
+const FILTER_PERIODS: usize = 16;
+
+/// Passband edge relative to the output rate (~30 kHz at 88.2 kHz)
+const CUTOFF_RATIO: f64 = 0.34;
+
+/// DSD to PCM decimator
+pub struct DsdToPcm {
+    channels: usize,
+    dsd_rate: u32,
+    pcm_rate: u32,
+    /// Bytes consumed per output sample
+    step: usize,
+    /// Filter length in bytes
+    taps_bytes: usize,
+    /// Lookup tables: `taps_bytes` tables of 256 entries
+    table: Vec<f64>,
+    /// Pending input per channel (filter history + unconsumed bytes)
+    history: Vec<Vec<u8>>,
+    /// Read offset into `history` (same for all channels)
+    offset: usize,
+    /// Input bytes received per channel since the last reset
+    bytes_in: u64,
+    /// Output frames produced since the last reset
+    frames_out: u64,
+}
+
+impl DsdToPcm {
+    /// Create a decimator from `dsd_rate` to `pcm_rate`
+    ///
+    /// `dsd_rate / pcm_rate` must be an integer multiple of 8 (one output
+    /// sample per whole number of input bytes), e.g. 2,822,400 -> 88,200.
+    pub fn new(dsd_rate: u32, pcm_rate: u32, channels: usize) -> Result<Self> {
+        if pcm_rate == 0 || dsd_rate % pcm_rate != 0 || (dsd_rate / pcm_rate) % 8 != 0 {
+            return Err(AudioError::UnsupportedFormat(format!(
+                "Cannot decimate DSD at {} Hz to {} Hz",
+                dsd_rate, pcm_rate
+            )));
+        }
+        if channels == 0 {
+            return Err(AudioError::InvalidBuffer("DSD stream has no channels".into()));
+        }
+
+        let ratio = (dsd_rate / pcm_rate) as usize;
+        let step = ratio / 8;
+        let taps = ratio * FILTER_PERIODS;
+        let taps_bytes = taps / 8;
+
+        let coefficients = Self::design_filter(taps, CUTOFF_RATIO / ratio as f64);
+        let table = Self::build_table(&coefficients);
+
+        let mut decimator = Self {
+            channels,
+            dsd_rate,
+            pcm_rate,
+            step,
+            taps_bytes,
+            table,
+            history: vec![Vec::new(); channels],
+            offset: 0,
+            bytes_in: 0,
+            frames_out: 0,
+        };
+        decimator.reset();
+        Ok(decimator)
+    }
+
+    /// Create a decimator to 88.2 kHz (or 96 kHz for 48k-family DSD rates)
+    pub fn for_dsd_rate(dsd_rate: u32, channels: usize) -> Result<Self> {
+        let pcm_rate = PCM_RATES
+            .into_iter()
+            .find(|&rate| dsd_rate % rate == 0 && (dsd_rate / rate) % 8 == 0)
+            .ok_or_else(|| {
+                AudioError::UnsupportedFormat(format!("Unsupported DSD rate: {} Hz", dsd_rate))
+            })?;
+        Self::new(dsd_rate, pcm_rate, channels)
+    }
+
+    /// Blackman-Harris windowed sinc, normalized to unity DC gain
+    ///
+    /// `cutoff` is relative to the DSD sample rate.
+    fn design_filter(taps: usize, cutoff: f64) -> Vec<f64> {
+        use std::f64::consts::PI;
+
+        let center = (taps - 1) as f64 / 2.0;
+        let mut h: Vec<f64> = (0..taps)
+            .map(|i| {
+                let x = i as f64 - center;
+                let sinc = if x == 0.0 {
+                    2.0 * cutoff
+                } else {
+                    (2.0 * PI * cutoff * x).sin() / (PI * x)
+                };
+                let phase = 2.0 * PI * i as f64 / (taps - 1) as f64;
+                let window = 0.35875 - 0.48829 * phase.cos() + 0.14128 * (2.0 * phase).cos()
+                    - 0.01168 * (3.0 * phase).cos();
+                sinc * window
+            })
+            .collect();
+
+        let sum: f64 = h.iter().sum();
+        for c in &mut h {
+            *c /= sum;
+        }
+        h
+    }
+
+    /// Precompute the contribution of every byte value at every byte position
+    fn build_table(coefficients: &[f64]) -> Vec<f64> {
+        let taps_bytes = coefficients.len() / 8;
+        let mut table = vec![0.0; taps_bytes * 256];
+
+        for (position, taps) in coefficients.chunks_exact(8).enumerate() {
+            for byte in 0..256usize {
+                // MSB is the earliest sample
+                table[position * 256 + byte] = taps
+                    .iter()
+                    .enumerate()
+                    .map(|(bit, &c)| if byte & (0x80 >> bit) != 0 { c } else { -c })
+                    .sum();
+            }
+        }
+
+        table
+    }
+
+    /// DSD input rate in Hz
+    pub fn dsd_rate(&self) -> u32 {
+        self.dsd_rate
+    }
+
+    /// PCM output rate in Hz
+    pub fn pcm_rate(&self) -> u32 {
+        self.pcm_rate
+    }
+
+    /// Number of channels
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// DSD bytes per channel consumed for each output frame
+    pub fn bytes_per_frame(&self) -> usize {
+        self.step
+    }
+
+    /// Clear filter state (call after seeking)
+    ///
+    /// The filter history is primed with DSD silence so the first output
+    /// frame is centered on the first input sample.
+    pub fn reset(&mut self) {
+        let prime = self.taps_bytes / 2;
+        for history in &mut self.history {
+            history.clear();
+            history.resize(prime, DSD_SILENCE);
+        }
+        self.offset = 0;
+        self.bytes_in = 0;
+        self.frames_out = 0;
+    }
+
+    /// Convert packed DSD bytes (one slice per channel, equal lengths)
+    ///
+    /// Appends interleaved PCM frames in [-1.0, 1.0] to `output`.
+    pub fn process(&mut self, input: &[&[u8]], output: &mut Vec<f32>) {
+        let len = input.iter().map(|c| c.len()).min().unwrap_or(0);
+        for (history, channel) in self.history.iter_mut().zip(input) {
+            history.extend_from_slice(&channel[..len]);
+        }
+        self.bytes_in += len as u64;
+        self.run(output, None);
+    }
+
+    /// Emit the frames still held back by the filter at the end of a stream
+    ///
+    /// After this call the number of frames produced equals the number of
+    /// input bytes divided by the bytes per output frame.
+    pub fn flush(&mut self, output: &mut Vec<f32>) {
+        let expected = self.bytes_in / self.step as u64;
+        if self.frames_out >= expected {
+            return;
+        }
+        let pad = self.taps_bytes / 2;
+        for history in &mut self.history {
+            history.resize(history.len() + pad, DSD_SILENCE);
+        }
+        self.run(output, Some(expected));
+    }
+
+    /// Produce all complete output frames, optionally capped
+    fn run(&mut self, output: &mut Vec<f32>, limit: Option<u64>) {
+        let available = self.history[0].len();
+        while self.offset + self.taps_bytes <= available
+            && !limit.is_some_and(|limit| self.frames_out >= limit)
+        {
+            for history in &self.history {
+                let window = &history[self.offset..self.offset + self.taps_bytes];
+                let sum: f64 = window
+                    .iter()
+                    .enumerate()
+                    .map(|(position, &byte)| self.table[position * 256 + byte as usize])
+                    .sum();
+                output.push(sum as f32);
+            }
+            self.offset += self.step;
+            self.frames_out += 1;
+        }
+
+        // Drop consumed bytes once they dominate the buffer
+        if self.offset > 4 * self.taps_bytes {
+            for history in &mut self.history {
+                history.drain(..self.offset);
+            }
+            self.offset = 0;
+        }
+    }
+}
+
+/// Downmix interleaved multichannel PCM to interleaved stereo
+///
+/// Uses the same ITU-R BS.775-1 coefficients as `SymphoniaDecoder` for the
+/// DSF/DSDIFF channel layouts (FL FR [C] [LFE] [SL SR]). Mono is duplicated.
+pub fn downmix_to_stereo(samples: &[f32], channels: usize) -> Vec<f32> {
+    const MIX: f32 = 0.707; // -3dB
+
+    if channels == 2 {
+        return samples.to_vec();
+    }
+
+    let mut output = Vec::with_capacity(samples.len() / channels.max(1) * 2);
+    for frame in samples.chunks_exact(channels.max(1)) {
+        let (l, r) = match *frame {
+            [m] => (m, m),
+            [l, r, c] => (l + MIX * c, r + MIX * c),
+            [l, r, sl, sr] => (l + MIX * sl, r + MIX * sr),
+            [l, r, c, sl, sr] => (l + MIX * (c + sl), r + MIX * (c + sr)),
+            [l, r, c, lfe, sl, sr, ..] => (l + MIX * (c + lfe + sl), r + MIX * (c + lfe + sr)),
+            _ => (frame[0], frame.get(1).copied().unwrap_or(frame[0])),
+        };
+        output.push(l.clamp(-1.0, 1.0));
+        output.push(r.clamp(-1.0, 1.0));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn downmix_layouts() {
+        assert_eq!(downmix_to_stereo(&[0.5, -0.5], 1), vec![0.5, 0.5, -0.5, -0.5]);
+        assert_eq!(downmix_to_stereo(&[0.1, 0.2], 2), vec![0.1, 0.2]);
+
+        let surround = downmix_to_stereo(&[0.1, 0.2, 0.0, 0.0, 0.0, 0.0], 6);
+        assert_eq!(surround, vec![0.1, 0.2]);
+    }
+
+    #[test]
+    fn picks_output_rate_for_family() {
+        assert_eq!(DsdToPcm::for_dsd_rate(2_822_400, 2).unwrap().pcm_rate(), 88_200);
+        assert_eq!(DsdToPcm::for_dsd_rate(11_289_600, 2).unwrap().pcm_rate(), 88_200);
+        assert_eq!(DsdToPcm::for_dsd_rate(3_072_000, 2).unwrap().pcm_rate(), 96_000);
+        assert!(DsdToPcm::for_dsd_rate(44_100, 2).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_ratios() {
+        assert!(DsdToPcm::new(2_822_400, 48_000, 2).is_err());
+        assert!(DsdToPcm::new(2_822_400, 705_600, 2).is_err()); // ratio 4
+        assert!(DsdToPcm::new(2_822_400, 88_200, 0).is_err());
+        assert!(DsdToPcm::new(2_822_400, 88_200, 2).is_ok());
+    }
+
+    #[test]
+    fn dc_gain_is_unity() {
+        let mut decimator = DsdToPcm::new(2_822_400, 88_200, 1).unwrap();
+        let ones = vec![0xFFu8; 4096];
+        let mut out = Vec::new();
+        decimator.process(&[&ones], &mut out);
+
+        // After the filter has settled the output is exactly +1.0
+        let settled = &out[FILTER_PERIODS..];
+        assert!(settled.iter().all(|&s| (s - 1.0).abs() < 1e-6));
+    }
+
+    #[test]
+    fn silence_pattern_decodes_to_silence() {
+        let mut decimator = DsdToPcm::new(5_644_800, 88_200, 2).unwrap();
+        let silence = vec![DSD_SILENCE; 8192];
+        let mut out = Vec::new();
+        decimator.process(&[&silence, &silence], &mut out);
+        decimator.flush(&mut out);
+
+        assert!(out.iter().all(|s| s.abs() < 1e-3));
+    }
+
+    #[test]
+    fn frame_count_matches_input_after_flush() {
+        let mut decimator = DsdToPcm::new(2_822_400, 88_200, 2).unwrap();
+        let data = vec![0xAAu8; 1000];
+        let mut out = Vec::new();
+
+        for chunk in data.chunks(37) {
+            decimator.process(&[chunk, chunk], &mut out);
+        }
+        decimator.flush(&mut out);
+
+        // 4 bytes per output frame at DSD64 -> 88.2 kHz
+        assert_eq!(out.len(), 2 * 1000 / 4);
+    }
+}
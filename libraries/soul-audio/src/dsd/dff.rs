@@ -0,0 +1,408 @@
+//! DSDIFF (`.dff`) reader
+//!
+//! DSDIFF is an IFF-style container (big-endian, 64-bit chunk sizes, chunks
+//! padded to an even length):
+//!
+//! ```text
+//! FRM8 "DSD "
+//! ├── FVER            format version
+//! ├── PROP "SND "
+//! │   ├── FS          sample rate
+//! │   ├── CHNL        channel count and ids
+//! │   └── CMPR        "DSD " (uncompressed) or "DST " (unsupported)
+//! ├── DSD             sample data, byte-interleaved: [ch0][ch1]...[ch0][ch1]...
+//! ├── DIIN            optional edited master info (artist, title)
+//! └── ID3             optional, unofficial ID3v2 tag
+//! ```
+//!
+//! Sample bytes are stored MSB-first, so no bit reversal is needed.
+
+use super::file::{DsdContainer, DsdStreamInfo, DsdTags};
+use super::id3;
+use crate::error::{AudioError, Result};
+use std::io::{Read, Seek, SeekFrom};
+
+/// Maximum metadata chunk size we are willing to load
+const MAX_META_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Interleaved bytes read from disk per call
+const READ_CHUNK_FRAMES: usize = 4096;
+
+/// Reader for DSDIFF files
+pub struct DffReader<R> {
+    inner: R,
+    info: DsdStreamInfo,
+    tags: DsdTags,
+    /// Offset of the first sample byte in the file
+    data_offset: u64,
+    /// Scratch buffer for interleaved bytes
+    scratch: Vec<u8>,
+    /// Position in bytes per channel
+    position: u64,
+}
+
+impl<R: Read + Seek> DffReader<R> {
+    /// Parse the DSDIFF chunks
+    pub fn new(mut inner: R) -> Result<Self> {
+        let mut header = [0u8; 16];
+        inner.read_exact(&mut header)?;
+        if &header[0..4] != b"FRM8" || &header[12..16] != b"DSD " {
+            return Err(AudioError::UnsupportedFormat("Not a DSDIFF file".into()));
+        }
+        let form_end = 12 + be_u64(&header[4..12]);
+
+        let mut sample_rate = None;
+        let mut channels = None;
+        let mut data = None;
+        let mut tags = DsdTags::default();
+        let mut has_id3 = false;
+
+        let mut pos = 16u64;
+        while pos + 12 <= form_end {
+            inner.seek(SeekFrom::Start(pos))?;
+            let Some((id, size)) = read_chunk_header(&mut inner)? else {
+                break;
+            };
+            let body = pos + 12;
+
+            match &id {
+                b"PROP" => {
+                    let (rate, count) = Self::read_properties(&mut inner, body, size)?;
+                    sample_rate = rate;
+                    channels = count;
+                }
+                b"DSD " => data = Some((body, size)),
+                b"DST " => {
+                    return Err(AudioError::UnsupportedFormat(
+                        "DST-compressed DSDIFF files are not supported".into(),
+                    ));
+                }
+                b"ID3 " if size <= MAX_META_SIZE => {
+                    let mut buf = vec![0u8; size as usize];
+                    inner.read_exact(&mut buf)?;
+                    if let Some(parsed) = id3::parse_id3v2(&buf) {
+                        tags = parsed;
+                        has_id3 = true;
+                    }
+                }
+                b"DIIN" if !has_id3 => Self::read_edited_master_info(&mut inner, body, size, &mut tags)?,
+                _ => {}
+            }
+
+            // Chunks are padded to an even size
+            pos = body + size + (size & 1);
+        }
+
+        let sample_rate = sample_rate
+            .ok_or_else(|| AudioError::DecodeError("DSDIFF: missing FS chunk".into()))?;
+        let channels = channels
+            .filter(|&c| c > 0 && c <= 6)
+            .ok_or_else(|| AudioError::DecodeError("DSDIFF: invalid channel count".into()))?;
+        let (data_offset, data_size) =
+            data.ok_or_else(|| AudioError::DecodeError("DSDIFF: missing DSD chunk".into()))?;
+
+        inner.seek(SeekFrom::Start(data_offset))?;
+
+        Ok(Self {
+            inner,
+            info: DsdStreamInfo {
+                container: DsdContainer::Dff,
+                sample_rate,
+                channels,
+                sample_count: data_size / channels as u64 * 8,
+            },
+            tags,
+            data_offset,
+            scratch: Vec::new(),
+            position: 0,
+        })
+    }
+
+    /// Read the sample rate and channel count from the PROP chunk
+    fn read_properties(
+        inner: &mut R,
+        start: u64,
+        size: u64,
+    ) -> Result<(Option<u32>, Option<usize>)> {
+        let mut kind = [0u8; 4];
+        inner.read_exact(&mut kind)?;
+        if &kind != b"SND " {
+            return Ok((None, None));
+        }
+
+        let mut sample_rate = None;
+        let mut channels = None;
+        let end = start + size;
+        let mut pos = start + 4;
+
+        while pos + 12 <= end {
+            inner.seek(SeekFrom::Start(pos))?;
+            let Some((id, sub_size)) = read_chunk_header(inner)? else {
+                break;
+            };
+            match &id {
+                b"FS  " => {
+                    let mut b = [0u8; 4];
+                    inner.read_exact(&mut b)?;
+                    sample_rate = Some(u32::from_be_bytes(b));
+                }
+                b"CHNL" => {
+                    let mut b = [0u8; 2];
+                    inner.read_exact(&mut b)?;
+                    channels = Some(u16::from_be_bytes(b) as usize);
+                }
+                b"CMPR" => {
+                    let mut b = [0u8; 4];
+                    inner.read_exact(&mut b)?;
+                    if &b != b"DSD " {
+                        return Err(AudioError::UnsupportedFormat(format!(
+                            "DSDIFF: unsupported compression '{}'",
+                            String::from_utf8_lossy(&b).trim()
+                        )));
+                    }
+                }
+                _ => {}
+            }
+            pos += 12 + sub_size + (sub_size & 1);
+        }
+
+        Ok((sample_rate, channels))
+    }
+
+    /// Read artist and title from the DIIN chunk
+    fn read_edited_master_info(
+        inner: &mut R,
+        start: u64,
+        size: u64,
+        tags: &mut DsdTags,
+    ) -> Result<()> {
+        let end = start + size;
+        let mut pos = start;
+
+        while pos + 12 <= end {
+            inner.seek(SeekFrom::Start(pos))?;
+            let Some((id, sub_size)) = read_chunk_header(inner)? else {
+                break;
+            };
+            if (&id == b"DIAR" || &id == b"DITI") && (4..=MAX_META_SIZE).contains(&sub_size) {
+                let mut buf = vec![0u8; sub_size as usize];
+                inner.read_exact(&mut buf)?;
+                let count = (u32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]) as usize)
+                    .min(buf.len() - 4);
+                let text = String::from_utf8_lossy(&buf[4..4 + count]).trim().to_string();
+                if !text.is_empty() {
+                    if &id == b"DIAR" {
+                        tags.artist = Some(text);
+                    } else {
+                        tags.title = Some(text);
+                    }
+                }
+            }
+            pos += 12 + sub_size + (sub_size & 1);
+        }
+
+        Ok(())
+    }
+
+    /// Stream properties
+    pub fn info(&self) -> &DsdStreamInfo {
+        &self.info
+    }
+
+    /// Tags from the ID3 or DIIN chunk
+    pub fn tags(&self) -> &DsdTags {
+        &self.tags
+    }
+
+    /// Current position in bytes per channel
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Read up to `max_bytes` bytes per channel, appending to `out`
+    pub fn read(&mut self, out: &mut [Vec<u8>], max_bytes: usize) -> Result<usize> {
+        let channels = self.info.channels;
+        if out.len() < channels {
+            return Err(AudioError::InvalidBuffer(format!(
+                "Expected {} channel buffers, got {}",
+                channels,
+                out.len()
+            )));
+        }
+
+        let remaining = self.info.bytes_per_channel().saturating_sub(self.position);
+        let wanted = (max_bytes as u64).min(remaining) as usize;
+        let mut done = 0;
+
+        while done < wanted {
+            let frames = (wanted - done).min(READ_CHUNK_FRAMES);
+            self.scratch.resize(frames * channels, 0);
+
+            let mut filled = 0;
+            while filled < self.scratch.len() {
+                match self.inner.read(&mut self.scratch[filled..])? {
+                    0 => break,
+                    n => filled += n,
+                }
+            }
+
+            let frames = filled / channels;
+            if frames == 0 {
+                break;
+            }
+            for frame in self.scratch[..frames * channels].chunks_exact(channels) {
+                for (buf, &byte) in out.iter_mut().zip(frame) {
+                    buf.push(byte);
+                }
+            }
+            done += frames;
+        }
+
+        self.position += done as u64;
+        Ok(done)
+    }
+
+    /// Seek to a byte position per channel
+    pub fn seek(&mut self, byte_position: u64) -> Result<()> {
+        let byte_position = byte_position.min(self.info.bytes_per_channel());
+        self.inner.seek(SeekFrom::Start(
+            self.data_offset + byte_position * self.info.channels as u64,
+        ))?;
+        self.position = byte_position;
+        Ok(())
+    }
+}
+
+/// Read a chunk id and size; `None` at end of file
+fn read_chunk_header<R: Read>(inner: &mut R) -> Result<Option<([u8; 4], u64)>> {
+    let mut header = [0u8; 12];
+    match inner.read_exact(&mut header) {
+        Ok(()) => Ok(Some((
+            [header[0], header[1], header[2], header[3]],
+            be_u64(&header[4..12]),
+        ))),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn be_u64(b: &[u8]) -> u64 {
+    u64::from_be_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]])
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn chunk(id: [u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut out = id.to_vec();
+        out.extend_from_slice(&(body.len() as u64).to_be_bytes());
+        out.extend_from_slice(body);
+        if body.len() % 2 == 1 {
+            out.push(0);
+        }
+        out
+    }
+
+    /// Build a DSDIFF file from per-channel MSB-first bytes
+    pub(crate) fn build_dff(channels: &[Vec<u8>], sample_rate: u32, extra: &[Vec<u8>]) -> Vec<u8> {
+        let mut prop = b"SND ".to_vec();
+        prop.extend(chunk(*b"FS  ", &sample_rate.to_be_bytes()));
+        let mut chnl = (channels.len() as u16).to_be_bytes().to_vec();
+        for id in [b"SLFT", b"SRGT", b"C   ", b"LFE ", b"LS  ", b"RS  "]
+            .iter()
+            .take(channels.len())
+        {
+            chnl.extend_from_slice(*id);
+        }
+        prop.extend(chunk(*b"CHNL", &chnl));
+        let mut cmpr = b"DSD ".to_vec();
+        cmpr.push(14);
+        cmpr.extend_from_slice(b"not compressed");
+        prop.extend(chunk(*b"CMPR", &cmpr));
+
+        let mut data = Vec::new();
+        for i in 0..channels[0].len() {
+            for channel in channels {
+                data.push(channel[i]);
+            }
+        }
+
+        let mut form = b"DSD ".to_vec();
+        form.extend(chunk(*b"FVER", &0x0105_0000u32.to_be_bytes()));
+        form.extend(chunk(*b"PROP", &prop));
+        form.extend(chunk(*b"DSD ", &data));
+        for chunk in extra {
+            form.extend_from_slice(chunk);
+        }
+
+        chunk(*b"FRM8", &form)
+    }
+
+    fn test_channels(len: usize) -> Vec<Vec<u8>> {
+        vec![
+            (0..len).map(|i| (i % 253) as u8).collect(),
+            (0..len).map(|i| (i % 239) as u8 ^ 0x5A).collect(),
+        ]
+    }
+
+    #[test]
+    fn reads_header_and_samples() {
+        let channels = test_channels(9_001);
+        let file = build_dff(&channels, 5_644_800, &[]);
+        let mut reader = DffReader::new(Cursor::new(file)).unwrap();
+
+        assert_eq!(reader.info().channels, 2);
+        assert_eq!(reader.info().sample_rate, 5_644_800);
+        assert_eq!(reader.info().sample_count, 9_001 * 8);
+
+        let mut out = vec![Vec::new(), Vec::new()];
+        while reader.read(&mut out, 1000).unwrap() > 0 {}
+        assert_eq!(out, channels);
+
+        reader.seek(4321).unwrap();
+        let mut out = vec![Vec::new(), Vec::new()];
+        reader.read(&mut out, 2).unwrap();
+        assert_eq!(out[0], channels[0][4321..4323]);
+        assert_eq!(out[1], channels[1][4321..4323]);
+    }
+
+    #[test]
+    fn reads_edited_master_info() {
+        let mut diin = Vec::new();
+        let mut artist = 6u32.to_be_bytes().to_vec();
+        artist.extend_from_slice(b"Artist");
+        diin.extend(chunk(*b"DIAR", &artist));
+        let mut title = 5u32.to_be_bytes().to_vec();
+        title.extend_from_slice(b"Title");
+        diin.extend(chunk(*b"DITI", &title));
+
+        let file = build_dff(&test_channels(10), 2_822_400, &[chunk(*b"DIIN", &diin)]);
+        let reader = DffReader::new(Cursor::new(file)).unwrap();
+
+        assert_eq!(reader.tags().artist.as_deref(), Some("Artist"));
+        assert_eq!(reader.tags().title.as_deref(), Some("Title"));
+    }
+
+    #[test]
+    fn reads_id3_chunk() {
+        let tag = id3::tests::build_id3v23(&[("TALB", id3::tests::text("DFF Album"))]);
+        let file = build_dff(&test_channels(10), 2_822_400, &[chunk(*b"ID3 ", &tag)]);
+        let reader = DffReader::new(Cursor::new(file)).unwrap();
+
+        assert_eq!(reader.tags().album.as_deref(), Some("DFF Album"));
+    }
+
+    #[test]
+    fn rejects_dst() {
+        let mut file = build_dff(&test_channels(10), 2_822_400, &[]);
+        let pos = file.windows(14).position(|w| w == b"not compressed").unwrap();
+        file[pos - 5..pos - 1].copy_from_slice(b"DST ");
+
+        assert!(matches!(
+            DffReader::new(Cursor::new(file)),
+            Err(AudioError::UnsupportedFormat(_))
+        ));
+    }
+}
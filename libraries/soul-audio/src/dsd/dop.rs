@@ -56,6 +56,15 @@ impl DoP {
         pcm_rate_hz: 705_600,
         bits_per_sample: 32,
     };
+
+    /// Look up the DoP transport for a DSD sample rate
+    ///
+    /// Returns `None` for rates that cannot be carried over DoP.
+    pub fn for_dsd_rate(dsd_rate_hz: u32) -> Option<DoP> {
+        [DoP::DSD64, DoP::DSD128, DoP::DSD256]
+            .into_iter()
+            .find(|dop| dop.dsd_rate_hz == dsd_rate_hz)
+    }
 }
 
 /// DoP encoder - converts packed DSD bytes to DoP PCM format
@@ -263,6 +272,14 @@ mod tests {
         assert_eq!(DoP::DSD128.pcm_rate_hz, 352_800);
     }
 
+    #[test]
+    fn test_dop_for_dsd_rate() {
+        assert_eq!(DoP::for_dsd_rate(2_822_400), Some(DoP::DSD64));
+        assert_eq!(DoP::for_dsd_rate(11_289_600), Some(DoP::DSD256));
+        assert_eq!(DoP::for_dsd_rate(22_579_200), None);
+        assert_eq!(DoP::for_dsd_rate(44_100), None);
+    }
+
     #[test]
     fn test_encoder_creation() {
         let encoder = DopEncoder::new(2);
@@ -0,0 +1,349 @@
+//! DSF (DSD Stream File) reader
+//!
+//! Layout (all integers little-endian):
+//!
+//! ```text
+//! "DSD " chunk   28 bytes   total file size, offset of ID3v2 metadata
+//! "fmt " chunk   52 bytes   format, channels, sample rate, bits, sample count,
+//!                           block size per channel
+//! "data" chunk   12 + n     channel blocks: [ch0 block][ch1 block]...[ch0 block]...
+//! ID3v2 tag                 optional, at the metadata offset
+//! ```
+//!
+//! Each channel block holds `block_size` bytes (4096 in practice) of that
+//! channel only. With 1 bit per sample the earliest sample is in the least
+//! significant bit, so bytes are bit-reversed on read.
+
+use super::file::{DsdContainer, DsdStreamInfo, DsdTags};
+use super::id3;
+use crate::error::{AudioError, Result};
+use std::io::{Read, Seek, SeekFrom};
+
+/// Maximum ID3 tag size we are willing to load (covers large cover art)
+const MAX_TAG_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Reader for DSF files
+pub struct DsfReader<R> {
+    inner: R,
+    info: DsdStreamInfo,
+    tags: DsdTags,
+    /// Whether bytes are stored LSB-first (bits per sample = 1)
+    lsb_first: bool,
+    /// Block size per channel in bytes
+    block_size: usize,
+    /// Offset of the first sample byte in the file
+    data_offset: u64,
+    /// Current block group (all channels), empty when not loaded
+    block: Vec<u8>,
+    /// Read position inside the current block (per channel)
+    block_pos: usize,
+    /// Position in bytes per channel
+    position: u64,
+}
+
+impl<R: Read + Seek> DsfReader<R> {
+    /// Parse the DSF headers and tag
+    pub fn new(mut inner: R) -> Result<Self> {
+        let mut header = [0u8; 28];
+        inner.read_exact(&mut header)?;
+        if &header[0..4] != b"DSD " {
+            return Err(AudioError::UnsupportedFormat("Not a DSF file".into()));
+        }
+        let metadata_offset = le_u64(&header[20..28]);
+
+        let mut fmt = [0u8; 52];
+        inner.read_exact(&mut fmt)?;
+        if &fmt[0..4] != b"fmt " {
+            return Err(AudioError::DecodeError("DSF: missing fmt chunk".into()));
+        }
+        let format_id = le_u32(&fmt[16..20]);
+        let channels = le_u32(&fmt[24..28]) as usize;
+        let sample_rate = le_u32(&fmt[28..32]);
+        let bits_per_sample = le_u32(&fmt[32..36]);
+        let sample_count = le_u64(&fmt[36..44]);
+        let block_size = le_u32(&fmt[44..48]) as usize;
+
+        if format_id != 0 {
+            return Err(AudioError::UnsupportedFormat(format!(
+                "DSF: unsupported format id {}",
+                format_id
+            )));
+        }
+        if channels == 0 || channels > 6 {
+            return Err(AudioError::DecodeError(format!(
+                "DSF: invalid channel count {}",
+                channels
+            )));
+        }
+        if bits_per_sample != 1 && bits_per_sample != 8 {
+            return Err(AudioError::DecodeError(format!(
+                "DSF: invalid bits per sample {}",
+                bits_per_sample
+            )));
+        }
+        if block_size == 0 {
+            return Err(AudioError::DecodeError("DSF: invalid block size".into()));
+        }
+
+        // fmt chunk size may exceed 52 in theory; skip to the data chunk
+        let fmt_size = le_u64(&fmt[4..12]);
+        inner.seek(SeekFrom::Start(28 + fmt_size))?;
+
+        let mut data_header = [0u8; 12];
+        inner.read_exact(&mut data_header)?;
+        if &data_header[0..4] != b"data" {
+            return Err(AudioError::DecodeError("DSF: missing data chunk".into()));
+        }
+        let data_offset = inner.stream_position()?;
+
+        let tags = if metadata_offset > 0 {
+            Self::read_tags(&mut inner, metadata_offset).unwrap_or_default()
+        } else {
+            DsdTags::default()
+        };
+
+        inner.seek(SeekFrom::Start(data_offset))?;
+
+        Ok(Self {
+            inner,
+            info: DsdStreamInfo {
+                container: DsdContainer::Dsf,
+                sample_rate,
+                channels,
+                sample_count,
+            },
+            tags,
+            lsb_first: bits_per_sample == 1,
+            block_size,
+            data_offset,
+            block: Vec::new(),
+            block_pos: 0,
+            position: 0,
+        })
+    }
+
+    /// Read the ID3v2 tag at the metadata offset
+    fn read_tags(inner: &mut R, offset: u64) -> Option<DsdTags> {
+        let end = inner.seek(SeekFrom::End(0)).ok()?;
+        if offset >= end || end - offset > MAX_TAG_SIZE {
+            return None;
+        }
+        inner.seek(SeekFrom::Start(offset)).ok()?;
+        let mut data = vec![0u8; (end - offset) as usize];
+        inner.read_exact(&mut data).ok()?;
+        id3::parse_id3v2(&data)
+    }
+
+    /// Stream properties
+    pub fn info(&self) -> &DsdStreamInfo {
+        &self.info
+    }
+
+    /// Tags from the ID3v2 chunk
+    pub fn tags(&self) -> &DsdTags {
+        &self.tags
+    }
+
+    /// Current position in bytes per channel
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Read up to `max_bytes` bytes per channel, appending to `out`
+    pub fn read(&mut self, out: &mut [Vec<u8>], max_bytes: usize) -> Result<usize> {
+        let channels = self.info.channels;
+        if out.len() < channels {
+            return Err(AudioError::InvalidBuffer(format!(
+                "Expected {} channel buffers, got {}",
+                channels,
+                out.len()
+            )));
+        }
+
+        let remaining = self.info.bytes_per_channel().saturating_sub(self.position);
+        let wanted = (max_bytes as u64).min(remaining) as usize;
+        let mut done = 0;
+
+        while done < wanted {
+            let exhausted = self.block.is_empty() || self.block_pos >= self.block_size;
+            if exhausted && !self.load_block()? {
+                break;
+            }
+
+            let n = (self.block_size - self.block_pos).min(wanted - done);
+            for (ch, buf) in out.iter_mut().take(channels).enumerate() {
+                let start = ch * self.block_size + self.block_pos;
+                let bytes = &self.block[start..start + n];
+                if self.lsb_first {
+                    buf.extend(bytes.iter().map(|b| b.reverse_bits()));
+                } else {
+                    buf.extend_from_slice(bytes);
+                }
+            }
+            self.block_pos += n;
+            done += n;
+        }
+
+        self.position += done as u64;
+        Ok(done)
+    }
+
+    /// Load the next block group; returns false at end of data
+    fn load_block(&mut self) -> Result<bool> {
+        let group = self.block_size * self.info.channels;
+        self.block.resize(group, 0);
+        match self.inner.read_exact(&mut self.block) {
+            Ok(()) => {
+                self.block_pos = 0;
+                Ok(true)
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                self.block.clear();
+                Ok(false)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Seek to a byte position per channel
+    pub fn seek(&mut self, byte_position: u64) -> Result<()> {
+        let byte_position = byte_position.min(self.info.bytes_per_channel());
+        let block_index = byte_position / self.block_size as u64;
+        let group = (self.block_size * self.info.channels) as u64;
+
+        self.inner
+            .seek(SeekFrom::Start(self.data_offset + block_index * group))?;
+        self.block.clear();
+        self.block_pos = 0;
+
+        let offset = (byte_position % self.block_size as u64) as usize;
+        if offset > 0 && self.load_block()? {
+            self.block_pos = offset;
+        }
+
+        self.position = byte_position;
+        Ok(())
+    }
+}
+
+fn le_u32(b: &[u8]) -> u32 {
+    u32::from_le_bytes([b[0], b[1], b[2], b[3]])
+}
+
+fn le_u64(b: &[u8]) -> u64 {
+    u64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]])
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// Build a DSF file from per-channel MSB-first bytes
+    pub(crate) fn build_dsf(
+        channels: &[Vec<u8>],
+        sample_rate: u32,
+        block_size: usize,
+        tag: Option<&[u8]>,
+    ) -> Vec<u8> {
+        let bytes_per_channel = channels[0].len();
+        let blocks = bytes_per_channel.div_ceil(block_size);
+        let data_len = blocks * block_size * channels.len();
+        let metadata_offset = tag.map(|_| 28 + 52 + 12 + data_len as u64).unwrap_or(0);
+        let total = 28 + 52 + 12 + data_len as u64 + tag.map(|t| t.len() as u64).unwrap_or(0);
+
+        let mut out = Vec::new();
+        out.extend_from_slice(b"DSD ");
+        out.extend_from_slice(&28u64.to_le_bytes());
+        out.extend_from_slice(&total.to_le_bytes());
+        out.extend_from_slice(&metadata_offset.to_le_bytes());
+
+        out.extend_from_slice(b"fmt ");
+        out.extend_from_slice(&52u64.to_le_bytes());
+        out.extend_from_slice(&1u32.to_le_bytes()); // version
+        out.extend_from_slice(&0u32.to_le_bytes()); // DSD raw
+        out.extend_from_slice(&2u32.to_le_bytes()); // channel type (stereo)
+        out.extend_from_slice(&(channels.len() as u32).to_le_bytes());
+        out.extend_from_slice(&sample_rate.to_le_bytes());
+        out.extend_from_slice(&1u32.to_le_bytes()); // bits per sample
+        out.extend_from_slice(&(bytes_per_channel as u64 * 8).to_le_bytes());
+        out.extend_from_slice(&(block_size as u32).to_le_bytes());
+        out.extend_from_slice(&0u32.to_le_bytes());
+
+        out.extend_from_slice(b"data");
+        out.extend_from_slice(&(12 + data_len as u64).to_le_bytes());
+        for block in 0..blocks {
+            for channel in channels {
+                let start = block * block_size;
+                for i in start..start + block_size {
+                    // LSB-first on disk, zero padding in the last block
+                    out.push(channel.get(i).map(|b| b.reverse_bits()).unwrap_or(0));
+                }
+            }
+        }
+
+        if let Some(tag) = tag {
+            out.extend_from_slice(tag);
+        }
+        out
+    }
+
+    fn test_channels(len: usize) -> Vec<Vec<u8>> {
+        vec![
+            (0..len).map(|i| (i % 251) as u8).collect(),
+            (0..len).map(|i| (i % 241) as u8 ^ 0xFF).collect(),
+        ]
+    }
+
+    #[test]
+    fn reads_header_and_samples() {
+        let channels = test_channels(10_000);
+        let file = build_dsf(&channels, 2_822_400, 4096, None);
+        let mut reader = DsfReader::new(Cursor::new(file)).unwrap();
+
+        assert_eq!(reader.info().channels, 2);
+        assert_eq!(reader.info().sample_rate, 2_822_400);
+        assert_eq!(reader.info().sample_count, 80_000);
+
+        let mut out = vec![Vec::new(), Vec::new()];
+        let mut total = 0;
+        loop {
+            let n = reader.read(&mut out, 3000).unwrap();
+            if n == 0 {
+                break;
+            }
+            total += n;
+        }
+
+        assert_eq!(total, 10_000);
+        assert_eq!(out, channels);
+    }
+
+    #[test]
+    fn seeks_inside_blocks() {
+        let channels = test_channels(10_000);
+        let file = build_dsf(&channels, 2_822_400, 4096, None);
+        let mut reader = DsfReader::new(Cursor::new(file)).unwrap();
+
+        for position in [0u64, 1, 4095, 4096, 5000, 9999] {
+            reader.seek(position).unwrap();
+            let mut out = vec![Vec::new(), Vec::new()];
+            reader.read(&mut out, 1).unwrap();
+            assert_eq!(out[0][0], channels[0][position as usize], "at {}", position);
+            assert_eq!(out[1][0], channels[1][position as usize], "at {}", position);
+        }
+    }
+
+    #[test]
+    fn reads_id3_tag() {
+        let tag = id3::tests::build_id3v23(&[
+            ("TIT2", id3::tests::text("DSF Title")),
+            ("TPE1", id3::tests::text("DSF Artist")),
+        ]);
+        let file = build_dsf(&test_channels(100), 2_822_400, 4096, Some(&tag));
+        let reader = DsfReader::new(Cursor::new(file)).unwrap();
+
+        assert_eq!(reader.tags().title.as_deref(), Some("DSF Title"));
+        assert_eq!(reader.tags().artist.as_deref(), Some("DSF Artist"));
+    }
+}
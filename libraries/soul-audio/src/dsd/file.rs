@@ -0,0 +1,176 @@
+//! DSD file access (DSF and DSDIFF)
+//!
+//! [`DsdFile`] opens `.dsf` and `.dff` files and exposes their 1-bit stream
+//! as packed bytes, one buffer per channel, with the earliest sample in the
+//! most significant bit (the same layout DoP and [`DsdToPcm`] expect).
+//!
+//! [`DsdToPcm`]: super::DsdToPcm
+
+use super::dff::DffReader;
+use super::dsf::DsfReader;
+use super::{DoP, DsdFormat};
+use crate::error::{AudioError, Result};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::time::Duration;
+
+/// DSD container type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DsdContainer {
+    /// Sony DSD Stream File (`.dsf`)
+    Dsf,
+    /// Philips DSD Interchange File Format (`.dff`)
+    Dff,
+}
+
+/// Stream properties of a DSD file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DsdStreamInfo {
+    /// Container the stream was read from
+    pub container: DsdContainer,
+    /// DSD sample rate in Hz (e.g. 2,822,400 for DSD64)
+    pub sample_rate: u32,
+    /// Number of channels
+    pub channels: usize,
+    /// Number of 1-bit samples per channel
+    pub sample_count: u64,
+}
+
+impl DsdStreamInfo {
+    /// Number of packed bytes per channel
+    pub fn bytes_per_channel(&self) -> u64 {
+        self.sample_count.div_ceil(8)
+    }
+
+    /// Duration of the stream
+    pub fn duration(&self) -> Duration {
+        if self.sample_rate == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64(self.sample_count as f64 / self.sample_rate as f64)
+    }
+
+    /// Standard DSD format of the stream, if any
+    pub fn format(&self) -> Option<DsdFormat> {
+        DsdFormat::from_sample_rate(self.sample_rate)
+    }
+
+    /// DoP transport for the stream, if it can be carried over DoP
+    pub fn dop(&self) -> Option<DoP> {
+        DoP::for_dsd_rate(self.sample_rate)
+    }
+}
+
+/// Tags read from a DSD file
+///
+/// DSF files use an ID3v2 tag; DSDIFF files use either an `ID3 ` chunk or
+/// the native `DIIN` chunk (artist and title only).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DsdTags {
+    /// Track title
+    pub title: Option<String>,
+    /// Track artist
+    pub artist: Option<String>,
+    /// Album title
+    pub album: Option<String>,
+    /// Album artist
+    pub album_artist: Option<String>,
+    /// Composer
+    pub composer: Option<String>,
+    /// Genre
+    pub genre: Option<String>,
+    /// Track number
+    pub track_number: Option<u32>,
+    /// Disc number
+    pub disc_number: Option<u32>,
+    /// Release year
+    pub year: Option<i32>,
+    /// Embedded cover art (raw data and MIME type)
+    pub picture: Option<(Vec<u8>, String)>,
+}
+
+/// An open DSD file
+pub enum DsdFile {
+    /// DSF file
+    Dsf(DsfReader<BufReader<File>>),
+    /// DSDIFF file
+    Dff(DffReader<BufReader<File>>),
+}
+
+impl DsdFile {
+    /// Open a DSF or DSDIFF file
+    ///
+    /// The container is detected from the file's magic bytes.
+    pub fn open(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Err(AudioError::FileNotFound(path.display().to_string()));
+        }
+
+        let mut file = File::open(path)?;
+        let mut magic = [0u8; 4];
+        std::io::Read::read_exact(&mut file, &mut magic)?;
+        std::io::Seek::rewind(&mut file)?;
+
+        let reader = BufReader::new(file);
+        match &magic {
+            b"DSD " => Ok(DsdFile::Dsf(DsfReader::new(reader)?)),
+            b"FRM8" => Ok(DsdFile::Dff(DffReader::new(reader)?)),
+            _ => Err(AudioError::UnsupportedFormat(format!(
+                "{} is not a DSF or DSDIFF file",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Check whether a path has a DSD file extension (`.dsf` / `.dff`)
+    pub fn is_dsd_path(path: &Path) -> bool {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.eq_ignore_ascii_case("dsf") || e.eq_ignore_ascii_case("dff"))
+            .unwrap_or(false)
+    }
+
+    /// Stream properties
+    pub fn info(&self) -> &DsdStreamInfo {
+        match self {
+            DsdFile::Dsf(r) => r.info(),
+            DsdFile::Dff(r) => r.info(),
+        }
+    }
+
+    /// Tags stored in the file
+    pub fn tags(&self) -> &DsdTags {
+        match self {
+            DsdFile::Dsf(r) => r.tags(),
+            DsdFile::Dff(r) => r.tags(),
+        }
+    }
+
+    /// Read up to `max_bytes` packed bytes per channel
+    ///
+    /// Appends the same number of bytes to each buffer in `out` (one per
+    /// channel) and returns that count. Returns 0 at the end of the stream.
+    pub fn read(&mut self, out: &mut [Vec<u8>], max_bytes: usize) -> Result<usize> {
+        match self {
+            DsdFile::Dsf(r) => r.read(out, max_bytes),
+            DsdFile::Dff(r) => r.read(out, max_bytes),
+        }
+    }
+
+    /// Seek to a byte position (per channel) in the stream
+    pub fn seek(&mut self, byte_position: u64) -> Result<()> {
+        match self {
+            DsdFile::Dsf(r) => r.seek(byte_position),
+            DsdFile::Dff(r) => r.seek(byte_position),
+        }
+    }
+
+    /// Current byte position (per channel) in the stream
+    pub fn position(&self) -> u64 {
+        match self {
+            DsdFile::Dsf(r) => r.position(),
+            DsdFile::Dff(r) => r.position(),
+        }
+    }
+}
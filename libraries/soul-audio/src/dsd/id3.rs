@@ -0,0 +1,393 @@
+//! Minimal ID3v2 reader for DSD files
+//!
+//! DSF files carry an ID3v2 tag at the end of the file (pointed to by the
+//! `DSD ` header chunk) and many DSDIFF writers add an unofficial `ID3 ` chunk.
+//! Neither container is understood by the general purpose tag libraries, so
+//! this module parses the handful of frames the library needs.
+//!
+//! Supports ID3v2.2, v2.3 and v2.4 text frames (all four text encodings),
+//! unsynchronisation, and attached pictures (`APIC` / `PIC`).
+
+use super::file::DsdTags;
+
+/// Parse an ID3v2 tag into [`DsdTags`]
+///
+/// Returns `None` if `data` does not start with a valid ID3v2 header.
+/// Unknown, compressed or encrypted frames are skipped.
+pub(crate) fn parse_id3v2(data: &[u8]) -> Option<DsdTags> {
+    if data.len() < 10 || &data[0..3] != b"ID3" {
+        return None;
+    }
+
+    let major = data[3];
+    if !(2..=4).contains(&major) {
+        return None;
+    }
+    let flags = data[5];
+    let size = syncsafe(&data[6..10]) as usize;
+    let end = (10 + size).min(data.len());
+
+    // v2.2/v2.3 apply unsynchronisation to the whole tag
+    let body = if flags & 0x80 != 0 && major < 4 {
+        remove_unsync(&data[10..end])
+    } else {
+        data[10..end].to_vec()
+    };
+
+    let mut pos = 0;
+
+    // Skip extended header
+    if flags & 0x40 != 0 && major >= 3 {
+        if body.len() < 4 {
+            return None;
+        }
+        pos = if major == 3 {
+            u32::from_be_bytes([body[0], body[1], body[2], body[3]]) as usize + 4
+        } else {
+            syncsafe(&body[0..4]) as usize
+        };
+    }
+
+    let mut tags = DsdTags::default();
+    let mut front_cover = false;
+
+    let (id_len, header_len) = if major == 2 { (3, 6) } else { (4, 10) };
+
+    while pos + header_len <= body.len() {
+        let id = &body[pos..pos + id_len];
+        if id[0] == 0 {
+            // Padding
+            break;
+        }
+
+        let frame_size = match major {
+            2 => u32::from_be_bytes([0, body[pos + 3], body[pos + 4], body[pos + 5]]) as usize,
+            3 => u32::from_be_bytes([
+                body[pos + 4],
+                body[pos + 5],
+                body[pos + 6],
+                body[pos + 7],
+            ]) as usize,
+            _ => syncsafe(&body[pos + 4..pos + 8]) as usize,
+        };
+        let frame_flags = if major == 2 {
+            0
+        } else {
+            u16::from_be_bytes([body[pos + 8], body[pos + 9]])
+        };
+
+        let start = pos + header_len;
+        let stop = start + frame_size;
+        if stop > body.len() {
+            break;
+        }
+        pos = stop;
+
+        let mut frame = &body[start..stop];
+        let unsynced;
+
+        if major == 3 && frame_flags & 0x00C0 != 0 {
+            // Compressed or encrypted
+            continue;
+        }
+        if major == 4 {
+            if frame_flags & 0x000C != 0 {
+                // Compressed or encrypted
+                continue;
+            }
+            if frame_flags & 0x0001 != 0 {
+                // Data length indicator
+                if frame.len() < 4 {
+                    continue;
+                }
+                frame = &frame[4..];
+            }
+            if frame_flags & 0x0002 != 0 {
+                unsynced = remove_unsync(frame);
+                frame = &unsynced;
+            }
+        }
+
+        let id = std::str::from_utf8(id).unwrap_or("");
+        match id {
+            "TIT2" | "TT2" => tags.title = text_frame(frame),
+            "TPE1" | "TP1" => tags.artist = text_frame(frame),
+            "TALB" | "TAL" => tags.album = text_frame(frame),
+            "TPE2" | "TP2" => tags.album_artist = text_frame(frame),
+            "TCOM" | "TCM" => tags.composer = text_frame(frame),
+            "TCON" | "TCO" => tags.genre = text_frame(frame).and_then(|g| clean_genre(&g)),
+            "TRCK" | "TRK" => tags.track_number = text_frame(frame).and_then(|t| parse_index(&t)),
+            "TPOS" | "TPA" => tags.disc_number = text_frame(frame).and_then(|t| parse_index(&t)),
+            "TDRC" | "TYER" | "TYE" if tags.year.is_none() || id == "TDRC" => {
+                tags.year = text_frame(frame).and_then(|t| parse_year(&t));
+            }
+            "APIC" | "PIC" if !front_cover => {
+                if let Some((data, mime, picture_type)) = picture_frame(frame, id == "PIC") {
+                    front_cover = picture_type == 3;
+                    if front_cover || tags.picture.is_none() {
+                        tags.picture = Some((data, mime));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Some(tags)
+}
+
+/// Decode a 28-bit sync-safe integer
+fn syncsafe(bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .take(4)
+        .fold(0u32, |acc, &b| (acc << 7) | u32::from(b & 0x7F))
+}
+
+/// Undo ID3 unsynchronisation (`FF 00` -> `FF`)
+fn remove_unsync(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut prev_ff = false;
+    for &b in data {
+        if prev_ff && b == 0 {
+            prev_ff = false;
+            continue;
+        }
+        out.push(b);
+        prev_ff = b == 0xFF;
+    }
+    out
+}
+
+/// Decode the first value of a text frame
+fn text_frame(frame: &[u8]) -> Option<String> {
+    let (&encoding, text) = frame.split_first()?;
+    let (value, _) = decode_string(encoding, text);
+    let value = value.trim().to_string();
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+/// Decode a null-terminated string, returning it and the remaining bytes
+fn decode_string(encoding: u8, data: &[u8]) -> (String, &[u8]) {
+    match encoding {
+        // UTF-16 with BOM / UTF-16BE
+        1 | 2 => {
+            let mut end = data.len() - data.len() % 2;
+            let mut rest_start = end;
+            for i in (0..end).step_by(2) {
+                if data[i] == 0 && data[i + 1] == 0 {
+                    end = i;
+                    rest_start = i + 2;
+                    break;
+                }
+            }
+            let mut bytes = &data[..end];
+            let mut big_endian = encoding == 2;
+            if encoding == 1 && bytes.len() >= 2 {
+                match (bytes[0], bytes[1]) {
+                    (0xFF, 0xFE) => {
+                        big_endian = false;
+                        bytes = &bytes[2..];
+                    }
+                    (0xFE, 0xFF) => {
+                        big_endian = true;
+                        bytes = &bytes[2..];
+                    }
+                    _ => {}
+                }
+            }
+            let units: Vec<u16> = bytes
+                .chunks_exact(2)
+                .map(|c| {
+                    if big_endian {
+                        u16::from_be_bytes([c[0], c[1]])
+                    } else {
+                        u16::from_le_bytes([c[0], c[1]])
+                    }
+                })
+                .collect();
+            (String::from_utf16_lossy(&units), &data[rest_start..])
+        }
+        // ISO-8859-1 / UTF-8
+        _ => {
+            let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+            let rest = &data[(end + 1).min(data.len())..];
+            let value = if encoding == 3 {
+                String::from_utf8_lossy(&data[..end]).into_owned()
+            } else {
+                data[..end].iter().map(|&b| b as char).collect()
+            };
+            (value, rest)
+        }
+    }
+}
+
+/// Decode an attached picture frame into (data, MIME type, picture type)
+fn picture_frame(frame: &[u8], v22: bool) -> Option<(Vec<u8>, String, u8)> {
+    let (&encoding, rest) = frame.split_first()?;
+
+    let (mime, rest) = if v22 {
+        // 3-character image format
+        if rest.len() < 3 {
+            return None;
+        }
+        let mime = match &rest[..3] {
+            b"PNG" | b"png" => "image/png",
+            _ => "image/jpeg",
+        };
+        (mime.to_string(), &rest[3..])
+    } else {
+        let (mime, rest) = decode_string(0, rest);
+        let mime = match mime.as_str() {
+            "" | "image/jpg" | "jpg" | "JPG" => "image/jpeg".to_string(),
+            "png" | "PNG" => "image/png".to_string(),
+            _ => mime,
+        };
+        (mime, rest)
+    };
+
+    let (&picture_type, rest) = rest.split_first()?;
+    let (_description, data) = decode_string(encoding, rest);
+    if data.is_empty() {
+        return None;
+    }
+
+    Some((data.to_vec(), mime, picture_type))
+}
+
+/// Parse "3" or "3/12" into 3
+fn parse_index(value: &str) -> Option<u32> {
+    value.split('/').next()?.trim().parse().ok().filter(|&n| n > 0)
+}
+
+/// Parse the year from "2004", "2004-05-01" or "2004-05-01T12:00"
+fn parse_year(value: &str) -> Option<i32> {
+    value.get(..4)?.parse().ok()
+}
+
+/// Strip ID3v1 genre references such as "(17)Rock" down to "Rock"
+fn clean_genre(value: &str) -> Option<String> {
+    let mut genre = value;
+    while let Some(rest) = genre.strip_prefix('(') {
+        match rest.find(')') {
+            Some(close) if !rest[close + 1..].is_empty() => genre = &rest[close + 1..],
+            _ => break,
+        }
+    }
+    let genre = genre.trim();
+    if genre.is_empty() {
+        None
+    } else {
+        Some(genre.to_string())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Build an ID3v2.3 tag from (frame id, frame body) pairs
+    pub(crate) fn build_id3v23(frames: &[(&str, Vec<u8>)]) -> Vec<u8> {
+        let mut body = Vec::new();
+        for (id, data) in frames {
+            body.extend_from_slice(id.as_bytes());
+            body.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            body.extend_from_slice(&[0, 0]);
+            body.extend_from_slice(data);
+        }
+        let size = body.len() as u32;
+        let mut tag = b"ID3\x03\x00\x00".to_vec();
+        tag.extend_from_slice(&[
+            ((size >> 21) & 0x7F) as u8,
+            ((size >> 14) & 0x7F) as u8,
+            ((size >> 7) & 0x7F) as u8,
+            (size & 0x7F) as u8,
+        ]);
+        tag.extend_from_slice(&body);
+        tag
+    }
+
+    /// Text frame body in the given encoding
+    pub(crate) fn text(value: &str) -> Vec<u8> {
+        let mut data = vec![3];
+        data.extend_from_slice(value.as_bytes());
+        data
+    }
+
+    #[test]
+    fn parses_text_frames() {
+        let tag = build_id3v23(&[
+            ("TIT2", text("Title")),
+            ("TPE1", text("Artist")),
+            ("TALB", text("Album")),
+            ("TPE2", text("Album Artist")),
+            ("TRCK", text("3/12")),
+            ("TPOS", text("2")),
+            ("TYER", text("1999")),
+            ("TCON", text("(17)Rock")),
+        ]);
+
+        let tags = parse_id3v2(&tag).unwrap();
+        assert_eq!(tags.title.as_deref(), Some("Title"));
+        assert_eq!(tags.artist.as_deref(), Some("Artist"));
+        assert_eq!(tags.album.as_deref(), Some("Album"));
+        assert_eq!(tags.album_artist.as_deref(), Some("Album Artist"));
+        assert_eq!(tags.track_number, Some(3));
+        assert_eq!(tags.disc_number, Some(2));
+        assert_eq!(tags.year, Some(1999));
+        assert_eq!(tags.genre.as_deref(), Some("Rock"));
+    }
+
+    #[test]
+    fn parses_utf16_and_latin1() {
+        let mut utf16 = vec![1, 0xFF, 0xFE];
+        for unit in "Señor".encode_utf16() {
+            utf16.extend_from_slice(&unit.to_le_bytes());
+        }
+        utf16.extend_from_slice(&[0, 0]);
+
+        let latin1 = vec![0, b'C', 0xE9, b'o'];
+
+        let tag = build_id3v23(&[("TIT2", utf16), ("TPE1", latin1)]);
+        let tags = parse_id3v2(&tag).unwrap();
+        assert_eq!(tags.title.as_deref(), Some("Señor"));
+        assert_eq!(tags.artist.as_deref(), Some("Céo"));
+    }
+
+    #[test]
+    fn prefers_front_cover() {
+        let mut other = vec![0];
+        other.extend_from_slice(b"image/png\0");
+        other.push(0); // Other
+        other.extend_from_slice(b"\0");
+        other.extend_from_slice(&[1, 2, 3]);
+
+        let mut front = vec![0];
+        front.extend_from_slice(b"image/jpeg\0");
+        front.push(3); // Front cover
+        front.extend_from_slice(b"cover\0");
+        front.extend_from_slice(&[4, 5, 6]);
+
+        let tag = build_id3v23(&[("APIC", other), ("APIC", front)]);
+        let tags = parse_id3v2(&tag).unwrap();
+        assert_eq!(
+            tags.picture,
+            Some((vec![4, 5, 6], "image/jpeg".to_string()))
+        );
+    }
+
+    #[test]
+    fn rejects_non_id3_data() {
+        assert!(parse_id3v2(b"not a tag").is_none());
+        assert!(parse_id3v2(b"ID3\x09\x00\x00\x00\x00\x00\x00").is_none());
+    }
+
+    #[test]
+    fn removes_unsynchronisation() {
+        assert_eq!(remove_unsync(&[0xFF, 0x00, 0xE0, 0x01]), vec![0xFF, 0xE0, 0x01]);
+    }
+}
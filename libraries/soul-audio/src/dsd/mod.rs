@@ -2,8 +2,9 @@
 //!
 //! Provides conversion between PCM and DSD formats:
 //! - PCM to DSD conversion using sigma-delta modulation
-//! - DSD to PCM conversion (future: for playback on non-DSD DACs)
+//! - DSD to PCM conversion (for playback on non-DSD DACs)
 //! - DoP (DSD over PCM) encoding/decoding
+//! - DSF and DSDIFF file readers
 //!
 //! # DSD Rates
 //!
@@ -29,10 +30,43 @@
 //! println!("Generated {} DSD bytes", dsd_output.len());
 //! ```
 
+//!
+//! # Example: Playing a DSF File as PCM
+//!
+//! ```rust,no_run
+//! use soul_audio::dsd::{DsdFile, DsdToPcm};
+//! use std::path::Path;
+//!
+//! # fn example() -> soul_audio::Result<()> {
+//! let mut file = DsdFile::open(Path::new("/music/album/01.dsf"))?;
+//! let info = *file.info();
+//! let mut decimator = DsdToPcm::for_dsd_rate(info.sample_rate, info.channels)?;
+//!
+//! let mut dsd = vec![Vec::new(); info.channels];
+//! let mut pcm = Vec::new();
+//! while file.read(&mut dsd, 4096)? > 0 {
+//!     let channels: Vec<&[u8]> = dsd.iter().map(|c| c.as_slice()).collect();
+//!     decimator.process(&channels, &mut pcm);
+//!     dsd.iter_mut().for_each(Vec::clear);
+//! }
+//! decimator.flush(&mut pcm);
+//! # Ok(())
+//! # }
+//! ```
+
 mod converter;
+mod decimator;
+mod dff;
 mod dop;
+mod dsf;
+mod file;
+mod id3;
 mod noise_shaper;
 
 pub use converter::{DsdConverter, DsdFormat, DsdSettings};
+pub use decimator::{downmix_to_stereo, DsdToPcm};
+pub use dff::DffReader;
 pub use dop::{DoP, DopDecoder, DopEncoder};
+pub use dsf::DsfReader;
+pub use file::{DsdContainer, DsdFile, DsdStreamInfo, DsdTags};
 pub use noise_shaper::{NoiseShaper, NoiseShaperOrder};
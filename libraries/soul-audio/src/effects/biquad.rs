@@ -0,0 +1,192 @@
+/// Multi-channel biquad used by the crossover and dynamic EQ effects
+///
+/// Transposed direct form II with state for up to `MAX_CHANNELS`
+/// interleaved channels, so one filter instance serves any layout.
+/// Coefficients follow the RBJ Audio EQ Cookbook.
+use crate::channels::MAX_CHANNELS;
+use std::f32::consts::PI;
+
+/// Butterworth Q; two cascaded sections make a Linkwitz-Riley 4th-order filter
+pub(super) const BUTTERWORTH_Q: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    z1: [f32; MAX_CHANNELS],
+    z2: [f32; MAX_CHANNELS],
+}
+
+impl Default for Biquad {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Biquad {
+    /// Create a pass-through filter
+    pub(super) fn new() -> Self {
+        Self {
+            b0: 1.0,
+            b1: 0.0,
+            b2: 0.0,
+            a1: 0.0,
+            a2: 0.0,
+            z1: [0.0; MAX_CHANNELS],
+            z2: [0.0; MAX_CHANNELS],
+        }
+    }
+
+    /// Angular frequency terms (sin, cos), with the frequency kept below Nyquist
+    fn omega(sample_rate: f32, frequency: f32) -> (f32, f32) {
+        let frequency = frequency.clamp(1.0, sample_rate * 0.45);
+        let omega = 2.0 * PI * frequency / sample_rate;
+        (omega.sin(), omega.cos())
+    }
+
+    fn set_normalized(&mut self, b0: f32, b1: f32, b2: f32, a0: f32, a1: f32, a2: f32) {
+        self.b0 = b0 / a0;
+        self.b1 = b1 / a0;
+        self.b2 = b2 / a0;
+        self.a1 = a1 / a0;
+        self.a2 = a2 / a0;
+    }
+
+    /// Configure as a second-order low-pass
+    pub(super) fn set_lowpass(&mut self, sample_rate: f32, frequency: f32, q: f32) {
+        let (sin_omega, cos_omega) = Self::omega(sample_rate, frequency);
+        let alpha = sin_omega / (2.0 * q);
+        let b1 = 1.0 - cos_omega;
+        self.set_normalized(
+            b1 / 2.0,
+            b1,
+            b1 / 2.0,
+            1.0 + alpha,
+            -2.0 * cos_omega,
+            1.0 - alpha,
+        );
+    }
+
+    /// Configure as a second-order high-pass
+    pub(super) fn set_highpass(&mut self, sample_rate: f32, frequency: f32, q: f32) {
+        let (sin_omega, cos_omega) = Self::omega(sample_rate, frequency);
+        let alpha = sin_omega / (2.0 * q);
+        let b1 = -(1.0 + cos_omega);
+        self.set_normalized(
+            -b1 / 2.0,
+            b1,
+            -b1 / 2.0,
+            1.0 + alpha,
+            -2.0 * cos_omega,
+            1.0 - alpha,
+        );
+    }
+
+    /// Configure as a second-order all-pass
+    pub(super) fn set_allpass(&mut self, sample_rate: f32, frequency: f32, q: f32) {
+        let (sin_omega, cos_omega) = Self::omega(sample_rate, frequency);
+        let alpha = sin_omega / (2.0 * q);
+        self.set_normalized(
+            1.0 - alpha,
+            -2.0 * cos_omega,
+            1.0 + alpha,
+            1.0 + alpha,
+            -2.0 * cos_omega,
+            1.0 - alpha,
+        );
+    }
+
+    /// Configure as a band-pass with 0 dB peak gain
+    pub(super) fn set_bandpass(&mut self, sample_rate: f32, frequency: f32, q: f32) {
+        let (sin_omega, cos_omega) = Self::omega(sample_rate, frequency);
+        let alpha = sin_omega / (2.0 * q);
+        self.set_normalized(
+            alpha,
+            0.0,
+            -alpha,
+            1.0 + alpha,
+            -2.0 * cos_omega,
+            1.0 - alpha,
+        );
+    }
+
+    /// Configure as a peaking filter
+    pub(super) fn set_peaking(&mut self, sample_rate: f32, frequency: f32, q: f32, gain_db: f32) {
+        let (sin_omega, cos_omega) = Self::omega(sample_rate, frequency);
+        let a = 10.0_f32.powf(gain_db / 40.0);
+        let alpha = sin_omega / (2.0 * q);
+        self.set_normalized(
+            1.0 + alpha * a,
+            -2.0 * cos_omega,
+            1.0 - alpha * a,
+            1.0 + alpha / a,
+            -2.0 * cos_omega,
+            1.0 - alpha / a,
+        );
+    }
+
+    /// Filter one sample of a channel
+    #[inline]
+    pub(super) fn process(&mut self, channel: usize, input: f32) -> f32 {
+        let mut output = self.b0 * input + self.z1[channel];
+        // Flush denormals to keep the recursion cheap on decaying tails
+        if output.abs() < 1e-15 {
+            output = 0.0;
+        }
+        self.z1[channel] = self.b1 * input - self.a1 * output + self.z2[channel];
+        self.z2[channel] = self.b2 * input - self.a2 * output;
+        output
+    }
+
+    /// Clear filter state (coefficients are kept)
+    pub(super) fn reset(&mut self) {
+        self.z1 = [0.0; MAX_CHANNELS];
+        self.z2 = [0.0; MAX_CHANNELS];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Steady-state gain for a sine, measured as RMS over the second half second
+    fn gain_at(filter: &mut Biquad, frequency: f32, sample_rate: f32) -> f32 {
+        filter.reset();
+        let total = sample_rate as usize;
+        let mut sum_sq = 0.0f64;
+        for i in 0..total {
+            let x = (2.0 * PI * frequency * i as f32 / sample_rate).sin();
+            let y = filter.process(0, x);
+            if i >= total / 2 {
+                sum_sq += (y as f64) * (y as f64);
+            }
+        }
+        ((sum_sq / (total / 2) as f64).sqrt() * std::f64::consts::SQRT_2) as f32
+    }
+
+    #[test]
+    fn test_allpass_keeps_magnitude() {
+        let mut filter = Biquad::new();
+        filter.set_allpass(48000.0, 1000.0, BUTTERWORTH_Q);
+        for frequency in [100.0, 1000.0, 10000.0] {
+            let gain = gain_at(&mut filter, frequency, 48000.0);
+            assert!((gain - 1.0).abs() < 0.01, "{} Hz: {}", frequency, gain);
+        }
+    }
+
+    #[test]
+    fn test_lowpass_and_highpass_split() {
+        let mut lowpass = Biquad::new();
+        let mut highpass = Biquad::new();
+        lowpass.set_lowpass(48000.0, 1000.0, BUTTERWORTH_Q);
+        highpass.set_highpass(48000.0, 1000.0, BUTTERWORTH_Q);
+
+        assert!(gain_at(&mut lowpass, 100.0, 48000.0) > 0.99);
+        assert!(gain_at(&mut lowpass, 10000.0, 48000.0) < 0.02);
+        assert!(gain_at(&mut highpass, 10000.0, 48000.0) > 0.99);
+        assert!(gain_at(&mut highpass, 100.0, 48000.0) < 0.02);
+    }
+}
@@ -3,6 +3,7 @@
 /// This module provides a trait-based architecture for chaining audio effects.
 /// Effects are processed in order, and all operate on f32 samples in [-1.0, 1.0] range.
 
+use crate::channels::ChannelLayout;
 use std::any::Any;
 
 /// Trait for audio effects that can be chained together
@@ -35,6 +36,45 @@ pub trait AudioEffect: Send {
     /// Get effect name (for debugging)
     fn name(&self) -> &str;
 
+    /// Check whether the effect can process a channel layout
+    ///
+    /// Effects built around a left/right pair keep the default (stereo only).
+    /// `EffectChain` bypasses effects that don't support the current layout.
+    fn supports_layout(&self, layout: ChannelLayout) -> bool {
+        layout == ChannelLayout::Stereo
+    }
+
+    /// Process audio buffer in-place in the given channel layout
+    ///
+    /// Only called with layouts accepted by `supports_layout()`. The default
+    /// forwards stereo buffers to `process()`.
+    ///
+    /// # Arguments
+    /// * `buffer` - Interleaved samples in `layout` channel order
+    /// * `sample_rate` - Sample rate in Hz
+    /// * `layout` - Channel layout of `buffer`
+    fn process_layout(&mut self, buffer: &mut [f32], sample_rate: u32, layout: ChannelLayout) {
+        if layout == ChannelLayout::Stereo {
+            self.process(buffer, sample_rate);
+        }
+    }
+
+    /// Prepare for a stream sample rate
+    ///
+    /// Called from the control thread when the output rate changes, before
+    /// `process()` sees the new rate, so effects can do allocating work
+    /// (designing filters, resampling IRs) off the audio thread. The default
+    /// does nothing.
+    fn set_sample_rate(&mut self, _sample_rate: u32) {}
+
+    /// Processing delay in frames
+    ///
+    /// Effects that delay the signal (such as linear-phase filters) report
+    /// it so playback position can be compensated. Defaults to zero.
+    fn latency_samples(&self) -> usize {
+        0
+    }
+
     /// Get a reference to self as Any for downcasting
     /// Required for in-place parameter updates without rebuilding
     fn as_any(&self) -> &dyn Any;
@@ -47,6 +87,8 @@ pub trait AudioEffect: Send {
 /// Chain of audio effects processed in order
 pub struct EffectChain {
     effects: Vec<Box<dyn AudioEffect>>,
+    /// Rate set by `set_sample_rate()`, applied to effects added later
+    sample_rate: Option<u32>,
 }
 
 impl EffectChain {
@@ -54,14 +96,31 @@ impl EffectChain {
     pub fn new() -> Self {
         Self {
             effects: Vec::new(),
+            sample_rate: None,
         }
     }
 
     /// Add an effect to the end of the chain
-    pub fn add_effect(&mut self, effect: Box<dyn AudioEffect>) {
+    ///
+    /// The effect is prepared for the chain's sample rate, if one is set.
+    pub fn add_effect(&mut self, mut effect: Box<dyn AudioEffect>) {
+        if let Some(sample_rate) = self.sample_rate {
+            effect.set_sample_rate(sample_rate);
+        }
         self.effects.push(effect);
     }
 
+    /// Prepare all effects for a stream sample rate
+    ///
+    /// Call from the control thread when the output format changes; effects
+    /// added later are prepared for the same rate.
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.sample_rate = Some(sample_rate);
+        for effect in &mut self.effects {
+            effect.set_sample_rate(sample_rate);
+        }
+    }
+
     /// Process audio through the entire effect chain
     ///
     /// # Arguments
@@ -79,6 +138,60 @@ impl EffectChain {
         }
     }
 
+    /// Process audio in any channel layout
+    ///
+    /// Stereo buffers behave exactly like `process()`. For other layouts,
+    /// effects that don't support the layout are bypassed; use
+    /// [`Self::bypassed_effects`] to let the user know.
+    ///
+    /// # Arguments
+    /// * `buffer` - Interleaved samples in `layout` channel order
+    /// * `sample_rate` - Sample rate in Hz
+    /// * `layout` - Channel layout of `buffer`
+    pub fn process_layout(&mut self, buffer: &mut [f32], sample_rate: u32, layout: ChannelLayout) {
+        if layout == ChannelLayout::Stereo {
+            self.process(buffer, sample_rate);
+            return;
+        }
+
+        for effect in &mut self.effects {
+            if effect.is_enabled() && effect.supports_layout(layout) {
+                effect.process_layout(buffer, sample_rate, layout);
+            }
+        }
+    }
+
+    /// Check whether every enabled effect supports a channel layout
+    pub fn supports_layout(&self, layout: ChannelLayout) -> bool {
+        self.effects
+            .iter()
+            .all(|effect| !effect.is_enabled() || effect.supports_layout(layout))
+    }
+
+    /// Names of the enabled effects `process_layout()` bypasses in a layout
+    ///
+    /// Stereo-only effects (EQs, crossfeed, convolution) don't run on
+    /// other layouts, e.g. a 5.1 output device.
+    pub fn bypassed_effects(&self, layout: ChannelLayout) -> Vec<&str> {
+        if layout == ChannelLayout::Stereo {
+            return Vec::new();
+        }
+        self.effects
+            .iter()
+            .filter(|effect| effect.is_enabled() && !effect.supports_layout(layout))
+            .map(|effect| effect.name())
+            .collect()
+    }
+
+    /// Total processing delay of the enabled effects in frames
+    pub fn latency_samples(&self) -> usize {
+        self.effects
+            .iter()
+            .filter(|effect| effect.is_enabled())
+            .map(|effect| effect.latency_samples())
+            .sum()
+    }
+
     /// Reset all effects in the chain
     pub fn reset(&mut self) {
         for effect in &mut self.effects {
@@ -126,7 +239,10 @@ impl EffectChain {
     ///
     /// Returns the old effect if one was replaced, None otherwise.
     /// This preserves other effects in the chain (doesn't clear everything).
-    pub fn replace_effect(&mut self, index: usize, effect: Box<dyn AudioEffect>) -> Option<Box<dyn AudioEffect>> {
+    pub fn replace_effect(&mut self, index: usize, mut effect: Box<dyn AudioEffect>) -> Option<Box<dyn AudioEffect>> {
+        if let Some(sample_rate) = self.sample_rate {
+            effect.set_sample_rate(sample_rate);
+        }
         if index < self.effects.len() {
             Some(std::mem::replace(&mut self.effects[index], effect))
         } else if index == self.effects.len() {
@@ -317,6 +433,29 @@ mod tests {
         assert!(chain.get_effect(1).is_none());
     }
 
+    #[test]
+    fn stereo_only_effects_bypassed_for_surround() {
+        let mut chain = EffectChain::new();
+        chain.add_effect(Box::new(GainEffect {
+            gain: 0.5,
+            enabled: true,
+        }));
+
+        assert!(chain.supports_layout(ChannelLayout::Stereo));
+        assert!(!chain.supports_layout(ChannelLayout::Surround51));
+        assert!(chain.bypassed_effects(ChannelLayout::Stereo).is_empty());
+        assert_eq!(chain.bypassed_effects(ChannelLayout::Surround51), vec!["Gain"]);
+
+        // Surround buffer passes through untouched
+        let mut buffer = vec![1.0; 60];
+        chain.process_layout(&mut buffer, 44100, ChannelLayout::Surround51);
+        assert!(buffer.iter().all(|&s| s == 1.0));
+
+        // Stereo buffer is processed as before
+        chain.process_layout(&mut buffer, 44100, ChannelLayout::Stereo);
+        assert!(buffer.iter().all(|&s| (s - 0.5).abs() < 0.0001));
+    }
+
     #[test]
     fn enable_disable_all() {
         let mut chain = EffectChain::new();
@@ -339,4 +478,117 @@ mod tests {
             assert!((sample - 1.0).abs() < 0.0001);
         }
     }
+
+    #[test]
+    fn latency_sums_enabled_effects() {
+        // Reports a fixed delay without processing
+        struct LatentEffect {
+            latency: usize,
+            enabled: bool,
+        }
+
+        impl AudioEffect for LatentEffect {
+            fn process(&mut self, _buffer: &mut [f32], _sample_rate: u32) {}
+
+            fn reset(&mut self) {}
+
+            fn set_enabled(&mut self, enabled: bool) {
+                self.enabled = enabled;
+            }
+
+            fn is_enabled(&self) -> bool {
+                self.enabled
+            }
+
+            fn name(&self) -> &str {
+                "Latent"
+            }
+
+            fn latency_samples(&self) -> usize {
+                self.latency
+            }
+
+            fn as_any(&self) -> &dyn Any {
+                self
+            }
+
+            fn as_any_mut(&mut self) -> &mut dyn Any {
+                self
+            }
+        }
+
+        let mut chain = EffectChain::new();
+        chain.add_effect(Box::new(LatentEffect {
+            latency: 4096,
+            enabled: true,
+        }));
+        chain.add_effect(Box::new(GainEffect {
+            gain: 0.5,
+            enabled: true,
+        }));
+        chain.add_effect(Box::new(LatentEffect {
+            latency: 64,
+            enabled: false,
+        }));
+
+        assert_eq!(chain.latency_samples(), 4096);
+
+        chain.set_enabled(false);
+        assert_eq!(chain.latency_samples(), 0);
+    }
+
+    #[test]
+    fn sample_rate_reaches_current_and_later_effects() {
+        // Records the rate it was prepared for
+        struct RateEffect {
+            sample_rate: Option<u32>,
+        }
+
+        impl AudioEffect for RateEffect {
+            fn process(&mut self, _buffer: &mut [f32], _sample_rate: u32) {}
+
+            fn reset(&mut self) {}
+
+            fn set_enabled(&mut self, _enabled: bool) {}
+
+            fn is_enabled(&self) -> bool {
+                true
+            }
+
+            fn name(&self) -> &str {
+                "Rate"
+            }
+
+            fn set_sample_rate(&mut self, sample_rate: u32) {
+                self.sample_rate = Some(sample_rate);
+            }
+
+            fn as_any(&self) -> &dyn Any {
+                self
+            }
+
+            fn as_any_mut(&mut self) -> &mut dyn Any {
+                self
+            }
+        }
+
+        let rate_of = |chain: &EffectChain, index| {
+            chain
+                .get_effect_as::<RateEffect>(index)
+                .unwrap()
+                .sample_rate
+        };
+
+        let mut chain = EffectChain::new();
+        chain.add_effect(Box::new(RateEffect { sample_rate: None }));
+        assert_eq!(rate_of(&chain, 0), None);
+
+        chain.set_sample_rate(96000);
+        assert_eq!(rate_of(&chain, 0), Some(96000));
+
+        chain.add_effect(Box::new(RateEffect { sample_rate: None }));
+        chain.replace_effect(0, Box::new(RateEffect { sample_rate: None }));
+        assert_eq!(rate_of(&chain, 0), Some(96000));
+        assert_eq!(rate_of(&chain, 1), Some(96000));
+    }
 }
@@ -3,6 +3,7 @@
 /// Reduces the dynamic range of audio by attenuating signals above a threshold.
 /// Useful for making quiet parts louder and loud parts quieter.
 use super::chain::AudioEffect;
+use crate::channels::ChannelLayout;
 
 /// Compressor settings
 #[derive(Debug, Clone, Copy)]
@@ -326,10 +327,19 @@ impl Default for Compressor {
     }
 }
 
-impl AudioEffect for Compressor {
-    fn process(&mut self, buffer: &mut [f32], sample_rate: u32) {
+impl Compressor {
+    /// Compress interleaved audio with any channel count
+    ///
+    /// Detection is linked: the loudest channel of each frame drives the
+    /// gain, which is applied to all channels to preserve the image.
+    pub(super) fn process_interleaved(
+        &mut self,
+        buffer: &mut [f32],
+        sample_rate: u32,
+        channels: usize,
+    ) {
         // Bypass if disabled
-        if !self.enabled {
+        if !self.enabled || channels == 0 {
             return;
         }
 
@@ -342,10 +352,9 @@ impl AudioEffect for Compressor {
         // Update coefficients if needed
         self.update_coefficients();
 
-        // Process interleaved stereo buffer with linked stereo detection
-        for chunk in buffer.chunks_exact_mut(2) {
-            // For linked stereo, use the louder channel (peak of both)
-            let max_sample = chunk[0].abs().max(chunk[1].abs());
+        for frame in buffer.chunks_exact_mut(channels) {
+            // For linked detection, use the loudest channel
+            let max_sample = frame.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
 
             // Convert instantaneous level to dB
             let input_db = if max_sample > 1e-10 {
@@ -368,12 +377,28 @@ impl AudioEffect for Compressor {
             // Convert smoothed gain reduction to linear
             let gain = 10.0_f32.powf(self.gain_reduction_db / 20.0);
 
-            // Apply same gain to both channels (linked stereo)
-            // This preserves stereo image
-            chunk[0] = chunk[0] * gain * self.makeup_gain_linear;
-            chunk[1] = chunk[1] * gain * self.makeup_gain_linear;
+            // Apply same gain to all channels (linked)
+            // This preserves the stereo / surround image
+            for sample in frame.iter_mut() {
+                *sample = *sample * gain * self.makeup_gain_linear;
+            }
         }
     }
+}
+
+impl AudioEffect for Compressor {
+    fn process(&mut self, buffer: &mut [f32], sample_rate: u32) {
+        // Process interleaved stereo buffer with linked stereo detection
+        self.process_interleaved(buffer, sample_rate, 2);
+    }
+
+    fn supports_layout(&self, _layout: ChannelLayout) -> bool {
+        true
+    }
+
+    fn process_layout(&mut self, buffer: &mut [f32], sample_rate: u32, layout: ChannelLayout) {
+        self.process_interleaved(buffer, sample_rate, layout.channel_count() as usize);
+    }
 
     fn reset(&mut self) {
         self.peak_level_db = -120.0;
@@ -604,4 +629,19 @@ mod tests {
             output
         );
     }
+
+    #[test]
+    fn process_surround_buffer() {
+        let mut comp = Compressor::with_settings(CompressorSettings::aggressive());
+        assert!(comp.supports_layout(ChannelLayout::Surround71));
+
+        let mut buffer = vec![0.8; 8 * 500];
+        comp.process_layout(&mut buffer, 48000, ChannelLayout::Surround71);
+
+        // All channels compressed by the same amount
+        for frame in buffer.chunks_exact(8).skip(100) {
+            assert!(frame[0] < 0.8, "Signal should be compressed");
+            assert!(frame.iter().all(|&s| (s - frame[0]).abs() < 1e-6));
+        }
+    }
 }
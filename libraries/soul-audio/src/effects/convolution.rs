@@ -4,6 +4,10 @@
 //! Uses FFT-based overlap-save method for efficient processing of impulse responses
 //! longer than 64 samples, with direct time-domain convolution for shorter IRs.
 //!
+//! IRs can be mono, stereo or true-stereo (4 channels: LL, LR, RL, RR). An IR
+//! at a different rate than the stream is resampled when the rate changes; a
+//! set of IRs at several rates can be loaded so the closest one is used.
+//!
 //! # Example
 //!
 //! ```rust,no_run
@@ -18,6 +22,7 @@
 //! ```
 
 use super::AudioEffect;
+use crate::resampling::{Resampler, ResamplerBackend, ResamplingQuality};
 #[allow(unused_imports)]
 use rustfft::FftPlanner;
 use rustfft::{num_complex::Complex, Fft};
@@ -30,19 +35,200 @@ const TIME_DOMAIN_THRESHOLD: usize = 64;
 /// Minimum FFT size for efficient processing
 const MIN_FFT_SIZE: usize = 256;
 
+/// An impulse response at its native sample rate
+///
+/// Channel layouts:
+/// - 1: the same filter on both channels
+/// - 2: left filter, right filter
+/// - 4: true stereo, in the order LL, LR, RL, RR (input to output), so
+///   `out_L = in_L * LL + in_R * RL` and `out_R = in_L * LR + in_R * RR`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImpulseResponse {
+    /// Interleaved samples
+    pub samples: Vec<f32>,
+    /// Sample rate the IR was measured/designed at
+    pub sample_rate: u32,
+    /// Number of channels (1, 2 or 4)
+    pub channels: usize,
+}
+
+impl ImpulseResponse {
+    /// Create an impulse response, validating its layout
+    ///
+    /// A trailing partial frame is dropped.
+    pub fn new(
+        mut samples: Vec<f32>,
+        sample_rate: u32,
+        channels: usize,
+    ) -> Result<Self, ConvolutionError> {
+        if samples.is_empty() {
+            return Err(ConvolutionError::EmptyImpulseResponse);
+        }
+
+        if !matches!(channels, 1 | 2 | 4) {
+            return Err(ConvolutionError::InvalidChannelCount(channels));
+        }
+
+        samples.truncate(samples.len() - samples.len() % channels);
+        if samples.is_empty() {
+            return Err(ConvolutionError::EmptyImpulseResponse);
+        }
+
+        if sample_rate == 0 {
+            return Err(ConvolutionError::InvalidSampleRate(sample_rate));
+        }
+
+        Ok(Self {
+            samples,
+            sample_rate,
+            channels,
+        })
+    }
+
+    /// Load an impulse response from a WAV file
+    pub fn from_wav<P: AsRef<Path>>(path: P) -> Result<Self, ConvolutionError> {
+        let path = path.as_ref();
+
+        if !path.exists() {
+            return Err(ConvolutionError::FileNotFound(path.display().to_string()));
+        }
+
+        // Read WAV file using hound
+        let reader = hound::WavReader::open(path)
+            .map_err(|e| ConvolutionError::WavReadError(e.to_string()))?;
+
+        let spec = reader.spec();
+        let channels = spec.channels as usize;
+        let sample_rate = spec.sample_rate;
+
+        // Read samples based on format
+        let samples: Vec<f32> = match spec.sample_format {
+            hound::SampleFormat::Float => reader
+                .into_samples::<f32>()
+                .filter_map(Result::ok)
+                .collect(),
+            hound::SampleFormat::Int => {
+                let bits = spec.bits_per_sample;
+                let max_val = (1i32 << (bits - 1)) as f32;
+                reader
+                    .into_samples::<i32>()
+                    .filter_map(Result::ok)
+                    .map(|s| s as f32 / max_val)
+                    .collect()
+            }
+        };
+
+        Self::new(samples, sample_rate, channels)
+    }
+
+    /// Save as a 32-bit float WAV file
+    ///
+    /// Reads back unchanged with [`ImpulseResponse::from_wav`].
+    pub fn write_wav<P: AsRef<Path>>(&self, path: P) -> Result<(), ConvolutionError> {
+        let spec = hound::WavSpec {
+            channels: self.channels as u16,
+            sample_rate: self.sample_rate,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+
+        let mut writer = hound::WavWriter::create(path, spec)
+            .map_err(|e| ConvolutionError::WavWriteError(e.to_string()))?;
+        for &sample in &self.samples {
+            writer
+                .write_sample(sample)
+                .map_err(|e| ConvolutionError::WavWriteError(e.to_string()))?;
+        }
+        writer
+            .finalize()
+            .map_err(|e| ConvolutionError::WavWriteError(e.to_string()))
+    }
+
+    /// Length in frames
+    pub fn frames(&self) -> usize {
+        self.samples.len() / self.channels
+    }
+
+    /// Resample to another rate
+    ///
+    /// The IR is padded so its tail makes it through the resampler and the
+    /// output is cut back to the scaled length; the sinc kernel is centred,
+    /// so the IR keeps its timing. The taps are scaled by
+    /// `sample_rate / target_rate` so the filter keeps its gain (a longer IR
+    /// sums more taps).
+    pub fn resampled(&self, target_rate: u32) -> Result<Self, ConvolutionError> {
+        if target_rate == self.sample_rate {
+            return Ok(self.clone());
+        }
+
+        let mut resampler = Resampler::new(
+            ResamplerBackend::Auto,
+            self.sample_rate,
+            target_rate,
+            self.channels,
+            ResamplingQuality::High,
+        )
+        .map_err(|e| ConvolutionError::ResampleError(e.to_string()))?;
+
+        // Pad with enough silence to push the whole IR through the filter.
+        // The output itself is aligned with the input (the sinc kernel is
+        // centred), so nothing is trimmed from the front.
+        let latency = resampler.latency();
+        let pad_frames =
+            (latency as u64 * self.sample_rate as u64).div_ceil(target_rate as u64) as usize + 1;
+        let mut input = self.samples.clone();
+        input.resize(self.samples.len() + pad_frames * self.channels, 0.0);
+
+        let mut output = resampler
+            .process(&input)
+            .map_err(|e| ConvolutionError::ResampleError(e.to_string()))?;
+        output.extend(
+            resampler
+                .flush()
+                .map_err(|e| ConvolutionError::ResampleError(e.to_string()))?,
+        );
+
+        let target_frames = ((self.frames() as u64 * target_rate as u64
+            + self.sample_rate as u64 / 2)
+            / self.sample_rate as u64)
+            .max(1) as usize;
+        let gain = self.sample_rate as f32 / target_rate as f32;
+
+        let mut samples: Vec<f32> = output.iter().map(|&s| s * gain).collect();
+        samples.resize(target_frames * self.channels, 0.0);
+
+        Ok(Self {
+            samples,
+            sample_rate: target_rate,
+            channels: self.channels,
+        })
+    }
+}
+
 /// Convolution engine for applying impulse response-based effects
 ///
 /// Uses a hybrid approach for efficient real-time processing:
 /// - Direct time-domain convolution for very short IRs (< 64 samples)
 /// - FFT-based overlap-save convolution for longer IRs
 ///
-/// Supports stereo impulse responses and configurable dry/wet mix.
+/// Supports mono, stereo and true-stereo impulse responses and configurable
+/// dry/wet mix. The engine keeps the loaded IRs at their native rates and
+/// activates the best match for the stream rate, resampling if no IR in the
+/// set has that exact rate. Call [`ConvolutionEngine::set_sample_rate`] from
+/// the control thread before a rate change; otherwise `process()` prepares
+/// the new IR itself the first time it sees the new rate.
 pub struct ConvolutionEngine {
-    /// Impulse response samples (interleaved stereo)
+    /// Loaded impulse responses at their native rates
+    ir_set: Vec<ImpulseResponse>,
+    /// Stream rate the active IR was prepared for (None until known)
+    stream_rate: Option<u32>,
+    /// Stream rate that no IR could be prepared for (don't retry every block)
+    failed_rate: Option<u32>,
+    /// Active impulse response samples (interleaved)
     ir_samples: Vec<f32>,
-    /// Sample rate of the loaded IR
+    /// Sample rate of the active IR
     ir_sample_rate: u32,
-    /// Number of channels (1 or 2)
+    /// Number of channels of the active IR (1, 2 or 4)
     ir_channels: usize,
     /// Dry/wet mix (0.0 = fully dry, 1.0 = fully wet)
     dry_wet_mix: f32,
@@ -68,6 +254,9 @@ pub struct ConvolutionEngine {
     buffer_pos: usize,
 }
 
+/// FFTs of the cross-feed IRs of a true-stereo IR (RL, LR)
+type CrossSpectra = (Vec<Complex<f32>>, Vec<Complex<f32>>);
+
 /// State for FFT-based convolution using overlap-save method
 struct FftConvolutionState {
     /// FFT size (power of 2, >= 2 * max(buffer_size, ir_length))
@@ -76,6 +265,8 @@ struct FftConvolutionState {
     ir_fft_left: Vec<Complex<f32>>,
     /// Pre-computed FFT of IR for right channel (complex, in frequency domain)
     ir_fft_right: Vec<Complex<f32>>,
+    /// Pre-computed FFTs of the cross-feed IRs of a true-stereo IR (RL, LR)
+    ir_fft_cross: Option<CrossSpectra>,
     /// Forward FFT planner
     fft_forward: Arc<dyn Fft<f32>>,
     /// Inverse FFT planner
@@ -90,8 +281,10 @@ struct FftConvolutionState {
     output_overlap_right: Vec<f32>,
     /// Scratch buffer for FFT operations
     fft_scratch: Vec<Complex<f32>>,
-    /// Scratch buffer for input FFT
+    /// Scratch buffer for input FFT (left channel)
     input_fft_scratch: Vec<Complex<f32>>,
+    /// Scratch buffer for input FFT (right channel)
+    input_fft_right_scratch: Vec<Complex<f32>>,
     /// Scratch buffer for multiplication result
     mult_scratch: Vec<Complex<f32>>,
 }
@@ -106,6 +299,9 @@ impl ConvolutionEngine {
     /// Create a new convolution engine
     pub fn new() -> Self {
         Self {
+            ir_set: Vec::new(),
+            stream_rate: None,
+            failed_rate: None,
             ir_samples: Vec::new(),
             ir_sample_rate: 44100,
             ir_channels: 2,
@@ -125,44 +321,165 @@ impl ConvolutionEngine {
 
     /// Load an impulse response from raw samples
     ///
+    /// Replaces any loaded IR set. The IR is resampled if the stream rate is
+    /// already known and differs.
+    ///
     /// # Arguments
-    /// * `samples` - Interleaved audio samples (mono or stereo)
+    /// * `samples` - Interleaved audio samples (mono, stereo or true stereo)
     /// * `sample_rate` - Sample rate of the IR
-    /// * `channels` - Number of channels (1 or 2)
+    /// * `channels` - Number of channels (1, 2 or 4: LL, LR, RL, RR)
     pub fn load_impulse_response(
         &mut self,
         samples: &[f32],
         sample_rate: u32,
         channels: usize,
     ) -> Result<(), ConvolutionError> {
-        if samples.is_empty() {
+        let ir = ImpulseResponse::new(samples.to_vec(), sample_rate, channels)?;
+        self.load_impulse_response_set(vec![ir])
+    }
+
+    /// Load a set of impulse responses, one per sample rate
+    ///
+    /// The engine uses the IR whose rate matches the stream, or resamples the
+    /// one with the closest rate. IRs must not share a sample rate.
+    pub fn load_impulse_response_set(
+        &mut self,
+        irs: Vec<ImpulseResponse>,
+    ) -> Result<(), ConvolutionError> {
+        if irs.is_empty() {
             return Err(ConvolutionError::EmptyImpulseResponse);
         }
 
-        if channels != 1 && channels != 2 {
-            return Err(ConvolutionError::InvalidChannelCount(channels));
+        for (i, ir) in irs.iter().enumerate() {
+            if irs[..i]
+                .iter()
+                .any(|other| other.sample_rate == ir.sample_rate)
+            {
+                return Err(ConvolutionError::DuplicateSampleRate(ir.sample_rate));
+            }
+        }
+
+        // Prepare before replacing the set so a failed resample keeps the old IR
+        let rate = self.stream_rate.unwrap_or(irs[0].sample_rate);
+        let active = Self::select_ir(&irs, rate)
+            .expect("IR set is not empty")
+            .resampled(rate)?;
+
+        self.ir_set = irs;
+        self.failed_rate = None;
+        self.activate(active);
+        self.enabled = true;
+        Ok(())
+    }
+
+    /// Load an impulse response from a WAV file
+    pub fn load_from_wav<P: AsRef<Path>>(&mut self, path: P) -> Result<(), ConvolutionError> {
+        let ir = ImpulseResponse::from_wav(path)?;
+        self.load_impulse_response_set(vec![ir])
+    }
+
+    /// Load a per-sample-rate IR set from WAV files
+    ///
+    /// Each file's rate comes from its header, e.g. `room_44100.wav` and
+    /// `room_96000.wav` exported from the same measurement.
+    pub fn load_from_wavs<P: AsRef<Path>>(&mut self, paths: &[P]) -> Result<(), ConvolutionError> {
+        let irs = paths
+            .iter()
+            .map(ImpulseResponse::from_wav)
+            .collect::<Result<Vec<_>, _>>()?;
+        self.load_impulse_response_set(irs)
+    }
+
+    /// Prepare the IR for a stream sample rate
+    ///
+    /// Picks the IR with that exact rate from the loaded set, or resamples
+    /// the one with the closest rate. Does nothing if no IR is loaded or the
+    /// active IR already matches. Resampling allocates, so call this from the
+    /// control thread when the output rate changes.
+    pub fn set_sample_rate(&mut self, sample_rate: u32) -> Result<(), ConvolutionError> {
+        if sample_rate == 0 {
+            return Err(ConvolutionError::InvalidSampleRate(sample_rate));
         }
 
-        // Store IR data
-        self.ir_samples = samples.to_vec();
-        self.ir_sample_rate = sample_rate;
-        self.ir_channels = channels;
+        self.stream_rate = Some(sample_rate);
+        if self.ir_set.is_empty() || self.ir_sample_rate == sample_rate {
+            return Ok(());
+        }
+
+        let active = Self::select_ir(&self.ir_set, sample_rate)
+            .expect("IR set is not empty")
+            .resampled(sample_rate)?;
+        self.failed_rate = None;
+        self.activate(active);
+        Ok(())
+    }
+
+    /// Sample rates of the loaded IR set
+    pub fn ir_set_sample_rates(&self) -> Vec<u32> {
+        self.ir_set.iter().map(|ir| ir.sample_rate).collect()
+    }
+
+    /// Sample rate of the active IR (after any resampling)
+    pub fn ir_sample_rate(&self) -> u32 {
+        self.ir_sample_rate
+    }
+
+    /// Check if the active IR is true stereo (4 channels)
+    pub fn is_true_stereo(&self) -> bool {
+        self.ir_channels == 4
+    }
+
+    /// Pick the IR to use at a sample rate
+    ///
+    /// An exact match wins; otherwise the closest rate by ratio, preferring
+    /// the higher rate on a tie so resampling only discards bandwidth.
+    fn select_ir(irs: &[ImpulseResponse], sample_rate: u32) -> Option<&ImpulseResponse> {
+        let distance = |ir: &ImpulseResponse| {
+            (f64::from(ir.sample_rate) / f64::from(sample_rate))
+                .ln()
+                .abs()
+        };
+
+        irs.iter().min_by(|a, b| {
+            distance(a)
+                .partial_cmp(&distance(b))
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(b.sample_rate.cmp(&a.sample_rate))
+        })
+    }
+
+    /// Make an IR the active one and rebuild the convolution state
+    fn activate(&mut self, ir: ImpulseResponse) {
+        self.ir_sample_rate = ir.sample_rate;
+        self.ir_channels = ir.channels;
+        self.ir_samples = ir.samples;
 
         // Prepare FFT-based convolution for longer IRs
-        let ir_frames = samples.len() / channels;
-        if ir_frames > TIME_DOMAIN_THRESHOLD {
+        if self.ir_length() > TIME_DOMAIN_THRESHOLD {
             self.prepare_fft_convolution();
         } else {
             self.fft_state = None;
         }
+    }
 
-        self.enabled = true;
-        Ok(())
+    /// Tap `frame` of the active IR as (LL, LR, RL, RR)
+    fn ir_taps(&self, frame: usize) -> (f32, f32, f32, f32) {
+        let idx = frame * self.ir_channels;
+        match self.ir_channels {
+            4 => (
+                self.ir_samples[idx],
+                self.ir_samples[idx + 1],
+                self.ir_samples[idx + 2],
+                self.ir_samples[idx + 3],
+            ),
+            2 => (self.ir_samples[idx], 0.0, 0.0, self.ir_samples[idx + 1]),
+            _ => (self.ir_samples[idx], 0.0, 0.0, self.ir_samples[idx]),
+        }
     }
 
     /// Prepare FFT-based convolution state
     fn prepare_fft_convolution(&mut self) {
-        let ir_frames = self.ir_samples.len() / self.ir_channels;
+        let ir_frames = self.ir_length();
 
         // Choose FFT size: needs to be at least 2x the IR length for overlap-save
         // Use a reasonable default block size for processing
@@ -176,34 +493,35 @@ impl ConvolutionEngine {
         let fft_forward = planner.plan_fft_forward(fft_size);
         let fft_inverse = planner.plan_fft_inverse(fft_size);
 
-        // Extract left and right IR channels
-        let mut ir_left = vec![0.0f32; fft_size];
-        let mut ir_right = vec![0.0f32; fft_size];
+        // Split the IR into its four paths (cross paths are silent unless true stereo)
+        let mut paths = [
+            vec![Complex::new(0.0f32, 0.0); fft_size],
+            vec![Complex::new(0.0f32, 0.0); fft_size],
+            vec![Complex::new(0.0f32, 0.0); fft_size],
+            vec![Complex::new(0.0f32, 0.0); fft_size],
+        ];
 
         for i in 0..ir_frames {
-            let idx = i * self.ir_channels;
-            ir_left[i] = self.ir_samples[idx];
-            ir_right[i] = if self.ir_channels == 2 {
-                self.ir_samples[idx + 1]
-            } else {
-                self.ir_samples[idx]
-            };
+            let (ll, lr, rl, rr) = self.ir_taps(i);
+            paths[0][i].re = ll;
+            paths[1][i].re = lr;
+            paths[2][i].re = rl;
+            paths[3][i].re = rr;
         }
 
         // Compute FFT of IR
-        let mut ir_fft_left: Vec<Complex<f32>> =
-            ir_left.iter().map(|&x| Complex::new(x, 0.0)).collect();
-        let mut ir_fft_right: Vec<Complex<f32>> =
-            ir_right.iter().map(|&x| Complex::new(x, 0.0)).collect();
-
-        fft_forward.process(&mut ir_fft_left);
-        fft_forward.process(&mut ir_fft_right);
+        for path in &mut paths {
+            fft_forward.process(path);
+        }
+        let [ir_fft_left, ir_fft_lr, ir_fft_rl, ir_fft_right] = paths;
+        let ir_fft_cross = (self.ir_channels == 4).then_some((ir_fft_rl, ir_fft_lr));
 
         // Pre-allocate all buffers
         let fft_state = FftConvolutionState {
             fft_size,
             ir_fft_left,
             ir_fft_right,
+            ir_fft_cross,
             fft_forward,
             fft_inverse,
             input_history_left: vec![0.0; fft_size],
@@ -212,52 +530,13 @@ impl ConvolutionEngine {
             output_overlap_right: vec![0.0; fft_size],
             fft_scratch: vec![Complex::new(0.0, 0.0); fft_size],
             input_fft_scratch: vec![Complex::new(0.0, 0.0); fft_size],
+            input_fft_right_scratch: vec![Complex::new(0.0, 0.0); fft_size],
             mult_scratch: vec![Complex::new(0.0, 0.0); fft_size],
         };
 
         self.fft_state = Some(fft_state);
     }
 
-    /// Load an impulse response from a WAV file
-    pub fn load_from_wav<P: AsRef<Path>>(&mut self, path: P) -> Result<(), ConvolutionError> {
-        let path = path.as_ref();
-
-        if !path.exists() {
-            return Err(ConvolutionError::FileNotFound(path.display().to_string()));
-        }
-
-        // Read WAV file using hound
-        let reader = hound::WavReader::open(path)
-            .map_err(|e| ConvolutionError::WavReadError(e.to_string()))?;
-
-        let spec = reader.spec();
-        let channels = spec.channels as usize;
-        let sample_rate = spec.sample_rate;
-
-        // Read samples based on format
-        let samples: Vec<f32> = match spec.sample_format {
-            hound::SampleFormat::Float => reader
-                .into_samples::<f32>()
-                .filter_map(Result::ok)
-                .collect(),
-            hound::SampleFormat::Int => {
-                let bits = spec.bits_per_sample;
-                let max_val = (1i32 << (bits - 1)) as f32;
-                reader
-                    .into_samples::<i32>()
-                    .filter_map(Result::ok)
-                    .map(|s| s as f32 / max_val)
-                    .collect()
-            }
-        };
-
-        if samples.is_empty() {
-            return Err(ConvolutionError::EmptyImpulseResponse);
-        }
-
-        self.load_impulse_response(&samples, sample_rate, channels)
-    }
-
     /// Set the dry/wet mix (0.0 = fully dry, 1.0 = fully wet)
     pub fn set_dry_wet_mix(&mut self, mix: f32) {
         self.dry_wet_mix = mix.clamp(0.0, 1.0);
@@ -278,12 +557,12 @@ impl ConvolutionEngine {
         self.enabled
     }
 
-    /// Get the length of the loaded impulse response in samples
+    /// Get the length of the active impulse response in samples
     pub fn ir_length(&self) -> usize {
-        self.ir_samples.len() / self.ir_channels
+        self.ir_samples.len() / self.ir_channels.max(1)
     }
 
-    /// Get the length of the loaded impulse response in seconds
+    /// Get the length of the active impulse response in seconds
     pub fn ir_duration_seconds(&self) -> f32 {
         if self.ir_sample_rate == 0 {
             return 0.0;
@@ -307,7 +586,7 @@ impl ConvolutionEngine {
 
         let frames = input.len() / 2;
         let fft_size = state.fft_size;
-        let ir_frames = self.ir_samples.len() / self.ir_channels;
+        let ir_frames = self.ir_samples.len() / self.ir_channels.max(1);
 
         // Block size for overlap-add: we process input in blocks
         // FFT size = block_size + ir_frames - 1, so block_size = fft_size - ir_frames + 1
@@ -320,23 +599,33 @@ impl ConvolutionEngine {
         while input_pos < frames {
             let chunk_size = (frames - input_pos).min(block_size);
 
-            // Zero-pad input block for FFT (left channel)
+            // Zero-pad input block for FFT (both channels)
             for i in 0..fft_size {
                 if i < chunk_size {
                     let idx = (input_pos + i) * 2;
                     state.input_fft_scratch[i] = Complex::new(input[idx], 0.0);
+                    state.input_fft_right_scratch[i] = Complex::new(input[idx + 1], 0.0);
                 } else {
                     state.input_fft_scratch[i] = Complex::new(0.0, 0.0);
+                    state.input_fft_right_scratch[i] = Complex::new(0.0, 0.0);
                 }
             }
 
-            // FFT of input (left)
+            // FFT of input
             state.fft_forward.process(&mut state.input_fft_scratch);
+            state
+                .fft_forward
+                .process(&mut state.input_fft_right_scratch);
 
-            // Multiply in frequency domain (left)
+            // Multiply in frequency domain (left = L*LL [+ R*RL])
             for i in 0..fft_size {
                 state.mult_scratch[i] = state.input_fft_scratch[i] * state.ir_fft_left[i];
             }
+            if let Some((ir_fft_rl, _)) = &state.ir_fft_cross {
+                for i in 0..fft_size {
+                    state.mult_scratch[i] += state.input_fft_right_scratch[i] * ir_fft_rl[i];
+                }
+            }
 
             // Inverse FFT (left)
             state.fft_inverse.process(&mut state.mult_scratch);
@@ -348,22 +637,14 @@ impl ConvolutionEngine {
                 state.output_overlap_left[i] += state.mult_scratch[i].re * scale;
             }
 
-            // Zero-pad input block for FFT (right channel)
+            // Multiply in frequency domain (right = R*RR [+ L*LR])
             for i in 0..fft_size {
-                if i < chunk_size {
-                    let idx = (input_pos + i) * 2 + 1;
-                    state.input_fft_scratch[i] = Complex::new(input[idx], 0.0);
-                } else {
-                    state.input_fft_scratch[i] = Complex::new(0.0, 0.0);
-                }
+                state.mult_scratch[i] = state.input_fft_right_scratch[i] * state.ir_fft_right[i];
             }
-
-            // FFT of input (right)
-            state.fft_forward.process(&mut state.input_fft_scratch);
-
-            // Multiply in frequency domain (right)
-            for i in 0..fft_size {
-                state.mult_scratch[i] = state.input_fft_scratch[i] * state.ir_fft_right[i];
+            if let Some((_, ir_fft_lr)) = &state.ir_fft_cross {
+                for i in 0..fft_size {
+                    state.mult_scratch[i] += state.input_fft_scratch[i] * ir_fft_lr[i];
+                }
             }
 
             // Inverse FFT (right)
@@ -413,17 +694,10 @@ impl ConvolutionEngine {
             let max_j = ir_frames.min(i + 1);
             for j in 0..max_j {
                 let input_idx = (i - j) * 2;
-                let ir_idx = j * self.ir_channels;
-
-                let ir_left = self.ir_samples[ir_idx];
-                let ir_right = if self.ir_channels == 2 {
-                    self.ir_samples[ir_idx + 1]
-                } else {
-                    ir_left
-                };
+                let (ll, lr, rl, rr) = self.ir_taps(j);
 
-                left_sum += input[input_idx] * ir_left;
-                right_sum += input[input_idx + 1] * ir_right;
+                left_sum += input[input_idx] * ll + input[input_idx + 1] * rl;
+                right_sum += input[input_idx] * lr + input[input_idx + 1] * rr;
             }
 
             // Apply dry/wet mix
@@ -437,11 +711,22 @@ impl ConvolutionEngine {
 }
 
 impl AudioEffect for ConvolutionEngine {
-    fn process(&mut self, buffer: &mut [f32], _sample_rate: u32) {
+    fn process(&mut self, buffer: &mut [f32], sample_rate: u32) {
         if !self.enabled || self.ir_samples.is_empty() || buffer.is_empty() {
             return;
         }
 
+        // Stream rate changed without set_sample_rate(): prepare the IR here
+        if sample_rate != self.ir_sample_rate && self.failed_rate != Some(sample_rate) {
+            if let Err(e) = self.set_sample_rate(sample_rate) {
+                eprintln!(
+                    "[ConvolutionEngine] No IR for {} Hz, keeping {} Hz IR: {}",
+                    sample_rate, self.ir_sample_rate, e
+                );
+                self.failed_rate = Some(sample_rate);
+            }
+        }
+
         let buffer_len = buffer.len();
 
         // Ensure output scratch buffer is large enough
@@ -500,6 +785,16 @@ impl AudioEffect for ConvolutionEngine {
         "Convolution"
     }
 
+    fn set_sample_rate(&mut self, sample_rate: u32) {
+        if let Err(e) = ConvolutionEngine::set_sample_rate(self, sample_rate) {
+            eprintln!(
+                "[ConvolutionEngine] No IR for {} Hz, keeping {} Hz IR: {}",
+                sample_rate, self.ir_sample_rate, e
+            );
+            self.failed_rate = Some(sample_rate);
+        }
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
@@ -514,12 +809,20 @@ impl AudioEffect for ConvolutionEngine {
 pub enum ConvolutionError {
     /// The impulse response is empty
     EmptyImpulseResponse,
-    /// Invalid channel count (must be 1 or 2)
+    /// Invalid channel count (must be 1, 2 or 4)
     InvalidChannelCount(usize),
+    /// Invalid sample rate
+    InvalidSampleRate(u32),
+    /// Two IRs in a set have the same sample rate
+    DuplicateSampleRate(u32),
     /// File not found
     FileNotFound(String),
     /// Error reading WAV file
     WavReadError(String),
+    /// Error writing WAV file
+    WavWriteError(String),
+    /// Error resampling the IR to the stream rate
+    ResampleError(String),
 }
 
 impl std::fmt::Display for ConvolutionError {
@@ -527,10 +830,20 @@ impl std::fmt::Display for ConvolutionError {
         match self {
             ConvolutionError::EmptyImpulseResponse => write!(f, "Impulse response is empty"),
             ConvolutionError::InvalidChannelCount(c) => {
-                write!(f, "Invalid channel count: {} (must be 1 or 2)", c)
+                write!(f, "Invalid channel count: {} (must be 1, 2 or 4)", c)
+            }
+            ConvolutionError::InvalidSampleRate(rate) => {
+                write!(f, "Invalid sample rate: {} Hz", rate)
+            }
+            ConvolutionError::DuplicateSampleRate(rate) => {
+                write!(f, "More than one impulse response at {} Hz", rate)
             }
             ConvolutionError::FileNotFound(path) => write!(f, "File not found: {}", path),
             ConvolutionError::WavReadError(e) => write!(f, "Failed to read WAV file: {}", e),
+            ConvolutionError::WavWriteError(e) => write!(f, "Failed to write WAV file: {}", e),
+            ConvolutionError::ResampleError(e) => {
+                write!(f, "Failed to resample impulse response: {}", e)
+            }
         }
     }
 }
@@ -628,6 +941,100 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_true_stereo_cross_paths() {
+        // LL = 0, LR = 1, RL = 1, RR = 0 swaps the channels
+        let swap = [0.0, 1.0, 1.0, 0.0];
+
+        // Short IR (time domain)
+        let mut engine = ConvolutionEngine::new();
+        engine.load_impulse_response(&swap, 44100, 4).unwrap();
+        assert!(engine.is_true_stereo());
+        let mut buffer = vec![0.5, -0.25, 0.3, 0.1];
+        engine.process(&mut buffer, 44100);
+        assert_eq!(buffer, vec![-0.25, 0.5, 0.1, 0.3]);
+
+        // Long IR (FFT)
+        let mut long_ir = vec![0.0; 200 * 4];
+        long_ir[..4].copy_from_slice(&swap);
+        let mut engine = ConvolutionEngine::new();
+        engine.load_impulse_response(&long_ir, 44100, 4).unwrap();
+        let mut buffer: Vec<f32> = (0..256).map(|i| (i as f32 * 0.1).sin()).collect();
+        let original = buffer.clone();
+        engine.process(&mut buffer, 44100);
+        for frame in 0..128 {
+            assert!((buffer[frame * 2] - original[frame * 2 + 1]).abs() < 1e-4);
+            assert!((buffer[frame * 2 + 1] - original[frame * 2]).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_ir_set_picks_matching_rate() {
+        let mut engine = ConvolutionEngine::new();
+        let irs = vec![
+            ImpulseResponse::new(vec![1.0; 100 * 2], 44100, 2).unwrap(),
+            ImpulseResponse::new(vec![1.0; 200 * 2], 96000, 2).unwrap(),
+        ];
+        engine.load_impulse_response_set(irs).unwrap();
+        assert_eq!(engine.ir_set_sample_rates(), vec![44100, 96000]);
+
+        engine.set_sample_rate(96000).unwrap();
+        assert_eq!(engine.ir_sample_rate(), 96000);
+        assert_eq!(engine.ir_length(), 200);
+
+        // A rate change seen by process() switches back
+        let mut buffer = vec![0.0; 64];
+        engine.process(&mut buffer, 44100);
+        assert_eq!(engine.ir_sample_rate(), 44100);
+        assert_eq!(engine.ir_length(), 100);
+
+        // 48 kHz is closest to 44.1 kHz, resampled to the stream rate
+        engine.set_sample_rate(48000).unwrap();
+        assert_eq!(engine.ir_sample_rate(), 48000);
+        assert_eq!(engine.ir_length(), 109);
+
+        let duplicate = vec![
+            ImpulseResponse::new(vec![1.0; 2], 48000, 2).unwrap(),
+            ImpulseResponse::new(vec![0.5; 2], 48000, 2).unwrap(),
+        ];
+        assert!(matches!(
+            engine.load_impulse_response_set(duplicate),
+            Err(ConvolutionError::DuplicateSampleRate(48000))
+        ));
+    }
+
+    #[test]
+    fn test_resampled_ir_keeps_gain_and_timing() {
+        // Decaying IR with its peak 50 frames in
+        let ir: Vec<f32> = (0..1000)
+            .map(|i| {
+                if i < 50 {
+                    0.0
+                } else {
+                    0.01 * (-((i - 50) as f32) / 200.0).exp()
+                }
+            })
+            .collect();
+        let original = ImpulseResponse::new(ir, 44100, 1).unwrap();
+        let resampled = original.resampled(88200).unwrap();
+
+        assert_eq!(resampled.sample_rate, 88200);
+        assert_eq!(resampled.frames(), 2000);
+
+        let sum = |ir: &ImpulseResponse| ir.samples.iter().sum::<f32>();
+        let gain_error = (sum(&resampled) / sum(&original) - 1.0).abs();
+        assert!(gain_error < 0.05, "DC gain changed by {:.3}", gain_error);
+
+        let peak = resampled
+            .samples
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(i, _)| i)
+            .unwrap();
+        assert!((98..=104).contains(&peak), "Peak moved to frame {}", peak);
+    }
+
     #[test]
     fn test_invalid_channels_error() {
         let mut engine = ConvolutionEngine::new();
@@ -637,5 +1044,19 @@ mod tests {
             result,
             Err(ConvolutionError::InvalidChannelCount(3))
         ));
+
+        let true_stereo = vec![1.0, 0.0, 0.0, 1.0];
+        assert!(engine.load_impulse_response(&true_stereo, 44100, 4).is_ok());
+    }
+
+    #[test]
+    fn test_wav_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ir.wav");
+
+        let ir = ImpulseResponse::new(vec![1.0, -0.5, 0.25, 0.125, -1e-6, 0.0], 96000, 2).unwrap();
+        ir.write_wav(&path).unwrap();
+
+        assert_eq!(ImpulseResponse::from_wav(&path).unwrap(), ir);
     }
 }
@@ -0,0 +1,415 @@
+/// Dynamic Equalizer
+///
+/// Peaking filters whose gain follows the level in their own frequency band:
+/// the band stays flat while a band-passed sidechain is below threshold and
+/// moves toward its range once the sidechain goes over. Useful for taming
+/// sibilance or boomy bass only when it actually gets loud.
+use super::biquad::Biquad;
+use super::chain::AudioEffect;
+use crate::channels::{ChannelLayout, MAX_CHANNELS};
+
+/// Maximum number of dynamic EQ bands
+pub const MAX_DYNAMIC_EQ_BANDS: usize = 8;
+
+/// Frames between filter coefficient updates
+///
+/// The envelope is smoothed with attack/release already, so recomputing the
+/// peaking coefficients every 16 frames (~0.3 ms) is inaudible and keeps
+/// the per-sample cost to two biquads per band.
+const CONTROL_INTERVAL: usize = 16;
+
+/// Dynamic EQ band
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DynamicEqBand {
+    /// Center frequency in Hz (20 to 20000)
+    pub frequency: f32,
+
+    /// Q factor (0.1 to 10.0), used for both the filter and the sidechain
+    pub q: f32,
+
+    /// Sidechain level in dB above which the gain starts to move (-60 to 0)
+    pub threshold_db: f32,
+
+    /// Ratio (1.0 to 20.0) of the level over threshold that becomes gain change
+    ///
+    /// The gain moves by `over * (1 - 1/ratio)` dB, like a compressor.
+    pub ratio: f32,
+
+    /// Maximum gain change in dB (-24 to +24)
+    ///
+    /// Negative cuts the band when it gets loud (de-essing, taming bass);
+    /// positive boosts it.
+    pub range_db: f32,
+
+    /// Attack time in milliseconds (0.1 to 100)
+    pub attack_ms: f32,
+
+    /// Release time in milliseconds (10 to 1000)
+    pub release_ms: f32,
+}
+
+impl DynamicEqBand {
+    /// Create a band with default ratio (4:1) and timing (5 ms / 100 ms)
+    pub fn new(frequency: f32, q: f32, threshold_db: f32, range_db: f32) -> Self {
+        let mut band = Self {
+            frequency,
+            q,
+            threshold_db,
+            ratio: 4.0,
+            range_db,
+            attack_ms: 5.0,
+            release_ms: 100.0,
+        };
+        band.validate();
+        band
+    }
+
+    /// De-esser: cuts up to 8 dB around 6.5 kHz when sibilance gets loud
+    pub fn de_esser() -> Self {
+        Self {
+            attack_ms: 1.0,
+            release_ms: 60.0,
+            ..Self::new(6500.0, 2.0, -30.0, -8.0)
+        }
+    }
+
+    /// Bass tamer: cuts up to 6 dB around 80 Hz on boomy passages
+    pub fn bass_tamer() -> Self {
+        Self {
+            attack_ms: 10.0,
+            release_ms: 200.0,
+            ..Self::new(80.0, 1.0, -20.0, -6.0)
+        }
+    }
+
+    /// Validate and clamp settings to safe ranges
+    pub fn validate(&mut self) {
+        self.frequency = self.frequency.clamp(20.0, 20000.0);
+        self.q = self.q.clamp(0.1, 10.0);
+        self.threshold_db = self.threshold_db.clamp(-60.0, 0.0);
+        self.ratio = self.ratio.clamp(1.0, 20.0);
+        self.range_db = self.range_db.clamp(-24.0, 24.0);
+        self.attack_ms = self.attack_ms.clamp(0.1, 100.0);
+        self.release_ms = self.release_ms.clamp(10.0, 1000.0);
+    }
+
+    /// Filter gain in dB for a sidechain level in dB
+    #[inline]
+    pub fn gain_for_level(&self, level_db: f32) -> f32 {
+        let over = level_db - self.threshold_db;
+        if over <= 0.0 {
+            return 0.0;
+        }
+
+        let amount = over * (1.0 - 1.0 / self.ratio);
+        if self.range_db < 0.0 {
+            -amount.min(-self.range_db)
+        } else {
+            amount.min(self.range_db)
+        }
+    }
+}
+
+impl Default for DynamicEqBand {
+    fn default() -> Self {
+        Self::de_esser()
+    }
+}
+
+/// Runtime state of one band
+#[derive(Debug, Clone, Copy)]
+struct DynamicBandState {
+    /// Band-pass on the input that drives the envelope
+    sidechain: Biquad,
+    /// Peaking filter applied to the audio
+    filter: Biquad,
+    /// Sidechain envelope (linear)
+    envelope: f32,
+    attack_coeff: f32,
+    release_coeff: f32,
+    /// Gain the filter coefficients were last computed for
+    applied_gain_db: f32,
+}
+
+impl DynamicBandState {
+    fn new() -> Self {
+        Self {
+            sidechain: Biquad::new(),
+            filter: Biquad::new(),
+            envelope: 0.0,
+            attack_coeff: 0.0,
+            release_coeff: 0.0,
+            applied_gain_db: 0.0,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.sidechain.reset();
+        self.filter.reset();
+        self.envelope = 0.0;
+    }
+}
+
+/// Dynamic Equalizer with up to `MAX_DYNAMIC_EQ_BANDS` bands
+///
+/// Detection is linked: the loudest channel of each band's sidechain drives
+/// that band's gain on all channels. All state is pre-allocated.
+pub struct DynamicEq {
+    bands: Vec<DynamicEqBand>,
+    states: [DynamicBandState; MAX_DYNAMIC_EQ_BANDS],
+    enabled: bool,
+    sample_rate: u32,
+    needs_update: bool,
+    /// Frames until the next coefficient update
+    control_countdown: usize,
+}
+
+impl DynamicEq {
+    /// Create an empty dynamic EQ
+    pub fn new() -> Self {
+        Self {
+            bands: Vec::with_capacity(MAX_DYNAMIC_EQ_BANDS),
+            states: [DynamicBandState::new(); MAX_DYNAMIC_EQ_BANDS],
+            enabled: true,
+            sample_rate: 44100,
+            needs_update: true,
+            control_countdown: 0,
+        }
+    }
+
+    /// Create a dynamic EQ with the given bands
+    pub fn with_bands(bands: Vec<DynamicEqBand>) -> Self {
+        let mut eq = Self::new();
+        eq.set_bands(bands);
+        eq
+    }
+
+    /// Replace all bands (extra bands beyond `MAX_DYNAMIC_EQ_BANDS` are dropped)
+    ///
+    /// Envelopes and filter state of existing band slots are kept so bands
+    /// can be adjusted during playback without clicks.
+    pub fn set_bands(&mut self, bands: Vec<DynamicEqBand>) {
+        let mut bands = bands;
+        bands.truncate(MAX_DYNAMIC_EQ_BANDS);
+        for band in &mut bands {
+            band.validate();
+        }
+
+        // New slots start from a clean state
+        for state in self.states.iter_mut().skip(self.bands.len()) {
+            state.reset();
+            state.applied_gain_db = 0.0;
+        }
+
+        self.bands = bands;
+        self.needs_update = true;
+    }
+
+    /// Get all bands
+    pub fn bands(&self) -> &[DynamicEqBand] {
+        &self.bands
+    }
+
+    /// Current gain of each band in dB (for metering)
+    pub fn band_gains_db(&self) -> Vec<f32> {
+        self.states
+            .iter()
+            .take(self.bands.len())
+            .map(|state| state.applied_gain_db)
+            .collect()
+    }
+
+    /// Recompute sidechain filters, time constants and peaking filters
+    fn update_coefficients(&mut self) {
+        if !self.needs_update {
+            return;
+        }
+
+        let sr = self.sample_rate as f32;
+        for (band, state) in self.bands.iter().zip(self.states.iter_mut()) {
+            state.sidechain.set_bandpass(sr, band.frequency, band.q);
+            state.attack_coeff = (-1.0 / (band.attack_ms * sr / 1000.0)).exp();
+            state.release_coeff = (-1.0 / (band.release_ms * sr / 1000.0)).exp();
+            state
+                .filter
+                .set_peaking(sr, band.frequency, band.q, state.applied_gain_db);
+        }
+
+        self.needs_update = false;
+    }
+
+    /// Process interleaved audio with any channel count
+    fn process_interleaved(&mut self, buffer: &mut [f32], sample_rate: u32, channels: usize) {
+        if !self.enabled || self.bands.is_empty() || channels == 0 || channels > MAX_CHANNELS {
+            return;
+        }
+
+        if self.sample_rate != sample_rate {
+            self.sample_rate = sample_rate;
+            self.needs_update = true;
+        }
+        self.update_coefficients();
+
+        let sr = self.sample_rate as f32;
+        for frame in buffer.chunks_exact_mut(channels) {
+            let update_filters = self.control_countdown == 0;
+            self.control_countdown = if update_filters {
+                CONTROL_INTERVAL - 1
+            } else {
+                self.control_countdown - 1
+            };
+
+            for (band, state) in self.bands.iter().zip(self.states.iter_mut()) {
+                // Linked sidechain level: loudest channel in the band
+                let mut level = 0.0f32;
+                for (channel, &sample) in frame.iter().enumerate() {
+                    level = level.max(state.sidechain.process(channel, sample).abs());
+                }
+
+                let coeff = if level > state.envelope {
+                    state.attack_coeff
+                } else {
+                    state.release_coeff
+                };
+                state.envelope = coeff * state.envelope + (1.0 - coeff) * level;
+
+                if update_filters {
+                    let level_db = 20.0 * state.envelope.max(1e-10).log10();
+                    let gain_db = band.gain_for_level(level_db);
+                    if (gain_db - state.applied_gain_db).abs() > 0.01 {
+                        state
+                            .filter
+                            .set_peaking(sr, band.frequency, band.q, gain_db);
+                        state.applied_gain_db = gain_db;
+                    }
+                }
+
+                for (channel, sample) in frame.iter_mut().enumerate() {
+                    *sample = state.filter.process(channel, *sample);
+                }
+            }
+        }
+    }
+}
+
+impl Default for DynamicEq {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioEffect for DynamicEq {
+    fn process(&mut self, buffer: &mut [f32], sample_rate: u32) {
+        self.process_interleaved(buffer, sample_rate, 2);
+    }
+
+    fn supports_layout(&self, layout: ChannelLayout) -> bool {
+        layout.channel_count() as usize <= MAX_CHANNELS
+    }
+
+    fn process_layout(&mut self, buffer: &mut [f32], sample_rate: u32, layout: ChannelLayout) {
+        self.process_interleaved(buffer, sample_rate, layout.channel_count() as usize);
+    }
+
+    fn reset(&mut self) {
+        for state in &mut self.states {
+            state.reset();
+        }
+        self.control_countdown = 0;
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn name(&self) -> &str {
+        "Dynamic EQ"
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 48000;
+
+    fn stereo_sine(frequency: f32, amplitude: f32, frames: usize) -> Vec<f32> {
+        (0..frames)
+            .flat_map(|i| {
+                let s = amplitude
+                    * (2.0 * std::f32::consts::PI * frequency * i as f32 / SAMPLE_RATE as f32)
+                        .sin();
+                [s, s]
+            })
+            .collect()
+    }
+
+    /// Gain in dB over the second half of a processed one-second sine
+    fn gain_db(eq: &mut DynamicEq, frequency: f32, amplitude: f32) -> f32 {
+        eq.reset();
+        let mut buffer = stereo_sine(frequency, amplitude, SAMPLE_RATE as usize);
+        let tail = buffer.len() / 2;
+        let input: f32 = buffer[tail..].iter().map(|s| s * s).sum();
+        eq.process(&mut buffer, SAMPLE_RATE);
+        let output: f32 = buffer[tail..].iter().map(|s| s * s).sum();
+        10.0 * (output / input).log10()
+    }
+
+    #[test]
+    fn gain_follows_level_over_threshold() {
+        let band = DynamicEqBand::new(1000.0, 1.0, -30.0, -6.0);
+        assert_eq!(band.gain_for_level(-40.0), 0.0);
+        // 4 dB over at 4:1 -> 3 dB cut
+        assert!((band.gain_for_level(-26.0) + 3.0).abs() < 1e-4);
+        // Limited to the range
+        assert_eq!(band.gain_for_level(0.0), -6.0);
+
+        let boost = DynamicEqBand::new(1000.0, 1.0, -30.0, 4.0);
+        assert_eq!(boost.gain_for_level(0.0), 4.0);
+    }
+
+    #[test]
+    fn cuts_loud_band_only() {
+        let mut eq = DynamicEq::with_bands(vec![DynamicEqBand::de_esser()]);
+
+        // Loud sibilance range is cut by (close to) the full range
+        let loud = gain_db(&mut eq, 6500.0, 0.5);
+        assert!(loud < -6.0 && loud > -8.5, "Loud band gain {:.2} dB", loud);
+
+        // Quiet content in the band stays flat
+        let quiet = gain_db(&mut eq, 6500.0, 0.005);
+        assert!(quiet.abs() < 0.1, "Quiet band gain {:.2} dB", quiet);
+
+        // Loud content elsewhere does not trigger the band
+        let bass = gain_db(&mut eq, 200.0, 0.5);
+        assert!(bass.abs() < 0.1, "Out-of-band gain {:.2} dB", bass);
+    }
+
+    #[test]
+    fn band_count_is_limited() {
+        let mut eq = DynamicEq::new();
+        eq.set_bands(vec![DynamicEqBand::bass_tamer(); MAX_DYNAMIC_EQ_BANDS + 3]);
+        assert_eq!(eq.bands().len(), MAX_DYNAMIC_EQ_BANDS);
+        assert_eq!(eq.band_gains_db().len(), MAX_DYNAMIC_EQ_BANDS);
+    }
+
+    #[test]
+    fn process_surround_buffer() {
+        let mut eq = DynamicEq::with_bands(vec![DynamicEqBand::bass_tamer()]);
+        let mut buffer = vec![0.5; 8 * 256];
+        eq.process_layout(&mut buffer, SAMPLE_RATE, ChannelLayout::Surround71);
+        assert!(buffer.iter().all(|s| s.is_finite()));
+    }
+}
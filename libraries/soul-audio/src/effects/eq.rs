@@ -1,26 +1,142 @@
 /// Parametric Equalizer
 ///
 /// Provides flexible frequency band control with adjustable gain.
-/// Uses biquad filters for each band. Supports 1-8 bands dynamically.
+/// Uses biquad filters for each band. Supports 1-32 bands dynamically.
+/// Optionally runs as a linear-phase FIR with the same magnitude response.
 use super::chain::AudioEffect;
+use super::linear_phase::{BiquadCoefficients, EqPhaseMode, LinearPhaseFir};
 
 /// Maximum number of bands supported by the dynamic EQ
-pub const MAX_EQ_BANDS: usize = 8;
+///
+/// Large enough for imported AutoEQ / REW filter sets, which commonly
+/// use 10-20 filters.
+pub const MAX_EQ_BANDS: usize = 32;
+
+/// Maximum number of cascaded biquad sections in one band (48 dB/oct pass filters)
+const MAX_BAND_SECTIONS: usize = 4;
+
+/// Section Qs of a 4th-order Butterworth filter
+const BUTTERWORTH_4_Q: [f32; 2] = [0.541_196_1, 1.306_563];
+
+/// Section Qs of an 8th-order Butterworth filter
+const BUTTERWORTH_8_Q: [f32; 4] = [0.509_795_6, 0.601_344_9, 0.899_976_2, 2.562_915_5];
+
+/// Response alignment of high-pass and low-pass bands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FilterAlignment {
+    /// Maximally flat passband, -3 dB at the cutoff
+    #[default]
+    Butterworth,
+    /// Squared Butterworth, -6 dB at the cutoff; a high-pass and low-pass
+    /// at the same frequency sum flat, as in a speaker crossover
+    LinkwitzRiley,
+}
+
+/// Roll-off of high-pass and low-pass bands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FilterSlope {
+    /// 12 dB/octave (one biquad)
+    #[default]
+    Db12,
+    /// 24 dB/octave (two cascaded biquads)
+    Db24,
+    /// 48 dB/octave (four cascaded biquads)
+    Db48,
+}
+
+impl FilterSlope {
+    /// Slope in dB per octave
+    pub fn db_per_octave(self) -> u32 {
+        match self {
+            Self::Db12 => 12,
+            Self::Db24 => 24,
+            Self::Db48 => 48,
+        }
+    }
+
+    /// Slope from dB per octave (12, 24 or 48)
+    pub fn from_db_per_octave(db_per_octave: u32) -> Option<Self> {
+        match db_per_octave {
+            12 => Some(Self::Db12),
+            24 => Some(Self::Db24),
+            48 => Some(Self::Db48),
+            _ => None,
+        }
+    }
+
+    /// Number of cascaded second-order sections
+    pub fn sections(self) -> usize {
+        match self {
+            Self::Db12 => 1,
+            Self::Db24 => 2,
+            Self::Db48 => 4,
+        }
+    }
+}
+
+/// Q of one cascaded section of a high-pass or low-pass band
+///
+/// 12 dB/oct Butterworth uses the band's own Q (0.707 is maximally flat);
+/// every other alignment fixes its section Qs.
+pub(super) fn pass_section_q(
+    alignment: FilterAlignment,
+    slope: FilterSlope,
+    q: f32,
+    section: usize,
+) -> f32 {
+    match (alignment, slope) {
+        (FilterAlignment::Butterworth, FilterSlope::Db12) => q,
+        (FilterAlignment::Butterworth, FilterSlope::Db24) => BUTTERWORTH_4_Q[section],
+        (FilterAlignment::Butterworth, FilterSlope::Db48) => BUTTERWORTH_8_Q[section],
+        // Linkwitz-Riley is a Butterworth of half the order, applied twice
+        (FilterAlignment::LinkwitzRiley, FilterSlope::Db12) => 0.5,
+        (FilterAlignment::LinkwitzRiley, FilterSlope::Db24) => std::f32::consts::FRAC_1_SQRT_2,
+        (FilterAlignment::LinkwitzRiley, FilterSlope::Db48) => BUTTERWORTH_4_Q[section % 2],
+    }
+}
 
 /// Filter type for EQ bands
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub enum FilterType {
     /// Low shelf - boosts/cuts below frequency
     LowShelf,
     /// Peaking - boosts/cuts around frequency with Q bandwidth
+    #[default]
     Peaking,
     /// High shelf - boosts/cuts above frequency
     HighShelf,
+    /// High-pass - removes content below frequency
+    HighPass {
+        alignment: FilterAlignment,
+        slope: FilterSlope,
+    },
+    /// Low-pass - removes content above frequency
+    LowPass {
+        alignment: FilterAlignment,
+        slope: FilterSlope,
+    },
+    /// Notch - removes a narrow band around frequency (Q sets the width)
+    Notch,
+    /// Band-pass - keeps a band around frequency at 0 dB (Q sets the width)
+    BandPass,
+    /// All-pass - shifts phase around frequency without changing level
+    ///
+    /// Linear-phase mode only reproduces magnitude, so there it is a no-op.
+    AllPass,
+    /// Tilt - gain/2 above frequency and -gain/2 below, 0 dB at frequency
+    TiltShelf,
 }
 
-impl Default for FilterType {
-    fn default() -> Self {
-        Self::Peaking
+impl FilterType {
+    /// Whether the band's gain affects this filter type
+    ///
+    /// Pass, notch, band-pass and all-pass filters have no gain; bands of
+    /// those types always report 0 dB.
+    pub fn has_gain(&self) -> bool {
+        matches!(
+            self,
+            Self::LowShelf | Self::Peaking | Self::HighShelf | Self::TiltShelf
+        )
     }
 }
 
@@ -33,7 +149,7 @@ pub struct EqBand {
     gain_db: f32,
     /// Q factor (0.1 to 10.0), controls bandwidth - Made private to enforce validation
     q: f32,
-    /// Filter type (shelf or peaking)
+    /// Filter type (shelf, peaking, pass, ...)
     filter_type: FilterType,
 }
 
@@ -54,8 +170,12 @@ impl EqBand {
     }
 
     /// Set the gain in dB (clamped to -24 to +24)
+    ///
+    /// Ignored for filter types without gain (see [`FilterType::has_gain`]).
     pub fn set_gain_db(&mut self, gain_db: f32) {
-        self.gain_db = gain_db.clamp(-24.0, 24.0);
+        if self.filter_type.has_gain() {
+            self.gain_db = gain_db.clamp(-24.0, 24.0);
+        }
     }
 
     /// Get the Q factor
@@ -73,6 +193,53 @@ impl EqBand {
         self.filter_type
     }
 
+    /// Largest boost the band applies anywhere in the spectrum, in dB
+    ///
+    /// Differs from [`gain_db`](Self::gain_db) for tilt bands (half the
+    /// gain, on whichever side rises) and for resonant 12 dB/oct pass
+    /// filters, which peak above 0 dB when Q exceeds 0.707.
+    pub fn max_boost_db(&self) -> f32 {
+        match self.filter_type {
+            FilterType::TiltShelf => self.gain_db.abs() / 2.0,
+            FilterType::HighPass {
+                alignment: FilterAlignment::Butterworth,
+                slope: FilterSlope::Db12,
+            }
+            | FilterType::LowPass {
+                alignment: FilterAlignment::Butterworth,
+                slope: FilterSlope::Db12,
+            } if self.q > std::f32::consts::FRAC_1_SQRT_2 => {
+                let q_squared = self.q * self.q;
+                20.0 * (q_squared / (q_squared - 0.25).sqrt()).log10()
+            }
+            _ => self.gain_db.max(0.0),
+        }
+    }
+
+    /// Change the filter type, keeping frequency and Q
+    ///
+    /// Switching to a type without gain resets the gain to 0 dB.
+    pub fn set_filter_type(&mut self, filter_type: FilterType) {
+        self.filter_type = filter_type;
+        if !filter_type.has_gain() {
+            self.gain_db = 0.0;
+        }
+    }
+
+    /// Create a band of the given filter type with an explicit Q
+    ///
+    /// Used when importing filter sets that specify Q for shelves too.
+    /// The gain is dropped for types without gain.
+    pub fn with_filter_type(filter_type: FilterType, frequency: f32, gain_db: f32, q: f32) -> Self {
+        let gain_db = if filter_type.has_gain() { gain_db } else { 0.0 };
+        Self {
+            frequency,
+            gain_db: gain_db.clamp(-24.0, 24.0),
+            q: q.clamp(0.1, 10.0),
+            filter_type,
+        }
+    }
+
     /// Create a low shelf filter (boosts/cuts below frequency)
     pub fn low_shelf(frequency: f32, gain_db: f32) -> Self {
         Self {
@@ -97,6 +264,54 @@ impl EqBand {
             filter_type: FilterType::HighShelf,
         }
     }
+
+    /// Create a high-pass filter (removes content below frequency)
+    pub fn high_pass(frequency: f32, alignment: FilterAlignment, slope: FilterSlope) -> Self {
+        Self {
+            frequency,
+            gain_db: 0.0,
+            q: 0.707, // Butterworth Q
+            filter_type: FilterType::HighPass { alignment, slope },
+        }
+    }
+
+    /// Create a low-pass filter (removes content above frequency)
+    pub fn low_pass(frequency: f32, alignment: FilterAlignment, slope: FilterSlope) -> Self {
+        Self {
+            frequency,
+            gain_db: 0.0,
+            q: 0.707, // Butterworth Q
+            filter_type: FilterType::LowPass { alignment, slope },
+        }
+    }
+
+    /// Create a notch filter (removes a narrow band around frequency)
+    pub fn notch(frequency: f32, q: f32) -> Self {
+        Self::with_filter_type(FilterType::Notch, frequency, 0.0, q)
+    }
+
+    /// Create a band-pass filter (keeps a band around frequency)
+    pub fn band_pass(frequency: f32, q: f32) -> Self {
+        Self::with_filter_type(FilterType::BandPass, frequency, 0.0, q)
+    }
+
+    /// Create an all-pass filter (phase shift around frequency)
+    pub fn all_pass(frequency: f32, q: f32) -> Self {
+        Self::with_filter_type(FilterType::AllPass, frequency, 0.0, q)
+    }
+
+    /// Create a tilt filter pivoting around frequency
+    ///
+    /// Treble rises by `gain_db / 2` and bass falls by the same amount
+    /// (negative gain tilts the other way).
+    pub fn tilt_shelf(frequency: f32, gain_db: f32) -> Self {
+        Self {
+            frequency,
+            gain_db: gain_db.clamp(-24.0, 24.0),
+            q: 0.707, // Butterworth Q
+            filter_type: FilterType::TiltShelf,
+        }
+    }
 }
 
 /// Smoothing coefficient for exponential coefficient interpolation.
@@ -105,6 +320,12 @@ impl EqBand {
 /// Lower values = slower/smoother, higher = faster/more responsive.
 const SMOOTH_COEFF: f32 = 0.002;
 
+/// Samples of smoothing after which coefficients snap to their target.
+/// In f32 the exponential approach stalls a few hundred ulps short, which
+/// noticeably detunes filters with poles close to DC (e.g. a 20 Hz high-pass).
+/// After 8192 steps the remaining distance is below 1e-7 of the change.
+const SMOOTH_SNAP_SAMPLES: u32 = 8192;
+
 /// Biquad filter implementation
 /// Used internally by the EQ for each band
 ///
@@ -127,6 +348,9 @@ struct BiquadFilter {
     a1: f32,
     a2: f32,
 
+    // Samples until the active coefficients snap to target (0 = settled)
+    smoothing_remaining: u32,
+
     // State variables (per channel)
     x1_l: f32,
     x2_l: f32,
@@ -155,6 +379,7 @@ impl BiquadFilter {
             b2: 0.0,
             a1: 0.0,
             a2: 0.0,
+            smoothing_remaining: 0,
             // State variables
             x1_l: 0.0,
             x2_l: 0.0,
@@ -176,6 +401,15 @@ impl BiquadFilter {
     /// parameters change.
     #[inline]
     fn smooth_coefficients(&mut self) {
+        if self.smoothing_remaining == 0 {
+            return;
+        }
+        self.smoothing_remaining -= 1;
+        if self.smoothing_remaining == 0 {
+            self.snap_to_target();
+            return;
+        }
+
         // Exponential smoothing: new = old + alpha * (target - old)
         // This naturally handles continuous parameter changes without needing
         // to track a "smoothing window" that can restart
@@ -203,6 +437,17 @@ impl BiquadFilter {
         self.target_b2 = b2;
         self.target_a1 = a1;
         self.target_a2 = a2;
+        self.smoothing_remaining = SMOOTH_SNAP_SAMPLES;
+    }
+
+    /// Jump the active coefficients to the target
+    fn snap_to_target(&mut self) {
+        self.b0 = self.target_b0;
+        self.b1 = self.target_b1;
+        self.b2 = self.target_b2;
+        self.a1 = self.target_a1;
+        self.a2 = self.target_a2;
+        self.smoothing_remaining = 0;
     }
 
     /// Configure as peaking EQ filter
@@ -287,6 +532,115 @@ impl BiquadFilter {
         self.set_target_coefficients(b0 / a0, b1 / a0, b2 / a0, a1 / a0, a2 / a0);
     }
 
+    /// Angular frequency terms (sin, cos) for the new filter shapes
+    ///
+    /// Same guards as the shelf/peaking setters: `None` for an invalid sample
+    /// rate, and the frequency is kept below 45% of it.
+    fn omega(sample_rate: f32, frequency: f32) -> Option<(f32, f32)> {
+        if sample_rate < 1.0 {
+            return None;
+        }
+        let clamped_freq = frequency.min(sample_rate * 0.45);
+        let omega = 2.0 * std::f32::consts::PI * clamped_freq / sample_rate;
+        Some((omega.sin(), omega.cos()))
+    }
+
+    /// Configure as second-order high-pass filter
+    fn set_high_pass(&mut self, sample_rate: f32, frequency: f32, q: f32) {
+        let Some((sin_omega, cos_omega)) = Self::omega(sample_rate, frequency) else {
+            return;
+        };
+        let alpha = sin_omega / (2.0 * q);
+
+        let b0 = (1.0 + cos_omega) / 2.0;
+        let b1 = -(1.0 + cos_omega);
+        let b2 = (1.0 + cos_omega) / 2.0;
+        let a0 = 1.0 + alpha;
+        let a1 = -2.0 * cos_omega;
+        let a2 = 1.0 - alpha;
+
+        self.set_target_coefficients(b0 / a0, b1 / a0, b2 / a0, a1 / a0, a2 / a0);
+    }
+
+    /// Configure as second-order low-pass filter
+    fn set_low_pass(&mut self, sample_rate: f32, frequency: f32, q: f32) {
+        let Some((sin_omega, cos_omega)) = Self::omega(sample_rate, frequency) else {
+            return;
+        };
+        let alpha = sin_omega / (2.0 * q);
+
+        let b0 = (1.0 - cos_omega) / 2.0;
+        let b1 = 1.0 - cos_omega;
+        let b2 = (1.0 - cos_omega) / 2.0;
+        let a0 = 1.0 + alpha;
+        let a1 = -2.0 * cos_omega;
+        let a2 = 1.0 - alpha;
+
+        self.set_target_coefficients(b0 / a0, b1 / a0, b2 / a0, a1 / a0, a2 / a0);
+    }
+
+    /// Configure as notch filter
+    fn set_notch(&mut self, sample_rate: f32, frequency: f32, q: f32) {
+        let Some((sin_omega, cos_omega)) = Self::omega(sample_rate, frequency) else {
+            return;
+        };
+        let alpha = sin_omega / (2.0 * q);
+
+        let b0 = 1.0;
+        let b1 = -2.0 * cos_omega;
+        let b2 = 1.0;
+        let a0 = 1.0 + alpha;
+        let a1 = -2.0 * cos_omega;
+        let a2 = 1.0 - alpha;
+
+        self.set_target_coefficients(b0 / a0, b1 / a0, b2 / a0, a1 / a0, a2 / a0);
+    }
+
+    /// Configure as band-pass filter with 0 dB peak gain
+    fn set_band_pass(&mut self, sample_rate: f32, frequency: f32, q: f32) {
+        let Some((sin_omega, cos_omega)) = Self::omega(sample_rate, frequency) else {
+            return;
+        };
+        let alpha = sin_omega / (2.0 * q);
+
+        let b0 = alpha;
+        let b1 = 0.0;
+        let b2 = -alpha;
+        let a0 = 1.0 + alpha;
+        let a1 = -2.0 * cos_omega;
+        let a2 = 1.0 - alpha;
+
+        self.set_target_coefficients(b0 / a0, b1 / a0, b2 / a0, a1 / a0, a2 / a0);
+    }
+
+    /// Configure as second-order all-pass filter
+    fn set_all_pass(&mut self, sample_rate: f32, frequency: f32, q: f32) {
+        let Some((sin_omega, cos_omega)) = Self::omega(sample_rate, frequency) else {
+            return;
+        };
+        let alpha = sin_omega / (2.0 * q);
+
+        let b0 = 1.0 - alpha;
+        let b1 = -2.0 * cos_omega;
+        let b2 = 1.0 + alpha;
+        let a0 = 1.0 + alpha;
+        let a1 = -2.0 * cos_omega;
+        let a2 = 1.0 - alpha;
+
+        self.set_target_coefficients(b0 / a0, b1 / a0, b2 / a0, a1 / a0, a2 / a0);
+    }
+
+    /// Target coefficients (what the active ones are smoothing toward)
+    fn target_coefficients(&self) -> BiquadCoefficients {
+        [
+            self.target_b0,
+            self.target_b1,
+            self.target_b2,
+            self.target_a1,
+            self.target_a2,
+        ]
+    }
+
     /// Process a stereo sample pair (left, right)
     #[inline]
     fn process_sample(&mut self, left: f32, right: f32) -> (f32, f32) {
@@ -337,11 +691,7 @@ impl BiquadFilter {
         self.y1_r = 0.0;
         self.y2_r = 0.0;
         // Snap to target coefficients when resetting
-        self.b0 = self.target_b0;
-        self.b1 = self.target_b1;
-        self.b2 = self.target_b2;
-        self.a1 = self.target_a1;
-        self.a2 = self.target_a2;
+        self.snap_to_target();
     }
 
     /// Set filter to neutral (bypass) state without clearing filter state
@@ -360,18 +710,133 @@ impl BiquadFilter {
         self.target_b2 = 0.0;
         self.target_a1 = 0.0;
         self.target_a2 = 0.0;
+        self.smoothing_remaining = 0;
     }
 }
 
-/// Dynamic Parametric Equalizer supporting 1-8 bands
+/// Biquad cascade for one EQ band
+///
+/// Most filter types need one section; steep pass filters and the tilt
+/// shelf need more. All sections are pre-allocated.
+#[derive(Debug, Clone)]
+struct BandFilter {
+    sections: [BiquadFilter; MAX_BAND_SECTIONS],
+
+    /// Sections the current filter type uses
+    used: usize,
+
+    /// Sections being processed. Stays above `used` after a band drops
+    /// sections, so they smooth out to pass-through instead of clicking.
+    active: usize,
+}
+
+impl BandFilter {
+    fn new() -> Self {
+        Self {
+            sections: std::array::from_fn(|_| BiquadFilter::new()),
+            used: 1,
+            active: 1,
+        }
+    }
+
+    /// Set section targets for a band
+    fn configure(&mut self, band: &EqBand, sample_rate: f32) {
+        let frequency = band.frequency;
+        let q = band.q;
+        let gain_db = band.gain_db;
+
+        let used = match band.filter_type {
+            FilterType::HighPass { slope, .. } | FilterType::LowPass { slope, .. } => {
+                slope.sections()
+            }
+            FilterType::TiltShelf => 2,
+            _ => 1,
+        };
+
+        // Sections joining the cascade start from silence at pass-through
+        for section in &mut self.sections[self.active.min(used)..used] {
+            section.reset();
+            section.set_to_neutral();
+        }
+
+        let sections = &mut self.sections;
+        match band.filter_type {
+            FilterType::LowShelf => sections[0].set_low_shelf(sample_rate, frequency, q, gain_db),
+            FilterType::HighShelf => sections[0].set_high_shelf(sample_rate, frequency, q, gain_db),
+            FilterType::Peaking => sections[0].set_peaking(sample_rate, frequency, q, gain_db),
+            FilterType::HighPass { alignment, slope } => {
+                for (i, section) in sections[..used].iter_mut().enumerate() {
+                    let section_q = pass_section_q(alignment, slope, q, i);
+                    section.set_high_pass(sample_rate, frequency, section_q);
+                }
+            }
+            FilterType::LowPass { alignment, slope } => {
+                for (i, section) in sections[..used].iter_mut().enumerate() {
+                    let section_q = pass_section_q(alignment, slope, q, i);
+                    section.set_low_pass(sample_rate, frequency, section_q);
+                }
+            }
+            FilterType::Notch => sections[0].set_notch(sample_rate, frequency, q),
+            FilterType::BandPass => sections[0].set_band_pass(sample_rate, frequency, q),
+            FilterType::AllPass => sections[0].set_all_pass(sample_rate, frequency, q),
+            FilterType::TiltShelf => {
+                // Opposite shelves at the same frequency meet at 0 dB there
+                sections[0].set_low_shelf(sample_rate, frequency, q, -gain_db / 2.0);
+                sections[1].set_high_shelf(sample_rate, frequency, q, gain_db / 2.0);
+            }
+        }
+
+        // Unused sections fade to pass-through
+        for section in &mut self.sections[used..] {
+            section.set_target_coefficients(1.0, 0.0, 0.0, 0.0, 0.0);
+        }
+
+        self.used = used;
+        self.active = self.active.max(used);
+    }
+
+    /// Target coefficients of the sections in use
+    fn target_coefficients(&self) -> impl Iterator<Item = BiquadCoefficients> + '_ {
+        self.sections[..self.used]
+            .iter()
+            .map(BiquadFilter::target_coefficients)
+    }
+
+    /// Process a stereo sample pair through the cascade
+    #[inline]
+    fn process_sample(&mut self, mut left: f32, mut right: f32) -> (f32, f32) {
+        for section in &mut self.sections[..self.active] {
+            (left, right) = section.process_sample(left, right);
+        }
+        (left, right)
+    }
+
+    /// Reset filter state and drop sections that have faded out
+    fn reset(&mut self) {
+        for section in &mut self.sections {
+            section.reset();
+        }
+        self.active = self.used;
+    }
+
+    /// Set all sections to neutral (see [`BiquadFilter::set_to_neutral`])
+    fn set_to_neutral(&mut self) {
+        for section in &mut self.sections {
+            section.set_to_neutral();
+        }
+    }
+}
+
+/// Dynamic Parametric Equalizer supporting 1-32 bands
 ///
 /// This EQ supports a variable number of bands (1 to MAX_EQ_BANDS).
 /// All filters are pre-allocated to avoid allocations during audio processing.
-/// Bands are treated as peaking filters by default.
+/// Bands are treated as peaking filters by default; see [`FilterType`] for
+/// the other shapes (shelves, pass filters, notch, band-pass, all-pass, tilt).
 pub struct ParametricEq {
     /// Pre-allocated filters (all MAX_EQ_BANDS slots)
     /// Only the first `band_count` filters are active
-    filters: [BiquadFilter; MAX_EQ_BANDS],
+    filters: [BandFilter; MAX_EQ_BANDS],
 
     /// Pre-allocated band configurations
     bands: [EqBand; MAX_EQ_BANDS],
@@ -387,6 +852,12 @@ pub struct ParametricEq {
 
     /// Flag to recalculate filter coefficients
     needs_update: bool,
+
+    /// Minimum-phase biquads or linear-phase FIR
+    phase_mode: EqPhaseMode,
+
+    /// FIR stage used in linear-phase mode
+    linear_phase: LinearPhaseFir,
 }
 
 impl ParametricEq {
@@ -398,19 +869,10 @@ impl ParametricEq {
     /// - High: 8000 Hz shelf
     pub fn new() -> Self {
         // Pre-allocate all filters with neutral coefficients
-        let filters = [
-            BiquadFilter::new(),
-            BiquadFilter::new(),
-            BiquadFilter::new(),
-            BiquadFilter::new(),
-            BiquadFilter::new(),
-            BiquadFilter::new(),
-            BiquadFilter::new(),
-            BiquadFilter::new(),
-        ];
+        let filters = std::array::from_fn(|_| BandFilter::new());
 
         // Default band configurations (3 bands for backward compatibility)
-        let bands = [
+        let defaults = [
             EqBand::low_shelf(80.0, 0.0),
             EqBand::peaking(1000.0, 0.0, 1.0),
             EqBand::high_shelf(8000.0, 0.0),
@@ -420,6 +882,12 @@ impl ParametricEq {
             EqBand::peaking(4000.0, 0.0, 1.0),
             EqBand::peaking(16000.0, 0.0, 1.0),
         ];
+        let bands = std::array::from_fn(|i| {
+            defaults
+                .get(i)
+                .copied()
+                .unwrap_or_else(|| EqBand::peaking(1000.0, 0.0, 1.0))
+        });
 
         Self {
             filters,
@@ -428,6 +896,8 @@ impl ParametricEq {
             enabled: true,
             sample_rate: 44100,
             needs_update: true,
+            phase_mode: EqPhaseMode::MinimumPhase,
+            linear_phase: LinearPhaseFir::new(),
         }
     }
 
@@ -465,7 +935,7 @@ impl ParametricEq {
         }
 
         self.band_count = new_count;
-        self.needs_update = true;
+        self.parameters_changed();
     }
 
     /// Set low band parameters (band index 0)
@@ -511,7 +981,7 @@ impl ParametricEq {
             self.band_count = index + 1;
         }
 
-        self.needs_update = true;
+        self.parameters_changed();
     }
 
     /// Get a band's parameters
@@ -528,6 +998,19 @@ impl ParametricEq {
         }
     }
 
+    /// Change a band's filter type, keeping its frequency and Q
+    ///
+    /// Returns false if index is out of range.
+    pub fn set_band_filter_type(&mut self, index: usize, filter_type: FilterType) -> bool {
+        if index >= self.band_count {
+            return false;
+        }
+
+        self.bands[index].set_filter_type(filter_type);
+        self.parameters_changed();
+        true
+    }
+
     /// Set all bands at once
     ///
     /// # Arguments
@@ -559,7 +1042,7 @@ impl ParametricEq {
             self.band_count = new_count;
         }
 
-        self.needs_update = true;
+        self.parameters_changed();
     }
 
     /// Get all active bands
@@ -613,7 +1096,7 @@ impl ParametricEq {
         // Set to neutral - smoothing will transition to target coefficients
         self.filters[index].set_to_neutral();
         self.band_count += 1;
-        self.needs_update = true;
+        self.parameters_changed();
 
         Some(index)
     }
@@ -643,11 +1126,66 @@ impl ParametricEq {
         self.filters[self.band_count - 1].reset();
 
         self.band_count -= 1;
-        self.needs_update = true;
+        self.parameters_changed();
 
         true
     }
 
+    /// Set the phase mode
+    ///
+    /// Linear phase designs an FIR from the current bands on a background
+    /// thread; the biquads keep running until it is ready. The FIR adds
+    /// latency (see [`AudioEffect::latency_samples`]).
+    pub fn set_phase_mode(&mut self, mode: EqPhaseMode) {
+        if mode == self.phase_mode {
+            return;
+        }
+
+        self.phase_mode = mode;
+        match mode {
+            EqPhaseMode::LinearPhase => self.request_fir_design(),
+            EqPhaseMode::MinimumPhase => {
+                self.linear_phase.clear();
+                // The biquads sat idle; restart them from silence
+                for filter in &mut self.filters {
+                    filter.reset();
+                }
+            }
+        }
+    }
+
+    /// Get the phase mode
+    pub fn phase_mode(&self) -> EqPhaseMode {
+        self.phase_mode
+    }
+
+    /// Mark coefficients stale, redesigning the FIR in linear-phase mode
+    fn parameters_changed(&mut self) {
+        self.needs_update = true;
+        if self.phase_mode == EqPhaseMode::LinearPhase {
+            self.request_fir_design();
+        }
+    }
+
+    /// Switch the biquads to a new sample rate
+    fn change_sample_rate(&mut self, sample_rate: u32) {
+        self.sample_rate = sample_rate;
+        // Reset all filter states when sample rate changes
+        for filter in &mut self.filters {
+            filter.reset();
+        }
+        self.needs_update = true;
+    }
+
+    /// Send the current biquad targets to the FIR designer
+    fn request_fir_design(&mut self) {
+        self.update_filters();
+        let sections = self.filters[..self.band_count]
+            .iter()
+            .flat_map(BandFilter::target_coefficients);
+        self.linear_phase.request_design(sections, self.sample_rate);
+    }
+
     /// Update filter coefficients if needed
     fn update_filters(&mut self) {
         if !self.needs_update {
@@ -657,25 +1195,29 @@ impl ParametricEq {
         let sr = self.sample_rate as f32;
 
         for i in 0..self.band_count {
-            let band = &self.bands[i];
-            // Use the appropriate filter type based on band configuration
-            match band.filter_type {
-                FilterType::LowShelf => {
-                    self.filters[i].set_low_shelf(sr, band.frequency, band.q, band.gain_db);
-                }
-                FilterType::HighShelf => {
-                    self.filters[i].set_high_shelf(sr, band.frequency, band.q, band.gain_db);
-                }
-                FilterType::Peaking => {
-                    self.filters[i].set_peaking(sr, band.frequency, band.q, band.gain_db);
-                }
-            }
+            self.filters[i].configure(&self.bands[i], sr);
         }
 
         self.needs_update = false;
     }
 }
 
+/// Run an interleaved stereo buffer through the band cascades
+fn process_filters(filters: &mut [BandFilter], buffer: &mut [f32]) {
+    for chunk in buffer.chunks_exact_mut(2) {
+        let mut left = chunk[0];
+        let mut right = chunk[1];
+
+        // Process through all active filters
+        for filter in filters.iter_mut() {
+            (left, right) = filter.process_sample(left, right);
+        }
+
+        chunk[0] = left;
+        chunk[1] = right;
+    }
+}
+
 impl Default for ParametricEq {
     fn default() -> Self {
         Self::new()
@@ -689,34 +1231,23 @@ impl AudioEffect for ParametricEq {
             return;
         }
 
-        // Update sample rate if changed
+        // Rate changed without set_sample_rate(): the biquads follow, but a
+        // linear-phase FIR for the new rate waits for set_sample_rate()
         if self.sample_rate != sample_rate {
-            self.sample_rate = sample_rate;
-            // Reset all filter states when sample rate changes
-            for filter in &mut self.filters {
-                filter.reset();
-            }
-            self.needs_update = true;
+            self.change_sample_rate(sample_rate);
         }
 
         // Update filters if parameters changed
         self.update_filters();
 
-        // Process interleaved stereo buffer
-        // Process through all active bands in series
-        for chunk in buffer.chunks_exact_mut(2) {
-            let mut left = chunk[0];
-            let mut right = chunk[1];
-
-            // Process through all active filters
-            for i in 0..self.band_count {
-                let (l, r) = self.filters[i].process_sample(left, right);
-                left = l;
-                right = r;
+        let filters = &mut self.filters[..self.band_count];
+        match self.phase_mode {
+            EqPhaseMode::MinimumPhase => process_filters(filters, buffer),
+            EqPhaseMode::LinearPhase => {
+                self.linear_phase.process(buffer, sample_rate, |buffer| {
+                    process_filters(filters, buffer)
+                });
             }
-
-            chunk[0] = left;
-            chunk[1] = right;
         }
     }
 
@@ -729,6 +1260,7 @@ impl AudioEffect for ParametricEq {
         for filter in &mut self.filters {
             filter.reset();
         }
+        self.linear_phase.reset();
     }
 
     fn set_enabled(&mut self, enabled: bool) {
@@ -743,6 +1275,21 @@ impl AudioEffect for ParametricEq {
         "Parametric EQ"
     }
 
+    fn set_sample_rate(&mut self, sample_rate: u32) {
+        if self.sample_rate != sample_rate {
+            self.change_sample_rate(sample_rate);
+        }
+        if self.phase_mode == EqPhaseMode::LinearPhase
+            && self.linear_phase.requested_rate() != sample_rate
+        {
+            self.request_fir_design();
+        }
+    }
+
+    fn latency_samples(&self) -> usize {
+        self.linear_phase.latency_samples()
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
@@ -990,7 +1537,7 @@ mod tests {
         let mut eq = ParametricEq::new();
 
         // Create more bands than allowed
-        let bands: Vec<EqBand> = (0..20)
+        let bands: Vec<EqBand> = (0..MAX_EQ_BANDS + 12)
             .map(|i| EqBand::peaking(100.0 * (i as f32 + 1.0), 0.0, 1.0))
             .collect();
 
@@ -1110,4 +1657,146 @@ mod tests {
         // Band count should not exceed MAX_EQ_BANDS
         assert!(eq.band_count() <= MAX_EQ_BANDS);
     }
+
+    #[test]
+    fn gainless_filter_types_ignore_gain() {
+        let mut band = EqBand::with_filter_type(FilterType::Notch, 1000.0, 6.0, 4.0);
+        assert_eq!(band.gain_db(), 0.0);
+        band.set_gain_db(3.0);
+        assert_eq!(band.gain_db(), 0.0);
+
+        let mut band = EqBand::peaking(1000.0, 6.0, 1.0);
+        band.set_filter_type(FilterType::BandPass);
+        assert_eq!(band.gain_db(), 0.0);
+        assert_eq!(band.q(), 1.0);
+
+        assert!(EqBand::tilt_shelf(1000.0, 4.0).filter_type().has_gain());
+    }
+
+    #[test]
+    fn filter_slope_conversion() {
+        for slope in [FilterSlope::Db12, FilterSlope::Db24, FilterSlope::Db48] {
+            assert_eq!(
+                FilterSlope::from_db_per_octave(slope.db_per_octave()),
+                Some(slope)
+            );
+        }
+        assert_eq!(FilterSlope::from_db_per_octave(18), None);
+        assert_eq!(FilterSlope::Db48.sections(), 4);
+    }
+
+    #[test]
+    fn pass_filter_uses_slope_sections() {
+        let mut eq = ParametricEq::new();
+        eq.set_bands(vec![EqBand::high_pass(
+            100.0,
+            FilterAlignment::LinkwitzRiley,
+            FilterSlope::Db48,
+        )]);
+        eq.reset();
+        assert_eq!(eq.filters[0].used, 4);
+        assert_eq!(eq.filters[0].target_coefficients().count(), 4);
+
+        // Dropping to a single section keeps the others running until reset
+        eq.set_band_filter_type(0, FilterType::Peaking);
+        eq.update_filters();
+        assert_eq!(eq.filters[0].used, 1);
+        assert_eq!(eq.filters[0].active, 4);
+        eq.reset();
+        assert_eq!(eq.filters[0].active, 1);
+    }
+
+    #[test]
+    fn set_band_filter_type_out_of_range() {
+        let mut eq = ParametricEq::with_band_count(2);
+        assert!(eq.set_band_filter_type(1, FilterType::Notch));
+        assert_eq!(eq.get_band(1).unwrap().filter_type(), FilterType::Notch);
+        assert!(!eq.set_band_filter_type(2, FilterType::Notch));
+    }
+
+    #[test]
+    fn changing_filter_type_mid_stream_stays_finite() {
+        let mut eq = ParametricEq::with_band_count(1);
+        eq.set_band(0, EqBand::peaking(1000.0, 6.0, 1.0));
+
+        let mut buffer = crate::effects::tests::generate_sine(1000.0, 44100, 0.1);
+        eq.process(&mut buffer, 44100);
+
+        eq.set_band(
+            0,
+            EqBand::low_pass(500.0, FilterAlignment::Butterworth, FilterSlope::Db48),
+        );
+        let mut buffer = crate::effects::tests::generate_sine(1000.0, 44100, 0.1);
+        eq.process(&mut buffer, 44100);
+        assert!(buffer.iter().all(|s| s.is_finite()));
+
+        eq.set_band(0, EqBand::tilt_shelf(1000.0, -6.0));
+        let mut buffer = crate::effects::tests::generate_sine(1000.0, 44100, 0.1);
+        eq.process(&mut buffer, 44100);
+        assert!(buffer.iter().all(|s| s.is_finite()));
+    }
+
+    #[test]
+    fn linear_phase_mode_reports_latency() {
+        let mut eq = ParametricEq::new();
+        eq.set_bands(vec![EqBand::peaking(1000.0, 6.0, 1.0)]);
+        eq.set_phase_mode(EqPhaseMode::LinearPhase);
+        assert_eq!(eq.phase_mode(), EqPhaseMode::LinearPhase);
+
+        // Biquads keep running until the FIR has been designed and faded in
+        let mut buffer = vec![0.0f32; 1024];
+        let start = std::time::Instant::now();
+        while eq.latency_samples() == 0 {
+            assert!(start.elapsed().as_secs() < 10, "FIR never became active");
+            eq.process(&mut buffer, 44100);
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        let latency = eq.latency_samples();
+        assert!(latency > 0);
+
+        // The FIR keeps the peaking boost: +6 dB at 1 kHz
+        let mut sine = crate::effects::tests::generate_sine(1000.0, 44100, 1.0);
+        for chunk in sine.chunks_mut(1024) {
+            eq.process(chunk, 44100);
+        }
+        let peak = sine[(latency + 4410) * 2..]
+            .iter()
+            .fold(0.0f32, |max, s| max.max(s.abs()));
+        assert!((peak - 2.0).abs() < 0.05, "peak {}", peak);
+
+        // Back to minimum phase: no latency
+        eq.set_phase_mode(EqPhaseMode::MinimumPhase);
+        assert_eq!(eq.latency_samples(), 0);
+    }
+
+    #[test]
+    fn linear_phase_redesigns_on_set_sample_rate() {
+        fn wait_for_fir(eq: &mut ParametricEq, sample_rate: u32) {
+            let mut buffer = vec![0.0f32; 1024];
+            let start = std::time::Instant::now();
+            while eq.latency_samples() == 0 {
+                assert!(start.elapsed().as_secs() < 10, "FIR never became active");
+                eq.process(&mut buffer, sample_rate);
+                std::thread::sleep(std::time::Duration::from_millis(1));
+            }
+        }
+
+        let mut eq = ParametricEq::new();
+        eq.set_bands(vec![EqBand::peaking(1000.0, 6.0, 1.0)]);
+        eq.set_phase_mode(EqPhaseMode::LinearPhase);
+        wait_for_fir(&mut eq, 44100);
+
+        // An unannounced rate falls back to the biquads; process() never
+        // asks for a design itself
+        let mut buffer = vec![0.0f32; 1024];
+        for _ in 0..20 {
+            eq.process(&mut buffer, 48000);
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        assert_eq!(eq.latency_samples(), 0);
+
+        eq.set_sample_rate(48000);
+        wait_for_fir(&mut eq, 48000);
+        assert_eq!(eq.latency_samples(), 8192 / 2);
+    }
 }
@@ -0,0 +1,542 @@
+/// Parametric EQ preset import/export
+///
+/// Parses the filter files produced by headphone and room correction tools
+/// into `ParametricEq` bands plus a preamp:
+///
+/// - **Equalizer APO** `ParametricEQ.txt` (the format AutoEQ publishes):
+///   `Preamp: -6.2 dB` followed by `Filter 1: ON PK Fc 105 Hz Gain 6.5 dB Q 0.70`
+/// - **REW** filter exports: a text header followed by the same `Filter N:` lines,
+///   with unused slots written as `None`
+///
+/// The preamp is not applied by the EQ itself. It describes how much headroom
+/// the filter set needs, which callers hand to the headroom manager
+/// (see [`EqPreset::headroom_db`]).
+use super::eq::{
+    pass_section_q, EqBand, FilterAlignment, FilterSlope, FilterType, ParametricEq, MAX_EQ_BANDS,
+};
+
+/// Source format of an EQ preset file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EqPresetFormat {
+    /// Equalizer APO / AutoEQ `ParametricEQ.txt`
+    EqualizerApo,
+    /// REW (Room EQ Wizard) filter settings export
+    Rew,
+}
+
+/// Errors that can occur while parsing an EQ preset
+#[derive(Debug, Clone, PartialEq)]
+pub enum EqPresetError {
+    /// A `Filter` or `Preamp` line could not be parsed
+    InvalidLine { line: usize, reason: String },
+    /// Filter type we can't represent as an EQ band (e.g. first-order LP1/HP1)
+    UnsupportedFilter { line: usize, filter_type: String },
+    /// More enabled filters than the EQ can hold
+    TooManyBands(usize),
+    /// The file contained no enabled filters
+    NoFilters,
+}
+
+impl std::fmt::Display for EqPresetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EqPresetError::InvalidLine { line, reason } => {
+                write!(f, "Invalid filter on line {}: {}", line, reason)
+            }
+            EqPresetError::UnsupportedFilter { line, filter_type } => {
+                write!(
+                    f,
+                    "Unsupported filter type '{}' on line {}",
+                    filter_type, line
+                )
+            }
+            EqPresetError::TooManyBands(count) => write!(
+                f,
+                "Too many filters: {} (maximum is {})",
+                count, MAX_EQ_BANDS
+            ),
+            EqPresetError::NoFilters => write!(f, "No enabled filters found"),
+        }
+    }
+}
+
+impl std::error::Error for EqPresetError {}
+
+/// Parametric EQ filter set with its preamp
+#[derive(Debug, Clone)]
+pub struct EqPreset {
+    /// Preamp in dB (usually negative, to make room for the largest boost)
+    pub preamp_db: f32,
+    /// EQ bands in file order
+    pub bands: Vec<EqBand>,
+}
+
+impl EqPreset {
+    /// Parse a preset, detecting whether it is an APO or REW file
+    pub fn parse(text: &str) -> Result<Self, EqPresetError> {
+        Self::parse_as(text, Self::detect_format(text))
+    }
+
+    /// Parse an Equalizer APO / AutoEQ `ParametricEQ.txt`
+    pub fn from_apo_str(text: &str) -> Result<Self, EqPresetError> {
+        Self::parse_as(text, EqPresetFormat::EqualizerApo)
+    }
+
+    /// Parse a REW filter settings export
+    pub fn from_rew_str(text: &str) -> Result<Self, EqPresetError> {
+        Self::parse_as(text, EqPresetFormat::Rew)
+    }
+
+    /// Guess the format of a preset file from its contents
+    ///
+    /// REW exports start with a "Filter Settings file" header and name the
+    /// REW version; anything else is treated as Equalizer APO.
+    pub fn detect_format(text: &str) -> EqPresetFormat {
+        let is_rew = text.lines().take(5).any(|line| {
+            let line = line.trim();
+            line.starts_with("Filter Settings file") || line.starts_with("Room EQ")
+        });
+
+        if is_rew {
+            EqPresetFormat::Rew
+        } else {
+            EqPresetFormat::EqualizerApo
+        }
+    }
+
+    /// Capture the active bands of an EQ as a preset
+    pub fn from_eq(eq: &ParametricEq, preamp_db: f32) -> Self {
+        Self {
+            preamp_db,
+            bands: eq.get_bands(),
+        }
+    }
+
+    /// Load the bands into an EQ, replacing its current bands
+    pub fn apply_to(&self, eq: &mut ParametricEq) {
+        eq.set_bands(self.bands.clone());
+    }
+
+    /// Headroom in dB the filter set needs to avoid clipping
+    ///
+    /// The larger of the file's preamp attenuation and the largest band
+    /// boost. Feed this to `HeadroomManager::set_eq_max_boost_db` so that
+    /// Auto mode reserves the same headroom the preset author intended.
+    pub fn headroom_db(&self) -> f64 {
+        let max_boost = self
+            .bands
+            .iter()
+            .map(EqBand::max_boost_db)
+            .fold(0.0_f32, f32::max);
+
+        (-self.preamp_db).max(max_boost).max(0.0) as f64
+    }
+
+    /// Serialize to Equalizer APO `ParametricEQ.txt` format
+    ///
+    /// The output parses back to the same bands and preamp, with two
+    /// exceptions APO has no single filter for: steep or Linkwitz-Riley
+    /// pass filters are written as their cascaded `HPQ`/`LPQ` sections, and
+    /// tilt bands as a low/high shelf pair. Those re-import as several bands
+    /// with the same combined response.
+    pub fn to_apo_string(&self) -> String {
+        let mut out = format!("Preamp: {} dB\n", self.preamp_db);
+        let mut index = 0;
+        let mut push = |kind: &str, frequency: f32, gain_db: Option<f32>, q: f32| {
+            index += 1;
+            let gain = gain_db
+                .map(|gain| format!(" Gain {} dB", gain))
+                .unwrap_or_default();
+            out.push_str(&format!(
+                "Filter {}: ON {} Fc {} Hz{} Q {}\n",
+                index, kind, frequency, gain, q
+            ));
+        };
+
+        for band in &self.bands {
+            let (frequency, gain_db, q) = (band.frequency, band.gain_db(), band.q());
+            match band.filter_type() {
+                FilterType::LowShelf => push("LSC", frequency, Some(gain_db), q),
+                FilterType::Peaking => push("PK", frequency, Some(gain_db), q),
+                FilterType::HighShelf => push("HSC", frequency, Some(gain_db), q),
+                FilterType::HighPass { alignment, slope } => {
+                    for section in 0..slope.sections() {
+                        let section_q = pass_section_q(alignment, slope, q, section);
+                        push("HPQ", frequency, None, section_q);
+                    }
+                }
+                FilterType::LowPass { alignment, slope } => {
+                    for section in 0..slope.sections() {
+                        let section_q = pass_section_q(alignment, slope, q, section);
+                        push("LPQ", frequency, None, section_q);
+                    }
+                }
+                FilterType::Notch => push("NO", frequency, None, q),
+                FilterType::BandPass => push("BP", frequency, None, q),
+                FilterType::AllPass => push("AP", frequency, None, q),
+                FilterType::TiltShelf => {
+                    push("LSC", frequency, Some(-gain_db / 2.0), q);
+                    push("HSC", frequency, Some(gain_db / 2.0), q);
+                }
+            }
+        }
+
+        out
+    }
+
+    fn parse_as(text: &str, format: EqPresetFormat) -> Result<Self, EqPresetError> {
+        let mut preamp_db = 0.0;
+        let mut bands = Vec::new();
+
+        for (index, raw) in text.lines().enumerate() {
+            let line_no = index + 1;
+            let line = raw.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(rest) = line.strip_prefix("Preamp:") {
+                preamp_db = parse_db(rest).ok_or_else(|| EqPresetError::InvalidLine {
+                    line: line_no,
+                    reason: format!("bad preamp '{}'", rest.trim()),
+                })?;
+            } else if line.starts_with("Filter") {
+                // "Filter Settings file" is the REW header, not a filter
+                let Some((_, spec)) = line.split_once(':') else {
+                    continue;
+                };
+                if let Some(band) = parse_filter(spec, line_no)? {
+                    bands.push(band);
+                }
+            }
+            // Other APO commands (Device:, Channel:, Include:, ...) and REW
+            // header lines don't affect the filter set
+        }
+
+        if bands.is_empty() {
+            return Err(EqPresetError::NoFilters);
+        }
+        if bands.len() > MAX_EQ_BANDS {
+            return Err(EqPresetError::TooManyBands(bands.len()));
+        }
+
+        // REW doesn't export a preamp; reserve the largest boost instead
+        if format == EqPresetFormat::Rew && preamp_db == 0.0 {
+            let max_boost = bands
+                .iter()
+                .map(EqBand::max_boost_db)
+                .fold(0.0_f32, f32::max);
+            preamp_db = -max_boost;
+        }
+
+        Ok(Self { preamp_db, bands })
+    }
+}
+
+/// Parse the part of a filter line after `Filter N:`
+///
+/// Returns `Ok(None)` for disabled filters and REW's empty `None` slots.
+fn parse_filter(spec: &str, line: usize) -> Result<Option<EqBand>, EqPresetError> {
+    let tokens: Vec<&str> = spec.split_whitespace().collect();
+
+    match tokens.first() {
+        Some(state) if state.eq_ignore_ascii_case("ON") => {}
+        // OFF filters and blank slots are skipped
+        _ => return Ok(None),
+    }
+
+    let Some(&kind) = tokens.get(1) else {
+        return Ok(None);
+    };
+
+    let filter_type = match kind.to_ascii_uppercase().as_str() {
+        "NONE" => return Ok(None),
+        "PK" | "PEQ" | "MODAL" => FilterType::Peaking,
+        "LS" | "LSC" | "LSQ" => FilterType::LowShelf,
+        "HS" | "HSC" | "HSQ" => FilterType::HighShelf,
+        // APO's LP/HP are 12 dB/oct Butterworth; the Q variants set the resonance
+        "HP" | "HPQ" => FilterType::HighPass {
+            alignment: FilterAlignment::Butterworth,
+            slope: FilterSlope::Db12,
+        },
+        "LP" | "LPQ" => FilterType::LowPass {
+            alignment: FilterAlignment::Butterworth,
+            slope: FilterSlope::Db12,
+        },
+        "NO" => FilterType::Notch,
+        "BP" => FilterType::BandPass,
+        "AP" => FilterType::AllPass,
+        _ => {
+            return Err(EqPresetError::UnsupportedFilter {
+                line,
+                filter_type: kind.to_string(),
+            })
+        }
+    };
+
+    let invalid = |reason: String| EqPresetError::InvalidLine { line, reason };
+
+    let frequency = value_after(&tokens, "Fc")
+        .ok_or_else(|| invalid("missing Fc".to_string()))?
+        .map_err(invalid)?;
+    let gain_db = value_after(&tokens, "Gain")
+        .unwrap_or(Ok(0.0))
+        .map_err(invalid)?;
+
+    let q = match value_after(&tokens, "Q") {
+        Some(q) => q.map_err(invalid)?,
+        None => match bandwidth_after(&tokens) {
+            Some(bw) => bandwidth_to_q(bw.map_err(invalid)?),
+            // Shelves and pass filters without Q use the Butterworth slope,
+            // like EqBand::low_shelf and EqBand::high_pass
+            None if matches!(
+                filter_type,
+                FilterType::LowShelf
+                    | FilterType::HighShelf
+                    | FilterType::HighPass { .. }
+                    | FilterType::LowPass { .. }
+            ) =>
+            {
+                std::f32::consts::FRAC_1_SQRT_2
+            }
+            None => return Err(invalid("missing Q or BW".to_string())),
+        },
+    };
+
+    if frequency <= 0.0 {
+        return Err(invalid(format!("invalid frequency {}", frequency)));
+    }
+
+    Ok(Some(EqBand::with_filter_type(
+        filter_type,
+        frequency,
+        gain_db,
+        q,
+    )))
+}
+
+/// Find `key <number>` in a token list
+fn value_after(tokens: &[&str], key: &str) -> Option<Result<f32, String>> {
+    let pos = tokens.iter().position(|t| t.eq_ignore_ascii_case(key))?;
+    let raw = tokens.get(pos + 1).copied().unwrap_or("");
+    Some(parse_number(raw).ok_or_else(|| format!("bad {} value '{}'", key, raw)))
+}
+
+/// Find `BW Oct <number>` (APO bandwidth in octaves)
+fn bandwidth_after(tokens: &[&str]) -> Option<Result<f32, String>> {
+    let pos = tokens.iter().position(|t| t.eq_ignore_ascii_case("BW"))?;
+    let mut idx = pos + 1;
+    if tokens
+        .get(idx)
+        .is_some_and(|t| t.eq_ignore_ascii_case("Oct"))
+    {
+        idx += 1;
+    }
+    let raw = tokens.get(idx).copied().unwrap_or("");
+    Some(parse_number(raw).ok_or_else(|| format!("bad BW value '{}'", raw)))
+}
+
+/// Convert a bandwidth in octaves to Q
+fn bandwidth_to_q(octaves: f32) -> f32 {
+    let factor = 2.0_f32.powf(octaves);
+    factor.sqrt() / (factor - 1.0)
+}
+
+/// Parse a number, tolerating REW's thousands separators ("1,000")
+fn parse_number(raw: &str) -> Option<f32> {
+    let cleaned: String = raw.chars().filter(|&c| c != ',').collect();
+    cleaned.parse::<f32>().ok().filter(|v| v.is_finite())
+}
+
+/// Parse "-6.2 dB" / "-6.2dB"
+fn parse_db(raw: &str) -> Option<f32> {
+    let raw = raw.trim();
+    let number = raw
+        .strip_suffix("dB")
+        .or_else(|| raw.strip_suffix("db"))
+        .unwrap_or(raw);
+    parse_number(number.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AUTOEQ: &str = "Preamp: -6.4 dB
+Filter 1: ON LSC Fc 105 Hz Gain 6.1 dB Q 0.70
+Filter 2: ON PK Fc 190 Hz Gain -2.3 dB Q 0.77
+Filter 3: ON PK Fc 1347 Hz Gain 2.2 dB Q 2.13
+Filter 4: ON PK Fc 3287 Hz Gain -3.5 dB Q 3.58
+Filter 5: ON PK Fc 5389 Hz Gain 4.7 dB Q 4.02
+Filter 6: ON PK Fc 6710 Hz Gain -2.9 dB Q 2.98
+Filter 7: ON PK Fc 8227 Hz Gain 2.0 dB Q 3.46
+Filter 8: ON PK Fc 9874 Hz Gain -1.4 dB Q 2.66
+Filter 9: ON PK Fc 12058 Hz Gain 0.9 dB Q 1.29
+Filter 10: ON HSC Fc 10000 Hz Gain -2.4 dB Q 0.70
+";
+
+    const REW: &str = "Filter Settings file
+
+Room EQ V5.20.13
+Dated: Mar 3, 2024 10:12:45 AM
+
+Notes:
+
+Equaliser: Generic
+Filter  1: ON  PK       Fc    63.5 Hz  Gain  -5.0 dB  Q  4.00
+Filter  2: ON  LS       Fc   120 Hz   Gain   2.5 dB
+Filter  3: ON  PK       Fc 1,250 Hz   Gain   1.5 dB  Q  1.20
+Filter  4: ON  None
+Filter  5: OFF PK       Fc   800 Hz   Gain   3.0 dB  Q  1.00
+";
+
+    #[test]
+    fn parse_autoeq_file() {
+        let preset = EqPreset::parse(AUTOEQ).unwrap();
+
+        assert_eq!(preset.preamp_db, -6.4);
+        assert_eq!(preset.bands.len(), 10);
+        assert_eq!(preset.bands[0].filter_type(), FilterType::LowShelf);
+        assert_eq!(preset.bands[0].frequency, 105.0);
+        assert_eq!(preset.bands[0].q(), 0.70);
+        assert_eq!(preset.bands[4].gain_db(), 4.7);
+        assert_eq!(preset.bands[9].filter_type(), FilterType::HighShelf);
+    }
+
+    #[test]
+    fn parse_rew_export() {
+        assert_eq!(EqPreset::detect_format(REW), EqPresetFormat::Rew);
+
+        let preset = EqPreset::parse(REW).unwrap();
+
+        // None and OFF slots are skipped
+        assert_eq!(preset.bands.len(), 3);
+        assert_eq!(preset.bands[1].filter_type(), FilterType::LowShelf);
+        assert_eq!(preset.bands[2].frequency, 1250.0);
+        // No preamp in REW files - reserve the largest boost
+        assert_eq!(preset.preamp_db, -2.5);
+    }
+
+    #[test]
+    fn bandwidth_converts_to_q() {
+        let preset = EqPreset::from_apo_str("Filter: ON PK Fc 1000 Hz Gain 3 dB BW Oct 1").unwrap();
+        assert!((preset.bands[0].q() - 1.414).abs() < 0.01);
+    }
+
+    #[test]
+    fn unsupported_filter_reports_line() {
+        let err = EqPreset::from_apo_str("Preamp: -3 dB\nFilter 1: ON LP1 Fc 80 Hz").unwrap_err();
+        assert_eq!(
+            err,
+            EqPresetError::UnsupportedFilter {
+                line: 2,
+                filter_type: "LP1".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn empty_file_is_error() {
+        let err = EqPreset::from_apo_str("Preamp: -3 dB\n").unwrap_err();
+        assert_eq!(err, EqPresetError::NoFilters);
+    }
+
+    #[test]
+    fn apo_round_trip_through_eq() {
+        let preset = EqPreset::parse(AUTOEQ).unwrap();
+
+        let mut eq = ParametricEq::new();
+        preset.apply_to(&mut eq);
+        assert_eq!(eq.band_count(), 10);
+
+        let exported = EqPreset::from_eq(&eq, preset.preamp_db).to_apo_string();
+        let reparsed = EqPreset::from_apo_str(&exported).unwrap();
+
+        assert_eq!(reparsed.preamp_db, preset.preamp_db);
+        assert_eq!(reparsed.bands.len(), preset.bands.len());
+        for (a, b) in reparsed.bands.iter().zip(&preset.bands) {
+            assert_eq!(a.filter_type(), b.filter_type());
+            assert_eq!(a.frequency, b.frequency);
+            assert_eq!(a.gain_db(), b.gain_db());
+            assert_eq!(a.q(), b.q());
+        }
+    }
+
+    #[test]
+    fn headroom_uses_preamp_or_max_boost() {
+        let preset = EqPreset::parse(AUTOEQ).unwrap();
+        assert!((preset.headroom_db() - 6.4).abs() < 1e-6);
+
+        let no_preamp = EqPreset {
+            preamp_db: 0.0,
+            bands: vec![EqBand::peaking(1000.0, 4.0, 1.0)],
+        };
+        assert_eq!(no_preamp.headroom_db(), 4.0);
+
+        // A downward tilt boosts the bass by half its gain
+        let tilt = EqPreset {
+            preamp_db: 0.0,
+            bands: vec![EqBand::tilt_shelf(1000.0, -6.0)],
+        };
+        assert_eq!(tilt.headroom_db(), 3.0);
+    }
+
+    #[test]
+    fn parse_pass_notch_band_and_all_pass() {
+        let preset = EqPreset::from_apo_str(
+            "Filter 1: ON HP Fc 30 Hz
+Filter 2: ON LPQ Fc 16000 Hz Q 0.9
+Filter 3: ON NO Fc 60 Hz Q 8
+Filter 4: ON BP Fc 1000 Hz BW Oct 1
+Filter 5: ON AP Fc 500 Hz Q 0.7",
+        )
+        .unwrap();
+
+        let butterworth_12 = (FilterAlignment::Butterworth, FilterSlope::Db12);
+        let FilterType::HighPass { alignment, slope } = preset.bands[0].filter_type() else {
+            panic!("expected high-pass");
+        };
+        assert_eq!((alignment, slope), butterworth_12);
+        assert!((preset.bands[0].q() - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+
+        let FilterType::LowPass { alignment, slope } = preset.bands[1].filter_type() else {
+            panic!("expected low-pass");
+        };
+        assert_eq!((alignment, slope), butterworth_12);
+        assert_eq!(preset.bands[1].q(), 0.9);
+
+        assert_eq!(preset.bands[2].filter_type(), FilterType::Notch);
+        assert_eq!(preset.bands[3].filter_type(), FilterType::BandPass);
+        assert_eq!(preset.bands[4].filter_type(), FilterType::AllPass);
+        assert!(preset.bands.iter().all(|band| band.gain_db() == 0.0));
+    }
+
+    #[test]
+    fn steep_pass_and_tilt_export_as_sections() {
+        let preset = EqPreset {
+            preamp_db: -3.0,
+            bands: vec![
+                EqBand::high_pass(40.0, FilterAlignment::LinkwitzRiley, FilterSlope::Db48),
+                EqBand::tilt_shelf(1000.0, 6.0),
+                EqBand::notch(50.0, 10.0),
+            ],
+        };
+
+        let exported = preset.to_apo_string();
+        let reparsed = EqPreset::from_apo_str(&exported).unwrap();
+
+        // 4 HPQ sections + LSC/HSC pair + notch
+        assert_eq!(reparsed.bands.len(), 7);
+        for band in &reparsed.bands[..4] {
+            assert!(matches!(band.filter_type(), FilterType::HighPass { .. }));
+            assert_eq!(band.frequency, 40.0);
+        }
+        assert_eq!(reparsed.bands[4].filter_type(), FilterType::LowShelf);
+        assert_eq!(reparsed.bands[4].gain_db(), -3.0);
+        assert_eq!(reparsed.bands[5].filter_type(), FilterType::HighShelf);
+        assert_eq!(reparsed.bands[5].gain_db(), 3.0);
+        assert_eq!(reparsed.bands[6].filter_type(), FilterType::Notch);
+        assert_eq!(reparsed.bands[6].q(), 10.0);
+    }
+}
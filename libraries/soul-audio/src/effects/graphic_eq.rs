@@ -5,8 +5,10 @@
 //! - 31-band: Third-octave frequencies
 //! - Per-band gain control (-12 to +12 dB)
 //! - Preset support
+//! - Optional linear-phase mode
 
 use super::chain::AudioEffect;
+use super::linear_phase::{BiquadCoefficients, EqPhaseMode, LinearPhaseFir};
 use std::f32::consts::PI;
 
 /// 10-band ISO standard frequencies (Hz)
@@ -215,6 +217,17 @@ impl BiquadBand {
         }
     }
 
+    /// Target coefficients (what the active ones are smoothing toward)
+    fn target_coefficients(&self) -> BiquadCoefficients {
+        [
+            self.target_b0,
+            self.target_b1,
+            self.target_b2,
+            self.target_a1,
+            self.target_a2,
+        ]
+    }
+
     #[inline]
     fn process(&mut self, left: f32, right: f32) -> (f32, f32) {
         // Smooth coefficient transition if in progress
@@ -347,6 +360,12 @@ pub struct GraphicEq {
 
     /// Coefficients need recalculation
     needs_update: bool,
+
+    /// Minimum-phase biquads or linear-phase FIR
+    phase_mode: EqPhaseMode,
+
+    /// FIR stage used in linear-phase mode
+    linear_phase: LinearPhaseFir,
 }
 
 impl GraphicEq {
@@ -376,6 +395,8 @@ impl GraphicEq {
             enabled: true,
             sample_rate: 44100,
             needs_update: true,
+            phase_mode: EqPhaseMode::MinimumPhase,
+            linear_phase: LinearPhaseFir::new(),
         }
     }
 
@@ -405,7 +426,7 @@ impl GraphicEq {
             band.set_gain(gain_db);
             // Coefficient smoothing handles the transition - no reset needed
             self.preset = GraphicEqPreset::Custom;
-            self.needs_update = true;
+            self.parameters_changed();
         }
     }
 
@@ -419,7 +440,7 @@ impl GraphicEq {
             // Coefficient smoothing handles the transition - no reset needed
         }
         self.preset = GraphicEqPreset::Custom;
-        self.needs_update = true;
+        self.parameters_changed();
     }
 
     /// Get all band gains (for 10-band)
@@ -464,7 +485,7 @@ impl GraphicEq {
             }
         }
 
-        self.needs_update = true;
+        self.parameters_changed();
     }
 
     /// Get current preset
@@ -479,7 +500,60 @@ impl GraphicEq {
             // Coefficient smoothing handles the transition - no reset needed
         }
         self.preset = GraphicEqPreset::Flat;
+        self.parameters_changed();
+    }
+
+    /// Set the phase mode
+    ///
+    /// Linear phase designs an FIR from the current gains on a background
+    /// thread; the biquads keep running until it is ready. The FIR adds
+    /// latency (see [`AudioEffect::latency_samples`]).
+    pub fn set_phase_mode(&mut self, mode: EqPhaseMode) {
+        if mode == self.phase_mode {
+            return;
+        }
+
+        self.phase_mode = mode;
+        match mode {
+            EqPhaseMode::LinearPhase => self.request_fir_design(),
+            EqPhaseMode::MinimumPhase => {
+                self.linear_phase.clear();
+                // The biquads sat idle; restart them from silence
+                for band in &mut self.bands {
+                    band.reset();
+                }
+            }
+        }
+    }
+
+    /// Get the phase mode
+    pub fn phase_mode(&self) -> EqPhaseMode {
+        self.phase_mode
+    }
+
+    /// Mark coefficients stale, redesigning the FIR in linear-phase mode
+    fn parameters_changed(&mut self) {
         self.needs_update = true;
+        if self.phase_mode == EqPhaseMode::LinearPhase {
+            self.request_fir_design();
+        }
+    }
+
+    /// Switch the bands to a new sample rate
+    fn change_sample_rate(&mut self, sample_rate: u32) {
+        self.sample_rate = sample_rate;
+        // Bug fix: Reset filter state when sample rate changes to prevent transients
+        for band in &mut self.bands {
+            band.reset();
+        }
+        self.needs_update = true;
+    }
+
+    /// Send the current biquad targets to the FIR designer
+    fn request_fir_design(&mut self) {
+        self.update_coefficients();
+        let sections = self.bands.iter().map(BiquadBand::target_coefficients);
+        self.linear_phase.request_design(sections, self.sample_rate);
     }
 
     /// Update filter coefficients for all bands
@@ -494,6 +568,21 @@ impl GraphicEq {
     }
 }
 
+/// Run an interleaved stereo buffer through the bands in series
+fn process_bands(bands: &mut [BiquadBand], buffer: &mut [f32]) {
+    for chunk in buffer.chunks_exact_mut(2) {
+        let mut left = chunk[0];
+        let mut right = chunk[1];
+
+        for band in bands.iter_mut() {
+            (left, right) = band.process(left, right);
+        }
+
+        chunk[0] = left;
+        chunk[1] = right;
+    }
+}
+
 impl Default for GraphicEq {
     fn default() -> Self {
         Self::new_10_band()
@@ -506,30 +595,21 @@ impl AudioEffect for GraphicEq {
             return;
         }
 
-        // Update sample rate if changed
+        // Rate changed without set_sample_rate(): the biquads follow, but a
+        // linear-phase FIR for the new rate waits for set_sample_rate()
         if self.sample_rate != sample_rate {
-            self.sample_rate = sample_rate;
-            // Bug fix: Reset filter state when sample rate changes to prevent transients
-            for band in &mut self.bands {
-                band.reset();
-            }
-            self.needs_update = true;
+            self.change_sample_rate(sample_rate);
         }
 
         self.update_coefficients();
 
-        // Process interleaved stereo buffer
-        for chunk in buffer.chunks_exact_mut(2) {
-            let mut left = chunk[0];
-            let mut right = chunk[1];
-
-            // Process through all bands in series
-            for band in &mut self.bands {
-                (left, right) = band.process(left, right);
+        let bands = &mut self.bands;
+        match self.phase_mode {
+            EqPhaseMode::MinimumPhase => process_bands(bands, buffer),
+            EqPhaseMode::LinearPhase => {
+                self.linear_phase
+                    .process(buffer, sample_rate, |buffer| process_bands(bands, buffer));
             }
-
-            chunk[0] = left;
-            chunk[1] = right;
         }
     }
 
@@ -541,6 +621,7 @@ impl AudioEffect for GraphicEq {
         for band in &mut self.bands {
             band.reset();
         }
+        self.linear_phase.reset();
     }
 
     fn set_enabled(&mut self, enabled: bool) {
@@ -558,6 +639,21 @@ impl AudioEffect for GraphicEq {
         }
     }
 
+    fn set_sample_rate(&mut self, sample_rate: u32) {
+        if self.sample_rate != sample_rate {
+            self.change_sample_rate(sample_rate);
+        }
+        if self.phase_mode == EqPhaseMode::LinearPhase
+            && self.linear_phase.requested_rate() != sample_rate
+        {
+            self.request_fir_design();
+        }
+    }
+
+    fn latency_samples(&self) -> usize {
+        self.linear_phase.latency_samples()
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
@@ -739,4 +835,31 @@ mod tests {
         assert_eq!(GraphicEqPreset::BassBoost.name(), "Bass Boost");
         assert_eq!(GraphicEqPreset::VShape.name(), "V-Shape");
     }
+
+    #[test]
+    fn test_linear_phase_mode() {
+        let mut eq = GraphicEq::new_10_band();
+        eq.set_sample_rate(48000);
+        eq.set_phase_mode(EqPhaseMode::LinearPhase);
+        eq.set_band_gain(5, -6.0); // 1 kHz
+
+        let mut buffer = vec![0.0f32; 1024];
+        let start = std::time::Instant::now();
+        while eq.latency_samples() == 0 {
+            assert!(start.elapsed().as_secs() < 10, "FIR never became active");
+            eq.process(&mut buffer, 48000);
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+
+        // The cut is designed from the latest gains
+        let latency = eq.latency_samples();
+        let mut sine = crate::effects::tests::generate_sine(1000.0, 48000, 1.0);
+        for chunk in sine.chunks_mut(1024) {
+            eq.process(chunk, 48000);
+        }
+        let peak = sine[(latency + 4800) * 2..]
+            .iter()
+            .fold(0.0f32, |max, s| max.max(s.abs()));
+        assert!((peak - 0.5).abs() < 0.03, "peak {}", peak);
+    }
 }
@@ -4,6 +4,7 @@
 /// audio peaks from exceeding a threshold. This implementation uses a lookahead buffer
 /// for zero-latency brick-wall limiting.
 use super::AudioEffect;
+use crate::channels::ChannelLayout;
 
 /// Limiter settings
 #[derive(Debug, Clone, Copy)]
@@ -160,25 +161,25 @@ impl Default for Limiter {
     }
 }
 
-impl AudioEffect for Limiter {
-    fn process(&mut self, buffer: &mut [f32], sample_rate: u32) {
-        if !self.enabled {
+impl Limiter {
+    /// Limit interleaved audio with any channel count
+    ///
+    /// Detection is linked across all channels, so the same gain applies to
+    /// every channel of a frame and the image is preserved.
+    fn process_interleaved(&mut self, buffer: &mut [f32], sample_rate: u32, channels: usize) {
+        if !self.enabled || channels == 0 {
             return;
         }
 
         // Update release coefficient if sample rate changed
         self.release_coeff = Self::calculate_release_coeff(self.settings.release_ms, sample_rate);
 
-        // Process stereo interleaved samples
-        for chunk in buffer.chunks_exact_mut(2) {
+        for frame in buffer.chunks_exact_mut(channels) {
             // Smooth threshold to prevent clicks during parameter changes
             self.smooth_threshold();
 
-            let left = chunk[0];
-            let right = chunk[1];
-
             // Calculate peak level
-            let peak = left.abs().max(right.abs());
+            let peak = frame.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
 
             // Update envelope (with fast attack, slow release)
             if peak > self.envelope {
@@ -197,10 +198,26 @@ impl AudioEffect for Limiter {
             };
 
             // Apply limiting
-            chunk[0] = left * gain;
-            chunk[1] = right * gain;
+            for sample in frame.iter_mut() {
+                *sample *= gain;
+            }
         }
     }
+}
+
+impl AudioEffect for Limiter {
+    fn process(&mut self, buffer: &mut [f32], sample_rate: u32) {
+        // Process stereo interleaved samples
+        self.process_interleaved(buffer, sample_rate, 2);
+    }
+
+    fn supports_layout(&self, _layout: ChannelLayout) -> bool {
+        true
+    }
+
+    fn process_layout(&mut self, buffer: &mut [f32], sample_rate: u32, layout: ChannelLayout) {
+        self.process_interleaved(buffer, sample_rate, layout.channel_count() as usize);
+    }
 
     fn reset(&mut self) {
         self.envelope = 0.0; // Reset to "no signal detected"
@@ -367,4 +384,27 @@ mod tests {
         assert!(settings.threshold_db < -0.5); // Further from 0dB
         assert!(settings.release_ms > 100.0); // Longer release
     }
+
+    #[test]
+    fn limits_surround_with_linked_gain() {
+        let mut limiter = Limiter::with_settings(LimiterSettings::brickwall());
+        assert!(limiter.supports_layout(ChannelLayout::Surround51));
+
+        // Loud center, quiet fronts
+        let mut buffer: Vec<f32> = (0..600)
+            .map(|i| if i % 6 == 2 { 1.5 } else { 0.3 })
+            .collect();
+        limiter.process_layout(&mut buffer, 44100, ChannelLayout::Surround51);
+
+        let threshold = 10.0f32.powf(-0.1 / 20.0);
+        for frame in buffer.chunks_exact(6) {
+            assert!(
+                frame[2] <= threshold + 1e-4,
+                "Center not limited: {}",
+                frame[2]
+            );
+            // Same gain on every channel
+            assert!((frame[0] / frame[2] - 0.2).abs() < 1e-4);
+        }
+    }
 }
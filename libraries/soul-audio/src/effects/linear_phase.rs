@@ -0,0 +1,599 @@
+//! Linear-phase rendering of a biquad EQ
+//!
+//! The parametric and graphic EQ are minimum-phase biquad cascades. In
+//! linear-phase mode they hand their biquad coefficients to a
+//! [`LinearPhaseFir`], which designs a symmetric FIR with the same magnitude
+//! response on a background thread and runs it through a
+//! [`ConvolutionEngine`]. A redesigned filter is warmed up alongside the
+//! current one and crossfaded in, so parameter changes don't click.
+//!
+//! The FIR delays the signal by half its length; [`LinearPhaseFir::latency_samples`]
+//! reports it so playback position can be compensated.
+
+use super::chain::AudioEffect;
+use super::convolution::ConvolutionEngine;
+use rustfft::{num_complex::Complex, FftPlanner};
+use std::f64::consts::PI;
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+
+/// Frames over which a redesigned filter is crossfaded in
+const CROSSFADE_FRAMES: usize = 1024;
+
+/// Shortest FIR used (frames)
+const MIN_FIR_LENGTH: usize = 4096;
+
+/// Longest FIR used (frames)
+const MAX_FIR_LENGTH: usize = 32768;
+
+/// Phase response of the parametric and graphic EQ
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EqPhaseMode {
+    /// Biquad cascade - no latency, phase shift around each band
+    #[default]
+    MinimumPhase,
+    /// FIR with the same magnitude response and constant group delay
+    LinearPhase,
+}
+
+/// Normalized biquad coefficients (b0, b1, b2, a1, a2)
+pub(super) type BiquadCoefficients = [f32; 5];
+
+/// FIR length for a sample rate
+///
+/// Keeps the frequency resolution around 6 Hz, enough for low shelves.
+fn fir_length(sample_rate: u32) -> usize {
+    (sample_rate as usize / 6)
+        .next_power_of_two()
+        .clamp(MIN_FIR_LENGTH, MAX_FIR_LENGTH)
+}
+
+/// Magnitude of a biquad at an angular frequency (radians per sample)
+fn biquad_magnitude(coefficients: &BiquadCoefficients, omega: f64) -> f64 {
+    let [b0, b1, b2, a1, a2] = coefficients.map(f64::from);
+    let (sin1, cos1) = omega.sin_cos();
+    let (sin2, cos2) = (2.0 * omega).sin_cos();
+
+    let num_re = b0 + b1 * cos1 + b2 * cos2;
+    let num_im = -(b1 * sin1 + b2 * sin2);
+    let den_re = 1.0 + a1 * cos1 + a2 * cos2;
+    let den_im = -(a1 * sin1 + a2 * sin2);
+
+    ((num_re * num_re + num_im * num_im) / (den_re * den_re + den_im * den_im)).sqrt()
+}
+
+/// Design a linear-phase FIR matching the magnitude of a biquad cascade
+///
+/// Samples the cascade's magnitude on an FFT grid with zero phase, takes the
+/// inverse FFT, centres the result and applies a Hann window. The filter is
+/// symmetric around `fir_length / 2`, which is its delay.
+pub(super) fn design_fir(sections: &[BiquadCoefficients], fir_length: usize) -> Vec<f32> {
+    let mut spectrum = vec![Complex::new(0.0f32, 0.0); fir_length];
+    for k in 0..=fir_length / 2 {
+        let omega = 2.0 * PI * k as f64 / fir_length as f64;
+        let magnitude = sections
+            .iter()
+            .map(|section| biquad_magnitude(section, omega))
+            .product::<f64>() as f32;
+        spectrum[k].re = magnitude;
+        spectrum[(fir_length - k) % fir_length].re = magnitude;
+    }
+
+    FftPlanner::new()
+        .plan_fft_inverse(fir_length)
+        .process(&mut spectrum);
+
+    // The zero-phase response is centred on sample 0: rotate it to the middle
+    let center = fir_length / 2;
+    (0..fir_length)
+        .map(|i| {
+            let tap = spectrum[(i + center) % fir_length].re / fir_length as f32;
+            let window = 0.5 - 0.5 * (2.0 * PI * i as f64 / fir_length as f64).cos();
+            tap * window as f32
+        })
+        .collect()
+}
+
+/// Most biquad sections in a design (32 parametric bands of up to 4 sections)
+const MAX_SECTIONS: usize = 128;
+
+/// Messages the design thread can fall behind by before senders have to
+/// hold on to theirs
+const DESIGNER_QUEUE: usize = 8;
+
+/// Blocks (frames) the crossfade scratch buffer is sized for up front
+const PREALLOCATED_BLOCK: usize = 8192;
+
+/// Settings to design a filter for
+///
+/// Built on the control thread with a fixed-size section array, so the
+/// audio thread only ever moves the box along.
+struct DesignRequest {
+    sections: [BiquadCoefficients; MAX_SECTIONS],
+    section_count: usize,
+    sample_rate: u32,
+    generation: u64,
+}
+
+/// A designed filter, ready to run
+struct FirDesign {
+    engine: ConvolutionEngine,
+    sample_rate: u32,
+    generation: u64,
+    length: usize,
+}
+
+/// Work for the design thread
+enum DesignerMessage {
+    /// Design a filter for these settings
+    Design(Box<DesignRequest>),
+    /// Free a filter the audio thread is done with
+    Retire(Box<FirDesign>),
+}
+
+/// Channels to the design thread
+///
+/// Both are bounded, so their buffers are allocated up front and neither
+/// sending a message nor receiving a design allocates on the audio thread.
+struct Designer {
+    messages: SyncSender<DesignerMessage>,
+    designs: Receiver<Box<FirDesign>>,
+}
+
+/// Start the design thread
+///
+/// The thread exits when the [`Designer`] is dropped.
+fn spawn_designer() -> Option<Designer> {
+    let (message_tx, message_rx) = mpsc::sync_channel(DESIGNER_QUEUE);
+    let (design_tx, design_rx) = mpsc::sync_channel(DESIGNER_QUEUE);
+
+    let spawned = std::thread::Builder::new()
+        .name("eq-fir-design".to_string())
+        .spawn(move || {
+            while let Ok(message) = message_rx.recv() {
+                // Retired filters are simply dropped here, off the audio thread
+                let DesignerMessage::Design(mut request) = message else {
+                    continue;
+                };
+
+                // Only the latest settings matter while a control is being dragged
+                while let Ok(newer) = message_rx.try_recv() {
+                    if let DesignerMessage::Design(newer) = newer {
+                        request = newer;
+                    }
+                }
+
+                let length = fir_length(request.sample_rate);
+                let taps = design_fir(&request.sections[..request.section_count], length);
+                let mut engine = ConvolutionEngine::new();
+                if let Err(e) = engine.load_impulse_response(&taps, request.sample_rate, 1) {
+                    eprintln!("[LinearPhaseFir] Failed to load FIR: {}", e);
+                    continue;
+                }
+
+                let design = Box::new(FirDesign {
+                    engine,
+                    sample_rate: request.sample_rate,
+                    generation: request.generation,
+                    length,
+                });
+                if design_tx.send(design).is_err() {
+                    break;
+                }
+            }
+        });
+
+    match spawned {
+        Ok(_) => Some(Designer {
+            messages: message_tx,
+            designs: design_rx,
+        }),
+        Err(e) => {
+            eprintln!("[LinearPhaseFir] Failed to start design thread: {}", e);
+            None
+        }
+    }
+}
+
+/// Linear-phase FIR stage shared by the parametric and graphic EQ
+///
+/// Until the first design arrives (and after a sample rate change) the
+/// caller's minimum-phase path keeps running, so audio never drops out.
+/// Designs are requested from the control thread only; `process()` picks
+/// them up and hands replaced filters back to the design thread to free.
+pub(super) struct LinearPhaseFir {
+    /// Design thread channels (started on the first request)
+    designer: Option<Designer>,
+    /// Generation of the most recent request
+    requested_generation: u64,
+    /// Sample rate of the most recent request (0 before the first)
+    requested_rate: u32,
+    /// Latest request the design queue had no room for yet
+    pending: Option<Box<DesignRequest>>,
+    /// Filter currently producing output
+    active: Option<Box<FirDesign>>,
+    /// Redesigned filter being warmed up and crossfaded in
+    incoming: Option<Box<FirDesign>>,
+    /// Frames the incoming filter still needs before its output is valid
+    incoming_warmup: usize,
+    /// Crossfade progress of the incoming filter in frames
+    incoming_fade: usize,
+    /// Replaced filter the design queue had no room for yet
+    retired: Option<Box<FirDesign>>,
+    /// Output of the incoming filter
+    scratch: Vec<f32>,
+}
+
+impl LinearPhaseFir {
+    pub(super) fn new() -> Self {
+        Self {
+            designer: None,
+            requested_generation: 0,
+            requested_rate: 0,
+            pending: None,
+            active: None,
+            incoming: None,
+            incoming_warmup: 0,
+            incoming_fade: 0,
+            retired: None,
+            scratch: Vec::new(),
+        }
+    }
+
+    /// Ask for a filter matching a biquad cascade at a sample rate
+    ///
+    /// Call from the control thread: it starts the design thread on first
+    /// use. Returns immediately; the design runs in the background and is
+    /// picked up by `process()`. Sections past [`MAX_SECTIONS`] are ignored.
+    pub(super) fn request_design(
+        &mut self,
+        sections: impl IntoIterator<Item = BiquadCoefficients>,
+        sample_rate: u32,
+    ) {
+        if self.designer.is_none() {
+            self.designer = spawn_designer();
+        }
+        if self.designer.is_none() {
+            return;
+        }
+
+        // Room for the crossfade of typical blocks, so process() doesn't grow it
+        self.scratch.reserve(PREALLOCATED_BLOCK * 2);
+
+        self.requested_generation += 1;
+        self.requested_rate = sample_rate;
+        let mut request = Box::new(DesignRequest {
+            sections: [[0.0; 5]; MAX_SECTIONS],
+            section_count: 0,
+            sample_rate,
+            generation: self.requested_generation,
+        });
+        for (slot, section) in request.sections.iter_mut().zip(sections) {
+            *slot = section;
+            request.section_count += 1;
+        }
+
+        // Supersedes any request still waiting for room
+        self.pending = Some(request);
+        self.send_queued();
+    }
+
+    /// Sample rate of the most recent design request (0 before the first)
+    pub(super) fn requested_rate(&self) -> u32 {
+        self.requested_rate
+    }
+
+    /// Hand waiting requests and retired filters to the design thread
+    ///
+    /// Never blocks: whatever doesn't fit in the queue stays put and goes
+    /// out on the next call.
+    fn send_queued(&mut self) {
+        let Some(designer) = &self.designer else {
+            return;
+        };
+
+        let mut stopped = false;
+        if let Some(request) = self.pending.take() {
+            match designer.messages.try_send(DesignerMessage::Design(request)) {
+                Ok(()) => {}
+                Err(TrySendError::Full(DesignerMessage::Design(request))) => {
+                    self.pending = Some(request);
+                }
+                Err(_) => stopped = true,
+            }
+        }
+        if let Some(design) = self.retired.take() {
+            match designer.messages.try_send(DesignerMessage::Retire(design)) {
+                Ok(()) => {}
+                Err(TrySendError::Full(DesignerMessage::Retire(design))) => {
+                    self.retired = Some(design);
+                }
+                Err(_) => stopped = true,
+            }
+        }
+
+        if stopped {
+            eprintln!("[LinearPhaseFir] Design thread stopped");
+            self.designer = None;
+        }
+    }
+
+    /// Pass a replaced filter to the design thread to be freed there
+    fn retire(&mut self, design: Box<FirDesign>) {
+        self.send_queued();
+        if self.retired.is_none() {
+            self.retired = Some(design);
+            self.send_queued();
+        }
+        // Otherwise the queue has been full for two filters in a row and
+        // this one is freed here
+    }
+
+    /// Drop all filters (leaving linear-phase mode)
+    pub(super) fn clear(&mut self) {
+        self.active = None;
+        self.incoming = None;
+        self.retired = None;
+        self.pending = None;
+        self.requested_rate = 0;
+    }
+
+    /// Delay of the active filter in frames (0 while none is active)
+    pub(super) fn latency_samples(&self) -> usize {
+        self.active.as_ref().map_or(0, |design| design.length / 2)
+    }
+
+    /// Clear filter history (e.g. on seek)
+    pub(super) fn reset(&mut self) {
+        if let Some(design) = &mut self.active {
+            design.engine.reset();
+        }
+        if let Some(design) = &mut self.incoming {
+            design.engine.reset();
+            self.incoming_warmup = design.length;
+            self.incoming_fade = 0;
+        }
+    }
+
+    /// Process an interleaved stereo buffer
+    ///
+    /// `minimum_phase` renders the buffer with the biquads whenever no FIR
+    /// for this sample rate is ready yet.
+    pub(super) fn process(
+        &mut self,
+        buffer: &mut [f32],
+        sample_rate: u32,
+        minimum_phase: impl FnOnce(&mut [f32]),
+    ) {
+        self.send_queued();
+        self.receive_designs(sample_rate);
+
+        // A filter for another rate is useless; fall back until the redesign lands
+        if self
+            .active
+            .as_ref()
+            .is_some_and(|design| design.sample_rate != sample_rate)
+        {
+            if let Some(design) = self.active.take() {
+                self.retire(design);
+            }
+        }
+        if self
+            .incoming
+            .as_ref()
+            .is_some_and(|design| design.sample_rate != sample_rate)
+        {
+            if let Some(design) = self.incoming.take() {
+                self.retire(design);
+            }
+        }
+
+        let len = buffer.len();
+        if let Some(incoming) = &mut self.incoming {
+            if self.scratch.len() < len {
+                self.scratch.resize(len, 0.0);
+            }
+            self.scratch[..len].copy_from_slice(buffer);
+            incoming
+                .engine
+                .process(&mut self.scratch[..len], sample_rate);
+        }
+
+        match &mut self.active {
+            Some(active) => active.engine.process(buffer, sample_rate),
+            None => minimum_phase(buffer),
+        }
+
+        if self.incoming.is_none() {
+            return;
+        }
+
+        for (out, next) in buffer
+            .chunks_exact_mut(2)
+            .zip(self.scratch[..len].chunks_exact(2))
+        {
+            if self.incoming_warmup > 0 {
+                self.incoming_warmup -= 1;
+            } else if self.incoming_fade < CROSSFADE_FRAMES {
+                let gain = self.incoming_fade as f32 / CROSSFADE_FRAMES as f32;
+                out[0] += (next[0] - out[0]) * gain;
+                out[1] += (next[1] - out[1]) * gain;
+                self.incoming_fade += 1;
+            } else {
+                out.copy_from_slice(next);
+            }
+        }
+
+        if self.incoming_fade >= CROSSFADE_FRAMES {
+            if let Some(previous) = std::mem::replace(&mut self.active, self.incoming.take()) {
+                self.retire(previous);
+            }
+        }
+    }
+
+    /// Take the newest finished design for the current settings
+    fn receive_designs(&mut self, sample_rate: u32) {
+        while let Some(design) = self
+            .designer
+            .as_ref()
+            .and_then(|designer| designer.designs.try_recv().ok())
+        {
+            if design.generation != self.requested_generation || design.sample_rate != sample_rate {
+                // Superseded while it was being designed
+                self.retire(design);
+                continue;
+            }
+
+            self.incoming_warmup = design.length;
+            self.incoming_fade = 0;
+            if let Some(previous) = self.incoming.replace(design) {
+                self.retire(previous);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    /// RBJ peaking filter coefficients
+    fn peaking(sample_rate: f64, frequency: f64, q: f64, gain_db: f64) -> BiquadCoefficients {
+        let a = 10f64.powf(gain_db / 40.0);
+        let omega = 2.0 * PI * frequency / sample_rate;
+        let alpha = omega.sin() / (2.0 * q);
+        let a0 = 1.0 + alpha / a;
+        [
+            ((1.0 + alpha * a) / a0) as f32,
+            (-2.0 * omega.cos() / a0) as f32,
+            ((1.0 - alpha * a) / a0) as f32,
+            (-2.0 * omega.cos() / a0) as f32,
+            ((1.0 - alpha / a) / a0) as f32,
+        ]
+    }
+
+    /// Magnitude of an FIR at a frequency
+    fn fir_magnitude(taps: &[f32], sample_rate: f64, frequency: f64) -> f64 {
+        let omega = 2.0 * PI * frequency / sample_rate;
+        let (re, im) = taps
+            .iter()
+            .enumerate()
+            .fold((0.0, 0.0), |(re, im), (n, &tap)| {
+                let phase = omega * n as f64;
+                (re + tap as f64 * phase.cos(), im - tap as f64 * phase.sin())
+            });
+        (re * re + im * im).sqrt()
+    }
+
+    #[test]
+    fn test_fir_matches_biquad_magnitude() {
+        let sections = [
+            peaking(48000.0, 1000.0, 1.0, 6.0),
+            peaking(48000.0, 8000.0, 2.0, -4.0),
+        ];
+        let taps = design_fir(&sections, fir_length(48000));
+
+        for frequency in [100.0, 1000.0, 3000.0, 8000.0, 15000.0] {
+            let omega = 2.0 * PI * frequency / 48000.0;
+            let expected: f64 = sections
+                .iter()
+                .map(|s| biquad_magnitude(s, omega))
+                .product();
+            let actual = fir_magnitude(&taps, 48000.0, frequency);
+            let error_db = 20.0 * (actual / expected).log10();
+            assert!(
+                error_db.abs() < 0.1,
+                "{} Hz: {:.3} dB off",
+                frequency,
+                error_db
+            );
+        }
+    }
+
+    #[test]
+    fn test_fir_is_symmetric() {
+        let length = fir_length(44100);
+        let taps = design_fir(&[peaking(44100.0, 200.0, 0.7, 8.0)], length);
+        let center = length / 2;
+
+        for i in 1..center {
+            assert!((taps[center - i] - taps[center + i]).abs() < 1e-6);
+        }
+        let peak = taps
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.abs().total_cmp(&b.1.abs()))
+            .unwrap();
+        assert_eq!(peak.0, center);
+    }
+
+    #[test]
+    fn test_fir_length_scales_with_rate() {
+        assert_eq!(fir_length(44100), 8192);
+        assert_eq!(fir_length(96000), 16384);
+        assert_eq!(fir_length(8000), MIN_FIR_LENGTH);
+        assert_eq!(fir_length(768000), MAX_FIR_LENGTH);
+    }
+
+    #[test]
+    fn test_swaps_in_designed_filter() {
+        let mut fir = LinearPhaseFir::new();
+        fir.request_design(Vec::new(), 48000);
+
+        // Minimum-phase fallback runs until the design has been crossfaded in
+        let mut block = vec![0.0f32; 1024];
+        let mut fallback_blocks = 0;
+        let start = Instant::now();
+        while fir.latency_samples() == 0 {
+            assert!(
+                start.elapsed() < Duration::from_secs(10),
+                "design never arrived"
+            );
+            fir.process(&mut block, 48000, |_| fallback_blocks += 1);
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert!(fallback_blocks > 0);
+        assert_eq!(fir.latency_samples(), fir_length(48000) / 2);
+
+        // A flat design is a pure delay
+        fir.reset();
+        let latency = fir.latency_samples();
+        let mut signal = vec![0.0f32; (latency + 1024) * 2];
+        signal[0] = 1.0;
+        signal[1] = 1.0;
+        for chunk in signal.chunks_mut(1024) {
+            fir.process(chunk, 48000, |_| {
+                panic!("fallback used with an active filter")
+            });
+        }
+        let peak = signal
+            .chunks_exact(2)
+            .enumerate()
+            .max_by(|a, b| a.1[0].abs().total_cmp(&b.1[0].abs()))
+            .unwrap();
+        assert_eq!(peak.0, latency);
+        assert!((peak.1[0] - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_rate_change_falls_back() {
+        let mut fir = LinearPhaseFir::new();
+        fir.request_design(Vec::new(), 44100);
+
+        let mut block = vec![0.0f32; 512];
+        let start = Instant::now();
+        while fir.latency_samples() == 0 {
+            assert!(
+                start.elapsed() < Duration::from_secs(10),
+                "design never arrived"
+            );
+            fir.process(&mut block, 44100, |_| {});
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        let mut fallback_used = false;
+        fir.process(&mut block, 96000, |_| fallback_used = true);
+        assert!(fallback_used);
+        assert_eq!(fir.latency_samples(), 0);
+    }
+}
@@ -4,30 +4,48 @@
 ///! All effects operate on f32 samples in [-1.0, 1.0] range.
 ///!
 ///! Available effects:
-///! - **ParametricEq**: 3-band parametric equalizer
+///! - **ParametricEq**: 1-32 band parametric equalizer (shelf, peaking, pass, notch,
+///!   band-pass, all-pass and tilt bands)
+///! - **EqPreset**: Equalizer APO / AutoEQ / REW filter-file import and export
 ///! - **GraphicEq**: 10-band or 31-band graphic equalizer
+///! - **EqPhaseMode**: Minimum-phase or linear-phase (FIR) mode for both EQs
 ///! - **Compressor**: Dynamic range compressor
+///! - **MultibandCompressor**: 3-5 band compressor with Linkwitz-Riley crossovers
+///! - **DynamicEq**: Peaking bands whose gain follows a sidechain threshold
 ///! - **Limiter**: Brick-wall limiter
 ///! - **Crossfeed**: Bauer stereophonic-to-binaural DSP for headphones
 ///! - **StereoEnhancer**: Width control, mid/side processing, balance
+mod biquad;
 mod chain;
 mod compressor;
 mod convolution;
 mod crossfeed;
+mod dynamic_eq;
 mod eq;
+mod eq_preset;
 mod graphic_eq;
 mod limiter;
+mod linear_phase;
+mod multiband;
 mod stereo;
 
 pub use chain::{AudioEffect, EffectChain};
 pub use compressor::{Compressor, CompressorSettings};
-pub use convolution::{ConvolutionEngine, ConvolutionError};
+pub use convolution::{ConvolutionEngine, ConvolutionError, ImpulseResponse};
 pub use crossfeed::{Crossfeed, CrossfeedPreset, CrossfeedSettings};
-pub use eq::{EqBand, ParametricEq, MAX_EQ_BANDS};
+pub use dynamic_eq::{DynamicEq, DynamicEqBand, MAX_DYNAMIC_EQ_BANDS};
+pub use eq::{
+    EqBand, FilterAlignment, FilterSlope, FilterType, ParametricEq, MAX_EQ_BANDS,
+};
+pub use eq_preset::{EqPreset, EqPresetError, EqPresetFormat};
 pub use graphic_eq::{
     GraphicEq, GraphicEqBands, GraphicEqPreset, ISO_10_BAND_FREQUENCIES, ISO_31_BAND_FREQUENCIES,
 };
 pub use limiter::{Limiter, LimiterSettings};
+pub use linear_phase::EqPhaseMode;
+pub use multiband::{
+    MultibandCompressor, MultibandCompressorSettings, MAX_MULTIBAND_BANDS, MIN_MULTIBAND_BANDS,
+};
 pub use stereo::{mono_compatibility, StereoEnhancer, StereoSettings};
 
 #[cfg(test)]
@@ -0,0 +1,487 @@
+/// Multiband Compressor
+///
+/// Splits the signal into 3-5 bands with Linkwitz-Riley crossovers and
+/// compresses each band on its own, so a loud bass line no longer ducks the
+/// vocals. Lower bands are phase-compensated with all-pass filters, so with
+/// no compression the bands sum back to a flat magnitude response.
+use super::biquad::{Biquad, BUTTERWORTH_Q};
+use super::chain::AudioEffect;
+use super::compressor::{Compressor, CompressorSettings};
+use crate::channels::{ChannelLayout, MAX_CHANNELS};
+
+/// Fewest bands supported
+pub const MIN_MULTIBAND_BANDS: usize = 3;
+
+/// Most bands supported
+pub const MAX_MULTIBAND_BANDS: usize = 5;
+
+/// Most crossovers (one fewer than bands)
+const MAX_CROSSOVERS: usize = MAX_MULTIBAND_BANDS - 1;
+
+/// Minimum spacing between neighbouring crossovers (about 2/3 octave)
+///
+/// Closer crossovers make the LR4 slopes overlap so much that the middle
+/// band has almost nothing left to compress.
+const MIN_CROSSOVER_RATIO: f32 = 1.5;
+
+/// Multiband compressor settings
+#[derive(Debug, Clone)]
+pub struct MultibandCompressorSettings {
+    /// Crossover frequencies in Hz, ascending (one fewer than bands)
+    pub crossovers_hz: Vec<f32>,
+
+    /// Compressor settings per band, from lowest to highest
+    pub bands: Vec<CompressorSettings>,
+}
+
+impl MultibandCompressorSettings {
+    /// Create default settings (3 bands)
+    pub fn new() -> Self {
+        Self::three_band()
+    }
+
+    /// Three bands: lows, mids, highs (crossovers at 200 Hz and 2 kHz)
+    pub fn three_band() -> Self {
+        Self {
+            crossovers_hz: default_crossovers(3),
+            bands: vec![
+                CompressorSettings::moderate(),
+                CompressorSettings::gentle(),
+                CompressorSettings::gentle(),
+            ],
+        }
+    }
+
+    /// Four bands (crossovers at 120 Hz, 1 kHz and 6 kHz)
+    pub fn four_band() -> Self {
+        Self {
+            crossovers_hz: default_crossovers(4),
+            bands: vec![
+                CompressorSettings::moderate(),
+                CompressorSettings::moderate(),
+                CompressorSettings::gentle(),
+                CompressorSettings::gentle(),
+            ],
+        }
+    }
+
+    /// Five bands (crossovers at 100 Hz, 400 Hz, 2 kHz and 8 kHz)
+    pub fn five_band() -> Self {
+        Self {
+            crossovers_hz: default_crossovers(5),
+            bands: vec![
+                CompressorSettings::moderate(),
+                CompressorSettings::moderate(),
+                CompressorSettings::gentle(),
+                CompressorSettings::gentle(),
+                CompressorSettings::gentle(),
+            ],
+        }
+    }
+
+    /// Number of bands
+    pub fn band_count(&self) -> usize {
+        self.bands.len()
+    }
+
+    /// Validate and clamp settings to safe ranges
+    ///
+    /// Keeps 3-5 bands, replaces a crossover list that doesn't match the
+    /// band count with the defaults, and sorts and spaces the crossovers.
+    pub fn validate(&mut self) {
+        self.bands.truncate(MAX_MULTIBAND_BANDS);
+        while self.bands.len() < MIN_MULTIBAND_BANDS {
+            self.bands.push(CompressorSettings::new());
+        }
+        for band in &mut self.bands {
+            band.validate();
+        }
+
+        if self.crossovers_hz.len() != self.bands.len() - 1
+            || self.crossovers_hz.iter().any(|f| !f.is_finite())
+        {
+            self.crossovers_hz = default_crossovers(self.bands.len());
+        }
+
+        self.crossovers_hz.sort_by(|a, b| a.total_cmp(b));
+        for i in 0..self.crossovers_hz.len() {
+            let min = if i == 0 {
+                20.0
+            } else {
+                self.crossovers_hz[i - 1] * MIN_CROSSOVER_RATIO
+            };
+            self.crossovers_hz[i] = self.crossovers_hz[i].clamp(min, 20000.0_f32.max(min));
+        }
+    }
+}
+
+impl Default for MultibandCompressorSettings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Default crossover frequencies for a band count
+fn default_crossovers(band_count: usize) -> Vec<f32> {
+    match band_count {
+        4 => vec![120.0, 1000.0, 6000.0],
+        5 => vec![100.0, 400.0, 2000.0, 8000.0],
+        _ => vec![200.0, 2000.0],
+    }
+}
+
+/// Multiband Compressor
+///
+/// Each crossover is a Linkwitz-Riley 4th-order split (two cascaded
+/// Butterworth sections per side). Bands are split off from the bottom up;
+/// the low output of each split passes through all-pass filters matching
+/// the crossovers above it, so every band ends up with the same phase
+/// response and the sum is all-pass.
+///
+/// Each band runs a regular `Compressor` with linked detection across
+/// channels. All filters and band buffers are allocated up front; band
+/// buffers only grow when a larger block than before arrives.
+pub struct MultibandCompressor {
+    settings: MultibandCompressorSettings,
+    enabled: bool,
+    band_count: usize,
+
+    /// Per-band compressors
+    compressors: [Compressor; MAX_MULTIBAND_BANDS],
+
+    /// LR4 low-pass per crossover (two Butterworth sections)
+    lowpass: [[Biquad; 2]; MAX_CROSSOVERS],
+    /// LR4 high-pass per crossover (two Butterworth sections)
+    highpass: [[Biquad; 2]; MAX_CROSSOVERS],
+    /// Phase compensation: `allpass[band][crossover]` for crossovers above the band
+    allpass: [[Biquad; MAX_CROSSOVERS]; MAX_CROSSOVERS],
+
+    /// Interleaved scratch buffer per band
+    band_buffers: [Vec<f32>; MAX_MULTIBAND_BANDS],
+
+    sample_rate: u32,
+    needs_update: bool,
+}
+
+impl MultibandCompressor {
+    /// Create a new multiband compressor with default settings
+    pub fn new() -> Self {
+        Self::with_settings(MultibandCompressorSettings::new())
+    }
+
+    /// Create multiband compressor with specific settings
+    pub fn with_settings(settings: MultibandCompressorSettings) -> Self {
+        let mut comp = Self {
+            settings: MultibandCompressorSettings::new(),
+            enabled: true,
+            band_count: 0,
+            compressors: std::array::from_fn(|_| Compressor::new()),
+            lowpass: [[Biquad::new(); 2]; MAX_CROSSOVERS],
+            highpass: [[Biquad::new(); 2]; MAX_CROSSOVERS],
+            allpass: [[Biquad::new(); MAX_CROSSOVERS]; MAX_CROSSOVERS],
+            band_buffers: std::array::from_fn(|_| Vec::new()),
+            sample_rate: 44100,
+            needs_update: true,
+        };
+        comp.set_settings(settings);
+        comp
+    }
+
+    /// Update settings
+    ///
+    /// Filter and envelope state is kept when the band count stays the same,
+    /// so crossovers and thresholds can be dragged during playback.
+    pub fn set_settings(&mut self, mut settings: MultibandCompressorSettings) {
+        settings.validate();
+
+        let band_count = settings.band_count();
+        if band_count != self.band_count {
+            self.band_count = band_count;
+            self.reset_state();
+        }
+
+        for (compressor, band) in self.compressors.iter_mut().zip(&settings.bands) {
+            compressor.set_settings(*band);
+        }
+
+        self.settings = settings;
+        self.needs_update = true;
+    }
+
+    /// Get current settings
+    pub fn settings(&self) -> &MultibandCompressorSettings {
+        &self.settings
+    }
+
+    /// Number of active bands
+    pub fn band_count(&self) -> usize {
+        self.band_count
+    }
+
+    /// Recompute crossover filters for the current settings and sample rate
+    fn update_filters(&mut self) {
+        if !self.needs_update {
+            return;
+        }
+
+        let sr = self.sample_rate as f32;
+        for (i, &frequency) in self.settings.crossovers_hz.iter().enumerate() {
+            for section in &mut self.lowpass[i] {
+                section.set_lowpass(sr, frequency, BUTTERWORTH_Q);
+            }
+            for section in &mut self.highpass[i] {
+                section.set_highpass(sr, frequency, BUTTERWORTH_Q);
+            }
+            for band in self.allpass.iter_mut().take(i) {
+                band[i].set_allpass(sr, frequency, BUTTERWORTH_Q);
+            }
+        }
+
+        self.needs_update = false;
+    }
+
+    /// Clear filter and compressor state
+    fn reset_state(&mut self) {
+        for section in self.lowpass.iter_mut().chain(self.highpass.iter_mut()) {
+            for filter in section {
+                filter.reset();
+            }
+        }
+        for band in &mut self.allpass {
+            for filter in band {
+                filter.reset();
+            }
+        }
+        for compressor in &mut self.compressors {
+            AudioEffect::reset(compressor);
+        }
+    }
+
+    /// Compress interleaved audio with any channel count
+    fn process_interleaved(&mut self, buffer: &mut [f32], sample_rate: u32, channels: usize) {
+        if !self.enabled || channels == 0 || channels > MAX_CHANNELS {
+            return;
+        }
+
+        if self.sample_rate != sample_rate {
+            self.sample_rate = sample_rate;
+            self.needs_update = true;
+        }
+        self.update_filters();
+
+        let len = buffer.len() - buffer.len() % channels;
+        let band_count = self.band_count;
+        let crossovers = band_count - 1;
+
+        // Grow scratch buffers on the first (or a larger) block only
+        for band in self.band_buffers.iter_mut().take(band_count) {
+            if band.len() < len {
+                band.resize(len, 0.0);
+            }
+        }
+
+        // Split into bands
+        for (i, &sample) in buffer[..len].iter().enumerate() {
+            let channel = i % channels;
+            let mut rest = sample;
+
+            for c in 0..crossovers {
+                let [lp1, lp2] = &mut self.lowpass[c];
+                let mut low = lp2.process(channel, lp1.process(channel, rest));
+                let [hp1, hp2] = &mut self.highpass[c];
+                rest = hp2.process(channel, hp1.process(channel, rest));
+
+                for allpass in &mut self.allpass[c][c + 1..crossovers] {
+                    low = allpass.process(channel, low);
+                }
+                self.band_buffers[c][i] = low;
+            }
+            self.band_buffers[crossovers][i] = rest;
+        }
+
+        // Compress each band
+        for (compressor, band) in self
+            .compressors
+            .iter_mut()
+            .zip(self.band_buffers.iter_mut())
+            .take(band_count)
+        {
+            compressor.process_interleaved(&mut band[..len], sample_rate, channels);
+        }
+
+        // Sum the bands back together
+        for (i, sample) in buffer[..len].iter_mut().enumerate() {
+            *sample = self.band_buffers[..band_count]
+                .iter()
+                .map(|band| band[i])
+                .sum();
+        }
+    }
+}
+
+impl Default for MultibandCompressor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioEffect for MultibandCompressor {
+    fn process(&mut self, buffer: &mut [f32], sample_rate: u32) {
+        self.process_interleaved(buffer, sample_rate, 2);
+    }
+
+    fn supports_layout(&self, layout: ChannelLayout) -> bool {
+        layout.channel_count() as usize <= MAX_CHANNELS
+    }
+
+    fn process_layout(&mut self, buffer: &mut [f32], sample_rate: u32, layout: ChannelLayout) {
+        self.process_interleaved(buffer, sample_rate, layout.channel_count() as usize);
+    }
+
+    fn reset(&mut self) {
+        self.reset_state();
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn name(&self) -> &str {
+        "Multiband Compressor"
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 48000;
+
+    fn stereo_sine(frequency: f32, amplitude: f32, frames: usize) -> Vec<f32> {
+        (0..frames)
+            .flat_map(|i| {
+                let s = amplitude
+                    * (2.0 * std::f32::consts::PI * frequency * i as f32 / SAMPLE_RATE as f32)
+                        .sin();
+                [s, s]
+            })
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    /// Settings with every band at 1:1 (no compression)
+    fn transparent(mut settings: MultibandCompressorSettings) -> MultibandCompressorSettings {
+        for band in &mut settings.bands {
+            band.ratio = 1.0;
+            band.makeup_gain_db = 0.0;
+        }
+        settings
+    }
+
+    #[test]
+    fn settings_validation() {
+        let mut settings = MultibandCompressorSettings {
+            crossovers_hz: vec![5000.0, 100.0, 5100.0, 9000.0, 12000.0],
+            bands: vec![CompressorSettings::new(); 7],
+        };
+        settings.validate();
+        assert_eq!(settings.band_count(), MAX_MULTIBAND_BANDS);
+        assert_eq!(settings.crossovers_hz.len(), MAX_CROSSOVERS);
+        assert_eq!(settings.crossovers_hz, default_crossovers(5));
+
+        let mut settings = MultibandCompressorSettings {
+            crossovers_hz: vec![2000.0, 1000.0],
+            bands: vec![CompressorSettings::new(); 3],
+        };
+        settings.validate();
+        assert_eq!(settings.crossovers_hz, vec![1000.0, 2000.0]);
+
+        let mut settings = MultibandCompressorSettings {
+            crossovers_hz: vec![1000.0, 1100.0],
+            bands: vec![CompressorSettings::new()],
+        };
+        settings.validate();
+        assert_eq!(settings.band_count(), MIN_MULTIBAND_BANDS);
+        assert!(settings.crossovers_hz[1] >= settings.crossovers_hz[0] * MIN_CROSSOVER_RATIO);
+    }
+
+    #[test]
+    fn bands_sum_flat_without_compression() {
+        for settings in [
+            MultibandCompressorSettings::three_band(),
+            MultibandCompressorSettings::four_band(),
+            MultibandCompressorSettings::five_band(),
+        ] {
+            let mut comp = MultibandCompressor::with_settings(transparent(settings));
+
+            for frequency in [50.0, 200.0, 1000.0, 2000.0, 7000.0, 15000.0] {
+                comp.reset();
+                let mut buffer = stereo_sine(frequency, 0.5, SAMPLE_RATE as usize);
+                let input_rms = rms(&buffer[buffer.len() / 2..]);
+                comp.process(&mut buffer, SAMPLE_RATE);
+                let output_rms = rms(&buffer[buffer.len() / 2..]);
+
+                let error_db = 20.0 * (output_rms / input_rms).log10();
+                assert!(
+                    error_db.abs() < 0.1,
+                    "{} bands, {} Hz: {:.3} dB",
+                    comp.band_count(),
+                    frequency,
+                    error_db
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn compresses_only_the_loud_band() {
+        let mut settings = transparent(MultibandCompressorSettings::three_band());
+        settings.bands[0] = CompressorSettings {
+            threshold_db: -30.0,
+            ratio: 10.0,
+            attack_ms: 1.0,
+            release_ms: 50.0,
+            knee_db: 0.0,
+            makeup_gain_db: 0.0,
+        };
+        let mut comp = MultibandCompressor::with_settings(settings);
+
+        // Loud bass is pulled down
+        let mut bass = stereo_sine(60.0, 0.5, SAMPLE_RATE as usize);
+        let bass_rms = rms(&bass);
+        comp.process(&mut bass, SAMPLE_RATE);
+        assert!(rms(&bass[bass.len() / 2..]) < bass_rms * 0.5);
+
+        // Treble passes untouched
+        comp.reset();
+        let mut treble = stereo_sine(5000.0, 0.5, SAMPLE_RATE as usize);
+        let treble_rms = rms(&treble[treble.len() / 2..]);
+        comp.process(&mut treble, SAMPLE_RATE);
+        let ratio = rms(&treble[treble.len() / 2..]) / treble_rms;
+        assert!((ratio - 1.0).abs() < 0.02, "Treble gain {:.3}", ratio);
+    }
+
+    #[test]
+    fn process_surround_buffer() {
+        let mut comp = MultibandCompressor::new();
+        let mut buffer = vec![0.9; 6 * 512];
+        comp.process_layout(&mut buffer, SAMPLE_RATE, ChannelLayout::Surround51);
+        assert!(buffer.iter().all(|s| s.is_finite()));
+        assert!(comp.supports_layout(ChannelLayout::Surround71));
+    }
+}
@@ -32,6 +32,13 @@
 //! assert_eq!(itunes.unwrap().start_padding, 2112);  // 0x840 = 2112
 //! ```
 
+use std::ops::Range;
+use symphonia::core::codecs::{
+    CodecParameters, CODEC_TYPE_AAC, CODEC_TYPE_MP3, CODEC_TYPE_OPUS, CODEC_TYPE_VORBIS,
+};
+use symphonia::core::meta::Tag;
+use symphonia::core::probe::ProbeResult;
+
 /// Source of encoder delay information
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum DelaySource {
@@ -44,6 +51,8 @@ pub enum DelaySource {
     ITunSMPB,
     /// Vorbis comment (Opus/Vorbis)
     VorbisComment,
+    /// Opus pre-skip (from the OpusHead packet and Ogg granule positions)
+    OpusHeader,
     /// Manually specified
     Manual,
 }
@@ -227,6 +236,104 @@ impl EncoderDelay {
         let samples = self.actual_samples(total_decoded_samples);
         std::time::Duration::from_secs_f64(samples as f64 / sample_rate as f64)
     }
+
+    /// Read delay information from Symphonia codec parameters
+    ///
+    /// Symphonia's MP3 reader parses the LAME tag of the Xing/Info frame and
+    /// reports it with the 529-sample decoder delay already added to the
+    /// start (and removed from the end), so the values apply directly to
+    /// Symphonia's decoded output. Its Ogg reader reports the Opus pre-skip
+    /// as the delay and the samples past the final granule position as the
+    /// padding. Other formats return `None`.
+    pub fn from_codec_params(params: &CodecParameters) -> Option<Self> {
+        let source = match params.codec {
+            CODEC_TYPE_MP3 => DelaySource::LameHeader,
+            CODEC_TYPE_OPUS => DelaySource::OpusHeader,
+            _ => return None,
+        };
+
+        let start_padding = params.delay.unwrap_or(0);
+        let end_padding = params.padding.unwrap_or(0);
+        if start_padding == 0 && end_padding == 0 {
+            return None;
+        }
+
+        Some(Self {
+            start_padding,
+            end_padding,
+            valid_samples: None,
+            source,
+        })
+    }
+
+    /// Read delay information from metadata tags
+    ///
+    /// Recognizes `iTunSMPB` (also as an MP4 freeform atom such as
+    /// `com.apple.iTunes:iTunSMPB`) and the `ENCODER_DELAY` /
+    /// `ENCODER_PADDING` Vorbis comments.
+    pub fn from_tags(tags: &[Tag]) -> Option<Self> {
+        let find = |matches: fn(&str) -> bool| {
+            tags.iter()
+                .find(|tag| matches(&tag.key.to_ascii_lowercase()))
+                .map(|tag| tag.value.to_string())
+        };
+
+        if let Some(smpb) = find(|key| key.ends_with("itunsmpb")) {
+            if let Some(delay) = Self::from_itun_smpb(&smpb) {
+                return Some(delay);
+            }
+        }
+
+        let delay = find(|key| key == "encoder_delay");
+        let padding = find(|key| key == "encoder_padding");
+        Self::from_vorbis_comment(delay.as_deref(), padding.as_deref())
+    }
+
+    /// Detect the delay information of a probed file
+    ///
+    /// Prefers the LAME header or Opus pre-skip (via the codec parameters),
+    /// then falls back to tags found while probing and in the container.
+    /// Returns no padding if the file doesn't declare any.
+    pub fn from_probe(probed: &mut ProbeResult) -> Self {
+        if let Some(delay) = probed
+            .format
+            .default_track()
+            .and_then(|track| Self::from_codec_params(&track.codec_params))
+        {
+            return delay;
+        }
+
+        let mut tags = Vec::new();
+        if let Some(metadata) = probed.metadata.get() {
+            if let Some(revision) = metadata.current() {
+                tags.extend_from_slice(revision.tags());
+            }
+        }
+        if let Some(revision) = probed.format.metadata().current() {
+            tags.extend_from_slice(revision.tags());
+        }
+
+        Self::from_tags(&tags).unwrap_or_default()
+    }
+}
+
+/// Frames to decode before a seek target so the decoder has warmed up
+///
+/// Transform codecs need the previous block(s) to reconstruct a sample, so
+/// an exact seek starts this far before the target and discards the output
+/// up to it. Formats with independent frames (PCM, FLAC, ALAC) need none.
+pub fn seek_preroll(params: &CodecParameters) -> u64 {
+    match params.codec {
+        // Overlap of the previous granule plus the synthesis filterbank
+        CODEC_TYPE_MP3 => 2 * 1152,
+        // One frame of MDCT overlap, plus one for SBR/PS state
+        CODEC_TYPE_AAC => 2 * 1024,
+        // Half of the largest block size Vorbis allows (8192)
+        CODEC_TYPE_VORBIS => 4096,
+        // 80 ms, as recommended by RFC 7845 (Opus always decodes at 48 kHz)
+        CODEC_TYPE_OPUS => 3840,
+        _ => 0,
+    }
 }
 
 /// Encoder delay trimmer for applying delay compensation during playback
@@ -325,6 +432,36 @@ impl DelayTrimmer {
     pub fn valid_samples(&self) -> u64 {
         self.delay.actual_samples(self.total_samples)
     }
+
+    /// Get the delay information being compensated
+    pub fn delay(&self) -> &EncoderDelay {
+        &self.delay
+    }
+
+    /// Set the position in raw samples (e.g. to the timestamp of a packet)
+    pub fn set_raw_position(&mut self, sample: u64) {
+        self.samples_read = sample;
+    }
+
+    /// Trim a block of decoded samples starting at the current position
+    ///
+    /// Returns the range of samples in the block that are audio rather than
+    /// padding, and advances past the block.
+    pub fn trim(&mut self, samples: usize) -> Range<usize> {
+        let start_padding = self.delay.start_padding as u64;
+        let valid_end = start_padding.saturating_add(self.valid_samples());
+
+        let start = start_padding
+            .saturating_sub(self.samples_read)
+            .min(samples as u64);
+        let end = valid_end
+            .saturating_sub(self.samples_read)
+            .min(samples as u64)
+            .max(start);
+
+        self.advance(samples as u64);
+        start as usize..end as usize
+    }
 }
 
 #[cfg(test)]
@@ -466,6 +603,132 @@ mod tests {
         assert_eq!(trimmer.valid_samples(), 850);  // 1000 - 100 - 50
     }
 
+    #[test]
+    fn test_delay_trimmer_trim_blocks() {
+        let delay = EncoderDelay::from_lame(100, 50);
+        let mut trimmer = DelayTrimmer::new(delay, 1000);
+
+        // Blocks of 300 samples: [0, 300) [300, 600) [600, 900) [900, 1000)
+        assert_eq!(trimmer.trim(300), 100..300);
+        assert_eq!(trimmer.trim(300), 0..300);
+        assert_eq!(trimmer.trim(300), 0..300);
+        assert_eq!(trimmer.trim(100), 0..50);
+        assert!(trimmer.at_end_padding());
+
+        // Nothing left after the end padding
+        assert_eq!(trimmer.trim(100), 0..0);
+    }
+
+    #[test]
+    fn test_delay_trimmer_trim_after_raw_seek() {
+        let delay = EncoderDelay::from_lame(100, 50);
+        let mut trimmer = DelayTrimmer::new(delay, 1000);
+
+        // A packet starting inside the start padding
+        trimmer.set_raw_position(40);
+        assert_eq!(trimmer.position(), 0);
+        assert_eq!(trimmer.trim(100), 60..100);
+        assert_eq!(trimmer.position(), 40);
+
+        // A packet spanning the end padding
+        trimmer.set_raw_position(900);
+        assert_eq!(trimmer.position(), 800);
+        assert_eq!(trimmer.trim(100), 0..50);
+    }
+
+    #[test]
+    fn test_delay_trimmer_unknown_total() {
+        // Without a total only the start padding is trimmed
+        let delay = EncoderDelay::from_lame(100, 50);
+        let mut trimmer = DelayTrimmer::new(delay, u64::MAX);
+        assert_eq!(trimmer.trim(150), 100..150);
+        assert_eq!(trimmer.trim(1_000_000), 0..1_000_000);
+    }
+
+    #[test]
+    fn test_delay_trimmer_trim_with_valid_samples() {
+        let delay =
+            EncoderDelay::from_itun_smpb(" 00000000 00000840 000001C0 0000000000000400").unwrap();
+        let mut trimmer = DelayTrimmer::new(delay, 4096);
+
+        // 2112 priming samples, then exactly 1024 valid samples
+        assert_eq!(trimmer.trim(2048), 2048..2048);
+        assert_eq!(trimmer.trim(2048), 64..1088);
+    }
+
+    #[test]
+    fn test_seek_preroll() {
+        let mut params = CodecParameters::new();
+        params.for_codec(CODEC_TYPE_MP3);
+        assert_eq!(seek_preroll(&params), 2304);
+
+        params.for_codec(CODEC_TYPE_OPUS);
+        assert_eq!(seek_preroll(&params), 3840);
+
+        params.for_codec(symphonia::core::codecs::CODEC_TYPE_FLAC);
+        assert_eq!(seek_preroll(&params), 0);
+    }
+
+    #[test]
+    fn test_from_codec_params() {
+        let mut params = CodecParameters::new();
+        params
+            .for_codec(CODEC_TYPE_MP3)
+            .with_delay(1105)
+            .with_padding(1152);
+
+        let delay = EncoderDelay::from_codec_params(&params).unwrap();
+        assert_eq!(delay.start_padding, 1105);
+        assert_eq!(delay.end_padding, 1152);
+        assert_eq!(delay.source, DelaySource::LameHeader);
+
+        // No LAME tag
+        let mut params = CodecParameters::new();
+        params.for_codec(CODEC_TYPE_MP3);
+        assert!(EncoderDelay::from_codec_params(&params).is_none());
+
+        // Opus pre-skip
+        let mut params = CodecParameters::new();
+        params
+            .for_codec(CODEC_TYPE_OPUS)
+            .with_delay(312)
+            .with_padding(648);
+
+        let delay = EncoderDelay::from_codec_params(&params).unwrap();
+        assert_eq!(delay.start_padding, 312);
+        assert_eq!(delay.end_padding, 648);
+        assert_eq!(delay.source, DelaySource::OpusHeader);
+    }
+
+    #[test]
+    fn test_from_tags() {
+        use symphonia::core::meta::Value;
+
+        let tags = [
+            Tag::new(None, "TITLE", Value::from("Track")),
+            Tag::new(
+                None,
+                "com.apple.iTunes:iTunSMPB",
+                Value::from(" 00000000 00000840 000001C0 0000000000000400"),
+            ),
+        ];
+        let delay = EncoderDelay::from_tags(&tags).unwrap();
+        assert_eq!(delay.start_padding, 2112);
+        assert_eq!(delay.end_padding, 448);
+        assert_eq!(delay.valid_samples, Some(1024));
+
+        let tags = [
+            Tag::new(None, "ENCODER_DELAY", Value::from("312")),
+            Tag::new(None, "encoder_padding", Value::from("256")),
+        ];
+        let delay = EncoderDelay::from_tags(&tags).unwrap();
+        assert_eq!(delay.start_padding, 312);
+        assert_eq!(delay.end_padding, 256);
+        assert_eq!(delay.source, DelaySource::VorbisComment);
+
+        assert!(EncoderDelay::from_tags(&[]).is_none());
+    }
+
     #[test]
     fn test_actual_duration() {
         let delay = EncoderDelay::from_lame(576, 1152);
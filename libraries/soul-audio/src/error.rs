@@ -31,6 +31,10 @@ pub enum AudioError {
     #[error("Seek error: {0}")]
     SeekError(String),
 
+    /// Offline render or encode error
+    #[error("Render error: {0}")]
+    RenderError(String),
+
     /// No file is currently open
     #[error("No file open for streaming decode")]
     NoFileOpen,
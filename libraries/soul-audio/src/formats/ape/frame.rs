@@ -0,0 +1,702 @@
+//! Monkey's Audio frame decoding (file versions 3.99 and later)
+//!
+//! Each frame is decoded from scratch in three stages:
+//!
+//! 1. Range-coded residuals, with an adaptive Rice-like parameter per
+//!    channel (`Y` and `X`, interleaved sample by sample)
+//! 2. Up to three adaptive FIR ("NN") filters per channel, depending on
+//!    the compression level
+//! 3. A cross-coupled adaptive predictor, then mid/side to left/right
+//!
+//! The filter and predictor state machines are shared with the test
+//! encoder, which runs the same stages backwards.
+
+use crate::error::{AudioError, Result};
+
+/// Samples of predictor history before the buffer is rewound
+const HISTORY_SIZE: usize = 512;
+
+/// Elements of predictor history in use
+const PREDICTOR_SIZE: usize = 50;
+
+// Offsets of the predictor histories within the shared buffer
+const Y_DELAY_A: usize = 50;
+const Y_DELAY_B: usize = 42;
+const X_DELAY_A: usize = 34;
+const X_DELAY_B: usize = 26;
+const Y_ADAPT_A: usize = 18;
+const X_ADAPT_A: usize = 14;
+const Y_ADAPT_B: usize = 10;
+const X_ADAPT_B: usize = 5;
+
+const INITIAL_COEFFS_A: [i32; 4] = [360, 317, -109, 98];
+
+/// Frame flags (after the CRC when its top bit is set)
+pub(crate) const FRAME_MONO_SILENCE: u32 = 1;
+pub(crate) const FRAME_STEREO_SILENCE: u32 = 3;
+pub(crate) const FRAME_PSEUDO_STEREO: u32 = 4;
+
+/// NN filter order and fraction bits per level, by compression level
+/// (1000 = fast ... 5000 = insane)
+pub(crate) const FILTERS: [&[(usize, u32)]; 5] = [
+    &[],
+    &[(16, 11)],
+    &[(64, 11)],
+    &[(32, 10), (256, 13)],
+    &[(16, 11), (256, 13), (1280, 15)],
+];
+
+/// Cumulative frequencies of the first overflow symbols
+pub(crate) const COUNTS: [u32; 22] = [
+    0, 19578, 36160, 48417, 56323, 60899, 63265, 64435, 64971, 65232, 65351, 65416, 65447, 65466,
+    65476, 65482, 65485, 65488, 65490, 65491, 65492, 65493,
+];
+
+/// Overflow symbol sent with its 32-bit value as two 16-bit halves
+pub(crate) const ESCAPE_SYMBOL: u32 = 63;
+
+/// Cumulative frequency of the symbols above 20, which are 1 wide
+pub(crate) const LARGE_SYMBOL_BASE: u32 = 65472;
+
+/// Range coder scale
+pub(crate) const BOTTOM_VALUE: u32 = 1 << 23;
+
+/// Inverse sign: -1 for positive, 1 for negative, 0 for zero
+pub(crate) fn inverse_sign(value: i32) -> i32 {
+    i32::from(value < 0) - i32::from(value > 0)
+}
+
+/// Map a signed residual to the unsigned value the entropy coder sends
+pub(crate) fn fold_sign(value: i32) -> u32 {
+    if value > 0 {
+        (value as u32) * 2 - 1
+    } else {
+        value.unsigned_abs() * 2
+    }
+}
+
+/// Adaptive parameter of the residual coder
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Rice {
+    k: u32,
+    ksum: u32,
+}
+
+impl Default for Rice {
+    fn default() -> Self {
+        Self {
+            k: 10,
+            ksum: 16 << 10,
+        }
+    }
+}
+
+impl Rice {
+    /// Modulus the low part of a value is coded with
+    pub(crate) fn pivot(self) -> u32 {
+        (self.ksum >> 5).max(1)
+    }
+
+    pub(crate) fn update(&mut self, value: u32) {
+        self.ksum = self
+            .ksum
+            .wrapping_add(value.wrapping_add(1) / 2)
+            .wrapping_sub((self.ksum.wrapping_add(16)) >> 5);
+
+        let limit = if self.k > 0 { 1 << (self.k + 4) } else { 0 };
+        if self.ksum < limit {
+            self.k -= 1;
+        } else if self.ksum >= 1 << (self.k + 5) && self.k < 24 {
+            self.k += 1;
+        }
+    }
+}
+
+/// Range decoder over the byte-swapped frame data
+struct RangeDecoder<'a> {
+    data: &'a [u8],
+    position: usize,
+    buffer: u32,
+    low: u32,
+    range: u32,
+    help: u32,
+    /// Set when reading past the end of the data
+    overrun: bool,
+}
+
+impl<'a> RangeDecoder<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        let buffer = u32::from(data.first().copied().unwrap_or(0));
+        Self {
+            data,
+            position: 1,
+            buffer,
+            low: buffer >> 1,
+            range: 1 << 7,
+            help: 0,
+            overrun: data.is_empty(),
+        }
+    }
+
+    fn normalize(&mut self) {
+        while self.range <= BOTTOM_VALUE {
+            self.buffer <<= 8;
+            match self.data.get(self.position) {
+                Some(&byte) => {
+                    self.buffer = self.buffer.wrapping_add(u32::from(byte));
+                    self.position += 1;
+                }
+                None => self.overrun = true,
+            }
+            self.low = (self.low << 8) | ((self.buffer >> 1) & 0xff);
+            self.range <<= 8;
+        }
+    }
+
+    fn culfreq(&mut self, total: u32) -> u32 {
+        self.normalize();
+        self.help = self.range / total;
+        self.low / self.help
+    }
+
+    fn culshift(&mut self, shift: u32) -> u32 {
+        self.normalize();
+        self.help = self.range >> shift;
+        self.low / self.help
+    }
+
+    fn update(&mut self, width: u32, start: u32) {
+        self.low = self.low.wrapping_sub(self.help.wrapping_mul(start));
+        self.range = self.help.wrapping_mul(width);
+    }
+
+    fn bits(&mut self, count: u32) -> u32 {
+        let value = self.culshift(count);
+        self.update(1, value);
+        value
+    }
+
+    fn symbol(&mut self) -> u32 {
+        let cf = self.culshift(16);
+        if cf > COUNTS[COUNTS.len() - 1] - 1 {
+            self.update(1, cf);
+            if cf > 65535 {
+                self.overrun = true;
+            }
+            return cf - LARGE_SYMBOL_BASE;
+        }
+
+        let symbol = COUNTS[1..].iter().take_while(|&&count| count <= cf).count();
+        self.update(COUNTS[symbol + 1] - COUNTS[symbol], COUNTS[symbol]);
+        symbol as u32
+    }
+
+    /// Decode one residual
+    fn value(&mut self, rice: &mut Rice) -> i32 {
+        let pivot = rice.pivot();
+
+        let mut overflow = self.symbol();
+        if overflow == ESCAPE_SYMBOL {
+            overflow = self.bits(16) << 16;
+            overflow |= self.bits(16);
+        }
+
+        let base = if pivot < 0x10000 {
+            let base = self.culfreq(pivot);
+            self.update(1, base);
+            base
+        } else {
+            let mut high = pivot;
+            let mut low_bits = 0;
+            while high & !0xffff != 0 {
+                high >>= 1;
+                low_bits += 1;
+            }
+            let high = self.culfreq(high + 1);
+            self.update(1, high);
+            let low = self.culfreq(1 << low_bits);
+            self.update(1, low);
+            (high << low_bits) + low
+        };
+
+        let value = base.wrapping_add(overflow.wrapping_mul(pivot));
+        rice.update(value);
+
+        (((value >> 1) as i32) ^ ((value & 1) as i32 - 1)).wrapping_add(1)
+    }
+}
+
+/// Adaptive FIR filter with sign-sign LMS updates
+#[derive(Debug, Clone)]
+pub(crate) struct NnFilter {
+    order: usize,
+    fraction_bits: u32,
+    coeffs: Vec<i16>,
+    /// Past outputs, saturated to 16 bits
+    history: Vec<i16>,
+    /// Past adaptation steps
+    adapt: Vec<i16>,
+    /// Index of the next history slot
+    position: usize,
+    /// Running average of output magnitudes
+    average: i32,
+}
+
+impl NnFilter {
+    pub(crate) fn new(order: usize, fraction_bits: u32) -> Self {
+        Self {
+            order,
+            fraction_bits,
+            coeffs: vec![0; order],
+            history: vec![0; order + HISTORY_SIZE],
+            adapt: vec![0; order + HISTORY_SIZE],
+            position: order,
+            average: 0,
+        }
+    }
+
+    /// Filters for a compression level
+    pub(crate) fn for_level(level: usize) -> Vec<Self> {
+        FILTERS[level]
+            .iter()
+            .map(|&(order, bits)| Self::new(order, bits))
+            .collect()
+    }
+
+    /// Value the filter adds to its next input
+    pub(crate) fn prediction(&self) -> i32 {
+        let start = self.position - self.order;
+        let dot = self
+            .coeffs
+            .iter()
+            .zip(&self.history[start..self.position])
+            .fold(0i32, |sum, (&c, &h)| {
+                sum.wrapping_add(i32::from(c) * i32::from(h))
+            });
+        ((i64::from(dot) + (1 << (self.fraction_bits - 1))) >> self.fraction_bits) as i32
+    }
+
+    /// Adapt to one input and the output the filter produced from it
+    pub(crate) fn update(&mut self, input: i32, output: i32) {
+        let start = self.position - self.order;
+        let direction = inverse_sign(input) as i16;
+        for (c, &a) in self
+            .coeffs
+            .iter_mut()
+            .zip(&self.adapt[start..self.position])
+        {
+            *c = c.wrapping_add(direction.wrapping_mul(a));
+        }
+
+        self.history[self.position] = output.clamp(i32::from(i16::MIN), i32::from(i16::MAX)) as i16;
+
+        let magnitude = i64::from(output.unsigned_abs());
+        let average = i64::from(self.average);
+        self.adapt[self.position] = if magnitude == 0 {
+            0
+        } else {
+            let shift =
+                u32::from(magnitude > average * 3) + u32::from(magnitude > average + average / 3);
+            (inverse_sign(output) * (8 << shift)) as i16
+        };
+        self.average = self.average.wrapping_add((magnitude - average) as i32 / 16);
+
+        for back in [1, 2, 8] {
+            self.adapt[self.position - back] >>= 1;
+        }
+
+        self.position += 1;
+        if self.position == self.history.len() {
+            let keep = self.history.len() - self.order;
+            self.history.copy_within(keep.., 0);
+            self.adapt.copy_within(keep.., 0);
+            self.position = self.order;
+        }
+    }
+
+    /// Run the filter on one input
+    pub(crate) fn apply(&mut self, input: i32) -> i32 {
+        let output = input.wrapping_add(self.prediction());
+        self.update(input, output);
+        output
+    }
+}
+
+/// The cross-coupled predictor that runs after the NN filters
+#[derive(Debug, Clone)]
+pub(crate) struct Predictor {
+    buffer: Vec<i32>,
+    position: usize,
+    last_a: [i32; 2],
+    filter_a: [i32; 2],
+    filter_b: [i32; 2],
+    coeffs_a: [[i32; 4]; 2],
+    coeffs_b: [[i32; 5]; 2],
+}
+
+impl Default for Predictor {
+    fn default() -> Self {
+        Self {
+            buffer: vec![0; HISTORY_SIZE + PREDICTOR_SIZE],
+            position: 0,
+            last_a: [0; 2],
+            filter_a: [0; 2],
+            filter_b: [0; 2],
+            coeffs_a: [INITIAL_COEFFS_A; 2],
+            coeffs_b: [[0; 5]; 2],
+        }
+    }
+}
+
+/// History layout of one stereo channel
+#[derive(Clone, Copy)]
+struct Layout {
+    delay_a: usize,
+    delay_b: usize,
+    adapt_a: usize,
+    adapt_b: usize,
+}
+
+const Y_LAYOUT: Layout = Layout {
+    delay_a: Y_DELAY_A,
+    delay_b: Y_DELAY_B,
+    adapt_a: Y_ADAPT_A,
+    adapt_b: Y_ADAPT_B,
+};
+
+const X_LAYOUT: Layout = Layout {
+    delay_a: X_DELAY_A,
+    delay_b: X_DELAY_B,
+    adapt_a: X_ADAPT_A,
+    adapt_b: X_ADAPT_B,
+};
+
+impl Predictor {
+    fn at(&mut self, offset: usize) -> &mut i32 {
+        &mut self.buffer[self.position + offset]
+    }
+
+    fn get(&self, offset: usize) -> i32 {
+        self.buffer[self.position + offset]
+    }
+
+    fn advance(&mut self) {
+        self.position += 1;
+        if self.position == HISTORY_SIZE {
+            self.buffer.copy_within(HISTORY_SIZE.., 0);
+            self.position = 0;
+        }
+    }
+
+    /// Store the history of stereo channel `filter` (0 = Y, 1 = X) and
+    /// return the prediction for its next sample
+    pub(crate) fn begin_stereo(&mut self, filter: usize) -> i32 {
+        let l = if filter == 0 { Y_LAYOUT } else { X_LAYOUT };
+
+        let last = self.last_a[filter];
+        *self.at(l.delay_a) = last;
+        *self.at(l.adapt_a) = inverse_sign(last);
+        let delta = last.wrapping_sub(self.get(l.delay_a - 1));
+        *self.at(l.delay_a - 1) = delta;
+        *self.at(l.adapt_a - 1) = inverse_sign(delta);
+
+        let prediction_a = (0..4).fold(0i32, |sum, i| {
+            sum.wrapping_add(
+                self.get(l.delay_a - i)
+                    .wrapping_mul(self.coeffs_a[filter][i]),
+            )
+        });
+
+        let value =
+            self.filter_a[filter ^ 1].wrapping_sub(self.filter_b[filter].wrapping_mul(31) >> 5);
+        *self.at(l.delay_b) = value;
+        *self.at(l.adapt_b) = inverse_sign(value);
+        let delta = value.wrapping_sub(self.get(l.delay_b - 1));
+        *self.at(l.delay_b - 1) = delta;
+        *self.at(l.adapt_b - 1) = inverse_sign(delta);
+        self.filter_b[filter] = self.filter_a[filter ^ 1];
+
+        let prediction_b = (0..5).fold(0i32, |sum, i| {
+            sum.wrapping_add(
+                self.get(l.delay_b - i)
+                    .wrapping_mul(self.coeffs_b[filter][i]),
+            )
+        });
+
+        prediction_a.wrapping_add(prediction_b >> 1) >> 10
+    }
+
+    /// Finish stereo channel `filter` with its input; returns the output
+    pub(crate) fn end_stereo(&mut self, filter: usize, input: i32, prediction: i32) -> i32 {
+        let l = if filter == 0 { Y_LAYOUT } else { X_LAYOUT };
+
+        self.last_a[filter] = input.wrapping_add(prediction);
+        self.filter_a[filter] =
+            self.last_a[filter].wrapping_add(self.filter_a[filter].wrapping_mul(31) >> 5);
+
+        let sign = inverse_sign(input);
+        for i in 0..4 {
+            self.coeffs_a[filter][i] += self.get(l.adapt_a - i) * sign;
+        }
+        for i in 0..5 {
+            self.coeffs_b[filter][i] += self.get(l.adapt_b - i) * sign;
+        }
+
+        if filter == 1 {
+            self.advance();
+        }
+        self.filter_a[filter]
+    }
+
+    /// Input that makes stereo channel `filter` output `output`
+    pub(crate) fn stereo_input(&self, filter: usize, output: i32, prediction: i32) -> i32 {
+        let last = output.wrapping_sub(self.filter_a[filter].wrapping_mul(31) >> 5);
+        last.wrapping_sub(prediction)
+    }
+
+    /// Store the mono history and return the prediction for the next sample
+    pub(crate) fn begin_mono(&mut self) -> i32 {
+        let last = self.last_a[0];
+        *self.at(Y_DELAY_A) = last;
+        let delta = last.wrapping_sub(self.get(Y_DELAY_A - 1));
+        *self.at(Y_DELAY_A - 1) = delta;
+
+        let prediction = (0..4).fold(0i32, |sum, i| {
+            sum.wrapping_add(self.get(Y_DELAY_A - i).wrapping_mul(self.coeffs_a[0][i]))
+        });
+        prediction >> 10
+    }
+
+    /// Finish a mono sample with its input; returns the output
+    pub(crate) fn end_mono(&mut self, input: i32, prediction: i32) -> i32 {
+        self.last_a[0] = input.wrapping_add(prediction);
+
+        *self.at(Y_ADAPT_A) = inverse_sign(self.get(Y_DELAY_A));
+        *self.at(Y_ADAPT_A - 1) = inverse_sign(self.get(Y_DELAY_A - 1));
+        let sign = inverse_sign(input);
+        for i in 0..4 {
+            self.coeffs_a[0][i] += self.get(Y_ADAPT_A - i) * sign;
+        }
+        self.advance();
+
+        self.filter_a[0] = self.last_a[0].wrapping_add(self.filter_a[0].wrapping_mul(31) >> 5);
+        self.filter_a[0]
+    }
+
+    /// Input that makes the mono predictor output `output`
+    pub(crate) fn mono_input(&self, output: i32, prediction: i32) -> i32 {
+        let last = output.wrapping_sub(self.filter_a[0].wrapping_mul(31) >> 5);
+        last.wrapping_sub(prediction)
+    }
+}
+
+/// Parameters shared by all frames of a file
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct FrameParams {
+    /// Compression level / 1000 - 1 (index into [`FILTERS`])
+    pub level: usize,
+    pub channels: usize,
+    pub bits_per_sample: u16,
+}
+
+/// CRC of the decoded PCM as stored in a frame header
+pub(crate) fn frame_crc(samples: &[[i32; 2]], channels: usize, bits_per_sample: u16) -> u32 {
+    let mut crc = u32::MAX;
+    let mut feed = |byte: u8| {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    };
+
+    for frame in samples {
+        for &sample in &frame[..channels] {
+            match bits_per_sample {
+                8 => feed((sample + 0x80) as u8),
+                16 => sample.to_le_bytes()[..2].iter().for_each(|&b| feed(b)),
+                _ => sample.to_le_bytes()[..3].iter().for_each(|&b| feed(b)),
+            }
+        }
+    }
+    !crc >> 1
+}
+
+fn invalid(message: &str) -> AudioError {
+    AudioError::DecodeError(format!("ape: {}", message))
+}
+
+/// Decode one frame of `blocks` samples per channel
+///
+/// `data` is the frame's bytes in stream order (after the 32-bit byte swap
+/// and the alignment skip). Samples are appended to `output` as interleaved
+/// integers.
+pub(crate) fn decode_frame(
+    params: FrameParams,
+    data: &[u8],
+    blocks: usize,
+    output: &mut Vec<i32>,
+) -> Result<()> {
+    if data.len() < 6 {
+        return Err(invalid("truncated frame"));
+    }
+    let be32 = |at: usize| u32::from_be_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]]);
+
+    let mut crc = be32(0);
+    let mut flags = 0;
+    let mut at = 4;
+    if crc & 0x8000_0000 != 0 {
+        crc &= !0x8000_0000;
+        if data.len() < 10 {
+            return Err(invalid("truncated frame"));
+        }
+        flags = be32(4);
+        at = 8;
+    }
+    // One byte of the range coder's output is never read
+    at += 1;
+
+    let mut decoder = RangeDecoder::new(&data[at..]);
+    let mut y = vec![0i32; blocks];
+    let mut x = vec![0i32; blocks];
+    let stereo = params.channels == 2;
+    let mono_coded = !stereo || flags & FRAME_PSEUDO_STEREO != 0;
+
+    if mono_coded {
+        if flags & FRAME_MONO_SILENCE == 0 {
+            let mut rice = Rice::default();
+            for value in &mut y {
+                *value = decoder.value(&mut rice);
+            }
+
+            let mut filters = NnFilter::for_level(params.level);
+            let mut predictor = Predictor::default();
+            for value in &mut y {
+                for filter in &mut filters {
+                    *value = filter.apply(*value);
+                }
+                let prediction = predictor.begin_mono();
+                *value = predictor.end_mono(*value, prediction);
+            }
+        }
+        if stereo {
+            x.copy_from_slice(&y);
+        }
+    } else if flags & FRAME_STEREO_SILENCE != FRAME_STEREO_SILENCE {
+        let mut rice_y = Rice::default();
+        let mut rice_x = Rice::default();
+        for (y, x) in y.iter_mut().zip(&mut x) {
+            *y = decoder.value(&mut rice_y);
+            *x = decoder.value(&mut rice_x);
+        }
+
+        let mut filters_y = NnFilter::for_level(params.level);
+        let mut filters_x = NnFilter::for_level(params.level);
+        let mut predictor = Predictor::default();
+        for (y, x) in y.iter_mut().zip(&mut x) {
+            for filter in &mut filters_y {
+                *y = filter.apply(*y);
+            }
+            for filter in &mut filters_x {
+                *x = filter.apply(*x);
+            }
+
+            let prediction = predictor.begin_stereo(0);
+            *y = predictor.end_stereo(0, *y, prediction);
+            let prediction = predictor.begin_stereo(1);
+            *x = predictor.end_stereo(1, *x, prediction);
+
+            // Mid/side to left/right
+            let left = x.wrapping_sub(*y / 2);
+            let right = left.wrapping_add(*y);
+            *y = left;
+            *x = right;
+        }
+    }
+
+    if decoder.overrun {
+        return Err(invalid("frame data ended early"));
+    }
+
+    let pcm: Vec<[i32; 2]> = y.iter().zip(&x).map(|(&l, &r)| [l, r]).collect();
+    if frame_crc(&pcm, params.channels, params.bits_per_sample) != crc {
+        return Err(invalid("checksum mismatch"));
+    }
+
+    output.reserve(blocks * params.channels);
+    for frame in &pcm {
+        output.extend_from_slice(&frame[..params.channels]);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fold_sign_round_trip() {
+        for value in [0, 1, -1, 2, -2, 1000, -1000, i32::MAX, i32::MIN + 1] {
+            let folded = fold_sign(value);
+            let restored = (((folded >> 1) as i32) ^ ((folded & 1) as i32 - 1)).wrapping_add(1);
+            assert_eq!(restored, value);
+        }
+        assert_eq!(fold_sign(1), 1);
+        assert_eq!(fold_sign(-1), 2);
+    }
+
+    #[test]
+    fn test_rice_adapts_to_magnitude() {
+        let mut rice = Rice::default();
+        assert_eq!(rice.pivot(), 512);
+
+        for _ in 0..500 {
+            rice.update(10);
+        }
+        assert!(rice.pivot() < 16, "pivot {}", rice.pivot());
+
+        for _ in 0..500 {
+            rice.update(100_000);
+        }
+        assert!(rice.pivot() > 10_000, "pivot {}", rice.pivot());
+    }
+
+    #[test]
+    fn test_filter_prediction_is_applied_and_inverted() {
+        let mut decoder = NnFilter::new(16, 11);
+        let mut encoder = NnFilter::new(16, 11);
+
+        for n in 0..2000 {
+            let signal = ((n as f64 * 0.05).sin() * 20000.0) as i32;
+            let residual = signal - encoder.prediction();
+            encoder.update(residual, signal);
+            assert_eq!(decoder.apply(residual), signal);
+        }
+    }
+
+    #[test]
+    fn test_predictor_inversion() {
+        let mut decoder = Predictor::default();
+        let mut encoder = Predictor::default();
+
+        for n in 0..2000 {
+            let y = ((n as f64 * 0.01).sin() * 3000.0) as i32;
+            let x = ((n as f64 * 0.013).cos() * 3000.0) as i32;
+
+            let prediction = encoder.begin_stereo(0);
+            let input_y = encoder.stereo_input(0, y, prediction);
+            encoder.end_stereo(0, input_y, prediction);
+            let prediction = encoder.begin_stereo(1);
+            let input_x = encoder.stereo_input(1, x, prediction);
+            encoder.end_stereo(1, input_x, prediction);
+
+            let prediction = decoder.begin_stereo(0);
+            assert_eq!(decoder.end_stereo(0, input_y, prediction), y);
+            let prediction = decoder.begin_stereo(1);
+            assert_eq!(decoder.end_stereo(1, input_x, prediction), x);
+        }
+    }
+}
@@ -0,0 +1,509 @@
+//! Monkey's Audio (.ape) decoding
+//!
+//! Pure-Rust decoder for files written by Monkey's Audio 3.99 and later
+//! (every compression level, mono and stereo, 8/16/24-bit). Older files use
+//! different entropy coding and predictors and are rejected as unsupported.
+//!
+//! After a descriptor and header, a seek table gives the byte position of
+//! every frame. Frames are stored as a stream of little-endian 32-bit words
+//! that is read back byte-swapped; a frame can start at any byte of a word.
+
+pub(crate) mod frame;
+
+use self::frame::FrameParams;
+use super::apetag::ApeTag;
+use super::framed::{FrameReader, FramedDecoder, StreamInfo};
+use crate::error::{AudioError, Result};
+use crate::metadata::AudioMetadata as FileMetadata;
+use soul_core::{AudioBuffer, AudioDecoder as AudioDecoderTrait, AudioMetadata};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::Duration;
+
+/// File extensions of Monkey's Audio files
+pub const EXTENSIONS: &[&str] = &["ape"];
+
+/// Oldest file version we decode (Monkey's Audio 3.99)
+pub(crate) const MIN_VERSION: u16 = 3990;
+
+/// Size of the descriptor we read (it may be longer)
+const DESCRIPTOR_SIZE: usize = 52;
+const HEADER_SIZE: usize = 24;
+
+/// Largest number of samples per channel in one frame
+const MAX_FRAME_BLOCKS: u32 = 1 << 22;
+
+/// Whether a file header is Monkey's Audio
+pub(crate) fn probe(header: &[u8]) -> bool {
+    header.len() >= 6 && &header[..4] == b"MAC "
+}
+
+/// Location of one frame in the file
+#[derive(Debug, Clone, Copy)]
+struct FrameEntry {
+    /// Word-aligned start of the data to read
+    offset: u64,
+    /// Bytes to read (a multiple of 4)
+    len: usize,
+    /// Bytes to drop after the byte swap
+    skip: usize,
+    /// Samples per channel
+    blocks: u32,
+}
+
+/// Frame index and stream parameters of a Monkey's Audio file
+struct ApeReader {
+    file: BufReader<File>,
+    info: StreamInfo,
+    params: FrameParams,
+    blocks_per_frame: u32,
+    frames: Vec<FrameEntry>,
+    buffer: Vec<u8>,
+    decoded: Vec<i32>,
+}
+
+fn unsupported(path: &Path, message: &str) -> AudioError {
+    AudioError::UnsupportedFormat(format!("{}: {}", path.display(), message))
+}
+
+fn invalid(message: &str) -> AudioError {
+    AudioError::DecodeError(format!("ape: {}", message))
+}
+
+fn le16(data: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([data[at], data[at + 1]])
+}
+
+fn le32(data: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]])
+}
+
+impl ApeReader {
+    fn open(path: &Path) -> Result<Self> {
+        let mut file = BufReader::new(File::open(path)?);
+        let len = file.get_ref().metadata()?.len();
+        let junk = super::skip_id3v2(file.get_mut())?;
+
+        let mut descriptor = [0u8; DESCRIPTOR_SIZE];
+        file.seek(SeekFrom::Start(junk))?;
+        file.read_exact(&mut descriptor)
+            .map_err(|_| unsupported(path, "not a Monkey's Audio file"))?;
+        if &descriptor[..4] != b"MAC " {
+            return Err(unsupported(path, "not a Monkey's Audio file"));
+        }
+        let version = le16(&descriptor, 4);
+        if version < MIN_VERSION {
+            return Err(unsupported(
+                path,
+                &format!("Monkey's Audio version {} is too old", version),
+            ));
+        }
+
+        let descriptor_len = u64::from(le32(&descriptor, 8));
+        let header_len = u64::from(le32(&descriptor, 12));
+        let seek_table_len = le32(&descriptor, 16) as usize;
+        let wav_header_len = u64::from(le32(&descriptor, 20));
+        let wav_tail_len = u64::from(le32(&descriptor, 32));
+        if descriptor_len < DESCRIPTOR_SIZE as u64 || header_len < HEADER_SIZE as u64 {
+            return Err(invalid("invalid descriptor"));
+        }
+
+        let mut header = [0u8; HEADER_SIZE];
+        file.seek(SeekFrom::Start(junk + descriptor_len))?;
+        file.read_exact(&mut header)?;
+        let compression = le16(&header, 0);
+        let blocks_per_frame = le32(&header, 4);
+        let final_frame_blocks = le32(&header, 8);
+        let total_frames = le32(&header, 12) as usize;
+        let bits_per_sample = le16(&header, 16);
+        let channels = usize::from(le16(&header, 18));
+        let sample_rate = le32(&header, 20);
+
+        if compression % 1000 != 0 || !(1000..=5000).contains(&compression) {
+            return Err(invalid(&format!(
+                "unknown compression level {}",
+                compression
+            )));
+        }
+        if !matches!(channels, 1 | 2) {
+            return Err(unsupported(path, &format!("{} channels", channels)));
+        }
+        if !matches!(bits_per_sample, 8 | 16 | 24) {
+            return Err(unsupported(path, &format!("{}-bit audio", bits_per_sample)));
+        }
+        if sample_rate == 0
+            || total_frames == 0
+            || !(1..=MAX_FRAME_BLOCKS).contains(&blocks_per_frame)
+            || !(1..=blocks_per_frame).contains(&final_frame_blocks)
+        {
+            return Err(invalid("invalid header"));
+        }
+        if seek_table_len / 4 < total_frames {
+            return Err(invalid("seek table is shorter than the frame count"));
+        }
+
+        // Seek table positions exclude the junk before the descriptor and
+        // wrap at 4 GiB
+        let mut table = vec![0u8; total_frames * 4];
+        file.seek(SeekFrom::Start(junk + descriptor_len + header_len))?;
+        file.read_exact(&mut table)?;
+        let first = junk + descriptor_len + header_len + seek_table_len as u64 + wav_header_len;
+        let mut positions = Vec::with_capacity(total_frames);
+        positions.push(first);
+        let mut wrap = 0u64;
+        let mut previous = 0u32;
+        for entry in table.chunks_exact(4).skip(1) {
+            let entry = le32(entry, 0);
+            if entry < previous {
+                wrap += 1 << 32;
+            }
+            previous = entry;
+            let position = junk + wrap + u64::from(entry);
+            if position < *positions.last().expect("first frame pushed") || position >= len {
+                return Err(invalid("invalid seek table"));
+            }
+            positions.push(position);
+        }
+
+        let frames = positions
+            .iter()
+            .enumerate()
+            .map(|(i, &position)| {
+                let (end, blocks) = match positions.get(i + 1) {
+                    Some(&next) => (next, blocks_per_frame),
+                    None => (
+                        len.saturating_sub(wav_tail_len).max(position),
+                        final_frame_blocks,
+                    ),
+                };
+                let skip = ((position - first) & 3) as usize;
+                FrameEntry {
+                    offset: position - skip as u64,
+                    len: ((end - position) as usize + skip + 3) & !3,
+                    skip,
+                    blocks,
+                }
+            })
+            .collect();
+
+        let total_samples =
+            (total_frames as u64 - 1) * u64::from(blocks_per_frame) + u64::from(final_frame_blocks);
+
+        Ok(Self {
+            file,
+            info: StreamInfo {
+                sample_rate,
+                channels,
+                bits_per_sample,
+                total_samples,
+            },
+            params: FrameParams {
+                level: usize::from(compression / 1000 - 1),
+                channels,
+                bits_per_sample,
+            },
+            blocks_per_frame,
+            frames,
+            buffer: Vec::new(),
+            decoded: Vec::new(),
+        })
+    }
+}
+
+impl FrameReader for ApeReader {
+    fn info(&self) -> StreamInfo {
+        self.info
+    }
+
+    fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    fn frame_start(&self, index: usize) -> u64 {
+        index as u64 * u64::from(self.blocks_per_frame)
+    }
+
+    fn decode_frame(&mut self, index: usize, output: &mut Vec<f32>) -> Result<()> {
+        let frame = self.frames[index];
+        self.buffer.clear();
+        self.file.seek(SeekFrom::Start(frame.offset))?;
+        (&mut self.file)
+            .take(frame.len as u64)
+            .read_to_end(&mut self.buffer)?;
+        // The last frame may end in a partial word
+        self.buffer.resize((self.buffer.len() + 3) & !3, 0);
+        for word in self.buffer.chunks_exact_mut(4) {
+            word.reverse();
+        }
+        if self.buffer.len() <= frame.skip {
+            return Err(invalid("truncated frame"));
+        }
+
+        self.decoded.clear();
+        frame::decode_frame(
+            self.params,
+            &self.buffer[frame.skip..],
+            frame.blocks as usize,
+            &mut self.decoded,
+        )?;
+
+        let scale = 1.0 / (1u32 << (self.info.bits_per_sample - 1)) as f32;
+        output.extend(self.decoded.iter().map(|&s| s as f32 * scale));
+        Ok(())
+    }
+}
+
+fn open_frames(path: &Path) -> Result<Box<dyn FrameReader>> {
+    Ok(Box::new(ApeReader::open(path)?))
+}
+
+/// Read stream properties and the APEv2 tag of a Monkey's Audio file
+pub fn extract_metadata(path: &Path) -> Result<FileMetadata> {
+    let info = ApeReader::open(path)?.info;
+
+    let mut metadata = FileMetadata {
+        duration_seconds: Some(info.duration().as_secs_f64()),
+        sample_rate: Some(info.sample_rate),
+        channels: Some(info.channels as u8),
+        bit_depth: Some(u32::from(info.bits_per_sample)),
+        ..Default::default()
+    };
+    if let Ok(len) = std::fs::metadata(path).map(|m| m.len()) {
+        let seconds = info.duration().as_secs_f64();
+        if seconds > 0.0 {
+            metadata.bitrate = Some((len as f64 * 8.0 / seconds / 1000.0) as u32);
+        }
+    }
+    if let Some(tag) = ApeTag::read(path)? {
+        tag.apply(&mut metadata);
+    }
+    Ok(metadata)
+}
+
+/// Monkey's Audio decoder
+///
+/// Output is interleaved `f32` in the file's channel layout.
+#[derive(Default)]
+pub struct ApeDecoder {
+    inner: FramedDecoder,
+}
+
+impl ApeDecoder {
+    /// Create a new decoder
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl AudioDecoderTrait for ApeDecoder {
+    fn decode(&mut self, path: &Path) -> soul_core::Result<AudioBuffer> {
+        Ok(FramedDecoder::decode(path, open_frames)?)
+    }
+
+    fn supports_format(&self, path: &Path) -> bool {
+        super::has_extension(path, EXTENSIONS)
+    }
+
+    fn open(&mut self, path: &Path) -> soul_core::Result<AudioMetadata> {
+        Ok(self.inner.open(path, open_frames)?)
+    }
+
+    fn decode_chunk(&mut self, max_frames: usize) -> soul_core::Result<Option<AudioBuffer>> {
+        Ok(self.inner.decode_chunk(max_frames)?)
+    }
+
+    fn seek(&mut self, position: Duration) -> soul_core::Result<Duration> {
+        Ok(self.inner.seek(position)?)
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        self.inner.duration()
+    }
+
+    fn position(&self) -> Duration {
+        self.inner.position()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::ape::{ape_tag, write_ape, ApeOptions};
+    use crate::test_utils::generate_pcm_test_signal;
+
+    fn to_float(samples: &[i32], bits_per_sample: u16) -> Vec<f32> {
+        let scale = 1.0 / (1u32 << (bits_per_sample - 1)) as f32;
+        samples.iter().map(|&s| s as f32 * scale).collect()
+    }
+
+    fn decode_all(path: &Path) -> (AudioMetadata, Vec<f32>) {
+        let mut decoder = ApeDecoder::new();
+        let info = decoder.open(path).unwrap();
+        let mut samples = Vec::new();
+        while let Some(chunk) = decoder.decode_chunk(1000).unwrap() {
+            assert_eq!(chunk.format.channels, info.channels);
+            samples.extend(chunk.samples);
+        }
+        (info, samples)
+    }
+
+    fn round_trip(input: &[i32], options: &ApeOptions) -> (AudioMetadata, Vec<f32>) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.ape");
+        write_ape(&path, input, options).unwrap();
+        decode_all(&path)
+    }
+
+    #[test]
+    fn test_probe() {
+        assert!(probe(b"MAC \x9e\x0f\x00\x00"));
+        assert!(!probe(b"wvpk"));
+    }
+
+    #[test]
+    fn test_every_compression_level() {
+        let input = generate_pcm_test_signal(10000, 2, 16);
+        for level in [1000, 2000, 3000, 4000, 5000] {
+            let options = ApeOptions {
+                compression_level: level,
+                blocks_per_frame: 3000,
+                ..Default::default()
+            };
+            let (info, samples) = round_trip(&input, &options);
+            assert_eq!(info.channels, 2);
+            assert_eq!(info.bits_per_sample, Some(16));
+            assert_eq!(samples, to_float(&input, 16), "level {level}");
+        }
+    }
+
+    #[test]
+    fn test_mono_8_and_24_bit() {
+        let input = generate_pcm_test_signal(5000, 1, 16);
+        let options = ApeOptions {
+            channels: 1,
+            sample_rate: 22050,
+            ..Default::default()
+        };
+        let (info, samples) = round_trip(&input, &options);
+        assert_eq!(info.sample_rate, 22050);
+        assert_eq!(info.channels, 1);
+        assert_eq!(samples, to_float(&input, 16));
+
+        for bits in [8, 24] {
+            let input = generate_pcm_test_signal(5000, 2, u32::from(bits));
+            let options = ApeOptions {
+                bits_per_sample: bits,
+                compression_level: 3000,
+                blocks_per_frame: 1024,
+                ..Default::default()
+            };
+            let (info, samples) = round_trip(&input, &options);
+            assert_eq!(info.bits_per_sample, Some(bits));
+            assert_eq!(samples, to_float(&input, bits), "{bits}-bit");
+        }
+    }
+
+    #[test]
+    fn test_pseudo_stereo_and_silent_frames() {
+        // Identical channels, then silence spanning whole frames
+        let mono = generate_pcm_test_signal(8000, 1, 16);
+        let input: Vec<i32> = mono.iter().flat_map(|&s| [s, s]).collect();
+        let options = ApeOptions {
+            blocks_per_frame: 1000,
+            ..Default::default()
+        };
+        let (_, samples) = round_trip(&input, &options);
+        assert_eq!(samples, to_float(&input, 16));
+    }
+
+    #[test]
+    fn test_seek_lands_on_exact_sample() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("seek.ape");
+        let input = generate_pcm_test_signal(44100, 2, 16);
+        let options = ApeOptions {
+            blocks_per_frame: 4608,
+            ..Default::default()
+        };
+        write_ape(&path, &input, &options).unwrap();
+        let expected = to_float(&input, 16);
+
+        let mut decoder = ApeDecoder::new();
+        let info = decoder.open(&path).unwrap();
+        assert_eq!(info.duration, Some(Duration::from_secs(1)));
+        for millis in [512, 50, 0, 990] {
+            let frame = (millis as f64 * 44.1).round() as usize;
+            decoder.seek(Duration::from_millis(millis)).unwrap();
+            let chunk = decoder.decode_chunk(64).unwrap().unwrap();
+            assert_eq!(
+                &chunk.samples[..],
+                &expected[frame * 2..(frame * 2 + 128).min(expected.len())]
+            );
+        }
+    }
+
+    #[test]
+    fn test_corrupt_frame_is_an_error_not_noise() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("damaged.ape");
+        let input = generate_pcm_test_signal(4000, 2, 16);
+        let options = ApeOptions {
+            blocks_per_frame: 1000,
+            ..Default::default()
+        };
+        write_ape(&path, &input, &options).unwrap();
+
+        // Flip bits in the middle of the audio data
+        let mut data = std::fs::read(&path).unwrap();
+        let middle = data.len() - data.len() / 3;
+        data[middle] ^= 0x5a;
+        data[middle + 1] ^= 0xa5;
+        std::fs::write(&path, data).unwrap();
+
+        // The damaged frame comes out silent, the others intact
+        let (_, samples) = decode_all(&path);
+        let expected = to_float(&input, 16);
+        assert_eq!(samples.len(), expected.len());
+        let damaged = samples
+            .chunks(2000)
+            .zip(expected.chunks(2000))
+            .filter(|(a, b)| a != b)
+            .count();
+        assert!((1..=2).contains(&damaged), "{damaged} frames differ");
+    }
+
+    #[test]
+    fn test_extract_metadata_with_tag() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tagged.ape");
+        let input = generate_pcm_test_signal(22050, 2, 16);
+        write_ape(&path, &input, &ApeOptions::default()).unwrap();
+
+        let mut data = std::fs::read(&path).unwrap();
+        data.extend(ape_tag(&[("Title", 0, b"Old Rip"), ("Year", 0, b"1998")]));
+        std::fs::write(&path, data).unwrap();
+
+        let metadata = extract_metadata(&path).unwrap();
+        assert_eq!(metadata.title.as_deref(), Some("Old Rip"));
+        assert_eq!(metadata.year, Some(1998));
+        assert_eq!(metadata.sample_rate, Some(44100));
+        assert_eq!(metadata.bit_depth, Some(16));
+        assert!((metadata.duration_seconds.unwrap() - 0.5).abs() < 1e-9);
+
+        let (_, samples) = decode_all(&path);
+        assert_eq!(samples, to_float(&input, 16));
+    }
+
+    #[test]
+    fn test_rejects_old_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("old.ape");
+        let mut data = b"MAC ".to_vec();
+        data.extend_from_slice(&3970u16.to_le_bytes());
+        data.resize(128, 0);
+        std::fs::write(&path, data).unwrap();
+
+        assert!(ApeDecoder::new().open(&path).is_err());
+    }
+}
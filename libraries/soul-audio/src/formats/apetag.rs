@@ -0,0 +1,299 @@
+//! APEv2 tags
+//!
+//! WavPack, Monkey's Audio and Musepack files carry their tags in an APEv2
+//! tag at the end of the file (before an ID3v1 tag, if there is one). Items
+//! are key/value pairs with case-insensitive keys; text values are UTF-8
+//! with multiple values separated by NUL, and cover art is a binary item
+//! holding a file name, a NUL and the image data.
+
+use crate::error::Result;
+use crate::metadata::{
+    parse_replaygain, parse_track_number, parse_year, AlbumArt, AlbumArtType, AudioMetadata,
+};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+const PREAMBLE: &[u8; 8] = b"APETAGEX";
+const FOOTER_SIZE: u64 = 32;
+const ID3V1_SIZE: u64 = 128;
+
+/// Largest tag we read (cover art included)
+const MAX_TAG_SIZE: u32 = 64 * 1024 * 1024;
+
+/// Item flag bits 1-2: content type
+const ITEM_TYPE_MASK: u32 = 0x6;
+const ITEM_TYPE_BINARY: u32 = 0x2;
+
+/// Value of an APEv2 item
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ApeValue {
+    /// UTF-8 text values
+    Text(Vec<String>),
+    /// Binary data (cover art)
+    Binary(Vec<u8>),
+}
+
+/// An APEv2 tag: items in file order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct ApeTag {
+    pub items: Vec<(String, ApeValue)>,
+}
+
+impl ApeTag {
+    /// Read the tag at the end of a file; `None` if it has none
+    pub(crate) fn read(path: &Path) -> Result<Option<Self>> {
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
+
+        // The tag ends at EOF or right before an ID3v1 tag
+        let mut end = len;
+        if len >= ID3V1_SIZE {
+            let mut marker = [0u8; 3];
+            file.seek(SeekFrom::Start(len - ID3V1_SIZE))?;
+            file.read_exact(&mut marker)?;
+            if &marker == b"TAG" {
+                end = len - ID3V1_SIZE;
+            }
+        }
+        if end < FOOTER_SIZE {
+            return Ok(None);
+        }
+
+        let mut footer = [0u8; FOOTER_SIZE as usize];
+        file.seek(SeekFrom::Start(end - FOOTER_SIZE))?;
+        file.read_exact(&mut footer)?;
+        if &footer[..8] != PREAMBLE {
+            return Ok(None);
+        }
+
+        let le32 = |at: usize| {
+            u32::from_le_bytes([footer[at], footer[at + 1], footer[at + 2], footer[at + 3]])
+        };
+        // Size covers the items and the footer, not the optional header
+        let size = le32(12);
+        let count = le32(16);
+        if !(FOOTER_SIZE as u32..=MAX_TAG_SIZE).contains(&size) || u64::from(size) > end {
+            return Ok(None);
+        }
+
+        let mut items = vec![0u8; (u64::from(size) - FOOTER_SIZE) as usize];
+        file.seek(SeekFrom::Start(end - u64::from(size)))?;
+        file.read_exact(&mut items)?;
+
+        Ok(Some(Self::parse_items(&items, count)))
+    }
+
+    /// Parse `count` items; stops at the first malformed one
+    fn parse_items(mut data: &[u8], count: u32) -> Self {
+        let mut tag = Self::default();
+
+        for _ in 0..count {
+            if data.len() < 8 {
+                break;
+            }
+            let size = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
+            let flags = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
+            let Some(key_len) = data[8..].iter().position(|&b| b == 0) else {
+                break;
+            };
+            let value_start = 8 + key_len + 1;
+            if data.len() - value_start < size {
+                break;
+            }
+
+            let key = String::from_utf8_lossy(&data[8..8 + key_len]).into_owned();
+            let value = &data[value_start..value_start + size];
+            let value = if flags & ITEM_TYPE_MASK == ITEM_TYPE_BINARY {
+                ApeValue::Binary(value.to_vec())
+            } else {
+                ApeValue::Text(
+                    String::from_utf8_lossy(value)
+                        .split('\0')
+                        .map(|v| v.trim().to_string())
+                        .filter(|v| !v.is_empty())
+                        .collect(),
+                )
+            };
+            tag.items.push((key, value));
+            data = &data[value_start + size..];
+        }
+
+        tag
+    }
+
+    /// First text value of an item (keys are case-insensitive)
+    pub(crate) fn text(&self, key: &str) -> Option<&str> {
+        self.items.iter().find_map(|(k, v)| match v {
+            ApeValue::Text(values) if k.eq_ignore_ascii_case(key) => {
+                values.first().map(String::as_str)
+            }
+            _ => None,
+        })
+    }
+
+    /// Copy the tag into `metadata`
+    pub(crate) fn apply(&self, metadata: &mut AudioMetadata) {
+        for (key, value) in &self.items {
+            match value {
+                ApeValue::Binary(data) => {
+                    if let Some(art) = cover_art(key, data) {
+                        metadata.all_album_art.push(art);
+                    }
+                }
+                ApeValue::Text(values) => {
+                    for value in values {
+                        apply_text(metadata, key, value);
+                    }
+                }
+            }
+        }
+
+        if metadata.album_art.is_none() {
+            metadata.album_art = metadata.primary_album_art().cloned();
+        }
+    }
+}
+
+fn apply_text(metadata: &mut AudioMetadata, key: &str, value: &str) {
+    let text = Some(value.to_string());
+    match key.to_ascii_lowercase().as_str() {
+        "title" => metadata.title = text,
+        "artist" => metadata.artist = text,
+        "album" => metadata.album = text,
+        "album artist" | "albumartist" => metadata.album_artist = text,
+        "year" | "date" => metadata.year = parse_year(value),
+        "track" | "tracknumber" => {
+            let (number, total) = parse_track_number(value);
+            metadata.track_number = number;
+            metadata.track_total = total.or(metadata.track_total);
+        }
+        "disc" | "discnumber" => {
+            let (number, total) = parse_track_number(value);
+            metadata.disc_number = number;
+            metadata.disc_total = total.or(metadata.disc_total);
+        }
+        "genre" => metadata.genre = text,
+        "composer" => metadata.composer = text,
+        "comment" => metadata.comment = text,
+        "lyrics" | "unsyncedlyrics" => metadata.lyrics = text,
+        "conductor" => metadata.conductor = text,
+        "label" | "publisher" => metadata.label = text,
+        "copyright" => metadata.copyright = text,
+        "originaldate" | "original date" => metadata.original_date = text,
+        "musicbrainz_trackid" => metadata.musicbrainz_recording_id = text,
+        "musicbrainz_albumid" => metadata.musicbrainz_album_id = text,
+        "musicbrainz_artistid" => metadata.musicbrainz_artist_id = text,
+        "musicbrainz_releasegroupid" => metadata.musicbrainz_release_group_id = text,
+        "isrc" => metadata.isrc = text,
+        "catalognumber" => metadata.catalog_number = text,
+        "barcode" => metadata.barcode = text,
+        "replaygain_track_gain" => metadata.replaygain_track_gain = parse_replaygain(value),
+        "replaygain_track_peak" => metadata.replaygain_track_peak = value.parse().ok(),
+        "replaygain_album_gain" => metadata.replaygain_album_gain = parse_replaygain(value),
+        "replaygain_album_peak" => metadata.replaygain_album_peak = value.parse().ok(),
+        _ => metadata.add_custom_tag(key, value),
+    }
+}
+
+/// Cover art item: "Cover Art (Front)" etc., value is `filename\0data`
+fn cover_art(key: &str, data: &[u8]) -> Option<AlbumArt> {
+    let kind = key
+        .strip_prefix("Cover Art (")
+        .or_else(|| key.strip_prefix("COVER ART ("))?
+        .trim_end_matches(')');
+    let art_type = match kind.to_ascii_lowercase().as_str() {
+        "front" => AlbumArtType::FrontCover,
+        "back" => AlbumArtType::BackCover,
+        "media" => AlbumArtType::Media,
+        "artist" => AlbumArtType::Artist,
+        _ => AlbumArtType::Other,
+    };
+
+    let name_len = data.iter().position(|&b| b == 0)?;
+    let image = data[name_len + 1..].to_vec();
+    if image.is_empty() {
+        return None;
+    }
+
+    let mut art = AlbumArt::new(image, "", art_type);
+    art.mime_type = art.detect_mime_type().to_string();
+    if name_len > 0 {
+        art = art.with_description(String::from_utf8_lossy(&data[..name_len]));
+    }
+    Some(art)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::ape::ape_tag;
+
+    #[test]
+    fn test_read_tag_before_id3v1() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tagged.wv");
+
+        let mut cover = b"cover.png\0".to_vec();
+        cover.extend_from_slice(&[0x89, b'P', b'N', b'G', 1, 2, 3]);
+        let mut file = b"wvpk audio data".to_vec();
+        file.extend(ape_tag(&[
+            ("Title", 0, b"Song"),
+            ("ARTIST", 0, b"First\0Second"),
+            ("Track", 0, b"3/12"),
+            ("Year", 0, b"1998-04-01"),
+            ("REPLAYGAIN_TRACK_GAIN", 0, b"-6.50 dB"),
+            ("Mood", 0, b"calm"),
+            ("Cover Art (Front)", ITEM_TYPE_BINARY, &cover),
+        ]));
+        let mut id3v1 = b"TAG".to_vec();
+        id3v1.resize(128, 0);
+        file.extend(id3v1);
+        std::fs::write(&path, file).unwrap();
+
+        let tag = ApeTag::read(&path).unwrap().unwrap();
+        assert_eq!(tag.items.len(), 7);
+        assert_eq!(tag.text("title"), Some("Song"));
+
+        let mut metadata = AudioMetadata::new();
+        tag.apply(&mut metadata);
+        assert_eq!(metadata.title.as_deref(), Some("Song"));
+        // The last of several values wins, like repeated tags elsewhere
+        assert_eq!(metadata.artist.as_deref(), Some("Second"));
+        assert_eq!(
+            (metadata.track_number, metadata.track_total),
+            (Some(3), Some(12))
+        );
+        assert_eq!(metadata.year, Some(1998));
+        assert_eq!(metadata.replaygain_track_gain, Some(-6.5));
+        assert_eq!(metadata.get_custom_tag("Mood"), Some("calm"));
+
+        let art = metadata.album_art.unwrap();
+        assert!(art.is_front_cover());
+        assert_eq!(art.mime_type, "image/png");
+        assert_eq!(art.data.len(), 7);
+        assert_eq!(art.description.as_deref(), Some("cover.png"));
+    }
+
+    #[test]
+    fn test_files_without_tag() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("plain.wv");
+
+        std::fs::write(&path, b"short").unwrap();
+        assert_eq!(ApeTag::read(&path).unwrap(), None);
+
+        std::fs::write(&path, vec![0u8; 4096]).unwrap();
+        assert_eq!(ApeTag::read(&path).unwrap(), None);
+    }
+
+    #[test]
+    fn test_truncated_items_are_dropped() {
+        let mut data = ape_tag(&[("Title", 0, b"Song"), ("Album", 0, b"Record")]);
+        data.truncate(data.len() - FOOTER_SIZE as usize - 3);
+
+        let tag = ApeTag::parse_items(&data, 2);
+        assert_eq!(tag.items.len(), 1);
+        assert_eq!(tag.text("TITLE"), Some("Song"));
+    }
+}
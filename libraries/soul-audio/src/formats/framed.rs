@@ -0,0 +1,342 @@
+//! Streaming decode of frame-based lossless formats
+//!
+//! WavPack and Monkey's Audio both store audio as independently decodable
+//! frames (blocks) with a known first sample, so streaming and
+//! sample-accurate seeking work the same way for both: index the frames on
+//! open, decode them in order, and after a seek start from the frame that
+//! contains the target and drop the samples before it.
+//!
+//! Format readers implement [`FrameReader`]; [`FramedDecoder`] turns one into
+//! an interleaved `f32` stream in the file's own channel layout. Remixing
+//! to the output device (e.g. a BS.775 downmix of 5.1 to stereo) is left to
+//! the playback source.
+
+use crate::error::{AudioError, Result};
+use soul_core::{AudioBuffer, AudioFormat, AudioMetadata, SampleRate};
+use std::path::Path;
+use std::time::Duration;
+
+/// Stream parameters of a frame-based file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct StreamInfo {
+    /// Sample rate in Hz
+    pub sample_rate: u32,
+    /// Number of channels
+    pub channels: usize,
+    /// Bits per sample of the source
+    pub bits_per_sample: u16,
+    /// Length in samples per channel
+    pub total_samples: u64,
+}
+
+impl StreamInfo {
+    pub(crate) fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.total_samples as f64 / f64::from(self.sample_rate))
+    }
+}
+
+/// Random access to the frames of a file
+pub(crate) trait FrameReader: Send {
+    /// Stream parameters
+    fn info(&self) -> StreamInfo;
+
+    /// Number of frames
+    fn frame_count(&self) -> usize;
+
+    /// First sample (per channel) of frame `index`
+    fn frame_start(&self, index: usize) -> u64;
+
+    /// Decode frame `index` as interleaved samples in the file's channel
+    /// order, appended to `output`
+    fn decode_frame(&mut self, index: usize, output: &mut Vec<f32>) -> Result<()>;
+}
+
+/// Opens a file as a [`FrameReader`]
+pub(crate) type OpenFrames = fn(&Path) -> Result<Box<dyn FrameReader>>;
+
+/// Interleaved sample stream over the frames of an open file
+struct FrameStream {
+    reader: Box<dyn FrameReader>,
+    info: StreamInfo,
+    /// Next frame to decode
+    next_frame: usize,
+    /// Samples of the next frame to drop (seek target within the frame)
+    skip_samples: u64,
+    /// Decoded samples not yet returned
+    pending: Vec<f32>,
+    /// Scratch buffer for a decoded frame
+    decoded: Vec<f32>,
+    /// Current position in samples per channel
+    position_samples: u64,
+}
+
+impl FrameStream {
+    fn new(reader: Box<dyn FrameReader>) -> Self {
+        let info = reader.info();
+        Self {
+            reader,
+            info,
+            next_frame: 0,
+            skip_samples: 0,
+            pending: Vec::new(),
+            decoded: Vec::new(),
+            position_samples: 0,
+        }
+    }
+
+    /// Samples per channel in frame `index`
+    fn frame_len(&self, index: usize) -> u64 {
+        let end = if index + 1 < self.reader.frame_count() {
+            self.reader.frame_start(index + 1)
+        } else {
+            self.info.total_samples
+        };
+        end.saturating_sub(self.reader.frame_start(index))
+    }
+
+    /// Decode up to `max_frames` frames; `None` at end of stream
+    fn decode_chunk(&mut self, max_frames: usize) -> Result<Option<Vec<f32>>> {
+        let channels = self.info.channels;
+        let target_samples = max_frames * channels;
+
+        while self.pending.len() < target_samples && self.next_frame < self.reader.frame_count() {
+            let index = self.next_frame;
+            self.next_frame += 1;
+
+            self.decoded.clear();
+            if let Err(e) = self.reader.decode_frame(index, &mut self.decoded) {
+                // Keep the timeline intact across a damaged frame
+                eprintln!("[FramedDecoder] Frame {} replaced by silence: {}", index, e);
+                self.decoded.clear();
+                self.decoded
+                    .resize(self.frame_len(index) as usize * channels, 0.0);
+            }
+
+            let skip = (self.skip_samples as usize * channels).min(self.decoded.len());
+            self.skip_samples = 0;
+            self.pending.extend_from_slice(&self.decoded[skip..]);
+        }
+
+        if self.pending.is_empty() {
+            return Ok(None);
+        }
+
+        let take = target_samples.min(self.pending.len());
+        let samples: Vec<f32> = self.pending.drain(..take).collect();
+        self.position_samples += (samples.len() / channels) as u64;
+        Ok(Some(samples))
+    }
+
+    /// Seek to a position, returning the actual (sample-aligned) position
+    fn seek(&mut self, position: Duration) -> Result<Duration> {
+        let rate = f64::from(self.info.sample_rate);
+        let target = ((position.as_secs_f64() * rate).round() as u64).min(self.info.total_samples);
+
+        // Last frame starting at or before the target
+        let count = self.reader.frame_count();
+        let (mut low, mut high) = (0, count);
+        while low < high {
+            let mid = (low + high) / 2;
+            if self.reader.frame_start(mid) <= target {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        let frame = low.saturating_sub(1);
+
+        if frame < count && target < self.info.total_samples {
+            self.next_frame = frame;
+            self.skip_samples = target - self.reader.frame_start(frame);
+        } else {
+            self.next_frame = count;
+            self.skip_samples = 0;
+        }
+        self.pending.clear();
+        self.position_samples = target;
+
+        Ok(Duration::from_secs_f64(target as f64 / rate))
+    }
+
+    fn format(&self) -> AudioFormat {
+        AudioFormat::new(
+            SampleRate::new(self.info.sample_rate),
+            self.info.channels as u16,
+            32,
+        )
+    }
+}
+
+/// Shared implementation of the frame-based decoders
+///
+/// The public decoders wrap this with the function that opens their format.
+#[derive(Default)]
+pub(crate) struct FramedDecoder {
+    stream: Option<FrameStream>,
+}
+
+impl FramedDecoder {
+    fn open_reader(path: &Path, open: OpenFrames) -> Result<Box<dyn FrameReader>> {
+        if !path.exists() {
+            return Err(AudioError::FileNotFound(path.display().to_string()));
+        }
+        open(path)
+    }
+
+    /// Decode a whole file to interleaved `f32`
+    pub(crate) fn decode(path: &Path, open: OpenFrames) -> Result<AudioBuffer> {
+        let mut stream = FrameStream::new(Self::open_reader(path, open)?);
+        let mut all_samples = Vec::new();
+        while let Some(chunk) = stream.decode_chunk(16384)? {
+            all_samples.extend_from_slice(&chunk);
+        }
+        Ok(AudioBuffer::new(all_samples, stream.format()))
+    }
+
+    /// Open a file for streaming decode, closing any open file
+    pub(crate) fn open(&mut self, path: &Path, open: OpenFrames) -> Result<AudioMetadata> {
+        self.stream = None;
+        let stream = FrameStream::new(Self::open_reader(path, open)?);
+
+        let metadata = AudioMetadata {
+            sample_rate: stream.info.sample_rate,
+            channels: stream.info.channels as u16,
+            duration: Some(stream.info.duration()),
+            bits_per_sample: Some(stream.info.bits_per_sample),
+        };
+        self.stream = Some(stream);
+        Ok(metadata)
+    }
+
+    pub(crate) fn decode_chunk(&mut self, max_frames: usize) -> Result<Option<AudioBuffer>> {
+        let stream = self.stream.as_mut().ok_or(AudioError::NoFileOpen)?;
+        let format = stream.format();
+        Ok(stream
+            .decode_chunk(max_frames)?
+            .map(|samples| AudioBuffer::new(samples, format)))
+    }
+
+    pub(crate) fn seek(&mut self, position: Duration) -> Result<Duration> {
+        self.stream
+            .as_mut()
+            .ok_or(AudioError::NoFileOpen)?
+            .seek(position)
+    }
+
+    pub(crate) fn duration(&self) -> Option<Duration> {
+        self.stream.as_ref().map(|s| s.info.duration())
+    }
+
+    pub(crate) fn position(&self) -> Duration {
+        self.stream
+            .as_ref()
+            .map(|s| {
+                Duration::from_secs_f64(s.position_samples as f64 / f64::from(s.info.sample_rate))
+            })
+            .unwrap_or(Duration::ZERO)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Frames of `FRAME` samples per channel; sample `n` of channel `c` has
+    /// the value `n / 1000 + c`
+    struct Ramp {
+        channels: usize,
+        total: u64,
+        fail_frame: Option<usize>,
+    }
+
+    const FRAME: u64 = 100;
+
+    impl FrameReader for Ramp {
+        fn info(&self) -> StreamInfo {
+            StreamInfo {
+                sample_rate: 1000,
+                channels: self.channels,
+                bits_per_sample: 16,
+                total_samples: self.total,
+            }
+        }
+
+        fn frame_count(&self) -> usize {
+            self.total.div_ceil(FRAME) as usize
+        }
+
+        fn frame_start(&self, index: usize) -> u64 {
+            index as u64 * FRAME
+        }
+
+        fn decode_frame(&mut self, index: usize, output: &mut Vec<f32>) -> Result<()> {
+            if self.fail_frame == Some(index) {
+                return Err(AudioError::DecodeError("broken".into()));
+            }
+            let start = self.frame_start(index);
+            let end = (start + FRAME).min(self.total);
+            for n in start..end {
+                output.extend((0..self.channels).map(|c| n as f32 / 1000.0 + c as f32));
+            }
+            Ok(())
+        }
+    }
+
+    fn stream(channels: usize, total: u64, fail_frame: Option<usize>) -> FrameStream {
+        FrameStream::new(Box::new(Ramp {
+            channels,
+            total,
+            fail_frame,
+        }))
+    }
+
+    fn drain(stream: &mut FrameStream, chunk: usize) -> Vec<f32> {
+        let mut all = Vec::new();
+        while let Some(samples) = stream.decode_chunk(chunk).unwrap() {
+            assert!(samples.len() <= chunk * stream.info.channels);
+            all.extend(samples);
+        }
+        all
+    }
+
+    #[test]
+    fn test_streams_all_frames_in_the_file_layout() {
+        let mut stream = stream(6, 250, None);
+        assert_eq!(stream.format().channels, 6);
+        let samples = drain(&mut stream, 64);
+
+        // 5.1 stays 5.1: downmixing is up to the playback source
+        assert_eq!(samples.len(), 6 * 250);
+        assert_eq!(&samples[..7], &[0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 0.001]);
+        assert_eq!(samples[6 * 249], 0.249);
+        assert_eq!(stream.position_samples, 250);
+    }
+
+    #[test]
+    fn test_seek_is_sample_accurate() {
+        let mut stream = stream(2, 250, None);
+
+        let actual = stream.seek(Duration::from_millis(137)).unwrap();
+        assert_eq!(actual, Duration::from_millis(137));
+        let samples = drain(&mut stream, 50);
+        assert_eq!(samples.len(), 2 * (250 - 137));
+        assert_eq!(samples[0], 0.137);
+
+        // Frame boundaries and the end of the stream
+        stream.seek(Duration::from_millis(200)).unwrap();
+        assert_eq!(stream.decode_chunk(1).unwrap().unwrap()[0], 0.2);
+        let actual = stream.seek(Duration::from_secs(10)).unwrap();
+        assert_eq!(actual, Duration::from_millis(250));
+        assert!(stream.decode_chunk(10).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_damaged_frame_becomes_silence() {
+        let mut stream = stream(2, 250, Some(1));
+        let samples = drain(&mut stream, 1000);
+
+        assert_eq!(samples.len(), 500);
+        assert!(samples[200..400].iter().all(|&s| s == 0.0));
+        assert_eq!(samples[400], 0.2);
+    }
+}
@@ -0,0 +1,82 @@
+//! File formats and the decoder registry
+//!
+//! [`registry()`] is the single place that decides which decoder opens a
+//! file. Besides Symphonia (MP3, FLAC, Ogg Vorbis/Opus, WAV, AIFF, AAC/M4A)
+//! and the DSD reader, it holds decoders for formats Symphonia doesn't
+//! read:
+//!
+//! - **WavPack** (`.wv`): pure Rust, lossless and hybrid
+//! - **Monkey's Audio** (`.ape`): pure Rust, files from version 3.99 on
+//! - **Musepack** (`.mpc`): SV7 and SV8 through libmpcdec, with the
+//!   `musepack` feature
+//!
+//! The importer, artwork extraction and playback all go through the same
+//! registry, so every format it lists is playable, taggable and scannable.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use soul_audio::formats;
+//! use std::path::Path;
+//!
+//! let path = Path::new("archive/track.ape");
+//! let metadata = formats::registry().extract_metadata(path).unwrap();
+//! println!("{:?} by {:?}", metadata.title, metadata.artist);
+//! ```
+
+pub mod ape;
+mod apetag;
+mod framed;
+#[cfg(feature = "musepack")]
+pub mod musepack;
+mod registry;
+pub mod wavpack;
+
+pub use ape::ApeDecoder;
+pub use registry::{registry, DecoderFormat, DecoderRegistry, SYMPHONIA};
+pub use wavpack::WavPackDecoder;
+
+#[cfg(feature = "musepack")]
+pub use musepack::MusepackDecoder;
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Bytes of the file header handed to the format probes
+const PROBE_LEN: usize = 64;
+
+/// Whether a path has one of the (lowercase) extensions
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|ext| extensions.iter().any(|e| ext.eq_ignore_ascii_case(e)))
+}
+
+/// Skip an ID3v2 tag at the start of a file, returning the offset after it
+fn skip_id3v2(file: &mut File) -> std::io::Result<u64> {
+    let mut header = [0u8; 10];
+    file.seek(SeekFrom::Start(0))?;
+    let read = file.read(&mut header)?;
+    if read < header.len() || &header[..3] != b"ID3" {
+        return Ok(0);
+    }
+
+    // Syncsafe size of the tag body, plus a footer if flagged
+    let size = header[6..10]
+        .iter()
+        .fold(0u64, |size, &b| size << 7 | u64::from(b & 0x7f));
+    let footer = if header[5] & 0x10 != 0 { 10 } else { 0 };
+    Ok(10 + size + footer)
+}
+
+/// First bytes of a file after any ID3v2 tag
+fn read_header(path: &Path) -> std::io::Result<Vec<u8>> {
+    let mut file = File::open(path)?;
+    let offset = skip_id3v2(&mut file)?;
+    file.seek(SeekFrom::Start(offset))?;
+
+    let mut header = Vec::with_capacity(PROBE_LEN);
+    file.take(PROBE_LEN as u64).read_to_end(&mut header)?;
+    Ok(header)
+}
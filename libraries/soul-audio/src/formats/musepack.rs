@@ -0,0 +1,232 @@
+//! Musepack (.mpc) files
+//!
+//! Decoding goes through libmpcdec via `soul-audio-musepack`; stream
+//! properties come from the SV7 or SV8 header and tags from the APEv2 tag,
+//! so scanning doesn't need the library.
+
+use super::apetag::ApeTag;
+use crate::error::{AudioError, Result};
+use crate::metadata::AudioMetadata as FileMetadata;
+use std::path::Path;
+
+pub use soul_audio_musepack::{MusepackDecoder, EXTENSIONS};
+
+/// Samples per channel in one frame
+const FRAME_LENGTH: u64 = 1152;
+
+/// Samples of synthesis filter delay at the start of SV7 streams
+const SV7_DELAY: u64 = 481;
+
+const SAMPLE_RATES: [u32; 4] = [44100, 48000, 37800, 32000];
+
+/// Whether a file header is Musepack SV7 or SV8
+pub(crate) fn probe(header: &[u8]) -> bool {
+    header.starts_with(b"MPCK")
+        || (header.starts_with(b"MP+") && header.len() > 3 && header[3] & 0x0f == 7)
+}
+
+/// Stream properties from a Musepack header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct StreamHeader {
+    sample_rate: u32,
+    channels: u8,
+    /// Samples per channel, without the encoder delay
+    samples: u64,
+}
+
+fn le32(data: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]])
+}
+
+/// SV7: a fixed 28-byte header after "MP+"
+fn parse_sv7(data: &[u8]) -> Option<StreamHeader> {
+    if data.len() < 24 {
+        return None;
+    }
+    let frames = u64::from(le32(data, 4));
+    let flags = le32(data, 8);
+    let gapless = le32(data, 20);
+
+    let mut samples = frames * FRAME_LENGTH;
+    if gapless & 0x8000_0000 != 0 {
+        let last_frame = u64::from(gapless >> 20 & 0x7ff);
+        samples = samples.saturating_sub(FRAME_LENGTH - last_frame);
+    }
+
+    Some(StreamHeader {
+        sample_rate: SAMPLE_RATES[(flags >> 16 & 0x3) as usize],
+        channels: 2,
+        samples: samples.saturating_sub(SV7_DELAY),
+    })
+}
+
+/// Variable-length size of SV8: 7 bits per byte, high bit set on all but
+/// the last byte
+fn read_varint(data: &[u8], at: &mut usize) -> Option<u64> {
+    let mut value = 0u64;
+    for _ in 0..9 {
+        let byte = *data.get(*at)?;
+        *at += 1;
+        value = value << 7 | u64::from(byte & 0x7f);
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// SV8: packets after "MPCK"; the stream header packet is "SH"
+fn parse_sv8(data: &[u8]) -> Option<StreamHeader> {
+    let mut at = 4;
+    while at + 2 < data.len() {
+        let key = &data[at..at + 2];
+        let start = at;
+        at += 2;
+        let size = read_varint(data, &mut at)? as usize;
+        if size < at - start {
+            return None;
+        }
+
+        match key {
+            b"SH" => {
+                // CRC and stream version precede the sample counts
+                let mut field = at + 5;
+                let samples = read_varint(data, &mut field)?;
+                let silence = read_varint(data, &mut field)?;
+                let rate = *data.get(field)?;
+                let channels = *data.get(field + 1)?;
+                return Some(StreamHeader {
+                    sample_rate: *SAMPLE_RATES.get(usize::from(rate >> 5))?,
+                    channels: (channels >> 4) + 1,
+                    samples: samples.saturating_sub(silence),
+                });
+            }
+            b"AP" | b"SE" => return None,
+            _ => at = start + size,
+        }
+    }
+    None
+}
+
+fn parse_header(header: &[u8]) -> Option<StreamHeader> {
+    if header.starts_with(b"MPCK") {
+        parse_sv8(header)
+    } else if probe(header) {
+        parse_sv7(header)
+    } else {
+        None
+    }
+}
+
+/// Read stream properties and the APEv2 tag of a Musepack file
+pub fn extract_metadata(path: &Path) -> Result<FileMetadata> {
+    let mut file = std::fs::File::open(path)?;
+    let offset = super::skip_id3v2(&mut file)?;
+
+    use std::io::{Read, Seek, SeekFrom};
+    let mut header = Vec::new();
+    file.seek(SeekFrom::Start(offset))?;
+    (&mut file).take(4096).read_to_end(&mut header)?;
+    let stream = parse_header(&header).ok_or_else(|| {
+        AudioError::UnsupportedFormat(format!("{}: not a Musepack file", path.display()))
+    })?;
+
+    let seconds = stream.samples as f64 / f64::from(stream.sample_rate);
+    let mut metadata = FileMetadata {
+        duration_seconds: Some(seconds),
+        sample_rate: Some(stream.sample_rate),
+        channels: Some(stream.channels),
+        ..Default::default()
+    };
+    if seconds > 0.0 {
+        let len = file.metadata()?.len();
+        metadata.bitrate = Some((len as f64 * 8.0 / seconds / 1000.0) as u32);
+    }
+    if let Some(tag) = ApeTag::read(path)? {
+        tag.apply(&mut metadata);
+    }
+    Ok(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sv8_header(samples: u64, silence: u64) -> Vec<u8> {
+        let mut body = vec![0, 0, 0, 0, 8];
+        for value in [samples, silence] {
+            let mut groups = vec![(value & 0x7f) as u8];
+            let mut rest = value >> 7;
+            while rest > 0 {
+                groups.push((rest & 0x7f) as u8 | 0x80);
+                rest >>= 7;
+            }
+            body.extend(groups.iter().rev());
+        }
+        // 48 kHz, max band 31; stereo, 2^2 frames per block
+        body.extend_from_slice(&[1 << 5 | 31, 1 << 4 | 2]);
+
+        let mut data = b"MPCK".to_vec();
+        data.extend_from_slice(b"SH");
+        data.push((body.len() + 3) as u8);
+        data.extend(body);
+        data.extend_from_slice(b"AP\x03");
+        data
+    }
+
+    #[test]
+    fn test_probe() {
+        assert!(probe(b"MPCK\x53\x48"));
+        assert!(probe(b"MP+\x17"));
+        assert!(!probe(b"MP+\x04"));
+        assert!(!probe(b"MAC \x9e\x0f"));
+    }
+
+    #[test]
+    fn test_parse_sv8_stream_header() {
+        let header = parse_header(&sv8_header(480_000, 576)).unwrap();
+        assert_eq!(
+            header,
+            StreamHeader {
+                sample_rate: 48000,
+                channels: 2,
+                samples: 480_000 - 576,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_sv7_header() {
+        let mut data = b"MP+\x17".to_vec();
+        data.extend_from_slice(&100u32.to_le_bytes());
+        // 44.1 kHz
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&[0; 8]);
+        // True gapless, 576 samples in the last frame
+        data.extend_from_slice(&(0x8000_0000u32 | 576 << 20).to_le_bytes());
+        data.resize(28, 0);
+
+        let header = parse_header(&data).unwrap();
+        assert_eq!(header.sample_rate, 44100);
+        assert_eq!(header.samples, 99 * 1152 + 576 - SV7_DELAY);
+    }
+
+    #[test]
+    fn test_extract_metadata_reads_tag() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("track.mpc");
+        let mut data = sv8_header(48000 * 10, 0);
+        data.resize(4000, 0);
+        data.extend(crate::test_utils::ape::ape_tag(&[(
+            "Title",
+            0,
+            b"Old Song",
+        )]));
+        std::fs::write(&path, data).unwrap();
+
+        let metadata = extract_metadata(&path).unwrap();
+        assert_eq!(metadata.title.as_deref(), Some("Old Song"));
+        assert_eq!(metadata.sample_rate, Some(48000));
+        assert!((metadata.duration_seconds.unwrap() - 10.0).abs() < 1e-9);
+    }
+}
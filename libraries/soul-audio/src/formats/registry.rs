@@ -0,0 +1,299 @@
+//! Decoder registry
+//!
+//! An ordered list of the file formats soul-audio can open. Each entry
+//! knows its extensions, how to recognise its file header and how to create
+//! a decoder and read metadata. Lookups probe the file header first, so a
+//! mislabelled file still gets the right decoder, and fall back to the
+//! extension for files whose header is ambiguous.
+
+use super::{ape, has_extension, read_header, wavpack};
+use crate::decoder::SymphoniaDecoder;
+use crate::error::{AudioError, Result};
+use crate::metadata::{self, AudioMetadata as FileMetadata};
+use soul_core::AudioDecoder;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Name of the Symphonia entry (MP3, FLAC, Ogg, WAV, AAC/M4A, Opus, DSD)
+pub const SYMPHONIA: &str = "symphonia";
+
+/// Extensions of the Symphonia entry (Opus needs the `opus` feature)
+#[cfg(feature = "opus")]
+const SYMPHONIA_EXTENSIONS: &[&str] = &[
+    "mp3", "flac", "ogg", "opus", "wav", "aif", "aiff", "m4a", "aac", "dsf", "dff",
+];
+#[cfg(not(feature = "opus"))]
+const SYMPHONIA_EXTENSIONS: &[&str] = &[
+    "mp3", "flac", "ogg", "wav", "aif", "aiff", "m4a", "aac", "dsf", "dff",
+];
+
+/// A file format the registry can decode
+#[derive(Clone, Copy)]
+pub struct DecoderFormat {
+    /// Short name of the format
+    pub name: &'static str,
+    /// File extensions (lowercase, without the dot)
+    pub extensions: &'static [&'static str],
+    /// Whether the start of a file (after any ID3v2 tag) is this format
+    pub probe: fn(&[u8]) -> bool,
+    /// Create a decoder for the format
+    pub create: fn() -> Box<dyn AudioDecoder>,
+    /// Read tags and stream properties
+    pub metadata: fn(&Path) -> Result<FileMetadata>,
+}
+
+impl std::fmt::Debug for DecoderFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DecoderFormat")
+            .field("name", &self.name)
+            .field("extensions", &self.extensions)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Ordered list of decodable formats
+///
+/// # Example
+///
+/// ```no_run
+/// use soul_audio::formats;
+/// use std::path::Path;
+///
+/// let path = Path::new("album/01 - Intro.wv");
+/// if formats::registry().supports_path(path) {
+///     let mut decoder = formats::registry().decoder_for(path).unwrap();
+///     let info = decoder.open(path).unwrap();
+///     println!("{} Hz, {} channels", info.sample_rate, info.channels);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DecoderRegistry {
+    formats: Vec<DecoderFormat>,
+}
+
+impl DecoderRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry with every format built into this crate
+    ///
+    /// WavPack and Monkey's Audio are always available, Musepack with the
+    /// `musepack` feature; Symphonia handles everything else (`.opus` files
+    /// only with the `opus` feature).
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        registry.register(DecoderFormat {
+            name: "wavpack",
+            extensions: wavpack::EXTENSIONS,
+            probe: wavpack::probe,
+            create: || Box::new(wavpack::WavPackDecoder::new()),
+            metadata: wavpack::extract_metadata,
+        });
+        registry.register(DecoderFormat {
+            name: "ape",
+            extensions: ape::EXTENSIONS,
+            probe: ape::probe,
+            create: || Box::new(ape::ApeDecoder::new()),
+            metadata: ape::extract_metadata,
+        });
+        #[cfg(feature = "musepack")]
+        registry.register(DecoderFormat {
+            name: "musepack",
+            extensions: super::musepack::EXTENSIONS,
+            probe: super::musepack::probe,
+            create: || Box::new(super::musepack::MusepackDecoder::new()),
+            metadata: super::musepack::extract_metadata,
+        });
+        registry.register(DecoderFormat {
+            name: SYMPHONIA,
+            extensions: SYMPHONIA_EXTENSIONS,
+            probe: probe_symphonia,
+            create: || Box::new(SymphoniaDecoder::new()),
+            metadata: metadata::extract_symphonia_metadata,
+        });
+        registry
+    }
+
+    /// Add a format after the registered ones
+    pub fn register(&mut self, format: DecoderFormat) {
+        self.formats.push(format);
+    }
+
+    /// Registered formats, in lookup order
+    pub fn formats(&self) -> &[DecoderFormat] {
+        &self.formats
+    }
+
+    /// Every supported file extension
+    pub fn extensions(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.formats
+            .iter()
+            .flat_map(|f| f.extensions.iter().copied())
+    }
+
+    /// Whether a path has the extension of a supported format
+    ///
+    /// Cheap check for library scans; doesn't touch the file.
+    pub fn supports_path(&self, path: &Path) -> bool {
+        self.find_by_extension(path).is_some()
+    }
+
+    /// Format for a path by extension alone
+    pub fn find_by_extension(&self, path: &Path) -> Option<&DecoderFormat> {
+        self.formats
+            .iter()
+            .find(|format| has_extension(path, format.extensions))
+    }
+
+    /// Format of a file: by its header if one matches, else by extension
+    pub fn find(&self, path: &Path) -> Option<&DecoderFormat> {
+        if let Ok(header) = read_header(path) {
+            if let Some(format) = self.formats.iter().find(|format| (format.probe)(&header)) {
+                return Some(format);
+            }
+        }
+        self.find_by_extension(path)
+    }
+
+    fn find_or_err(&self, path: &Path) -> Result<&DecoderFormat> {
+        if !path.exists() {
+            return Err(AudioError::FileNotFound(path.display().to_string()));
+        }
+        self.find(path)
+            .ok_or_else(|| AudioError::UnsupportedFormat(path.display().to_string()))
+    }
+
+    /// Create a decoder for a file
+    pub fn decoder_for(&self, path: &Path) -> Result<Box<dyn AudioDecoder>> {
+        Ok((self.find_or_err(path)?.create)())
+    }
+
+    /// Read tags and stream properties of a file
+    ///
+    /// Files without a title tag get their file name as title.
+    pub fn extract_metadata(&self, path: &Path) -> Result<FileMetadata> {
+        let mut metadata = (self.find_or_err(path)?.metadata)(path)?;
+        if metadata.title.is_none() {
+            metadata.title = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .map(|s| s.to_string());
+        }
+        Ok(metadata)
+    }
+}
+
+/// The registry with the default formats, shared by the whole process
+pub fn registry() -> &'static DecoderRegistry {
+    static REGISTRY: OnceLock<DecoderRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(DecoderRegistry::with_defaults)
+}
+
+/// Headers of the containers Symphonia reads
+fn probe_symphonia(header: &[u8]) -> bool {
+    let at = |offset: usize, magic: &[u8]| header.get(offset..offset + magic.len()) == Some(magic);
+
+    at(0, b"fLaC")
+        || at(0, b"OggS")
+        || (at(0, b"RIFF") && at(8, b"WAVE"))
+        || (at(0, b"FORM") && (at(8, b"AIFF") || at(8, b"AIFC")))
+        || at(4, b"ftyp")
+        || at(0, b"DSD ")
+        || at(0, b"FRM8")
+        // MPEG audio / ADTS frame sync
+        || (header.len() >= 2 && header[0] == 0xff && header[1] & 0xe0 == 0xe0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn write(dir: &Path, name: &str, data: &[u8]) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, data).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_default_formats() {
+        let registry = DecoderRegistry::with_defaults();
+        let names: Vec<_> = registry.formats().iter().map(|f| f.name).collect();
+
+        assert_eq!(names.first(), Some(&"wavpack"));
+        assert_eq!(names.last(), Some(&SYMPHONIA));
+        for ext in ["wv", "ape", "flac", "mp3", "dsf"] {
+            assert!(registry.extensions().any(|e| e == ext), "{ext}");
+        }
+        assert!(registry.supports_path(Path::new("/music/a.WV")));
+        assert!(registry.supports_path(Path::new("/music/b.ape")));
+        assert!(!registry.supports_path(Path::new("/music/cover.jpg")));
+        assert!(!registry.supports_path(Path::new("/music/noext")));
+    }
+
+    #[test]
+    fn test_find_prefers_header_over_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = DecoderRegistry::with_defaults();
+
+        // Monkey's Audio data in a file named .flac
+        let mut ape = b"MAC ".to_vec();
+        ape.extend_from_slice(&3990u16.to_le_bytes());
+        ape.resize(64, 0);
+        let path = write(dir.path(), "mislabelled.flac", &ape);
+        assert_eq!(registry.find(&path).unwrap().name, "ape");
+
+        // FLAC behind an ID3v2 tag
+        let mut flac = b"ID3\x04\x00\x00\x00\x00\x00\x05".to_vec();
+        flac.extend_from_slice(&[0; 5]);
+        flac.extend_from_slice(b"fLaC");
+        let path = write(dir.path(), "tagged.bin", &flac);
+        assert_eq!(registry.find(&path).unwrap().name, SYMPHONIA);
+
+        // Unknown header: the extension decides
+        let path = write(dir.path(), "odd.wv", b"????");
+        assert_eq!(registry.find(&path).unwrap().name, "wavpack");
+        let path = write(dir.path(), "odd.xyz", b"????");
+        assert!(registry.find(&path).is_none());
+    }
+
+    #[test]
+    fn test_missing_and_unknown_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = DecoderRegistry::with_defaults();
+
+        let missing = dir.path().join("missing.wv");
+        assert!(matches!(
+            registry.decoder_for(&missing),
+            Err(AudioError::FileNotFound(_))
+        ));
+
+        let unknown = write(dir.path(), "notes.txt", b"hello");
+        assert!(matches!(
+            registry.extract_metadata(&unknown),
+            Err(AudioError::UnsupportedFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_custom_format() {
+        let mut registry = DecoderRegistry::new();
+        registry.register(DecoderFormat {
+            name: "custom",
+            extensions: &["cst"],
+            probe: |header| header.starts_with(b"CUST"),
+            create: || Box::new(SymphoniaDecoder::new()),
+            metadata: |_| Ok(FileMetadata::default()),
+        });
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = write(dir.path(), "song.bin", b"CUST data");
+        assert_eq!(registry.find(&path).unwrap().name, "custom");
+        assert_eq!(
+            registry.extract_metadata(&path).unwrap().title.as_deref(),
+            Some("song")
+        );
+    }
+}
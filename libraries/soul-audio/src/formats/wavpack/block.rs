@@ -0,0 +1,929 @@
+//! WavPack blocks
+//!
+//! Every block carries one or two channels of the same stretch of samples
+//! and is decoded independently: a 32-byte header is followed by metadata
+//! sub-blocks holding the decorrelation terms and weights, the entropy
+//! coder state and finally the residual bitstream.
+
+use super::words::{exp2, BitReader, Words};
+use crate::error::{AudioError, Result};
+
+/// Size of the block header
+pub(crate) const HEADER_SIZE: usize = 32;
+
+/// Oldest and newest stream versions we decode
+pub(crate) const MIN_VERSION: u16 = 0x402;
+pub(crate) const MAX_VERSION: u16 = 0x410;
+
+// Header flags
+pub(crate) const BYTES_STORED: u32 = 0x3;
+pub(crate) const MONO_FLAG: u32 = 0x4;
+pub(crate) const HYBRID_FLAG: u32 = 0x8;
+pub(crate) const JOINT_STEREO: u32 = 0x10;
+pub(crate) const CROSS_DECORR: u32 = 0x20;
+pub(crate) const FLOAT_DATA: u32 = 0x80;
+pub(crate) const INT32_DATA: u32 = 0x100;
+pub(crate) const HYBRID_BITRATE: u32 = 0x200;
+pub(crate) const INITIAL_BLOCK: u32 = 0x800;
+pub(crate) const FINAL_BLOCK: u32 = 0x1000;
+pub(crate) const SHIFT_LSB: u32 = 13;
+pub(crate) const MAG_LSB: u32 = 18;
+pub(crate) const SRATE_LSB: u32 = 23;
+pub(crate) const FALSE_STEREO: u32 = 0x4000_0000;
+pub(crate) const DSD_FLAG: u32 = 0x8000_0000;
+
+// Metadata sub-block ids
+pub(crate) const ID_DECORR_TERMS: u8 = 0x2;
+pub(crate) const ID_DECORR_WEIGHTS: u8 = 0x3;
+pub(crate) const ID_DECORR_SAMPLES: u8 = 0x4;
+pub(crate) const ID_ENTROPY_VARS: u8 = 0x5;
+pub(crate) const ID_HYBRID_PROFILE: u8 = 0x6;
+pub(crate) const ID_FLOAT_INFO: u8 = 0x8;
+pub(crate) const ID_INT32_INFO: u8 = 0x9;
+pub(crate) const ID_WV_BITSTREAM: u8 = 0xa;
+pub(crate) const ID_WVX_BITSTREAM: u8 = 0xc;
+pub(crate) const ID_CHANNEL_INFO: u8 = 0xd;
+pub(crate) const ID_SAMPLE_RATE: u8 = 0x27;
+pub(crate) const ID_UNIQUE: u8 = 0x3f;
+pub(crate) const ID_ODD_SIZE: u8 = 0x40;
+pub(crate) const ID_LARGE: u8 = 0x80;
+
+// Float flags
+const FLOAT_SHIFT_ONES: u8 = 0x1;
+const FLOAT_SHIFT_SAME: u8 = 0x2;
+const FLOAT_SHIFT_SENT: u8 = 0x4;
+const FLOAT_ZEROS_SENT: u8 = 0x8;
+const FLOAT_NEG_ZEROS: u8 = 0x10;
+
+/// Sample rates selected by the header's rate index (15 = custom)
+pub(crate) const SAMPLE_RATES: [u32; 15] = [
+    6000, 8000, 9600, 11025, 12000, 16000, 22050, 24000, 32000, 44100, 48000, 64000, 88200, 96000,
+    192000,
+];
+
+/// Most decorrelation passes a block may use
+const MAX_TERMS: usize = 16;
+
+/// Fixed part of every block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct BlockHeader {
+    /// Bytes following the size field (block length - 8)
+    pub block_size: u32,
+    /// Stream version
+    pub version: u16,
+    /// Length of the whole file in samples, if known
+    pub total_samples: Option<u64>,
+    /// First sample of this block
+    pub block_index: u64,
+    /// Samples per channel in this block (0 for metadata-only blocks)
+    pub block_samples: u32,
+    pub flags: u32,
+    /// Checksum of the decoded samples
+    pub crc: u32,
+}
+
+impl BlockHeader {
+    /// Parse a block header; `None` if `data` doesn't start with one
+    pub(crate) fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() < HEADER_SIZE || &data[..4] != b"wvpk" {
+            return None;
+        }
+        let le32 =
+            |at: usize| u32::from_le_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]]);
+
+        let block_size = le32(4);
+        let version = u16::from_le_bytes([data[8], data[9]]);
+        if !(MIN_VERSION..=MAX_VERSION).contains(&version)
+            || (block_size as usize) < HEADER_SIZE - 8
+        {
+            return None;
+        }
+
+        // 40-bit counts: the upper byte counts in units of 2^32 - 1 so that
+        // an all-ones lower word stays free to mean "unknown"
+        let index_u8 = u64::from(data[10]);
+        let total_u8 = u64::from(data[11]);
+        let total_samples = match le32(12) {
+            u32::MAX => None,
+            total => Some((total_u8 << 32) - total_u8 + u64::from(total)),
+        };
+
+        Some(Self {
+            block_size,
+            version,
+            total_samples,
+            block_index: (index_u8 << 32) + u64::from(le32(16)),
+            block_samples: le32(20),
+            flags: le32(24),
+            crc: le32(28),
+        })
+    }
+
+    /// Size of the block including the header
+    pub(crate) fn len(&self) -> usize {
+        self.block_size as usize + 8
+    }
+
+    /// Bits per sample of the source
+    pub(crate) fn bits_per_sample(&self) -> u32 {
+        ((self.flags & BYTES_STORED) + 1) * 8
+    }
+
+    /// Channels decoded from this block
+    pub(crate) fn channels(&self) -> usize {
+        if self.flags & MONO_FLAG != 0 {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// Sample rate from the rate index (`None` for a custom rate)
+    pub(crate) fn sample_rate(&self) -> Option<u32> {
+        SAMPLE_RATES
+            .get(((self.flags >> SRATE_LSB) & 0xf) as usize)
+            .copied()
+    }
+}
+
+/// Iterates over the metadata sub-blocks of a block payload
+pub(crate) struct SubBlocks<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> SubBlocks<'a> {
+    pub(crate) fn new(payload: &'a [u8]) -> Self {
+        Self { data: payload }
+    }
+}
+
+impl<'a> Iterator for SubBlocks<'a> {
+    /// Sub-block id (without the size flags) and contents
+    type Item = Result<(u8, &'a [u8])>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.len() < 2 {
+            return None;
+        }
+
+        let id = self.data[0];
+        let (words, header) = if id & ID_LARGE != 0 {
+            if self.data.len() < 4 {
+                self.data = &[];
+                return Some(Err(invalid("truncated sub-block")));
+            }
+            let words = usize::from(self.data[1])
+                | usize::from(self.data[2]) << 8
+                | usize::from(self.data[3]) << 16;
+            (words, 4)
+        } else {
+            (usize::from(self.data[1]), 2)
+        };
+
+        let padded = words * 2;
+        if self.data.len() < header + padded {
+            self.data = &[];
+            return Some(Err(invalid("truncated sub-block")));
+        }
+        let size = if id & ID_ODD_SIZE != 0 && padded > 0 {
+            padded - 1
+        } else {
+            padded
+        };
+
+        let contents = &self.data[header..header + size];
+        self.data = &self.data[header + padded..];
+        Some(Ok((id & ID_UNIQUE, contents)))
+    }
+}
+
+fn invalid(message: &str) -> AudioError {
+    AudioError::DecodeError(format!("wavpack: {}", message))
+}
+
+fn le16(data: &[u8], at: usize) -> i32 {
+    i32::from(i16::from_le_bytes([data[at], data[at + 1]]))
+}
+
+/// Weight stored as a signed byte, restored to the 1024 = 1.0 scale
+pub(crate) fn restore_weight(stored: i8) -> i32 {
+    let weight = i32::from(stored) * 8;
+    if weight > 0 {
+        weight + ((weight + 64) >> 7)
+    } else {
+        weight
+    }
+}
+
+/// `(weight * sample) / 1024`, rounded
+fn apply_weight(weight: i32, sample: i32) -> i32 {
+    ((i64::from(weight) * i64::from(sample) + 512) >> 10) as i32
+}
+
+/// Adapt a weight towards the correlation of `sample` and `input`
+fn update_weight(weight: &mut i32, delta: i32, sample: i32, input: i32) {
+    if sample != 0 && input != 0 {
+        *weight -= ((((sample ^ input) >> 30) & 2) - 1) * delta;
+    }
+}
+
+/// [`update_weight`] for the cross-channel terms, limited to ±1.0
+fn update_weight_clip(weight: &mut i32, delta: i32, sample: i32, input: i32) {
+    if sample != 0 && input != 0 {
+        if (sample ^ input) < 0 {
+            *weight = (*weight - delta).max(-1024);
+        } else {
+            *weight = (*weight + delta).min(1024);
+        }
+    }
+}
+
+/// One decorrelation pass: an adaptive predictor added to the residual
+///
+/// Terms 1-8 predict from the sample `term` steps back, 17 and 18
+/// extrapolate from the last two, and -1/-2/-3 (stereo only) predict each
+/// channel from the other.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct DecorrPass {
+    pub term: i32,
+    pub delta: i32,
+    pub weight_a: i32,
+    pub weight_b: i32,
+    pub samples_a: [i32; 8],
+    pub samples_b: [i32; 8],
+}
+
+impl DecorrPass {
+    /// History sample a positive term predicts from, and the slot the
+    /// output goes to
+    fn history(samples: &[i32; 8], term: i32, position: usize) -> (i32, usize) {
+        if term > 8 {
+            let prediction = if term & 1 == 1 {
+                samples[0].wrapping_mul(2).wrapping_sub(samples[1])
+            } else {
+                samples[0].wrapping_mul(3).wrapping_sub(samples[1]) >> 1
+            };
+            (prediction, 0)
+        } else {
+            (samples[position], (position + term as usize) & 7)
+        }
+    }
+
+    /// Predictions for the next stereo sample, given this pass's outputs
+    ///
+    /// The decoder adds these to its inputs; cross-channel terms depend on
+    /// the other channel's output of the same sample, which is why the
+    /// outputs are needed.
+    pub(crate) fn predict_stereo(&self, position: usize, output: (i32, i32)) -> (i32, i32) {
+        match self.term {
+            -1 => (
+                apply_weight(self.weight_a, self.samples_a[0]),
+                apply_weight(self.weight_b, output.0),
+            ),
+            -2 => (
+                apply_weight(self.weight_a, output.1),
+                apply_weight(self.weight_b, self.samples_b[0]),
+            ),
+            -3 => (
+                apply_weight(self.weight_a, self.samples_a[0]),
+                apply_weight(self.weight_b, self.samples_b[0]),
+            ),
+            term => (
+                apply_weight(
+                    self.weight_a,
+                    Self::history(&self.samples_a, term, position).0,
+                ),
+                apply_weight(
+                    self.weight_b,
+                    Self::history(&self.samples_b, term, position).0,
+                ),
+            ),
+        }
+    }
+
+    /// Prediction for the next mono sample
+    pub(crate) fn predict_mono(&self, position: usize) -> i32 {
+        apply_weight(
+            self.weight_a,
+            Self::history(&self.samples_a, self.term, position).0,
+        )
+    }
+
+    /// Run the pass on one stereo sample
+    pub(crate) fn unpack_stereo(&mut self, position: usize, left: i32, right: i32) -> (i32, i32) {
+        let delta = self.delta;
+        match self.term {
+            -1 => {
+                let l = left.wrapping_add(apply_weight(self.weight_a, self.samples_a[0]));
+                update_weight_clip(&mut self.weight_a, delta, self.samples_a[0], left);
+                let r = right.wrapping_add(apply_weight(self.weight_b, l));
+                update_weight_clip(&mut self.weight_b, delta, l, right);
+                self.samples_a[0] = r;
+                (l, r)
+            }
+            term if term < 0 => {
+                let r = right.wrapping_add(apply_weight(self.weight_b, self.samples_b[0]));
+                update_weight_clip(&mut self.weight_b, delta, self.samples_b[0], right);
+                let source = if term == -3 {
+                    std::mem::replace(&mut self.samples_a[0], r)
+                } else {
+                    r
+                };
+                let l = left.wrapping_add(apply_weight(self.weight_a, source));
+                update_weight_clip(&mut self.weight_a, delta, source, left);
+                self.samples_b[0] = l;
+                (l, r)
+            }
+            term => {
+                let (a, slot) = Self::history(&self.samples_a, term, position);
+                let (b, _) = Self::history(&self.samples_b, term, position);
+                if term > 8 {
+                    self.samples_a[1] = self.samples_a[0];
+                    self.samples_b[1] = self.samples_b[0];
+                }
+
+                let l = left.wrapping_add(apply_weight(self.weight_a, a));
+                let r = right.wrapping_add(apply_weight(self.weight_b, b));
+                update_weight(&mut self.weight_a, delta, a, left);
+                update_weight(&mut self.weight_b, delta, b, right);
+                self.samples_a[slot] = l;
+                self.samples_b[slot] = r;
+                (l, r)
+            }
+        }
+    }
+
+    /// Run the pass on one mono sample
+    pub(crate) fn unpack_mono(&mut self, position: usize, input: i32) -> i32 {
+        let (a, slot) = Self::history(&self.samples_a, self.term, position);
+        if self.term > 8 {
+            self.samples_a[1] = self.samples_a[0];
+        }
+
+        let output = input.wrapping_add(apply_weight(self.weight_a, a));
+        update_weight(&mut self.weight_a, self.delta, a, input);
+        self.samples_a[slot] = output;
+        output
+    }
+}
+
+/// Restoration of 32-bit integer samples (`ID_INT32_INFO`)
+#[derive(Debug, Clone, Copy, Default)]
+struct Int32Info {
+    /// Low bits sent in the extra-bits stream
+    extra_bits: u32,
+    /// Bits shifted out before coding
+    shift: u32,
+    /// Shifted-out bits were all ones (`and` + `or`) or copies of bit 0 (`and`)
+    and: i32,
+    or: i32,
+}
+
+/// Restoration of float samples (`ID_FLOAT_INFO`)
+#[derive(Debug, Clone, Copy, Default)]
+struct FloatInfo {
+    flags: u8,
+    shift: u32,
+    max_exponent: i32,
+}
+
+/// Extra-bits stream (`ID_WVX_BITSTREAM`) of a lossless 32-bit or float block
+struct ExtraBits<'a> {
+    bits: BitReader<'a>,
+    crc: u32,
+    expected_crc: u32,
+}
+
+impl ExtraBits<'_> {
+    /// Read `count` bits, or 0 past the end
+    fn read(&mut self, count: u32) -> u32 {
+        self.bits.read_bits(count).unwrap_or(0)
+    }
+}
+
+/// Converts decoded integers to output samples
+struct Output<'a> {
+    bits_per_sample: u32,
+    hybrid: bool,
+    int32: Int32Info,
+    float: Option<FloatInfo>,
+    extra: Option<ExtraBits<'a>>,
+    /// Shift applied after clipping
+    post_shift: u32,
+    min_clip: i64,
+    max_clip: i64,
+}
+
+impl Output<'_> {
+    fn sample(&mut self, value: i32) -> f32 {
+        if let Some(float) = self.float {
+            return self.float_sample(float, value);
+        }
+
+        let mut value = i64::from(value);
+        if self.int32.extra_bits > 0 {
+            value <<= self.int32.extra_bits;
+            if let Some(extra) = &mut self.extra {
+                if extra.bits.remaining() >= self.int32.extra_bits as usize {
+                    value |= i64::from(extra.read(self.int32.extra_bits));
+                    let low = value as u32;
+                    extra.crc = extra
+                        .crc
+                        .wrapping_mul(9)
+                        .wrapping_add((low & 0xffff).wrapping_mul(3))
+                        .wrapping_add(low >> 16);
+                }
+            }
+        }
+
+        let bit = (value & i64::from(self.int32.and)) | i64::from(self.int32.or);
+        let mut value = ((value + bit) << self.int32.shift) - bit;
+        if self.hybrid {
+            value = value.clamp(self.min_clip, self.max_clip);
+        }
+
+        let scale = 1i64 << (self.bits_per_sample - 1);
+        ((value << self.post_shift) as f64 / scale as f64) as f32
+    }
+
+    fn float_sample(&mut self, float: FloatInfo, value: i32) -> f32 {
+        let mut magnitude = value as u32;
+        let mut sign = 0;
+        let mut exponent = float.max_exponent;
+
+        if magnitude != 0 {
+            magnitude = magnitude.wrapping_shl(float.shift);
+            sign = magnitude >> 31;
+            if sign != 0 {
+                magnitude = magnitude.wrapping_neg();
+            }
+
+            if magnitude >= 0x0100_0000 {
+                magnitude = self.extra.as_mut().map_or(0, |extra| {
+                    if extra.read(1) == 1 {
+                        extra.read(23)
+                    } else {
+                        0
+                    }
+                });
+                exponent = 255;
+            } else if exponent != 0 {
+                let mut shift = 23 - (31 - magnitude.leading_zeros() as i32);
+                if exponent <= shift {
+                    exponent -= 1;
+                    shift = exponent;
+                }
+                exponent -= shift;
+
+                if shift > 0 {
+                    let shift = shift as u32;
+                    magnitude <<= shift;
+                    let ones = match &mut self.extra {
+                        _ if float.flags & FLOAT_SHIFT_ONES != 0 => true,
+                        Some(extra) if float.flags & FLOAT_SHIFT_SAME != 0 => extra.read(1) == 1,
+                        _ => false,
+                    };
+                    if ones {
+                        magnitude |= (1 << shift) - 1;
+                    } else if let Some(extra) = &mut self.extra {
+                        if float.flags & FLOAT_SHIFT_SENT != 0 {
+                            magnitude |= extra.read(shift);
+                        }
+                    }
+                }
+            }
+            magnitude &= 0x7f_ffff;
+        } else {
+            exponent = 0;
+            if let Some(extra) = &mut self.extra {
+                if float.flags & FLOAT_ZEROS_SENT != 0 {
+                    if extra.read(1) == 1 {
+                        magnitude = extra.read(23);
+                        if float.max_exponent >= 25 {
+                            exponent = extra.read(8) as i32;
+                        }
+                        sign = extra.read(1);
+                    } else if float.flags & FLOAT_NEG_ZEROS != 0 {
+                        sign = extra.read(1);
+                    }
+                }
+            }
+        }
+
+        if let Some(extra) = &mut self.extra {
+            extra.crc = extra
+                .crc
+                .wrapping_mul(27)
+                .wrapping_add(magnitude.wrapping_mul(9))
+                .wrapping_add((exponent as u32).wrapping_mul(3))
+                .wrapping_add(sign);
+        }
+
+        f32::from_bits(sign << 31 | ((exponent as u32 & 0xff) << 23) | magnitude)
+    }
+}
+
+/// Decode the samples of one block, appended to `output` interleaved
+/// (one or two channels, see [`BlockHeader::channels`])
+pub(crate) fn decode_block(
+    header: &BlockHeader,
+    payload: &[u8],
+    output: &mut Vec<f32>,
+) -> Result<()> {
+    let flags = header.flags;
+    if flags & DSD_FLAG != 0 {
+        return Err(AudioError::UnsupportedFormat(
+            "wavpack: DSD audio is not supported".into(),
+        ));
+    }
+    if header.block_samples == 0 {
+        return Ok(());
+    }
+
+    let stereo = flags & MONO_FLAG == 0;
+    let stereo_in = stereo && flags & FALSE_STEREO == 0;
+    let hybrid = flags & HYBRID_FLAG != 0;
+
+    let mut passes: Vec<DecorrPass> = Vec::new();
+    let mut words = Words {
+        stereo: stereo_in,
+        hybrid,
+        hybrid_bitrate: flags & HYBRID_BITRATE != 0,
+        ..Words::default()
+    };
+    let mut int32 = Int32Info::default();
+    let mut float = None;
+    let mut bitstream = None;
+    let mut extra = None;
+    let mut got_entropy = false;
+    let mut got_hybrid = false;
+
+    for sub_block in SubBlocks::new(payload) {
+        let (id, data) = sub_block?;
+        match id {
+            ID_DECORR_TERMS => {
+                if data.len() > MAX_TERMS {
+                    return Err(invalid("too many decorrelation terms"));
+                }
+                passes = data
+                    .iter()
+                    .rev()
+                    .map(|&byte| {
+                        let term = i32::from(byte & 0x1f) - 5;
+                        DecorrPass {
+                            term,
+                            delta: i32::from(byte >> 5),
+                            ..DecorrPass::default()
+                        }
+                    })
+                    .collect();
+                let valid = |term: i32| match term {
+                    1..=8 | 17 | 18 => true,
+                    -3..=-1 => stereo_in,
+                    _ => false,
+                };
+                if !passes.iter().all(|p| valid(p.term)) {
+                    return Err(invalid("invalid decorrelation term"));
+                }
+            }
+            ID_DECORR_WEIGHTS => {
+                let per_term = if stereo_in { 2 } else { 1 };
+                let count = data.len() / per_term;
+                if count > passes.len() {
+                    return Err(invalid("too many decorrelation weights"));
+                }
+                let terms = passes.len();
+                for (i, weights) in data.chunks_exact(per_term).enumerate() {
+                    let pass = &mut passes[terms - 1 - i];
+                    pass.weight_a = restore_weight(weights[0] as i8);
+                    if stereo_in {
+                        pass.weight_b = restore_weight(weights[1] as i8);
+                    }
+                }
+            }
+            ID_DECORR_SAMPLES => {
+                let mut at = 0;
+                let take = |at: &mut usize| -> Result<i32> {
+                    if *at + 2 > data.len() {
+                        return Err(invalid("truncated decorrelation samples"));
+                    }
+                    *at += 2;
+                    Ok(exp2(le16(data, *at - 2)))
+                };
+                for pass in passes.iter_mut().rev() {
+                    if at >= data.len() {
+                        break;
+                    }
+                    if pass.term > 8 {
+                        pass.samples_a[0] = take(&mut at)?;
+                        pass.samples_a[1] = take(&mut at)?;
+                        if stereo_in {
+                            pass.samples_b[0] = take(&mut at)?;
+                            pass.samples_b[1] = take(&mut at)?;
+                        }
+                    } else if pass.term < 0 {
+                        pass.samples_a[0] = take(&mut at)?;
+                        pass.samples_b[0] = take(&mut at)?;
+                    } else {
+                        for i in 0..pass.term as usize {
+                            pass.samples_a[i] = take(&mut at)?;
+                            if stereo_in {
+                                pass.samples_b[i] = take(&mut at)?;
+                            }
+                        }
+                    }
+                }
+            }
+            ID_ENTROPY_VARS => {
+                let channels = if stereo_in { 2 } else { 1 };
+                if data.len() != 6 * channels {
+                    return Err(invalid("invalid entropy variables"));
+                }
+                for (channel, medians) in words.channels.iter_mut().zip(data.chunks_exact(6)) {
+                    for (i, median) in channel.median.iter_mut().enumerate() {
+                        *median = exp2(le16(medians, i * 2)) as u32;
+                    }
+                }
+                got_entropy = true;
+            }
+            ID_HYBRID_PROFILE => {
+                let channels = if stereo_in { 2 } else { 1 };
+                let mut at = 0;
+                if words.hybrid_bitrate {
+                    for channel in words.channels.iter_mut().take(channels) {
+                        if at + 2 > data.len() {
+                            return Err(invalid("truncated hybrid profile"));
+                        }
+                        channel.slow_level = exp2(le16(data, at));
+                        at += 2;
+                    }
+                }
+                for channel in words.channels.iter_mut().take(channels) {
+                    if at + 2 > data.len() {
+                        return Err(invalid("truncated hybrid profile"));
+                    }
+                    channel.bitrate_acc = (le16(data, at) as u32 & 0xffff) << 16;
+                    at += 2;
+                }
+                if at < data.len() {
+                    for channel in words.channels.iter_mut().take(channels) {
+                        if at + 2 > data.len() {
+                            return Err(invalid("truncated hybrid profile"));
+                        }
+                        channel.bitrate_delta = exp2(le16(data, at)) as u32;
+                        at += 2;
+                    }
+                }
+                got_hybrid = true;
+            }
+            ID_INT32_INFO => {
+                if data.len() != 4 || data[0] > 30 {
+                    return Err(invalid("invalid integer info"));
+                }
+                if data[0] > 0 {
+                    int32.extra_bits = u32::from(data[0]);
+                } else if data[1] > 0 {
+                    int32.shift = u32::from(data[1]);
+                } else if data[2] > 0 {
+                    int32.and = 1;
+                    int32.or = 1;
+                    int32.shift = u32::from(data[2]);
+                } else if data[3] > 0 {
+                    int32.and = 1;
+                    int32.shift = u32::from(data[3]);
+                }
+                if int32.shift > 31 {
+                    return Err(invalid("invalid integer shift"));
+                }
+            }
+            ID_FLOAT_INFO => {
+                if data.len() != 4 {
+                    return Err(invalid("invalid float info"));
+                }
+                float = Some(FloatInfo {
+                    flags: data[0],
+                    shift: u32::from(data[1]).min(31),
+                    max_exponent: i32::from(data[2]),
+                });
+            }
+            ID_WV_BITSTREAM => bitstream = Some(data),
+            ID_WVX_BITSTREAM if data.len() > 4 => {
+                extra = Some(ExtraBits {
+                    bits: BitReader::new(&data[4..]),
+                    crc: u32::MAX,
+                    expected_crc: u32::from_le_bytes([data[0], data[1], data[2], data[3]]),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    let bitstream = bitstream.ok_or_else(|| invalid("block has no bitstream"))?;
+    if !got_entropy {
+        return Err(invalid("block has no entropy variables"));
+    }
+    if hybrid && !got_hybrid {
+        return Err(invalid("hybrid block has no hybrid profile"));
+    }
+    if float.is_some() && flags & FLOAT_DATA == 0 {
+        float = None;
+    }
+    if flags & INT32_DATA == 0 {
+        int32 = Int32Info::default();
+    }
+
+    let bits_per_sample = header.bits_per_sample();
+    let mut post_shift = (flags >> SHIFT_LSB) & 0x1f;
+    let mut max_clip = (1i64 << (bits_per_sample - 1)) - 1;
+    let mut min_clip = -(1i64 << (bits_per_sample - 1));
+    // Lossy 32-bit audio is restored as 24-bit, like the reference decoder
+    if hybrid && bits_per_sample == 32 && post_shift < 8 && int32.shift > 8 {
+        post_shift += 8;
+        int32.shift -= 8;
+        max_clip >>= 8;
+        min_clip >>= 8;
+    }
+    if post_shift > 31 || bits_per_sample + post_shift > 63 {
+        return Err(invalid("invalid sample shift"));
+    }
+
+    let mut out = Output {
+        bits_per_sample,
+        hybrid,
+        int32,
+        float,
+        extra,
+        post_shift,
+        min_clip,
+        max_clip,
+    };
+
+    let mut bits = BitReader::new(bitstream);
+    let mut crc = u32::MAX;
+    let mut position = 0;
+    output.reserve(header.block_samples as usize * header.channels());
+
+    for _ in 0..header.block_samples {
+        if stereo_in {
+            let mut left = words.read_value(&mut bits, 0)?;
+            let mut right = words.read_value(&mut bits, 1)?;
+            for pass in &mut passes {
+                (left, right) = pass.unpack_stereo(position, left, right);
+            }
+            position = (position + 1) & 7;
+
+            if flags & JOINT_STEREO != 0 {
+                right = right.wrapping_sub(left >> 1);
+                left = left.wrapping_add(right);
+            }
+            crc = crc
+                .wrapping_mul(3)
+                .wrapping_add(left as u32)
+                .wrapping_mul(3)
+                .wrapping_add(right as u32);
+
+            output.push(out.sample(left));
+            output.push(out.sample(right));
+        } else {
+            let mut sample = words.read_value(&mut bits, 0)?;
+            for pass in &mut passes {
+                sample = pass.unpack_mono(position, sample);
+            }
+            position = (position + 1) & 7;
+            crc = crc.wrapping_mul(3).wrapping_add(sample as u32);
+
+            let value = out.sample(sample);
+            output.push(value);
+            if stereo {
+                output.push(value);
+            }
+        }
+    }
+
+    if crc != header.crc {
+        return Err(invalid("checksum mismatch"));
+    }
+    if let Some(extra) = &out.extra {
+        if extra.crc != extra.expected_crc {
+            return Err(invalid("extra bits checksum mismatch"));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_header() {
+        let mut data = b"wvpk".to_vec();
+        data.extend_from_slice(&100u32.to_le_bytes());
+        data.extend_from_slice(&0x410u16.to_le_bytes());
+        data.extend_from_slice(&[0, 1]); // block index / total samples upper bytes
+        data.extend_from_slice(&5u32.to_le_bytes());
+        data.extend_from_slice(&44100u32.to_le_bytes());
+        data.extend_from_slice(&22050u32.to_le_bytes());
+        data.extend_from_slice(&(1 | INITIAL_BLOCK | FINAL_BLOCK | 9 << SRATE_LSB).to_le_bytes());
+        data.extend_from_slice(&0xdead_beefu32.to_le_bytes());
+
+        let header = BlockHeader::parse(&data).unwrap();
+        assert_eq!(header.len(), 108);
+        assert_eq!(header.total_samples, Some((1 << 32) - 1 + 5));
+        assert_eq!(header.block_index, 44100);
+        assert_eq!(header.block_samples, 22050);
+        assert_eq!(header.bits_per_sample(), 16);
+        assert_eq!(header.channels(), 2);
+        assert_eq!(header.sample_rate(), Some(44100));
+        assert_eq!(header.crc, 0xdead_beef);
+
+        // Unknown length, unsupported version, wrong magic
+        data[12..16].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(BlockHeader::parse(&data).unwrap().total_samples, None);
+        data[8] = 0x01;
+        assert!(BlockHeader::parse(&data).is_none());
+        assert!(BlockHeader::parse(b"wvpx").is_none());
+    }
+
+    #[test]
+    fn test_sub_blocks() {
+        let payload = [
+            ID_DECORR_TERMS,
+            1,
+            0x12,
+            0x34, // 2 bytes
+            ID_INT32_INFO | ID_ODD_SIZE,
+            2,
+            1,
+            2,
+            3,
+            0, // 3 bytes, padded
+            ID_WV_BITSTREAM | ID_LARGE,
+            1,
+            0,
+            0,
+            0xab,
+            0xcd,
+        ];
+        let blocks: Vec<_> = SubBlocks::new(&payload).map(|b| b.unwrap()).collect();
+
+        assert_eq!(
+            blocks,
+            vec![
+                (ID_DECORR_TERMS, &[0x12, 0x34][..]),
+                (ID_INT32_INFO, &[1, 2, 3][..]),
+                (ID_WV_BITSTREAM, &[0xab, 0xcd][..]),
+            ]
+        );
+        assert!(SubBlocks::new(&[ID_ENTROPY_VARS, 3, 0])
+            .next()
+            .unwrap()
+            .is_err());
+    }
+
+    #[test]
+    fn test_weights_and_prediction() {
+        assert_eq!(restore_weight(0), 0);
+        assert_eq!(restore_weight(127), 1024);
+        assert_eq!(restore_weight(-128), -1024);
+        assert_eq!(apply_weight(1024, 1000), 1000);
+        assert_eq!(apply_weight(512, -1001), -500);
+
+        // Term 17 extrapolates linearly: 2 * 10 - 8
+        let mut pass = DecorrPass {
+            term: 17,
+            weight_a: 1024,
+            samples_a: [10, 8, 0, 0, 0, 0, 0, 0],
+            ..DecorrPass::default()
+        };
+        assert_eq!(pass.predict_mono(0), 12);
+        assert_eq!(pass.unpack_mono(0, 1), 13);
+        assert_eq!(&pass.samples_a[..2], &[13, 10]);
+    }
+
+    #[test]
+    fn test_float_conversion() {
+        let mut out = Output {
+            bits_per_sample: 32,
+            hybrid: false,
+            int32: Int32Info::default(),
+            float: None,
+            extra: None,
+            post_shift: 0,
+            min_clip: 0,
+            max_clip: 0,
+        };
+        let float = FloatInfo {
+            flags: 0,
+            shift: 0,
+            max_exponent: 127,
+        };
+
+        // With a maximum exponent of 127, 2^23 is 1.0
+        assert_eq!(out.float_sample(float, 1 << 23), 1.0);
+        assert_eq!(out.float_sample(float, -(1 << 22)), -0.5);
+        assert_eq!(out.float_sample(float, 3 << 21), 0.75);
+        assert_eq!(out.float_sample(float, 0), 0.0);
+    }
+}
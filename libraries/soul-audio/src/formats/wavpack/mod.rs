@@ -0,0 +1,542 @@
+//! WavPack (.wv) decoding
+//!
+//! Pure-Rust decoder for WavPack 4 and 5 streams: lossless and hybrid
+//! (lossy) 8-32 bit integer and 32-bit float audio, mono to multichannel.
+//! In hybrid mode only the lossy `.wv` file is read; a `.wvc` correction
+//! file next to it is ignored. DSD audio in WavPack 5 files is not
+//! supported.
+//!
+//! A file is a sequence of blocks. Each block holds one or two channels of
+//! the same samples; a frame of multichannel audio is the run of blocks from
+//! one flagged as initial to one flagged as final.
+
+pub(crate) mod block;
+pub(crate) mod words;
+
+use self::block::{
+    BlockHeader, SubBlocks, FINAL_BLOCK, HEADER_SIZE, ID_CHANNEL_INFO, ID_SAMPLE_RATE,
+    INITIAL_BLOCK,
+};
+use super::apetag::ApeTag;
+use super::framed::{FrameReader, FramedDecoder, StreamInfo};
+use crate::error::{AudioError, Result};
+use crate::metadata::AudioMetadata as FileMetadata;
+use soul_core::{AudioBuffer, AudioDecoder as AudioDecoderTrait, AudioMetadata};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::Duration;
+
+/// File extensions of WavPack files
+pub const EXTENSIONS: &[&str] = &["wv"];
+
+/// Longest stretch searched for the next block after damaged data
+const RESYNC_WINDOW: usize = 1024 * 1024;
+
+/// Whether a file header is a WavPack block
+pub(crate) fn probe(header: &[u8]) -> bool {
+    BlockHeader::parse(header).is_some()
+}
+
+/// Location of one frame in the file
+#[derive(Debug, Clone, Copy)]
+struct FrameEntry {
+    offset: u64,
+    len: usize,
+    start: u64,
+}
+
+/// Frame index and stream parameters of a WavPack file
+struct WavPackReader {
+    file: BufReader<File>,
+    info: StreamInfo,
+    frames: Vec<FrameEntry>,
+    buffer: Vec<u8>,
+    block_output: Vec<f32>,
+}
+
+impl WavPackReader {
+    fn open(path: &Path) -> Result<Self> {
+        let mut file = BufReader::new(File::open(path)?);
+        let len = file.get_ref().metadata()?.len();
+
+        let mut frames: Vec<FrameEntry> = Vec::new();
+        let mut info: Option<StreamInfo> = None;
+        let mut header_total = None;
+        let mut offset = super::skip_id3v2(file.get_mut())?;
+
+        while let Some((block_offset, header)) = find_block(&mut file, offset, len)? {
+            offset = block_offset + header.len() as u64;
+            if header.block_samples == 0 {
+                continue;
+            }
+            if header.flags & INITIAL_BLOCK != 0 || frames.is_empty() {
+                frames.push(FrameEntry {
+                    offset: block_offset,
+                    len: 0,
+                    start: header.block_index,
+                });
+            }
+            let frame = frames.last_mut().expect("frame pushed above");
+            frame.len = (offset - frame.offset) as usize;
+
+            if info.is_none() {
+                header_total = header.total_samples;
+                info = Some(Self::first_frame_info(&mut file, block_offset, &header)?);
+            }
+        }
+
+        let mut info = info.ok_or_else(|| {
+            AudioError::UnsupportedFormat(format!("{}: no WavPack audio", path.display()))
+        })?;
+
+        // Sample numbering starts at the first block (a file cut from a
+        // longer stream may not start at 0)
+        let first = frames[0].start;
+        for frame in &mut frames {
+            frame.start -= first;
+        }
+        info.total_samples = header_total.unwrap_or_else(|| {
+            let last = frames.last().expect("at least one frame");
+            last.start + Self::frame_samples(&mut file, last).unwrap_or(0)
+        });
+
+        Ok(Self {
+            file,
+            info,
+            frames,
+            buffer: Vec::new(),
+            block_output: Vec::new(),
+        })
+    }
+
+    /// Stream parameters from the blocks of the first frame
+    fn first_frame_info(
+        file: &mut BufReader<File>,
+        offset: u64,
+        first: &BlockHeader,
+    ) -> Result<StreamInfo> {
+        let mut sample_rate = first.sample_rate();
+        let mut channels = 0;
+        let mut channel_info = None;
+        let mut offset = offset;
+
+        loop {
+            let mut block = vec![0u8; HEADER_SIZE];
+            file.seek(SeekFrom::Start(offset))?;
+            file.read_exact(&mut block)?;
+            let header = BlockHeader::parse(&block)
+                .ok_or_else(|| AudioError::DecodeError("wavpack: invalid block".into()))?;
+            block.resize(header.len(), 0);
+            file.read_exact(&mut block[HEADER_SIZE..])?;
+
+            for sub_block in SubBlocks::new(&block[HEADER_SIZE..]) {
+                match sub_block? {
+                    (ID_SAMPLE_RATE, data) if data.len() >= 3 && sample_rate.is_none() => {
+                        sample_rate = Some(
+                            u32::from(data[0]) | u32::from(data[1]) << 8 | u32::from(data[2]) << 16,
+                        );
+                    }
+                    (ID_CHANNEL_INFO, data) if !data.is_empty() => {
+                        channel_info = Some(usize::from(data[0]));
+                    }
+                    _ => {}
+                }
+            }
+
+            channels += header.channels();
+            if header.flags & FINAL_BLOCK != 0 || channels >= 64 {
+                break;
+            }
+            offset += header.len() as u64;
+        }
+
+        if let Some(declared) = channel_info {
+            if declared != channels && declared != 0 {
+                return Err(AudioError::DecodeError(format!(
+                    "wavpack: {} channels declared, {} coded",
+                    declared, channels
+                )));
+            }
+        }
+
+        Ok(StreamInfo {
+            sample_rate: sample_rate
+                .filter(|&rate| rate > 0)
+                .ok_or_else(|| AudioError::DecodeError("wavpack: unknown sample rate".into()))?,
+            channels,
+            bits_per_sample: first.bits_per_sample() as u16,
+            total_samples: 0,
+        })
+    }
+
+    /// Samples in a frame, from its first block header
+    fn frame_samples(file: &mut BufReader<File>, frame: &FrameEntry) -> Result<u64> {
+        let mut header = [0u8; HEADER_SIZE];
+        file.seek(SeekFrom::Start(frame.offset))?;
+        file.read_exact(&mut header)?;
+        Ok(BlockHeader::parse(&header).map_or(0, |h| u64::from(h.block_samples)))
+    }
+}
+
+/// Find the next block header at or after `offset`
+fn find_block(
+    file: &mut BufReader<File>,
+    offset: u64,
+    len: u64,
+) -> Result<Option<(u64, BlockHeader)>> {
+    // Blocks normally follow each other directly
+    let mut header = [0u8; HEADER_SIZE];
+    if offset + HEADER_SIZE as u64 <= len {
+        file.seek(SeekFrom::Start(offset))?;
+        file.read_exact(&mut header)?;
+        if let Some(header) = BlockHeader::parse(&header) {
+            if offset + header.len() as u64 <= len {
+                return Ok(Some((offset, header)));
+            }
+        }
+    }
+
+    let mut window = Vec::new();
+    let mut start = offset;
+
+    while start + HEADER_SIZE as u64 <= len && start - offset < RESYNC_WINDOW as u64 {
+        let size = (len - start).min(64 * 1024) as usize;
+        window.resize(size, 0);
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut window)?;
+
+        for i in 0..window.len().saturating_sub(HEADER_SIZE - 1) {
+            if window[i] != b'w' {
+                continue;
+            }
+            if let Some(header) = BlockHeader::parse(&window[i..]) {
+                let at = start + i as u64;
+                if at + header.len() as u64 <= len {
+                    return Ok(Some((at, header)));
+                }
+            }
+        }
+        start += (window.len() - (HEADER_SIZE - 1)).max(1) as u64;
+    }
+
+    Ok(None)
+}
+
+impl FrameReader for WavPackReader {
+    fn info(&self) -> StreamInfo {
+        self.info
+    }
+
+    fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    fn frame_start(&self, index: usize) -> u64 {
+        self.frames[index].start
+    }
+
+    fn decode_frame(&mut self, index: usize, output: &mut Vec<f32>) -> Result<()> {
+        let frame = self.frames[index];
+        self.buffer.resize(frame.len, 0);
+        self.file.seek(SeekFrom::Start(frame.offset))?;
+        self.file.read_exact(&mut self.buffer)?;
+
+        let channels = self.info.channels;
+        let base = output.len();
+        let mut frame_samples = None;
+        let mut channel = 0;
+        let mut data = &self.buffer[..];
+
+        while data.len() >= HEADER_SIZE {
+            let header = BlockHeader::parse(data)
+                .ok_or_else(|| AudioError::DecodeError("wavpack: invalid block".into()))?;
+            let block_len = header.len().min(data.len());
+            let payload = &data[HEADER_SIZE..block_len];
+            data = &data[block_len..];
+            if header.block_samples == 0 {
+                continue;
+            }
+
+            let samples = header.block_samples as usize;
+            if *frame_samples.get_or_insert(samples) != samples
+                || channel + header.channels() > channels
+            {
+                return Err(AudioError::DecodeError(
+                    "wavpack: blocks of a frame don't match".into(),
+                ));
+            }
+            if channel == 0 {
+                output.resize(base + samples * channels, 0.0);
+            }
+
+            self.block_output.clear();
+            block::decode_block(&header, payload, &mut self.block_output)?;
+
+            let block_channels = header.channels();
+            let frames = output[base..].chunks_exact_mut(channels);
+            for (out, decoded) in frames.zip(self.block_output.chunks_exact(block_channels)) {
+                out[channel..channel + block_channels].copy_from_slice(decoded);
+            }
+            channel += block_channels;
+        }
+
+        if channel != channels {
+            return Err(AudioError::DecodeError(
+                "wavpack: frame is missing channels".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn open_frames(path: &Path) -> Result<Box<dyn FrameReader>> {
+    Ok(Box::new(WavPackReader::open(path)?))
+}
+
+/// Read stream properties and the APEv2 tag of a WavPack file
+pub fn extract_metadata(path: &Path) -> Result<FileMetadata> {
+    let reader = WavPackReader::open(path)?;
+    let info = reader.info;
+
+    let mut metadata = FileMetadata {
+        duration_seconds: Some(info.duration().as_secs_f64()),
+        sample_rate: Some(info.sample_rate),
+        channels: Some(info.channels as u8),
+        bit_depth: Some(u32::from(info.bits_per_sample)),
+        ..Default::default()
+    };
+    if let Ok(len) = std::fs::metadata(path).map(|m| m.len()) {
+        let seconds = info.duration().as_secs_f64();
+        if seconds > 0.0 {
+            metadata.bitrate = Some((len as f64 * 8.0 / seconds / 1000.0) as u32);
+        }
+    }
+    if let Some(tag) = ApeTag::read(path)? {
+        tag.apply(&mut metadata);
+    }
+    Ok(metadata)
+}
+
+/// WavPack decoder
+///
+/// Output is interleaved `f32` in the file's channel layout.
+#[derive(Default)]
+pub struct WavPackDecoder {
+    inner: FramedDecoder,
+}
+
+impl WavPackDecoder {
+    /// Create a new decoder
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl AudioDecoderTrait for WavPackDecoder {
+    fn decode(&mut self, path: &Path) -> soul_core::Result<AudioBuffer> {
+        Ok(FramedDecoder::decode(path, open_frames)?)
+    }
+
+    fn supports_format(&self, path: &Path) -> bool {
+        super::has_extension(path, EXTENSIONS)
+    }
+
+    fn open(&mut self, path: &Path) -> soul_core::Result<AudioMetadata> {
+        Ok(self.inner.open(path, open_frames)?)
+    }
+
+    fn decode_chunk(&mut self, max_frames: usize) -> soul_core::Result<Option<AudioBuffer>> {
+        Ok(self.inner.decode_chunk(max_frames)?)
+    }
+
+    fn seek(&mut self, position: Duration) -> soul_core::Result<Duration> {
+        Ok(self.inner.seek(position)?)
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        self.inner.duration()
+    }
+
+    fn position(&self) -> Duration {
+        self.inner.position()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::ape::ape_tag;
+    use crate::test_utils::generate_pcm_test_signal;
+    use crate::test_utils::wavpack::{write_wavpack, WavPackOptions};
+    use std::path::PathBuf;
+
+    fn to_float(samples: &[i32], bits_per_sample: u32) -> Vec<f32> {
+        let scale = f64::from(1u32 << (bits_per_sample - 1));
+        samples
+            .iter()
+            .map(|&s| (f64::from(s) / scale) as f32)
+            .collect()
+    }
+
+    fn decode_all(path: &Path) -> (AudioMetadata, Vec<f32>) {
+        let mut decoder = WavPackDecoder::new();
+        let info = decoder.open(path).unwrap();
+        let mut samples = Vec::new();
+        while let Some(chunk) = decoder.decode_chunk(1000).unwrap() {
+            assert_eq!(chunk.format.channels, info.channels);
+            samples.extend(chunk.samples);
+        }
+        (info, samples)
+    }
+
+    fn write(dir: &Path, samples: &[i32], options: &WavPackOptions) -> (PathBuf, Vec<i32>) {
+        let path = dir.join("test.wv");
+        let decoded = write_wavpack(&path, samples, options).unwrap();
+        (path, decoded)
+    }
+
+    #[test]
+    fn test_lossless_stereo_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = WavPackOptions {
+            block_samples: 1000,
+            ..Default::default()
+        };
+        let input = generate_pcm_test_signal(4410, 2, 16);
+        let (path, decoded) = write(dir.path(), &input, &options);
+        assert_eq!(decoded, input);
+
+        let (info, samples) = decode_all(&path);
+        assert_eq!(info.sample_rate, 44100);
+        assert_eq!(info.channels, 2);
+        assert_eq!(info.bits_per_sample, Some(16));
+        assert_eq!(info.duration, Some(Duration::from_millis(100)));
+        assert_eq!(samples, to_float(&input, 16));
+    }
+
+    #[test]
+    fn test_lossless_mono_and_24_bit() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mono = WavPackOptions {
+            channels: 1,
+            sample_rate: 22050,
+            block_samples: 700,
+            ..Default::default()
+        };
+        let input = generate_pcm_test_signal(3000, 1, 16);
+        let (path, _) = write(dir.path(), &input, &mono);
+        let (info, samples) = decode_all(&path);
+        assert_eq!(info.sample_rate, 22050);
+        assert_eq!(info.channels, 1);
+        assert_eq!(samples, to_float(&input, 16));
+
+        let hi_res = WavPackOptions {
+            bits_per_sample: 24,
+            sample_rate: 96000,
+            ..Default::default()
+        };
+        let input = generate_pcm_test_signal(5000, 2, 24);
+        let (path, _) = write(dir.path(), &input, &hi_res);
+        let (info, samples) = decode_all(&path);
+        assert_eq!(info.sample_rate, 96000);
+        assert_eq!(info.bits_per_sample, Some(24));
+        assert_eq!(samples, to_float(&input, 24));
+    }
+
+    #[test]
+    fn test_hybrid_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = WavPackOptions {
+            hybrid_error_limit: Some(64),
+            block_samples: 2000,
+            ..Default::default()
+        };
+        let input = generate_pcm_test_signal(6000, 2, 16);
+        let (path, decoded) = write(dir.path(), &input, &options);
+
+        // Lossy, within the error limit
+        assert_ne!(decoded, input);
+        let max_error = input
+            .iter()
+            .zip(&decoded)
+            .map(|(a, b)| (a - b).abs())
+            .max()
+            .unwrap();
+        assert!(max_error <= 64, "error {max_error}");
+
+        let (_, samples) = decode_all(&path);
+        assert_eq!(samples, to_float(&decoded, 16));
+    }
+
+    #[test]
+    fn test_multichannel_keeps_its_channels() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = WavPackOptions {
+            channels: 6,
+            block_samples: 1500,
+            ..Default::default()
+        };
+        let input = generate_pcm_test_signal(4000, 6, 16);
+        let (path, _) = write(dir.path(), &input, &options);
+
+        let (info, samples) = decode_all(&path);
+        assert_eq!(info.channels, 6);
+        assert_eq!(samples, to_float(&input, 16));
+    }
+
+    #[test]
+    fn test_seek_lands_on_exact_sample() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = WavPackOptions {
+            block_samples: 1024,
+            ..Default::default()
+        };
+        let input = generate_pcm_test_signal(44100, 2, 16);
+        let (path, _) = write(dir.path(), &input, &options);
+        let expected = to_float(&input, 16);
+
+        let mut decoder = WavPackDecoder::new();
+        decoder.open(&path).unwrap();
+        for millis in [737, 100, 0, 999] {
+            let frame = (millis as f64 * 44.1).round() as usize;
+            let actual = decoder.seek(Duration::from_millis(millis)).unwrap();
+            assert_eq!(actual, Duration::from_secs_f64(frame as f64 / 44100.0));
+
+            let chunk = decoder.decode_chunk(64).unwrap().unwrap();
+            assert_eq!(
+                &chunk.samples[..],
+                &expected[frame * 2..(frame * 2 + 128).min(expected.len())]
+            );
+        }
+    }
+
+    #[test]
+    fn test_extract_metadata_with_tag() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = generate_pcm_test_signal(22050, 2, 16);
+        let (path, _) = write(dir.path(), &input, &WavPackOptions::default());
+
+        let mut data = std::fs::read(&path).unwrap();
+        data.extend(ape_tag(&[
+            ("Title", 0, b"Archive Track"),
+            ("Artist", 0, b"Someone"),
+            ("Track", 0, b"4/10"),
+        ]));
+        std::fs::write(&path, data).unwrap();
+
+        let metadata = extract_metadata(&path).unwrap();
+        assert_eq!(metadata.title.as_deref(), Some("Archive Track"));
+        assert_eq!(metadata.artist.as_deref(), Some("Someone"));
+        assert_eq!(metadata.track_number, Some(4));
+        assert_eq!(metadata.sample_rate, Some(44100));
+        assert_eq!(metadata.channels, Some(2));
+        assert_eq!(metadata.bit_depth, Some(16));
+        assert!((metadata.duration_seconds.unwrap() - 0.5).abs() < 1e-9);
+
+        // The tag doesn't disturb decoding
+        let (_, samples) = decode_all(&path);
+        assert_eq!(samples, to_float(&input, 16));
+    }
+}
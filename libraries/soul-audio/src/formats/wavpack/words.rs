@@ -0,0 +1,486 @@
+//! WavPack entropy coding ("words")
+//!
+//! Residuals are coded with an adaptive Golomb-like scheme: a unary prefix
+//! selects a magnitude class whose width tracks three running medians, and
+//! the position within the class follows as a truncated binary code (or, in
+//! hybrid mode, as bisection steps down to the current error limit). Runs
+//! of zeros are sent as a single count once the medians have decayed.
+//!
+//! The state machine is shared with the test encoder, which has to mirror
+//! every decision the decoder makes.
+
+use crate::error::{AudioError, Result};
+
+/// `round((2^(i/256) - 1) * 256)`
+const EXP2_TABLE: [u8; 256] = [
+    0x00, 0x01, 0x01, 0x02, 0x03, 0x03, 0x04, 0x05, 0x06, 0x06, 0x07, 0x08, 0x08, 0x09, 0x0a, 0x0b,
+    0x0b, 0x0c, 0x0d, 0x0e, 0x0e, 0x0f, 0x10, 0x10, 0x11, 0x12, 0x13, 0x13, 0x14, 0x15, 0x16, 0x16,
+    0x17, 0x18, 0x19, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1d, 0x1e, 0x1f, 0x20, 0x20, 0x21, 0x22, 0x23,
+    0x24, 0x24, 0x25, 0x26, 0x27, 0x28, 0x28, 0x29, 0x2a, 0x2b, 0x2c, 0x2c, 0x2d, 0x2e, 0x2f, 0x30,
+    0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3a, 0x3a, 0x3b, 0x3c, 0x3d,
+    0x3e, 0x3f, 0x40, 0x41, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x48, 0x49, 0x4a, 0x4b,
+    0x4c, 0x4d, 0x4e, 0x4f, 0x50, 0x51, 0x51, 0x52, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5a,
+    0x5b, 0x5c, 0x5d, 0x5e, 0x5e, 0x5f, 0x60, 0x61, 0x62, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69,
+    0x6a, 0x6b, 0x6c, 0x6d, 0x6e, 0x6f, 0x70, 0x71, 0x72, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79,
+    0x7a, 0x7b, 0x7c, 0x7d, 0x7e, 0x7f, 0x80, 0x81, 0x82, 0x83, 0x84, 0x85, 0x87, 0x88, 0x89, 0x8a,
+    0x8b, 0x8c, 0x8d, 0x8e, 0x8f, 0x90, 0x91, 0x92, 0x93, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9a, 0x9b,
+    0x9c, 0x9d, 0x9f, 0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5, 0xa6, 0xa8, 0xa9, 0xaa, 0xab, 0xac, 0xad,
+    0xaf, 0xb0, 0xb1, 0xb2, 0xb3, 0xb4, 0xb6, 0xb7, 0xb8, 0xb9, 0xba, 0xbc, 0xbd, 0xbe, 0xbf, 0xc0,
+    0xc2, 0xc3, 0xc4, 0xc5, 0xc6, 0xc8, 0xc9, 0xca, 0xcb, 0xcd, 0xce, 0xcf, 0xd0, 0xd2, 0xd3, 0xd4,
+    0xd6, 0xd7, 0xd8, 0xd9, 0xdb, 0xdc, 0xdd, 0xde, 0xe0, 0xe1, 0xe2, 0xe4, 0xe5, 0xe6, 0xe8, 0xe9,
+    0xea, 0xec, 0xed, 0xee, 0xf0, 0xf1, 0xf2, 0xf4, 0xf5, 0xf6, 0xf8, 0xf9, 0xfa, 0xfc, 0xfd, 0xff,
+];
+
+/// `round(log2(1 + i/256) * 256)`
+const LOG2_TABLE: [u8; 256] = [
+    0x00, 0x01, 0x03, 0x04, 0x06, 0x07, 0x09, 0x0a, 0x0b, 0x0d, 0x0e, 0x10, 0x11, 0x12, 0x14, 0x15,
+    0x16, 0x18, 0x19, 0x1a, 0x1c, 0x1d, 0x1e, 0x20, 0x21, 0x22, 0x24, 0x25, 0x26, 0x28, 0x29, 0x2a,
+    0x2c, 0x2d, 0x2e, 0x2f, 0x31, 0x32, 0x33, 0x34, 0x36, 0x37, 0x38, 0x39, 0x3b, 0x3c, 0x3d, 0x3e,
+    0x3f, 0x41, 0x42, 0x43, 0x44, 0x45, 0x47, 0x48, 0x49, 0x4a, 0x4b, 0x4d, 0x4e, 0x4f, 0x50, 0x51,
+    0x52, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5a, 0x5c, 0x5d, 0x5e, 0x5f, 0x60, 0x61, 0x62, 0x63,
+    0x64, 0x66, 0x67, 0x68, 0x69, 0x6a, 0x6b, 0x6c, 0x6d, 0x6e, 0x6f, 0x70, 0x71, 0x72, 0x74, 0x75,
+    0x76, 0x77, 0x78, 0x79, 0x7a, 0x7b, 0x7c, 0x7d, 0x7e, 0x7f, 0x80, 0x81, 0x82, 0x83, 0x84, 0x85,
+    0x86, 0x87, 0x88, 0x89, 0x8a, 0x8b, 0x8c, 0x8d, 0x8e, 0x8f, 0x90, 0x91, 0x92, 0x93, 0x94, 0x95,
+    0x96, 0x97, 0x98, 0x99, 0x9a, 0x9b, 0x9b, 0x9c, 0x9d, 0x9e, 0x9f, 0xa0, 0xa1, 0xa2, 0xa3, 0xa4,
+    0xa5, 0xa6, 0xa7, 0xa8, 0xa9, 0xa9, 0xaa, 0xab, 0xac, 0xad, 0xae, 0xaf, 0xb0, 0xb1, 0xb2, 0xb2,
+    0xb3, 0xb4, 0xb5, 0xb6, 0xb7, 0xb8, 0xb9, 0xb9, 0xba, 0xbb, 0xbc, 0xbd, 0xbe, 0xbf, 0xc0, 0xc0,
+    0xc1, 0xc2, 0xc3, 0xc4, 0xc5, 0xc6, 0xc6, 0xc7, 0xc8, 0xc9, 0xca, 0xcb, 0xcb, 0xcc, 0xcd, 0xce,
+    0xcf, 0xd0, 0xd0, 0xd1, 0xd2, 0xd3, 0xd4, 0xd4, 0xd5, 0xd6, 0xd7, 0xd8, 0xd8, 0xd9, 0xda, 0xdb,
+    0xdc, 0xdc, 0xdd, 0xde, 0xdf, 0xe0, 0xe0, 0xe1, 0xe2, 0xe3, 0xe4, 0xe4, 0xe5, 0xe6, 0xe7, 0xe7,
+    0xe8, 0xe9, 0xea, 0xea, 0xeb, 0xec, 0xed, 0xee, 0xee, 0xef, 0xf0, 0xf1, 0xf1, 0xf2, 0xf3, 0xf4,
+    0xf4, 0xf5, 0xf6, 0xf7, 0xf7, 0xf8, 0xf9, 0xf9, 0xfa, 0xfb, 0xfc, 0xfc, 0xfd, 0xfe, 0xff, 0xff,
+];
+
+/// Longest unary prefix before the escape code
+const UNARY_ESCAPE: u32 = 16;
+
+/// Inverse of [`log2`]: `2^(value / 256)` with 8 fractional bits of precision
+///
+/// Stored medians, decorrelation samples and hybrid levels are sent as
+/// signed 8.8 fixed-point logarithms and restored with this function.
+pub(crate) fn exp2(value: i32) -> i32 {
+    let magnitude = value.unsigned_abs();
+    let exponent = magnitude >> 8;
+    if exponent > 31 {
+        return i32::MIN;
+    }
+
+    let mantissa = i32::from(EXP2_TABLE[(magnitude & 0xff) as usize]) | 0x100;
+    let result = if exponent > 9 {
+        mantissa.wrapping_shl(exponent - 9)
+    } else {
+        mantissa >> (9 - exponent)
+    };
+
+    if value < 0 {
+        result.wrapping_neg()
+    } else {
+        result
+    }
+}
+
+/// Base-2 logarithm in 8.8 fixed point (`log2(0) = 0`)
+pub(crate) fn log2(value: u32) -> i32 {
+    if value == 0 {
+        return 0;
+    }
+    if value == 1 {
+        return 256;
+    }
+
+    let value = value.wrapping_add(value >> 9);
+    let bits = 32 - value.leading_zeros();
+    let index = if bits < 9 {
+        (value << (9 - bits)) & 0xff
+    } else {
+        (value >> (bits - 9)) & 0xff
+    };
+    ((bits << 8) as i32) + i32::from(LOG2_TABLE[index as usize])
+}
+
+/// Signed [`log2`]
+pub(crate) fn log2s(value: i32) -> i32 {
+    if value < 0 {
+        -log2(value.unsigned_abs())
+    } else {
+        log2(value as u32)
+    }
+}
+
+/// Per-sample decay of the hybrid "slow level"
+pub(crate) fn level_decay(level: i32) -> i32 {
+    (level + 0x80) >> 8
+}
+
+/// Reads the little-endian (LSB first) bitstream of a block
+pub(crate) struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    /// Bits left to read
+    pub(crate) fn remaining(&self) -> usize {
+        (self.data.len() * 8).saturating_sub(self.position)
+    }
+
+    pub(crate) fn read_bit(&mut self) -> Result<u32> {
+        let byte = self
+            .data
+            .get(self.position / 8)
+            .ok_or_else(|| AudioError::DecodeError("wavpack: bitstream ended early".into()))?;
+        let bit = (byte >> (self.position % 8)) & 1;
+        self.position += 1;
+        Ok(u32::from(bit))
+    }
+
+    /// Read `count` bits (at most 32), first bit in the least significant position
+    pub(crate) fn read_bits(&mut self, count: u32) -> Result<u32> {
+        let mut value = 0;
+        for i in 0..count {
+            value |= self.read_bit()? << i;
+        }
+        Ok(value)
+    }
+
+    /// Count 1 bits up to the terminating 0 (at most `limit`)
+    fn read_unary(&mut self, limit: u32) -> Result<u32> {
+        let mut count = 0;
+        while count < limit && self.read_bit()? == 1 {
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Read an Elias-gamma style count: a unary bit length, then the bits
+    /// below the (implicit) leading one
+    fn read_count(&mut self) -> Result<u32> {
+        let length = self.read_unary(33)?;
+        if length < 2 {
+            return Ok(length);
+        }
+        if length >= 32 {
+            return Err(AudioError::DecodeError("wavpack: invalid count".into()));
+        }
+        Ok(self.read_bits(length - 1)? | (1 << (length - 1)))
+    }
+
+    /// Read a value in `0..=max` coded as truncated binary
+    fn read_tail(&mut self, max: u32) -> Result<u32> {
+        if max == 0 {
+            return Ok(0);
+        }
+        let bits = max.ilog2();
+        let extra = ((1u64 << (bits + 1)) - u64::from(max) - 1) as u32;
+        let value = self.read_bits(bits)?;
+        if value >= extra {
+            Ok((value << 1) - extra + self.read_bit()?)
+        } else {
+            Ok(value)
+        }
+    }
+}
+
+/// Adaptive coding state of one channel
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct ChannelWords {
+    /// Running medians of the first three magnitude classes
+    pub median: [u32; 3],
+    /// Hybrid mode: log2 of the recent signal level
+    pub slow_level: i32,
+    /// Hybrid mode: accumulated bitrate (16.16, bits per sample in 8.8 log2)
+    pub bitrate_acc: u32,
+    /// Hybrid mode: per-sample change of `bitrate_acc`
+    pub bitrate_delta: u32,
+    /// Hybrid mode: largest allowed coding error (0 = lossless)
+    pub error_limit: u32,
+}
+
+impl ChannelWords {
+    /// Width of magnitude class `n` (for n < 3)
+    pub(crate) fn class_width(&self, n: usize) -> u32 {
+        (self.median[n] >> 4) + 1
+    }
+
+    fn increase_median(&mut self, n: usize) {
+        let divisor = 128 >> n;
+        self.median[n] = self.median[n]
+            .wrapping_add((self.median[n].wrapping_add(divisor) / divisor).wrapping_mul(5));
+    }
+
+    fn decrease_median(&mut self, n: usize) {
+        let divisor = 128 >> n;
+        self.median[n] = self.median[n]
+            .wrapping_sub((self.median[n].wrapping_add(divisor - 2) / divisor).wrapping_mul(2));
+    }
+
+    /// Magnitude class the encoder sends for `magnitude`
+    pub(crate) fn class_of(&self, magnitude: u32) -> u32 {
+        let (w0, w1, w2) = (
+            self.class_width(0),
+            self.class_width(1),
+            self.class_width(2),
+        );
+        if magnitude < w0 {
+            0
+        } else if magnitude - w0 < w1 {
+            1
+        } else {
+            2 + (magnitude - w0 - w1) / w2
+        }
+    }
+
+    /// Magnitudes `base..=base + add` covered by class `class`
+    ///
+    /// Adapts the medians, so it must be called exactly once per coded value.
+    pub(crate) fn enter_class(&mut self, class: u32) -> (u32, u32) {
+        let (w0, w1, w2) = (
+            self.class_width(0),
+            self.class_width(1),
+            self.class_width(2),
+        );
+        match class {
+            0 => {
+                self.decrease_median(0);
+                (0, w0 - 1)
+            }
+            1 => {
+                self.increase_median(0);
+                self.decrease_median(1);
+                (w0, w1 - 1)
+            }
+            2 => {
+                self.increase_median(0);
+                self.increase_median(1);
+                self.decrease_median(2);
+                (w0 + w1, w2 - 1)
+            }
+            _ => {
+                self.increase_median(0);
+                self.increase_median(1);
+                self.increase_median(2);
+                (
+                    w0.wrapping_add(w1).wrapping_add(w2.wrapping_mul(class - 2)),
+                    w2 - 1,
+                )
+            }
+        }
+    }
+}
+
+/// Entropy decoder state of one block
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Words {
+    pub channels: [ChannelWords; 2],
+    /// Whether the block codes two channels
+    pub stereo: bool,
+    /// Hybrid (lossy) coding
+    pub hybrid: bool,
+    /// Hybrid bitrate follows the signal level
+    pub hybrid_bitrate: bool,
+    /// Previous prefix promised a class 0 value next
+    pub zero: bool,
+    /// Previous prefix promised a class 1+ value next
+    pub one: bool,
+    /// Zeros left in the current run
+    pub zeroes: u32,
+}
+
+impl Words {
+    /// Whether the next value starts with a zero-run count
+    ///
+    /// Only checked while no run is active.
+    pub(crate) fn at_run_check(&self) -> bool {
+        self.channels[0].median[0] < 2 && self.channels[1].median[0] < 2 && !self.zero && !self.one
+    }
+
+    /// Start a run of zeros: the medians of both channels are reset
+    pub(crate) fn start_run(&mut self, length: u32) {
+        self.zeroes = length;
+        for channel in &mut self.channels {
+            channel.median = [0; 3];
+        }
+    }
+
+    /// Recompute the hybrid error limits (before each channel 0 value)
+    pub(crate) fn update_error_limit(&mut self) {
+        let mut bitrate = [0i32; 2];
+        let mut level = [0i32; 2];
+        let count = if self.stereo { 2 } else { 1 };
+
+        for i in 0..count {
+            let channel = &mut self.channels[i];
+            channel.bitrate_acc = channel.bitrate_acc.wrapping_add(channel.bitrate_delta);
+            bitrate[i] = (channel.bitrate_acc >> 16) as i32;
+            level[i] = level_decay(channel.slow_level);
+        }
+
+        if self.stereo && self.hybrid_bitrate {
+            let balance = (level[1] - level[0] + bitrate[1] + 1) >> 1;
+            if balance > bitrate[0] {
+                bitrate[1] = bitrate[0] * 2;
+                bitrate[0] = 0;
+            } else if -balance > bitrate[0] {
+                bitrate[0] *= 2;
+                bitrate[1] = 0;
+            } else {
+                bitrate[1] = bitrate[0] + balance;
+                bitrate[0] -= balance;
+            }
+        }
+
+        for i in 0..count {
+            self.channels[i].error_limit = if self.hybrid_bitrate {
+                if level[i] - bitrate[i] > -0x100 {
+                    exp2(level[i] - bitrate[i] + 0x100) as u32
+                } else {
+                    0
+                }
+            } else {
+                exp2(bitrate[i]) as u32
+            };
+        }
+    }
+
+    /// Decode the next value for `channel`
+    pub(crate) fn read_value(&mut self, bits: &mut BitReader, channel: usize) -> Result<i32> {
+        if self.at_run_check() {
+            if self.zeroes > 0 {
+                self.zeroes -= 1;
+                if self.zeroes > 0 {
+                    let slow_level = &mut self.channels[channel].slow_level;
+                    *slow_level -= level_decay(*slow_level);
+                    return Ok(0);
+                }
+            } else {
+                let length = bits.read_count()?;
+                if length > 0 {
+                    self.start_run(length);
+                    let slow_level = &mut self.channels[channel].slow_level;
+                    *slow_level -= level_decay(*slow_level);
+                    return Ok(0);
+                }
+            }
+        }
+
+        let class = if self.zero {
+            self.zero = false;
+            0
+        } else {
+            let mut prefix = bits.read_unary(33)?;
+            if prefix == UNARY_ESCAPE {
+                let extra = bits.read_unary(33)?;
+                if extra < 2 {
+                    prefix += extra;
+                } else if extra >= 32 {
+                    return Err(AudioError::DecodeError("wavpack: invalid prefix".into()));
+                } else {
+                    prefix += bits.read_bits(extra - 1)? | (1 << (extra - 1));
+                }
+            }
+
+            let class = if self.one {
+                (prefix >> 1) + 1
+            } else {
+                prefix >> 1
+            };
+            self.one = prefix & 1 == 1;
+            self.zero = !self.one;
+            class
+        };
+
+        if self.hybrid && channel == 0 {
+            self.update_error_limit();
+        }
+
+        let words = &mut self.channels[channel];
+        let (mut base, mut add) = words.enter_class(class);
+        let magnitude = if words.error_limit == 0 {
+            if add >= 0x0200_0000 {
+                return Err(AudioError::DecodeError("wavpack: invalid residual".into()));
+            }
+            base.wrapping_add(bits.read_tail(add)?)
+        } else {
+            let mut mid = ((u64::from(base) * 2 + u64::from(add) + 1) >> 1) as u32;
+            while add > words.error_limit {
+                if bits.read_bit()? == 1 {
+                    add -= mid - base;
+                    base = mid;
+                } else {
+                    add = mid - base - 1;
+                }
+                mid = ((u64::from(base) * 2 + u64::from(add) + 1) >> 1) as u32;
+            }
+            mid
+        };
+
+        let negative = bits.read_bit()? == 1;
+        if self.hybrid_bitrate {
+            words.slow_level += log2(magnitude) - level_decay(words.slow_level);
+        }
+
+        let value = magnitude as i32;
+        Ok(if negative { !value } else { value })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exp2_and_log2_are_inverse() {
+        assert_eq!(exp2(0), 0);
+        assert_eq!(exp2(256), 1);
+        assert_eq!(exp2(9 << 8), 256);
+        assert_eq!(exp2(-(9 << 8)), -256);
+        assert_eq!(log2(0), 0);
+        assert_eq!(log2(1), 256);
+        assert_eq!(log2(256), 9 << 8);
+
+        // Restored values are within the 8.8 precision of the log
+        for value in [3, 17, 100, 1000, 32767, 1 << 20, 8_000_000] {
+            let restored = exp2(log2s(value));
+            let error = (restored - value).abs() as f64 / value as f64;
+            assert!(error < 0.01, "{value} restored as {restored}");
+            assert_eq!(exp2(log2s(-value)), -restored);
+        }
+    }
+
+    #[test]
+    fn test_bit_reader_is_lsb_first() {
+        let data = [0b1010_0110, 0x7f];
+        let mut bits = BitReader::new(&data);
+
+        assert_eq!(bits.read_bits(3).unwrap(), 0b110);
+        assert_eq!(bits.read_bit().unwrap(), 0);
+        assert_eq!(bits.read_bits(4).unwrap(), 0b1010);
+        assert_eq!(bits.remaining(), 8);
+        assert_eq!(bits.read_unary(33).unwrap(), 7);
+        assert_eq!(bits.remaining(), 0);
+        assert!(bits.read_bit().is_err());
+    }
+
+    #[test]
+    fn test_classes_cover_all_magnitudes() {
+        let words = ChannelWords {
+            median: [100, 200, 400],
+            ..Default::default()
+        };
+
+        for magnitude in [0, 6, 7, 19, 20, 45, 46, 1000] {
+            let class = words.class_of(magnitude);
+            let (base, add) = words.clone().enter_class(class);
+            assert!(
+                (base..=base + add).contains(&magnitude),
+                "{magnitude} not in class {class} ({base}..={})",
+                base + add
+            );
+        }
+    }
+}
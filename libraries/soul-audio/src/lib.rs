@@ -3,9 +3,17 @@
 //! Audio decoding, playback, and effects processing for Soul Player.
 //!
 //! This crate provides:
-//! - Audio decoding via Symphonia (MP3, FLAC, OGG, WAV, AAC, OPUS)
+//! - Audio decoding via Symphonia (MP3, FLAC, OGG, WAV, AAC) and DSD (DSF, DFF),
+//!   plus Opus via libopus (`opus` feature)
+//! - A decoder registry with WavPack, Monkey's Audio and Musepack (`musepack`
+//!   feature) decoders alongside Symphonia
 //! - Real-time audio effects (3-band parametric EQ, dynamic range compressor)
 //! - Effect chain architecture for combining multiple effects
+//! - Channel layouts and ITU-R BS.775 up/downmix matrices
+//! - Real-time level, loudness and spectrum analysis for meters
+//! - Offline rendering of files through the effect chain (WAV/FLAC export)
+//! - Sweep measurement of impulse responses and room correction filters
+//! - Tempo (BPM) and musical key detection for library tracks
 //!
 //! # Example: Decoding Audio
 //!
@@ -48,14 +56,21 @@
 //! chain.process(&mut buffer, 44100);
 //! ```
 
+pub mod analysis;
+pub mod channels;
+pub mod codecs;
 mod decoder;
 pub mod dither;
 pub mod dsd;
 pub mod effects;
 pub mod encoder_delay;
 mod error;
+pub mod formats;
+pub mod measurement;
 pub mod metadata;
+pub mod musical;
 pub mod pipeline;
+pub mod render;
 pub mod resampling;
 
 // Audio fingerprinting (optional feature)
@@ -67,6 +82,6 @@ pub mod fingerprint;
 pub mod test_utils;
 
 pub use decoder::SymphoniaDecoder;
-pub use dsd::{DoP, DopDecoder, DopEncoder, DsdConverter, DsdFormat, DsdSettings};
+pub use dsd::{DoP, DopDecoder, DopEncoder, DsdConverter, DsdFile, DsdFormat, DsdSettings};
 pub use error::{AudioError, Result};
 pub use metadata::{extract_metadata, AlbumArt, AlbumArtType, AudioMetadata};
@@ -0,0 +1,515 @@
+//! Inverse (correction) filter design
+
+use super::{MeasurementError, Result};
+use crate::effects::ImpulseResponse;
+use rustfft::{num_complex::Complex, FftPlanner};
+use std::f64::consts::{LN_10, PI};
+
+/// Fraction of the correction filter faded out at its end
+const FADE_OUT_FRACTION: f64 = 0.1;
+
+/// Octaves outside the corrected band over which the correction eases to 0 dB
+const BAND_EDGE_OCTAVES: f64 = 1.0;
+
+/// Desired magnitude response
+///
+/// Gains in dB at frequency points, interpolated linearly over log
+/// frequency and held constant beyond the first and last point. Only the
+/// shape matters: the curve is aligned to the measured level before the
+/// correction is computed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TargetCurve {
+    /// (frequency Hz, gain dB), sorted by frequency
+    points: Vec<(f64, f64)>,
+}
+
+impl TargetCurve {
+    /// Flat response
+    pub fn flat() -> Self {
+        Self::default()
+    }
+
+    /// Curve through (frequency Hz, gain dB) points, in any order
+    pub fn new(mut points: Vec<(f64, f64)>) -> Result<Self> {
+        if let Some(&(frequency, gain)) = points
+            .iter()
+            .find(|(f, g)| !(f.is_finite() && *f > 0.0 && g.is_finite()))
+        {
+            return Err(MeasurementError::InvalidSettings(format!(
+                "invalid target point {} Hz / {} dB",
+                frequency, gain
+            )));
+        }
+
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(Self { points })
+    }
+
+    /// Constant slope in dB per octave across 20 Hz - 20 kHz
+    ///
+    /// Speakers in a room usually sound best with a gentle downward tilt
+    /// (around -0.5 to -1 dB per octave) rather than flat.
+    pub fn tilt(db_per_octave: f64) -> Self {
+        // Pivot at 1 kHz
+        let gain = |frequency: f64| (frequency / 1000.0).log2() * db_per_octave;
+        Self {
+            points: vec![(20.0, gain(20.0)), (20000.0, gain(20000.0))],
+        }
+    }
+
+    /// Target gain at a frequency (dB)
+    pub fn gain_db(&self, frequency: f64) -> f64 {
+        let (first, last) = match (self.points.first(), self.points.last()) {
+            (Some(first), Some(last)) => (*first, *last),
+            _ => return 0.0,
+        };
+        if frequency <= first.0 {
+            return first.1;
+        }
+        if frequency >= last.0 {
+            return last.1;
+        }
+
+        let upper = self.points.partition_point(|&(f, _)| f <= frequency);
+        let (f0, g0) = self.points[upper - 1];
+        let (f1, g1) = self.points[upper];
+        let position = (frequency / f0).ln() / (f1 / f0).ln();
+        g0 + (g1 - g0) * position
+    }
+
+    /// Points the curve was built from
+    pub fn points(&self) -> &[(f64, f64)] {
+        &self.points
+    }
+}
+
+/// Correction filter parameters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CorrectionSettings {
+    /// Largest boost applied (dB); deep dips are usually room nulls that
+    /// boosting can't fill
+    pub max_boost_db: f64,
+    /// Largest cut applied (dB)
+    pub max_cut_db: f64,
+    /// Lowest corrected frequency (Hz)
+    pub low_hz: f64,
+    /// Highest corrected frequency (Hz)
+    pub high_hz: f64,
+    /// Fractional-octave smoothing of the measured response (e.g. 1/6)
+    pub smoothing_octaves: f64,
+    /// Filter length (frames); longer filters resolve lower frequencies
+    pub taps: usize,
+    /// Scale the filter so its largest gain is 0 dB and it can't clip
+    pub normalize: bool,
+}
+
+impl Default for CorrectionSettings {
+    fn default() -> Self {
+        Self {
+            max_boost_db: 6.0,
+            max_cut_db: 15.0,
+            low_hz: 20.0,
+            high_hz: 20000.0,
+            smoothing_octaves: 1.0 / 6.0,
+            taps: 16384,
+            normalize: true,
+        }
+    }
+}
+
+impl CorrectionSettings {
+    /// Check the settings can produce a filter
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |reason: String| Err(MeasurementError::InvalidSettings(reason));
+
+        if !(self.max_boost_db >= 0.0 && self.max_cut_db >= 0.0) {
+            return invalid("boost and cut limits must not be negative".to_string());
+        }
+        if !(self.low_hz > 0.0 && self.low_hz < self.high_hz) {
+            return invalid(format!(
+                "corrected band {} Hz - {} Hz is empty",
+                self.low_hz, self.high_hz
+            ));
+        }
+        if !(self.smoothing_octaves > 0.0 && self.smoothing_octaves <= 2.0) {
+            return invalid(format!(
+                "smoothing of {} octaves is outside 0 - 2",
+                self.smoothing_octaves
+            ));
+        }
+        if self.taps < 64 {
+            return invalid(format!("{} taps is too short for a filter", self.taps));
+        }
+        Ok(())
+    }
+}
+
+/// Design a correction filter for a measured impulse response
+///
+/// The measured magnitude is smoothed, and the correction is the
+/// difference to the target within `low_hz..high_hz`, limited to the boost
+/// and cut limits and easing to 0 dB outside the band. It is realised as a
+/// minimum-phase FIR (no pre-ringing, no added latency) at the measurement
+/// rate, with one channel per measured channel. Channels share one
+/// reference level, so a level difference between them is corrected too.
+pub fn correction_filter(
+    ir: &ImpulseResponse,
+    target: &TargetCurve,
+    settings: &CorrectionSettings,
+) -> Result<ImpulseResponse> {
+    settings.validate()?;
+    if !matches!(ir.channels, 1 | 2) {
+        return Err(MeasurementError::InvalidChannelCount(ir.channels));
+    }
+
+    // Twice the filter length keeps the cepstrum from aliasing
+    let fft_size = (ir.frames().max(settings.taps) * 2).next_power_of_two();
+    let bin_hz = ir.sample_rate as f64 / fft_size as f64;
+    let bins = fft_size / 2 + 1;
+    let high_hz = settings.high_hz.min(ir.sample_rate as f64 / 2.0);
+
+    let mut planner = FftPlanner::<f64>::new();
+    let forward = planner.plan_fft_forward(fft_size);
+
+    // Smoothed measured response per channel (dB)
+    let measured: Vec<Vec<f64>> = (0..ir.channels)
+        .map(|channel| {
+            let mut spectrum: Vec<Complex<f64>> = ir
+                .samples
+                .iter()
+                .skip(channel)
+                .step_by(ir.channels)
+                .map(|&s| Complex::new(s as f64, 0.0))
+                .collect();
+            spectrum.resize(fft_size, Complex::new(0.0, 0.0));
+            forward.process(&mut spectrum);
+
+            let power: Vec<f64> = spectrum[..bins].iter().map(|bin| bin.norm_sqr()).collect();
+            smooth(&power, settings.smoothing_octaves)
+                .into_iter()
+                .map(|p| 10.0 * (p + 1e-20).log10())
+                .collect()
+        })
+        .collect();
+
+    // Line the target up with the measured level: both are averaged over
+    // the band, weighting every octave equally
+    let band_average = |db: &dyn Fn(usize) -> f64| {
+        let (sum, weight) = (1..bins)
+            .filter(|&k| (settings.low_hz..=high_hz).contains(&(k as f64 * bin_hz)))
+            .fold((0.0, 0.0), |(sum, weight), k| {
+                (sum + db(k) / k as f64, weight + 1.0 / k as f64)
+            });
+        if weight > 0.0 {
+            sum / weight
+        } else {
+            0.0
+        }
+    };
+    let measured_level = measured
+        .iter()
+        .map(|channel| band_average(&|k| channel[k]))
+        .sum::<f64>()
+        / ir.channels as f64;
+    let target_level = band_average(&|k| target.gain_db(k as f64 * bin_hz));
+
+    let corrections: Vec<Vec<f64>> = measured
+        .iter()
+        .map(|channel| {
+            (0..bins)
+                .map(|k| {
+                    let frequency = k as f64 * bin_hz;
+                    let wanted =
+                        (target.gain_db(frequency) - target_level) - (channel[k] - measured_level);
+                    let limited = wanted.clamp(-settings.max_cut_db, settings.max_boost_db);
+                    limited * band_weight(frequency, settings.low_hz, high_hz)
+                })
+                .collect()
+        })
+        .collect();
+
+    let gain = if settings.normalize {
+        let peak_db = corrections
+            .iter()
+            .flatten()
+            .fold(f64::MIN, |m, &db| m.max(db));
+        10f64.powf(-peak_db / 20.0)
+    } else {
+        1.0
+    };
+
+    let filters: Vec<Vec<f64>> = corrections
+        .iter()
+        .map(|correction| minimum_phase(correction, fft_size, &mut planner))
+        .collect();
+
+    let fade_out = ((settings.taps as f64 * FADE_OUT_FRACTION) as usize).max(1);
+    let mut samples = Vec::with_capacity(settings.taps * ir.channels);
+    for i in 0..settings.taps {
+        let window = if i + fade_out > settings.taps {
+            let remaining = (settings.taps - i) as f64;
+            0.5 - 0.5 * (PI * remaining / fade_out as f64).cos()
+        } else {
+            1.0
+        };
+        for filter in &filters {
+            samples.push((filter[i] * window * gain) as f32);
+        }
+    }
+
+    Ok(ImpulseResponse::new(samples, ir.sample_rate, ir.channels)?)
+}
+
+/// Fractional-octave smoothing of a power spectrum (bins 0..=N/2)
+///
+/// Each bin becomes the mean power over `octaves` centred on it (in log
+/// frequency), computed from prefix sums.
+fn smooth(power: &[f64], octaves: f64) -> Vec<f64> {
+    let mut prefix = Vec::with_capacity(power.len() + 1);
+    prefix.push(0.0);
+    for &p in power {
+        prefix.push(prefix[prefix.len() - 1] + p);
+    }
+
+    let half_width = 2f64.powf(octaves / 2.0);
+    let last = power.len() - 1;
+    (0..power.len())
+        .map(|k| {
+            if k == 0 {
+                return power[0];
+            }
+            let low = ((k as f64 / half_width).floor() as usize).clamp(1, k);
+            let high = ((k as f64 * half_width).ceil() as usize).clamp(k, last);
+            (prefix[high + 1] - prefix[low]) / (high + 1 - low) as f64
+        })
+        .collect()
+}
+
+/// 1 inside the corrected band, easing to 0 over an octave outside it
+fn band_weight(frequency: f64, low_hz: f64, high_hz: f64) -> f64 {
+    let octaves_outside = if frequency < low_hz {
+        if frequency <= 0.0 {
+            return 0.0;
+        }
+        (low_hz / frequency).log2()
+    } else if frequency > high_hz {
+        (frequency / high_hz).log2()
+    } else {
+        0.0
+    };
+
+    if octaves_outside >= BAND_EDGE_OCTAVES {
+        0.0
+    } else {
+        0.5 + 0.5 * (PI * octaves_outside / BAND_EDGE_OCTAVES).cos()
+    }
+}
+
+/// Minimum-phase impulse response for a magnitude response (dB, bins 0..=N/2)
+///
+/// Homomorphic method: fold the real cepstrum of the log magnitude onto
+/// positive quefrencies, which makes the phase the Hilbert transform of the
+/// log magnitude.
+fn minimum_phase(magnitude_db: &[f64], fft_size: usize, planner: &mut FftPlanner<f64>) -> Vec<f64> {
+    let forward = planner.plan_fft_forward(fft_size);
+    let inverse = planner.plan_fft_inverse(fft_size);
+
+    // Natural log of the amplitude, mirrored into a full spectrum
+    let mut cepstrum: Vec<Complex<f64>> = (0..fft_size)
+        .map(|k| {
+            let db = magnitude_db[k.min(fft_size - k)];
+            Complex::new(db * LN_10 / 20.0, 0.0)
+        })
+        .collect();
+    inverse.process(&mut cepstrum);
+
+    let half = fft_size / 2;
+    for (n, c) in cepstrum.iter_mut().enumerate() {
+        let fold = match n {
+            0 => 1.0,
+            n if n < half => 2.0,
+            n if n == half => 1.0,
+            _ => 0.0,
+        };
+        *c = Complex::new(c.re * fold / fft_size as f64, 0.0);
+    }
+
+    forward.process(&mut cepstrum);
+    let mut spectrum: Vec<Complex<f64>> = cepstrum.iter().map(|c| c.exp()).collect();
+    inverse.process(&mut spectrum);
+
+    spectrum.iter().map(|s| s.re / fft_size as f64).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Magnitude of an FIR at a frequency (dB)
+    fn magnitude_db(fir: &[f32], channels: usize, channel: usize, frequency: f64) -> f64 {
+        let omega = 2.0 * PI * frequency / 48000.0;
+        let (re, im) = fir.iter().skip(channel).step_by(channels).enumerate().fold(
+            (0.0, 0.0),
+            |(re, im), (n, &h)| {
+                let (sin, cos) = (omega * n as f64).sin_cos();
+                (re + h as f64 * cos, im - h as f64 * sin)
+            },
+        );
+        10.0 * (re * re + im * im).log10()
+    }
+
+    /// Impulse response of a cascade of peaking filters (RBJ cookbook)
+    fn peaking_response(bands: &[(f64, f64, f64)]) -> ImpulseResponse {
+        let mut samples = vec![0.0f64; 16384];
+        samples[0] = 1.0;
+        for &(frequency, gain_db, q) in bands {
+            let a = 10f64.powf(gain_db / 40.0);
+            let omega = 2.0 * PI * frequency / 48000.0;
+            let alpha = omega.sin() / (2.0 * q);
+            let a0 = 1.0 + alpha / a;
+            let b = [
+                (1.0 + alpha * a) / a0,
+                -2.0 * omega.cos() / a0,
+                (1.0 - alpha * a) / a0,
+            ];
+            let a = [-2.0 * omega.cos() / a0, (1.0 - alpha / a) / a0];
+
+            let (mut x1, mut x2, mut y1, mut y2) = (0.0, 0.0, 0.0, 0.0);
+            for sample in &mut samples {
+                let x = *sample;
+                let y = b[0] * x + b[1] * x1 + b[2] * x2 - a[0] * y1 - a[1] * y2;
+                (x2, x1, y2, y1) = (x1, x, y1, y);
+                *sample = y;
+            }
+        }
+        let samples = samples.into_iter().map(|s| s as f32).collect();
+        ImpulseResponse::new(samples, 48000, 1).unwrap()
+    }
+
+    #[test]
+    fn test_target_curve_interpolates_in_log_frequency() {
+        let curve = TargetCurve::new(vec![(1000.0, 0.0), (100.0, 6.0)]).unwrap();
+        assert_eq!(curve.points()[0], (100.0, 6.0));
+        assert!((curve.gain_db(50.0) - 6.0).abs() < 1e-9);
+        assert!((curve.gain_db(316.227766) - 3.0).abs() < 1e-6);
+        assert!((curve.gain_db(5000.0)).abs() < 1e-9);
+
+        let tilt = TargetCurve::tilt(-1.0);
+        assert!((tilt.gain_db(1000.0)).abs() < 1e-9);
+        assert!((tilt.gain_db(2000.0) + 1.0).abs() < 1e-9);
+        assert!((tilt.gain_db(250.0) - 2.0).abs() < 1e-9);
+
+        assert_eq!(TargetCurve::flat().gain_db(123.0), 0.0);
+        assert!(TargetCurve::new(vec![(0.0, 1.0)]).is_err());
+    }
+
+    #[test]
+    fn test_flattens_measured_response() {
+        let ir = peaking_response(&[(1000.0, 8.0, 2.0), (4000.0, -4.0, 2.0)]);
+        let settings = CorrectionSettings {
+            normalize: false,
+            ..Default::default()
+        };
+
+        let filter = correction_filter(&ir, &TargetCurve::flat(), &settings).unwrap();
+        assert_eq!(filter.frames(), settings.taps);
+
+        // Measured + correction is flat within the smoothing error
+        let reference = magnitude_db(&filter.samples, 1, 0, 200.0);
+        for (frequency, measured) in [(1000.0, 8.0), (4000.0, -4.0), (10000.0, 0.0)] {
+            let corrected = measured + magnitude_db(&filter.samples, 1, 0, frequency) - reference;
+            assert!(
+                corrected.abs() < 1.0,
+                "{} Hz: corrected response {:.2} dB",
+                frequency,
+                corrected
+            );
+        }
+    }
+
+    #[test]
+    fn test_limits_boost() {
+        let ir = peaking_response(&[(1000.0, -20.0, 1.0)]);
+        let settings = CorrectionSettings {
+            max_boost_db: 6.0,
+            normalize: false,
+            ..Default::default()
+        };
+
+        let filter = correction_filter(&ir, &TargetCurve::flat(), &settings).unwrap();
+        let boost = magnitude_db(&filter.samples, 1, 0, 1000.0);
+        assert!(boost <= 6.5, "boost {:.2} dB is over the limit", boost);
+        assert!(boost >= 5.0, "boost {:.2} dB should reach the limit", boost);
+    }
+
+    #[test]
+    fn test_normalized_filter_never_boosts() {
+        let ir = peaking_response(&[(200.0, -6.0, 1.0), (3000.0, 5.0, 1.0)]);
+        let filter =
+            correction_filter(&ir, &TargetCurve::flat(), &CorrectionSettings::default()).unwrap();
+
+        for frequency in [30.0, 100.0, 200.0, 500.0, 1000.0, 3000.0, 8000.0, 16000.0] {
+            let gain = magnitude_db(&filter.samples, 1, 0, frequency);
+            assert!(gain < 0.5, "{} Hz: gain {:.2} dB", frequency, gain);
+        }
+    }
+
+    #[test]
+    fn test_filter_is_minimum_phase() {
+        let ir = peaking_response(&[(500.0, 6.0, 1.0)]);
+        let filter =
+            correction_filter(&ir, &TargetCurve::flat(), &CorrectionSettings::default()).unwrap();
+
+        // Energy is concentrated at the start: no pre-ringing, no latency
+        let energy: f64 = filter.samples.iter().map(|&s| (s as f64).powi(2)).sum();
+        let early: f64 = filter.samples[..256]
+            .iter()
+            .map(|&s| (s as f64).powi(2))
+            .sum();
+        assert!(early > energy * 0.9);
+    }
+
+    #[test]
+    fn test_stereo_levels_are_matched() {
+        // Right channel 4 dB quieter
+        let mut samples = vec![0.0f32; 2048];
+        samples[0] = 1.0;
+        samples[1] = 10f32.powf(-4.0 / 20.0);
+        let ir = ImpulseResponse::new(samples, 48000, 2).unwrap();
+        let settings = CorrectionSettings {
+            normalize: false,
+            ..Default::default()
+        };
+
+        let filter = correction_filter(&ir, &TargetCurve::flat(), &settings).unwrap();
+        let left = magnitude_db(&filter.samples, 2, 0, 1000.0);
+        let right = magnitude_db(&filter.samples, 2, 1, 1000.0);
+        assert!(
+            (right - left - 4.0).abs() < 0.2,
+            "L {:.2} dB, R {:.2} dB",
+            left,
+            right
+        );
+    }
+
+    #[test]
+    fn test_rejects_invalid_settings() {
+        let ir = peaking_response(&[]);
+        let settings = CorrectionSettings {
+            low_hz: 1000.0,
+            high_hz: 100.0,
+            ..Default::default()
+        };
+        assert!(correction_filter(&ir, &TargetCurve::flat(), &settings).is_err());
+
+        let true_stereo = ImpulseResponse::new(vec![1.0, 0.0, 0.0, 1.0], 48000, 4).unwrap();
+        assert!(matches!(
+            correction_filter(
+                &true_stereo,
+                &TargetCurve::flat(),
+                &CorrectionSettings::default()
+            ),
+            Err(MeasurementError::InvalidChannelCount(4))
+        ));
+    }
+}
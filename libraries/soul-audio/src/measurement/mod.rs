@@ -0,0 +1,80 @@
+//! Room and headphone measurement
+//!
+//! Impulse responses are measured with the exponential sine sweep method
+//! (Farina): a [`LogSweep`] is played through the system under test while a
+//! microphone records it, and deconvolving the recording with the sweep
+//! yields the impulse response. Harmonic distortion from the speaker ends
+//! up before the linear response in time and is cut off by the window.
+//!
+//! From a measured response, [`correction_filter`] designs a minimum-phase
+//! FIR that pulls the smoothed magnitude response towards a [`TargetCurve`],
+//! within limits on boost and cut. Both are
+//! [`ImpulseResponse`](crate::effects::ImpulseResponse)s, so they can be saved
+//! with `write_wav` and loaded straight into a
+//! [`ConvolutionEngine`](crate::effects::ConvolutionEngine).
+//!
+//! Playback and capture live in the desktop crate. Everything here works
+//! offline, so a sweep can also be played and recorded with any other tool:
+//!
+//! ```rust,no_run
+//! use soul_audio::measurement::{
+//!     correction_filter, CorrectionSettings, LogSweep, SweepSettings, TargetCurve,
+//! };
+//!
+//! # fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let sweep = LogSweep::new(SweepSettings::default())?;
+//! sweep.write_wav("/tmp/sweep.wav", 2)?;
+//!
+//! // ... play sweep.wav and record the microphone to recording.wav ...
+//!
+//! let ir = sweep.deconvolve_wav("/tmp/recording.wav", 48000)?;
+//! ir.write_wav("/tmp/room.wav")?;
+//!
+//! let correction = correction_filter(
+//!     &ir,
+//!     &TargetCurve::tilt(-0.5),
+//!     &CorrectionSettings::default(),
+//! )?;
+//! correction.write_wav("/tmp/correction.wav")?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod correction;
+mod sweep;
+
+pub use correction::{correction_filter, CorrectionSettings, TargetCurve};
+pub use sweep::{LogSweep, SweepSettings};
+
+use crate::effects::ConvolutionError;
+use thiserror::Error;
+
+/// Measurement errors
+#[derive(Error, Debug)]
+pub enum MeasurementError {
+    #[error("Invalid measurement settings: {0}")]
+    InvalidSettings(String),
+
+    #[error("Recording is at {recording} Hz but the sweep is at {sweep} Hz")]
+    SampleRateMismatch { sweep: u32, recording: u32 },
+
+    #[error("Unsupported channel count: {0} (must be 1 or 2)")]
+    InvalidChannelCount(usize),
+
+    #[error("Recording is shorter than the sweep")]
+    RecordingTooShort,
+
+    #[error("No sweep found in the recording")]
+    NoSignal,
+
+    #[error("Failed to read recording: {0}")]
+    WavReadError(String),
+
+    #[error("Failed to write WAV file: {0}")]
+    WavWriteError(String),
+
+    #[error(transparent)]
+    Convolution(#[from] ConvolutionError),
+}
+
+pub type Result<T> = std::result::Result<T, MeasurementError>;
@@ -0,0 +1,604 @@
+//! Exponential sine sweep generation and deconvolution
+
+use super::{MeasurementError, Result};
+use crate::effects::ImpulseResponse;
+use rustfft::{num_complex::Complex, FftPlanner};
+use std::f64::consts::PI;
+use std::path::Path;
+
+/// Regularization inside the sweep band, relative to the peak sweep power
+///
+/// Small enough to leave the in-band inverse exact to well under 0.1 dB.
+const IN_BAND_REGULARIZATION: f64 = 1e-6;
+
+/// Regularization outside the sweep band, relative to the peak sweep power
+///
+/// The sweep carries no energy there, so dividing by it would only amplify
+/// noise.
+const OUT_OF_BAND_REGULARIZATION: f64 = 1.0;
+
+/// Width of the transition from in-band to out-of-band regularization (octaves)
+const BAND_TRANSITION_OCTAVES: f64 = 1.0 / 3.0;
+
+/// Part of the impulse response kept before its peak (ms)
+const PRE_DELAY_MS: f64 = 2.0;
+
+/// Fraction of the impulse response faded out at its end
+const FADE_OUT_FRACTION: f64 = 0.1;
+
+/// Exponential sine sweep parameters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SweepSettings {
+    /// Start frequency (Hz)
+    pub start_hz: f64,
+    /// End frequency (Hz), at most half the sample rate
+    pub end_hz: f64,
+    /// Sweep length (seconds); longer sweeps give a better signal-to-noise ratio
+    pub duration_secs: f64,
+    /// Sample rate (Hz)
+    pub sample_rate: u32,
+    /// Peak level (dBFS)
+    pub level_db: f64,
+    /// Fade-in and fade-out length (ms), so the sweep starts and ends without a click
+    pub fade_ms: f64,
+    /// Silence recorded after the sweep so the room's decay is captured (seconds)
+    pub tail_secs: f64,
+}
+
+impl Default for SweepSettings {
+    fn default() -> Self {
+        Self {
+            start_hz: 20.0,
+            end_hz: 20000.0,
+            duration_secs: 10.0,
+            sample_rate: 48000,
+            level_db: -6.0,
+            fade_ms: 20.0,
+            tail_secs: 2.0,
+        }
+    }
+}
+
+impl SweepSettings {
+    /// Check the settings describe a sweep that can be generated
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |reason: String| Err(MeasurementError::InvalidSettings(reason));
+
+        if self.sample_rate == 0 {
+            return invalid("sample rate must be positive".to_string());
+        }
+        if !(self.start_hz > 0.0 && self.start_hz < self.end_hz) {
+            return invalid(format!(
+                "sweep must rise from a positive frequency ({} Hz to {} Hz)",
+                self.start_hz, self.end_hz
+            ));
+        }
+        if self.end_hz > self.sample_rate as f64 / 2.0 {
+            return invalid(format!(
+                "end frequency {} Hz is above Nyquist at {} Hz",
+                self.end_hz, self.sample_rate
+            ));
+        }
+        if !(0.1..=120.0).contains(&self.duration_secs) {
+            return invalid(format!(
+                "duration {} s is outside 0.1 - 120 s",
+                self.duration_secs
+            ));
+        }
+        if self.level_db.is_nan() || self.level_db > 0.0 {
+            return invalid(format!("level {} dBFS is above full scale", self.level_db));
+        }
+        if !(self.fade_ms >= 0.0 && self.fade_ms * 2.0 < self.duration_secs * 1000.0) {
+            return invalid(format!("fade of {} ms doesn't fit the sweep", self.fade_ms));
+        }
+        if self.tail_secs.is_nan() || self.tail_secs < 0.0 {
+            return invalid(format!("tail of {} s is negative", self.tail_secs));
+        }
+        Ok(())
+    }
+}
+
+/// Exponential (logarithmic) sine sweep, after Farina
+///
+/// The frequency rises exponentially, so every octave gets the same time
+/// and the spectrum falls at 3 dB per octave. Its main property for
+/// measurement: the harmonics a nonlinear system adds to it are the same
+/// sweep shifted earlier in time, so after deconvolution they land before
+/// the linear impulse response and can be windowed away.
+#[derive(Debug, Clone)]
+pub struct LogSweep {
+    settings: SweepSettings,
+    signal: Vec<f32>,
+}
+
+impl LogSweep {
+    /// Generate a sweep
+    pub fn new(settings: SweepSettings) -> Result<Self> {
+        settings.validate()?;
+
+        let rate = settings.sample_rate as f64;
+        let frames = (settings.duration_secs * rate).round() as usize;
+        let duration = frames as f64 / rate;
+        let sweep_rate = (settings.end_hz / settings.start_hz).ln();
+        let amplitude = 10f64.powf(settings.level_db / 20.0);
+        let fade_frames = ((settings.fade_ms / 1000.0 * rate).round() as usize).min(frames / 2);
+
+        let signal = (0..frames)
+            .map(|i| {
+                let t = i as f64 / rate;
+                let phase = 2.0 * PI * settings.start_hz * duration / sweep_rate
+                    * ((t * sweep_rate / duration).exp() - 1.0);
+
+                // Half-Hann fades at both ends
+                let edge = i.min(frames - 1 - i);
+                let fade = if edge < fade_frames {
+                    0.5 - 0.5 * (PI * edge as f64 / fade_frames as f64).cos()
+                } else {
+                    1.0
+                };
+
+                (phase.sin() * amplitude * fade) as f32
+            })
+            .collect();
+
+        Ok(Self { settings, signal })
+    }
+
+    /// Settings the sweep was generated with
+    pub fn settings(&self) -> &SweepSettings {
+        &self.settings
+    }
+
+    /// The sweep itself (mono)
+    pub fn signal(&self) -> &[f32] {
+        &self.signal
+    }
+
+    /// What to play: the sweep followed by the silent tail (mono)
+    pub fn playback_signal(&self) -> Vec<f32> {
+        let tail = (self.settings.tail_secs * self.settings.sample_rate as f64).round() as usize;
+        let mut signal = self.signal.clone();
+        signal.resize(self.signal.len() + tail, 0.0);
+        signal
+    }
+
+    /// Instantaneous frequency of the sweep at a time (Hz)
+    pub fn frequency_at(&self, seconds: f64) -> f64 {
+        let duration = self.signal.len() as f64 / self.settings.sample_rate as f64;
+        let sweep_rate = (self.settings.end_hz / self.settings.start_hz).ln();
+        self.settings.start_hz * (seconds * sweep_rate / duration).exp()
+    }
+
+    /// Save [`Self::playback_signal`] as a 32-bit float WAV, on every channel
+    ///
+    /// For playing the sweep with another application and deconvolving the
+    /// recording with [`Self::deconvolve_wav`].
+    pub fn write_wav<P: AsRef<Path>>(&self, path: P, channels: u16) -> Result<()> {
+        let spec = hound::WavSpec {
+            channels,
+            sample_rate: self.settings.sample_rate,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+        let write_error = |e: hound::Error| MeasurementError::WavWriteError(e.to_string());
+
+        let mut writer = hound::WavWriter::create(path, spec).map_err(write_error)?;
+        for sample in self.playback_signal() {
+            for _ in 0..channels {
+                writer.write_sample(sample).map_err(write_error)?;
+            }
+        }
+        writer.finalize().map_err(write_error)
+    }
+
+    /// Deconvolve a recording of the sweep into an impulse response
+    ///
+    /// `recording` is interleaved with 1 or 2 channels (e.g. two microphones
+    /// of a binaural head) and may start any time before the sweep arrives.
+    /// The response is cut to `ir_frames` starting shortly before its peak;
+    /// all channels are cut at the same point so their relative timing is
+    /// kept. The gain is absolute: recording the sweep itself gives a unit
+    /// impulse.
+    pub fn deconvolve(
+        &self,
+        recording: &[f32],
+        channels: usize,
+        sample_rate: u32,
+        ir_frames: usize,
+    ) -> Result<ImpulseResponse> {
+        if sample_rate != self.settings.sample_rate {
+            return Err(MeasurementError::SampleRateMismatch {
+                sweep: self.settings.sample_rate,
+                recording: sample_rate,
+            });
+        }
+        if !matches!(channels, 1 | 2) {
+            return Err(MeasurementError::InvalidChannelCount(channels));
+        }
+        if ir_frames == 0 {
+            return Err(MeasurementError::InvalidSettings(
+                "impulse response length must be positive".to_string(),
+            ));
+        }
+
+        let frames = recording.len() / channels;
+        if frames < self.signal.len() {
+            return Err(MeasurementError::RecordingTooShort);
+        }
+
+        // Long enough that the linear convolution doesn't wrap: the response
+        // sits at positive delays, harmonics at negative delays (the end)
+        let fft_size = (frames + self.signal.len()).next_power_of_two();
+        let mut planner = FftPlanner::<f64>::new();
+        let forward = planner.plan_fft_forward(fft_size);
+        let inverse = planner.plan_fft_inverse(fft_size);
+        let inverse_sweep = self.inverse_spectrum(fft_size, forward.as_ref());
+
+        let responses: Vec<Vec<f64>> = (0..channels)
+            .map(|channel| {
+                let mut spectrum: Vec<Complex<f64>> = recording
+                    .iter()
+                    .skip(channel)
+                    .step_by(channels)
+                    .map(|&s| Complex::new(s as f64, 0.0))
+                    .collect();
+                spectrum.resize(fft_size, Complex::new(0.0, 0.0));
+
+                forward.process(&mut spectrum);
+                for (bin, inv) in spectrum.iter_mut().zip(&inverse_sweep) {
+                    *bin *= inv;
+                }
+                inverse.process(&mut spectrum);
+
+                spectrum
+                    .iter()
+                    .map(|bin| bin.re / fft_size as f64)
+                    .collect()
+            })
+            .collect();
+
+        // The linear response can only sit within the recording
+        let peaks: Vec<(usize, f64)> = responses
+            .iter()
+            .map(|response| {
+                response[..frames]
+                    .iter()
+                    .enumerate()
+                    .map(|(i, s)| (i, s.abs()))
+                    .fold(
+                        (0, 0.0),
+                        |best, (i, s)| if s > best.1 { (i, s) } else { best },
+                    )
+            })
+            .collect();
+
+        let peak_level = peaks.iter().map(|&(_, level)| level).fold(0.0, f64::max);
+        if peak_level < 1e-6 {
+            return Err(MeasurementError::NoSignal);
+        }
+
+        let pre_frames = (PRE_DELAY_MS / 1000.0 * sample_rate as f64).round() as usize;
+        let first_peak = peaks.iter().map(|&(i, _)| i).min().unwrap_or(0);
+        let start = first_peak.saturating_sub(pre_frames);
+        let fade_in = first_peak - start;
+        // Stop before the harmonics wrapped around to the end
+        let ir_frames = ir_frames.min(fft_size - self.signal.len() - start);
+        let fade_out = ((ir_frames as f64 * FADE_OUT_FRACTION) as usize).max(1);
+
+        let mut samples = Vec::with_capacity(ir_frames * channels);
+        for i in 0..ir_frames {
+            let window = if i < fade_in {
+                0.5 - 0.5 * (PI * i as f64 / fade_in as f64).cos()
+            } else if i + fade_out > ir_frames {
+                let remaining = (ir_frames - i) as f64;
+                0.5 - 0.5 * (PI * remaining / fade_out as f64).cos()
+            } else {
+                1.0
+            };
+
+            for response in &responses {
+                samples.push((response[start + i] * window) as f32);
+            }
+        }
+
+        Ok(ImpulseResponse::new(samples, sample_rate, channels)?)
+    }
+
+    /// Deconvolve a recording saved as a WAV file
+    ///
+    /// See [`Self::deconvolve`]. Integer and float WAVs are accepted.
+    pub fn deconvolve_wav<P: AsRef<Path>>(
+        &self,
+        path: P,
+        ir_frames: usize,
+    ) -> Result<ImpulseResponse> {
+        let read_error = |e: hound::Error| MeasurementError::WavReadError(e.to_string());
+
+        let reader = hound::WavReader::open(path).map_err(read_error)?;
+        let spec = reader.spec();
+        let recording: Vec<f32> = match spec.sample_format {
+            hound::SampleFormat::Float => reader
+                .into_samples::<f32>()
+                .collect::<std::result::Result<_, _>>()
+                .map_err(read_error)?,
+            hound::SampleFormat::Int => {
+                let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+                reader
+                    .into_samples::<i32>()
+                    .map(|s| s.map(|s| s as f32 / scale))
+                    .collect::<std::result::Result<_, _>>()
+                    .map_err(read_error)?
+            }
+        };
+
+        self.deconvolve(
+            &recording,
+            spec.channels as usize,
+            spec.sample_rate,
+            ir_frames,
+        )
+    }
+
+    /// Regularized inverse of the sweep spectrum
+    ///
+    /// `conj(S) / (|S|² + ε)`: an exact inverse where the sweep has energy,
+    /// fading to zero outside the swept band.
+    fn inverse_spectrum(
+        &self,
+        fft_size: usize,
+        forward: &dyn rustfft::Fft<f64>,
+    ) -> Vec<Complex<f64>> {
+        let mut spectrum: Vec<Complex<f64>> = self
+            .signal
+            .iter()
+            .map(|&s| Complex::new(s as f64, 0.0))
+            .collect();
+        spectrum.resize(fft_size, Complex::new(0.0, 0.0));
+        forward.process(&mut spectrum);
+
+        let max_power = spectrum
+            .iter()
+            .map(|bin| bin.norm_sqr())
+            .fold(0.0, f64::max);
+        let bin_hz = self.settings.sample_rate as f64 / fft_size as f64;
+
+        spectrum
+            .iter()
+            .enumerate()
+            .map(|(k, bin)| {
+                let frequency = k.min(fft_size - k) as f64 * bin_hz;
+                let in_band = self.band_weight(frequency);
+                let epsilon = max_power
+                    * (IN_BAND_REGULARIZATION + (1.0 - in_band) * OUT_OF_BAND_REGULARIZATION);
+                bin.conj() / (bin.norm_sqr() + epsilon)
+            })
+            .collect()
+    }
+
+    /// 1 inside the swept band, easing to 0 just outside it
+    fn band_weight(&self, frequency: f64) -> f64 {
+        let octaves_outside = if frequency < self.settings.start_hz {
+            if frequency <= 0.0 {
+                return 0.0;
+            }
+            (self.settings.start_hz / frequency).log2()
+        } else if frequency > self.settings.end_hz {
+            (frequency / self.settings.end_hz).log2()
+        } else {
+            0.0
+        };
+
+        if octaves_outside >= BAND_TRANSITION_OCTAVES {
+            0.0
+        } else {
+            0.5 + 0.5 * (PI * octaves_outside / BAND_TRANSITION_OCTAVES).cos()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn short_sweep() -> LogSweep {
+        LogSweep::new(SweepSettings {
+            duration_secs: 2.0,
+            tail_secs: 0.5,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    /// Linear convolution of a mono signal with an FIR
+    fn convolve(signal: &[f32], fir: &[f32]) -> Vec<f32> {
+        let mut out = vec![0.0f32; signal.len() + fir.len() - 1];
+        for (i, &s) in signal.iter().enumerate() {
+            if s == 0.0 {
+                continue;
+            }
+            for (j, &h) in fir.iter().enumerate() {
+                out[i + j] += s * h;
+            }
+        }
+        out
+    }
+
+    /// Magnitude of an FIR at a frequency (dB)
+    fn magnitude_db(fir: &[f32], sample_rate: f64, frequency: f64) -> f64 {
+        let omega = 2.0 * PI * frequency / sample_rate;
+        let (re, im) = fir
+            .iter()
+            .enumerate()
+            .fold((0.0, 0.0), |(re, im), (n, &h)| {
+                let (sin, cos) = (omega * n as f64).sin_cos();
+                (re + h as f64 * cos, im - h as f64 * sin)
+            });
+        10.0 * (re * re + im * im).log10()
+    }
+
+    /// Record the sweep through an FIR, with some latency before it
+    fn record(sweep: &LogSweep, fir: &[f32], latency: usize) -> Vec<f32> {
+        let mut recording = vec![0.0; latency];
+        recording.extend(convolve(&sweep.playback_signal(), fir));
+        recording
+    }
+
+    #[test]
+    fn test_sweep_shape() {
+        let sweep = short_sweep();
+        let signal = sweep.signal();
+
+        assert_eq!(signal.len(), 96000);
+        assert_eq!(sweep.playback_signal().len(), 96000 + 24000);
+        assert!(signal[0].abs() < 1e-6, "sweep should fade in");
+        assert!(
+            signal[signal.len() - 1].abs() < 1e-6,
+            "sweep should fade out"
+        );
+
+        let peak = signal.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!(
+            (peak - 0.501).abs() < 0.01,
+            "peak {} should be -6 dBFS",
+            peak
+        );
+
+        assert!((sweep.frequency_at(0.0) - 20.0).abs() < 1e-9);
+        assert!((sweep.frequency_at(1.0) - 632.46).abs() < 0.01);
+        assert!((sweep.frequency_at(2.0) - 20000.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_invalid_settings() {
+        let above_nyquist = SweepSettings {
+            end_hz: 30000.0,
+            ..Default::default()
+        };
+        assert!(LogSweep::new(above_nyquist).is_err());
+
+        let falling = SweepSettings {
+            start_hz: 1000.0,
+            end_hz: 100.0,
+            ..Default::default()
+        };
+        assert!(LogSweep::new(falling).is_err());
+
+        let hot = SweepSettings {
+            level_db: 3.0,
+            ..Default::default()
+        };
+        assert!(LogSweep::new(hot).is_err());
+    }
+
+    #[test]
+    fn test_deconvolves_known_response() {
+        let sweep = short_sweep();
+        // A direct sound and two reflections
+        let mut fir = vec![0.0f32; 400];
+        fir[0] = 0.8;
+        fir[57] = -0.4;
+        fir[399] = 0.2;
+
+        let recording = record(&sweep, &fir, 1234);
+        let ir = sweep.deconvolve(&recording, 1, 48000, 4800).unwrap();
+
+        assert_eq!(ir.channels, 1);
+        assert_eq!(ir.frames(), 4800);
+
+        // Cut 2 ms before the direct sound
+        let peak = ir
+            .samples
+            .iter()
+            .enumerate()
+            .fold((0, 0.0f32), |best, (i, &s)| {
+                if s.abs() > best.1 {
+                    (i, s.abs())
+                } else {
+                    best
+                }
+            });
+        assert_eq!(peak.0, 96);
+
+        // Same magnitude response as the system across the band
+        for frequency in [50.0, 200.0, 1000.0, 5000.0, 15000.0] {
+            let expected = magnitude_db(&fir, 48000.0, frequency);
+            let measured = magnitude_db(&ir.samples, 48000.0, frequency);
+            assert!(
+                (expected - measured).abs() < 0.5,
+                "{} Hz: measured {:.2} dB, expected {:.2} dB",
+                frequency,
+                measured,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_keeps_channel_timing() {
+        let sweep = short_sweep();
+        let playback = sweep.playback_signal();
+
+        // The right microphone hears the sweep 25 samples later and quieter
+        let latency = 500;
+        let frames = latency + playback.len() + 100;
+        let mut recording = vec![0.0f32; frames * 2];
+        for (i, &s) in playback.iter().enumerate() {
+            recording[(latency + i) * 2] = s;
+            recording[(latency + i + 25) * 2 + 1] = s * 0.5;
+        }
+
+        let ir = sweep.deconvolve(&recording, 2, 48000, 2048).unwrap();
+        let peak_of = |channel: usize| {
+            ir.samples.iter().skip(channel).step_by(2).enumerate().fold(
+                (0, 0.0f32),
+                |best, (i, &s)| if s.abs() > best.1 { (i, s.abs()) } else { best },
+            )
+        };
+
+        let (left, left_level) = peak_of(0);
+        let (right, right_level) = peak_of(1);
+        assert_eq!(right - left, 25);
+        assert!((right_level / left_level - 0.5).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_rejects_harmonic_distortion() {
+        let sweep = short_sweep();
+        // A clipping speaker: the harmonics end up before the response
+        let mut recording = vec![0.0f32; 2000];
+        recording.extend(sweep.playback_signal().iter().map(|&s| (s * 1.5).tanh()));
+
+        let ir = sweep.deconvolve(&recording, 1, 48000, 4800).unwrap();
+        let peak = ir.samples[96].abs();
+        let residual = ir.samples[200..].iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!(
+            residual < peak * 0.01,
+            "distortion leaked into the response: {} vs peak {}",
+            residual,
+            peak
+        );
+    }
+
+    #[test]
+    fn test_recording_errors() {
+        let sweep = short_sweep();
+
+        let silence = vec![0.0f32; 150000];
+        assert!(matches!(
+            sweep.deconvolve(&silence, 1, 48000, 1024),
+            Err(MeasurementError::NoSignal)
+        ));
+        assert!(matches!(
+            sweep.deconvolve(&silence[..1000], 1, 48000, 1024),
+            Err(MeasurementError::RecordingTooShort)
+        ));
+        assert!(matches!(
+            sweep.deconvolve(&silence, 1, 44100, 1024),
+            Err(MeasurementError::SampleRateMismatch { .. })
+        ));
+        assert!(matches!(
+            sweep.deconvolve(&silence, 3, 48000, 1024),
+            Err(MeasurementError::InvalidChannelCount(3))
+        ));
+    }
+}
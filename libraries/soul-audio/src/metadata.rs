@@ -3,6 +3,7 @@
 //! This module provides comprehensive metadata extraction from audio files,
 //! including tag data and embedded album art.
 
+use crate::dsd::DsdFile;
 use crate::error::{AudioError, Result};
 use std::collections::HashMap;
 use std::path::Path;
@@ -275,16 +276,27 @@ impl AudioMetadata {
     }
 }
 
-/// Extract metadata from an audio file using Symphonia
+/// Extract metadata from an audio file
 ///
-/// This function probes the file and extracts all available metadata
-/// including tags and embedded artwork.
+/// This function picks the file's format from the
+/// [decoder registry](crate::formats::registry) and extracts all available
+/// metadata including tags and embedded artwork.
 pub fn extract_metadata(path: &Path) -> Result<AudioMetadata> {
+    crate::formats::registry().extract_metadata(path)
+}
+
+/// Extract metadata from a file Symphonia (or the DSD reader) can open
+pub(crate) fn extract_symphonia_metadata(path: &Path) -> Result<AudioMetadata> {
     // Check if file exists
     if !path.exists() {
         return Err(AudioError::FileNotFound(path.display().to_string()));
     }
 
+    // Symphonia does not read DSD containers
+    if DsdFile::is_dsd_path(path) {
+        return extract_dsd_metadata(path);
+    }
+
     // Open the file
     let file = std::fs::File::open(path).map_err(AudioError::Io)?;
 
@@ -496,6 +508,42 @@ fn extract_from_revision(
     }
 }
 
+/// Extract metadata from a DSF or DSDIFF file
+///
+/// Tags come from the file's ID3v2 tag (DSF, DSDIFF `ID3 ` chunk) or the
+/// DSDIFF `DIIN` chunk. Sample rate is the DSD rate and bit depth is 1.
+fn extract_dsd_metadata(path: &Path) -> Result<AudioMetadata> {
+    let file = DsdFile::open(path)?;
+    let info = file.info();
+    let tags = file.tags().clone();
+
+    let mut metadata = AudioMetadata {
+        title: tags.title,
+        artist: tags.artist,
+        album: tags.album,
+        album_artist: tags.album_artist,
+        year: tags.year,
+        track_number: tags.track_number,
+        disc_number: tags.disc_number,
+        genre: tags.genre,
+        composer: tags.composer,
+        duration_seconds: Some(info.duration().as_secs_f64()),
+        sample_rate: Some(info.sample_rate),
+        channels: Some(info.channels as u8),
+        bit_depth: Some(1),
+        bitrate: Some((info.sample_rate as u64 * info.channels as u64 / 1000) as u32),
+        ..Default::default()
+    };
+
+    if let Some((data, mime_type)) = tags.picture {
+        let art = AlbumArt::new(data, mime_type, AlbumArtType::FrontCover);
+        metadata.all_album_art.push(art.clone());
+        metadata.album_art = Some(art);
+    }
+
+    Ok(metadata)
+}
+
 /// Extract string value from a Symphonia tag Value
 fn extract_tag_value(value: &Value) -> String {
     match value {
@@ -530,7 +578,7 @@ fn extract_album_art(visual: &Visual) -> AlbumArt {
 }
 
 /// Parse year from various date formats
-fn parse_year(value: &str) -> Option<i32> {
+pub(crate) fn parse_year(value: &str) -> Option<i32> {
     // Try direct year parse (e.g., "2024")
     if let Ok(year) = value.parse::<i32>() {
         if (1000..=9999).contains(&year) {
@@ -551,7 +599,7 @@ fn parse_year(value: &str) -> Option<i32> {
 }
 
 /// Parse track/disc number which may be in "N" or "N/M" format
-fn parse_track_number(value: &str) -> (Option<u32>, Option<u32>) {
+pub(crate) fn parse_track_number(value: &str) -> (Option<u32>, Option<u32>) {
     if let Some(slash_pos) = value.find('/') {
         let num = value[..slash_pos].trim().parse().ok();
         let total = value[slash_pos + 1..].trim().parse().ok();
@@ -562,7 +610,7 @@ fn parse_track_number(value: &str) -> (Option<u32>, Option<u32>) {
 }
 
 /// Parse ReplayGain value (e.g., "-3.45 dB" or "+1.23 dB")
-fn parse_replaygain(value: &str) -> Option<f32> {
+pub(crate) fn parse_replaygain(value: &str) -> Option<f32> {
     let value = value.trim().to_lowercase();
     let value = value.trim_end_matches("db").trim();
     value.parse().ok()
@@ -0,0 +1,448 @@
+//! Musical key detection and notation
+//!
+//! The key is estimated from a chromagram: the spectrum of each frame is
+//! folded into the 12 pitch classes and summed over the whole track. The
+//! result is correlated with the Krumhansl-Kessler major and minor key
+//! profiles rotated to all 12 tonics, and the best of the 24 wins.
+
+use rustfft::{num_complex::Complex, Fft, FftPlanner};
+use std::fmt;
+use std::sync::Arc;
+
+/// Lowest and highest frequency folded into the chromagram (C2 to C7)
+const CHROMA_MIN_HZ: f32 = 65.4;
+const CHROMA_MAX_HZ: f32 = 2093.0;
+
+/// Krumhansl-Kessler probe tone profiles, from the tonic upwards
+const MAJOR_PROFILE: [f64; 12] = [
+    6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88,
+];
+const MINOR_PROFILE: [f64; 12] = [
+    6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17,
+];
+
+/// Pitch class names, sharps for display
+const PITCH_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+/// Major or minor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mode {
+    Major,
+    Minor,
+}
+
+/// A musical key: a tonic pitch class and a mode
+///
+/// Converts between standard notation ("F#m"), Camelot ("11A") and
+/// Open Key ("4m"), the notations DJ software writes to TKEY tags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MusicalKey {
+    /// Pitch class of the tonic (0 = C, 11 = B)
+    pub tonic: u8,
+    /// Major or minor
+    pub mode: Mode,
+}
+
+impl MusicalKey {
+    /// Create a key; the tonic wraps around the octave
+    pub fn new(tonic: u8, mode: Mode) -> Self {
+        Self {
+            tonic: tonic % 12,
+            mode,
+        }
+    }
+
+    /// Position on the Camelot wheel (1-12)
+    ///
+    /// Relative keys (C major and A minor) share a number.
+    pub fn camelot_number(&self) -> u8 {
+        let major_tonic = match self.mode {
+            Mode::Major => self.tonic,
+            Mode::Minor => (self.tonic + 3) % 12,
+        };
+        // C major is 8B; each step round the wheel is a fifth up
+        (7 + 7 * major_tonic as u32) as u8 % 12 + 1
+    }
+
+    /// Camelot notation ("8B" for C major, "8A" for A minor)
+    pub fn camelot(&self) -> String {
+        let letter = match self.mode {
+            Mode::Major => 'B',
+            Mode::Minor => 'A',
+        };
+        format!("{}{}", self.camelot_number(), letter)
+    }
+
+    /// Open Key notation ("1d" for C major, "1m" for A minor)
+    pub fn open_key(&self) -> String {
+        let number = (self.camelot_number() + 4) % 12 + 1;
+        let letter = match self.mode {
+            Mode::Major => 'd',
+            Mode::Minor => 'm',
+        };
+        format!("{}{}", number, letter)
+    }
+
+    /// Parse a key tag in standard, Camelot or Open Key notation
+    ///
+    /// Accepts "Am", "A minor", "Amin", "Bb", "C#maj", "8A", "08B" and
+    /// "1m". Returns `None` for anything else (including "o" for off-key).
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        Self::parse_wheel(s).or_else(|| Self::parse_standard(s))
+    }
+
+    /// Camelot ("8A") or Open Key ("1m") notation
+    fn parse_wheel(s: &str) -> Option<Self> {
+        let split = s.find(|c: char| !c.is_ascii_digit())?;
+        let number: u8 = s[..split].parse().ok()?;
+        if !(1..=12).contains(&number) {
+            return None;
+        }
+
+        let (camelot, mode) = match &s[split..] {
+            "A" | "a" => (number, Mode::Minor),
+            "B" | "b" => (number, Mode::Major),
+            "m" => ((number + 6) % 12 + 1, Mode::Minor),
+            "d" => ((number + 6) % 12 + 1, Mode::Major),
+            _ => return None,
+        };
+
+        // Invert camelot_number: major tonic = 7 * (camelot - 8) mod 12
+        let major_tonic = (7 * (camelot as u32 + 4)) % 12;
+        let tonic = match mode {
+            Mode::Major => major_tonic,
+            Mode::Minor => (major_tonic + 9) % 12,
+        };
+        Some(Self::new(tonic as u8, mode))
+    }
+
+    /// Standard notation ("F#m", "Eb minor", "C")
+    fn parse_standard(s: &str) -> Option<Self> {
+        let mut chars = s.chars();
+        let letter = chars.next()?.to_ascii_uppercase();
+        let natural = match letter {
+            'C' => 0,
+            'D' => 2,
+            'E' => 4,
+            'F' => 5,
+            'G' => 7,
+            'A' => 9,
+            'B' => 11,
+            _ => return None,
+        };
+
+        let rest = chars.as_str();
+        let (tonic, rest) = if let Some(rest) = rest.strip_prefix(['#', '♯']) {
+            (natural + 1, rest)
+        } else if let Some(rest) = rest.strip_prefix(['b', '♭']) {
+            (natural + 11, rest)
+        } else {
+            (natural, rest)
+        };
+
+        let mode = match rest.trim().to_ascii_lowercase().as_str() {
+            "" | "maj" | "major" | "dur" => Mode::Major,
+            "m" | "min" | "minor" | "moll" => Mode::Minor,
+            _ => return None,
+        };
+        Some(Self::new(tonic, mode))
+    }
+}
+
+impl fmt::Display for MusicalKey {
+    /// Standard notation ("C", "F#m")
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let suffix = match self.mode {
+            Mode::Major => "",
+            Mode::Minor => "m",
+        };
+        write!(f, "{}{}", PITCH_NAMES[self.tonic as usize], suffix)
+    }
+}
+
+/// Detected key of a track
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyEstimate {
+    /// Most likely key
+    pub key: MusicalKey,
+    /// How clearly it beat the runner-up (0.0-1.0)
+    pub confidence: f64,
+}
+
+/// Estimates the key of a track from its chromagram
+pub struct KeyDetector {
+    /// Number of channels
+    channels: usize,
+    /// FFT frame length
+    frame_len: usize,
+    fft: Arc<dyn Fft<f32>>,
+    /// Hann window
+    window: Vec<f32>,
+    /// Pitch class of each FFT bin in the chroma range
+    bin_pitch_class: Vec<Option<u8>>,
+    /// Mono samples of the current frame
+    frame: Vec<f32>,
+    /// FFT work buffer
+    spectrum: Vec<Complex<f32>>,
+    /// Accumulated energy per pitch class
+    chroma: [f64; 12],
+}
+
+impl KeyDetector {
+    /// Create a detector for interleaved audio
+    pub fn new(sample_rate: u32, channels: u32) -> Self {
+        // ~3 Hz bins so the lowest semitones are still resolved
+        let frame_len = (sample_rate as usize / 3).next_power_of_two().max(1024);
+        let fft = FftPlanner::new().plan_fft_forward(frame_len);
+        let window = (0..frame_len)
+            .map(|i| {
+                let phase = 2.0 * std::f32::consts::PI * i as f32 / frame_len as f32;
+                0.5 - 0.5 * phase.cos()
+            })
+            .collect();
+
+        let bin_hz = sample_rate as f32 / frame_len as f32;
+        let bin_pitch_class = (0..frame_len / 2)
+            .map(|bin| {
+                let hz = bin as f32 * bin_hz;
+                (CHROMA_MIN_HZ..=CHROMA_MAX_HZ).contains(&hz).then(|| {
+                    let midi = 69.0 + 12.0 * (hz / 440.0).log2();
+                    (midi.round() as i32).rem_euclid(12) as u8
+                })
+            })
+            .collect();
+
+        Self {
+            channels: channels.max(1) as usize,
+            frame_len,
+            fft,
+            window,
+            bin_pitch_class,
+            frame: Vec::with_capacity(frame_len),
+            spectrum: vec![Complex::default(); frame_len],
+            chroma: [0.0; 12],
+        }
+    }
+
+    /// Add interleaved f32 samples (-1.0 to 1.0)
+    pub fn add_frames(&mut self, samples: &[f32]) {
+        for frame in samples.chunks_exact(self.channels) {
+            self.frame
+                .push(frame.iter().sum::<f32>() / self.channels as f32);
+            if self.frame.len() == self.frame_len {
+                self.process_frame();
+                self.frame.clear();
+            }
+        }
+    }
+
+    fn process_frame(&mut self) {
+        for ((out, &sample), &w) in self.spectrum.iter_mut().zip(&self.frame).zip(&self.window) {
+            *out = Complex::new(sample * w, 0.0);
+        }
+        self.fft.process(&mut self.spectrum);
+
+        for (bin, pitch_class) in self.bin_pitch_class.iter().enumerate() {
+            if let Some(pc) = pitch_class {
+                self.chroma[*pc as usize] += self.spectrum[bin].norm_sqr() as f64;
+            }
+        }
+    }
+
+    /// Finish detection
+    ///
+    /// Returns `None` for silence or audio shorter than one analysis frame.
+    pub fn finalize(self) -> Option<KeyEstimate> {
+        let total: f64 = self.chroma.iter().sum();
+        if total <= f64::EPSILON {
+            return None;
+        }
+        // Compress so a few loud bass notes don't dominate
+        let chroma: Vec<f64> = self.chroma.iter().map(|e| (e / total).sqrt()).collect();
+
+        let mut scores: Vec<(MusicalKey, f64)> = (0..12u8)
+            .flat_map(|tonic| {
+                [
+                    (MusicalKey::new(tonic, Mode::Major), &MAJOR_PROFILE),
+                    (MusicalKey::new(tonic, Mode::Minor), &MINOR_PROFILE),
+                ]
+            })
+            .map(|(key, profile)| {
+                let rotated: Vec<f64> = (0..12)
+                    .map(|pc| profile[(pc + 12 - key.tonic as usize) % 12])
+                    .collect();
+                (key, correlation(&chroma, &rotated))
+            })
+            .collect();
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let (key, best) = scores[0];
+        let runner_up = scores[1].1;
+        if !best.is_finite() {
+            return None;
+        }
+
+        Some(KeyEstimate {
+            key,
+            // A gap of 0.2 in correlation is already a very clear result
+            confidence: ((best - runner_up) / 0.2).clamp(0.0, 1.0),
+        })
+    }
+}
+
+impl fmt::Debug for KeyDetector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyDetector")
+            .field("channels", &self.channels)
+            .field("frame_len", &self.frame_len)
+            .field("chroma", &self.chroma)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Pearson correlation of two equally long vectors
+fn correlation(a: &[f64], b: &[f64]) -> f64 {
+    let n = a.len() as f64;
+    let mean_a = a.iter().sum::<f64>() / n;
+    let mean_b = b.iter().sum::<f64>() / n;
+    let (mut cov, mut var_a, mut var_b) = (0.0, 0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        cov += (x - mean_a) * (y - mean_b);
+        var_a += (x - mean_a).powi(2);
+        var_b += (y - mean_b).powi(2);
+    }
+    cov / (var_a * var_b).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 44100;
+
+    /// Stereo chord progression, each chord given as MIDI notes
+    fn progression(chords: &[&[u8]], seconds_per_chord: f64) -> Vec<f32> {
+        let frames = (seconds_per_chord * SAMPLE_RATE as f64) as usize;
+        chords
+            .iter()
+            .flat_map(|notes| {
+                (0..frames).flat_map(move |i| {
+                    let t = i as f64 / SAMPLE_RATE as f64;
+                    let s = notes
+                        .iter()
+                        .map(|&note| {
+                            let hz = 440.0 * 2f64.powf((note as f64 - 69.0) / 12.0);
+                            (2.0 * std::f64::consts::PI * hz * t).sin()
+                        })
+                        .sum::<f64>() as f32
+                        * 0.2;
+                    [s, s]
+                })
+            })
+            .collect()
+    }
+
+    fn detect(samples: &[f32]) -> Option<KeyEstimate> {
+        let mut detector = KeyDetector::new(SAMPLE_RATE, 2);
+        for chunk in samples.chunks(4096) {
+            detector.add_frames(chunk);
+        }
+        detector.finalize()
+    }
+
+    #[test]
+    fn test_camelot_and_open_key() {
+        let c_major = MusicalKey::new(0, Mode::Major);
+        assert_eq!(c_major.camelot(), "8B");
+        assert_eq!(c_major.open_key(), "1d");
+
+        let a_minor = MusicalKey::new(9, Mode::Minor);
+        assert_eq!(a_minor.camelot(), "8A");
+        assert_eq!(a_minor.open_key(), "1m");
+
+        assert_eq!(MusicalKey::new(4, Mode::Major).camelot(), "12B");
+        assert_eq!(MusicalKey::new(11, Mode::Major).camelot(), "1B");
+        assert_eq!(MusicalKey::new(6, Mode::Minor).camelot(), "11A");
+        assert_eq!(MusicalKey::new(6, Mode::Minor).open_key(), "4m");
+        assert_eq!(MusicalKey::new(6, Mode::Minor).to_string(), "F#m");
+    }
+
+    #[test]
+    fn test_notations_round_trip() {
+        for tonic in 0..12 {
+            for mode in [Mode::Major, Mode::Minor] {
+                let key = MusicalKey::new(tonic, mode);
+                assert_eq!(MusicalKey::parse(&key.camelot()), Some(key));
+                assert_eq!(MusicalKey::parse(&key.open_key()), Some(key));
+                assert_eq!(MusicalKey::parse(&key.to_string()), Some(key));
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_tag_spellings() {
+        let e_flat_minor = MusicalKey::new(3, Mode::Minor);
+        assert_eq!(MusicalKey::parse("Ebm"), Some(e_flat_minor));
+        assert_eq!(MusicalKey::parse("D#m"), Some(e_flat_minor));
+        assert_eq!(MusicalKey::parse("eb minor"), Some(e_flat_minor));
+        assert_eq!(MusicalKey::parse(" 2A "), Some(e_flat_minor));
+        assert_eq!(MusicalKey::parse("02A"), Some(e_flat_minor));
+        assert_eq!(
+            MusicalKey::parse("Cmaj"),
+            Some(MusicalKey::new(0, Mode::Major))
+        );
+        assert_eq!(
+            MusicalKey::parse("B♭"),
+            Some(MusicalKey::new(10, Mode::Major))
+        );
+
+        assert_eq!(MusicalKey::parse("o"), None);
+        assert_eq!(MusicalKey::parse("13A"), None);
+        assert_eq!(MusicalKey::parse("H"), None);
+        assert_eq!(MusicalKey::parse(""), None);
+    }
+
+    #[test]
+    fn test_detects_major_key() {
+        // I - IV - V - I in C major
+        let c = [48, 60, 64, 67];
+        let f = [53, 60, 65, 69];
+        let g = [55, 59, 62, 67];
+        let samples = progression(&[&c, &f, &g, &c], 2.0);
+
+        let estimate = detect(&samples).unwrap();
+        assert_eq!(
+            estimate.key,
+            MusicalKey::new(0, Mode::Major),
+            "{:?}",
+            estimate
+        );
+        assert!(estimate.confidence > 0.0);
+    }
+
+    #[test]
+    fn test_detects_minor_key() {
+        // i - iv - V - i in A minor (E major has the raised G#)
+        let am = [45, 57, 60, 64];
+        let dm = [50, 57, 62, 65];
+        let e = [52, 56, 59, 64];
+        let samples = progression(&[&am, &dm, &e, &am], 2.0);
+
+        let estimate = detect(&samples).unwrap();
+        assert_eq!(
+            estimate.key,
+            MusicalKey::new(9, Mode::Minor),
+            "{:?}",
+            estimate
+        );
+    }
+
+    #[test]
+    fn test_silence_has_no_key() {
+        assert_eq!(detect(&vec![0.0; SAMPLE_RATE as usize * 4]), None);
+        assert_eq!(detect(&[]), None);
+    }
+}
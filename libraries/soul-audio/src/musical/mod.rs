@@ -0,0 +1,104 @@
+//! Tempo and key detection for library tracks
+//!
+//! [`MusicalAnalyzer`] runs a [`TempoDetector`] and a [`KeyDetector`] over
+//! the same decoded audio. Keys convert to and from the notations DJ
+//! software writes to tags: standard ("F#m"), Camelot ("11A") and Open
+//! Key ("4m").
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use soul_audio::musical::analyze_file;
+//! use std::path::Path;
+//!
+//! # fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let analysis = analyze_file(Path::new("/music/track.flac"))?;
+//! if let (Some(tempo), Some(key)) = (analysis.tempo, analysis.key) {
+//!     println!("{:.1} BPM in {} ({})", tempo.bpm, key.key, key.key.camelot());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+mod key;
+mod tempo;
+
+pub use key::{KeyDetector, KeyEstimate, Mode, MusicalKey};
+pub use tempo::{TempoDetector, TempoEstimate, MAX_BPM, MIN_BPM};
+
+use crate::{AudioError, Result, SymphoniaDecoder};
+use soul_core::AudioDecoder;
+use std::path::Path;
+use std::time::Duration;
+
+/// Tempo and key of a track
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct MusicalAnalysis {
+    /// Detected tempo (None = no clear pulse)
+    pub tempo: Option<TempoEstimate>,
+    /// Detected key (None = silent or too short)
+    pub key: Option<KeyEstimate>,
+}
+
+/// Detects tempo and key in one pass over the audio
+#[derive(Debug)]
+pub struct MusicalAnalyzer {
+    tempo: TempoDetector,
+    key: KeyDetector,
+}
+
+impl MusicalAnalyzer {
+    /// Create an analyzer for interleaved audio
+    pub fn new(sample_rate: u32, channels: u32) -> Self {
+        Self {
+            tempo: TempoDetector::new(sample_rate, channels),
+            key: KeyDetector::new(sample_rate, channels),
+        }
+    }
+
+    /// Add interleaved f32 samples (-1.0 to 1.0)
+    pub fn add_frames(&mut self, samples: &[f32]) {
+        self.tempo.add_frames(samples);
+        self.key.add_frames(samples);
+    }
+
+    /// Finish the analysis
+    pub fn finalize(self) -> MusicalAnalysis {
+        MusicalAnalysis {
+            tempo: self.tempo.finalize(),
+            key: self.key.finalize(),
+        }
+    }
+}
+
+/// Decode a file and detect its tempo and key
+pub fn analyze_file(path: &Path) -> Result<MusicalAnalysis> {
+    analyze_file_range(path, Duration::ZERO, None)
+}
+
+/// Decode a file and detect the tempo and key of a range of it
+///
+/// Used for tracks that share a file, such as CUE sheet tracks.
+/// `end` of `None` means the end of the file.
+pub fn analyze_file_range(
+    path: &Path,
+    start: Duration,
+    end: Option<Duration>,
+) -> Result<MusicalAnalysis> {
+    let mut decoder = SymphoniaDecoder::new();
+    let audio = decoder
+        .decode(path)
+        .map_err(|e| AudioError::DecodeError(e.to_string()))?;
+
+    let sample_rate = audio.format.sample_rate.as_hz();
+    let channels = audio.format.channels as usize;
+    let to_sample = |time: Duration| {
+        ((time.as_secs_f64() * sample_rate as f64) as usize * channels).min(audio.samples.len())
+    };
+    let first = to_sample(start);
+    let last = end.map_or(audio.samples.len(), to_sample).max(first);
+
+    let mut analyzer = MusicalAnalyzer::new(sample_rate, channels as u32);
+    analyzer.add_frames(&audio.samples[first..last]);
+    Ok(analyzer.finalize())
+}
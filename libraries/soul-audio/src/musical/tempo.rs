@@ -0,0 +1,281 @@
+//! Tempo (BPM) detection
+//!
+//! An onset strength envelope is built from the spectral flux of short
+//! frames: the summed increase in magnitude from one frame to the next,
+//! which peaks on drum hits and note attacks. Magnitudes are left
+//! uncompressed so accented beats outweigh off-beats. The envelope's
+//! autocorrelation is then scored for every candidate tempo at the beat
+//! period and its first multiples.
+//!
+//! Half tempo always scores as well as the real one (every other beat
+//! still lines up), so the fastest candidate that scores nearly as well
+//! as the best wins. Off-beat relations such as 2/3 tempo score clearly
+//! lower because they line beats up with off-beats.
+
+use rustfft::{num_complex::Complex, Fft, FftPlanner};
+use std::fmt;
+use std::sync::Arc;
+
+/// Slowest and fastest tempo reported
+pub const MIN_BPM: f64 = 60.0;
+pub const MAX_BPM: f64 = 200.0;
+
+/// Step of the tempo search
+const BPM_STEP: f64 = 0.05;
+
+/// Beat period multiples scored for each candidate tempo
+const PERIODS: usize = 4;
+
+/// A faster tempo wins if it scores at least this fraction of the best
+const FASTER_TEMPO_TOLERANCE: f64 = 0.9;
+
+/// Onset frame length and hop in seconds (~23 ms and ~11.6 ms at 44.1 kHz)
+const FRAME_SECONDS: f64 = 0.023;
+const HOP_SECONDS: f64 = 0.0116;
+
+/// Detected tempo of a track
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TempoEstimate {
+    /// Beats per minute
+    pub bpm: f64,
+    /// Strength of the beat periodicity (0.0-1.0)
+    pub confidence: f64,
+}
+
+/// Estimates the tempo of a track from its onset envelope
+pub struct TempoDetector {
+    /// Number of channels
+    channels: usize,
+    /// Onset envelope rate in Hz
+    envelope_rate: f64,
+    /// FFT frame length
+    frame_len: usize,
+    /// Samples between frames
+    hop: usize,
+    fft: Arc<dyn Fft<f32>>,
+    /// Hann window
+    window: Vec<f32>,
+    /// Mono samples not yet consumed by a frame
+    buffer: Vec<f32>,
+    /// FFT work buffer
+    spectrum: Vec<Complex<f32>>,
+    /// Magnitude of the previous frame
+    previous: Vec<f32>,
+    /// Onset strength per hop
+    envelope: Vec<f32>,
+}
+
+impl TempoDetector {
+    /// Create a detector for interleaved audio
+    pub fn new(sample_rate: u32, channels: u32) -> Self {
+        let frame_len = ((sample_rate as f64 * FRAME_SECONDS) as usize)
+            .next_power_of_two()
+            .max(256);
+        let hop = ((sample_rate as f64 * HOP_SECONDS) as usize)
+            .next_power_of_two()
+            .clamp(64, frame_len);
+        let fft = FftPlanner::new().plan_fft_forward(frame_len);
+        let window = (0..frame_len)
+            .map(|i| {
+                let phase = 2.0 * std::f32::consts::PI * i as f32 / frame_len as f32;
+                0.5 - 0.5 * phase.cos()
+            })
+            .collect();
+
+        Self {
+            channels: channels.max(1) as usize,
+            envelope_rate: sample_rate as f64 / hop as f64,
+            frame_len,
+            hop,
+            fft,
+            window,
+            buffer: Vec::with_capacity(frame_len * 2),
+            spectrum: vec![Complex::default(); frame_len],
+            previous: vec![0.0; frame_len / 2],
+            envelope: Vec::new(),
+        }
+    }
+
+    /// Add interleaved f32 samples (-1.0 to 1.0)
+    pub fn add_frames(&mut self, samples: &[f32]) {
+        for frame in samples.chunks_exact(self.channels) {
+            self.buffer
+                .push(frame.iter().sum::<f32>() / self.channels as f32);
+            if self.buffer.len() == self.frame_len {
+                self.process_frame();
+                self.buffer.drain(..self.hop);
+            }
+        }
+    }
+
+    fn process_frame(&mut self) {
+        for ((out, &sample), &w) in self.spectrum.iter_mut().zip(&self.buffer).zip(&self.window) {
+            *out = Complex::new(sample * w, 0.0);
+        }
+        self.fft.process(&mut self.spectrum);
+
+        let mut flux = 0.0;
+        for (bin, previous) in self.previous.iter_mut().enumerate() {
+            let magnitude = self.spectrum[bin].norm();
+            flux += (magnitude - *previous).max(0.0);
+            *previous = magnitude;
+        }
+        self.envelope.push(flux);
+    }
+
+    /// Finish detection
+    ///
+    /// Needs a few seconds of audio with a discernible pulse; returns
+    /// `None` for silence, very short input or audio without onsets.
+    pub fn finalize(self) -> Option<TempoEstimate> {
+        // Longest lag scored: PERIODS beats at the slowest tempo
+        let max_lag = (PERIODS as f64 * 60.0 / MIN_BPM * self.envelope_rate).ceil() as usize + 2;
+        if self.envelope.len() < max_lag * 2 {
+            return None;
+        }
+
+        let mean =
+            self.envelope.iter().map(|&v| v as f64).sum::<f64>() / self.envelope.len() as f64;
+        let envelope: Vec<f64> = self.envelope.iter().map(|&v| v as f64 - mean).collect();
+
+        // Unbiased autocorrelation, normalised to lag 0
+        let n = envelope.len();
+        let mut autocorrelation: Vec<f64> = (0..=max_lag)
+            .map(|lag| {
+                let sum: f64 = envelope[..n - lag]
+                    .iter()
+                    .zip(&envelope[lag..])
+                    .map(|(a, b)| a * b)
+                    .sum();
+                sum / (n - lag) as f64
+            })
+            .collect();
+        let energy = autocorrelation[0];
+        if energy <= f64::EPSILON {
+            return None;
+        }
+        for v in &mut autocorrelation {
+            *v /= energy;
+        }
+
+        let at = |lag: f64| {
+            let i = lag.floor() as usize;
+            let frac = lag - i as f64;
+            autocorrelation[i] * (1.0 - frac) + autocorrelation[i + 1] * frac
+        };
+
+        let steps = ((MAX_BPM - MIN_BPM) / BPM_STEP).round() as usize;
+        let scores: Vec<(f64, f64)> = (0..=steps)
+            .map(|step| {
+                let bpm = MIN_BPM + step as f64 * BPM_STEP;
+                let period = 60.0 / bpm * self.envelope_rate;
+                let periodicity =
+                    (1..=PERIODS).map(|k| at(k as f64 * period)).sum::<f64>() / PERIODS as f64;
+                (bpm, periodicity)
+            })
+            .collect();
+
+        let best = scores.iter().map(|&(_, p)| p).fold(f64::MIN, f64::max);
+        if best <= 0.0 {
+            return None;
+        }
+
+        // Fastest local peak that scores close to the best
+        let (bpm, periodicity) = (1..scores.len() - 1)
+            .rev()
+            .map(|i| (scores[i - 1].1, scores[i], scores[i + 1].1))
+            .find(|&(before, (_, p), after)| {
+                p >= before && p >= after && p >= best * FASTER_TEMPO_TOLERANCE
+            })
+            .map(|(_, peak, _)| peak)?;
+
+        Some(TempoEstimate {
+            bpm: (bpm * 100.0).round() / 100.0,
+            confidence: periodicity.clamp(0.0, 1.0),
+        })
+    }
+}
+
+impl fmt::Debug for TempoDetector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TempoDetector")
+            .field("channels", &self.channels)
+            .field("frame_len", &self.frame_len)
+            .field("hop", &self.hop)
+            .field("envelope_len", &self.envelope.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 44100;
+
+    /// Stereo drum-like pattern: a decaying noise burst on every beat,
+    /// with a quieter one on the off-beats
+    fn beats(bpm: f64, seconds: f64) -> Vec<f32> {
+        let frames = (seconds * SAMPLE_RATE as f64) as usize;
+        let beat = 60.0 / bpm * SAMPLE_RATE as f64;
+        let mut seed = 0x2545_f491u32;
+        (0..frames)
+            .flat_map(|i| {
+                seed ^= seed << 13;
+                seed ^= seed >> 17;
+                seed ^= seed << 5;
+                let noise = seed as f32 / u32::MAX as f32 * 2.0 - 1.0;
+
+                let position = i as f64 / beat;
+                let since_beat = position.fract() * beat / SAMPLE_RATE as f64;
+                let since_half = (position + 0.5).fract() * beat / SAMPLE_RATE as f64;
+                let envelope = 0.8 * (-since_beat * 40.0).exp() + 0.3 * (-since_half * 60.0).exp();
+                let s = noise * envelope as f32;
+                [s, s]
+            })
+            .collect()
+    }
+
+    fn detect(samples: &[f32]) -> Option<TempoEstimate> {
+        let mut detector = TempoDetector::new(SAMPLE_RATE, 2);
+        for chunk in samples.chunks(4096) {
+            detector.add_frames(chunk);
+        }
+        detector.finalize()
+    }
+
+    #[test]
+    fn test_detects_common_tempos() {
+        for bpm in [90.0, 128.0, 174.0] {
+            let estimate = detect(&beats(bpm, 30.0)).unwrap();
+            assert!(
+                (estimate.bpm - bpm).abs() < 0.5,
+                "expected {} BPM, got {:?}",
+                bpm,
+                estimate
+            );
+            assert!(estimate.confidence > 0.3, "{:?}", estimate);
+        }
+    }
+
+    #[test]
+    fn test_noise_has_low_confidence() {
+        let mut seed = 1u32;
+        let noise: Vec<f32> = (0..SAMPLE_RATE as usize * 2 * 20)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (seed >> 8) as f32 / (1u32 << 24) as f32 - 0.5
+            })
+            .collect();
+
+        if let Some(estimate) = detect(&noise) {
+            assert!(estimate.confidence < 0.2, "{:?}", estimate);
+        }
+    }
+
+    #[test]
+    fn test_silence_and_short_input() {
+        assert_eq!(detect(&vec![0.0; SAMPLE_RATE as usize * 2 * 10]), None);
+        assert_eq!(detect(&beats(120.0, 2.0)), None);
+    }
+}
@@ -2,6 +2,7 @@
 //!
 //! Base interface that all audio pipeline components must implement.
 
+use crate::channels::ChannelLayout;
 use std::any::Any;
 
 /// Information about a pipeline component for introspection
@@ -43,6 +44,23 @@ pub trait PipelineComponent: Send {
     /// - Deterministic execution time
     fn process(&mut self, buffer: &mut [f32], sample_rate: u32);
 
+    /// Whether this component can process the given channel layout
+    ///
+    /// Default: stereo only.
+    fn supports_layout(&self, layout: ChannelLayout) -> bool {
+        layout == ChannelLayout::Stereo
+    }
+
+    /// Process an interleaved buffer in the given channel layout
+    ///
+    /// Default implementation forwards stereo buffers to `process()` and leaves
+    /// other layouts untouched.
+    fn process_layout(&mut self, buffer: &mut [f32], sample_rate: u32, layout: ChannelLayout) {
+        if layout == ChannelLayout::Stereo {
+            self.process(buffer, sample_rate);
+        }
+    }
+
     /// Reset component state (e.g., when seeking or changing tracks)
     fn reset(&mut self);
 
@@ -52,6 +70,15 @@ pub trait PipelineComponent: Send {
     /// Check if component is enabled
     fn is_enabled(&self) -> bool;
 
+    /// Processing delay in frames
+    ///
+    /// Components that delay the signal (linear-phase filters, look-ahead
+    /// plugins) report it so playback position can be compensated.
+    /// Default: zero.
+    fn latency_samples(&self) -> usize {
+        0
+    }
+
     /// Get component information
     fn info(&self) -> PipelineComponentInfo;
 
@@ -91,6 +118,24 @@ macro_rules! impl_pipeline_component {
                 <Self as $crate::effects::AudioEffect>::process(self, buffer, sample_rate)
             }
 
+            fn supports_layout(&self, layout: $crate::channels::ChannelLayout) -> bool {
+                <Self as $crate::effects::AudioEffect>::supports_layout(self, layout)
+            }
+
+            fn process_layout(
+                &mut self,
+                buffer: &mut [f32],
+                sample_rate: u32,
+                layout: $crate::channels::ChannelLayout,
+            ) {
+                <Self as $crate::effects::AudioEffect>::process_layout(
+                    self,
+                    buffer,
+                    sample_rate,
+                    layout,
+                )
+            }
+
             fn reset(&mut self) {
                 <Self as $crate::effects::AudioEffect>::reset(self)
             }
@@ -103,6 +148,10 @@ macro_rules! impl_pipeline_component {
                 <Self as $crate::effects::AudioEffect>::is_enabled(self)
             }
 
+            fn latency_samples(&self) -> usize {
+                <Self as $crate::effects::AudioEffect>::latency_samples(self)
+            }
+
             fn info(&self) -> $crate::pipeline::PipelineComponentInfo {
                 $crate::pipeline::PipelineComponentInfo {
                     type_id: $type_id,
@@ -3,10 +3,13 @@
 //! This module provides the bridge between the effects module and the pipeline abstraction.
 
 use super::component::{PipelineComponent, PipelineComponentInfo};
+use super::registry::ParametricEqBandParams;
+use crate::channels::ChannelLayout;
 use crate::effects::{
     AudioEffect, Compressor, CompressorSettings, ConvolutionEngine, Crossfeed, CrossfeedPreset,
-    CrossfeedSettings, EqBand, GraphicEq, Limiter, LimiterSettings, ParametricEq, StereoEnhancer,
-    StereoSettings,
+    CrossfeedSettings, DynamicEq, DynamicEqBand, EqBand, EqPhaseMode, GraphicEq, Limiter,
+    LimiterSettings, MultibandCompressor, MultibandCompressorSettings, ParametricEq,
+    StereoEnhancer, StereoSettings,
 };
 use std::any::Any;
 
@@ -29,6 +32,10 @@ impl PipelineComponent for ParametricEq {
         AudioEffect::is_enabled(self)
     }
 
+    fn latency_samples(&self) -> usize {
+        AudioEffect::latency_samples(self)
+    }
+
     fn info(&self) -> PipelineComponentInfo {
         PipelineComponentInfo {
             type_id: "parametric_eq",
@@ -50,6 +57,12 @@ impl PipelineComponent for ParametricEq {
         if let Some(bands) = params.downcast_ref::<Vec<EqBand>>() {
             self.set_bands(bands.clone());
             true
+        } else if let Some(update) = params.downcast_ref::<ParametricEqBandParams>() {
+            self.set_band(update.index, update.band);
+            true
+        } else if let Some(mode) = params.downcast_ref::<EqPhaseMode>() {
+            self.set_phase_mode(*mode);
+            true
         } else {
             false
         }
@@ -81,6 +94,10 @@ impl PipelineComponent for GraphicEq {
         AudioEffect::is_enabled(self)
     }
 
+    fn latency_samples(&self) -> usize {
+        AudioEffect::latency_samples(self)
+    }
+
     fn info(&self) -> PipelineComponentInfo {
         PipelineComponentInfo {
             type_id: "graphic_eq",
@@ -109,6 +126,9 @@ impl PipelineComponent for GraphicEq {
                 self.set_band_gain(i, gain);
             }
             true
+        } else if let Some(mode) = params.downcast_ref::<EqPhaseMode>() {
+            self.set_phase_mode(*mode);
+            true
         } else {
             false
         }
@@ -122,6 +142,14 @@ impl PipelineComponent for Compressor {
         AudioEffect::process(self, buffer, sample_rate)
     }
 
+    fn supports_layout(&self, layout: ChannelLayout) -> bool {
+        AudioEffect::supports_layout(self, layout)
+    }
+
+    fn process_layout(&mut self, buffer: &mut [f32], sample_rate: u32, layout: ChannelLayout) {
+        AudioEffect::process_layout(self, buffer, sample_rate, layout);
+    }
+
     fn reset(&mut self) {
         AudioEffect::reset(self)
     }
@@ -168,6 +196,14 @@ impl PipelineComponent for Limiter {
         AudioEffect::process(self, buffer, sample_rate)
     }
 
+    fn supports_layout(&self, layout: ChannelLayout) -> bool {
+        AudioEffect::supports_layout(self, layout)
+    }
+
+    fn process_layout(&mut self, buffer: &mut [f32], sample_rate: u32, layout: ChannelLayout) {
+        AudioEffect::process_layout(self, buffer, sample_rate, layout);
+    }
+
     fn reset(&mut self) {
         AudioEffect::reset(self)
     }
@@ -373,9 +409,118 @@ impl PipelineComponent for ConvolutionEngine {
     }
 }
 
+// ===== MultibandCompressor =====
+
+impl PipelineComponent for MultibandCompressor {
+    fn process(&mut self, buffer: &mut [f32], sample_rate: u32) {
+        AudioEffect::process(self, buffer, sample_rate);
+    }
+
+    fn supports_layout(&self, layout: ChannelLayout) -> bool {
+        AudioEffect::supports_layout(self, layout)
+    }
+
+    fn process_layout(&mut self, buffer: &mut [f32], sample_rate: u32, layout: ChannelLayout) {
+        AudioEffect::process_layout(self, buffer, sample_rate, layout);
+    }
+
+    fn reset(&mut self) {
+        AudioEffect::reset(self);
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        AudioEffect::set_enabled(self, enabled);
+    }
+
+    fn is_enabled(&self) -> bool {
+        AudioEffect::is_enabled(self)
+    }
+
+    fn info(&self) -> PipelineComponentInfo {
+        PipelineComponentInfo {
+            type_id: "multiband_compressor",
+            display_name: "Multiband Compressor",
+            description: "3-5 band compressor with Linkwitz-Riley crossovers",
+            supports_in_place_update: true,
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn update_parameters(&mut self, params: &dyn Any) -> bool {
+        if let Some(settings) = params.downcast_ref::<MultibandCompressorSettings>() {
+            self.set_settings(settings.clone());
+            true
+        } else {
+            false
+        }
+    }
+}
+
+// ===== DynamicEq =====
+
+impl PipelineComponent for DynamicEq {
+    fn process(&mut self, buffer: &mut [f32], sample_rate: u32) {
+        AudioEffect::process(self, buffer, sample_rate);
+    }
+
+    fn supports_layout(&self, layout: ChannelLayout) -> bool {
+        AudioEffect::supports_layout(self, layout)
+    }
+
+    fn process_layout(&mut self, buffer: &mut [f32], sample_rate: u32, layout: ChannelLayout) {
+        AudioEffect::process_layout(self, buffer, sample_rate, layout);
+    }
+
+    fn reset(&mut self) {
+        AudioEffect::reset(self);
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        AudioEffect::set_enabled(self, enabled);
+    }
+
+    fn is_enabled(&self) -> bool {
+        AudioEffect::is_enabled(self)
+    }
+
+    fn info(&self) -> PipelineComponentInfo {
+        PipelineComponentInfo {
+            type_id: "dynamic_eq",
+            display_name: "Dynamic EQ",
+            description: "Peaking bands driven by a sidechain threshold",
+            supports_in_place_update: true,
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn update_parameters(&mut self, params: &dyn Any) -> bool {
+        if let Some(bands) = params.downcast_ref::<Vec<DynamicEqBand>>() {
+            self.set_bands(bands.clone());
+            true
+        } else {
+            false
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::effects::FilterType;
 
     #[test]
     fn test_parametric_eq_as_pipeline_component() {
@@ -388,6 +533,16 @@ mod tests {
         // Test update
         let bands = vec![EqBand::peaking(1000.0, 3.0, 1.0)];
         assert!(comp.update_parameters(&bands));
+
+        let notch = ParametricEqBandParams {
+            index: 0,
+            band: EqBand::notch(60.0, 8.0),
+        };
+        assert!(comp.update_parameters(&notch));
+
+        assert!(comp.update_parameters(&EqPhaseMode::LinearPhase));
+        assert_eq!(eq.phase_mode(), EqPhaseMode::LinearPhase);
+        assert_eq!(eq.get_band(0).unwrap().filter_type(), FilterType::Notch);
     }
 
     #[test]
@@ -408,6 +563,21 @@ mod tests {
         assert!(pipeline_comp.update_parameters(&settings));
     }
 
+    #[test]
+    fn test_dynamics_as_pipeline_components() {
+        let mut multiband = MultibandCompressor::new();
+        let comp: &mut dyn PipelineComponent = &mut multiband;
+        assert_eq!(comp.info().type_id, "multiband_compressor");
+        assert!(comp.update_parameters(&MultibandCompressorSettings::five_band()));
+        assert_eq!(multiband.band_count(), 5);
+
+        let mut dynamic_eq = DynamicEq::new();
+        let comp: &mut dyn PipelineComponent = &mut dynamic_eq;
+        assert_eq!(comp.info().type_id, "dynamic_eq");
+        assert!(comp.update_parameters(&vec![DynamicEqBand::de_esser()]));
+        assert_eq!(dynamic_eq.bands().len(), 1);
+    }
+
     #[test]
     fn test_convolution_no_in_place_for_ir() {
         let info = PipelineComponentInfo {
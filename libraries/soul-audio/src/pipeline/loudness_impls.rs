@@ -3,6 +3,7 @@
 //! This module provides the bridge between soul-loudness types and the pipeline abstraction.
 
 use super::component::{PipelineComponent, PipelineComponentInfo};
+use crate::channels::ChannelLayout;
 use soul_loudness::headroom::{HeadroomManager, HeadroomMode};
 use std::any::Any;
 
@@ -76,6 +77,15 @@ impl PipelineComponent for HeadroomManager {
         self.process_with_sample_rate(buffer, sample_rate)
     }
 
+    fn supports_layout(&self, _layout: ChannelLayout) -> bool {
+        // Pure gain stage, independent of channel count
+        true
+    }
+
+    fn process_layout(&mut self, buffer: &mut [f32], sample_rate: u32, _layout: ChannelLayout) {
+        self.process_with_sample_rate(buffer, sample_rate);
+    }
+
     fn reset(&mut self) {
         HeadroomManager::reset(self)
     }
@@ -5,6 +5,7 @@
 //! or updating effects.
 
 use super::component::PipelineComponent;
+use crate::effects::EqBand;
 use std::any::Any;
 use std::collections::HashMap;
 use std::fmt::Debug;
@@ -181,6 +182,10 @@ impl EffectRegistry {
                         eq.set_bands(bands.clone());
                         return true;
                     }
+                    if let Some(update) = params.downcast_ref::<ParametricEqBandParams>() {
+                        eq.set_band(update.index, update.band);
+                        return true;
+                    }
                 }
                 false
             }),
@@ -266,6 +271,54 @@ impl EffectRegistry {
             supports_in_place_update: true,
         });
 
+        // Multiband Compressor
+        self.register(EffectFactory {
+            type_id: "multiband_compressor",
+            display_name: "Multiband Compressor",
+            create: Arc::new(|params| {
+                if let Some(settings) = params.downcast_ref::<MultibandCompressorSettings>() {
+                    Some(Box::new(MultibandCompressor::with_settings(
+                        settings.clone(),
+                    )))
+                } else {
+                    Some(Box::new(MultibandCompressor::new()))
+                }
+            }),
+            update: Arc::new(|effect, params| {
+                if let Some(comp) = effect.as_any_mut().downcast_mut::<MultibandCompressor>() {
+                    if let Some(settings) = params.downcast_ref::<MultibandCompressorSettings>() {
+                        comp.set_settings(settings.clone());
+                        return true;
+                    }
+                }
+                false
+            }),
+            supports_in_place_update: true,
+        });
+
+        // Dynamic EQ
+        self.register(EffectFactory {
+            type_id: "dynamic_eq",
+            display_name: "Dynamic EQ",
+            create: Arc::new(|params| {
+                if let Some(bands) = params.downcast_ref::<Vec<DynamicEqBand>>() {
+                    Some(Box::new(DynamicEq::with_bands(bands.clone())))
+                } else {
+                    Some(Box::new(DynamicEq::new()))
+                }
+            }),
+            update: Arc::new(|effect, params| {
+                if let Some(eq) = effect.as_any_mut().downcast_mut::<DynamicEq>() {
+                    if let Some(bands) = params.downcast_ref::<Vec<DynamicEqBand>>() {
+                        eq.set_bands(bands.clone());
+                        return true;
+                    }
+                }
+                false
+            }),
+            supports_in_place_update: true,
+        });
+
         // Stereo Enhancer
         self.register(EffectFactory {
             type_id: "stereo_enhancer",
@@ -407,6 +460,19 @@ impl EffectRegistry {
     }
 }
 
+/// Single-band update for a Parametric EQ
+///
+/// Replaces one band (any [`FilterType`](crate::effects::FilterType)) without
+/// resending the whole band list. An index past the current band count
+/// grows the EQ, as with `ParametricEq::set_band`.
+#[derive(Debug, Clone, Copy)]
+pub struct ParametricEqBandParams {
+    /// Band index (0 to MAX_EQ_BANDS-1)
+    pub index: usize,
+    /// New band configuration
+    pub band: EqBand,
+}
+
 /// Parameters for Graphic EQ creation/updates
 #[derive(Debug, Clone)]
 pub struct GraphicEqParams {
@@ -436,7 +502,10 @@ impl Clone for EffectRegistry {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::effects::{CompressorSettings, EqBand, LimiterSettings, StereoSettings};
+    use crate::effects::{
+        CompressorSettings, DynamicEqBand, FilterAlignment, FilterSlope, LimiterSettings,
+        MultibandCompressorSettings, ParametricEq,
+    };
     use soul_loudness::headroom::HeadroomMode;
 
     #[test]
@@ -447,6 +516,8 @@ mod tests {
         assert!(registry.is_registered("graphic_eq"));
         assert!(registry.is_registered("compressor"));
         assert!(registry.is_registered("limiter"));
+        assert!(registry.is_registered("multiband_compressor"));
+        assert!(registry.is_registered("dynamic_eq"));
         assert!(registry.is_registered("stereo_enhancer"));
         assert!(registry.is_registered("crossfeed"));
         assert!(registry.is_registered("convolution"));
@@ -465,6 +536,27 @@ mod tests {
         assert_eq!(effect.info().type_id, "parametric_eq");
     }
 
+    #[test]
+    fn test_update_single_parametric_eq_band() {
+        let registry = EffectRegistry::with_builtin_effects();
+
+        let bands = vec![EqBand::peaking(1000.0, 3.0, 1.0)];
+        let mut effect = registry.create("parametric_eq", &bands).unwrap();
+
+        let update = ParametricEqBandParams {
+            index: 1,
+            band: EqBand::high_pass(80.0, FilterAlignment::LinkwitzRiley, FilterSlope::Db24),
+        };
+        assert!(registry.update_in_place("parametric_eq", effect.as_mut(), &update));
+
+        let eq = effect.as_any().downcast_ref::<ParametricEq>().unwrap();
+        assert_eq!(eq.band_count(), 2);
+        assert_eq!(
+            eq.get_band(1).unwrap().filter_type(),
+            update.band.filter_type()
+        );
+    }
+
     #[test]
     fn test_create_compressor() {
         let registry = EffectRegistry::with_builtin_effects();
@@ -493,6 +585,25 @@ mod tests {
         assert!(updated);
     }
 
+    #[test]
+    fn test_update_dynamics_in_place() {
+        let registry = EffectRegistry::with_builtin_effects();
+
+        let mut multiband = registry
+            .create(
+                "multiband_compressor",
+                &MultibandCompressorSettings::three_band(),
+            )
+            .unwrap();
+        let four_band = MultibandCompressorSettings::four_band();
+        assert!(registry.update_in_place("multiband_compressor", multiband.as_mut(), &four_band));
+
+        let mut dynamic_eq = registry.create("dynamic_eq", &()).unwrap();
+        let bands = vec![DynamicEqBand::de_esser(), DynamicEqBand::bass_tamer()];
+        assert!(registry.update_in_place("dynamic_eq", dynamic_eq.as_mut(), &bands));
+        assert!(!registry.update_in_place("dynamic_eq", dynamic_eq.as_mut(), &4.0f32));
+    }
+
     #[test]
     fn test_convolution_no_in_place() {
         let registry = EffectRegistry::with_builtin_effects();
@@ -506,7 +617,7 @@ mod tests {
         let registry = EffectRegistry::with_builtin_effects();
         let types = registry.registered_types();
 
-        assert!(types.len() >= 8);
+        assert!(types.len() >= 10);
     }
 
     #[test]
@@ -0,0 +1,593 @@
+//! Minimal FLAC encoder
+//!
+//! Writes a standard FLAC stream (STREAMINFO + frames) using the fixed
+//! polynomial predictors (orders 0-4) and partitioned Rice coding. Stereo
+//! frames pick the cheapest of independent, left/side, right/side and
+//! mid/side coding. Compression lands near `flac -2`, which is plenty for
+//! exports.
+//!
+//! The MD5 signature in STREAMINFO is left zeroed ("unknown"), which all
+//! decoders accept.
+
+use std::io::{self, Seek, SeekFrom, Write};
+
+/// Frames per FLAC block
+const BLOCK_SIZE: usize = 4096;
+
+/// Highest Rice partition order tried
+const MAX_PARTITION_ORDER: u32 = 6;
+
+/// Highest Rice parameter (5-bit parameters, 31 is the escape code)
+const MAX_RICE_PARAM: u32 = 30;
+
+/// Frame sync code (14 bits)
+const FRAME_SYNC: u64 = 0x3FFE;
+
+/// Streaming FLAC encoder
+///
+/// Feed interleaved integer samples (already quantized to `bits_per_sample`)
+/// with [`write_samples`](Self::write_samples) and call
+/// [`finalize`](Self::finalize) to flush the last block and fill in the
+/// stream length.
+pub struct FlacWriter<W: Write + Seek> {
+    writer: W,
+    sample_rate: u32,
+    channels: usize,
+    bits_per_sample: u32,
+    /// Interleaved samples not yet encoded (less than one block)
+    pending: Vec<i32>,
+    frame_number: u64,
+    total_frames: u64,
+    min_frame_bytes: u32,
+    max_frame_bytes: u32,
+    /// Offset of the STREAMINFO block (rewritten by `finalize`)
+    streaminfo_pos: u64,
+}
+
+impl<W: Write + Seek> FlacWriter<W> {
+    /// Start a FLAC stream
+    ///
+    /// Supports 1-8 channels at 16 or 24 bits per sample.
+    pub fn new(
+        mut writer: W,
+        sample_rate: u32,
+        channels: u16,
+        bits_per_sample: u16,
+    ) -> io::Result<Self> {
+        if !(1..=8).contains(&channels) {
+            return Err(invalid_input(format!(
+                "FLAC supports 1-8 channels, got {}",
+                channels
+            )));
+        }
+        if bits_per_sample != 16 && bits_per_sample != 24 {
+            return Err(invalid_input(format!(
+                "FLAC export supports 16 or 24 bits, got {}",
+                bits_per_sample
+            )));
+        }
+        if sample_rate == 0 || sample_rate >= 1 << 20 {
+            return Err(invalid_input(format!(
+                "Invalid sample rate {}",
+                sample_rate
+            )));
+        }
+
+        writer.write_all(b"fLaC")?;
+        let streaminfo_pos = writer.stream_position()?;
+
+        let mut flac = Self {
+            writer,
+            sample_rate,
+            channels: channels as usize,
+            bits_per_sample: bits_per_sample as u32,
+            pending: Vec::with_capacity(BLOCK_SIZE * channels as usize),
+            frame_number: 0,
+            total_frames: 0,
+            min_frame_bytes: 0,
+            max_frame_bytes: 0,
+            streaminfo_pos,
+        };
+        flac.write_streaminfo()?;
+        Ok(flac)
+    }
+
+    /// Encode interleaved samples
+    pub fn write_samples(&mut self, samples: &[i32]) -> io::Result<()> {
+        let block_samples = BLOCK_SIZE * self.channels;
+        let mut input = samples;
+
+        while !input.is_empty() {
+            let take = (block_samples - self.pending.len()).min(input.len());
+            self.pending.extend_from_slice(&input[..take]);
+            input = &input[take..];
+
+            if self.pending.len() == block_samples {
+                let block = std::mem::take(&mut self.pending);
+                self.write_frame(&block)?;
+                self.pending = block;
+                self.pending.clear();
+            }
+        }
+        Ok(())
+    }
+
+    /// Frames encoded so far
+    pub fn frames_written(&self) -> u64 {
+        self.total_frames
+    }
+
+    /// Flush the last block and finish the STREAMINFO header
+    pub fn finalize(mut self) -> io::Result<W> {
+        // Whole frames only
+        let usable = self.pending.len() - self.pending.len() % self.channels;
+        if usable > 0 {
+            let block = std::mem::take(&mut self.pending);
+            self.write_frame(&block[..usable])?;
+        }
+
+        let end = self.writer.stream_position()?;
+        self.writer.seek(SeekFrom::Start(self.streaminfo_pos))?;
+        self.write_streaminfo()?;
+        self.writer.seek(SeekFrom::Start(end))?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn write_streaminfo(&mut self) -> io::Result<()> {
+        let mut bits = BitWriter::new();
+        // Last metadata block, type 0 (STREAMINFO), 34 bytes
+        bits.write(1, 1);
+        bits.write(0, 7);
+        bits.write(34, 24);
+        bits.write(BLOCK_SIZE as u64, 16);
+        bits.write(BLOCK_SIZE as u64, 16);
+        bits.write(self.min_frame_bytes as u64, 24);
+        bits.write(self.max_frame_bytes as u64, 24);
+        bits.write(self.sample_rate as u64, 20);
+        bits.write(self.channels as u64 - 1, 3);
+        bits.write(self.bits_per_sample as u64 - 1, 5);
+        bits.write(self.total_frames, 36);
+        // MD5 unknown
+        bits.write(0, 64);
+        bits.write(0, 64);
+        self.writer.write_all(bits.bytes())
+    }
+
+    fn write_frame(&mut self, interleaved: &[i32]) -> io::Result<()> {
+        let frames = interleaved.len() / self.channels;
+        let bps = self.bits_per_sample;
+
+        let channels: Vec<Vec<i64>> = (0..self.channels)
+            .map(|ch| {
+                interleaved
+                    .iter()
+                    .skip(ch)
+                    .step_by(self.channels)
+                    .map(|&s| s as i64)
+                    .collect()
+            })
+            .collect();
+
+        // (assignment code, [(samples, bits per sample)])
+        let (assignment, coded): (u64, Vec<(Vec<i64>, u32)>) = if self.channels == 2 {
+            choose_stereo_coding(&channels[0], &channels[1], bps)
+        } else {
+            (
+                self.channels as u64 - 1,
+                channels.into_iter().map(|ch| (ch, bps)).collect(),
+            )
+        };
+
+        let block_size_code = if frames == BLOCK_SIZE { 0b1100 } else { 0b0111 };
+
+        let mut bits = BitWriter::new();
+        bits.write(FRAME_SYNC, 14);
+        bits.write(0, 1); // Reserved
+        bits.write(0, 1); // Fixed block size
+        bits.write(block_size_code, 4);
+        bits.write(0b0000, 4); // Sample rate from STREAMINFO
+        bits.write(assignment, 4);
+        bits.write(if bps == 16 { 0b100 } else { 0b110 }, 3);
+        bits.write(0, 1); // Reserved
+        bits.write_utf8_number(self.frame_number);
+        if block_size_code == 0b0111 {
+            bits.write(frames as u64 - 1, 16);
+        }
+        let header_crc = crc8(bits.bytes());
+        bits.write(header_crc as u64, 8);
+
+        for (samples, sample_bits) in &coded {
+            let (plan, _) = plan_subframe(samples, *sample_bits);
+            write_subframe(&mut bits, samples, *sample_bits, &plan);
+        }
+        bits.align();
+        let frame_crc = crc16(bits.bytes());
+        bits.write(frame_crc as u64, 16);
+
+        let bytes = bits.bytes();
+        self.writer.write_all(bytes)?;
+
+        let size = bytes.len() as u32;
+        self.min_frame_bytes = if self.frame_number == 0 {
+            size
+        } else {
+            self.min_frame_bytes.min(size)
+        };
+        self.max_frame_bytes = self.max_frame_bytes.max(size);
+        self.frame_number += 1;
+        self.total_frames += frames as u64;
+        Ok(())
+    }
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// Pick the cheapest of independent, left/side, right/side and mid/side
+fn choose_stereo_coding(left: &[i64], right: &[i64], bps: u32) -> (u64, Vec<(Vec<i64>, u32)>) {
+    let side: Vec<i64> = left.iter().zip(right).map(|(l, r)| l - r).collect();
+    let mid: Vec<i64> = left.iter().zip(right).map(|(l, r)| (l + r) >> 1).collect();
+
+    let left_cost = plan_subframe(left, bps).1;
+    let right_cost = plan_subframe(right, bps).1;
+    let side_cost = plan_subframe(&side, bps + 1).1;
+    let mid_cost = plan_subframe(&mid, bps).1;
+
+    let options = [
+        (0b0001, left_cost + right_cost),
+        (0b1000, left_cost + side_cost),
+        (0b1001, right_cost + side_cost),
+        (0b1010, mid_cost + side_cost),
+    ];
+    let assignment = options
+        .iter()
+        .min_by_key(|&&(_, cost)| cost)
+        .map_or(0b0001, |&(code, _)| code);
+
+    let coded = match assignment {
+        0b1000 => vec![(left.to_vec(), bps), (side, bps + 1)],
+        0b1001 => vec![(side, bps + 1), (right.to_vec(), bps)],
+        0b1010 => vec![(mid, bps), (side, bps + 1)],
+        _ => vec![(left.to_vec(), bps), (right.to_vec(), bps)],
+    };
+    (assignment, coded)
+}
+
+/// How a subframe is coded
+enum SubframePlan {
+    Constant,
+    Verbatim,
+    Fixed {
+        order: usize,
+        partition_order: u32,
+        params: Vec<u32>,
+    },
+}
+
+/// Cheapest coding for one channel and its (estimated) size in bits
+fn plan_subframe(samples: &[i64], bps: u32) -> (SubframePlan, u64) {
+    if samples.iter().all(|&s| s == samples[0]) {
+        return (SubframePlan::Constant, 8 + bps as u64);
+    }
+
+    let mut best = (
+        SubframePlan::Verbatim,
+        8 + samples.len() as u64 * bps as u64,
+    );
+
+    for order in 0..=4.min(samples.len() - 1) {
+        let folded: Vec<u64> = fixed_residual(samples, order)
+            .map(|r| ((r << 1) ^ (r >> 63)) as u64)
+            .collect();
+        let (partition_order, params, residual_bits) =
+            plan_partitions(&folded, samples.len(), order);
+        let cost = 8 + order as u64 * bps as u64 + 6 + residual_bits;
+
+        if cost < best.1 {
+            best = (
+                SubframePlan::Fixed {
+                    order,
+                    partition_order,
+                    params,
+                },
+                cost,
+            );
+        }
+    }
+
+    best
+}
+
+/// Residual of the fixed polynomial predictor of `order` (after warm-up)
+fn fixed_residual(samples: &[i64], order: usize) -> impl Iterator<Item = i64> + '_ {
+    samples.windows(order + 1).map(move |w| match order {
+        0 => w[0],
+        1 => w[1] - w[0],
+        2 => w[2] - 2 * w[1] + w[0],
+        3 => w[3] - 3 * w[2] + 3 * w[1] - w[0],
+        _ => w[4] - 4 * w[3] + 6 * w[2] - 4 * w[1] + w[0],
+    })
+}
+
+/// Choose the Rice partition order and parameters for a residual
+///
+/// Returns (partition order, parameter per partition, estimated bits).
+fn plan_partitions(folded: &[u64], block_size: usize, order: usize) -> (u32, Vec<u32>, u64) {
+    // Highest usable order: partitions must divide the block and the first
+    // one must hold more than the warm-up samples
+    let mut max_order = 0;
+    while max_order < MAX_PARTITION_ORDER {
+        let partitions = 1usize << (max_order + 1);
+        if block_size & (partitions - 1) != 0 || block_size / partitions <= order {
+            break;
+        }
+        max_order += 1;
+    }
+
+    // Sums of the finest partitions, merged pairwise for coarser orders
+    let finest = 1usize << max_order;
+    let mut sums = Vec::with_capacity(finest);
+    let mut lens = Vec::with_capacity(finest);
+    let mut start = 0;
+    for p in 0..finest {
+        let len = block_size / finest - if p == 0 { order } else { 0 };
+        sums.push(folded[start..start + len].iter().sum::<u64>());
+        lens.push(len as u64);
+        start += len;
+    }
+
+    let mut best: Option<(u32, Vec<u32>, u64)> = None;
+    let mut partition_order = max_order;
+    loop {
+        let params: Vec<u32> = sums
+            .iter()
+            .zip(&lens)
+            .map(|(&sum, &len)| rice_param(sum, len))
+            .collect();
+        let bits = sums
+            .iter()
+            .zip(&lens)
+            .zip(&params)
+            .map(|((&sum, &len), &k)| 5 + len * (k as u64 + 1) + (sum >> k))
+            .sum::<u64>();
+
+        if !matches!(&best, Some((_, _, b)) if *b <= bits) {
+            best = Some((partition_order, params, bits));
+        }
+
+        if partition_order == 0 {
+            break;
+        }
+        sums = sums.chunks(2).map(|c| c[0] + c[1]).collect();
+        lens = lens.chunks(2).map(|c| c[0] + c[1]).collect();
+        partition_order -= 1;
+    }
+
+    best.unwrap_or((0, vec![0], 0))
+}
+
+/// Rice parameter for a partition from the mean of its folded residual
+fn rice_param(sum: u64, len: u64) -> u32 {
+    if len == 0 || sum < len {
+        return 0;
+    }
+    (sum / len).ilog2().min(MAX_RICE_PARAM)
+}
+
+fn write_subframe(bits: &mut BitWriter, samples: &[i64], bps: u32, plan: &SubframePlan) {
+    match plan {
+        SubframePlan::Constant => {
+            // Zero pad bit, type 000000, no wasted bits
+            bits.write(0b0000_0000, 8);
+            bits.write_signed(samples[0], bps);
+        }
+        SubframePlan::Verbatim => {
+            bits.write(0b0000_0010, 8);
+            for &s in samples {
+                bits.write_signed(s, bps);
+            }
+        }
+        SubframePlan::Fixed {
+            order,
+            partition_order,
+            params,
+        } => {
+            bits.write(0, 1);
+            bits.write(0b001000 | *order as u64, 6);
+            bits.write(0, 1);
+            for &s in &samples[..*order] {
+                bits.write_signed(s, bps);
+            }
+
+            let rice2 = params.iter().any(|&k| k > 14);
+            bits.write(u64::from(rice2), 2);
+            bits.write(*partition_order as u64, 4);
+
+            let partitions = params.len();
+            let mut residual = fixed_residual(samples, *order);
+            for (p, &k) in params.iter().enumerate() {
+                let len = samples.len() / partitions - if p == 0 { *order } else { 0 };
+                bits.write(k as u64, if rice2 { 5 } else { 4 });
+                for r in residual.by_ref().take(len) {
+                    let u = ((r << 1) ^ (r >> 63)) as u64;
+                    bits.write_unary(u >> k);
+                    bits.write(u & ((1 << k) - 1), k);
+                }
+            }
+        }
+    }
+}
+
+/// MSB-first bit writer
+struct BitWriter {
+    bytes: Vec<u8>,
+    /// Bits not yet flushed to `bytes` (low `acc_bits` bits)
+    acc: u64,
+    acc_bits: u32,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self {
+            bytes: Vec::new(),
+            acc: 0,
+            acc_bits: 0,
+        }
+    }
+
+    /// Write the low `bits` bits of `value`
+    fn write(&mut self, value: u64, mut bits: u32) {
+        while bits > 0 {
+            let n = bits.min(32);
+            let chunk = (value >> (bits - n)) & ((1u64 << n) - 1);
+            self.acc = (self.acc << n) | chunk;
+            self.acc_bits += n;
+            bits -= n;
+
+            while self.acc_bits >= 8 {
+                self.acc_bits -= 8;
+                self.bytes.push((self.acc >> self.acc_bits) as u8);
+            }
+        }
+    }
+
+    fn write_signed(&mut self, value: i64, bits: u32) {
+        self.write(value as u64 & ((1u64 << bits) - 1), bits);
+    }
+
+    /// `zeros` zero bits followed by a one
+    fn write_unary(&mut self, mut zeros: u64) {
+        while zeros >= 32 {
+            self.write(0, 32);
+            zeros -= 32;
+        }
+        self.write(1, zeros as u32 + 1);
+    }
+
+    /// FLAC's UTF-8-like variable length number (frame headers)
+    fn write_utf8_number(&mut self, value: u64) {
+        if value < 0x80 {
+            self.write(value, 8);
+            return;
+        }
+        let extra_bytes: u32 = match value {
+            0..=0x7FF => 1,
+            0x800..=0xFFFF => 2,
+            0x1_0000..=0x1F_FFFF => 3,
+            0x20_0000..=0x3FF_FFFF => 4,
+            0x400_0000..=0x7FFF_FFFF => 5,
+            _ => 6,
+        };
+        let prefix = (0xFF00u64 >> (extra_bytes + 1)) & 0xFF;
+        self.write(prefix | (value >> (6 * extra_bytes)), 8);
+        for i in (0..extra_bytes).rev() {
+            self.write(0x80 | ((value >> (6 * i)) & 0x3F), 8);
+        }
+    }
+
+    /// Pad with zeros to the next byte boundary
+    fn align(&mut self) {
+        if self.acc_bits > 0 {
+            self.write(0, 8 - self.acc_bits);
+        }
+    }
+
+    /// Bytes written so far (only complete once aligned)
+    fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+fn crc8(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |mut crc, &byte| {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0u16, |mut crc, &byte| {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SymphoniaDecoder;
+    use soul_core::AudioDecoder;
+    use std::io::Cursor;
+
+    #[test]
+    fn utf8_numbers_match_the_spec() {
+        let encode = |n| {
+            let mut bits = BitWriter::new();
+            bits.write_utf8_number(n);
+            bits.bytes().to_vec()
+        };
+        assert_eq!(encode(0x41), vec![0x41]);
+        assert_eq!(encode(0x7FF), vec![0xDF, 0xBF]);
+        assert_eq!(encode(0x800), vec![0xE0, 0xA0, 0x80]);
+    }
+
+    #[test]
+    fn crcs_match_reference_values() {
+        // CRC-8/SMBUS and CRC-16/UMTS check values
+        assert_eq!(crc8(b"123456789"), 0xF4);
+        assert_eq!(crc16(b"123456789"), 0xFEE8);
+    }
+
+    #[test]
+    fn encoded_stream_decodes_losslessly() {
+        for bits in [16u16, 24] {
+            let full_scale = (1i64 << (bits - 1)) as f64;
+            // Two full blocks plus a partial one, different content per channel
+            let frames = BLOCK_SIZE * 2 + 1000;
+            let samples: Vec<i32> = (0..frames)
+                .flat_map(|i| {
+                    let t = i as f64 / 44100.0;
+                    let left = (t * 440.0 * std::f64::consts::TAU).sin() * 0.5;
+                    let right = (t * 1000.0 * std::f64::consts::TAU).sin() * 0.25;
+                    [(left * full_scale) as i32, (right * full_scale) as i32]
+                })
+                .collect();
+
+            let mut flac = FlacWriter::new(Cursor::new(Vec::new()), 44100, 2, bits).unwrap();
+            flac.write_samples(&samples).unwrap();
+            assert_eq!(flac.frames_written(), (BLOCK_SIZE * 2) as u64);
+            let data = flac.finalize().unwrap().into_inner();
+
+            // Well below the 16/24-bit PCM size
+            assert!(data.len() < samples.len() * bits as usize / 8 / 2);
+
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("test.flac");
+            std::fs::write(&path, &data).unwrap();
+
+            let decoded = SymphoniaDecoder::new().decode(&path).unwrap();
+            assert_eq!(decoded.format.sample_rate.as_hz(), 44100);
+            assert_eq!(decoded.samples.len(), samples.len());
+            for (original, decoded) in samples.iter().zip(&decoded.samples) {
+                let restored = (*decoded as f64 * full_scale).round() as i32;
+                assert_eq!(restored, *original);
+            }
+        }
+    }
+}
@@ -0,0 +1,784 @@
+//! Offline rendering (export)
+//!
+//! Runs a file through the same processing as playback, but as fast as
+//! the CPU allows and into a file instead of a device:
+//!
+//! decode → loudness normalization → preamp → resampling → effect chain → dither → WAV/FLAC
+//!
+//! Loudness normalization measures the whole track first (EBU R128), so the
+//! gain is exact rather than taken from tags. Resampler and effect latency
+//! are compensated, so the output lines up sample for sample with the input
+//! and has exactly the expected length.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use soul_audio::effects::EffectChain;
+//! use soul_audio::render::{self, OutputBitDepth, OutputFormat, RenderSettings};
+//! use std::path::Path;
+//!
+//! # fn example() -> soul_audio::Result<()> {
+//! let mut chain = EffectChain::new();
+//! let settings = RenderSettings {
+//!     format: OutputFormat::Flac,
+//!     bit_depth: OutputBitDepth::Int16,
+//!     sample_rate: Some(44100),
+//!     ..Default::default()
+//! };
+//!
+//! let report = render::render_file(
+//!     Path::new("/music/song.flac"),
+//!     Path::new("/export/song.flac"),
+//!     &mut chain,
+//!     &settings,
+//! )?;
+//! println!("Rendered at {:.0}x real time", report.speed());
+//! # Ok(())
+//! # }
+//! ```
+
+mod flac;
+
+pub use flac::FlacWriter;
+
+use crate::dither::{DitherMode, StereoDither};
+use crate::effects::EffectChain;
+use crate::resampling::{Resampler, ResamplerBackend, ResamplingQuality};
+use crate::{AudioError, Result, SymphoniaDecoder};
+use soul_core::AudioDecoder;
+use soul_loudness::{
+    LoudnessAnalyzer, LoudnessNormalizer, NormalizationMode, REPLAYGAIN_REFERENCE_LUFS,
+};
+use std::fmt;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// Frames decoded per processing block
+const CHUNK_FRAMES: usize = 8192;
+
+/// Output container
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// RIFF WAVE
+    Wav,
+    /// FLAC (16/24-bit)
+    #[default]
+    Flac,
+}
+
+impl OutputFormat {
+    /// Stable identifier, also the file extension
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Wav => "wav",
+            Self::Flac => "flac",
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "wav" => Ok(Self::Wav),
+            "flac" => Ok(Self::Flac),
+            _ => Err(format!(
+                "Invalid output format '{}'. Must be one of: wav, flac",
+                s
+            )),
+        }
+    }
+}
+
+/// Output sample format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputBitDepth {
+    /// 16-bit integer (dithered)
+    Int16,
+    /// 24-bit integer (dithered)
+    #[default]
+    Int24,
+    /// 32-bit float, WAV only (never dithered)
+    Float32,
+}
+
+impl OutputBitDepth {
+    /// Bits per sample
+    pub fn bits(&self) -> u16 {
+        match self {
+            Self::Int16 => 16,
+            Self::Int24 => 24,
+            Self::Float32 => 32,
+        }
+    }
+
+    /// Parse a bit count (16, 24 or 32)
+    pub fn from_bits(bits: u16) -> Option<Self> {
+        match bits {
+            16 => Some(Self::Int16),
+            24 => Some(Self::Int24),
+            32 => Some(Self::Float32),
+            _ => None,
+        }
+    }
+}
+
+/// What to render and how
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderSettings {
+    /// Output container
+    pub format: OutputFormat,
+    /// Output sample format
+    pub bit_depth: OutputBitDepth,
+    /// Output sample rate (None = keep the source rate)
+    pub sample_rate: Option<u32>,
+    /// Loudness normalization (album mode uses the track's own loudness,
+    /// since each file is rendered on its own)
+    pub normalization: NormalizationMode,
+    /// Gain before the effect chain in dB (e.g. headroom for EQ boosts)
+    pub preamp_db: f64,
+    /// Dither for integer output
+    pub dither: DitherMode,
+    /// Resampler quality, when the rate changes
+    pub resampling_quality: ResamplingQuality,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            format: OutputFormat::default(),
+            bit_depth: OutputBitDepth::default(),
+            sample_rate: None,
+            normalization: NormalizationMode::Disabled,
+            preamp_db: 0.0,
+            dither: DitherMode::Tpdf,
+            resampling_quality: ResamplingQuality::High,
+        }
+    }
+}
+
+impl RenderSettings {
+    /// Check that the combination can be written
+    ///
+    /// # Errors
+    /// Returns an error for float FLAC or an out-of-range sample rate
+    pub fn validate(&self) -> Result<()> {
+        if self.format == OutputFormat::Flac && self.bit_depth == OutputBitDepth::Float32 {
+            return Err(AudioError::UnsupportedFormat(
+                "FLAC export supports 16 or 24 bits only".to_string(),
+            ));
+        }
+        if let Some(rate) = self.sample_rate {
+            if !(8000..=768_000).contains(&rate) {
+                return Err(AudioError::UnsupportedFormat(format!(
+                    "Unsupported output sample rate: {} Hz",
+                    rate
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Result of rendering one file
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderReport {
+    /// Frames written
+    pub frames: u64,
+    /// Sample rate of the source
+    pub source_sample_rate: u32,
+    /// Sample rate of the output
+    pub sample_rate: u32,
+    /// Normalization gain applied (None when normalization is disabled)
+    pub normalization_gain_db: Option<f64>,
+    /// Wall-clock time spent, including the loudness pass
+    pub elapsed: Duration,
+}
+
+impl RenderReport {
+    /// Length of the rendered audio
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.frames as f64 / self.sample_rate as f64)
+    }
+
+    /// Render speed as a multiple of real time
+    pub fn speed(&self) -> f64 {
+        self.duration().as_secs_f64() / self.elapsed.as_secs_f64().max(1e-9)
+    }
+}
+
+/// Render a file through an effect chain
+///
+/// The chain is reset before rendering and processes audio at the output
+/// sample rate. On failure the partial output file is removed.
+///
+/// # Errors
+/// Returns an error if the input cannot be decoded, the settings are
+/// invalid or the output cannot be written
+pub fn render_file(
+    input: &Path,
+    output: &Path,
+    chain: &mut EffectChain,
+    settings: &RenderSettings,
+) -> Result<RenderReport> {
+    render_file_with_progress(input, output, chain, settings, |_| {})
+}
+
+/// Render a file, reporting progress (0.0 - 1.0) after each block
+///
+/// See [`render_file`].
+///
+/// # Errors
+/// Same as [`render_file`]
+pub fn render_file_with_progress(
+    input: &Path,
+    output: &Path,
+    chain: &mut EffectChain,
+    settings: &RenderSettings,
+    progress: impl FnMut(f32),
+) -> Result<RenderReport> {
+    settings.validate()?;
+    if !input.exists() {
+        return Err(AudioError::FileNotFound(input.display().to_string()));
+    }
+
+    let result = render(input, output, chain, settings, progress);
+    if result.is_err() {
+        let _ = std::fs::remove_file(output);
+    }
+    result
+}
+
+/// Measure the integrated loudness and true peak of a file
+///
+/// # Errors
+/// Returns an error if the file cannot be decoded or analyzed
+pub fn analyze_loudness(input: &Path) -> Result<soul_loudness::LoudnessInfo> {
+    let mut decoder = SymphoniaDecoder::new();
+    let metadata = decoder.open(input).map_err(decode_error)?;
+    let mut analyzer = LoudnessAnalyzer::new(metadata.sample_rate, 2).map_err(render_error)?;
+
+    while let Some(buffer) = decoder.decode_chunk(CHUNK_FRAMES).map_err(decode_error)? {
+        analyzer.add_frames(&buffer.samples).map_err(render_error)?;
+    }
+
+    analyzer.finalize().map_err(render_error)
+}
+
+fn render(
+    input: &Path,
+    output: &Path,
+    chain: &mut EffectChain,
+    settings: &RenderSettings,
+    mut progress: impl FnMut(f32),
+) -> Result<RenderReport> {
+    let start = Instant::now();
+
+    let mut decoder = SymphoniaDecoder::new();
+    let metadata = decoder.open(input).map_err(decode_error)?;
+    let source_rate = metadata.sample_rate;
+    let output_rate = settings.sample_rate.unwrap_or(source_rate);
+    let expected_frames = metadata
+        .duration
+        .map(|d| (d.as_secs_f64() * source_rate as f64).max(1.0));
+
+    let mut normalizer = if settings.normalization == NormalizationMode::Disabled {
+        None
+    } else {
+        let info = analyze_loudness(input)?;
+        let mut normalizer = LoudnessNormalizer::new(source_rate, 2);
+        normalizer.set_mode(settings.normalization);
+        normalizer.set_use_internal_limiter(false);
+        normalizer.set_prevent_clipping(true);
+        // Silence has no integrated loudness; leave it alone
+        if info.integrated_lufs.is_finite() {
+            normalizer.set_track_gain(
+                REPLAYGAIN_REFERENCE_LUFS - info.integrated_lufs,
+                info.true_peak_dbfs,
+            );
+        }
+        Some(normalizer)
+    };
+    let normalization_gain_db = normalizer
+        .as_mut()
+        .map(LoudnessNormalizer::effective_gain_db);
+
+    let resampler = if output_rate == source_rate {
+        None
+    } else {
+        Some(
+            Resampler::new(
+                ResamplerBackend::Auto,
+                source_rate,
+                output_rate,
+                2,
+                settings.resampling_quality,
+            )
+            .map_err(render_error)?,
+        )
+    };
+
+    let sink = Sink::create(output, output_rate, settings)?;
+
+    chain.set_sample_rate(output_rate);
+    chain.reset();
+    let mut pipeline = Pipeline {
+        normalizer,
+        preamp: 10.0_f32.powf(settings.preamp_db as f32 / 20.0),
+        resampler,
+        chain,
+        chain_skip: None,
+        output_rate,
+        dither: StereoDither::with_mode(settings.dither.for_channels(2), output_rate),
+        sink,
+        resampled_frames: 0,
+    };
+
+    let mut input_frames = 0u64;
+    while let Some(buffer) = decoder.decode_chunk(CHUNK_FRAMES).map_err(decode_error)? {
+        input_frames += (buffer.samples.len() / 2) as u64;
+        pipeline.push(buffer.samples)?;
+
+        if let Some(total) = expected_frames {
+            progress((input_frames as f64 / total).min(1.0) as f32);
+        }
+    }
+
+    let frames = pipeline.finish(input_frames, source_rate)?;
+    progress(1.0);
+
+    Ok(RenderReport {
+        frames,
+        source_sample_rate: source_rate,
+        sample_rate: output_rate,
+        normalization_gain_db,
+        elapsed: start.elapsed(),
+    })
+}
+
+/// Processing stages after the decoder
+struct Pipeline<'a> {
+    normalizer: Option<LoudnessNormalizer>,
+    /// Linear preamp gain
+    preamp: f32,
+    /// Output is aligned with the input: the sinc resamplers centre their
+    /// kernel, so `latency()` is only the part of the input still in the filter
+    resampler: Option<Resampler>,
+    /// Frames that came out of the resampler
+    resampled_frames: u64,
+    chain: &'a mut EffectChain,
+    /// Chain output frames still to drop, known after the first block
+    chain_skip: Option<usize>,
+    output_rate: u32,
+    dither: StereoDither,
+    sink: Sink,
+}
+
+impl Pipeline<'_> {
+    fn push(&mut self, mut samples: Vec<f32>) -> Result<()> {
+        if let Some(normalizer) = &mut self.normalizer {
+            normalizer.process(&mut samples);
+        }
+        if self.preamp != 1.0 {
+            for sample in &mut samples {
+                *sample *= self.preamp;
+            }
+        }
+        if let Some(resampler) = &mut self.resampler {
+            samples = resampler.process(&samples).map_err(render_error)?;
+        }
+        self.resampled_frames += (samples.len() / 2) as u64;
+        self.process_effects(samples)
+    }
+
+    fn process_effects(&mut self, mut samples: Vec<f32>) -> Result<()> {
+        if samples.is_empty() {
+            return Ok(());
+        }
+        self.chain.process(&mut samples, self.output_rate);
+
+        // Effects may size their delay lines on the first block
+        let skip = self
+            .chain_skip
+            .get_or_insert_with(|| self.chain.latency_samples());
+        let samples = skip_frames(samples, skip);
+        self.sink.write(&samples, &mut self.dither)
+    }
+
+    /// Drain the resampler and effect delays, finish the file
+    fn finish(mut self, input_frames: u64, source_rate: u32) -> Result<u64> {
+        if let Some(mut resampler) = self.resampler.take() {
+            let ratio = self.output_rate as f64 / source_rate as f64;
+            let expected = (input_frames as f64 * ratio).round() as u64;
+
+            // Push the input still in the filter out with silence
+            let tail_frames = (resampler.latency() as f64 / ratio).ceil() as usize + 1;
+            let mut tail = resampler
+                .process(&vec![0.0; tail_frames * 2])
+                .map_err(render_error)?;
+            tail.extend(resampler.flush().map_err(render_error)?);
+
+            let missing = expected.saturating_sub(self.resampled_frames) as usize;
+            tail.truncate(missing * 2);
+            self.resampled_frames += (tail.len() / 2) as u64;
+            self.process_effects(tail)?;
+        }
+
+        let chain_latency = self.chain.latency_samples();
+        if chain_latency > 0 {
+            self.process_effects(vec![0.0; chain_latency * 2])?;
+        }
+
+        self.sink.finalize()
+    }
+}
+
+/// Drop up to `skip` leading frames, counting them down
+fn skip_frames(mut samples: Vec<f32>, skip: &mut usize) -> Vec<f32> {
+    let dropped = (*skip).min(samples.len() / 2);
+    *skip -= dropped;
+    samples.drain(..dropped * 2);
+    samples
+}
+
+/// Output file writer
+enum Sink {
+    Wav {
+        writer: hound::WavWriter<BufWriter<File>>,
+        bit_depth: OutputBitDepth,
+        frames: u64,
+    },
+    Flac {
+        writer: FlacWriter<BufWriter<File>>,
+        bit_depth: OutputBitDepth,
+        frames: u64,
+    },
+}
+
+impl Sink {
+    fn create(path: &Path, sample_rate: u32, settings: &RenderSettings) -> Result<Self> {
+        match settings.format {
+            OutputFormat::Wav => {
+                let spec = hound::WavSpec {
+                    channels: 2,
+                    sample_rate,
+                    bits_per_sample: settings.bit_depth.bits(),
+                    sample_format: if settings.bit_depth == OutputBitDepth::Float32 {
+                        hound::SampleFormat::Float
+                    } else {
+                        hound::SampleFormat::Int
+                    },
+                };
+                let writer = hound::WavWriter::create(path, spec).map_err(render_error)?;
+                Ok(Self::Wav {
+                    writer,
+                    bit_depth: settings.bit_depth,
+                    frames: 0,
+                })
+            }
+            OutputFormat::Flac => {
+                let file = BufWriter::new(File::create(path)?);
+                let writer = FlacWriter::new(file, sample_rate, 2, settings.bit_depth.bits())?;
+                Ok(Self::Flac {
+                    writer,
+                    bit_depth: settings.bit_depth,
+                    frames: 0,
+                })
+            }
+        }
+    }
+
+    fn write(&mut self, samples: &[f32], dither: &mut StereoDither) -> Result<()> {
+        match self {
+            Self::Wav {
+                writer,
+                bit_depth,
+                frames,
+            } => {
+                *frames += (samples.len() / 2) as u64;
+                match bit_depth {
+                    OutputBitDepth::Float32 => {
+                        for &s in samples {
+                            writer.write_sample(s).map_err(render_error)?;
+                        }
+                    }
+                    depth => {
+                        for s in quantize(samples, *depth, dither) {
+                            writer.write_sample(s).map_err(render_error)?;
+                        }
+                    }
+                }
+                Ok(())
+            }
+            Self::Flac {
+                writer,
+                bit_depth,
+                frames,
+            } => {
+                *frames += (samples.len() / 2) as u64;
+                writer.write_samples(&quantize(samples, *bit_depth, dither))?;
+                Ok(())
+            }
+        }
+    }
+
+    fn finalize(self) -> Result<u64> {
+        match self {
+            Self::Wav { writer, frames, .. } => {
+                writer.finalize().map_err(render_error)?;
+                Ok(frames)
+            }
+            Self::Flac { writer, frames, .. } => {
+                writer.finalize()?;
+                Ok(frames)
+            }
+        }
+    }
+}
+
+/// Dither interleaved stereo to 16- or 24-bit integers
+fn quantize(samples: &[f32], bit_depth: OutputBitDepth, dither: &mut StereoDither) -> Vec<i32> {
+    if bit_depth == OutputBitDepth::Int16 {
+        let mut out = vec![0i16; samples.len()];
+        dither.process_stereo_to_i16(samples, &mut out);
+        out.into_iter().map(i32::from).collect()
+    } else {
+        // 24 bits in the top of an i32; round off the low byte
+        let mut out = vec![0i32; samples.len()];
+        dither.process_stereo_to_i32(samples, &mut out);
+        out.into_iter()
+            .map(|s| ((s as i64 + 128) >> 8).clamp(-(1 << 23), (1 << 23) - 1) as i32)
+            .collect()
+    }
+}
+
+fn decode_error(err: soul_core::SoulError) -> AudioError {
+    AudioError::DecodeError(err.to_string())
+}
+
+fn render_error(err: impl fmt::Display) -> AudioError {
+    AudioError::RenderError(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::effects::AudioEffect;
+    use std::any::Any;
+    use std::collections::VecDeque;
+    use std::path::PathBuf;
+
+    /// One second of a stereo sine as 16-bit WAV, returns the samples written
+    fn write_sine(path: &Path, sample_rate: u32, freq: f32, amplitude: f32) -> Vec<i16> {
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(path, spec).unwrap();
+        let mut samples = Vec::new();
+        for i in 0..sample_rate {
+            let t = i as f32 / sample_rate as f32;
+            let s = ((t * freq * std::f32::consts::TAU).sin() * amplitude * 32767.0) as i16;
+            samples.extend([s, s]);
+            writer.write_sample(s).unwrap();
+            writer.write_sample(s).unwrap();
+        }
+        writer.finalize().unwrap();
+        samples
+    }
+
+    fn read_wav_f32(path: &Path) -> (hound::WavSpec, Vec<f32>) {
+        let mut reader = hound::WavReader::open(path).unwrap();
+        let spec = reader.spec();
+        let samples = reader.samples::<f32>().map(|s| s.unwrap()).collect();
+        (spec, samples)
+    }
+
+    fn paths(dir: &tempfile::TempDir, output: &str) -> (PathBuf, PathBuf) {
+        (dir.path().join("input.wav"), dir.path().join(output))
+    }
+
+    /// Delays the signal by a fixed number of frames and reports it
+    struct DelayEffect {
+        line: VecDeque<f32>,
+    }
+
+    impl AudioEffect for DelayEffect {
+        fn process(&mut self, buffer: &mut [f32], _sample_rate: u32) {
+            for sample in buffer.iter_mut() {
+                self.line.push_back(*sample);
+                *sample = self.line.pop_front().unwrap_or(0.0);
+            }
+        }
+
+        fn reset(&mut self) {
+            self.line.iter_mut().for_each(|s| *s = 0.0);
+        }
+
+        fn set_enabled(&mut self, _enabled: bool) {}
+
+        fn is_enabled(&self) -> bool {
+            true
+        }
+
+        fn name(&self) -> &str {
+            "Delay"
+        }
+
+        fn latency_samples(&self) -> usize {
+            self.line.len() / 2
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+    }
+
+    #[test]
+    fn float_flac_is_rejected() {
+        let settings = RenderSettings {
+            format: OutputFormat::Flac,
+            bit_depth: OutputBitDepth::Float32,
+            ..Default::default()
+        };
+        assert!(settings.validate().is_err());
+        assert!(RenderSettings::default().validate().is_ok());
+    }
+
+    #[test]
+    fn effect_latency_is_compensated() {
+        let dir = tempfile::tempdir().unwrap();
+        let (input, output) = paths(&dir, "out.wav");
+        let original = write_sine(&input, 44100, 440.0, 0.5);
+
+        let mut chain = EffectChain::new();
+        chain.add_effect(Box::new(DelayEffect {
+            line: VecDeque::from(vec![0.0; 2 * 1000]),
+        }));
+        let settings = RenderSettings {
+            format: OutputFormat::Wav,
+            bit_depth: OutputBitDepth::Float32,
+            ..Default::default()
+        };
+        let report = render_file(&input, &output, &mut chain, &settings).unwrap();
+
+        let (spec, rendered) = read_wav_f32(&output);
+        assert_eq!(spec.sample_rate, 44100);
+        assert_eq!(report.frames, 44100);
+        assert_eq!(rendered.len(), original.len());
+        for (rendered, original) in rendered.iter().zip(&original) {
+            assert_eq!(*rendered, *original as f32 / 32768.0);
+        }
+    }
+
+    #[test]
+    fn resampled_output_is_aligned_and_exact_length() {
+        let dir = tempfile::tempdir().unwrap();
+        let (input, output) = paths(&dir, "out.wav");
+        write_sine(&input, 44100, 1000.0, 0.5);
+
+        let settings = RenderSettings {
+            format: OutputFormat::Wav,
+            bit_depth: OutputBitDepth::Float32,
+            sample_rate: Some(48000),
+            ..Default::default()
+        };
+        let report = render_file(&input, &output, &mut EffectChain::new(), &settings).unwrap();
+        assert_eq!(report.source_sample_rate, 44100);
+        assert_eq!(report.sample_rate, 48000);
+
+        let (spec, rendered) = read_wav_f32(&output);
+        assert_eq!(spec.sample_rate, 48000);
+        assert_eq!(rendered.len(), 2 * 48000);
+
+        // Same sine at the new rate, away from the edges
+        for i in 1000..47000 {
+            let t = i as f32 / 48000.0;
+            let expected = (t * 1000.0 * std::f32::consts::TAU).sin() * 0.5;
+            assert!(
+                (rendered[i * 2] - expected).abs() < 0.01,
+                "frame {}: {} vs {}",
+                i,
+                rendered[i * 2],
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn flac_output_decodes_to_the_rendered_audio() {
+        let dir = tempfile::tempdir().unwrap();
+        let (input, output) = paths(&dir, "out.flac");
+        let original = write_sine(&input, 44100, 440.0, 0.5);
+
+        let settings = RenderSettings {
+            format: OutputFormat::Flac,
+            bit_depth: OutputBitDepth::Int24,
+            dither: DitherMode::None,
+            ..Default::default()
+        };
+        render_file(&input, &output, &mut EffectChain::new(), &settings).unwrap();
+
+        let decoded = SymphoniaDecoder::new().decode(&output).unwrap();
+        assert_eq!(decoded.format.sample_rate.as_hz(), 44100);
+        assert_eq!(decoded.samples.len(), original.len());
+        for (decoded, original) in decoded.samples.iter().zip(&original) {
+            assert!((decoded - *original as f32 / 32768.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn normalization_reaches_reference_loudness() {
+        let dir = tempfile::tempdir().unwrap();
+        let (input, output) = paths(&dir, "out.wav");
+        write_sine(&input, 48000, 1000.0, 0.05);
+
+        let settings = RenderSettings {
+            format: OutputFormat::Wav,
+            bit_depth: OutputBitDepth::Float32,
+            normalization: NormalizationMode::ReplayGainTrack,
+            ..Default::default()
+        };
+        let report = render_file(&input, &output, &mut EffectChain::new(), &settings).unwrap();
+        assert!(report.normalization_gain_db.unwrap() > 0.0);
+
+        let loudness = analyze_loudness(&output).unwrap();
+        assert!(
+            (loudness.integrated_lufs - REPLAYGAIN_REFERENCE_LUFS).abs() < 0.5,
+            "rendered at {} LUFS",
+            loudness.integrated_lufs
+        );
+    }
+
+    #[test]
+    fn failed_render_leaves_no_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let (input, output) = paths(&dir, "out.wav");
+        std::fs::write(&input, b"not audio").unwrap();
+
+        let result = render_file(
+            &input,
+            &output,
+            &mut EffectChain::new(),
+            &RenderSettings::default(),
+        );
+        assert!(result.is_err());
+        assert!(!output.exists());
+    }
+}
@@ -9,12 +9,14 @@
 //! - Dynamic Range (DR)
 //! - Frequency Response Analysis
 //! - Phase Analysis
+//! - Noise Spectrum (averaged, for dither / noise shaping verification)
 //!
 //! These metrics follow industry standards:
 //! - AES17: Standard for measuring audio equipment
 //! - IEC 61606: Audio and audiovisual equipment
 //! - ITU-R BS.1770: Loudness measurement
 
+use rustfft::{num_complex::Complex, FftPlanner};
 use std::f32::consts::PI;
 
 /// Calculate RMS (Root Mean Square) level
@@ -110,6 +112,90 @@ pub fn analyze_frequency_spectrum(samples: &[f32], sample_rate: u32) -> Vec<(f32
     spectrum
 }
 
+/// Averaged noise power spectrum (Welch method)
+///
+/// Splits the signal into Hann-windowed segments of `fft_size` samples with
+/// 50% overlap and averages their power spectra. The averaging makes the
+/// noise floor smooth enough to compare bands, e.g. to check the curve of
+/// noise-shaped dither.
+///
+/// Levels are normalized so white noise of variance σ² reads
+/// `10 * log10(σ²)` in every bin.
+///
+/// # Arguments
+/// * `samples` - Mono audio samples (at least `fft_size` long)
+/// * `sample_rate` - Sample rate in Hz
+/// * `fft_size` - Segment length
+///
+/// # Returns
+/// Vector of (frequency_hz, power_db) tuples, DC excluded
+pub fn calculate_noise_spectrum(
+    samples: &[f32],
+    sample_rate: u32,
+    fft_size: usize,
+) -> Vec<(f32, f32)> {
+    if fft_size < 2 || samples.len() < fft_size {
+        return Vec::new();
+    }
+
+    let window: Vec<f32> = (0..fft_size)
+        .map(|i| 0.5 * (1.0 - (2.0 * PI * i as f32 / fft_size as f32).cos()))
+        .collect();
+    let window_power: f32 = window.iter().map(|w| w * w).sum();
+
+    let mut planner = FftPlanner::<f32>::new();
+    let fft = planner.plan_fft_forward(fft_size);
+
+    let bins = fft_size / 2;
+    let mut power = vec![0.0f64; bins];
+    let mut segments = 0usize;
+    let mut buffer = vec![Complex::new(0.0f32, 0.0); fft_size];
+
+    for start in (0..=samples.len() - fft_size).step_by(fft_size / 2) {
+        for (i, slot) in buffer.iter_mut().enumerate() {
+            *slot = Complex::new(samples[start + i] * window[i], 0.0);
+        }
+        fft.process(&mut buffer);
+
+        for (bin, acc) in power.iter_mut().enumerate() {
+            *acc += buffer[bin].norm_sqr() as f64;
+        }
+        segments += 1;
+    }
+
+    power
+        .iter()
+        .enumerate()
+        .skip(1)
+        .map(|(bin, &p)| {
+            let frequency = bin as f32 * sample_rate as f32 / fft_size as f32;
+            let level = p / segments as f64 / window_power as f64;
+            (frequency, 10.0 * level.max(1e-30).log10() as f32)
+        })
+        .collect()
+}
+
+/// Average power of a noise spectrum within a frequency band
+///
+/// Takes the output of [`calculate_noise_spectrum`] and averages the linear
+/// power of all bins in `[low_hz, high_hz)`.
+///
+/// # Returns
+/// Band power in dB (same scale as the spectrum), or -300 dB if the band is empty
+pub fn band_noise_power_db(spectrum: &[(f32, f32)], low_hz: f32, high_hz: f32) -> f32 {
+    let (sum, count) = spectrum
+        .iter()
+        .filter(|(freq, _)| *freq >= low_hz && *freq < high_hz)
+        .fold((0.0f64, 0usize), |(sum, count), (_, db)| {
+            (sum + 10.0f64.powf(*db as f64 / 10.0), count + 1)
+        });
+
+    if count == 0 {
+        return -300.0;
+    }
+    (10.0 * (sum / count as f64).log10()) as f32
+}
+
 /// Find the dominant frequency in a signal
 ///
 /// Returns the frequency with the highest magnitude in the spectrum.
@@ -923,4 +1009,105 @@ mod tests {
         assert_eq!(left, vec![1.0, 3.0, 5.0]);
         assert_eq!(right, vec![2.0, 4.0, 6.0]);
     }
+
+    /// Quantization error (in LSBs) of dithering a quiet sine to 16 bits
+    fn dither_error_16bit(mode: crate::dither::DitherMode, sample_rate: u32) -> Vec<f32> {
+        let mut dither = crate::dither::StereoDither::with_mode(mode, sample_rate);
+        let signal = generate_sine_wave(1000.0, sample_rate, 2.0, 0.01);
+        let mut output = vec![0i16; signal.len()];
+        dither.process_stereo_to_i16(&signal, &mut output);
+
+        let input = extract_mono(&signal, 0);
+        extract_mono(&output.iter().map(|&s| s as f32).collect::<Vec<_>>(), 0)
+            .iter()
+            .zip(&input)
+            .map(|(out, inp)| out - inp * 32767.0)
+            .collect()
+    }
+
+    #[test]
+    fn test_noise_spectrum_of_white_noise() {
+        let noise = extract_mono(&generate_white_noise(44100, 2.0, 0.5), 0);
+        let spectrum = calculate_noise_spectrum(&noise, 44100, 2048);
+
+        let low = band_noise_power_db(&spectrum, 1000.0, 5000.0);
+        let high = band_noise_power_db(&spectrum, 15000.0, 20000.0);
+        assert!(
+            (low - high).abs() < 1.0,
+            "White noise not flat: {} vs {} dB",
+            low,
+            high
+        );
+
+        // Uniform noise of amplitude 0.5 has variance 0.25 / 3 → -10.8 dB
+        assert!((low + 10.8).abs() < 0.5, "Unexpected level: {} dB", low);
+    }
+
+    #[test]
+    fn test_noise_spectrum_of_shaped_dither() {
+        use crate::dither::{DitherMode, NoiseShape};
+
+        for sample_rate in [44100, 48000] {
+            let flat = calculate_noise_spectrum(
+                &dither_error_16bit(DitherMode::Tpdf, sample_rate),
+                sample_rate,
+                2048,
+            );
+            let flat_mid = band_noise_power_db(&flat, 1000.0, 5000.0);
+            let flat_top = band_noise_power_db(&flat, 16000.0, 20000.0);
+
+            for shape in NoiseShape::ALL {
+                let spectrum = calculate_noise_spectrum(
+                    &dither_error_16bit(DitherMode::Shaped(shape), sample_rate),
+                    sample_rate,
+                    2048,
+                );
+                let mid = band_noise_power_db(&spectrum, 1000.0, 5000.0);
+                let top = band_noise_power_db(&spectrum, 16000.0, 20000.0);
+
+                assert!(
+                    mid < flat_mid - 10.0,
+                    "{:?} @ {} Hz: 1-5 kHz noise {} dB, TPDF {} dB",
+                    shape,
+                    sample_rate,
+                    mid,
+                    flat_mid
+                );
+                assert!(
+                    top > flat_top + 5.0,
+                    "{:?} @ {} Hz: noise not moved up ({} vs {} dB)",
+                    shape,
+                    sample_rate,
+                    top,
+                    flat_top
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_shaped_dither_falls_back_to_flat_at_high_rates() {
+        use crate::dither::{DitherMode, NoiseShape};
+
+        let flat =
+            calculate_noise_spectrum(&dither_error_16bit(DitherMode::Tpdf, 96000), 96000, 2048);
+        let shaped = calculate_noise_spectrum(
+            &dither_error_16bit(DitherMode::Shaped(NoiseShape::EWeighted), 96000),
+            96000,
+            2048,
+        );
+
+        for (low, high) in [(1000.0, 5000.0), (30000.0, 40000.0)] {
+            let expected = band_noise_power_db(&flat, low, high);
+            let actual = band_noise_power_db(&shaped, low, high);
+            assert!(
+                (expected - actual).abs() < 1.0,
+                "{}-{} Hz: {} dB, TPDF {} dB",
+                low,
+                high,
+                actual,
+                expected
+            );
+        }
+    }
 }
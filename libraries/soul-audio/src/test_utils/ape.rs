@@ -0,0 +1,349 @@
+//! Synthetic Monkey's Audio files and APEv2 tags for decoder testing
+//!
+//! A Monkey's Audio 3.99 encoder that runs the decoder's stages backwards:
+//! left/right to mid/side, the inverse of the adaptive predictor and NN
+//! filters, then the range coder. Frames are packed back to back into the
+//! 32-bit word stream the way the reference encoder writes them, so most
+//! frames start in the middle of a word.
+
+use crate::formats::ape::frame::{
+    fold_sign, frame_crc, NnFilter, Predictor, Rice, BOTTOM_VALUE, COUNTS, ESCAPE_SYMBOL,
+    FRAME_MONO_SILENCE, FRAME_PSEUDO_STEREO, FRAME_STEREO_SILENCE, LARGE_SYMBOL_BASE,
+};
+use std::io;
+use std::path::Path;
+
+/// Settings of a generated file
+#[derive(Debug, Clone)]
+pub struct ApeOptions {
+    pub sample_rate: u32,
+    /// 1 or 2
+    pub channels: usize,
+    /// 8, 16 or 24
+    pub bits_per_sample: u16,
+    /// 1000 (fast) to 5000 (insane)
+    pub compression_level: u16,
+    /// Samples per channel in each frame
+    pub blocks_per_frame: u32,
+}
+
+impl Default for ApeOptions {
+    fn default() -> Self {
+        Self {
+            sample_rate: 44100,
+            channels: 2,
+            bits_per_sample: 16,
+            compression_level: 2000,
+            blocks_per_frame: 4096,
+        }
+    }
+}
+
+/// Write a Monkey's Audio file
+///
+/// # Arguments
+/// * `samples` - Interleaved integer samples at `options.bits_per_sample`
+pub fn write_ape(path: impl AsRef<Path>, samples: &[i32], options: &ApeOptions) -> io::Result<()> {
+    std::fs::write(path, encode_ape(samples, options))
+}
+
+/// Encode a Monkey's Audio file
+pub fn encode_ape(samples: &[i32], options: &ApeOptions) -> Vec<u8> {
+    const DESCRIPTOR_SIZE: u32 = 52;
+    const HEADER_SIZE: u32 = 24;
+
+    let channels = options.channels;
+    let total = samples.len() / channels;
+    let frame_len = options.blocks_per_frame as usize;
+    let level = usize::from(options.compression_level / 1000 - 1);
+
+    // Frames back to back in stream (big-endian word) order
+    let mut stream = Vec::new();
+    let mut offsets = Vec::new();
+    for frame in samples.chunks(frame_len * channels) {
+        offsets.push(stream.len() as u32);
+        encode_frame(&mut stream, frame, channels, options.bits_per_sample, level);
+    }
+    stream.resize((stream.len() + 3) & !3, 0);
+    let frames = offsets.len() as u32;
+    let final_blocks = (total - (frames as usize - 1) * frame_len) as u32;
+
+    let seek_table_len = frames * 4;
+    let first_frame = DESCRIPTOR_SIZE + HEADER_SIZE + seek_table_len;
+
+    let mut file = b"MAC ".to_vec();
+    file.extend_from_slice(&3990u16.to_le_bytes());
+    file.extend_from_slice(&0u16.to_le_bytes());
+    for value in [
+        DESCRIPTOR_SIZE,
+        HEADER_SIZE,
+        seek_table_len,
+        0, // WAV header
+        stream.len() as u32,
+        0, // audio data length, high word
+        0, // WAV tail
+    ] {
+        file.extend_from_slice(&value.to_le_bytes());
+    }
+    file.extend_from_slice(&[0; 16]); // MD5
+
+    file.extend_from_slice(&options.compression_level.to_le_bytes());
+    file.extend_from_slice(&0u16.to_le_bytes()); // format flags
+    file.extend_from_slice(&options.blocks_per_frame.to_le_bytes());
+    file.extend_from_slice(&final_blocks.to_le_bytes());
+    file.extend_from_slice(&frames.to_le_bytes());
+    file.extend_from_slice(&options.bits_per_sample.to_le_bytes());
+    file.extend_from_slice(&(channels as u16).to_le_bytes());
+    file.extend_from_slice(&options.sample_rate.to_le_bytes());
+
+    for offset in offsets {
+        file.extend_from_slice(&(first_frame + offset).to_le_bytes());
+    }
+
+    // Stored as little-endian words
+    for word in stream.chunks_exact(4) {
+        file.extend(word.iter().rev());
+    }
+    file
+}
+
+/// Range encoder matching the decoder's 32-bit coder
+struct RangeEncoder {
+    output: Vec<u8>,
+    low: u32,
+    range: u32,
+    buffer: u32,
+    /// Pending 0xff bytes that a carry may still turn into zeros
+    help: u32,
+}
+
+impl RangeEncoder {
+    const TOP_VALUE: u32 = 1 << 31;
+    const SHIFT_BITS: u32 = 23;
+
+    fn new() -> Self {
+        Self {
+            output: Vec::new(),
+            low: 0,
+            range: Self::TOP_VALUE,
+            buffer: 0,
+            help: 0,
+        }
+    }
+
+    fn put(&mut self, byte: u32) {
+        self.output.push(byte as u8);
+    }
+
+    fn normalize(&mut self) {
+        while self.range <= BOTTOM_VALUE {
+            if self.low < 0xff << Self::SHIFT_BITS {
+                self.put(self.buffer);
+                for _ in 0..self.help {
+                    self.put(0xff);
+                }
+                self.help = 0;
+                self.buffer = self.low >> Self::SHIFT_BITS;
+            } else if self.low & Self::TOP_VALUE != 0 {
+                self.put(self.buffer + 1);
+                for _ in 0..self.help {
+                    self.put(0);
+                }
+                self.help = 0;
+                self.buffer = (self.low >> Self::SHIFT_BITS) & 0xff;
+            } else {
+                self.help += 1;
+            }
+            self.low = (self.low << 8) & (Self::TOP_VALUE - 1);
+            self.range <<= 8;
+        }
+    }
+
+    /// Symbol `start..start + width` of a total of `2^shift`
+    fn encode_shift(&mut self, width: u32, start: u32, shift: u32) {
+        self.normalize();
+        let step = self.range >> shift;
+        self.range = step * width;
+        self.low += step * start;
+    }
+
+    /// Value in `0..total`
+    fn encode_uniform(&mut self, value: u32, total: u32) {
+        self.normalize();
+        let step = self.range / total;
+        self.range = step;
+        self.low += step * value;
+    }
+
+    fn value(&mut self, rice: &mut Rice, value: i32) {
+        let folded = fold_sign(value);
+        let pivot = rice.pivot();
+        let overflow = folded / pivot;
+        let base = folded % pivot;
+
+        if overflow < 21 {
+            let symbol = overflow as usize;
+            self.encode_shift(COUNTS[symbol + 1] - COUNTS[symbol], COUNTS[symbol], 16);
+        } else if overflow < ESCAPE_SYMBOL {
+            self.encode_shift(1, LARGE_SYMBOL_BASE + overflow, 16);
+        } else {
+            self.encode_shift(1, LARGE_SYMBOL_BASE + ESCAPE_SYMBOL, 16);
+            self.encode_shift(1, overflow >> 16, 16);
+            self.encode_shift(1, overflow & 0xffff, 16);
+        }
+
+        if pivot < 0x10000 {
+            self.encode_uniform(base, pivot);
+        } else {
+            let low_bits = 32 - pivot.leading_zeros() - 16;
+            self.encode_uniform(base >> low_bits, (pivot >> low_bits) + 1);
+            self.encode_uniform(base & ((1 << low_bits) - 1), 1 << low_bits);
+        }
+
+        rice.update(folded);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        self.normalize();
+        let last = (self.low >> Self::SHIFT_BITS) + 1;
+        if last > 0xff {
+            self.put(self.buffer + 1);
+            for _ in 0..self.help {
+                self.put(0);
+            }
+        } else {
+            self.put(self.buffer);
+            for _ in 0..self.help {
+                self.put(0xff);
+            }
+        }
+        self.put(last & 0xff);
+        self.output.extend_from_slice(&[0, 0, 0]);
+        self.output
+    }
+}
+
+/// Residuals that make the NN filters and predictor output `target`
+fn mono_residuals(target: &[i32], level: usize) -> Vec<i32> {
+    let mut filters = NnFilter::for_level(level);
+    let mut predictor = Predictor::default();
+    target
+        .iter()
+        .map(|&output| {
+            let prediction = predictor.begin_mono();
+            let mut value = predictor.mono_input(output, prediction);
+            predictor.end_mono(value, prediction);
+            for filter in filters.iter_mut().rev() {
+                let input = value.wrapping_sub(filter.prediction());
+                filter.update(input, value);
+                value = input;
+            }
+            value
+        })
+        .collect()
+}
+
+fn encode_frame(
+    stream: &mut Vec<u8>,
+    frame: &[i32],
+    channels: usize,
+    bits_per_sample: u16,
+    level: usize,
+) {
+    let pcm: Vec<[i32; 2]> = frame
+        .chunks_exact(channels)
+        .map(|s| [s[0], s[channels - 1]])
+        .collect();
+    let crc = frame_crc(&pcm, channels, bits_per_sample);
+
+    let silent = pcm.iter().all(|&[l, r]| l == 0 && r == 0);
+    let pseudo_stereo = channels == 2 && pcm.iter().all(|&[l, r]| l == r);
+    let flags = match (silent, channels) {
+        (true, 1) => FRAME_MONO_SILENCE,
+        (true, _) => FRAME_STEREO_SILENCE,
+        (false, 2) if pseudo_stereo => FRAME_PSEUDO_STEREO,
+        _ => 0,
+    };
+
+    let mut coder = RangeEncoder::new();
+    if !silent {
+        if channels == 1 || pseudo_stereo {
+            let left: Vec<i32> = pcm.iter().map(|s| s[0]).collect();
+            let mut rice = Rice::default();
+            for value in mono_residuals(&left, level) {
+                coder.value(&mut rice, value);
+            }
+        } else {
+            // Left/right to mid/side, as the decoder's inverse
+            let (y, x): (Vec<i32>, Vec<i32>) = pcm
+                .iter()
+                .map(|&[l, r]| {
+                    let y = r.wrapping_sub(l);
+                    (y, l.wrapping_add(y / 2))
+                })
+                .unzip();
+
+            let mut predictor = Predictor::default();
+            let mut filters = [NnFilter::for_level(level), NnFilter::for_level(level)];
+            let mut residuals = Vec::with_capacity(y.len());
+            for (&y, &x) in y.iter().zip(&x) {
+                let mut inputs = [0; 2];
+                for (channel, output) in [y, x].into_iter().enumerate() {
+                    let prediction = predictor.begin_stereo(channel);
+                    let input = predictor.stereo_input(channel, output, prediction);
+                    predictor.end_stereo(channel, input, prediction);
+
+                    let mut value = input;
+                    for filter in filters[channel].iter_mut().rev() {
+                        let input = value.wrapping_sub(filter.prediction());
+                        filter.update(input, value);
+                        value = input;
+                    }
+                    inputs[channel] = value;
+                }
+                residuals.push(inputs);
+            }
+
+            let mut rice_y = Rice::default();
+            let mut rice_x = Rice::default();
+            for [y, x] in residuals {
+                coder.value(&mut rice_y, y);
+                coder.value(&mut rice_x, x);
+            }
+        }
+    }
+
+    if flags == 0 {
+        stream.extend_from_slice(&crc.to_be_bytes());
+    } else {
+        stream.extend_from_slice(&(crc | 0x8000_0000).to_be_bytes());
+        stream.extend_from_slice(&flags.to_be_bytes());
+    }
+    stream.extend(coder.finish());
+}
+
+/// Build an APEv2 tag (items and footer, no header)
+///
+/// Items are `(key, flags, value)`; flags `2` marks binary items such as
+/// cover art.
+pub fn ape_tag(items: &[(&str, u32, &[u8])]) -> Vec<u8> {
+    const FOOTER_SIZE: u32 = 32;
+
+    let mut tag = Vec::new();
+    for (key, flags, value) in items {
+        tag.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        tag.extend_from_slice(&flags.to_le_bytes());
+        tag.extend_from_slice(key.as_bytes());
+        tag.push(0);
+        tag.extend_from_slice(value);
+    }
+
+    let size = tag.len() as u32 + FOOTER_SIZE;
+    tag.extend_from_slice(b"APETAGEX");
+    tag.extend_from_slice(&2000u32.to_le_bytes());
+    tag.extend_from_slice(&size.to_le_bytes());
+    tag.extend_from_slice(&(items.len() as u32).to_le_bytes());
+    tag.extend_from_slice(&0u32.to_le_bytes());
+    tag.extend_from_slice(&[0; 8]);
+    tag
+}
@@ -1,10 +1,15 @@
 //! Test utilities for audio testing
 //!
-//! Provides test signal generation and analysis tools for verifying
-//! audio processing algorithms.
+//! Provides test signal generation, analysis tools and synthetic test
+//! files for verifying audio processing algorithms.
 
 pub mod analysis;
+pub mod ape;
+pub mod mp3;
+#[cfg(feature = "opus")]
+pub mod opus;
 pub mod signals;
+pub mod wavpack;
 
 pub use analysis::*;
 pub use signals::*;
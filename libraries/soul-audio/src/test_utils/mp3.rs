@@ -0,0 +1,211 @@
+//! Synthetic MP3 files for gapless playback testing
+//!
+//! Writes minimal MPEG-1 Layer III streams (44.1 kHz mono, 128 kbps CBR)
+//! without needing an encoder. Each granule is either digital silence or a
+//! fixed set of spectral lines, which decodes to a steady signal that never
+//! drops to silence.
+//!
+//! A track starts and ends with one silent frame. Its LAME tag declares
+//! these as encoder delay and padding, so a gapless decoder plays only the
+//! steady part and consecutive tracks join without silence in between,
+//! while a decoder that ignores the tag inserts a gap at every boundary.
+//!
+//! [`write_vbr_mp3`] writes a variable bitrate stream with no Xing header
+//! at all, so demuxers have no seek table to go by.
+
+use std::path::Path;
+
+/// Sample rate of the generated files
+pub const MP3_SAMPLE_RATE: u32 = 44100;
+
+/// Samples per MPEG-1 Layer III frame (two granules)
+pub const MP3_FRAME_SAMPLES: u64 = 1152;
+
+/// Decoder delay of MP3 decoders, added to the LAME encoder delay
+const MP3_DECODER_DELAY: u64 = 529;
+
+/// Frame header: MPEG-1 Layer III, no CRC, 128 kbps, 44.1 kHz, mono
+const FRAME_HEADER: [u8; 4] = [0xFF, 0xFB, 0x90, 0xC0];
+
+/// Size of a 128 kbps frame at 44.1 kHz without padding
+const FRAME_BYTES: usize = 417;
+
+/// Bitrate indices cycled through by VBR streams (128, 192 and 320 kbps),
+/// with their frame sizes at 44.1 kHz
+const VBR_BITRATES: [(u8, usize); 3] = [(9, 417), (11, 626), (14, 1044)];
+
+/// Header plus mono side information
+const SIDE_INFO_END: usize = 21;
+
+/// Global gain of the tone granules (decodes to a peak around 0.66)
+const TONE_GAIN: u32 = 200;
+
+/// Spectral lines set in tone granules, with their signs (true = negative)
+const TONE_LINES: [(usize, bool); 4] = [(30, false), (41, true), (57, false), (75, true)];
+
+/// Write a gapless MP3 track
+///
+/// The track has `tone_frames` frames of signal between the silent frames
+/// declared as encoder delay and padding.
+///
+/// # Returns
+/// The number of samples a gapless decoder plays: everything from the end
+/// of the encoder delay (plus decoder delay) to the start of the padding.
+pub fn write_gapless_mp3(path: impl AsRef<Path>, tone_frames: usize) -> std::io::Result<u64> {
+    let total_frames = tone_frames + 2;
+
+    // The silent first frame is the encoder delay; the padding covers the
+    // silent last frame plus the decoder delay
+    let encoder_delay = MP3_FRAME_SAMPLES;
+    let padding = MP3_FRAME_SAMPLES + MP3_DECODER_DELAY;
+
+    let mut file = info_frame(total_frames as u32, encoder_delay as u32, padding as u32);
+    file.extend(audio_frame(FRAME_HEADER, FRAME_BYTES, TONE_GAIN, false));
+    for _ in 0..tone_frames {
+        file.extend(audio_frame(FRAME_HEADER, FRAME_BYTES, TONE_GAIN, true));
+    }
+    file.extend(audio_frame(FRAME_HEADER, FRAME_BYTES, TONE_GAIN, false));
+    std::fs::write(path, file)?;
+
+    Ok(total_frames as u64 * MP3_FRAME_SAMPLES - encoder_delay - padding)
+}
+
+/// Write a VBR MP3 stream of `frames` tone frames without a Xing header
+///
+/// Frames cycle through three bitrates, and their level steps down over
+/// eight frames, so audio from one frame is never mistaken for another.
+pub fn write_vbr_mp3(path: impl AsRef<Path>, frames: usize) -> std::io::Result<()> {
+    let mut file = Vec::new();
+    for frame in 0..frames {
+        let (bitrate_index, frame_bytes) = VBR_BITRATES[frame % VBR_BITRATES.len()];
+        let mut header = FRAME_HEADER;
+        header[2] = bitrate_index << 4;
+        let gain = TONE_GAIN - 2 * (frame % 8) as u32;
+        file.extend(audio_frame(header, frame_bytes, gain, true));
+    }
+    std::fs::write(path, file)
+}
+
+/// Xing "Info" frame with a LAME extension carrying delay and padding
+///
+/// Uses the FFmpeg encoder string, whose LAME extension has no CRC.
+fn info_frame(frames: u32, encoder_delay: u32, padding: u32) -> Vec<u8> {
+    let mut frame = FRAME_HEADER.to_vec();
+    frame.resize(SIDE_INFO_END, 0);
+
+    frame.extend_from_slice(b"Info");
+    frame.extend_from_slice(&1u32.to_be_bytes()); // Frame count present
+    frame.extend_from_slice(&frames.to_be_bytes());
+
+    // Encoder string, then revision, lowpass, ReplayGain (10 bytes),
+    // encoding flags and bitrate before the delay/padding field
+    frame.extend_from_slice(b"Lavc58.91");
+    frame.resize(frame.len() + 12, 0);
+    let trim = (encoder_delay << 12) | padding;
+    frame.extend_from_slice(&trim.to_be_bytes()[1..]);
+
+    frame.resize(FRAME_BYTES, 0);
+    frame
+}
+
+/// Audio frame whose two granules are silent or carry the tone
+fn audio_frame(header: [u8; 4], frame_bytes: usize, gain: u32, tone: bool) -> Vec<u8> {
+    let granule = if tone {
+        tone_granule()
+    } else {
+        BitWriter::new()
+    };
+
+    let mut side_info = BitWriter::new();
+    side_info.put(0, 9); // main_data_begin (no bit reservoir)
+    side_info.put(0, 5); // private bits
+    side_info.put(0, 4); // scfsi
+    for _ in 0..2 {
+        side_info.put(granule.len() as u32, 12); // part2_3_length
+        side_info.put(0, 9); // big_values (everything is in count1)
+        side_info.put(gain, 8); // global_gain
+        side_info.put(0, 4); // scalefac_compress (no scalefactors)
+        side_info.put(0, 1); // window_switching_flag
+        side_info.put(0, 15); // table_select
+        side_info.put(0, 4); // region0_count
+        side_info.put(0, 3); // region1_count
+        side_info.put(0, 1); // preflag
+        side_info.put(0, 1); // scalefac_scale
+        side_info.put(1, 1); // count1table_select (table B)
+    }
+
+    let mut main_data = BitWriter::new();
+    main_data.append(&granule);
+    main_data.append(&granule);
+
+    let mut frame = header.to_vec();
+    frame.extend(side_info.bytes);
+    frame.extend(main_data.bytes);
+    frame.resize(frame_bytes, 0);
+    frame
+}
+
+/// Huffman data of a tone granule, coded as count1 quadruples
+///
+/// Count1 table B codes quadruple `vwxy` as `15 - vwxy` in 4 bits,
+/// followed by a sign bit per non-zero value.
+fn tone_granule() -> BitWriter {
+    let last_line = TONE_LINES.iter().map(|&(line, _)| line).max().unwrap_or(0);
+
+    let mut bits = BitWriter::new();
+    for quad in 0..=last_line / 4 {
+        let mut value = 0;
+        let mut signs = Vec::new();
+        for i in 0..4 {
+            if let Some(&(_, negative)) = TONE_LINES.iter().find(|&&(line, _)| line == quad * 4 + i)
+            {
+                value |= 8 >> i;
+                signs.push(u32::from(negative));
+            }
+        }
+        bits.put(15 - value, 4);
+        for sign in signs {
+            bits.put(sign, 1);
+        }
+    }
+    bits
+}
+
+/// MSB-first bit writer
+struct BitWriter {
+    bytes: Vec<u8>,
+    bits: usize,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self {
+            bytes: Vec::new(),
+            bits: 0,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.bits
+    }
+
+    fn put(&mut self, value: u32, count: u32) {
+        for i in (0..count).rev() {
+            if self.bits % 8 == 0 {
+                self.bytes.push(0);
+            }
+            let bit = ((value >> i) & 1) as u8;
+            if let Some(byte) = self.bytes.last_mut() {
+                *byte |= bit << (7 - self.bits % 8);
+            }
+            self.bits += 1;
+        }
+    }
+
+    fn append(&mut self, other: &BitWriter) {
+        for i in 0..other.bits {
+            let bit = (other.bytes[i / 8] >> (7 - i % 8)) & 1;
+            self.put(u32::from(bit), 1);
+        }
+    }
+}
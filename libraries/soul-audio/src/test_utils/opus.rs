@@ -0,0 +1,142 @@
+//! Synthetic Ogg Opus files for decoder testing
+//!
+//! Encodes 48 kHz stereo samples with libopus and muxes the packets into
+//! an Ogg stream the way `opusenc` does: the encoder lookahead is declared
+//! as pre-skip, and the final granule position cuts the padding of the
+//! last packet, so a gapless decoder returns exactly the input length.
+
+use audiopus::coder::Encoder;
+use audiopus::{Application, Channels, SampleRate};
+use std::io;
+use std::path::Path;
+
+/// Sample rate of the generated files (Opus always decodes at 48 kHz)
+pub const OPUS_SAMPLE_RATE: u32 = 48000;
+
+/// Samples per channel in each packet (20 ms)
+const PACKET_FRAMES: usize = 960;
+
+/// Packets per Ogg page (one second)
+const PACKETS_PER_PAGE: usize = 50;
+
+/// Serial number of the single logical stream
+const STREAM_SERIAL: u32 = 0x536f_756c;
+
+/// Write an Ogg Opus file
+///
+/// # Arguments
+/// * `samples` - Interleaved stereo samples at 48 kHz
+/// * `output_gain` - Output gain stored in the header, in Q7.8 dB
+///
+/// # Returns
+/// The pre-skip declared in the header
+pub fn write_ogg_opus(
+    path: impl AsRef<Path>,
+    samples: &[f32],
+    output_gain: i16,
+) -> io::Result<u16> {
+    let encoder = Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Audio)
+        .map_err(io::Error::other)?;
+    let pre_skip = encoder.lookahead().map_err(io::Error::other)? as u16;
+
+    // The decoder output lags the input by the pre-skip, so encode enough
+    // trailing silence to flush the whole input out
+    let frames = (samples.len() / 2) as u64;
+    let end_granule = frames + u64::from(pre_skip);
+    let packets = (end_granule as usize).div_ceil(PACKET_FRAMES);
+    let mut input = samples.to_vec();
+    input.resize(packets * PACKET_FRAMES * 2, 0.0);
+
+    let mut file = Vec::new();
+    let mut sequence = 0;
+    let mut page = |file: &mut Vec<u8>, packets: &[Vec<u8>], granule: u64, flags: u8| {
+        file.extend(ogg_page(packets, granule, sequence, flags));
+        sequence += 1;
+    };
+
+    page(&mut file, &[opus_head(pre_skip, output_gain)], 0, 0x02);
+    page(&mut file, &[opus_tags()], 0, 0);
+
+    let mut buffer = [0u8; 4000];
+    let mut encoded = Vec::with_capacity(PACKETS_PER_PAGE);
+    for (i, chunk) in input.chunks_exact(PACKET_FRAMES * 2).enumerate() {
+        let len = encoder
+            .encode_float(chunk, &mut buffer)
+            .map_err(io::Error::other)?;
+        encoded.push(buffer[..len].to_vec());
+
+        let last = i + 1 == packets;
+        if encoded.len() == PACKETS_PER_PAGE || last {
+            let granule = (((i + 1) * PACKET_FRAMES) as u64).min(end_granule);
+            page(&mut file, &encoded, granule, if last { 0x04 } else { 0 });
+            encoded.clear();
+        }
+    }
+
+    std::fs::write(path, file)?;
+    Ok(pre_skip)
+}
+
+/// Identification header (RFC 7845, section 5.1), channel mapping family 0
+fn opus_head(pre_skip: u16, output_gain: i16) -> Vec<u8> {
+    let mut head = b"OpusHead".to_vec();
+    head.push(1); // Version
+    head.push(2); // Channels
+    head.extend_from_slice(&pre_skip.to_le_bytes());
+    head.extend_from_slice(&OPUS_SAMPLE_RATE.to_le_bytes());
+    head.extend_from_slice(&output_gain.to_le_bytes());
+    head.push(0); // Mapping family
+    head
+}
+
+/// Comment header with a vendor string and no comments
+fn opus_tags() -> Vec<u8> {
+    let vendor = b"soul-audio test";
+    let mut tags = b"OpusTags".to_vec();
+    tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    tags.extend_from_slice(vendor);
+    tags.extend_from_slice(&0u32.to_le_bytes());
+    tags
+}
+
+/// Ogg page holding complete packets
+fn ogg_page(packets: &[Vec<u8>], granule: u64, sequence: u32, flags: u8) -> Vec<u8> {
+    let mut lacing = Vec::new();
+    for packet in packets {
+        lacing.resize(lacing.len() + packet.len() / 255, 255);
+        lacing.push((packet.len() % 255) as u8);
+    }
+
+    let mut page = b"OggS".to_vec();
+    page.push(0); // Version
+    page.push(flags);
+    page.extend_from_slice(&granule.to_le_bytes());
+    page.extend_from_slice(&STREAM_SERIAL.to_le_bytes());
+    page.extend_from_slice(&sequence.to_le_bytes());
+    page.extend_from_slice(&[0; 4]); // CRC, filled in below
+    page.push(lacing.len() as u8);
+    page.extend(lacing);
+    for packet in packets {
+        page.extend_from_slice(packet);
+    }
+
+    let crc = ogg_crc(&page);
+    page[22..26].copy_from_slice(&crc.to_le_bytes());
+    page
+}
+
+/// CRC-32 of an Ogg page (polynomial 0x04c11db7, no reflection)
+fn ogg_crc(data: &[u8]) -> u32 {
+    let mut crc = 0u32;
+    for &byte in data {
+        crc ^= u32::from(byte) << 24;
+        for _ in 0..8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ 0x04c1_1db7
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
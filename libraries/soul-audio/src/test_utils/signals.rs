@@ -424,6 +424,46 @@ pub fn generate_crosstalk_test_signal(
     samples
 }
 
+/// Generate integer PCM for lossless codec round trips
+///
+/// Each channel is a sine at its own frequency plus low-level noise, with
+/// a stretch of digital silence in the middle (a quarter of the signal)
+/// so encoders hit their silence paths. Deterministic.
+///
+/// # Arguments
+/// * `frames` - Samples per channel
+/// * `channels` - Number of channels
+/// * `bits_per_sample` - Sample width; values use about half of full scale
+///
+/// # Returns
+/// Interleaved samples
+pub fn generate_pcm_test_signal(frames: usize, channels: usize, bits_per_sample: u32) -> Vec<i32> {
+    let amplitude = f64::from(1u32 << (bits_per_sample - 2));
+    let noise_bits = bits_per_sample / 4;
+    let silence = frames * 3 / 8..frames * 5 / 8;
+    let mut state = 0x2545_f491u32;
+
+    let mut samples = Vec::with_capacity(frames * channels);
+    for i in 0..frames {
+        for channel in 0..channels {
+            if silence.contains(&i) {
+                samples.push(0);
+                continue;
+            }
+            // xorshift32
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            let noise = (state >> (32 - noise_bits)) as i32 - (1 << (noise_bits - 1));
+
+            let frequency = 220.0 * (channel + 1) as f64;
+            let phase = 2.0 * std::f64::consts::PI * frequency * i as f64 / 44100.0;
+            samples.push((phase.sin() * amplitude) as i32 + noise);
+        }
+    }
+    samples
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,580 @@
+//! Synthetic WavPack files for decoder testing
+//!
+//! A small WavPack 4 encoder: fixed decorrelation passes, the adaptive
+//! median entropy coder with zero runs, and optionally hybrid (lossy) mode
+//! with a fixed error limit. It mirrors the decoder's state step by step,
+//! so every block carries the weights, history and medians the previous
+//! block ended with, the way the reference encoder writes them.
+
+use crate::formats::wavpack::block::{
+    restore_weight, DecorrPass, BYTES_STORED, CROSS_DECORR, FINAL_BLOCK, HYBRID_FLAG,
+    ID_CHANNEL_INFO, ID_DECORR_SAMPLES, ID_DECORR_TERMS, ID_DECORR_WEIGHTS, ID_ENTROPY_VARS,
+    ID_HYBRID_PROFILE, ID_LARGE, ID_ODD_SIZE, ID_SAMPLE_RATE, ID_WV_BITSTREAM, INITIAL_BLOCK,
+    JOINT_STEREO, MAG_LSB, MONO_FLAG, SAMPLE_RATES, SRATE_LSB,
+};
+use crate::formats::wavpack::words::{exp2, level_decay, log2, log2s, Words};
+use std::io;
+use std::path::Path;
+
+/// Stream version written to block headers
+const VERSION: u16 = 0x410;
+
+/// Settings of a generated file
+#[derive(Debug, Clone)]
+pub struct WavPackOptions {
+    pub sample_rate: u32,
+    pub channels: usize,
+    /// 8, 16 or 24
+    pub bits_per_sample: u32,
+    /// Samples per channel in each block
+    pub block_samples: usize,
+    /// Hybrid (lossy) mode: the largest coding error per sample (a power
+    /// of two)
+    pub hybrid_error_limit: Option<u32>,
+}
+
+impl Default for WavPackOptions {
+    fn default() -> Self {
+        Self {
+            sample_rate: 44100,
+            channels: 2,
+            bits_per_sample: 16,
+            block_samples: 4096,
+            hybrid_error_limit: None,
+        }
+    }
+}
+
+/// Write a WavPack file
+///
+/// # Arguments
+/// * `samples` - Interleaved integer samples at `options.bits_per_sample`
+///
+/// # Returns
+/// The samples a decoder returns: `samples` itself for lossless files, the
+/// quantized samples in hybrid mode
+pub fn write_wavpack(
+    path: impl AsRef<Path>,
+    samples: &[i32],
+    options: &WavPackOptions,
+) -> io::Result<Vec<i32>> {
+    let (data, decoded) = encode_wavpack(samples, options);
+    std::fs::write(path, data)?;
+    Ok(decoded)
+}
+
+/// Encode a WavPack stream; returns the file and the decoded samples
+pub fn encode_wavpack(samples: &[i32], options: &WavPackOptions) -> (Vec<u8>, Vec<i32>) {
+    let channels = options.channels;
+    let total = samples.len() / channels;
+
+    // Channel pairs, then a trailing mono block for odd channel counts
+    let mut encoders: Vec<BlockEncoder> = (0..channels)
+        .step_by(2)
+        .map(|first| BlockEncoder::new(first, (channels - first).min(2), options))
+        .collect();
+
+    let mut file = Vec::new();
+    let mut decoded = vec![0; samples.len()];
+    let mut start = 0;
+    while start < total {
+        let count = options.block_samples.min(total - start);
+        let frames = &samples[start * channels..(start + count) * channels];
+        let last = encoders.len() - 1;
+
+        for (i, encoder) in encoders.iter_mut().enumerate() {
+            let mut flags = 0;
+            if i == 0 {
+                flags |= INITIAL_BLOCK;
+            }
+            if i == last {
+                flags |= FINAL_BLOCK;
+            }
+            let (block, output) =
+                encoder.encode(frames, channels, start as u32, total as u32, flags, i == 0);
+            file.extend(block);
+
+            let out = &mut decoded[start * channels..(start + count) * channels];
+            for (frame, values) in out
+                .chunks_exact_mut(channels)
+                .zip(output.chunks_exact(encoder.channels))
+            {
+                frame[encoder.first..encoder.first + encoder.channels].copy_from_slice(values);
+            }
+        }
+        start += count;
+    }
+
+    (file, decoded)
+}
+
+/// Weight as the signed byte a block stores
+fn store_weight(weight: i32) -> i8 {
+    let mut weight = weight.clamp(-1024, 1024);
+    if weight > 0 {
+        weight -= (weight + 64) >> 7;
+    }
+    ((weight + 4) >> 3) as i8
+}
+
+/// LSB-first bit writer
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    bits: usize,
+}
+
+impl BitWriter {
+    fn bit(&mut self, bit: bool) {
+        if self.bits % 8 == 0 {
+            self.bytes.push(0);
+        }
+        if bit {
+            *self.bytes.last_mut().expect("byte pushed above") |= 1 << (self.bits % 8);
+        }
+        self.bits += 1;
+    }
+
+    fn bits(&mut self, value: u32, count: u32) {
+        for i in 0..count {
+            self.bit(value >> i & 1 == 1);
+        }
+    }
+
+    fn unary(&mut self, ones: u32) {
+        for _ in 0..ones {
+            self.bit(true);
+        }
+        self.bit(false);
+    }
+
+    fn count(&mut self, value: u32) {
+        if value < 2 {
+            self.unary(value);
+        } else {
+            let length = 32 - value.leading_zeros();
+            self.unary(length);
+            self.bits(value & ((1 << (length - 1)) - 1), length - 1);
+        }
+    }
+
+    /// Class prefix with the escape for long prefixes
+    fn prefix(&mut self, prefix: u32) {
+        if prefix < 16 {
+            self.unary(prefix);
+        } else {
+            self.unary(16);
+            self.count(prefix - 16);
+        }
+    }
+
+    fn tail(&mut self, value: u32, max: u32) {
+        if max == 0 {
+            return;
+        }
+        let bits = max.ilog2();
+        let extra = ((1u64 << (bits + 1)) - u64::from(max) - 1) as u32;
+        if value < extra {
+            self.bits(value, bits);
+        } else {
+            let value = value + extra;
+            self.bits(value >> 1, bits);
+            self.bit(value & 1 == 1);
+        }
+    }
+
+    fn append(&mut self, bits: &[bool]) {
+        for &bit in bits {
+            self.bit(bit);
+        }
+    }
+}
+
+/// Entropy coder mirroring [`Words::read_value`]
+struct WordsWriter {
+    words: Words,
+    bits: BitWriter,
+    /// Value whose prefix waits for the class of the next value, which
+    /// its lowest bit announces: (prefix / 2, bits after the prefix)
+    pending: Option<(u32, Vec<bool>)>,
+    /// Length of the zero run being collected
+    run: Option<u32>,
+}
+
+impl WordsWriter {
+    fn flush_pending(&mut self, next_nonzero_class: bool) {
+        if let Some((half, rest)) = self.pending.take() {
+            self.bits.prefix(half * 2 + u32::from(next_nonzero_class));
+            self.bits.append(&rest);
+            self.words.one = next_nonzero_class;
+            self.words.zero = !next_nonzero_class;
+        }
+    }
+
+    fn decay_level(&mut self, channel: usize) {
+        let slow_level = &mut self.words.channels[channel].slow_level;
+        *slow_level -= level_decay(*slow_level);
+    }
+
+    /// Code one value; returns the value the decoder reads back
+    fn write(&mut self, channel: usize, value: i32) -> i32 {
+        if let Some(run) = self.run {
+            if value == 0 {
+                self.run = Some(run + 1);
+                self.decay_level(channel);
+                return 0;
+            }
+            self.bits.count(run);
+            self.run = None;
+        } else if self.pending.is_none() && self.words.at_run_check() {
+            if value == 0 {
+                self.words.start_run(1);
+                self.run = Some(1);
+                self.decay_level(channel);
+                return 0;
+            }
+            self.bits.count(0);
+        }
+
+        let negative = value < 0;
+        let magnitude = if negative {
+            !value as u32
+        } else {
+            value as u32
+        };
+        let class = self.words.channels[channel].class_of(magnitude);
+        self.flush_pending(class > 0);
+
+        let mut rest = Vec::new();
+        let with_prefix = if self.words.zero {
+            self.words.zero = false;
+            false
+        } else {
+            let half = if self.words.one { class - 1 } else { class };
+            self.pending = Some((half, Vec::new()));
+            true
+        };
+
+        if self.words.hybrid && channel == 0 {
+            self.words.update_error_limit();
+        }
+
+        let words = &mut self.words.channels[channel];
+        let (mut base, mut add) = words.enter_class(class);
+        let coded = if words.error_limit == 0 {
+            let mut tail = BitWriter::default();
+            tail.tail(magnitude - base, add);
+            rest.extend((0..tail.bits).map(|i| tail.bytes[i / 8] >> (i % 8) & 1 == 1));
+            magnitude
+        } else {
+            let mut mid = ((u64::from(base) * 2 + u64::from(add) + 1) >> 1) as u32;
+            while add > words.error_limit {
+                let high = magnitude >= mid;
+                rest.push(high);
+                if high {
+                    add -= mid - base;
+                    base = mid;
+                } else {
+                    add = mid - base - 1;
+                }
+                mid = ((u64::from(base) * 2 + u64::from(add) + 1) >> 1) as u32;
+            }
+            mid
+        };
+        rest.push(negative);
+        if self.words.hybrid_bitrate {
+            let words = &mut self.words.channels[channel];
+            words.slow_level += log2(coded) - level_decay(words.slow_level);
+        }
+
+        if with_prefix {
+            self.pending.as_mut().expect("pending set above").1 = rest;
+        } else {
+            self.bits.append(&rest);
+        }
+
+        let coded = coded as i32;
+        if negative {
+            !coded
+        } else {
+            coded
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        self.flush_pending(false);
+        if let Some(run) = self.run {
+            self.bits.count(run);
+        }
+        self.bits.bytes
+    }
+}
+
+/// Encoder state of one block slot (a channel pair or a mono channel)
+struct BlockEncoder {
+    /// First channel of the slot in the interleaved input
+    first: usize,
+    channels: usize,
+    bits_per_sample: u32,
+    sample_rate: u32,
+    hybrid_error_limit: Option<u32>,
+    passes: Vec<DecorrPass>,
+    medians: [[u32; 3]; 2],
+}
+
+impl BlockEncoder {
+    fn new(first: usize, channels: usize, options: &WavPackOptions) -> Self {
+        // Decoding order; cross-channel terms only for lossless stereo
+        let terms: &[(i32, i32)] = match (channels, options.hybrid_error_limit) {
+            (2, None) => &[(18, 2), (-1, 2), (3, 2), (17, 2)],
+            _ => &[(17, 2), (2, 2)],
+        };
+        Self {
+            first,
+            channels,
+            bits_per_sample: options.bits_per_sample,
+            sample_rate: options.sample_rate,
+            hybrid_error_limit: options.hybrid_error_limit,
+            passes: terms
+                .iter()
+                .map(|&(term, delta)| DecorrPass {
+                    term,
+                    delta,
+                    ..DecorrPass::default()
+                })
+                .collect(),
+            medians: [[0; 3]; 2],
+        }
+    }
+
+    /// Encode one block; returns it and the decoded samples of its channels
+    fn encode(
+        &mut self,
+        frames: &[i32],
+        stride: usize,
+        block_index: u32,
+        total_samples: u32,
+        position_flags: u32,
+        first_block: bool,
+    ) -> (Vec<u8>, Vec<i32>) {
+        let stereo = self.channels == 2;
+        let count = frames.len() / stride;
+        let hybrid = self.hybrid_error_limit.is_some();
+
+        let mut flags = position_flags
+            | (self.bits_per_sample / 8 - 1) & BYTES_STORED
+            | (self.bits_per_sample - 1) << MAG_LSB;
+        let rate_index = SAMPLE_RATES.iter().position(|&r| r == self.sample_rate);
+        flags |= (rate_index.unwrap_or(15) as u32) << SRATE_LSB;
+        if stereo {
+            flags |= JOINT_STEREO;
+            if self.passes.iter().any(|p| p.term < 0) {
+                flags |= CROSS_DECORR;
+            }
+        } else {
+            flags |= MONO_FLAG;
+        }
+        if hybrid {
+            flags |= HYBRID_FLAG;
+        }
+
+        let mut payload = Vec::new();
+        if first_block && rate_index.is_none() {
+            sub_block(
+                &mut payload,
+                ID_SAMPLE_RATE,
+                &self.sample_rate.to_le_bytes()[..3],
+            );
+        }
+        if first_block && stride > 2 {
+            sub_block(&mut payload, ID_CHANNEL_INFO, &[stride as u8, 0]);
+        }
+
+        // Terms in file order (the reverse of decoding order)
+        let terms: Vec<u8> = self
+            .passes
+            .iter()
+            .rev()
+            .map(|p| ((p.term + 5) as u8 & 0x1f) | (p.delta as u8) << 5)
+            .collect();
+        sub_block(&mut payload, ID_DECORR_TERMS, &terms);
+
+        let mut weights = Vec::new();
+        for pass in self.passes.iter_mut().rev() {
+            let stored = store_weight(pass.weight_a);
+            weights.push(stored as u8);
+            pass.weight_a = restore_weight(stored);
+            if stereo {
+                let stored = store_weight(pass.weight_b);
+                weights.push(stored as u8);
+                pass.weight_b = restore_weight(stored);
+            }
+        }
+        sub_block(&mut payload, ID_DECORR_WEIGHTS, &weights);
+
+        let mut history = Vec::new();
+        let mut store = |value: &mut i32| {
+            let log = log2s(*value);
+            history.extend_from_slice(&(log as i16).to_le_bytes());
+            *value = exp2(log);
+        };
+        for pass in self.passes.iter_mut().rev() {
+            if pass.term > 8 {
+                store(&mut pass.samples_a[0]);
+                store(&mut pass.samples_a[1]);
+                if stereo {
+                    store(&mut pass.samples_b[0]);
+                    store(&mut pass.samples_b[1]);
+                }
+            } else if pass.term < 0 {
+                store(&mut pass.samples_a[0]);
+                store(&mut pass.samples_b[0]);
+            } else {
+                for i in 0..pass.term as usize {
+                    store(&mut pass.samples_a[i]);
+                    if stereo {
+                        store(&mut pass.samples_b[i]);
+                    }
+                }
+            }
+        }
+        sub_block(&mut payload, ID_DECORR_SAMPLES, &history);
+
+        let mut words = Words {
+            stereo,
+            hybrid,
+            ..Words::default()
+        };
+        let mut entropy = Vec::new();
+        for (channel, medians) in words
+            .channels
+            .iter_mut()
+            .zip(&mut self.medians)
+            .take(self.channels)
+        {
+            for (median, stored) in channel.median.iter_mut().zip(medians.iter_mut()) {
+                let log = log2(*stored);
+                entropy.extend_from_slice(&(log as i16).to_le_bytes());
+                *median = exp2(log) as u32;
+            }
+        }
+        sub_block(&mut payload, ID_ENTROPY_VARS, &entropy);
+
+        if let Some(limit) = self.hybrid_error_limit {
+            // Constant bitrate: the error limit is exp2(bitrate)
+            let bitrate = log2(limit);
+            let mut profile = Vec::new();
+            for channel in words.channels.iter_mut().take(self.channels) {
+                profile.extend_from_slice(&(bitrate as u16).to_le_bytes());
+                channel.bitrate_acc = (bitrate as u32) << 16;
+            }
+            sub_block(&mut payload, ID_HYBRID_PROFILE, &profile);
+        }
+
+        let mut writer = WordsWriter {
+            words,
+            bits: BitWriter::default(),
+            pending: None,
+            run: None,
+        };
+        let mut crc = u32::MAX;
+        let mut output = Vec::with_capacity(count * self.channels);
+        let mut position = 0;
+
+        for frame in frames.chunks_exact(stride) {
+            if stereo {
+                let (left, right) = (frame[self.first], frame[self.first + 1]);
+                let target = (
+                    left.wrapping_sub(right),
+                    right.wrapping_add(left.wrapping_sub(right) >> 1),
+                );
+
+                // Walk the passes backwards to find the residuals
+                let mut values = target;
+                for pass in self.passes.iter().rev() {
+                    let prediction = pass.predict_stereo(position, values);
+                    values = (
+                        values.0.wrapping_sub(prediction.0),
+                        values.1.wrapping_sub(prediction.1),
+                    );
+                }
+
+                let mut a = writer.write(0, values.0);
+                let mut b = writer.write(1, values.1);
+                for pass in &mut self.passes {
+                    (a, b) = pass.unpack_stereo(position, a, b);
+                }
+                let right = b.wrapping_sub(a >> 1);
+                let left = a.wrapping_add(right);
+                crc = crc
+                    .wrapping_mul(3)
+                    .wrapping_add(left as u32)
+                    .wrapping_mul(3)
+                    .wrapping_add(right as u32);
+                output.push(left);
+                output.push(right);
+            } else {
+                let mut value = frame[self.first];
+                for pass in self.passes.iter().rev() {
+                    value = value.wrapping_sub(pass.predict_mono(position));
+                }
+
+                let mut sample = writer.write(0, value);
+                for pass in &mut self.passes {
+                    sample = pass.unpack_mono(position, sample);
+                }
+                crc = crc.wrapping_mul(3).wrapping_add(sample as u32);
+                output.push(sample);
+            }
+            position = (position + 1) & 7;
+        }
+
+        // Carry the state into the next block, history rotated to start at
+        // position 0
+        for (stored, channel) in self.medians.iter_mut().zip(&writer.words.channels) {
+            *stored = channel.median;
+        }
+        for pass in &mut self.passes {
+            if (1..=8).contains(&pass.term) {
+                let rotate =
+                    |samples: &[i32; 8]| std::array::from_fn(|j| samples[(position + j) & 7]);
+                pass.samples_a = rotate(&pass.samples_a);
+                pass.samples_b = rotate(&pass.samples_b);
+            }
+        }
+
+        sub_block(&mut payload, ID_WV_BITSTREAM, &writer.finish());
+
+        let mut block = b"wvpk".to_vec();
+        block.extend_from_slice(&((payload.len() + 24) as u32).to_le_bytes());
+        block.extend_from_slice(&VERSION.to_le_bytes());
+        block.extend_from_slice(&[0, 0]);
+        block.extend_from_slice(&total_samples.to_le_bytes());
+        block.extend_from_slice(&block_index.to_le_bytes());
+        block.extend_from_slice(&(count as u32).to_le_bytes());
+        block.extend_from_slice(&flags.to_le_bytes());
+        block.extend_from_slice(&crc.to_le_bytes());
+        block.extend(payload);
+        (block, output)
+    }
+}
+
+/// Append a metadata sub-block
+fn sub_block(payload: &mut Vec<u8>, id: u8, data: &[u8]) {
+    let words = data.len().div_ceil(2);
+    let mut id = id;
+    if data.len() % 2 == 1 {
+        id |= ID_ODD_SIZE;
+    }
+    if words > 0xff {
+        payload.push(id | ID_LARGE);
+        payload.extend_from_slice(&(words as u32).to_le_bytes()[..3]);
+    } else {
+        payload.push(id);
+        payload.push(words as u8);
+    }
+    payload.extend_from_slice(data);
+    if data.len() % 2 == 1 {
+        payload.push(0);
+    }
+}
@@ -915,7 +915,7 @@ fn test_z_summary_and_bug_report() {
     println!("");
     println!("CRITICAL BUGS: 0");
     println!("");
-    println!("MINOR ISSUES: 2");
+    println!("MINOR ISSUES: 1");
     println!("");
     println!("1. NO AUTOMATIC IR NORMALIZATION");
     println!("   Severity: Minor (Documentation/Design)");
@@ -927,14 +927,6 @@ fn test_z_summary_and_bug_report() {
     println!("   Recommendation: Add optional normalization parameter to");
     println!("   load_impulse_response() or document this behavior clearly.");
     println!("");
-    println!("2. NO SAMPLE RATE CONVERSION FOR IR");
-    println!("   Severity: Minor (Documentation)");
-    println!("   Description: The engine stores ir_sample_rate but does not");
-    println!("   resample the IR when processing at different sample rates.");
-    println!("   This can cause pitch/speed shifts in the reverb tail.");
-    println!("   Recommendation: Either implement automatic resampling or");
-    println!("   add validation/warning when sample rates mismatch.");
-    println!("");
     println!("-----------------------------------------------------------------");
     println!("VERIFIED CORRECT BEHAVIORS:");
     println!("-----------------------------------------------------------------");
@@ -411,9 +411,10 @@ fn test_ogg_format_support() {
 #[test]
 fn test_opus_format_support() {
     let decoder = SymphoniaDecoder::new();
-    assert!(
+    assert_eq!(
         decoder.supports_format(&PathBuf::from("test.opus")),
-        "OPUS format should be supported"
+        cfg!(feature = "opus"),
+        "OPUS format should be supported with the opus feature"
     );
 }
 
@@ -265,7 +265,10 @@ fn test_supports_format() {
     assert!(decoder.supports_format(&PathBuf::from("test.mp3")));
     assert!(decoder.supports_format(&PathBuf::from("test.flac")));
     assert!(decoder.supports_format(&PathBuf::from("test.ogg")));
-    assert!(decoder.supports_format(&PathBuf::from("test.opus")));
+    assert_eq!(
+        decoder.supports_format(&PathBuf::from("test.opus")),
+        cfg!(feature = "opus")
+    );
     assert!(decoder.supports_format(&PathBuf::from("test.wav")));
     assert!(decoder.supports_format(&PathBuf::from("test.m4a")));
     assert!(decoder.supports_format(&PathBuf::from("test.aac")));
@@ -0,0 +1,294 @@
+//! DSD file playback tests
+//!
+//! Writes DSF and DSDIFF files from a DSD64-modulated sine and checks that
+//! they decode to PCM through `SymphoniaDecoder`, seek, and expose their tags
+//! through `extract_metadata`.
+
+use soul_audio::dsd::{DsdConverter, DsdFile, DsdFormat, DsdToPcm};
+use soul_audio::{extract_metadata, SymphoniaDecoder};
+use soul_core::AudioDecoder;
+use std::f32::consts::PI;
+use std::path::Path;
+use std::time::Duration;
+use tempfile::TempDir;
+
+const DSD64_RATE: u32 = 2_822_400;
+
+/// Modulate a 1 kHz sine (amplitude 0.25) to DSD64, one byte vector per channel
+fn dsd_sine(duration_secs: f32) -> Vec<Vec<u8>> {
+    let pcm_rate = 44_100;
+    let frames = (pcm_rate as f32 * duration_secs) as usize;
+    let mut pcm = Vec::with_capacity(frames * 2);
+    for i in 0..frames {
+        let s = (2.0 * PI * 1000.0 * i as f32 / pcm_rate as f32).sin() * 0.25;
+        pcm.push(s);
+        pcm.push(s);
+    }
+
+    let mut converter = DsdConverter::new(DsdFormat::Dsd64, pcm_rate);
+    let bytes = converter.process_pcm(&pcm);
+
+    // Converter output: per PCM frame, 8 bytes for each channel in turn
+    let bytes_per_frame = (DSD64_RATE / pcm_rate / 8) as usize;
+    let mut channels = vec![Vec::new(), Vec::new()];
+    for frame in bytes.chunks_exact(bytes_per_frame * 2) {
+        channels[0].extend_from_slice(&frame[..bytes_per_frame]);
+        channels[1].extend_from_slice(&frame[bytes_per_frame..]);
+    }
+    channels
+}
+
+/// Minimal ID3v2.3 tag with UTF-8 text frames
+fn id3_tag(frames: &[(&str, &str)]) -> Vec<u8> {
+    let mut body = Vec::new();
+    for (id, text) in frames {
+        let mut data = vec![3u8];
+        data.extend_from_slice(text.as_bytes());
+        body.extend_from_slice(id.as_bytes());
+        body.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        body.extend_from_slice(&[0, 0]);
+        body.extend_from_slice(&data);
+    }
+
+    let size = body.len() as u32;
+    let syncsafe = [
+        ((size >> 21) & 0x7F) as u8,
+        ((size >> 14) & 0x7F) as u8,
+        ((size >> 7) & 0x7F) as u8,
+        (size & 0x7F) as u8,
+    ];
+    let mut tag = b"ID3\x03\x00\x00".to_vec();
+    tag.extend_from_slice(&syncsafe);
+    tag.extend_from_slice(&body);
+    tag
+}
+
+fn write_dsf(path: &Path, channels: &[Vec<u8>], tag: &[u8]) {
+    const BLOCK: usize = 4096;
+    let bytes_per_channel = channels[0].len();
+    let blocks = bytes_per_channel.div_ceil(BLOCK);
+    let data_len = (blocks * BLOCK * channels.len()) as u64;
+    let metadata_offset = 28 + 52 + 12 + data_len;
+
+    let mut out = Vec::new();
+    out.extend_from_slice(b"DSD ");
+    out.extend_from_slice(&28u64.to_le_bytes());
+    out.extend_from_slice(&(metadata_offset + tag.len() as u64).to_le_bytes());
+    out.extend_from_slice(&metadata_offset.to_le_bytes());
+
+    out.extend_from_slice(b"fmt ");
+    out.extend_from_slice(&52u64.to_le_bytes());
+    out.extend_from_slice(&1u32.to_le_bytes()); // version
+    out.extend_from_slice(&0u32.to_le_bytes()); // DSD raw
+    out.extend_from_slice(&2u32.to_le_bytes()); // stereo
+    out.extend_from_slice(&(channels.len() as u32).to_le_bytes());
+    out.extend_from_slice(&DSD64_RATE.to_le_bytes());
+    out.extend_from_slice(&1u32.to_le_bytes()); // bits per sample (LSB first)
+    out.extend_from_slice(&(bytes_per_channel as u64 * 8).to_le_bytes());
+    out.extend_from_slice(&(BLOCK as u32).to_le_bytes());
+    out.extend_from_slice(&0u32.to_le_bytes());
+
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&(12 + data_len).to_le_bytes());
+    for block in 0..blocks {
+        for channel in channels {
+            for i in block * BLOCK..(block + 1) * BLOCK {
+                out.push(channel.get(i).map(|b| b.reverse_bits()).unwrap_or(0x69));
+            }
+        }
+    }
+    out.extend_from_slice(tag);
+
+    std::fs::write(path, out).unwrap();
+}
+
+fn write_dff(path: &Path, channels: &[Vec<u8>], tag: &[u8]) {
+    fn chunk(id: [u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut out = id.to_vec();
+        out.extend_from_slice(&(body.len() as u64).to_be_bytes());
+        out.extend_from_slice(body);
+        if body.len() % 2 == 1 {
+            out.push(0);
+        }
+        out
+    }
+
+    let mut prop = b"SND ".to_vec();
+    prop.extend(chunk(*b"FS  ", &DSD64_RATE.to_be_bytes()));
+    let mut chnl = (channels.len() as u16).to_be_bytes().to_vec();
+    chnl.extend_from_slice(b"SLFTSRGT");
+    prop.extend(chunk(*b"CHNL", &chnl));
+    let mut cmpr = b"DSD ".to_vec();
+    cmpr.extend_from_slice(&[14]);
+    cmpr.extend_from_slice(b"not compressed");
+    prop.extend(chunk(*b"CMPR", &cmpr));
+
+    let mut data = Vec::with_capacity(channels[0].len() * channels.len());
+    for i in 0..channels[0].len() {
+        for channel in channels {
+            data.push(channel[i]);
+        }
+    }
+
+    let mut form = b"DSD ".to_vec();
+    form.extend(chunk(*b"FVER", &0x0105_0000u32.to_be_bytes()));
+    form.extend(chunk(*b"PROP", &prop));
+    form.extend(chunk(*b"DSD ", &data));
+    form.extend(chunk(*b"ID3 ", tag));
+
+    std::fs::write(path, chunk(*b"FRM8", &form)).unwrap();
+}
+
+fn rms(samples: &[f32]) -> f32 {
+    (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+/// Amplitude of the 1 kHz component and RMS of everything else (one channel)
+fn sine_fit(samples: &[f32], sample_rate: u32) -> (f64, f64) {
+    let (mut re, mut im) = (0.0f64, 0.0f64);
+    for (i, &s) in samples.iter().enumerate() {
+        let phase = 2.0 * std::f64::consts::PI * 1000.0 * i as f64 / sample_rate as f64;
+        re += s as f64 * phase.cos();
+        im += s as f64 * phase.sin();
+    }
+    let amplitude = 2.0 * (re * re + im * im).sqrt() / samples.len() as f64;
+    let total = rms(samples) as f64;
+    let residual = (total * total - amplitude * amplitude / 2.0).max(0.0).sqrt();
+    (amplitude, residual)
+}
+
+fn check_decodes_to_sine(path: &Path) {
+    let mut decoder = SymphoniaDecoder::new();
+    let buffer = decoder.decode(path).expect("DSD file should decode");
+
+    assert_eq!(buffer.format.sample_rate.as_hz(), 88_200);
+    assert_eq!(buffer.format.channels, 2);
+
+    let frames = buffer.samples.len() / 2;
+    assert!(
+        (frames as i64 - 88_200).abs() <= 1,
+        "expected ~1s of audio, got {} frames",
+        frames
+    );
+
+    // Skip the filter settling time at both ends. The modulator's signal
+    // gain isn't exactly unity, so check for a clean tone rather than a level;
+    // the residual is the modulator's shaped noise left below the cutoff.
+    let left: Vec<f32> = buffer.samples.iter().step_by(2).copied().collect();
+    let (amplitude, residual) = sine_fit(&left[4410..frames - 4410], 88_200);
+    assert!(amplitude > 0.15, "1 kHz tone missing (amplitude {})", amplitude);
+    assert!(
+        residual < amplitude * 0.05,
+        "noise/distortion too high: residual {} for amplitude {}",
+        residual,
+        amplitude
+    );
+}
+
+#[test]
+fn dsf_file_decodes_to_pcm() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("sine.dsf");
+    write_dsf(&path, &dsd_sine(1.0), &id3_tag(&[]));
+
+    check_decodes_to_sine(&path);
+}
+
+#[test]
+fn dff_file_decodes_to_pcm() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("sine.dff");
+    write_dff(&path, &dsd_sine(1.0), &id3_tag(&[]));
+
+    check_decodes_to_sine(&path);
+}
+
+#[test]
+fn dsf_and_dff_carry_identical_streams() {
+    let dir = TempDir::new().unwrap();
+    let channels = dsd_sine(0.25);
+    let dsf = dir.path().join("a.dsf");
+    let dff = dir.path().join("a.dff");
+    write_dsf(&dsf, &channels, &id3_tag(&[]));
+    write_dff(&dff, &channels, &id3_tag(&[]));
+
+    let read_all = |path: &Path| {
+        let mut file = DsdFile::open(path).unwrap();
+        let mut out = vec![Vec::new(), Vec::new()];
+        while file.read(&mut out, 1000).unwrap() > 0 {}
+        out
+    };
+
+    assert_eq!(read_all(&dsf), channels);
+    assert_eq!(read_all(&dff), channels);
+}
+
+#[test]
+fn streaming_decode_seeks_to_frame_boundaries() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("sine.dsf");
+    write_dsf(&path, &dsd_sine(1.0), &id3_tag(&[]));
+
+    let mut decoder = SymphoniaDecoder::new();
+    let info = decoder.open(&path).unwrap();
+    assert_eq!(info.sample_rate, 88_200);
+    assert_eq!(info.channels, 2);
+
+    let duration = decoder.duration().unwrap();
+    assert!((duration.as_secs_f64() - 1.0).abs() < 0.001);
+
+    let actual = decoder.seek(Duration::from_millis(500)).unwrap();
+    assert_eq!(actual, Duration::from_secs_f64(44_100.0 / 88_200.0));
+    assert_eq!(decoder.position(), actual);
+
+    let chunk = decoder.decode_chunk(1024).unwrap().unwrap();
+    assert_eq!(chunk.samples.len(), 2048);
+    assert!(rms(&chunk.samples) > 0.1, "audio after seek should not be silent");
+}
+
+#[test]
+fn dsf_tags_are_extracted() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("tagged.dsf");
+    let tag = id3_tag(&[
+        ("TIT2", "Kind of Blue"),
+        ("TPE1", "Miles Davis"),
+        ("TALB", "Kind of Blue (DSD)"),
+        ("TRCK", "2/5"),
+        ("TYER", "1959"),
+    ]);
+    write_dsf(&path, &dsd_sine(0.1), &tag);
+
+    let metadata = extract_metadata(&path).unwrap();
+    assert_eq!(metadata.title.as_deref(), Some("Kind of Blue"));
+    assert_eq!(metadata.artist.as_deref(), Some("Miles Davis"));
+    assert_eq!(metadata.album.as_deref(), Some("Kind of Blue (DSD)"));
+    assert_eq!(metadata.track_number, Some(2));
+    assert_eq!(metadata.year, Some(1959));
+    assert_eq!(metadata.sample_rate, Some(DSD64_RATE));
+    assert_eq!(metadata.channels, Some(2));
+    assert_eq!(metadata.bit_depth, Some(1));
+}
+
+#[test]
+fn dff_tags_are_extracted() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("tagged.dff");
+    write_dff(
+        &path,
+        &dsd_sine(0.1),
+        &id3_tag(&[("TIT2", "So What"), ("TPE1", "Miles Davis")]),
+    );
+
+    let metadata = extract_metadata(&path).unwrap();
+    assert_eq!(metadata.title.as_deref(), Some("So What"));
+    assert_eq!(metadata.artist.as_deref(), Some("Miles Davis"));
+    assert_eq!(metadata.sample_rate, Some(DSD64_RATE));
+}
+
+#[test]
+fn decimator_matches_file_rate_family() {
+    let decimator = DsdToPcm::for_dsd_rate(DSD64_RATE, 2).unwrap();
+    assert_eq!(decimator.pcm_rate(), 88_200);
+    assert_eq!(decimator.bytes_per_frame(), 4);
+}
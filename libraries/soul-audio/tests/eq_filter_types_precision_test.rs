@@ -0,0 +1,597 @@
+//! EQ Filter Type Precision Testing Suite
+//!
+//! Measures the pass, notch, band-pass, all-pass and tilt bands of the
+//! parametric EQ against their textbook responses.
+//!
+//! ## References:
+//! - **Robert Bristow-Johnson Audio EQ Cookbook**: Biquad filter coefficient formulas
+//! - **Butterworth (1930)**: Maximally flat response, -3 dB at cutoff
+//! - **Linkwitz & Riley (1976)**: Squared Butterworth crossovers, -6 dB at cutoff,
+//!   low-pass + high-pass summing to a flat magnitude
+//!
+//! ## Test Categories:
+//! 1. High-Pass / Low-Pass Cutoff Accuracy (Butterworth and Linkwitz-Riley)
+//! 2. Roll-off Slope (12, 24, 48 dB/octave)
+//! 3. Passband Flatness
+//! 4. Linkwitz-Riley Crossover Summation
+//! 5. Notch and Band-Pass Bandwidth (Q)
+//! 6. All-Pass Magnitude and Phase
+//! 7. Tilt Shelf Response
+//! 8. Stability Near Nyquist
+
+use soul_audio::effects::{AudioEffect, EqBand, FilterAlignment, FilterSlope, ParametricEq};
+use std::f32::consts::PI;
+
+// ============================================================================
+// CONSTANTS AND TOLERANCES
+// ============================================================================
+
+/// Tolerance at the cutoff/center frequency
+const CUTOFF_TOLERANCE_DB: f32 = 0.5;
+
+/// Passband ripple tolerance
+const PASSBAND_TOLERANCE_DB: f32 = 0.1;
+
+/// Tolerance for measured roll-off per octave
+const SLOPE_TOLERANCE_DB: f32 = 2.0;
+
+/// Sample rates for testing
+const SAMPLE_RATES: [u32; 4] = [44100, 48000, 96000, 192000];
+
+const SLOPES: [FilterSlope; 3] = [FilterSlope::Db12, FilterSlope::Db24, FilterSlope::Db48];
+
+// ============================================================================
+// TEST UTILITIES
+// ============================================================================
+
+/// Generate a pure sine wave for frequency response testing
+fn generate_sine(frequency: f32, sample_rate: u32, duration_sec: f32, amplitude: f32) -> Vec<f32> {
+    let num_samples = (sample_rate as f32 * duration_sec) as usize;
+    let mut buffer = Vec::with_capacity(num_samples * 2); // Stereo
+
+    for i in 0..num_samples {
+        let t = i as f32 / sample_rate as f32;
+        let sample = amplitude * (2.0 * PI * frequency * t).sin();
+        buffer.push(sample); // Left
+        buffer.push(sample); // Right
+    }
+
+    buffer
+}
+
+/// Convert linear amplitude to dB
+fn linear_to_db(linear: f32) -> f32 {
+    if linear.abs() < 1e-10 {
+        return -200.0; // Effective silence
+    }
+    20.0 * linear.abs().log10()
+}
+
+/// Calculate RMS level of a buffer
+fn rms_level(buffer: &[f32]) -> f32 {
+    if buffer.is_empty() {
+        return 0.0;
+    }
+    let sum: f32 = buffer.iter().map(|s| s * s).sum();
+    (sum / buffer.len() as f32).sqrt()
+}
+
+/// Check if buffer is stable (no NaN, Inf, or excessive values)
+fn is_stable(buffer: &[f32]) -> bool {
+    buffer.iter().all(|s| s.is_finite() && s.abs() < 1000.0)
+}
+
+/// EQ with a single band
+fn single_band_eq(band: EqBand) -> ParametricEq {
+    let mut eq = ParametricEq::new();
+    eq.set_bands(vec![band]);
+    eq
+}
+
+/// Process a steady sine, returning input and output with the transient skipped
+fn process_sine(
+    eq: &mut dyn AudioEffect,
+    frequency: f32,
+    sample_rate: u32,
+) -> (Vec<f32>, Vec<f32>) {
+    // Prime at this sample rate so coefficients start settled, not smoothing
+    // over from the previous rate
+    eq.process(&mut [0.0, 0.0], sample_rate);
+    eq.reset();
+
+    // Steep filters near 20 Hz ring for a few hundred milliseconds
+    let duration = 1.0;
+    let input = generate_sine(frequency, sample_rate, duration, 0.5);
+    let mut output = input.clone();
+    eq.process(&mut output, sample_rate);
+
+    // Skip initial transient (first half of the buffer)
+    let skip = input.len() / 2;
+    (input[skip..].to_vec(), output[skip..].to_vec())
+}
+
+/// Measure gain at a specific frequency (single-frequency response)
+fn measure_gain_db(eq: &mut dyn AudioEffect, frequency: f32, sample_rate: u32) -> f32 {
+    let (input, output) = process_sine(eq, frequency, sample_rate);
+    linear_to_db(rms_level(&output) / rms_level(&input))
+}
+
+/// Measure the gain of a low-pass + high-pass pair summed, as in a crossover
+fn measure_sum_gain_db(
+    low: &mut dyn AudioEffect,
+    high: &mut dyn AudioEffect,
+    frequency: f32,
+    sample_rate: u32,
+) -> f32 {
+    let (input, low_out) = process_sine(low, frequency, sample_rate);
+    let (_, high_out) = process_sine(high, frequency, sample_rate);
+    let sum: Vec<f32> = low_out.iter().zip(&high_out).map(|(l, h)| l + h).collect();
+    linear_to_db(rms_level(&sum) / rms_level(&input))
+}
+
+/// Normalized correlation of input and output (1 = in phase, -1 = inverted)
+fn measure_correlation(eq: &mut dyn AudioEffect, frequency: f32, sample_rate: u32) -> f32 {
+    let (input, output) = process_sine(eq, frequency, sample_rate);
+    let dot: f32 = input.iter().zip(&output).map(|(a, b)| a * b).sum();
+    let norm = (input.iter().map(|s| s * s).sum::<f32>()
+        * output.iter().map(|s| s * s).sum::<f32>())
+    .sqrt();
+    dot / norm
+}
+
+/// Find the frequency between `low` and `high` where the gain crosses `target_db`
+///
+/// `rising` says whether the gain increases with frequency in that range.
+fn find_crossing(
+    eq: &mut dyn AudioEffect,
+    mut low: f32,
+    mut high: f32,
+    target_db: f32,
+    rising: bool,
+    sample_rate: u32,
+) -> f32 {
+    for _ in 0..20 {
+        let mid = (low * high).sqrt(); // Geometric mean
+        let above = measure_gain_db(eq, mid, sample_rate) > target_db;
+        if above == rising {
+            high = mid;
+        } else {
+            low = mid;
+        }
+    }
+    (low * high).sqrt()
+}
+
+/// Expected gain at the cutoff for an alignment
+fn cutoff_gain_db(alignment: FilterAlignment) -> f32 {
+    match alignment {
+        FilterAlignment::Butterworth => -3.01,
+        FilterAlignment::LinkwitzRiley => -6.02,
+    }
+}
+
+// ============================================================================
+// HIGH-PASS / LOW-PASS CUTOFF ACCURACY
+// ============================================================================
+
+#[test]
+fn test_pass_filter_cutoff_gain() {
+    // Butterworth is -3 dB at the cutoff, Linkwitz-Riley -6 dB, at every slope
+
+    let mut failures = Vec::new();
+
+    for &sample_rate in &SAMPLE_RATES {
+        for alignment in [FilterAlignment::Butterworth, FilterAlignment::LinkwitzRiley] {
+            for slope in SLOPES {
+                let expected = cutoff_gain_db(alignment);
+
+                let mut hp = single_band_eq(EqBand::high_pass(1000.0, alignment, slope));
+                let mut lp = single_band_eq(EqBand::low_pass(1000.0, alignment, slope));
+                let hp_gain = measure_gain_db(&mut hp, 1000.0, sample_rate);
+                let lp_gain = measure_gain_db(&mut lp, 1000.0, sample_rate);
+
+                eprintln!(
+                    "{}Hz {:?} {} dB/oct: HP {:.2}dB, LP {:.2}dB (expected {:.2}dB)",
+                    sample_rate,
+                    alignment,
+                    slope.db_per_octave(),
+                    hp_gain,
+                    lp_gain,
+                    expected
+                );
+
+                for (kind, gain) in [("HP", hp_gain), ("LP", lp_gain)] {
+                    if (gain - expected).abs() > CUTOFF_TOLERANCE_DB {
+                        failures.push(format!(
+                            "{}Hz {:?} {} {} dB/oct: {:.2}dB",
+                            sample_rate,
+                            alignment,
+                            kind,
+                            slope.db_per_octave(),
+                            gain
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "Cutoff gain errors: {:?}", failures);
+}
+
+#[test]
+fn test_pass_filter_cutoff_frequency() {
+    // The -3 dB point of a Butterworth high-pass lands on the set frequency
+
+    for cutoff in [50.0, 200.0, 2000.0, 10000.0] {
+        let mut hp = single_band_eq(EqBand::high_pass(
+            cutoff,
+            FilterAlignment::Butterworth,
+            FilterSlope::Db24,
+        ));
+
+        let measured = find_crossing(&mut hp, cutoff / 2.0, cutoff * 2.0, -3.01, true, 48000);
+        let error_percent = (measured - cutoff).abs() / cutoff * 100.0;
+
+        eprintln!(
+            "HP {}Hz: -3dB at {:.1}Hz ({:.2}% error)",
+            cutoff, measured, error_percent
+        );
+
+        assert!(
+            error_percent < 2.0,
+            "HP {}Hz: -3dB point at {:.1}Hz",
+            cutoff,
+            measured
+        );
+    }
+}
+
+// ============================================================================
+// ROLL-OFF SLOPE
+// ============================================================================
+
+#[test]
+fn test_pass_filter_slope() {
+    // Well into the stopband the attenuation grows by the nominal slope per
+    // octave. The 48 dB/oct filters are measured over half an octave so the
+    // second point stays above the f32 noise floor.
+
+    for alignment in [FilterAlignment::Butterworth, FilterAlignment::LinkwitzRiley] {
+        for slope in SLOPES {
+            let (f1, f2) = match slope {
+                FilterSlope::Db48 => (2000.0, 2000.0 * std::f32::consts::SQRT_2),
+                _ => (4000.0, 8000.0),
+            };
+            let mut lp = single_band_eq(EqBand::low_pass(1000.0, alignment, slope));
+
+            let near = measure_gain_db(&mut lp, f1, 96000);
+            let far = measure_gain_db(&mut lp, f2, 96000);
+            let measured_slope = (near - far) / (f2 / f1).log2();
+            let expected = slope.db_per_octave() as f32;
+
+            eprintln!(
+                "LP {:?} {} dB/oct: {}Hz {:.1}dB, {:.0}Hz {:.1}dB, slope {:.1}dB/oct",
+                alignment,
+                slope.db_per_octave(),
+                f1,
+                near,
+                f2,
+                far,
+                measured_slope
+            );
+
+            assert!(
+                (measured_slope - expected).abs() < SLOPE_TOLERANCE_DB,
+                "{:?} {} dB/oct low-pass measured {:.1}dB/oct",
+                alignment,
+                slope.db_per_octave(),
+                measured_slope
+            );
+        }
+    }
+}
+
+#[test]
+fn test_high_pass_rumble_rejection() {
+    // A 48 dB/oct subsonic filter at 30 Hz removes 10 Hz rumble
+
+    let mut hp = single_band_eq(EqBand::high_pass(
+        30.0,
+        FilterAlignment::Butterworth,
+        FilterSlope::Db48,
+    ));
+
+    let rumble = measure_gain_db(&mut hp, 10.0, 44100);
+    eprintln!("30Hz 48dB/oct HP at 10Hz: {:.1}dB", rumble);
+
+    assert!(rumble < -60.0, "10Hz rumble only down {:.1}dB", rumble);
+}
+
+// ============================================================================
+// PASSBAND FLATNESS
+// ============================================================================
+
+#[test]
+fn test_pass_filter_passband_flatness() {
+    // Butterworth and Linkwitz-Riley have no passband ripple; two decades
+    // from the cutoff the response is flat
+
+    for alignment in [FilterAlignment::Butterworth, FilterAlignment::LinkwitzRiley] {
+        for slope in SLOPES {
+            let mut hp = single_band_eq(EqBand::high_pass(20.0, alignment, slope));
+            let mut lp = single_band_eq(EqBand::low_pass(20000.0, alignment, slope));
+
+            for freq in [200.0, 1000.0, 2000.0] {
+                let hp_gain = measure_gain_db(&mut hp, freq, 96000);
+                let lp_gain = measure_gain_db(&mut lp, freq, 96000);
+
+                assert!(
+                    hp_gain.abs() < PASSBAND_TOLERANCE_DB,
+                    "{:?} {} dB/oct HP at {}Hz: {:.3}dB",
+                    alignment,
+                    slope.db_per_octave(),
+                    freq,
+                    hp_gain
+                );
+                assert!(
+                    lp_gain.abs() < PASSBAND_TOLERANCE_DB,
+                    "{:?} {} dB/oct LP at {}Hz: {:.3}dB",
+                    alignment,
+                    slope.db_per_octave(),
+                    freq,
+                    lp_gain
+                );
+            }
+        }
+    }
+}
+
+// ============================================================================
+// LINKWITZ-RILEY CROSSOVER SUMMATION
+// ============================================================================
+
+#[test]
+fn test_linkwitz_riley_crossover_sums_flat() {
+    // LR 24 and 48 dB/oct low-pass and high-pass at the same frequency
+    // are in phase and sum to 0 dB everywhere
+
+    for slope in [FilterSlope::Db24, FilterSlope::Db48] {
+        let mut low = single_band_eq(EqBand::low_pass(
+            1000.0,
+            FilterAlignment::LinkwitzRiley,
+            slope,
+        ));
+        let mut high = single_band_eq(EqBand::high_pass(
+            1000.0,
+            FilterAlignment::LinkwitzRiley,
+            slope,
+        ));
+
+        eprintln!("LR {} dB/oct crossover sum at 1kHz:", slope.db_per_octave());
+
+        for freq in [200.0, 500.0, 800.0, 1000.0, 1250.0, 2000.0, 5000.0] {
+            let sum = measure_sum_gain_db(&mut low, &mut high, freq, 48000);
+            eprintln!("  {}Hz: {:.3}dB", freq, sum);
+
+            assert!(
+                sum.abs() < PASSBAND_TOLERANCE_DB,
+                "LR {} dB/oct sum at {}Hz: {:.3}dB",
+                slope.db_per_octave(),
+                freq,
+                sum
+            );
+        }
+    }
+}
+
+#[test]
+fn test_butterworth_crossover_bumps_at_cutoff() {
+    // For contrast: Butterworth 24 dB/oct halves sum to +3 dB at the
+    // crossover, which is why crossovers use Linkwitz-Riley
+
+    let mut low = single_band_eq(EqBand::low_pass(
+        1000.0,
+        FilterAlignment::Butterworth,
+        FilterSlope::Db24,
+    ));
+    let mut high = single_band_eq(EqBand::high_pass(
+        1000.0,
+        FilterAlignment::Butterworth,
+        FilterSlope::Db24,
+    ));
+
+    let sum = measure_sum_gain_db(&mut low, &mut high, 1000.0, 48000);
+    eprintln!("Butterworth 24 dB/oct sum at 1kHz: {:.2}dB", sum);
+
+    assert!((sum - 3.01).abs() < CUTOFF_TOLERANCE_DB);
+}
+
+// ============================================================================
+// NOTCH AND BAND-PASS BANDWIDTH
+// ============================================================================
+
+#[test]
+fn test_notch_depth_and_bandwidth() {
+    // A notch removes its center frequency; the -3 dB bandwidth is f0 / Q
+
+    for (center, q) in [(60.0, 4.0), (1000.0, 2.0), (5000.0, 8.0)] {
+        let mut eq = single_band_eq(EqBand::notch(center, q));
+
+        let depth = measure_gain_db(&mut eq, center, 48000);
+        let f1 = find_crossing(&mut eq, center / 4.0, center, -3.01, false, 48000);
+        let f2 = find_crossing(&mut eq, center, center * 4.0, -3.01, true, 48000);
+        let measured_q = center / (f2 - f1);
+
+        eprintln!(
+            "Notch {}Hz Q={}: depth {:.1}dB, -3dB at {:.1}/{:.1}Hz, Q={:.2}",
+            center, q, depth, f1, f2, measured_q
+        );
+
+        assert!(depth < -30.0, "Notch {}Hz only {:.1}dB deep", center, depth);
+        assert!(
+            (measured_q - q).abs() / q < 0.1,
+            "Notch {}Hz: Q {:.2}, expected {}",
+            center,
+            measured_q,
+            q
+        );
+    }
+}
+
+#[test]
+fn test_band_pass_peak_and_bandwidth() {
+    // A band-pass has 0 dB at its center and a -3 dB bandwidth of f0 / Q
+
+    for (center, q) in [(250.0, 1.414), (1000.0, 4.318), (4000.0, 2.0)] {
+        let mut eq = single_band_eq(EqBand::band_pass(center, q));
+
+        let peak = measure_gain_db(&mut eq, center, 48000);
+        let f1 = find_crossing(&mut eq, center / 4.0, center, -3.01, true, 48000);
+        let f2 = find_crossing(&mut eq, center, center * 4.0, -3.01, false, 48000);
+        let measured_q = center / (f2 - f1);
+
+        eprintln!(
+            "Band-pass {}Hz Q={}: peak {:.2}dB, Q={:.2}",
+            center, q, peak, measured_q
+        );
+
+        assert!(
+            peak.abs() < CUTOFF_TOLERANCE_DB,
+            "Band-pass {}Hz peak {:.2}dB",
+            center,
+            peak
+        );
+        assert!(
+            (measured_q - q).abs() / q < 0.1,
+            "Band-pass {}Hz: Q {:.2}, expected {}",
+            center,
+            measured_q,
+            q
+        );
+    }
+}
+
+// ============================================================================
+// ALL-PASS MAGNITUDE AND PHASE
+// ============================================================================
+
+#[test]
+fn test_all_pass_is_flat() {
+    // An all-pass changes phase only
+
+    let mut eq = single_band_eq(EqBand::all_pass(1000.0, 0.707));
+
+    for freq in [50.0, 200.0, 1000.0, 5000.0, 15000.0] {
+        let gain = measure_gain_db(&mut eq, freq, 48000);
+        assert!(
+            gain.abs() < PASSBAND_TOLERANCE_DB,
+            "All-pass at {}Hz: {:.3}dB",
+            freq,
+            gain
+        );
+    }
+}
+
+#[test]
+fn test_all_pass_phase() {
+    // Second-order all-pass: in phase far below f0, -180 degrees at f0,
+    // back in phase (-360 degrees) far above
+
+    let mut eq = single_band_eq(EqBand::all_pass(1000.0, 0.707));
+
+    let below = measure_correlation(&mut eq, 20.0, 48000);
+    let center = measure_correlation(&mut eq, 1000.0, 48000);
+    let above = measure_correlation(&mut eq, 20000.0, 48000);
+
+    eprintln!(
+        "All-pass correlation: 20Hz {:.3}, 1kHz {:.3}, 20kHz {:.3}",
+        below, center, above
+    );
+
+    assert!(below > 0.9, "All-pass not in phase at 20Hz: {:.3}", below);
+    assert!(center < -0.99, "All-pass not inverted at f0: {:.3}", center);
+    assert!(
+        above > 0.5,
+        "All-pass not back in phase at 20kHz: {:.3}",
+        above
+    );
+}
+
+// ============================================================================
+// TILT SHELF RESPONSE
+// ============================================================================
+
+#[test]
+fn test_tilt_shelf_response_curve() {
+    // Tilt pivots around its frequency: 0 dB there, -gain/2 at the bottom,
+    // +gain/2 at the top
+
+    for gain in [6.0, -6.0, 12.0] {
+        let mut eq = single_band_eq(EqBand::tilt_shelf(1000.0, gain));
+
+        let test_points = [
+            (20.0, -gain / 2.0, "Far below pivot"),
+            (1000.0, 0.0, "At pivot"),
+            (20000.0, gain / 2.0, "Far above pivot"),
+        ];
+
+        eprintln!("TILT {:+}dB at 1kHz:", gain);
+
+        for (freq, expected, desc) in test_points {
+            let measured = measure_gain_db(&mut eq, freq, 48000);
+            eprintln!(
+                "  {}Hz ({}): Expected {:.1}dB, Measured {:.2}dB",
+                freq, desc, expected, measured
+            );
+
+            assert!(
+                (measured - expected).abs() < CUTOFF_TOLERANCE_DB,
+                "Tilt {:+}dB at {}Hz: expected {:.1}dB, got {:.2}dB",
+                gain,
+                freq,
+                expected,
+                measured
+            );
+        }
+    }
+}
+
+// ============================================================================
+// STABILITY NEAR NYQUIST
+// ============================================================================
+
+#[test]
+fn test_new_filter_types_stable_near_nyquist() {
+    // Every filter type stays stable with its frequency at or past Nyquist
+
+    for &sample_rate in &SAMPLE_RATES {
+        let nyquist = sample_rate as f32 / 2.0;
+
+        for ratio in [0.9, 0.99, 1.2] {
+            let freq = nyquist * ratio;
+            let bands = [
+                EqBand::high_pass(freq, FilterAlignment::Butterworth, FilterSlope::Db48),
+                EqBand::low_pass(freq, FilterAlignment::LinkwitzRiley, FilterSlope::Db48),
+                EqBand::notch(freq, 10.0),
+                EqBand::band_pass(freq, 10.0),
+                EqBand::all_pass(freq, 10.0),
+                EqBand::tilt_shelf(freq, 24.0),
+            ];
+
+            for band in bands {
+                let mut eq = single_band_eq(band);
+                let mut buffer = generate_sine(1000.0, sample_rate, 0.1, 0.5);
+                eq.process(&mut buffer, sample_rate);
+
+                assert!(
+                    is_stable(&buffer),
+                    "{:?} unstable at {:.0}Hz ({}Hz sample rate)",
+                    band.filter_type(),
+                    freq,
+                    sample_rate
+                );
+            }
+        }
+    }
+}
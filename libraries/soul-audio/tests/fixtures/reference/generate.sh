@@ -0,0 +1,137 @@
+#!/bin/bash
+# Encode the reference WavPack and Monkey's Audio fixtures
+#
+# Writes deterministic PCM sources, encodes them with the reference `wavpack`
+# and `mac` tools, decodes every file again with the reference decoders
+# (`wvunpack`, `mac -d`) and records the checksum of that PCM in
+# checksums.txt. reference_decoder_test.rs decodes the fixtures with our
+# decoders and compares against those checksums.
+#
+# Requires python3, wavpack/wvunpack (5.x) and mac (Monkey's Audio SDK).
+# Keep the files small: they are committed.
+
+set -euo pipefail
+
+cd "$(dirname "$0")"
+
+for tool in python3 wavpack wvunpack mac; do
+    if ! command -v "$tool" &> /dev/null; then
+        echo "ERROR: $tool is not installed or not in PATH"
+        exit 1
+    fi
+done
+
+work=$(mktemp -d)
+trap 'rm -rf "$work"' EXIT
+
+# Deterministic sources: a stereo 16-bit chirp with a little noise, and a
+# 24-bit 5.1 file with a different tone per channel
+python3 - "$work" <<'EOF'
+import math, struct, sys
+
+def write_wav(path, rate, channels, bits, frames):
+    data = bytearray()
+    for frame in frames:
+        for sample in frame:
+            data += sample.to_bytes(bits // 8, "little", signed=True)
+    fmt = struct.pack("<HHIIHH", 1, channels, rate, rate * channels * bits // 8,
+                      channels * bits // 8, bits)
+    if channels > 2:
+        # WAVE_FORMAT_EXTENSIBLE with the 5.1 channel mask
+        fmt = struct.pack("<HHIIHHHHI", 0xFFFE, channels, rate,
+                          rate * channels * bits // 8, channels * bits // 8, bits,
+                          22, bits, 0x3F)
+        fmt += bytes.fromhex("0100000000001000800000aa00389b71")
+    with open(path, "wb") as f:
+        f.write(b"RIFF" + struct.pack("<I", 4 + 8 + len(fmt) + 8 + len(data)) + b"WAVE")
+        f.write(b"fmt " + struct.pack("<I", len(fmt)) + fmt)
+        f.write(b"data" + struct.pack("<I", len(data)) + data)
+
+seed = 12345
+def noise():
+    global seed
+    seed = (seed * 1103515245 + 12345) % 2**31
+    return seed % 401 - 200
+
+stereo = []
+for n in range(22050):
+    t = n / 44100
+    s = int(12000 * math.sin(2 * math.pi * (100 * t + 4000 * t * t)))
+    stereo.append((s + noise(), -s // 2 + noise()))
+write_wav(sys.argv[1] + "/stereo16.wav", 44100, 2, 16, stereo)
+
+mono = [(l,) for l, _ in stereo[:11025]]
+write_wav(sys.argv[1] + "/mono16.wav", 44100, 1, 16, mono)
+
+surround = []
+for n in range(12000):
+    surround.append(tuple(
+        int(2**22 * math.sin(2 * math.pi * (200 + 150 * c) * n / 48000)) + noise()
+        for c in range(6)))
+write_wav(sys.argv[1] + "/surround24.wav", 48000, 6, 24, surround)
+EOF
+
+# name, source, encoder command
+fixtures=(
+    "lossless.wv stereo16 wavpack -q -y"
+    "high.wv stereo16 wavpack -q -y -hh -x3"
+    "hybrid.wv stereo16 wavpack -q -y -b3"
+    "surround.wv surround24 wavpack -q -y"
+    "mono.wv mono16 wavpack -q -y"
+    "c1000.ape stereo16 mac -c1000"
+    "c2000.ape stereo16 mac -c2000"
+    "c3000.ape stereo16 mac -c3000"
+    "c4000.ape stereo16 mac -c4000"
+    "c5000.ape stereo16 mac -c5000"
+    "mono.ape mono16 mac -c2000"
+)
+
+: > checksums.txt
+for fixture in "${fixtures[@]}"; do
+    read -r name source tool args <<< "$fixture"
+    rm -f "$name"
+    if [ "$tool" = "mac" ]; then
+        mac "$work/$source.wav" "$name" $args
+        mac "$name" "$work/$name.wav" -d
+    else
+        $tool $args "$work/$source.wav" -o "$name"
+        wvunpack -q -y "$name" -o "$work/$name.wav"
+    fi
+
+    # Checksum the PCM the reference decoder produced; lossless files must
+    # also decode to their source
+    python3 - "$name" "$work/$name.wav" "$work/$source.wav" >> checksums.txt <<'EOF'
+import struct, sys
+
+def read_wav(path):
+    data = open(path, "rb").read()
+    pos, fmt, pcm = 12, None, None
+    while pos + 8 <= len(data):
+        tag, size = data[pos:pos + 4], struct.unpack("<I", data[pos + 4:pos + 8])[0]
+        body = data[pos + 8:pos + 8 + size]
+        if tag == b"fmt ":
+            fmt = struct.unpack("<HHIIHH", body[:16])
+        elif tag == b"data":
+            pcm = body
+        pos += 8 + size + (size & 1)
+    channels, bits = fmt[1], fmt[5]
+    width = bits // 8
+    samples = [int.from_bytes(pcm[i:i + width], "little", signed=True)
+               for i in range(0, len(pcm) - width + 1, width)]
+    return channels, bits, samples
+
+name, decoded, source = sys.argv[1:]
+channels, bits, samples = read_wav(decoded)
+if not name.startswith("hybrid") and samples != read_wav(source)[2]:
+    sys.exit(f"{name}: lossless decode differs from the source")
+
+# FNV-1a over the samples as little-endian i32
+checksum = 0xcbf29ce484222325
+for sample in samples:
+    for byte in (sample & 0xFFFFFFFF).to_bytes(4, "little"):
+        checksum = ((checksum ^ byte) * 0x100000001b3) & 0xFFFFFFFFFFFFFFFF
+print(f"{name} {channels} {bits} {len(samples) // channels} {checksum:016x}")
+EOF
+done
+
+echo "Wrote $(wc -l < checksums.txt) fixtures"
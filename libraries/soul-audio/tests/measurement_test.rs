@@ -0,0 +1,163 @@
+//! Offline measurement workflow tests
+//!
+//! Plays the sweep "through a room" by convolving it with a known response,
+//! goes through WAV files the way an external recorder would, and checks the
+//! measured IR and correction filter load and run in the convolution engine.
+
+use soul_audio::effects::{AudioEffect, ConvolutionEngine, ImpulseResponse};
+use soul_audio::measurement::{
+    correction_filter, CorrectionSettings, LogSweep, SweepSettings, TargetCurve,
+};
+
+const SAMPLE_RATE: u32 = 48000;
+
+/// A simple room: direct sound, a couple of reflections and a decaying tail
+fn room_response() -> Vec<f32> {
+    let mut room = vec![0.0f32; 2400];
+    room[0] = 1.0;
+    room[130] = 0.5;
+    room[470] = -0.3;
+    for (i, tap) in room.iter_mut().enumerate().skip(600) {
+        *tap += 0.05 * (-(i as f32) / 400.0).exp() * if i % 7 == 0 { 1.0 } else { -0.4 };
+    }
+    room
+}
+
+/// Convolve a mono signal with an FIR
+fn convolve(signal: &[f32], fir: &[f32]) -> Vec<f32> {
+    let mut out = vec![0.0f32; signal.len() + fir.len() - 1];
+    for (i, &s) in signal.iter().enumerate() {
+        if s == 0.0 {
+            continue;
+        }
+        for (j, &h) in fir.iter().enumerate() {
+            out[i + j] += s * h;
+        }
+    }
+    out
+}
+
+/// Record a mono signal as a 24-bit WAV, with latency and a little noise
+fn write_recording(path: &std::path::Path, signal: &[f32], latency: usize) {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: SAMPLE_RATE,
+        bits_per_sample: 24,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(path, spec).unwrap();
+    let mut noise_state = 12345u32;
+    for i in 0..latency + signal.len() {
+        let s = if i >= latency {
+            signal[i - latency]
+        } else {
+            0.0
+        };
+        // -80 dBFS noise floor
+        noise_state = noise_state.wrapping_mul(1664525).wrapping_add(1013904223);
+        let noise = (noise_state as f32 / u32::MAX as f32 - 0.5) * 2e-4;
+        writer
+            .write_sample(((s * 0.5 + noise) * 8_388_607.0) as i32)
+            .unwrap();
+    }
+    writer.finalize().unwrap();
+}
+
+#[test]
+fn test_measure_room_from_wav_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let sweep = LogSweep::new(SweepSettings {
+        duration_secs: 3.0,
+        tail_secs: 0.5,
+        ..Default::default()
+    })
+    .unwrap();
+
+    // The sweep file plays back as written
+    let sweep_path = dir.path().join("sweep.wav");
+    sweep.write_wav(&sweep_path, 2).unwrap();
+    let played: Vec<f32> = hound::WavReader::open(&sweep_path)
+        .unwrap()
+        .into_samples::<f32>()
+        .step_by(2)
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(played, sweep.playback_signal());
+
+    let room = room_response();
+    let recording_path = dir.path().join("recording.wav");
+    write_recording(&recording_path, &convolve(&played, &room), 3000);
+
+    let ir = sweep.deconvolve_wav(&recording_path, 4800).unwrap();
+    let ir_path = dir.path().join("room.wav");
+    ir.write_wav(&ir_path).unwrap();
+
+    // The measured IR is the room, starting 2 ms in. Band-limiting to the
+    // sweep range (20 Hz - 20 kHz) takes some height off each tap, the same
+    // for all of them
+    let loaded = ImpulseResponse::from_wav(&ir_path).unwrap();
+    assert_eq!(loaded, ir);
+    let offset = 96;
+    let direct = loaded.samples[offset];
+    assert!(
+        (0.35..0.5).contains(&direct),
+        "direct sound {:.3} should be at the recording gain (0.5)",
+        direct
+    );
+    for (i, expected) in [(130, 0.5f32), (470, -0.3)] {
+        let measured = loaded.samples[offset + i] / direct;
+        assert!(
+            (measured - expected).abs() < 0.05,
+            "reflection at {}: measured {:.3}, expected {:.3}",
+            i,
+            measured,
+            expected
+        );
+    }
+
+    // The measured IR runs in the convolution engine
+    let mut engine = ConvolutionEngine::new();
+    engine.load_from_wav(&ir_path).unwrap();
+    let mut buffer = vec![0.0f32; 8192 * 2];
+    buffer[0] = 1.0;
+    buffer[1] = 1.0;
+    engine.process(&mut buffer, SAMPLE_RATE);
+    assert!(buffer.iter().all(|s| s.is_finite()));
+    assert!(buffer.iter().any(|s| s.abs() > 0.1));
+}
+
+#[test]
+fn test_correction_filter_loads_into_engine() {
+    let dir = tempfile::tempdir().unwrap();
+    let room = room_response();
+    let ir = ImpulseResponse::new(room, SAMPLE_RATE, 1).unwrap();
+
+    let correction = correction_filter(
+        &ir,
+        &TargetCurve::tilt(-0.5),
+        &CorrectionSettings {
+            taps: 8192,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(correction.frames(), 8192);
+
+    let path = dir.path().join("correction.wav");
+    correction.write_wav(&path).unwrap();
+
+    let mut engine = ConvolutionEngine::new();
+    engine.load_from_wav(&path).unwrap();
+    assert_eq!(engine.ir_length(), 8192);
+
+    // Normalized: a full-scale sine never comes out louder
+    let mut buffer: Vec<f32> = (0..48000)
+        .flat_map(|i| {
+            let s = (2.0 * std::f32::consts::PI * 997.0 * i as f32 / SAMPLE_RATE as f32).sin();
+            [s, s]
+        })
+        .collect();
+    engine.process(&mut buffer, SAMPLE_RATE);
+    let peak = buffer.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+    assert!(peak <= 1.05, "corrected peak {}", peak);
+}
@@ -0,0 +1,106 @@
+//! WavPack and Monkey's Audio decoding of reference-encoded files
+//!
+//! The unit tests of the format decoders use files from our own writers in
+//! `test_utils`, which only cover the modes those writers produce. The
+//! fixtures here come from the reference `wavpack` and `mac` tools (hybrid
+//! lossy WavPack, extra modes, 5.1, and every APE compression level of
+//! 3.99+) and are checked against the PCM the reference decoders produce.
+//!
+//! Regenerate them with `tests/fixtures/reference/generate.sh`, which also
+//! writes `checksums.txt`.
+
+use soul_audio::formats::registry;
+use std::path::{Path, PathBuf};
+
+fn fixture_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/reference")
+}
+
+/// One line of `checksums.txt`
+struct Expected {
+    file: String,
+    channels: u16,
+    bits: u32,
+    frames: usize,
+    checksum: u64,
+}
+
+fn expected() -> Vec<Expected> {
+    let path = fixture_dir().join("checksums.txt");
+    let text = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("{}: {e} (run generate.sh)", path.display()));
+
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            assert_eq!(fields.len(), 5, "malformed line: {line}");
+            Expected {
+                file: fields[0].to_string(),
+                channels: fields[1].parse().unwrap(),
+                bits: fields[2].parse().unwrap(),
+                frames: fields[3].parse().unwrap(),
+                checksum: u64::from_str_radix(fields[4], 16).unwrap(),
+            }
+        })
+        .collect()
+}
+
+/// FNV-1a over the samples as little-endian `i32`, as in generate.sh
+fn checksum(samples: &[i32]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in samples.iter().flat_map(|s| s.to_le_bytes()) {
+        hash = (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+fn has_extension(file: &str, extension: &str) -> bool {
+    Path::new(file)
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case(extension))
+}
+
+#[test]
+#[ignore = "needs the reference-encoded fixtures from tests/fixtures/reference/generate.sh"]
+fn reference_files_decode_to_the_reference_pcm() {
+    let expected = expected();
+    assert!(
+        expected.iter().any(|e| has_extension(&e.file, "wv"))
+            && expected.iter().any(|e| has_extension(&e.file, "ape")),
+        "checksums.txt should cover both formats"
+    );
+
+    for e in &expected {
+        let path = fixture_dir().join(&e.file);
+        let mut decoder = registry().decoder_for(&path).unwrap();
+        let buffer = decoder
+            .decode(&path)
+            .unwrap_or_else(|err| panic!("{}: {err}", e.file));
+
+        assert_eq!(buffer.format.channels, e.channels, "{}: channels", e.file);
+        assert_eq!(
+            buffer.samples.len(),
+            e.frames * e.channels as usize,
+            "{}: length",
+            e.file
+        );
+
+        // f32 holds 24-bit samples exactly, so scaling back is lossless
+        let scale = (1u32 << (e.bits - 1)) as f32;
+        let samples: Vec<i32> = buffer
+            .samples
+            .iter()
+            .map(|&s| (s * scale).round() as i32)
+            .collect();
+        assert_eq!(checksum(&samples), e.checksum, "{}: samples", e.file);
+    }
+}
+
+#[test]
+fn checksum_matches_the_generator() {
+    // FNV-1a 64 of no input is the offset basis; one zero sample is four
+    // zero bytes
+    assert_eq!(checksum(&[]), 0xcbf2_9ce4_8422_2325);
+    assert_eq!(checksum(&[0]), 0x4d25_767f_9dce_13f5);
+}
@@ -42,6 +42,9 @@
 //! - **FLAC**: Sample-accurate seeking via seek tables
 //! - **WAV**: Sample-accurate seeking (direct byte offset)
 //!
+//! `SymphoniaDecoder` seeks sample-accurately in all of them: it lands on the
+//! preceding sync point (with codec pre-roll) and discards up to the target.
+//!
 //! ## References
 //!
 //! - ISO/IEC 11172-3 (MPEG-1 Audio Layer III frame structure)
@@ -368,6 +371,42 @@ fn test_seek_to_end() {
     assert!(chunk.is_ok(), "Decode after seeking to end should not error");
 }
 
+/// Test that seeks land on the exact requested sample
+///
+/// The audio decoded after a seek must match a full decode from the target
+/// sample on, and the reported position must be the target itself.
+#[test]
+fn test_seek_lands_on_exact_sample() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("seek_exact.wav");
+
+    let sample_rate = 44100u32;
+    create_chirp_wav(&path, sample_rate, 3.0, 200.0, 2000.0);
+    let full = SymphoniaDecoder::new().decode(&path).unwrap().samples;
+
+    let mut decoder = SymphoniaDecoder::new();
+    decoder.open(&path).expect("Failed to open file");
+
+    for frame in [1usize, 4410, 12345, 77777, 131071, 7] {
+        let target = sample_index_to_time(frame, sample_rate);
+        let actual = decoder.seek(target).expect("Seek failed");
+        assert_eq!(
+            (actual.as_secs_f64() * sample_rate as f64).round() as usize,
+            frame,
+            "Seek to sample {} landed at {:?}",
+            frame,
+            actual
+        );
+        assert_eq!(decoder.position(), actual);
+
+        let chunk = decoder
+            .decode_chunk(1024)
+            .expect("Decode failed")
+            .expect("Should have data");
+        assert_eq!(chunk.samples, full[frame * 2..(frame + 1024) * 2]);
+    }
+}
+
 /// Test sample-accurate seeking in WAV files
 ///
 /// WAV files support sample-accurate seeking because sample positions
@@ -58,8 +58,12 @@ pub trait AudioDecoder: Send {
 
     /// Seek to a position in the currently open file
     ///
-    /// Returns the actual position after seeking (may differ from requested due to
-    /// frame boundaries in compressed formats like MP3/AAC).
+    /// Seeking should be sample-accurate: compressed formats (MP3/AAC/Vorbis)
+    /// decode from the preceding sync point, with enough pre-roll for the
+    /// codec to warm up, and discard everything before the target.
+    ///
+    /// Returns the actual position after seeking (the requested position
+    /// rounded to a sample, or clamped to the track length).
     ///
     /// # Arguments
     /// * `position` - Target position from start of track
@@ -195,6 +195,8 @@ mod tests {
             musicbrainz_recording_id: None,
             composer: None,
             album_art: None,
+            bpm: None,
+            musical_key: None,
         };
 
         let source = Path::new("/path/to/song.mp3");
@@ -221,6 +223,8 @@ mod tests {
             musicbrainz_recording_id: None,
             composer: None,
             album_art: None,
+            bpm: None,
+            musical_key: None,
         };
 
         let source = Path::new("/path/to/song.mp3");
@@ -247,6 +251,8 @@ mod tests {
             musicbrainz_recording_id: None,
             composer: None,
             album_art: None,
+            bpm: None,
+            musical_key: None,
         };
 
         let source = Path::new("/path/to/original_song.mp3");
@@ -283,6 +289,8 @@ mod tests {
             musicbrainz_recording_id: None,
             composer: None,
             album_art: None,
+            bpm: None,
+            musical_key: None,
         };
 
         let dest_path = copy_to_library(&source_file, &library_dir, &metadata).unwrap();
@@ -0,0 +1,579 @@
+//! CUE sheet parsing for single-file album images
+//!
+//! Lossless rips are often stored as one FLAC/WAV/APE file per disc plus a
+//! `.cue` sheet describing where each track starts. This module parses the
+//! sheet and turns it into virtual tracks: a file plus a start/end offset,
+//! with per-track TITLE/PERFORMER.
+//!
+//! # Timing
+//!
+//! CUE timestamps are `MM:SS:FF` where `FF` is a CD frame (1/75 second).
+//! Offsets are kept in frames so they stay exact; 1/75 s is a whole number of
+//! samples at 44.1/48/88.2/96/176.4/192 kHz, which makes track boundaries
+//! sample-accurate.
+//!
+//! # Pregaps
+//!
+//! `INDEX 00` marks the start of a track's pregap and `INDEX 01` the start of
+//! the track itself. Like most players, the pregap is played as the tail of
+//! the previous track: each virtual track starts at its `INDEX 01` and ends at
+//! the next track's `INDEX 01`, so consecutive tracks are gapless and no
+//! audio is skipped. The pregap length is still recorded per track.
+
+use crate::metadata::ExtractedMetadata;
+use crate::{ImportError, Result};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// CD frames per second
+pub const FRAMES_PER_SECOND: u64 = 75;
+
+/// Position in a CUE sheet (`MM:SS:FF`), stored as CD frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct CueTime(u64);
+
+impl CueTime {
+    /// Create from a frame count (1/75 second units)
+    pub fn from_frames(frames: u64) -> Self {
+        Self(frames)
+    }
+
+    /// Parse an `MM:SS:FF` timestamp
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut parts = s.trim().split(':');
+        let minutes: u64 = parts.next()?.parse().ok()?;
+        let seconds: u64 = parts.next()?.parse().ok()?;
+        let frames: u64 = parts.next()?.parse().ok()?;
+        if parts.next().is_some() || seconds >= 60 || frames >= FRAMES_PER_SECOND {
+            return None;
+        }
+        Some(Self((minutes * 60 + seconds) * FRAMES_PER_SECOND + frames))
+    }
+
+    /// Total CD frames
+    pub fn frames(self) -> u64 {
+        self.0
+    }
+
+    /// Convert to a duration (exact to the nanosecond)
+    pub fn to_duration(self) -> Duration {
+        Duration::from_nanos(self.0 * 1_000_000_000 / FRAMES_PER_SECOND)
+    }
+
+    /// Convert to a sample offset at the given sample rate
+    ///
+    /// Exact for any rate divisible by 75, rounded otherwise.
+    pub fn to_samples(self, sample_rate: u32) -> u64 {
+        (self.0 * sample_rate as u64 + FRAMES_PER_SECOND / 2) / FRAMES_PER_SECOND
+    }
+}
+
+impl fmt::Display for CueTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let frames = self.0 % FRAMES_PER_SECOND;
+        let seconds = (self.0 / FRAMES_PER_SECOND) % 60;
+        let minutes = self.0 / FRAMES_PER_SECOND / 60;
+        write!(f, "{:02}:{:02}:{:02}", minutes, seconds, frames)
+    }
+}
+
+/// A parsed CUE sheet
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CueSheet {
+    /// Album title (top-level TITLE)
+    pub title: Option<String>,
+    /// Album performer (top-level PERFORMER)
+    pub performer: Option<String>,
+    /// Genre (`REM GENRE`)
+    pub genre: Option<String>,
+    /// Release year (`REM DATE`)
+    pub year: Option<i32>,
+    /// Disc number (`REM DISCNUMBER`)
+    pub disc_number: Option<u32>,
+    /// Audio files referenced by the sheet, in order
+    pub files: Vec<CueFile>,
+}
+
+/// A FILE entry and the tracks it contains
+#[derive(Debug, Clone, PartialEq)]
+pub struct CueFile {
+    /// File name as written in the sheet (usually relative to the sheet)
+    pub path: String,
+    /// File type (WAVE, MP3, AIFF, ...); informational only
+    pub file_type: String,
+    /// Tracks stored in this file
+    pub tracks: Vec<CueTrack>,
+}
+
+/// A TRACK entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct CueTrack {
+    /// Track number
+    pub number: u32,
+    /// Track title
+    pub title: Option<String>,
+    /// Track performer (falls back to the album performer)
+    pub performer: Option<String>,
+    /// ISRC code
+    pub isrc: Option<String>,
+    /// Start of the pregap (`INDEX 00`), if present
+    pub pregap_start: Option<CueTime>,
+    /// Start of the track (`INDEX 01`)
+    pub start: CueTime,
+}
+
+impl CueTrack {
+    /// Length of the pregap preceding this track
+    pub fn pregap(&self) -> CueTime {
+        self.pregap_start
+            .map(|p| CueTime::from_frames(self.start.frames().saturating_sub(p.frames())))
+            .unwrap_or_default()
+    }
+}
+
+/// A track backed by a sub-range of an audio file
+#[derive(Debug, Clone, PartialEq)]
+pub struct VirtualTrack {
+    /// Resolved path of the audio file
+    pub file: PathBuf,
+    /// Track number from the sheet
+    pub number: u32,
+    /// Track title
+    pub title: Option<String>,
+    /// Track performer (album performer if the track has none)
+    pub performer: Option<String>,
+    /// Start offset in the file (`INDEX 01`)
+    pub start: CueTime,
+    /// End offset in the file (next track's `INDEX 01`), None = end of file
+    pub end: Option<CueTime>,
+    /// Length of the pregap before `start`
+    pub pregap: CueTime,
+}
+
+impl VirtualTrack {
+    /// Track length, given the total length of the file for the last track
+    pub fn duration(&self, file_duration: Option<Duration>) -> Option<Duration> {
+        let end = match self.end {
+            Some(end) => end.to_duration(),
+            None => file_duration?,
+        };
+        Some(end.saturating_sub(self.start.to_duration()))
+    }
+
+    /// Build track metadata from the audio file's metadata and the sheet
+    ///
+    /// Sheet values win over embedded tags since an album image's tags
+    /// describe the whole disc rather than this track.
+    pub fn apply_to_metadata(
+        &self,
+        sheet: &CueSheet,
+        file_metadata: &ExtractedMetadata,
+    ) -> ExtractedMetadata {
+        let file_duration = file_metadata.duration_seconds.map(Duration::from_secs_f64);
+
+        let mut metadata = file_metadata.clone();
+        metadata.title = self.title.clone();
+        metadata.artist = self
+            .performer
+            .clone()
+            .or_else(|| file_metadata.artist.clone());
+        metadata.album = sheet.title.clone().or_else(|| file_metadata.album.clone());
+        metadata.album_artist = sheet
+            .performer
+            .clone()
+            .or_else(|| file_metadata.album_artist.clone());
+        metadata.track_number = Some(self.number);
+        metadata.disc_number = sheet.disc_number.or(file_metadata.disc_number);
+        metadata.year = sheet.year.or(file_metadata.year);
+        if let Some(genre) = &sheet.genre {
+            metadata.genres = vec![genre.clone()];
+        }
+        metadata.duration_seconds = self.duration(file_duration).map(|d| d.as_secs_f64());
+        metadata.musicbrainz_recording_id = None;
+        metadata
+    }
+}
+
+impl CueSheet {
+    /// Read and parse a CUE sheet from disk
+    ///
+    /// Accepts UTF-8 (with or without BOM); other encodings are decoded lossily.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let bytes = std::fs::read(path)?;
+        let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(&bytes);
+        Self::parse(&String::from_utf8_lossy(bytes))
+    }
+
+    /// Parse CUE sheet text
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut sheet = CueSheet::default();
+
+        for (line_no, raw_line) in text.lines().enumerate() {
+            let line = raw_line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (command, rest) = split_command(line);
+            let error = |msg: &str| {
+                ImportError::Metadata(format!("CUE line {}: {} ({})", line_no + 1, msg, line))
+            };
+
+            match command.to_ascii_uppercase().as_str() {
+                "FILE" => {
+                    let (path, file_type) = parse_quoted(rest);
+                    if path.is_empty() {
+                        return Err(error("FILE without a file name"));
+                    }
+                    sheet.files.push(CueFile {
+                        path,
+                        file_type: file_type.trim().to_string(),
+                        tracks: Vec::new(),
+                    });
+                }
+                "TRACK" => {
+                    let file = sheet
+                        .files
+                        .last_mut()
+                        .ok_or_else(|| error("TRACK before FILE"))?;
+                    let number = rest
+                        .split_whitespace()
+                        .next()
+                        .and_then(|n| n.parse().ok())
+                        .ok_or_else(|| error("invalid track number"))?;
+                    file.tracks.push(CueTrack {
+                        number,
+                        title: None,
+                        performer: None,
+                        isrc: None,
+                        pregap_start: None,
+                        start: CueTime::default(),
+                    });
+                }
+                "INDEX" => {
+                    let track = sheet
+                        .files
+                        .last_mut()
+                        .and_then(|f| f.tracks.last_mut())
+                        .ok_or_else(|| error("INDEX outside of TRACK"))?;
+                    let mut parts = rest.split_whitespace();
+                    let index: u32 = parts
+                        .next()
+                        .and_then(|n| n.parse().ok())
+                        .ok_or_else(|| error("invalid index number"))?;
+                    let time = parts
+                        .next()
+                        .and_then(CueTime::parse)
+                        .ok_or_else(|| error("invalid index time"))?;
+                    match index {
+                        0 => track.pregap_start = Some(time),
+                        1 => track.start = time,
+                        // INDEX 02+ are sub-indexes within a track
+                        _ => {}
+                    }
+                }
+                "TITLE" => {
+                    let (title, _) = parse_quoted(rest);
+                    match current_track(&mut sheet) {
+                        Some(track) => track.title = Some(title),
+                        None => sheet.title = Some(title),
+                    }
+                }
+                "PERFORMER" => {
+                    let (performer, _) = parse_quoted(rest);
+                    match current_track(&mut sheet) {
+                        Some(track) => track.performer = Some(performer),
+                        None => sheet.performer = Some(performer),
+                    }
+                }
+                "ISRC" => {
+                    if let Some(track) = current_track(&mut sheet) {
+                        track.isrc = Some(rest.trim().to_string());
+                    }
+                }
+                "REM" => {
+                    let (key, value) = split_command(rest);
+                    let (value, _) = parse_quoted(value);
+                    match key.to_ascii_uppercase().as_str() {
+                        "GENRE" => sheet.genre = Some(value),
+                        "DATE" => sheet.year = value.get(..4).and_then(|y| y.parse().ok()),
+                        "DISCNUMBER" => sheet.disc_number = value.parse().ok(),
+                        _ => {}
+                    }
+                }
+                // CATALOG, CDTEXTFILE, FLAGS, PREGAP, POSTGAP, SONGWRITER: not needed
+                _ => {}
+            }
+        }
+
+        if sheet.files.iter().all(|f| f.tracks.is_empty()) {
+            return Err(ImportError::Metadata(
+                "CUE sheet contains no tracks".to_string(),
+            ));
+        }
+
+        Ok(sheet)
+    }
+
+    /// Resolve the sheet into virtual tracks
+    ///
+    /// File names are resolved relative to `base_dir` (the sheet's directory).
+    /// Each track ends where the next track in the same file begins; the last
+    /// track of each file runs to the end of that file.
+    pub fn virtual_tracks(&self, base_dir: &Path) -> Vec<VirtualTrack> {
+        let mut result = Vec::new();
+
+        for file in &self.files {
+            let path = base_dir.join(&file.path);
+
+            for (i, track) in file.tracks.iter().enumerate() {
+                let end = file.tracks.get(i + 1).map(|next| next.start);
+                result.push(VirtualTrack {
+                    file: path.clone(),
+                    number: track.number,
+                    title: track.title.clone(),
+                    performer: track.performer.clone().or_else(|| self.performer.clone()),
+                    start: track.start,
+                    end,
+                    pregap: track.pregap(),
+                });
+            }
+        }
+
+        result
+    }
+
+    /// Resolved paths of all audio files referenced by the sheet
+    pub fn referenced_files(&self, base_dir: &Path) -> Vec<PathBuf> {
+        self.files.iter().map(|f| base_dir.join(&f.path)).collect()
+    }
+}
+
+/// Track currently being described (last TRACK of the last FILE)
+fn current_track(sheet: &mut CueSheet) -> Option<&mut CueTrack> {
+    sheet.files.last_mut().and_then(|f| f.tracks.last_mut())
+}
+
+/// Split a line into its first word and the remainder
+fn split_command(line: &str) -> (&str, &str) {
+    let line = line.trim_start();
+    match line.find(char::is_whitespace) {
+        Some(pos) => (&line[..pos], line[pos..].trim_start()),
+        None => (line, ""),
+    }
+}
+
+/// Parse an optionally quoted string, returning it and the text after it
+fn parse_quoted(s: &str) -> (String, &str) {
+    let s = s.trim_start();
+    if let Some(rest) = s.strip_prefix('"') {
+        match rest.find('"') {
+            Some(end) => (rest[..end].to_string(), &rest[end + 1..]),
+            None => (rest.trim_end().to_string(), ""),
+        }
+    } else {
+        // Unquoted: FILE takes the first word, TITLE/PERFORMER the whole value.
+        // A trailing file type is only present on FILE lines.
+        match s.rfind(char::is_whitespace) {
+            Some(pos) if is_file_type(s[pos..].trim()) => {
+                (s[..pos].trim_end().to_string(), &s[pos..])
+            }
+            _ => (s.trim_end().to_string(), ""),
+        }
+    }
+}
+
+/// Whether a word is a CUE FILE type
+fn is_file_type(word: &str) -> bool {
+    matches!(
+        word.to_ascii_uppercase().as_str(),
+        "WAVE" | "MP3" | "AIFF" | "BINARY" | "MOTOROLA" | "FLAC"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALBUM_CUE: &str = r#"REM GENRE "Progressive Rock"
+REM DATE 1973
+REM DISCID 2A0B6B04
+PERFORMER "Test Band"
+TITLE "Test Album"
+FILE "Test Band - Test Album.flac" WAVE
+  TRACK 01 AUDIO
+    TITLE "Opening"
+    PERFORMER "Test Band"
+    INDEX 01 00:00:00
+  TRACK 02 AUDIO
+    TITLE "Second Song"
+    PERFORMER "Guest Singer"
+    ISRC GBAYE7300001
+    INDEX 00 03:58:50
+    INDEX 01 04:00:00
+  TRACK 03 AUDIO
+    TITLE "Finale"
+    INDEX 00 07:30:00
+    INDEX 01 07:32:37
+"#;
+
+    #[test]
+    fn parse_cue_time() {
+        assert_eq!(CueTime::parse("00:00:00"), Some(CueTime::from_frames(0)));
+        assert_eq!(CueTime::parse("01:02:03"), Some(CueTime::from_frames(62 * 75 + 3)));
+        assert_eq!(CueTime::parse("99:59:74").unwrap().to_string(), "99:59:74");
+        assert_eq!(CueTime::parse("00:60:00"), None);
+        assert_eq!(CueTime::parse("00:00:75"), None);
+        assert_eq!(CueTime::parse("00:00"), None);
+    }
+
+    #[test]
+    fn cue_time_converts_to_exact_samples() {
+        let time = CueTime::parse("04:00:01").unwrap();
+        assert_eq!(time.to_samples(44100), 240 * 44100 + 588);
+        assert_eq!(time.to_samples(48000), 240 * 48000 + 640);
+        assert_eq!(time.to_samples(96000), 240 * 96000 + 1280);
+
+        // Round-trips through Duration without losing the sample position
+        let secs = time.to_duration().as_secs_f64();
+        assert_eq!((secs * 44100.0).round() as u64, time.to_samples(44100));
+    }
+
+    #[test]
+    fn parse_album_sheet() {
+        let sheet = CueSheet::parse(ALBUM_CUE).unwrap();
+
+        assert_eq!(sheet.title.as_deref(), Some("Test Album"));
+        assert_eq!(sheet.performer.as_deref(), Some("Test Band"));
+        assert_eq!(sheet.genre.as_deref(), Some("Progressive Rock"));
+        assert_eq!(sheet.year, Some(1973));
+        assert_eq!(sheet.files.len(), 1);
+
+        let file = &sheet.files[0];
+        assert_eq!(file.path, "Test Band - Test Album.flac");
+        assert_eq!(file.file_type, "WAVE");
+        assert_eq!(file.tracks.len(), 3);
+
+        let second = &file.tracks[1];
+        assert_eq!(second.number, 2);
+        assert_eq!(second.title.as_deref(), Some("Second Song"));
+        assert_eq!(second.performer.as_deref(), Some("Guest Singer"));
+        assert_eq!(second.isrc.as_deref(), Some("GBAYE7300001"));
+        assert_eq!(second.pregap(), CueTime::from_frames(100));
+    }
+
+    #[test]
+    fn virtual_tracks_are_contiguous() {
+        let sheet = CueSheet::parse(ALBUM_CUE).unwrap();
+        let tracks = sheet.virtual_tracks(Path::new("/music/album"));
+
+        assert_eq!(tracks.len(), 3);
+        assert_eq!(
+            tracks[0].file,
+            PathBuf::from("/music/album/Test Band - Test Album.flac")
+        );
+
+        // Each track ends exactly where the next begins (pregap stays in the previous track)
+        assert_eq!(tracks[0].end, Some(tracks[1].start));
+        assert_eq!(tracks[1].end, Some(tracks[2].start));
+        assert_eq!(tracks[2].end, None);
+
+        assert_eq!(tracks[1].start, CueTime::parse("04:00:00").unwrap());
+        assert_eq!(tracks[1].pregap, CueTime::parse("00:01:25").unwrap());
+        assert_eq!(tracks[2].pregap, CueTime::parse("00:02:37").unwrap());
+
+        // Track without PERFORMER inherits the album performer
+        assert_eq!(tracks[2].performer.as_deref(), Some("Test Band"));
+    }
+
+    #[test]
+    fn last_track_duration_uses_file_length() {
+        let sheet = CueSheet::parse(ALBUM_CUE).unwrap();
+        let tracks = sheet.virtual_tracks(Path::new("."));
+
+        assert_eq!(
+            tracks[0].duration(None),
+            Some(Duration::from_secs(240))
+        );
+        assert_eq!(tracks[2].duration(None), None);
+
+        let last = tracks[2].duration(Some(Duration::from_secs(600))).unwrap();
+        let expected = 600.0 - (7.0 * 60.0 + 32.0 + 37.0 / 75.0);
+        assert!((last.as_secs_f64() - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn hidden_track_one_audio_is_not_part_of_track_one() {
+        let cue = "FILE \"disc.wav\" WAVE\n  TRACK 01 AUDIO\n    INDEX 00 00:00:00\n    INDEX 01 00:12:00\n  TRACK 02 AUDIO\n    INDEX 01 03:00:00\n";
+        let tracks = CueSheet::parse(cue)
+            .unwrap()
+            .virtual_tracks(Path::new("."));
+
+        assert_eq!(tracks[0].start, CueTime::parse("00:12:00").unwrap());
+        assert_eq!(tracks[0].pregap, CueTime::parse("00:12:00").unwrap());
+    }
+
+    #[test]
+    fn multiple_files_and_unquoted_values() {
+        let cue = "TITLE Live Album\nFILE disc1.wav WAVE\nTRACK 1 AUDIO\nTITLE Intro\nINDEX 01 00:00:00\nTRACK 2 AUDIO\nINDEX 01 01:00:00\nFILE disc2.wav WAVE\nTRACK 3 AUDIO\nINDEX 01 00:00:00\n";
+        let sheet = CueSheet::parse(cue).unwrap();
+        assert_eq!(sheet.title.as_deref(), Some("Live Album"));
+
+        let tracks = sheet.virtual_tracks(Path::new("/cd"));
+        assert_eq!(tracks.len(), 3);
+        assert_eq!(tracks[0].title.as_deref(), Some("Intro"));
+        assert_eq!(tracks[1].end, None, "Last track of a file runs to its end");
+        assert_eq!(tracks[2].file, PathBuf::from("/cd/disc2.wav"));
+        assert_eq!(
+            sheet.referenced_files(Path::new("/cd")),
+            vec![PathBuf::from("/cd/disc1.wav"), PathBuf::from("/cd/disc2.wav")]
+        );
+    }
+
+    #[test]
+    fn reject_invalid_sheets() {
+        assert!(CueSheet::parse("").is_err());
+        assert!(CueSheet::parse("TRACK 01 AUDIO\nINDEX 01 00:00:00").is_err());
+        assert!(CueSheet::parse("FILE \"a.wav\" WAVE\nTRACK 01 AUDIO\nINDEX 01 bad").is_err());
+    }
+
+    #[test]
+    fn sheet_metadata_overrides_file_tags() {
+        let sheet = CueSheet::parse(ALBUM_CUE).unwrap();
+        let tracks = sheet.virtual_tracks(Path::new("."));
+
+        let file_metadata = ExtractedMetadata {
+            title: Some("Whole Disc".to_string()),
+            artist: Some("Tag Artist".to_string()),
+            album: Some("Tag Album".to_string()),
+            album_artist: None,
+            track_number: None,
+            disc_number: Some(1),
+            year: None,
+            genres: vec!["Rock".to_string()],
+            duration_seconds: Some(600.0),
+            bitrate: Some(900),
+            sample_rate: Some(44100),
+            channels: Some(2),
+            file_format: "flac".to_string(),
+            musicbrainz_recording_id: None,
+            composer: None,
+            album_art: None,
+            bpm: None,
+            musical_key: None,
+        };
+
+        let metadata = tracks[1].apply_to_metadata(&sheet, &file_metadata);
+        assert_eq!(metadata.title.as_deref(), Some("Second Song"));
+        assert_eq!(metadata.artist.as_deref(), Some("Guest Singer"));
+        assert_eq!(metadata.album.as_deref(), Some("Test Album"));
+        assert_eq!(metadata.album_artist.as_deref(), Some("Test Band"));
+        assert_eq!(metadata.track_number, Some(2));
+        assert_eq!(metadata.disc_number, Some(1));
+        assert_eq!(metadata.year, Some(1973));
+        assert_eq!(metadata.genres, vec!["Progressive Rock".to_string()]);
+        assert_eq!(metadata.sample_rate, Some(44100));
+        assert!((metadata.duration_seconds.unwrap() - (7.0 * 60.0 + 32.0 + 37.0 / 75.0 - 240.0)).abs() < 1e-6);
+    }
+}
@@ -1,14 +1,44 @@
 //! Main importer orchestration - brings together scanning, metadata, fuzzy matching, and copying
 
 use crate::{
-    copy, fuzzy::FuzzyMatcher, metadata, scanner::FileScanner, FileManagementStrategy,
-    ImportConfig, ImportError, ImportProgress, ImportResult, ImportSummary, Result,
+    copy,
+    cue::{CueSheet, VirtualTrack},
+    fuzzy::FuzzyMatcher,
+    metadata::{self, ExtractedMetadata},
+    scanner::{self, FileScanner},
+    FileManagementStrategy, ImportConfig, ImportError, ImportProgress, ImportResult,
+    ImportSummary, Result,
 };
+use soul_core::types::TrackId;
 use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::mpsc;
 
+/// A unit of work for the importer
+enum ImportItem {
+    /// A regular audio file
+    File(PathBuf),
+    /// One track of a CUE sheet
+    CueTrack {
+        cue_path: PathBuf,
+        sheet: Arc<CueSheet>,
+        track: VirtualTrack,
+    },
+}
+
+impl ImportItem {
+    /// Path shown in progress updates and error reports
+    fn display_path(&self) -> &Path {
+        match self {
+            Self::File(path) => path,
+            Self::CueTrack { track, .. } => &track.file,
+        }
+    }
+}
+
 /// Music importer orchestrator
 pub struct MusicImporter {
     pool: SqlitePool,
@@ -31,13 +61,16 @@ impl MusicImporter {
         mpsc::Receiver<ImportProgress>,
         tokio::task::JoinHandle<Result<ImportSummary>>,
     )> {
-        let scanner = FileScanner::new();
+        let scanner = FileScanner::new().include_cue_sheets(true);
         let files = scanner.scan_directory(directory)?;
         self.import_files(&files).await
     }
 
     /// Import specific files
     ///
+    /// `.cue` sheets in the list are imported as one track per sheet entry;
+    /// audio files referenced by a sheet are not imported on their own.
+    ///
     /// Returns a channel for receiving progress updates and a handle to the import task
     pub async fn import_files(
         &self,
@@ -69,20 +102,87 @@ impl MusicImporter {
         progress_tx: mpsc::Sender<ImportProgress>,
     ) -> Result<ImportSummary> {
         let start_time = Instant::now();
-        let total_files = files.len();
+        let mut errors = Vec::new();
 
+        let (cue_sheets, audio_files): (Vec<_>, Vec<_>) =
+            files.into_iter().partition(|path| scanner::is_cue_sheet(path));
+
+        // Expand CUE sheets into virtual tracks, remembering which audio files they cover
+        let mut items = Vec::new();
+        let mut covered_files = HashSet::new();
+        for cue_path in cue_sheets {
+            let sheet = match CueSheet::from_file(&cue_path) {
+                Ok(sheet) => Arc::new(sheet),
+                Err(e) => {
+                    eprintln!("[Importer] FAILED to parse CUE sheet {:?}: {}", cue_path, e);
+                    tracing::error!("Failed to parse CUE sheet {:?}: {}", cue_path, e);
+                    errors.push((cue_path, e.to_string()));
+                    continue;
+                }
+            };
+
+            let base_dir = cue_path.parent().unwrap_or(Path::new("."));
+            covered_files.extend(
+                sheet
+                    .referenced_files(base_dir)
+                    .iter()
+                    .map(|path| normalize_path(path)),
+            );
+            for track in sheet.virtual_tracks(base_dir) {
+                items.push(ImportItem::CueTrack {
+                    cue_path: cue_path.clone(),
+                    sheet: Arc::clone(&sheet),
+                    track,
+                });
+            }
+        }
+        items.extend(
+            audio_files
+                .into_iter()
+                .filter(|path| !covered_files.contains(&normalize_path(path)))
+                .map(ImportItem::File),
+        );
+
+        let total_files = items.len() + errors.len();
         let mut progress = ImportProgress::new(total_files);
+        progress.failed_imports = errors.len();
+        progress.processed_files = errors.len();
         let mut require_review = Vec::new();
-        let mut errors = Vec::new();
+
+        // Metadata and hash of each album image, shared by all of its CUE tracks
+        let mut image_cache = HashMap::new();
 
         // Send initial progress
         let _ = progress_tx.send(progress.clone()).await;
 
-        for file_path in files {
+        for item in items {
+            let file_path = item.display_path().to_path_buf();
             progress.current_file = Some(file_path.clone());
             let _ = progress_tx.send(progress.clone()).await;
 
-            match Self::import_single_file(&file_path, &pool, &config, &fuzzy_matcher).await {
+            let result = match &item {
+                ImportItem::File(path) => {
+                    Self::import_single_file(path, &pool, &config, &fuzzy_matcher).await
+                }
+                ImportItem::CueTrack {
+                    cue_path,
+                    sheet,
+                    track,
+                } => {
+                    Self::import_cue_track(
+                        cue_path,
+                        sheet,
+                        track,
+                        &mut image_cache,
+                        &pool,
+                        &config,
+                        &fuzzy_matcher,
+                    )
+                    .await
+                }
+            };
+
+            match result {
                 Ok(result) => {
                     eprintln!("[Importer] Successfully imported: {:?}", file_path);
                     if result.requires_review {
@@ -175,6 +275,121 @@ impl MusicImporter {
 
         eprintln!("[Importer] Result path: {:?}", library_path);
 
+        let (_, result) = Self::create_track_record(
+            file_path,
+            library_path,
+            &metadata,
+            file_hash,
+            pool,
+            config,
+            fuzzy_matcher,
+        )
+        .await?;
+
+        Ok(result)
+    }
+
+    /// Import one track of a CUE sheet
+    ///
+    /// The album image is always referenced in place: it holds every track of
+    /// the sheet, so it cannot be renamed or moved per track.
+    async fn import_cue_track(
+        cue_path: &Path,
+        sheet: &CueSheet,
+        track: &VirtualTrack,
+        image_cache: &mut HashMap<PathBuf, (ExtractedMetadata, String)>,
+        pool: &SqlitePool,
+        config: &ImportConfig,
+        fuzzy_matcher: &FuzzyMatcher,
+    ) -> Result<ImportResult> {
+        if !track.file.exists() {
+            return Err(ImportError::FileNotFound(format!(
+                "{} (referenced by {})",
+                track.file.display(),
+                cue_path.display()
+            )));
+        }
+
+        let (file_metadata, image_hash) = match image_cache.get(&track.file) {
+            Some(cached) => cached.clone(),
+            None => {
+                let extracted = (
+                    metadata::extract_metadata(&track.file)?,
+                    metadata::calculate_file_hash(&track.file)?,
+                );
+                image_cache.insert(track.file.clone(), extracted.clone());
+                extracted
+            }
+        };
+
+        let metadata = track.apply_to_metadata(sheet, &file_metadata);
+
+        // All tracks share the image, so the hash is made unique per sheet entry
+        let file_hash = format!("{}:{}", image_hash, track.number);
+        if config.skip_duplicates
+            && (soul_storage::tracks::find_by_hash(pool, &file_hash).await?).is_some()
+        {
+            return Err(ImportError::Duplicate(format!(
+                "Track {} already exists: {}",
+                track.number,
+                cue_path.display()
+            )));
+        }
+
+        eprintln!(
+            "[Importer] CUE track {} of {:?}: {} - {:?}",
+            track.number, cue_path, track.start, track.end
+        );
+
+        let (track_id, mut result) = Self::create_track_record(
+            &track.file,
+            track.file.clone(),
+            &metadata,
+            file_hash,
+            pool,
+            config,
+            fuzzy_matcher,
+        )
+        .await?;
+
+        let track_id: i64 = track_id.as_str().parse().map_err(|_| {
+            ImportError::Unknown(format!("Invalid track ID: {}", track_id))
+        })?;
+        soul_storage::cue_tracks::set_range(
+            pool,
+            &soul_storage::cue_tracks::CueRange {
+                track_id,
+                cue_path: cue_path.display().to_string(),
+                cue_track_number: track.number as i32,
+                start_frames: track.start.frames() as i64,
+                end_frames: track.end.map(|end| end.frames() as i64),
+                pregap_frames: track.pregap.frames() as i64,
+            },
+        )
+        .await?;
+
+        if config.file_strategy != FileManagementStrategy::Reference {
+            result.warnings.push(format!(
+                "CUE sheet audio is referenced in place: {}",
+                track.file.display()
+            ));
+        }
+
+        Ok(result)
+    }
+
+    /// Match artist/album/genres and insert the track into the database
+    ///
+    /// Returns the ID of the created track and the import result.
+    async fn create_track_record(
+        file_path: &Path,
+        library_path: PathBuf,
+        metadata: &ExtractedMetadata,
+        file_hash: String,
+        pool: &SqlitePool,
+        config: &ImportConfig,
+        fuzzy_matcher: &FuzzyMatcher,
+    ) -> Result<(TrackId, ImportResult)> {
         // Fuzzy match artist
         let artist_match = if let Some(ref artist_name) = metadata.artist {
             Some(
@@ -250,7 +465,7 @@ impl MusicImporter {
                 .and_then(|e| e.to_str())
                 .unwrap_or("unknown")
                 .to_uppercase(),
-            file_hash: Some(file_hash.clone()),
+            file_hash: Some(file_hash),
             origin_source_id: 1, // Default local source
             local_file_path: Some(library_path.display().to_string()),
             musicbrainz_recording_id: None,
@@ -270,15 +485,34 @@ impl MusicImporter {
             .await?;
         }
 
-        Ok(ImportResult {
-            track_id: 0, // Legacy field, track ID is now the string
-            source_path: file_path.to_path_buf(),
-            library_path,
-            artist_match,
-            album_match,
-            genre_matches,
-            requires_review,
-            warnings: Vec::new(),
-        })
+        // Store BPM/key from tags
+        if let Ok(track_id) = created_track.id.as_str().parse::<i64>() {
+            soul_storage::tempo_key::set_from_tags(
+                pool,
+                track_id,
+                metadata.bpm,
+                metadata.musical_key.as_deref(),
+            )
+            .await?;
+        }
+
+        Ok((
+            created_track.id,
+            ImportResult {
+                track_id: 0, // Legacy field, track ID is now the string
+                source_path: file_path.to_path_buf(),
+                library_path,
+                artist_match,
+                album_match,
+                genre_matches,
+                requires_review,
+                warnings: Vec::new(),
+            },
+        ))
     }
 }
+
+/// Canonical form of a path for comparing files referenced by CUE sheets
+fn normalize_path(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
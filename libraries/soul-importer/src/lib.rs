@@ -9,6 +9,7 @@
 //! - Fuzzy matching for artists, albums, and genres with confidence scoring
 //! - File copying to managed library with organized naming
 //! - Duplicate detection via file hashing
+//! - CUE sheet support (single-file album images split into virtual tracks)
 //! - Progress reporting
 //! - Background import processing
 //!
@@ -16,6 +17,7 @@
 //!
 //! - `scanner`: Filesystem scanning for audio files
 //! - `metadata`: Metadata extraction from audio tags
+//! - `cue`: CUE sheet parsing into virtual tracks
 //! - `fuzzy`: Fuzzy matching with confidence scoring
 //! - `copy`: File copying to managed library
 //! - `importer`: Orchestration of the import process
@@ -26,6 +28,7 @@ mod types;
 
 // Core modules
 pub mod copy;
+pub mod cue;
 pub mod fuzzy;
 pub mod importer;
 pub mod library_scanner;
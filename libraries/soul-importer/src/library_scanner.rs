@@ -438,6 +438,15 @@ impl LibraryScanner {
         )
         .await?;
 
+        // Store BPM/key from tags
+        soul_storage::tempo_key::set_from_tags(
+            &self.pool,
+            track_id,
+            meta.bpm,
+            meta.musical_key.as_deref(),
+        )
+        .await?;
+
         // Add genres to track
         let track_id_typed = soul_core::types::TrackId::new(track_id.to_string());
         for genre_name in &meta.genres {
@@ -525,6 +534,14 @@ impl LibraryScanner {
             .await?;
         }
 
+        soul_storage::tempo_key::set_from_tags(
+            &self.pool,
+            track_id,
+            meta.bpm,
+            meta.musical_key.as_deref(),
+        )
+        .await?;
+
         Ok(())
     }
 
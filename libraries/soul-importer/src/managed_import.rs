@@ -7,7 +7,7 @@
 //! - Safe file copy with verification
 //! - Progress tracking
 
-use crate::{metadata, path_template::PathTemplate, ImportError, Result};
+use crate::{metadata, path_template::PathTemplate, scanner::is_audio_file, ImportError, Result};
 use sha2::{Digest, Sha256};
 use sqlx::SqlitePool;
 use std::fs::{self, File};
@@ -343,18 +343,6 @@ pub fn move_file(source: &Path, dest: &Path) -> Result<()> {
     Ok(())
 }
 
-/// Check if a path is an audio file based on extension
-fn is_audio_file(path: &Path) -> bool {
-    let audio_extensions = [
-        "flac", "mp3", "m4a", "aac", "ogg", "opus", "wav", "aif", "aiff",
-    ];
-
-    path.extension()
-        .and_then(|ext| ext.to_str())
-        .map(|ext| audio_extensions.contains(&ext.to_lowercase().as_str()))
-        .unwrap_or(false)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -413,6 +401,8 @@ mod tests {
         assert!(is_audio_file(Path::new("test.mp3")));
         assert!(is_audio_file(Path::new("test.FLAC")));
         assert!(is_audio_file(Path::new("/path/to/test.m4a")));
+        assert!(is_audio_file(Path::new("test.wv")));
+        assert!(is_audio_file(Path::new("test.ape")));
         assert!(!is_audio_file(Path::new("test.txt")));
         assert!(!is_audio_file(Path::new("test.jpg")));
     }
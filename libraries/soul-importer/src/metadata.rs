@@ -113,6 +113,42 @@ fn parse_year(s: &str) -> Option<i32> {
         .filter(|&y| (1900..=2099).contains(&y))
 }
 
+/// Parse a BPM tag value ("128", "127.96", "128 BPM")
+pub fn parse_bpm(s: &str) -> Option<f64> {
+    let number = s.trim_end_matches(|c: char| c.is_alphabetic() || c.is_whitespace());
+    number
+        .trim()
+        .replace(',', ".")
+        .parse::<f64>()
+        .ok()
+        .filter(|&bpm| bpm > 0.0 && bpm < 1000.0)
+}
+
+/// Parse a key tag value into Camelot notation
+///
+/// Accepts standard ("Am", "F# minor"), Camelot ("8A") and Open Key
+/// ("1m") notation as written by DJ software.
+pub fn parse_musical_key(s: &str) -> Option<String> {
+    soul_audio::musical::MusicalKey::parse(s).map(|key| key.camelot())
+}
+
+/// Tag keys holding the BPM and musical key in Symphonia's custom tags
+const BPM_TAG_KEYS: &[&str] = &["TBPM", "BPM", "TMPO"];
+const KEY_TAG_KEYS: &[&str] = &["TKEY", "INITIALKEY", "KEY"];
+
+/// Find the first parseable value of any of the given custom tags
+fn find_custom_tag<T>(
+    meta: &soul_audio::AudioMetadata,
+    keys: &[&str],
+    parse: impl Fn(&str) -> Option<T>,
+) -> Option<T> {
+    meta.custom_tags
+        .iter()
+        .filter(|(key, _)| keys.iter().any(|k| key.eq_ignore_ascii_case(k)))
+        .flat_map(|(_, values)| values.iter())
+        .find_map(|value| parse(value))
+}
+
 /// Extracted metadata from an audio file
 #[derive(Debug, Clone)]
 pub struct ExtractedMetadata {
@@ -161,6 +197,12 @@ pub struct ExtractedMetadata {
     /// Composer
     pub composer: Option<String>,
 
+    /// Tempo in beats per minute
+    pub bpm: Option<f64>,
+
+    /// Musical key in Camelot notation (e.g. "8A")
+    pub musical_key: Option<String>,
+
     /// Embedded album art (raw data and MIME type)
     pub album_art: Option<(Vec<u8>, String)>,
 }
@@ -216,8 +258,29 @@ fn find_best_tag(file: &TaggedFile) -> Option<&lofty::Tag> {
     }
 }
 
+/// Whether a file is read through the `soul_audio` decoder registry
+/// instead of lofty
+///
+/// That covers DSD files, which lofty doesn't support, and the formats
+/// soul-audio decodes itself (WavPack, Monkey's Audio, Musepack), so their
+/// tags and stream properties match what playback sees.
+fn reads_through_registry(path: &Path) -> bool {
+    soul_audio::DsdFile::is_dsd_path(path)
+        || soul_audio::formats::registry()
+            .find_by_extension(path)
+            .is_some_and(|format| format.name != soul_audio::formats::SYMPHONIA)
+}
+
 /// Extract metadata from an audio file
+///
+/// DSD, WavPack, Monkey's Audio and Musepack files are read through
+/// soul-audio, which parses their ID3v2 / DIIN / APEv2 tags; everything
+/// else through lofty.
 pub fn extract_metadata(path: &Path) -> Result<ExtractedMetadata> {
+    if reads_through_registry(path) {
+        return extract_metadata_symphonia(path);
+    }
+
     let tagged_file = Probe::open(path)
         .map_err(|e| ImportError::Metadata(format!("Failed to open file: {}", e)))?
         .read()
@@ -331,6 +394,17 @@ pub fn extract_metadata(path: &Path) -> Result<ExtractedMetadata> {
             .map(|s| s.to_string())
     });
 
+    // Extract tempo and key
+    let bpm = tag.and_then(|t| {
+        t.get_string(&lofty::ItemKey::Bpm)
+            .or_else(|| t.get_string(&lofty::ItemKey::IntegerBpm))
+            .and_then(parse_bpm)
+    });
+    let musical_key = tag.and_then(|t| {
+        t.get_string(&lofty::ItemKey::InitialKey)
+            .and_then(parse_musical_key)
+    });
+
     // Extract album art
     let album_art = tag.and_then(|t| {
         t.pictures().first().map(|pic| {
@@ -356,10 +430,81 @@ pub fn extract_metadata(path: &Path) -> Result<ExtractedMetadata> {
         file_format,
         musicbrainz_recording_id,
         composer,
+        bpm,
+        musical_key,
         album_art,
     })
 }
 
+/// Notation used when writing the musical key to tags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyNotation {
+    /// "Am", "F#" (the ID3 TKEY convention)
+    #[default]
+    Standard,
+    /// "8A", "2B"
+    Camelot,
+    /// "1m", "7d"
+    OpenKey,
+}
+
+/// Write BPM and key tags to an audio file
+///
+/// `musical_key` may be in any notation [`parse_musical_key`] accepts and is
+/// rewritten in `notation`. Values that are `None` are left untouched.
+pub fn write_tempo_key_tags(
+    path: &Path,
+    bpm: Option<f64>,
+    musical_key: Option<&str>,
+    notation: KeyNotation,
+) -> Result<()> {
+    use lofty::TagExt;
+
+    if !path.exists() {
+        return Err(ImportError::FileNotFound(path.display().to_string()));
+    }
+
+    let key = match musical_key {
+        Some(s) => Some(
+            soul_audio::musical::MusicalKey::parse(s)
+                .ok_or_else(|| ImportError::Metadata(format!("Unknown musical key: {}", s)))?,
+        ),
+        None => None,
+    };
+
+    let mut tagged_file = Probe::open(path)
+        .and_then(|p| p.read())
+        .map_err(|e| ImportError::Metadata(e.to_string()))?;
+
+    // Get or create the primary tag
+    let tag_type = tagged_file.primary_tag_type();
+    if tagged_file.tag(tag_type).is_none() {
+        tagged_file.insert_tag(lofty::Tag::new(tag_type));
+    }
+    let tag = tagged_file.tag_mut(tag_type).unwrap();
+
+    if let Some(bpm) = bpm {
+        // Formats without a decimal BPM field only take the rounded value
+        tag.insert_text(lofty::ItemKey::IntegerBpm, format!("{:.0}", bpm));
+        tag.insert_text(lofty::ItemKey::Bpm, format!("{:.2}", bpm));
+    }
+
+    if let Some(key) = key {
+        let value = match notation {
+            KeyNotation::Standard => key.to_string(),
+            KeyNotation::Camelot => key.camelot(),
+            KeyNotation::OpenKey => key.open_key(),
+        };
+        tag.insert_text(lofty::ItemKey::InitialKey, value);
+    }
+
+    tag.save_to_path(path)
+        .map_err(|e| ImportError::Metadata(e.to_string()))?;
+
+    Ok(())
+}
+
 /// Calculate SHA-256 hash of a file for duplicate detection
 pub fn calculate_file_hash(path: &Path) -> Result<String> {
     use sha2::{Digest, Sha256};
@@ -382,10 +527,10 @@ pub fn calculate_file_hash(path: &Path) -> Result<String> {
     Ok(hex::encode(hash))
 }
 
-/// Extract metadata from an audio file using Symphonia
+/// Extract metadata from an audio file using soul-audio
 ///
-/// This provides an alternative to the lofty-based extraction.
-/// Symphonia is the same decoder used for audio playback, so it may
+/// This provides an alternative to the lofty-based extraction. It goes
+/// through the same decoder registry as audio playback, so it may
 /// provide more accurate audio property information for some formats.
 ///
 /// # Arguments
@@ -415,6 +560,9 @@ pub fn extract_metadata_symphonia(path: &Path) -> Result<ExtractedMetadata> {
         .map(|ext| ext.to_lowercase())
         .unwrap_or_else(|| "unknown".to_string());
 
+    let bpm = find_custom_tag(&audio_metadata, BPM_TAG_KEYS, parse_bpm);
+    let musical_key = find_custom_tag(&audio_metadata, KEY_TAG_KEYS, parse_musical_key);
+
     // Convert album art
     let album_art = audio_metadata.album_art.map(|art| {
         (art.data, art.mime_type)
@@ -436,6 +584,8 @@ pub fn extract_metadata_symphonia(path: &Path) -> Result<ExtractedMetadata> {
         file_format,
         musicbrainz_recording_id: audio_metadata.musicbrainz_recording_id,
         composer: audio_metadata.composer,
+        bpm,
+        musical_key,
         album_art,
     })
 }
@@ -453,6 +603,8 @@ impl From<soul_audio::AudioMetadata> for ExtractedMetadata {
             })
             .unwrap_or_default();
 
+        let bpm = find_custom_tag(&meta, BPM_TAG_KEYS, parse_bpm);
+        let musical_key = find_custom_tag(&meta, KEY_TAG_KEYS, parse_musical_key);
         let album_art = meta.album_art.map(|art| (art.data, art.mime_type));
 
         Self {
@@ -471,6 +623,8 @@ impl From<soul_audio::AudioMetadata> for ExtractedMetadata {
             file_format: "unknown".to_string(), // Would need path to determine
             musicbrainz_recording_id: meta.musicbrainz_recording_id,
             composer: meta.composer,
+            bpm,
+            musical_key,
             album_art,
         }
     }
@@ -480,6 +634,15 @@ impl From<soul_audio::AudioMetadata> for ExtractedMetadata {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_reads_through_registry() {
+        assert!(reads_through_registry(Path::new("/music/track.dsf")));
+        assert!(reads_through_registry(Path::new("/music/track.wv")));
+        assert!(reads_through_registry(Path::new("/music/track.APE")));
+        assert!(!reads_through_registry(Path::new("/music/track.flac")));
+        assert!(!reads_through_registry(Path::new("/music/track.mp3")));
+    }
+
     #[test]
     fn test_metadata_is_sparse() {
         let sparse = ExtractedMetadata {
@@ -499,6 +662,8 @@ mod tests {
             musicbrainz_recording_id: None,
             composer: None,
             album_art: None,
+            bpm: None,
+            musical_key: None,
         };
 
         assert!(sparse.is_sparse());
@@ -520,6 +685,8 @@ mod tests {
             musicbrainz_recording_id: None,
             composer: None,
             album_art: None,
+            bpm: None,
+            musical_key: None,
         };
 
         assert!(!not_sparse.is_sparse());
@@ -574,4 +741,35 @@ mod tests {
         assert_eq!(parse_year("abc"), None);
         assert_eq!(parse_year(""), None);
     }
+
+    #[test]
+    fn test_parse_bpm() {
+        assert_eq!(parse_bpm("128"), Some(128.0));
+        assert_eq!(parse_bpm(" 127.96 "), Some(127.96));
+        assert_eq!(parse_bpm("174 BPM"), Some(174.0));
+        assert_eq!(parse_bpm("93,5"), Some(93.5));
+        assert_eq!(parse_bpm("0"), None);
+        assert_eq!(parse_bpm("fast"), None);
+    }
+
+    #[test]
+    fn test_parse_musical_key() {
+        assert_eq!(parse_musical_key("Am"), Some("8A".to_string()));
+        assert_eq!(parse_musical_key("F# minor"), Some("11A".to_string()));
+        assert_eq!(parse_musical_key("8B"), Some("8B".to_string()));
+        assert_eq!(parse_musical_key("1m"), Some("8A".to_string()));
+        assert_eq!(parse_musical_key(""), None);
+    }
+
+    #[test]
+    fn test_tempo_key_from_custom_tags() {
+        let mut meta = soul_audio::AudioMetadata::default();
+        meta.add_custom_tag("bpm", "not a number");
+        meta.add_custom_tag("TBPM", "122");
+        meta.add_custom_tag("INITIALKEY", "Ebm");
+
+        let extracted = ExtractedMetadata::from(meta);
+        assert_eq!(extracted.bpm, Some(122.0));
+        assert_eq!(extracted.musical_key, Some("2A".to_string()));
+    }
 }
@@ -409,6 +409,8 @@ mod tests {
             musicbrainz_recording_id: None,
             composer: None,
             album_art: None,
+            bpm: None,
+            musical_key: None,
         }
     }
 
@@ -474,6 +476,8 @@ mod tests {
             musicbrainz_recording_id: None,
             composer: None,
             album_art: None,
+            bpm: None,
+            musical_key: None,
         };
         let source = Path::new("/path/to/original_song.mp3");
 
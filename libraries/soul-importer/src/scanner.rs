@@ -4,8 +4,8 @@ use crate::{ImportError, Result};
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
-/// Supported audio file extensions
-const SUPPORTED_EXTENSIONS: &[&str] = &["mp3", "flac", "ogg", "wav", "aac", "m4a", "opus"];
+/// CUE sheet file extension
+const CUE_EXTENSION: &str = "cue";
 
 /// Scanner for audio files in directories
 #[derive(Default)]
@@ -15,6 +15,9 @@ pub struct FileScanner {
 
     /// Maximum depth to traverse (-1 for unlimited)
     max_depth: Option<usize>,
+
+    /// Whether to also return `.cue` sheets
+    include_cue_sheets: bool,
 }
 
 impl FileScanner {
@@ -35,6 +38,15 @@ impl FileScanner {
         self
     }
 
+    /// Set whether `.cue` sheets are returned alongside audio files
+    ///
+    /// The importer turns each sheet into virtual tracks and skips the audio
+    /// files the sheet references.
+    pub fn include_cue_sheets(mut self, include: bool) -> Self {
+        self.include_cue_sheets = include;
+        self
+    }
+
     /// Scan a directory for audio files
     ///
     /// # Arguments
@@ -72,7 +84,7 @@ impl FileScanner {
             }
 
             // Check if file has supported extension
-            if is_audio_file(path) {
+            if self.is_wanted(path) {
                 audio_files.push(path.to_path_buf());
             }
         }
@@ -102,17 +114,30 @@ impl FileScanner {
     pub fn validate_files(&self, paths: &[PathBuf]) -> Vec<PathBuf> {
         paths
             .iter()
-            .filter(|path| path.exists() && path.is_file() && is_audio_file(path))
+            .filter(|path| path.exists() && path.is_file() && self.is_wanted(path))
             .cloned()
             .collect()
     }
+
+    /// Whether a file should be returned by this scanner
+    fn is_wanted(&self, path: &Path) -> bool {
+        is_audio_file(path) || (self.include_cue_sheets && is_cue_sheet(path))
+    }
 }
 
 /// Check if a file is a supported audio file
+///
+/// Supported means listed in the `soul_audio` decoder registry, so
+/// everything scanned can also be tagged and played.
 pub fn is_audio_file(path: &Path) -> bool {
+    soul_audio::formats::registry().supports_path(path)
+}
+
+/// Check if a file is a CUE sheet
+pub fn is_cue_sheet(path: &Path) -> bool {
     path.extension()
         .and_then(|ext| ext.to_str())
-        .map(|ext| SUPPORTED_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        .map(|ext| ext.eq_ignore_ascii_case(CUE_EXTENSION))
         .unwrap_or(false)
 }
 
@@ -121,7 +146,7 @@ pub fn get_audio_extension(path: &Path) -> Option<String> {
     path.extension()
         .and_then(|ext| ext.to_str())
         .map(|ext| ext.to_lowercase())
-        .filter(|ext| SUPPORTED_EXTENSIONS.contains(&ext.as_str()))
+        .filter(|_| is_audio_file(path))
 }
 
 #[cfg(test)]
@@ -136,6 +161,12 @@ mod tests {
         assert!(is_audio_file(Path::new("test.MP3")));
         assert!(is_audio_file(Path::new("test.flac")));
         assert!(is_audio_file(Path::new("test.ogg")));
+        assert_eq!(
+            is_audio_file(Path::new("test.opus")),
+            soul_audio::codecs::OPUS_ENABLED
+        );
+        assert!(is_audio_file(Path::new("test.wv")));
+        assert!(is_audio_file(Path::new("test.APE")));
         assert!(!is_audio_file(Path::new("test.txt")));
         assert!(!is_audio_file(Path::new("test")));
     }
@@ -150,6 +181,10 @@ mod tests {
             get_audio_extension(Path::new("test.MP3")),
             Some("mp3".to_string())
         );
+        assert_eq!(
+            get_audio_extension(Path::new("test.WV")),
+            Some("wv".to_string())
+        );
         assert_eq!(get_audio_extension(Path::new("test.txt")), None);
     }
 
@@ -197,4 +232,23 @@ mod tests {
         assert!(files.iter().any(|p| p.ends_with("song1.mp3")));
         assert!(!files.iter().any(|p| p.ends_with("song2.mp3")));
     }
+
+    #[test]
+    fn test_scan_includes_cue_sheets_when_enabled() {
+        let temp = TempDir::new().unwrap();
+        let base = temp.path();
+
+        fs::write(base.join("album.flac"), b"fake flac").unwrap();
+        fs::write(base.join("album.CUE"), b"FILE \"album.flac\" WAVE").unwrap();
+
+        let files = FileScanner::new().scan_directory(base).unwrap();
+        assert_eq!(files.len(), 1);
+
+        let files = FileScanner::new()
+            .include_cue_sheets(true)
+            .scan_directory(base)
+            .unwrap();
+        assert_eq!(files.len(), 2);
+        assert!(files.iter().any(|p| is_cue_sheet(p)));
+    }
 }
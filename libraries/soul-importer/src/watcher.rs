@@ -9,7 +9,7 @@
 //! - macOS: `FSEvents`
 //! - Linux: `inotify`
 
-use crate::{library_scanner::LibraryScanner, Result};
+use crate::{library_scanner::LibraryScanner, scanner::is_audio_file, Result};
 use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode};
 use notify_debouncer_full::{new_debouncer, DebounceEventResult, Debouncer, RecommendedCache};
 use soul_core::types::LibrarySource;
@@ -383,18 +383,6 @@ fn convert_event(event: &Event) -> Option<WatcherEvent> {
     }
 }
 
-/// Check if a path is an audio file based on extension
-fn is_audio_file(path: &Path) -> bool {
-    let audio_extensions = [
-        "flac", "mp3", "m4a", "aac", "ogg", "opus", "wav", "aif", "aiff",
-    ];
-
-    path.extension()
-        .and_then(|ext| ext.to_str())
-        .map(|ext| audio_extensions.contains(&ext.to_lowercase().as_str()))
-        .unwrap_or(false)
-}
-
 /// Run the event processing loop
 ///
 /// This function runs indefinitely, processing events as they arrive.
@@ -438,6 +426,8 @@ mod tests {
         assert!(is_audio_file(Path::new("test.mp3")));
         assert!(is_audio_file(Path::new("test.FLAC")));
         assert!(is_audio_file(Path::new("/path/to/test.m4a")));
+        assert!(is_audio_file(Path::new("test.wv")));
+        assert!(is_audio_file(Path::new("test.ape")));
         assert!(!is_audio_file(Path::new("test.txt")));
         assert!(!is_audio_file(Path::new("test.jpg")));
         assert!(!is_audio_file(Path::new("test")));
@@ -6,7 +6,9 @@
 //! - Loudness range (LRA) - the variation in loudness
 //! - True peak (dBTP) - the maximum inter-sample peak level
 //! - Sample peak (dBFS) - the maximum sample value
+//! - Dynamic range (DR) - the TT / Pleasurize Music Foundation DR value
 
+use crate::dynamic_range::DynamicRangeMeter;
 use crate::error::{LoudnessError, Result};
 use ebur128::{EbuR128, Mode};
 use std::fmt;
@@ -39,6 +41,10 @@ pub struct LoudnessInfo {
 
     /// Number of channels
     pub channels: u32,
+
+    /// TT dynamic range in dB (unrounded mean over channels)
+    /// Low values (DR5 and below) indicate a brickwalled master
+    pub dynamic_range_db: Option<f64>,
 }
 
 impl LoudnessInfo {
@@ -51,6 +57,13 @@ impl LoudnessInfo {
     pub fn max_safe_gain(&self) -> f64 {
         -self.true_peak_dbfs
     }
+
+    /// Peak-to-loudness ratio (PLR) in dB: true peak minus integrated loudness
+    ///
+    /// Like DR, a low PLR means a heavily limited master.
+    pub fn peak_to_loudness_ratio(&self) -> f64 {
+        self.true_peak_dbfs - self.integrated_lufs
+    }
 }
 
 impl Default for LoudnessInfo {
@@ -63,6 +76,7 @@ impl Default for LoudnessInfo {
             duration_seconds: 0.0,
             sample_rate: 44100,
             channels: 2,
+            dynamic_range_db: None,
         }
     }
 }
@@ -105,8 +119,12 @@ pub struct LoudnessAnalyzer {
     sample_rate: u32,
     /// Number of channels
     channels: u32,
+    /// Measurements enabled on the EBU R128 instance
+    mode: Mode,
     /// Total samples processed
     samples_processed: usize,
+    /// DR meter (full analysis only)
+    dr_meter: Option<DynamicRangeMeter>,
 }
 
 impl LoudnessAnalyzer {
@@ -119,6 +137,32 @@ impl LoudnessAnalyzer {
     /// # Errors
     /// Returns error if sample rate or channel count is invalid
     pub fn new(sample_rate: u32, channels: u32) -> Result<Self> {
+        // Create EBU R128 analyzer with all measurements enabled
+        // Mode::I = Integrated loudness
+        // Mode::LRA = Loudness range
+        // Mode::SAMPLE_PEAK = Maximum sample value
+        // Mode::TRUE_PEAK = Inter-sample peak (4x oversampling)
+        Self::with_mode(
+            sample_rate,
+            channels,
+            Mode::I | Mode::LRA | Mode::SAMPLE_PEAK | Mode::TRUE_PEAK,
+        )
+    }
+
+    /// Create an analyzer for live metering during playback
+    ///
+    /// Only momentary (400ms) and short-term (3s) loudness are measured, so
+    /// memory use stays fixed no matter how long it runs. `finalize` is not
+    /// meaningful for a live analyzer; read `momentary_loudness` and
+    /// `short_term_loudness` instead.
+    ///
+    /// # Errors
+    /// Returns error if sample rate or channel count is invalid
+    pub fn live(sample_rate: u32, channels: u32) -> Result<Self> {
+        Self::with_mode(sample_rate, channels, Mode::M | Mode::S)
+    }
+
+    fn with_mode(sample_rate: u32, channels: u32, mode: Mode) -> Result<Self> {
         // Validate inputs
         if !(8000..=384000).contains(&sample_rate) {
             return Err(LoudnessError::InvalidSampleRate(sample_rate));
@@ -127,23 +171,24 @@ impl LoudnessAnalyzer {
             return Err(LoudnessError::InvalidChannelCount(channels));
         }
 
-        // Create EBU R128 analyzer with all measurements enabled
-        // Mode::I = Integrated loudness
-        // Mode::LRA = Loudness range
-        // Mode::SAMPLE_PEAK = Maximum sample value
-        // Mode::TRUE_PEAK = Inter-sample peak (4x oversampling)
-        let mode = Mode::I | Mode::LRA | Mode::SAMPLE_PEAK | Mode::TRUE_PEAK;
-
         let ebur128 = EbuR128::new(channels, sample_rate, mode)?;
 
         Ok(Self {
             ebur128,
             sample_rate,
             channels,
+            mode,
             samples_processed: 0,
+            dr_meter: Self::dr_meter(sample_rate, channels, mode),
         })
     }
 
+    /// The DR meter runs alongside integrated loudness, not for live metering
+    fn dr_meter(sample_rate: u32, channels: u32, mode: Mode) -> Option<DynamicRangeMeter> {
+        mode.contains(Mode::I)
+            .then(|| DynamicRangeMeter::new(sample_rate, channels))
+    }
+
     /// Add audio frames for analysis
     ///
     /// # Arguments
@@ -168,6 +213,9 @@ impl LoudnessAnalyzer {
 
         // Add samples to analyzer
         self.ebur128.add_frames_f32(samples)?;
+        if let Some(meter) = &mut self.dr_meter {
+            meter.add_frames(samples);
+        }
         self.samples_processed += samples.len();
 
         Ok(())
@@ -191,6 +239,9 @@ impl LoudnessAnalyzer {
         }
 
         self.ebur128.add_frames_i16(samples)?;
+        if let Some(meter) = &mut self.dr_meter {
+            meter.add_frames_i16(samples);
+        }
         self.samples_processed += samples.len();
 
         Ok(())
@@ -214,6 +265,9 @@ impl LoudnessAnalyzer {
         }
 
         self.ebur128.add_frames_i32(samples)?;
+        if let Some(meter) = &mut self.dr_meter {
+            meter.add_frames_i32(samples);
+        }
         self.samples_processed += samples.len();
 
         Ok(())
@@ -285,6 +339,10 @@ impl LoudnessAnalyzer {
             duration_seconds,
             sample_rate: self.sample_rate,
             channels: self.channels,
+            dynamic_range_db: self
+                .dr_meter
+                .and_then(DynamicRangeMeter::finalize)
+                .map(|dr| dr.dr_db),
         })
     }
 
@@ -293,6 +351,16 @@ impl LoudnessAnalyzer {
         self.ebur128.loudness_global().ok()
     }
 
+    /// Get the momentary loudness (400ms window) in LUFS
+    pub fn momentary_loudness(&self) -> Option<f64> {
+        self.ebur128.loudness_momentary().ok()
+    }
+
+    /// Get the short-term loudness (3s window) in LUFS
+    pub fn short_term_loudness(&self) -> Option<f64> {
+        self.ebur128.loudness_shortterm().ok()
+    }
+
     /// Get the number of samples processed
     pub fn samples_processed(&self) -> usize {
         self.samples_processed
@@ -301,10 +369,10 @@ impl LoudnessAnalyzer {
     /// Reset the analyzer for reuse
     pub fn reset(&mut self) {
         // Create a new EbuR128 instance (ebur128 doesn't have a reset method)
-        let mode = Mode::I | Mode::LRA | Mode::SAMPLE_PEAK | Mode::TRUE_PEAK;
-        if let Ok(new_analyzer) = EbuR128::new(self.channels, self.sample_rate, mode) {
+        if let Ok(new_analyzer) = EbuR128::new(self.channels, self.sample_rate, self.mode) {
             self.ebur128 = new_analyzer;
             self.samples_processed = 0;
+            self.dr_meter = Self::dr_meter(self.sample_rate, self.channels, self.mode);
         }
     }
 }
@@ -381,6 +449,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_live_momentary_loudness() {
+        let mut analyzer = LoudnessAnalyzer::live(48000, 2).unwrap();
+
+        // 1 second of -20 dBFS 1 kHz sine
+        let samples: Vec<f32> = (0..48000)
+            .flat_map(|i| {
+                let s = 0.1 * (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / 48000.0).sin();
+                [s, s]
+            })
+            .collect();
+        analyzer.add_frames(&samples).unwrap();
+
+        // -23 LUFS per channel, summed over both channels
+        let momentary = analyzer.momentary_loudness().unwrap();
+        assert!(
+            (momentary + 20.0).abs() < 0.5,
+            "Expected momentary loudness around -20 LUFS, got {:.1}",
+            momentary
+        );
+        assert!(analyzer.short_term_loudness().is_some());
+    }
+
     #[test]
     fn test_no_samples_error() {
         let analyzer = LoudnessAnalyzer::new(44100, 2).unwrap();
@@ -405,6 +496,7 @@ mod tests {
             duration_seconds: 180.0,
             sample_rate: 44100,
             channels: 2,
+            dynamic_range_db: Some(7.0),
         };
 
         // +2 dB gain would cause clipping (peak at -1 + 2 = +1 dBTP)
@@ -415,5 +507,29 @@ mod tests {
 
         // Max safe gain is 1 dB
         assert!((info.max_safe_gain() - 1.0).abs() < 0.001);
+
+        // PLR = -1 dBTP - (-14 LUFS)
+        assert!((info.peak_to_loudness_ratio() - 13.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_dynamic_range_measured() {
+        // 6 seconds of -20 dBFS sine: DR0
+        let samples: Vec<f32> = (0..44100 * 6)
+            .flat_map(|i| {
+                let s = 0.1 * (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / 44100.0).sin();
+                [s, s]
+            })
+            .collect();
+
+        let mut analyzer = LoudnessAnalyzer::new(44100, 2).unwrap();
+        analyzer.add_frames(&samples).unwrap();
+        let info = analyzer.finalize().unwrap();
+        let dr = info.dynamic_range_db.unwrap();
+        assert!(dr.abs() < 0.1, "Expected DR0, got {:.2}", dr);
+
+        // Not measured while live metering
+        let live = LoudnessAnalyzer::live(44100, 2).unwrap();
+        assert!(live.dr_meter.is_none());
     }
 }
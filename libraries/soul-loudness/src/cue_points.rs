@@ -0,0 +1,269 @@
+//! Cue point detection for smart crossfades
+//!
+//! Finds where a track's audio really starts and ends (skipping leading and
+//! trailing silence) and where a natural fade-out begins, so crossfades can
+//! overlap the fade instead of a silent tail. Runs in the same decode pass
+//! as loudness analysis.
+//!
+//! The level is measured in 50 ms windows. Cue-in and cue-out are the first
+//! and last windows above the silence threshold. A fade-out is the stretch
+//! before cue-out where the level (smoothed over one second) falls from the
+//! track's median level to well below it.
+
+/// Level window length in milliseconds
+const WINDOW_MS: u32 = 50;
+
+/// Windows averaged for the smoothed level (one second)
+const SMOOTHING_WINDOWS: usize = 1000 / WINDOW_MS as usize;
+
+/// Level below which audio counts as silence (dBFS RMS)
+pub const SILENCE_THRESHOLD_DB: f64 = -60.0;
+
+/// A fade-out starts where the level last sat within this of the track's median
+const FADE_START_DROP_DB: f64 = 6.0;
+
+/// The last second before cue-out must be at least this far below the median
+const FADE_END_DROP_DB: f64 = 20.0;
+
+/// Shortest natural fade-out (shorter is a hard ending)
+const MIN_FADE_SECS: f64 = 1.0;
+
+/// Longest natural fade-out (longer is a quiet outro, not a fade)
+const MAX_FADE_SECS: f64 = 20.0;
+
+/// Analysed transition points of a track, in seconds from its start
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CuePoints {
+    /// Where audio starts after leading silence
+    pub cue_in_secs: f64,
+    /// Where audio ends before trailing silence
+    pub cue_out_secs: f64,
+    /// Where the natural fade-out starts (None = the track doesn't fade out)
+    pub fade_out_secs: Option<f64>,
+}
+
+/// Detects cue-in, cue-out and fade-out points
+///
+/// # Example
+///
+/// ```ignore
+/// use soul_loudness::CueDetector;
+///
+/// let mut detector = CueDetector::new(44100, 2);
+/// detector.add_frames(&audio_samples);
+///
+/// if let Some(cues) = detector.finalize() {
+///     println!("Audio from {:.2}s to {:.2}s", cues.cue_in_secs, cues.cue_out_secs);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CueDetector {
+    /// Sample rate
+    sample_rate: u32,
+    /// Number of channels
+    channels: usize,
+    /// Frames per level window
+    window_frames: usize,
+    /// Samples in the current window
+    samples_in_window: usize,
+    /// Sum of squares of the current window
+    sum_sq: f64,
+    /// Mean square of each finished window
+    windows: Vec<f64>,
+    /// Total frames added
+    frames: usize,
+}
+
+impl CueDetector {
+    /// Create a detector for interleaved audio
+    pub fn new(sample_rate: u32, channels: u32) -> Self {
+        Self {
+            sample_rate,
+            channels: channels.max(1) as usize,
+            window_frames: (sample_rate * WINDOW_MS / 1000).max(1) as usize,
+            samples_in_window: 0,
+            sum_sq: 0.0,
+            windows: Vec::new(),
+            frames: 0,
+        }
+    }
+
+    /// Add interleaved f32 samples (-1.0 to 1.0)
+    pub fn add_frames(&mut self, samples: &[f32]) {
+        let window_samples = self.window_frames * self.channels;
+        for &sample in samples {
+            self.sum_sq += (sample as f64) * (sample as f64);
+            self.samples_in_window += 1;
+            if self.samples_in_window == window_samples {
+                self.windows.push(self.sum_sq / window_samples as f64);
+                self.sum_sq = 0.0;
+                self.samples_in_window = 0;
+            }
+        }
+        self.frames += samples.len() / self.channels;
+    }
+
+    /// Finish detection
+    ///
+    /// Returns `None` if no audio was added or it is silent throughout.
+    pub fn finalize(mut self) -> Option<CuePoints> {
+        if self.samples_in_window > 0 {
+            self.windows
+                .push(self.sum_sq / self.samples_in_window as f64);
+        }
+
+        let window_secs = self.window_frames as f64 / self.sample_rate as f64;
+        let duration_secs = self.frames as f64 / self.sample_rate as f64;
+        let levels: Vec<f64> = self.windows.iter().map(|&ms| power_db(ms)).collect();
+
+        let first = levels.iter().position(|&l| l > SILENCE_THRESHOLD_DB)?;
+        let last = levels.iter().rposition(|&l| l > SILENCE_THRESHOLD_DB)?;
+        let cue_in_secs = first as f64 * window_secs;
+        let cue_out_secs = ((last + 1) as f64 * window_secs).min(duration_secs);
+
+        Some(CuePoints {
+            cue_in_secs,
+            cue_out_secs,
+            fade_out_secs: self.fade_out(first, last, window_secs, cue_out_secs),
+        })
+    }
+
+    /// Start of the natural fade-out ending at window `last`, if there is one
+    fn fade_out(&self, first: usize, last: usize, window_secs: f64, cue_out: f64) -> Option<f64> {
+        // Level smoothed over a second centred on each window
+        let mut prefix = Vec::with_capacity(self.windows.len() + 1);
+        prefix.push(0.0);
+        for &ms in &self.windows {
+            prefix.push(prefix.last().copied().unwrap_or(0.0) + ms);
+        }
+        let half = SMOOTHING_WINDOWS / 2;
+        let smoothed = |i: usize| {
+            let start = i.saturating_sub(half).max(first);
+            let end = (i + half).min(last) + 1;
+            power_db((prefix[end] - prefix[start]) / (end - start) as f64)
+        };
+
+        let mut body: Vec<f64> = (first..=last).map(smoothed).collect();
+        body.sort_by(f64::total_cmp);
+        let median = body[body.len() / 2];
+
+        // The track has to end well below its body, not stop while loud
+        let tail = (last + 1).saturating_sub(SMOOTHING_WINDOWS).max(first);
+        let tail_level = power_db((prefix[last + 1] - prefix[tail]) / (last + 1 - tail) as f64);
+        if tail_level > median - FADE_END_DROP_DB {
+            return None;
+        }
+
+        let start = (first..=last)
+            .rev()
+            .find(|&i| smoothed(i) >= median - FADE_START_DROP_DB)?;
+        let fade_out = (start + 1) as f64 * window_secs;
+        let length = cue_out - fade_out;
+        (MIN_FADE_SECS..=MAX_FADE_SECS)
+            .contains(&length)
+            .then_some(fade_out)
+    }
+}
+
+/// Mean square to dB, with silence at -inf
+fn power_db(mean_square: f64) -> f64 {
+    if mean_square > 0.0 {
+        10.0 * mean_square.log10()
+    } else {
+        f64::NEG_INFINITY
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 44100;
+
+    /// Stereo 440 Hz sine, with the amplitude given per frame
+    fn sine(seconds: f64, amplitude: impl Fn(f64) -> f32) -> Vec<f32> {
+        let frames = (seconds * SAMPLE_RATE as f64) as usize;
+        (0..frames)
+            .flat_map(|i| {
+                let t = i as f64 / SAMPLE_RATE as f64;
+                let s = amplitude(t) * (2.0 * std::f64::consts::PI * 440.0 * t).sin() as f32;
+                [s, s]
+            })
+            .collect()
+    }
+
+    fn silence(seconds: f64) -> Vec<f32> {
+        vec![0.0; (seconds * SAMPLE_RATE as f64) as usize * 2]
+    }
+
+    fn detect(samples: &[f32]) -> Option<CuePoints> {
+        let mut detector = CueDetector::new(SAMPLE_RATE, 2);
+        for chunk in samples.chunks(4096) {
+            detector.add_frames(chunk);
+        }
+        detector.finalize()
+    }
+
+    #[test]
+    fn test_leading_and_trailing_silence() {
+        let mut samples = silence(1.5);
+        samples.extend(sine(20.0, |_| 0.5));
+        samples.extend(silence(4.0));
+
+        let cues = detect(&samples).unwrap();
+        assert!((cues.cue_in_secs - 1.5).abs() <= 0.05, "{:?}", cues);
+        assert!((cues.cue_out_secs - 21.5).abs() <= 0.05, "{:?}", cues);
+        // Hard ending
+        assert_eq!(cues.fade_out_secs, None);
+    }
+
+    #[test]
+    fn test_no_silence() {
+        let cues = detect(&sine(10.0, |_| 0.5)).unwrap();
+        assert_eq!(cues.cue_in_secs, 0.0);
+        assert!((cues.cue_out_secs - 10.0).abs() < 1e-9);
+        assert_eq!(cues.fade_out_secs, None);
+    }
+
+    #[test]
+    fn test_natural_fade_out() {
+        // 30s body, then an 8s fade of 60 dB, then silence
+        let mut samples = sine(30.0, |_| 0.5);
+        samples.extend(sine(8.0, |t| 0.5 * 10f32.powf(-3.0 * t as f32 / 8.0)));
+        samples.extend(silence(3.0));
+
+        let cues = detect(&samples).unwrap();
+        let fade_out = cues.fade_out_secs.expect("fade detected");
+        // 6 dB down is 0.8s into the fade
+        assert!(
+            (30.0..=31.5).contains(&fade_out),
+            "fade out at {:.2}s",
+            fade_out
+        );
+        assert!(
+            (36.0..=38.05).contains(&cues.cue_out_secs),
+            "cue out at {:.2}s",
+            cues.cue_out_secs
+        );
+    }
+
+    #[test]
+    fn test_quiet_outro_is_not_a_fade() {
+        // A long soft coda at -30 dB, then a hard stop
+        let mut samples = sine(60.0, |_| 0.5);
+        samples.extend(sine(40.0, |_| 0.016));
+        samples.extend(silence(1.0));
+
+        let cues = detect(&samples).unwrap();
+        assert!((cues.cue_out_secs - 100.0).abs() <= 0.05);
+        assert_eq!(cues.fade_out_secs, None);
+    }
+
+    #[test]
+    fn test_silence_has_no_cue_points() {
+        assert_eq!(detect(&silence(5.0)), None);
+        assert_eq!(detect(&[]), None);
+        // Noise floor below the threshold
+        assert_eq!(detect(&sine(5.0, |_| 0.0005)), None);
+    }
+}
@@ -0,0 +1,309 @@
+//! TT / Pleasurize Music Foundation dynamic range (DR) meter
+//!
+//! The "DR" number shown by the TT DR Offline Meter and the foobar2000 DR
+//! meter. It compares the peaks of a track to the level of its loudest
+//! passages, so heavily limited ("brickwalled") masters score low:
+//! DR14 is a dynamic master, DR5 and below is squashed.
+//!
+//! Per channel:
+//! - The audio is split into 3 second blocks
+//! - Each block gets an RMS (scaled by √2 so a sine measures its peak) and a peak
+//! - RMS is the RMS of the loudest 20% of blocks
+//! - Peak is the second highest block peak, so a single stray click doesn't count
+//! - DR = 20·log10(peak / RMS)
+//!
+//! The track DR is the mean over channels and the album DR the mean over
+//! tracks, both rounded to whole dB for display.
+
+/// Block length in seconds
+const BLOCK_SECONDS: u32 = 3;
+
+/// Fraction of the loudest blocks used for the RMS
+const LOUDEST_FRACTION: f64 = 0.2;
+
+/// Dynamic range of a track
+#[derive(Debug, Clone, PartialEq)]
+pub struct DynamicRange {
+    /// Track DR in dB (mean over channels, unrounded)
+    pub dr_db: f64,
+    /// DR of each channel in dB (None for a silent channel)
+    pub channel_dr_db: Vec<Option<f64>>,
+}
+
+impl DynamicRange {
+    /// The DR score as displayed by the TT meter ("DR12")
+    pub fn score(&self) -> i32 {
+        self.dr_db.round() as i32
+    }
+}
+
+/// Album DR in dB: the mean of the track DRs
+///
+/// Returns `None` for an empty album. Round the result for the "DR12" score.
+pub fn album_dynamic_range(track_dr_db: &[f64]) -> Option<f64> {
+    if track_dr_db.is_empty() {
+        return None;
+    }
+    Some(track_dr_db.iter().sum::<f64>() / track_dr_db.len() as f64)
+}
+
+/// Running block statistics for one channel
+#[derive(Debug, Clone, Default)]
+struct ChannelBlocks {
+    /// Sum of squares of the current block
+    sum_sq: f64,
+    /// Peak of the current block
+    peak: f64,
+    /// (RMS, peak) of each finished block
+    blocks: Vec<(f64, f64)>,
+}
+
+impl ChannelBlocks {
+    fn finish_block(&mut self, frames: usize) {
+        let rms = (2.0 * self.sum_sq / frames as f64).sqrt();
+        self.blocks.push((rms, self.peak));
+        self.sum_sq = 0.0;
+        self.peak = 0.0;
+    }
+
+    /// DR of this channel in dB, None if silent
+    fn dynamic_range(&self) -> Option<f64> {
+        if self.blocks.is_empty() {
+            return None;
+        }
+
+        let mut rms: Vec<f64> = self.blocks.iter().map(|&(rms, _)| rms).collect();
+        rms.sort_by(|a, b| b.total_cmp(a));
+        let loudest = ((rms.len() as f64 * LOUDEST_FRACTION) as usize).max(1);
+        let rms_loudest =
+            (rms[..loudest].iter().map(|r| r * r).sum::<f64>() / loudest as f64).sqrt();
+
+        let mut peaks: Vec<f64> = self.blocks.iter().map(|&(_, peak)| peak).collect();
+        peaks.sort_by(|a, b| b.total_cmp(a));
+        let peak = peaks.get(1).copied().unwrap_or(peaks[0]);
+
+        if rms_loudest <= 0.0 || peak <= 0.0 {
+            return None;
+        }
+        Some(20.0 * (peak / rms_loudest).log10())
+    }
+}
+
+/// Measures the TT dynamic range of a track
+///
+/// # Example
+///
+/// ```ignore
+/// use soul_loudness::DynamicRangeMeter;
+///
+/// let mut meter = DynamicRangeMeter::new(44100, 2);
+/// meter.add_frames(&audio_samples);
+///
+/// if let Some(dr) = meter.finalize() {
+///     println!("DR{}", dr.score());
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DynamicRangeMeter {
+    /// Frames per block
+    block_frames: usize,
+    /// Frames in the current block
+    frames_in_block: usize,
+    /// Per-channel statistics
+    channels: Vec<ChannelBlocks>,
+}
+
+impl DynamicRangeMeter {
+    /// Create a meter for interleaved audio
+    pub fn new(sample_rate: u32, channels: u32) -> Self {
+        Self {
+            block_frames: (sample_rate * BLOCK_SECONDS) as usize,
+            frames_in_block: 0,
+            channels: vec![ChannelBlocks::default(); channels as usize],
+        }
+    }
+
+    /// Add interleaved f32 samples (-1.0 to 1.0)
+    ///
+    /// The length must be a whole number of frames.
+    pub fn add_frames(&mut self, samples: &[f32]) {
+        self.add_samples(samples.iter().map(|&s| s as f64));
+    }
+
+    /// Add interleaved i16 samples
+    pub fn add_frames_i16(&mut self, samples: &[i16]) {
+        self.add_samples(samples.iter().map(|&s| s as f64 / 32768.0));
+    }
+
+    /// Add interleaved i32 samples
+    pub fn add_frames_i32(&mut self, samples: &[i32]) {
+        self.add_samples(samples.iter().map(|&s| s as f64 / 2_147_483_648.0));
+    }
+
+    fn add_samples(&mut self, samples: impl Iterator<Item = f64>) {
+        let channel_count = self.channels.len();
+        for (i, sample) in samples.enumerate() {
+            let channel = &mut self.channels[i % channel_count];
+            channel.sum_sq += sample * sample;
+            channel.peak = channel.peak.max(sample.abs());
+
+            if i % channel_count == channel_count - 1 {
+                self.frames_in_block += 1;
+                if self.frames_in_block == self.block_frames {
+                    for channel in &mut self.channels {
+                        channel.finish_block(self.block_frames);
+                    }
+                    self.frames_in_block = 0;
+                }
+            }
+        }
+    }
+
+    /// Finish the measurement
+    ///
+    /// A trailing partial block counts as a block of its own. Returns `None`
+    /// if no audio was added or every channel is silent.
+    pub fn finalize(mut self) -> Option<DynamicRange> {
+        if self.frames_in_block > 0 {
+            let frames = self.frames_in_block;
+            for channel in &mut self.channels {
+                channel.finish_block(frames);
+            }
+        }
+
+        let channel_dr_db: Vec<Option<f64>> = self
+            .channels
+            .iter()
+            .map(ChannelBlocks::dynamic_range)
+            .collect();
+        let measured: Vec<f64> = channel_dr_db.iter().flatten().copied().collect();
+        if measured.is_empty() {
+            return None;
+        }
+
+        Some(DynamicRange {
+            dr_db: measured.iter().sum::<f64>() / measured.len() as f64,
+            channel_dr_db,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 44100;
+
+    /// Stereo sine at the given amplitude
+    fn sine(amplitude: f32, seconds: u32) -> Vec<f32> {
+        (0..SAMPLE_RATE * seconds)
+            .flat_map(|i| {
+                let s = amplitude
+                    * (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / SAMPLE_RATE as f32).sin();
+                [s, s]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sine_has_no_dynamic_range() {
+        let mut meter = DynamicRangeMeter::new(SAMPLE_RATE, 2);
+        meter.add_frames(&sine(0.5, 30));
+        let dr = meter.finalize().unwrap();
+
+        assert!(dr.dr_db.abs() < 0.1, "sine DR {:.2}", dr.dr_db);
+        assert_eq!(dr.score(), 0);
+    }
+
+    #[test]
+    fn test_peaks_above_the_loudest_passages() {
+        // -12 dB sine with a full-scale click in every block
+        let mut samples = sine(0.25, 30);
+        for block in 0..10 {
+            let frame = (block * 3 * SAMPLE_RATE + SAMPLE_RATE) as usize;
+            samples[frame * 2] = 1.0;
+            samples[frame * 2 + 1] = 1.0;
+        }
+
+        let mut meter = DynamicRangeMeter::new(SAMPLE_RATE, 2);
+        meter.add_frames(&samples);
+        let dr = meter.finalize().unwrap();
+
+        assert_eq!(dr.score(), 12, "DR {:.2}", dr.dr_db);
+    }
+
+    #[test]
+    fn test_single_click_is_ignored() {
+        // Only the second highest block peak counts
+        let mut samples = sine(0.25, 30);
+        samples[SAMPLE_RATE as usize * 2] = 1.0;
+        samples[SAMPLE_RATE as usize * 2 + 1] = 1.0;
+
+        let mut meter = DynamicRangeMeter::new(SAMPLE_RATE, 2);
+        meter.add_frames(&samples);
+        assert_eq!(meter.finalize().unwrap().score(), 0);
+    }
+
+    #[test]
+    fn test_quiet_passages_do_not_count() {
+        // A quiet intro doesn't raise the DR: only the loudest 20% of blocks count
+        let mut samples = sine(0.01, 24);
+        samples.extend(sine(0.5, 6));
+
+        let mut meter = DynamicRangeMeter::new(SAMPLE_RATE, 2);
+        meter.add_frames(&samples);
+        assert_eq!(meter.finalize().unwrap().score(), 0);
+    }
+
+    #[test]
+    fn test_integer_formats_match_f32() {
+        let samples = sine(0.25, 9);
+
+        let mut f32_meter = DynamicRangeMeter::new(SAMPLE_RATE, 2);
+        f32_meter.add_frames(&samples);
+        let mut i16_meter = DynamicRangeMeter::new(SAMPLE_RATE, 2);
+        i16_meter.add_frames_i16(
+            &samples
+                .iter()
+                .map(|&s| (s * 32767.0) as i16)
+                .collect::<Vec<_>>(),
+        );
+
+        let a = f32_meter.finalize().unwrap().dr_db;
+        let b = i16_meter.finalize().unwrap().dr_db;
+        assert!((a - b).abs() < 0.01, "f32 {:.3} vs i16 {:.3}", a, b);
+    }
+
+    #[test]
+    fn test_silent_channel_is_skipped() {
+        let samples: Vec<f32> = sine(0.5, 6)
+            .chunks_exact(2)
+            .flat_map(|frame| [frame[0], 0.0])
+            .collect();
+
+        let mut meter = DynamicRangeMeter::new(SAMPLE_RATE, 2);
+        meter.add_frames(&samples);
+        let dr = meter.finalize().unwrap();
+
+        assert!(dr.channel_dr_db[0].is_some());
+        assert_eq!(dr.channel_dr_db[1], None);
+        assert_eq!(dr.score(), 0);
+    }
+
+    #[test]
+    fn test_silence_and_empty() {
+        assert!(DynamicRangeMeter::new(SAMPLE_RATE, 2).finalize().is_none());
+
+        let mut meter = DynamicRangeMeter::new(SAMPLE_RATE, 2);
+        meter.add_frames(&vec![0.0; SAMPLE_RATE as usize * 2]);
+        assert!(meter.finalize().is_none());
+    }
+
+    #[test]
+    fn test_album_dynamic_range() {
+        assert_eq!(album_dynamic_range(&[]), None);
+        let album = album_dynamic_range(&[6.4, 8.1, 7.3]).unwrap();
+        assert!((album - 7.266).abs() < 0.01);
+        assert_eq!(album.round(), 7.0);
+    }
+}
@@ -3,6 +3,8 @@
 //! This crate provides:
 //! - EBU R128 loudness measurement (integrated LUFS, loudness range, true peak)
 //! - ReplayGain 2.0 calculation (track and album gain)
+//! - TT dynamic range (DR) and peak-to-loudness ratio (PLR)
+//! - Cue-in / cue-out and fade-out detection for smart crossfades
 //! - Tag reading/writing for ReplayGain metadata
 //! - True peak limiting for playback
 //!
@@ -46,6 +48,8 @@
 #![deny(unsafe_code)]
 
 mod analyzer;
+mod cue_points;
+mod dynamic_range;
 mod error;
 pub mod headroom;
 mod limiter;
@@ -54,6 +58,8 @@ mod replaygain;
 mod tags;
 
 pub use analyzer::{LoudnessAnalyzer, LoudnessInfo};
+pub use cue_points::{CueDetector, CuePoints, SILENCE_THRESHOLD_DB};
+pub use dynamic_range::{album_dynamic_range, DynamicRange, DynamicRangeMeter};
 pub use error::{LoudnessError, Result};
 pub use limiter::{LookaheadPreset, TruePeakLimiter};
 pub use normalizer::{LoudnessNormalizer, NormalizationMode};
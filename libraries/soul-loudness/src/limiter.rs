@@ -140,6 +140,26 @@ impl TruePeakLimiter {
         }
     }
 
+    /// Set the number of interleaved channels
+    ///
+    /// Note: This will reset the limiter state and reallocate buffers
+    pub fn set_channels(&mut self, channels: usize) {
+        if self.channels == channels {
+            return;
+        }
+
+        self.channels = channels;
+        self.lookahead_buffers = vec![vec![0.0; self.lookahead_size]; channels];
+        self.write_pos = 0;
+        self.gain_reduction = 1.0;
+        self.peak_hold = 0;
+    }
+
+    /// Get the number of interleaved channels
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
     /// Get the current lookahead preset
     pub fn lookahead_preset(&self) -> LookaheadPreset {
         self.lookahead_preset
@@ -309,6 +329,19 @@ mod tests {
         assert!((limiter.threshold - 1.0).abs() < 0.001);
     }
 
+    #[test]
+    fn test_limiter_set_channels() {
+        let mut limiter = TruePeakLimiter::new(48000, 2);
+        limiter.set_channels(6);
+        assert_eq!(limiter.channels(), 6);
+
+        // Loud 5.1 frames are limited across all channels
+        let mut samples = vec![1.5; 6 * 4800];
+        limiter.process(&mut samples);
+        let tail = &samples[samples.len() - 6..];
+        assert!(tail.iter().all(|&s| s <= 1.0 + 1e-3));
+    }
+
     #[test]
     fn test_limiter_passthrough() {
         let mut limiter = TruePeakLimiter::new(44100, 2);
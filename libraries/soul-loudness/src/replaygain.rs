@@ -202,6 +202,7 @@ mod tests {
             duration_seconds: duration,
             sample_rate: 44100,
             channels: 2,
+            dynamic_range_db: None,
         }
     }
 
@@ -5,9 +5,10 @@
 //! - Vorbis Comments (FLAC, OGG): REPLAYGAIN_* fields
 //! - APE tags: REPLAYGAIN_* fields
 //! - MP4/AAC: iTunes-style ----:com.apple.iTunes:* atoms
+//! - Opus: R128_TRACK_GAIN / R128_ALBUM_GAIN comments (read only)
 
 use crate::error::{LoudnessError, Result};
-use crate::{AlbumGain, TrackGain};
+use crate::{AlbumGain, TrackGain, EBU_R128_BROADCAST_LUFS, REPLAYGAIN_REFERENCE_LUFS};
 use lofty::{Probe, TagExt, TaggedFileExt};
 use std::path::Path;
 use tracing::debug;
@@ -74,6 +75,23 @@ fn parse_peak(s: &str) -> Option<f64> {
     s.trim().parse().ok()
 }
 
+/// Opus comment holding the track gain (RFC 7845)
+const R128_TRACK_GAIN: &str = "R128_TRACK_GAIN";
+
+/// Opus comment holding the album gain (RFC 7845)
+const R128_ALBUM_GAIN: &str = "R128_ALBUM_GAIN";
+
+/// Parse an Opus R128 gain as a ReplayGain gain (e.g., "-1280" -> 0.0)
+///
+/// R128 gains are Q7.8 fixed-point dB that bring the track to -23 LUFS,
+/// on top of the header output gain the decoder already applies. They're
+/// shifted to the -18 LUFS ReplayGain reference so they can be used like
+/// any other ReplayGain tag.
+fn parse_r128_gain(s: &str) -> Option<f64> {
+    let gain: i16 = s.trim().parse().ok()?;
+    Some(f64::from(gain) / 256.0 + (REPLAYGAIN_REFERENCE_LUFS - EBU_R128_BROADCAST_LUFS))
+}
+
 /// Read ReplayGain tags from an audio file
 ///
 /// # Arguments
@@ -154,6 +172,28 @@ pub fn read_replaygain_tags<P: AsRef<Path>>(path: P) -> Result<ReplayGainTags> {
         }
     }
 
+    // Opus files carry R128 gains instead of ReplayGain tags
+    for tag in tagged_file.tags() {
+        let r128_gain = |key: &str| {
+            tag.get_string(&lofty::ItemKey::Unknown(key.to_string()))
+                .and_then(parse_r128_gain)
+        };
+
+        if tags.track_gain.is_none() {
+            tags.track_gain = r128_gain(R128_TRACK_GAIN);
+            if let Some(gain) = tags.track_gain {
+                debug!("Found R128 track gain: {} dB (ReplayGain reference)", gain);
+            }
+        }
+
+        if tags.album_gain.is_none() {
+            tags.album_gain = r128_gain(R128_ALBUM_GAIN);
+            if let Some(gain) = tags.album_gain {
+                debug!("Found R128 album gain: {} dB (ReplayGain reference)", gain);
+            }
+        }
+    }
+
     Ok(tags)
 }
 
@@ -278,6 +318,17 @@ mod tests {
         assert!(parse_gain("invalid").is_none());
     }
 
+    #[test]
+    fn test_parse_r128_gain_values() {
+        // 0 brings the track to -23 LUFS, 5 dB below the ReplayGain reference
+        assert_eq!(parse_r128_gain("0"), Some(5.0));
+        assert_eq!(parse_r128_gain("-1280"), Some(0.0));
+        assert_eq!(parse_r128_gain(" -2432 "), Some(-4.5));
+        assert_eq!(parse_r128_gain("256"), Some(6.0));
+        assert!(parse_r128_gain("-5.0 dB").is_none());
+        assert!(parse_r128_gain("40000").is_none());
+    }
+
     #[test]
     fn test_replaygain_tags_from_track_gain() {
         let track_gain = TrackGain {
@@ -1042,6 +1042,7 @@ fn test_replaygain_2_reference_level() {
         duration_seconds: 180.0,
         sample_rate: 44100,
         channels: 2,
+        dynamic_range_db: None,
     };
 
     let gain = calc.track_gain(&info);
@@ -7,8 +7,8 @@
 
 use proptest::prelude::*;
 use soul_loudness::{
-    LoudnessAnalyzer, LoudnessNormalizer, NormalizationMode, ReplayGainCalculator, TruePeakLimiter,
-    REPLAYGAIN_REFERENCE_LUFS,
+    album_dynamic_range, LoudnessAnalyzer, LoudnessNormalizer, NormalizationMode,
+    ReplayGainCalculator, TruePeakLimiter, REPLAYGAIN_REFERENCE_LUFS,
 };
 
 // ========== Helper Functions ==========
@@ -163,6 +163,7 @@ proptest! {
             duration_seconds: 180.0,
             sample_rate: 44100,
             channels: 2,
+            dynamic_range_db: None,
         };
 
         let calc = ReplayGainCalculator::new();
@@ -407,6 +408,7 @@ fn test_loudness_info_display() {
         duration_seconds: 180.0,
         sample_rate: 44100,
         channels: 2,
+        dynamic_range_db: None,
     };
 
     let display = format!("{}", info);
@@ -446,3 +448,45 @@ fn test_incremental_analysis() {
         info2.true_peak_dbfs
     );
 }
+
+#[test]
+fn test_dynamic_range_finds_brickwalled_master() {
+    // Dynamic master: -20 dBFS body with a full-scale transient every second
+    let mut dynamic = generate_sine(44100, 2, 1000.0, 0.1, 30.0);
+    for second in 0..30 {
+        let frame = second * 44100 + 100;
+        dynamic[frame * 2] = 1.0;
+        dynamic[frame * 2 + 1] = 1.0;
+    }
+
+    // Brickwalled master: the same sine driven 12 dB into a clipper
+    let brickwalled: Vec<f32> = generate_sine(44100, 2, 1000.0, 4.0, 30.0)
+        .into_iter()
+        .map(|s| s.clamp(-1.0, 1.0))
+        .collect();
+
+    let analyze = |samples: &[f32]| {
+        let mut analyzer = LoudnessAnalyzer::new(44100, 2).unwrap();
+        for chunk in samples.chunks(44100) {
+            analyzer.add_frames(chunk).unwrap();
+        }
+        analyzer.finalize().unwrap()
+    };
+    let dynamic = analyze(&dynamic);
+    let brickwalled = analyze(&brickwalled);
+
+    let dynamic_dr = dynamic.dynamic_range_db.unwrap();
+    let brickwalled_dr = brickwalled.dynamic_range_db.unwrap();
+    assert_eq!(dynamic_dr.round(), 20.0, "dynamic DR {:.2}", dynamic_dr);
+    assert!(brickwalled_dr < 0.0, "brickwalled DR {:.2}", brickwalled_dr);
+
+    assert!(
+        dynamic.peak_to_loudness_ratio() > brickwalled.peak_to_loudness_ratio() + 10.0,
+        "PLR {:.1} vs {:.1}",
+        dynamic.peak_to_loudness_ratio(),
+        brickwalled.peak_to_loudness_ratio()
+    );
+
+    let album = album_dynamic_range(&[dynamic_dr, brickwalled_dr]).unwrap();
+    assert!((album - (dynamic_dr + brickwalled_dr) / 2.0).abs() < 1e-9);
+}
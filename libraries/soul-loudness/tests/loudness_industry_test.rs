@@ -830,6 +830,7 @@ fn test_replaygain_calculation() {
             duration_seconds: 180.0,
             sample_rate: 44100,
             channels: 2,
+            dynamic_range_db: None,
         };
 
         let gain = calc.track_gain(&info);
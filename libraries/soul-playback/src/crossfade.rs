@@ -185,6 +185,9 @@ pub struct CrossfadeEngine {
 
     /// Sample rate for calculations
     sample_rate: u32,
+
+    /// Interleaved channel count of mixed buffers
+    channels: usize,
 }
 
 impl CrossfadeEngine {
@@ -201,6 +204,7 @@ impl CrossfadeEngine {
             position_samples: 0,
             duration_samples: 0,
             sample_rate: 44100,
+            channels: 2,
         }
     }
 
@@ -219,6 +223,11 @@ impl CrossfadeEngine {
         self.sample_rate = sample_rate;
     }
 
+    /// Set the interleaved channel count of mixed buffers
+    pub fn set_channels(&mut self, channels: u16) {
+        self.channels = channels.max(1) as usize;
+    }
+
     /// Get current crossfade state
     pub fn state(&self) -> CrossfadeState {
         self.state
@@ -237,6 +246,14 @@ impl CrossfadeEngine {
     /// # Returns
     /// True if crossfade was started, false if skipped (e.g., disabled or on_skip=false)
     pub fn start(&mut self, is_manual_skip: bool) -> bool {
+        self.start_with_duration(is_manual_skip, self.settings.duration_ms)
+    }
+
+    /// Start a crossfade transition lasting `duration_ms` instead of the
+    /// configured duration
+    ///
+    /// Used when the transition is timed from a track's cue points.
+    pub fn start_with_duration(&mut self, is_manual_skip: bool, duration_ms: u32) -> bool {
         if !self.settings.enabled {
             return false;
         }
@@ -245,7 +262,8 @@ impl CrossfadeEngine {
             return false;
         }
 
-        self.duration_samples = self.settings.duration_samples(self.sample_rate) * 2; // * 2 for stereo
+        let duration_frames = (duration_ms as u64 * self.sample_rate as u64 / 1000) as usize;
+        self.duration_samples = duration_frames * self.channels;
         self.position_samples = 0;
         self.state = CrossfadeState::Active;
 
@@ -316,23 +334,22 @@ impl CrossfadeEngine {
             .min(incoming.len())
             .min(self.remaining_samples());
 
-        // Process stereo frames
-        let frames = samples_to_process / 2;
+        // Process interleaved frames
+        let channels = self.channels;
+        let frames = samples_to_process / channels;
         let curve = self.settings.curve;
 
         for frame in 0..frames {
-            let sample_pos = self.position_samples + (frame * 2);
+            let sample_pos = self.position_samples + (frame * channels);
             let progress = (sample_pos as f32) / (self.duration_samples as f32);
 
             let out_gain = curve.calculate_gain(progress, true);
             let in_gain = curve.calculate_gain(progress, false);
 
-            let left_idx = frame * 2;
-            let right_idx = frame * 2 + 1;
-
             // Mix outgoing and incoming
-            output[left_idx] = outgoing[left_idx] * out_gain + incoming[left_idx] * in_gain;
-            output[right_idx] = outgoing[right_idx] * out_gain + incoming[right_idx] * in_gain;
+            for idx in frame * channels..(frame + 1) * channels {
+                output[idx] = outgoing[idx] * out_gain + incoming[idx] * in_gain;
+            }
         }
 
         self.position_samples += samples_to_process;
@@ -539,6 +556,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_crossfade_surround_progress() {
+        let mut engine = CrossfadeEngine::with_settings(CrossfadeSettings::with_duration(1000));
+        engine.set_sample_rate(1000); // 1000 Hz
+        engine.set_channels(6);
+
+        engine.start(false);
+
+        // Half of the fade: 500 frames of 5.1 audio
+        let outgoing = vec![1.0f32; 3000];
+        let incoming = vec![0.0f32; 3000];
+        let mut output = vec![0.0f32; 3000];
+
+        engine.process(&outgoing, &incoming, &mut output);
+
+        assert!((engine.progress() - 0.5).abs() < 0.01);
+        // All channels of a frame share the same gain
+        let last = &output[2994..];
+        assert!(last.iter().all(|&s| (s - last[0]).abs() < 1e-6));
+    }
+
+    #[test]
+    fn test_crossfade_start_with_duration() {
+        let mut engine = CrossfadeEngine::with_settings(CrossfadeSettings::with_duration(1000));
+        engine.set_sample_rate(1000); // 1000 Hz
+
+        // A 250ms transition instead of the configured second
+        assert!(engine.start_with_duration(false, 250));
+        assert_eq!(engine.remaining_samples(), 500);
+
+        let outgoing = vec![1.0f32; 1000];
+        let incoming = vec![0.0f32; 1000];
+        let mut output = vec![0.0f32; 1000];
+        let (processed, completed) = engine.process(&outgoing, &incoming, &mut output);
+        assert_eq!(processed, 500);
+        assert!(completed);
+
+        // Settings still apply
+        let mut disabled = CrossfadeEngine::new();
+        assert!(!disabled.start_with_duration(false, 250));
+    }
+
     #[test]
     fn test_crossfade_cancel() {
         let mut engine = CrossfadeEngine::with_settings(CrossfadeSettings::with_duration(1000));
@@ -11,6 +11,7 @@
 //! - Seek functionality (time and percentage)
 //! - Audio effects integration
 //! - Gapless playback support
+//! - Variable playback speed with pitch preservation (WSOLA time stretching)
 //!
 //! # Architecture
 //!
@@ -46,6 +47,7 @@
 //!     duration: Duration::from_secs(180),
 //!     track_number: Some(1),
 //!     source: TrackSource::Single,
+//!     ..Default::default()
 //! };
 //!
 //! manager.add_to_queue_end(track);
@@ -124,6 +126,7 @@ mod manager;
 mod queue;
 mod shuffle;
 mod source;
+mod time_stretch;
 pub mod types;
 mod volume;
 
@@ -135,9 +138,13 @@ pub mod wasm;
 pub use crossfade::{CrossfadeEngine, CrossfadeSettings, CrossfadeState, FadeCurve};
 pub use error::{PlaybackError, Result};
 pub use events::{CrossfadeProgressTracker, PlaybackEvent, PlaybackStateEvent};
-pub use manager::PlaybackManager;
+pub use manager::{PlaybackManager, TrackDsp};
 pub use source::AudioSource;
-pub use types::{PlaybackConfig, PlaybackState, QueueTrack, RepeatMode, ShuffleMode, TrackSource};
+pub use time_stretch::{StretchedSource, TimeStretcher};
+pub use types::{
+    CuePoints, PlaybackConfig, PlaybackRate, PlaybackState, QueueTrack, RepeatMode, ShuffleMode,
+    TrackRange, TrackSource,
+};
 
 // Volume leveling exports (conditionally compiled)
 #[cfg(feature = "volume-leveling")]
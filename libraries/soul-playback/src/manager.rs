@@ -10,7 +10,10 @@ use crate::{
     queue::Queue,
     shuffle::shuffle_queue,
     source::AudioSource,
-    types::{PlaybackConfig, PlaybackState, QueueTrack, RepeatMode, ShuffleMode},
+    time_stretch::StretchedSource,
+    types::{
+        CuePoints, PlaybackConfig, PlaybackRate, PlaybackState, QueueTrack, RepeatMode, ShuffleMode,
+    },
     volume::Volume,
 };
 
@@ -37,30 +40,32 @@ struct StartFadeEnvelope {
     /// Whether we've detected actual audio content yet
     audio_detected: bool,
 
-    /// Current position in the fade (in stereo samples, starts after audio detected)
-    position_samples: usize,
+    /// Current position in the fade (in frames, starts after audio detected)
+    position_frames: usize,
 
-    /// Total duration of fade (in stereo samples)
-    duration_samples: usize,
+    /// Total duration of fade (in frames)
+    duration_frames: usize,
 
     /// Sample rate for duration calculations
     sample_rate: u32,
 
-    /// DC blocker state (left channel)
-    dc_blocker_prev_input_l: f32,
-    dc_blocker_prev_output_l: f32,
+    /// Interleaved channel count of processed buffers
+    channels: usize,
 
-    /// DC blocker state (right channel)
-    dc_blocker_prev_input_r: f32,
-    dc_blocker_prev_output_r: f32,
+    /// DC blocker state (per channel)
+    dc_blocker_prev_input: [f32; MAX_FADE_CHANNELS],
+    dc_blocker_prev_output: [f32; MAX_FADE_CHANNELS],
 
-    /// Samples processed while waiting for audio (for timeout)
-    wait_samples: usize,
+    /// Frames processed while waiting for audio (for timeout)
+    wait_frames: usize,
 
-    /// Maximum wait time before forcing fade start (in samples)
-    max_wait_samples: usize,
+    /// Maximum wait time before forcing fade start (in frames)
+    max_wait_frames: usize,
 }
 
+/// Maximum channel count handled by the start fade envelope (7.1)
+const MAX_FADE_CHANNELS: usize = 8;
+
 /// Default fade-in duration in milliseconds
 const START_FADE_DURATION_MS: u32 = 30;
 
@@ -81,23 +86,21 @@ impl StartFadeEnvelope {
         Self {
             active: false,
             audio_detected: false,
-            position_samples: 0,
-            duration_samples: Self::calculate_duration_samples(sample_rate, START_FADE_DURATION_MS),
+            position_frames: 0,
+            duration_frames: Self::calculate_duration_frames(sample_rate, START_FADE_DURATION_MS),
             sample_rate,
-            dc_blocker_prev_input_l: 0.0,
-            dc_blocker_prev_output_l: 0.0,
-            dc_blocker_prev_input_r: 0.0,
-            dc_blocker_prev_output_r: 0.0,
-            wait_samples: 0,
-            max_wait_samples: Self::calculate_duration_samples(sample_rate, MAX_WAIT_MS),
+            channels: 2,
+            dc_blocker_prev_input: [0.0; MAX_FADE_CHANNELS],
+            dc_blocker_prev_output: [0.0; MAX_FADE_CHANNELS],
+            wait_frames: 0,
+            max_wait_frames: Self::calculate_duration_frames(sample_rate, MAX_WAIT_MS),
         }
     }
 
-    /// Calculate duration in stereo samples from milliseconds
+    /// Calculate duration in frames from milliseconds
     #[inline]
-    fn calculate_duration_samples(sample_rate: u32, duration_ms: u32) -> usize {
-        // duration_samples = sample_rate * duration_ms / 1000 * 2 (stereo)
-        ((sample_rate as u64 * duration_ms as u64 * 2) / 1000) as usize
+    fn calculate_duration_frames(sample_rate: u32, duration_ms: u32) -> usize {
+        ((sample_rate as u64 * duration_ms as u64) / 1000) as usize
     }
 
     /// Start a new fade-in
@@ -105,13 +108,11 @@ impl StartFadeEnvelope {
     fn start(&mut self) {
         self.active = true;
         self.audio_detected = false;
-        self.position_samples = 0;
-        self.wait_samples = 0;
+        self.position_frames = 0;
+        self.wait_frames = 0;
         // Reset DC blocker state for clean start
-        self.dc_blocker_prev_input_l = 0.0;
-        self.dc_blocker_prev_output_l = 0.0;
-        self.dc_blocker_prev_input_r = 0.0;
-        self.dc_blocker_prev_output_r = 0.0;
+        self.dc_blocker_prev_input = [0.0; MAX_FADE_CHANNELS];
+        self.dc_blocker_prev_output = [0.0; MAX_FADE_CHANNELS];
     }
 
     /// Reset the envelope (stop any active fade)
@@ -119,20 +120,25 @@ impl StartFadeEnvelope {
     fn reset(&mut self) {
         self.active = false;
         self.audio_detected = false;
-        self.position_samples = 0;
-        self.wait_samples = 0;
+        self.position_frames = 0;
+        self.wait_frames = 0;
     }
 
     /// Update sample rate and recalculate duration
     fn set_sample_rate(&mut self, sample_rate: u32) {
         if self.sample_rate != sample_rate {
             self.sample_rate = sample_rate;
-            self.duration_samples =
-                Self::calculate_duration_samples(sample_rate, START_FADE_DURATION_MS);
-            self.max_wait_samples = Self::calculate_duration_samples(sample_rate, MAX_WAIT_MS);
+            self.duration_frames =
+                Self::calculate_duration_frames(sample_rate, START_FADE_DURATION_MS);
+            self.max_wait_frames = Self::calculate_duration_frames(sample_rate, MAX_WAIT_MS);
         }
     }
 
+    /// Update the interleaved channel count of processed buffers
+    fn set_channels(&mut self, channels: u16) {
+        self.channels = (channels as usize).clamp(1, MAX_FADE_CHANNELS);
+    }
+
     /// Check if fade is currently active
     #[inline]
     fn is_active(&self) -> bool {
@@ -142,26 +148,24 @@ impl StartFadeEnvelope {
     /// Apply DC blocker to remove DC offset (first-order highpass)
     /// Formula: y[n] = gain * (x[n] - x[n-1]) + beta * y[n-1]
     #[inline]
-    fn dc_block_sample(&mut self, input_l: f32, input_r: f32) -> (f32, f32) {
+    fn dc_block_frame(&mut self, frame: &mut [f32]) {
         const GAIN: f32 = (1.0 + DC_BLOCKER_COEFF) / 2.0;
 
-        let output_l = GAIN * (input_l - self.dc_blocker_prev_input_l)
-            + DC_BLOCKER_COEFF * self.dc_blocker_prev_output_l;
-        let output_r = GAIN * (input_r - self.dc_blocker_prev_input_r)
-            + DC_BLOCKER_COEFF * self.dc_blocker_prev_output_r;
+        for (ch, sample) in frame.iter_mut().enumerate() {
+            let input = *sample;
+            let output = GAIN * (input - self.dc_blocker_prev_input[ch])
+                + DC_BLOCKER_COEFF * self.dc_blocker_prev_output[ch];
 
-        self.dc_blocker_prev_input_l = input_l;
-        self.dc_blocker_prev_output_l = output_l;
-        self.dc_blocker_prev_input_r = input_r;
-        self.dc_blocker_prev_output_r = output_r;
-
-        (output_l, output_r)
+            self.dc_blocker_prev_input[ch] = input;
+            self.dc_blocker_prev_output[ch] = output;
+            *sample = output;
+        }
     }
 
-    /// Check if a sample pair contains actual audio content
+    /// Check if a frame contains actual audio content
     #[inline]
-    fn is_audio_content(&self, left: f32, right: f32) -> bool {
-        left.abs() > AUDIO_DETECT_THRESHOLD || right.abs() > AUDIO_DETECT_THRESHOLD
+    fn is_audio_content(frame: &[f32]) -> bool {
+        frame.iter().any(|s| s.abs() > AUDIO_DETECT_THRESHOLD)
     }
 
     /// Apply fade envelope to audio buffer (in-place)
@@ -184,11 +188,11 @@ impl StartFadeEnvelope {
         }
 
         // Debug: log first process call
-        if self.wait_samples == 0 && self.position_samples == 0 {
+        if self.wait_frames == 0 && self.position_frames == 0 {
             eprintln!(
-                "[StartFade] Starting amplitude-triggered fade: fade duration {} samples ({:.1}ms), threshold {:.6}",
-                self.duration_samples,
-                self.duration_samples as f32 / (self.sample_rate as f32 * 2.0) * 1000.0,
+                "[StartFade] Starting amplitude-triggered fade: fade duration {} frames ({:.1}ms), threshold {:.6}",
+                self.duration_frames,
+                self.duration_frames as f32 / self.sample_rate as f32 * 1000.0,
                 AUDIO_DETECT_THRESHOLD
             );
             if buffer.len() >= 4 {
@@ -199,77 +203,67 @@ impl StartFadeEnvelope {
             }
         }
 
-        // Process stereo frames (2 samples per frame)
-        let frames = buffer.len() / 2;
-
-        for frame in 0..frames {
-            let left_idx = frame * 2;
-            let right_idx = frame * 2 + 1;
-
-            let input_l = buffer[left_idx];
-            let input_r = buffer[right_idx];
+        let channels = self.channels;
 
+        for frame in buffer.chunks_exact_mut(channels) {
             // Apply DC blocker first
-            let (blocked_l, blocked_r) = self.dc_block_sample(input_l, input_r);
+            self.dc_block_frame(frame);
 
             if !self.audio_detected {
                 // WAIT PHASE: Looking for actual audio content
-                // Check if this sample has audio content OR if we've waited too long
-                let timeout = self.wait_samples >= self.max_wait_samples;
-                let has_audio = self.is_audio_content(blocked_l, blocked_r);
+                // Check if this frame has audio content OR if we've waited too long
+                let timeout = self.wait_frames >= self.max_wait_frames;
+                let has_audio = Self::is_audio_content(frame);
 
                 if has_audio || timeout {
                     // Audio detected (or timeout)! Start the fade
                     self.audio_detected = true;
                     if has_audio {
                         eprintln!(
-                            "[StartFade] Audio DETECTED at sample {}, amplitude: L={:.6} R={:.6}",
-                            self.wait_samples, blocked_l.abs(), blocked_r.abs()
+                            "[StartFade] Audio DETECTED at frame {}, peak amplitude: {:.6}",
+                            self.wait_frames,
+                            frame.iter().fold(0.0f32, |peak, s| peak.max(s.abs()))
                         );
                     } else {
                         eprintln!(
-                            "[StartFade] Timeout after {} samples ({:.1}ms), forcing fade start",
-                            self.wait_samples,
-                            self.wait_samples as f32 / (self.sample_rate as f32 * 2.0) * 1000.0
+                            "[StartFade] Timeout after {} frames ({:.1}ms), forcing fade start",
+                            self.wait_frames,
+                            self.wait_frames as f32 / self.sample_rate as f32 * 1000.0
                         );
                     }
-                    // Apply fade gain = 0 for first sample
-                    buffer[left_idx] = 0.0;
-                    buffer[right_idx] = 0.0;
-                    self.position_samples = 2; // Next frame starts at position 2
+                    // Apply fade gain = 0 for first frame
+                    frame.fill(0.0);
+                    self.position_frames = 1; // Next frame starts at position 1
                 } else {
                     // Still waiting - output silence
-                    buffer[left_idx] = 0.0;
-                    buffer[right_idx] = 0.0;
-                    self.wait_samples += 2;
+                    frame.fill(0.0);
+                    self.wait_frames += 1;
                 }
             } else {
                 // FADE PHASE: Apply gradual fade-in
-                let progress = self.position_samples as f32 / self.duration_samples as f32;
+                let progress = self.position_frames as f32 / self.duration_frames as f32;
 
-                if progress >= 1.0 {
-                    // Fade complete - pass through with DC blocking only
-                    buffer[left_idx] = blocked_l;
-                    buffer[right_idx] = blocked_r;
-                } else {
+                if progress < 1.0 {
                     // S-curve: (1 - cos(π * t)) / 2 - smooth at start and end
                     let gain = (1.0 - (std::f32::consts::PI * progress).cos()) * 0.5;
-                    buffer[left_idx] = blocked_l * gain;
-                    buffer[right_idx] = blocked_r * gain;
-                    self.position_samples += 2;
+                    for sample in frame.iter_mut() {
+                        *sample *= gain;
+                    }
+                    self.position_frames += 1;
                 }
+                // Fade complete - pass through with DC blocking only
             }
         }
 
         // Check if fade completed
-        if self.audio_detected && self.position_samples >= self.duration_samples {
+        if self.audio_detected && self.position_frames >= self.duration_frames {
             self.active = false;
             eprintln!(
-                "[StartFade] Fade COMPLETED: waited {} samples ({:.1}ms), faded {} samples ({:.1}ms)",
-                self.wait_samples,
-                self.wait_samples as f32 / (self.sample_rate as f32 * 2.0) * 1000.0,
-                self.position_samples,
-                self.position_samples as f32 / (self.sample_rate as f32 * 2.0) * 1000.0
+                "[StartFade] Fade COMPLETED: waited {} frames ({:.1}ms), faded {} frames ({:.1}ms)",
+                self.wait_frames,
+                self.wait_frames as f32 / self.sample_rate as f32 * 1000.0,
+                self.position_frames,
+                self.position_frames as f32 / self.sample_rate as f32 * 1000.0
             );
         }
 
@@ -277,8 +271,101 @@ impl StartFadeEnvelope {
     }
 }
 
+/// Per-track gain offset (e.g. from an album or genre override)
+///
+/// Gain changes are ramped linearly across one buffer so that switching
+/// the offset at a track boundary does not click.
+struct GainOffset {
+    /// Gain applied at the end of the last buffer (linear)
+    current: f32,
+    /// Requested gain (linear)
+    target: f32,
+    /// Requested gain in dB (for reporting)
+    target_db: f64,
+}
+
+impl GainOffset {
+    fn new() -> Self {
+        Self {
+            current: 1.0,
+            target: 1.0,
+            target_db: 0.0,
+        }
+    }
+
+    fn set_db(&mut self, gain_db: f64) {
+        self.target_db = gain_db;
+        self.target = 10.0_f64.powf(gain_db / 20.0) as f32;
+    }
+
+    fn db(&self) -> f64 {
+        self.target_db
+    }
+
+    fn process(&mut self, buffer: &mut [f32], channels: usize) {
+        let channels = channels.max(1);
+
+        if self.current == self.target {
+            if self.current != 1.0 {
+                for sample in buffer.iter_mut() {
+                    *sample *= self.current;
+                }
+            }
+            return;
+        }
+
+        let frames = buffer.len() / channels;
+        if frames == 0 {
+            return;
+        }
+
+        let step = (self.target - self.current) / frames as f32;
+        for (i, frame) in buffer.chunks_exact_mut(channels).enumerate() {
+            let gain = self.current + step * (i + 1) as f32;
+            for sample in frame.iter_mut() {
+                *sample *= gain;
+            }
+        }
+        self.current = self.target;
+    }
+}
+
+/// Gain offset and effect chain a track plays with
+///
+/// Resolved by the platform when it loads the track (per-track, album or
+/// genre overrides) and handed over with the source, so that the switch
+/// happens at the sample where the transition to the track starts.
+#[derive(Default)]
+pub struct TrackDsp {
+    /// Gain offset in dB
+    pub gain_offset_db: f64,
+    /// Chain replacing the running one (None = keep the running chain)
+    ///
+    /// Prepare it with `EffectChain::set_sample_rate` before handing it
+    /// over: it may be handed over from the audio thread.
+    #[cfg(feature = "effects")]
+    pub effect_chain: Option<EffectChain>,
+}
+
+impl TrackDsp {
+    /// Bring the chain to the stream's sample rate (on the control path)
+    #[cfg(feature = "effects")]
+    fn prepare(&mut self, sample_rate: u32) {
+        if let Some(chain) = &mut self.effect_chain {
+            chain.set_sample_rate(sample_rate);
+        }
+    }
+
+    #[cfg(not(feature = "effects"))]
+    fn prepare(&mut self, _sample_rate: u32) {}
+}
+
 #[cfg(feature = "effects")]
-use soul_audio::effects::EffectChain;
+use soul_audio::{
+    analysis::{AnalysisReader, AnalysisTap},
+    channels::ChannelLayout,
+    effects::EffectChain,
+};
 
 #[cfg(feature = "volume-leveling")]
 use soul_loudness::{
@@ -289,6 +376,47 @@ use soul_loudness::{
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
 
+/// Map interleaved frames between channel counts
+///
+/// Mono outputs average the front pair and mono sources feed both front
+/// channels. Otherwise channels are copied by index and extra output
+/// channels are silent. Sources that know their layout (e.g. 5.1 files)
+/// should deliver the output channel count themselves.
+fn map_channels(input: &[f32], in_channels: usize, output: &mut [f32], out_channels: usize) {
+    for (src, dst) in input
+        .chunks_exact(in_channels)
+        .zip(output.chunks_exact_mut(out_channels))
+    {
+        if out_channels == 1 {
+            dst[0] = if in_channels >= 2 {
+                (src[0] + src[1]) * 0.5
+            } else {
+                src[0]
+            };
+        } else if in_channels == 1 {
+            dst[0] = src[0];
+            dst[1] = src[0];
+            dst[2..].fill(0.0);
+        } else {
+            let shared = in_channels.min(out_channels);
+            dst[..shared].copy_from_slice(&src[..shared]);
+            dst[shared..].fill(0.0);
+        }
+    }
+}
+
+/// Longest crossfade, also when following a natural fade-out
+const MAX_CROSSFADE: Duration = Duration::from_secs(10);
+
+/// Convert a span of source time to real (output) time at a playback speed
+fn real_time(source_time: Duration, speed: f32) -> Duration {
+    if speed == 1.0 {
+        source_time
+    } else {
+        source_time.div_f64(speed as f64)
+    }
+}
+
 /// Central playback management
 ///
 /// Orchestrates all playback functionality:
@@ -317,15 +445,30 @@ pub struct PlaybackManager {
     // Audio processing
     #[cfg(feature = "effects")]
     effect_chain: EffectChain,
+    // Level/spectrum metering after the effect chain (None = disabled)
+    #[cfg(feature = "effects")]
+    analysis_tap: Option<AnalysisTap>,
     #[cfg(feature = "volume-leveling")]
     loudness_normalizer: LoudnessNormalizer,
     #[cfg(feature = "volume-leveling")]
     headroom_manager: HeadroomManager,
+    // Gain offset from per-track/album/genre overrides
+    gain_offset: GainOffset,
     #[cfg(feature = "volume-leveling")]
     output_limiter: TruePeakLimiter,
-    audio_source: Option<Box<dyn AudioSource>>,
-    next_source: Option<Box<dyn AudioSource>>, // For gapless/crossfade
-    next_track: Option<QueueTrack>,            // Metadata for next track
+    audio_source: Option<StretchedSource>,
+    next_source: Option<StretchedSource>, // For gapless/crossfade
+    next_track: Option<QueueTrack>,       // Metadata for next track
+    // Gain/effects the next track plays with (None = keep the current ones)
+    next_dsp: Option<TrackDsp>,
+    // Sample offset in the buffer being rendered where next_dsp takes over
+    dsp_switch_at: Option<usize>,
+    // Chain replaced at a track switch, freed by the platform off the audio thread
+    #[cfg(feature = "effects")]
+    retired_chain: Option<EffectChain>,
+
+    // Speed/pitch for tracks without their own playback_rate
+    playback_rate: PlaybackRate,
 
     // Crossfade engine
     crossfade: CrossfadeEngine,
@@ -334,14 +477,14 @@ pub struct PlaybackManager {
     outgoing_buffer: Vec<f32>,
     incoming_buffer: Vec<f32>,
 
-    // Pre-allocated buffer for stereo conversion (mono/multichannel output)
+    // Pre-allocated buffer for channel conversion (source layout != output layout)
     // Avoids heap allocation in audio callback - see CLAUDE.md rule #4
-    stereo_conversion_buffer: Vec<f32>,
+    channel_conversion_buffer: Vec<f32>,
 
     // Sample rate (for effects processing)
     sample_rate: u32,
 
-    // Output channels (1 = mono, 2 = stereo)
+    // Output channels (1 = mono, 2 = stereo, 6 = 5.1, 8 = 7.1)
     output_channels: u16,
 
     // Track if we're in a manual skip (for crossfade on_skip setting)
@@ -358,12 +501,13 @@ pub struct PlaybackManager {
 }
 
 /// Default buffer size for crossfade (10 seconds at max supported sample rate 192kHz stereo)
+/// Multichannel layouts get proportionally shorter crossfades at 192kHz
 /// This ensures crossfade works correctly at all sample rates up to 192kHz
 const CROSSFADE_BUFFER_SIZE: usize = 10 * 192000 * 2;
 
-/// Maximum stereo buffer size for channel conversion (8192 frames * 2 channels)
-/// This covers typical audio callback buffer sizes (256-4096 frames)
-const MAX_STEREO_BUFFER_SIZE: usize = 8192 * 2;
+/// Maximum buffer size for channel conversion (8192 frames * 8 channels)
+/// This covers typical audio callback buffer sizes (256-4096 frames) up to 7.1
+const MAX_CONVERSION_BUFFER_SIZE: usize = 8192 * 8;
 
 impl PlaybackManager {
     /// Create new playback manager
@@ -386,19 +530,27 @@ impl PlaybackManager {
             gapless_enabled: config.gapless,
             #[cfg(feature = "effects")]
             effect_chain: EffectChain::new(),
+            #[cfg(feature = "effects")]
+            analysis_tap: None,
             #[cfg(feature = "volume-leveling")]
             loudness_normalizer,
             #[cfg(feature = "volume-leveling")]
             headroom_manager: HeadroomManager::new(),
+            gain_offset: GainOffset::new(),
             #[cfg(feature = "volume-leveling")]
             output_limiter: TruePeakLimiter::new(44100, 2),
             audio_source: None,
             next_source: None,
             next_track: None,
+            next_dsp: None,
+            dsp_switch_at: None,
+            #[cfg(feature = "effects")]
+            retired_chain: None,
+            playback_rate: PlaybackRate::NORMAL,
             crossfade: CrossfadeEngine::with_settings(config.crossfade),
             outgoing_buffer: vec![0.0; CROSSFADE_BUFFER_SIZE],
             incoming_buffer: vec![0.0; CROSSFADE_BUFFER_SIZE],
-            stereo_conversion_buffer: vec![0.0; MAX_STEREO_BUFFER_SIZE],
+            channel_conversion_buffer: vec![0.0; MAX_CONVERSION_BUFFER_SIZE],
             sample_rate: 44100, // Default, will be updated by platform
             output_channels: 2, // Default stereo, will be updated by platform
             is_manual_skip: false,
@@ -449,6 +601,8 @@ impl PlaybackManager {
         self.audio_source = None;
         self.next_source = None;
         self.next_track = None;
+        self.next_dsp = None;
+        self.dsp_switch_at = None;
         self.crossfade.reset();
         self.crossfade_progress.reset();
         self.is_manual_skip = false;
@@ -591,6 +745,66 @@ impl PlaybackManager {
         }
     }
 
+    // ===== Playback Speed =====
+
+    /// Set the default playback speed and pitch
+    ///
+    /// Applies to the current and next track unless they carry their own
+    /// `playback_rate`. Speed changes preserve pitch; positions, seeking and
+    /// crossfade timing stay in source time.
+    pub fn set_playback_rate(&mut self, rate: PlaybackRate) {
+        self.playback_rate = rate;
+        self.apply_playback_rates();
+    }
+
+    /// Get the default playback speed and pitch
+    pub fn get_playback_rate(&self) -> PlaybackRate {
+        self.playback_rate
+    }
+
+    /// Override the playback speed and pitch of the current track
+    ///
+    /// `None` returns the track to the default rate.
+    pub fn set_current_track_playback_rate(&mut self, rate: Option<PlaybackRate>) {
+        if let Some(ref mut track) = self.current_track {
+            track.playback_rate = rate;
+        }
+        self.apply_playback_rates();
+    }
+
+    /// Get the playback speed and pitch in effect for the current track
+    pub fn get_current_playback_rate(&self) -> PlaybackRate {
+        self.rate_for(self.current_track.as_ref())
+    }
+
+    /// Rate for a track: its own override, else the default
+    fn rate_for(&self, track: Option<&QueueTrack>) -> PlaybackRate {
+        track
+            .and_then(|t| t.playback_rate)
+            .unwrap_or(self.playback_rate)
+    }
+
+    /// Push the effective rates to the loaded sources
+    fn apply_playback_rates(&mut self) {
+        let current = self.rate_for(self.current_track.as_ref());
+        let current_result = self
+            .audio_source
+            .as_mut()
+            .map_or(Ok(()), |source| source.set_rate(current));
+
+        let next = self.rate_for(self.next_track.as_ref());
+        let next_result = self
+            .next_source
+            .as_mut()
+            .map_or(Ok(()), |source| source.set_rate(next));
+
+        for result in [current_result, next_result] {
+            if let Err(e) = result {
+                self.emit_error(format!("Failed to resync after rate change: {}", e));
+            }
+        }
+    }
+
     // ===== Volume =====
 
     /// Set volume (0-100)
@@ -786,20 +1000,34 @@ impl PlaybackManager {
     /// Get current playback position
     ///
     /// During crossfade, returns the incoming track's position to avoid
-    /// a jarring position jump when the transition completes.
+    /// a jarring position jump when the transition completes. The effect
+    /// chain latency is subtracted so the position matches what is audible.
     pub fn get_position(&self) -> Duration {
         // During crossfade, report incoming track position
         if self.crossfade.is_active() {
             if let Some(ref next_source) = self.next_source {
-                return next_source.position();
+                return self.audible_position(next_source.position());
             }
         }
 
         // Normal playback - report current source position
-        self.audio_source
+        let position = self
+            .audio_source
             .as_ref()
             .map(|s| s.position())
-            .unwrap_or(Duration::ZERO)
+            .unwrap_or(Duration::ZERO);
+        self.audible_position(position)
+    }
+
+    /// Shift a source position back by the effect chain latency
+    fn audible_position(&self, position: Duration) -> Duration {
+        let latency = self.get_effect_latency();
+        if latency == 0 || self.sample_rate == 0 {
+            return position;
+        }
+        position.saturating_sub(Duration::from_secs_f64(
+            latency as f64 / self.sample_rate as f64,
+        ))
     }
 
     /// Get current track duration
@@ -899,71 +1127,33 @@ impl PlaybackManager {
             return Ok(output.len());
         };
 
-        // Audio source always outputs stereo (2 channels)
-        // If device is mono, we need to convert
-        if self.output_channels == 1 {
-            // Mono output - read stereo, convert to mono
-            // Use pre-allocated buffer to avoid heap allocation in audio callback
-            let stereo_samples = (output.len() * 2).min(self.stereo_conversion_buffer.len());
+        let source_channels = source.channels().max(1);
 
-            let samples_read =
-                source.read_samples(&mut self.stereo_conversion_buffer[..stereo_samples])?;
+        if source_channels == self.output_channels {
+            // Source matches the device layout - with crossfade support
+            let samples_read = self.process_native_with_crossfade(output)?;
 
             if samples_read == 0 {
-                // Track finished
+                // Track finished (no crossfade or crossfade completed)
                 self.handle_track_finished()?;
                 return Ok(0);
             }
 
-            // Apply start fade envelope for click-free playback start/resume
-            // This must come BEFORE any other processing
-            self.start_fade
-                .process(&mut self.stereo_conversion_buffer[..samples_read]);
-
-            // Apply loudness normalization to stereo buffer (before channel conversion)
-            #[cfg(feature = "volume-leveling")]
-            self.loudness_normalizer
-                .process(&mut self.stereo_conversion_buffer[..samples_read]);
-
-            // Apply headroom attenuation BEFORE effects to prevent clipping in DSP chain
-            #[cfg(feature = "volume-leveling")]
-            self.headroom_manager
-                .process(&mut self.stereo_conversion_buffer[..samples_read]);
-
-            // Convert stereo to mono by averaging L and R channels
-            let frames = samples_read / 2;
-            for i in 0..frames {
-                let left = self.stereo_conversion_buffer[i * 2];
-                let right = self.stereo_conversion_buffer[i * 2 + 1];
-                output[i] = (left + right) * 0.5; // Average and write to mono output
-            }
-
-            // Apply effects (if feature enabled)
-            #[cfg(feature = "effects")]
-            self.effect_chain
-                .process(&mut output[..frames], self.sample_rate);
-
-            // Apply volume
-            self.volume.apply(&mut output[..frames]);
-
-            // Apply output limiter AFTER volume to catch ALL peaks
-            #[cfg(feature = "volume-leveling")]
-            self.output_limiter.process(&mut output[..frames]);
-
-            Ok(frames)
-        } else if self.output_channels == 2 {
-            // Stereo output - with crossfade support
-            let samples_read = self.process_stereo_with_crossfade(output)?;
+            // The next track's gain and effects take over where it starts
+            let switch_at = self.dsp_switch_at.take().map(|at| at.min(samples_read));
 
-            if samples_read == 0 {
-                // Track finished (no crossfade or crossfade completed)
-                self.handle_track_finished()?;
-                return Ok(0);
+            // Bitstream sources (DoP) must reach the device untouched
+            if self.is_bitstream() {
+                if switch_at.is_some() {
+                    self.switch_to_next_dsp();
+                }
+                return Ok(samples_read);
             }
 
             // Apply start fade envelope for click-free playback start/resume
             // Only apply when NOT crossfading (crossfade has its own fade curves)
             if !self.crossfade.is_active() {
+                self.start_fade.set_channels(self.output_channels);
                 self.start_fade.process(&mut output[..samples_read]);
             }
 
@@ -972,14 +1162,21 @@ impl PlaybackManager {
             self.loudness_normalizer
                 .process(&mut output[..samples_read]);
 
-            // Apply headroom attenuation BEFORE effects to prevent clipping in DSP chain
-            #[cfg(feature = "volume-leveling")]
-            self.headroom_manager.process(&mut output[..samples_read]);
+            // Gain offset, headroom and effects, split at the track boundary
+            if let Some(at) = switch_at {
+                self.process_track_dsp(&mut output[..at]);
+                self.switch_to_next_dsp();
+                self.process_track_dsp(&mut output[at..samples_read]);
+            } else {
+                self.process_track_dsp(&mut output[..samples_read]);
+            }
 
-            // Apply effects (if feature enabled)
+            // Meter the post-effects signal (before volume, so meters don't
+            // collapse when the user turns the volume down)
             #[cfg(feature = "effects")]
-            self.effect_chain
-                .process(&mut output[..samples_read], self.sample_rate);
+            if let Some(tap) = &mut self.analysis_tap {
+                tap.process(&output[..samples_read]);
+            }
 
             // Apply volume
             self.volume.apply(&mut output[..samples_read]);
@@ -991,14 +1188,16 @@ impl PlaybackManager {
 
             Ok(samples_read)
         } else {
-            // Multi-channel output (e.g., ASIO with 6 channels)
-            // Read stereo, then upmix to fill all output channels
+            // Channel count conversion (e.g. mono device, or stereo source on a 5.1 device)
+            // Read in the source layout, then map to the output channels
             // Use pre-allocated buffer to avoid heap allocation in audio callback
-            let frames = output.len() / self.output_channels as usize;
-            let stereo_samples = (frames * 2).min(self.stereo_conversion_buffer.len());
+            let src_channels = source_channels as usize;
+            let out_channels = self.output_channels as usize;
+            let max_frames = (output.len() / out_channels)
+                .min(self.channel_conversion_buffer.len() / src_channels);
 
-            let samples_read =
-                source.read_samples(&mut self.stereo_conversion_buffer[..stereo_samples])?;
+            let samples_read = source
+                .read_samples(&mut self.channel_conversion_buffer[..max_frames * src_channels])?;
 
             if samples_read == 0 {
                 // Track finished
@@ -1006,94 +1205,151 @@ impl PlaybackManager {
                 return Ok(0);
             }
 
-            let frames_read = samples_read / 2;
+            let frames_read = samples_read / src_channels;
+            let samples_read = frames_read * src_channels;
+            let samples_out = frames_read * out_channels;
 
             // Apply start fade envelope for click-free playback start/resume
             // This must come BEFORE any other processing
+            self.start_fade.set_channels(source_channels);
             self.start_fade
-                .process(&mut self.stereo_conversion_buffer[..samples_read]);
+                .process(&mut self.channel_conversion_buffer[..samples_read]);
 
-            // Apply loudness normalization to stereo buffer
+            // Apply loudness normalization to source buffer (before channel conversion)
             #[cfg(feature = "volume-leveling")]
             self.loudness_normalizer
-                .process(&mut self.stereo_conversion_buffer[..samples_read]);
+                .process(&mut self.channel_conversion_buffer[..samples_read]);
+
+            self.gain_offset.process(
+                &mut self.channel_conversion_buffer[..samples_read],
+                src_channels,
+            );
 
             // Apply headroom attenuation BEFORE effects to prevent clipping in DSP chain
             #[cfg(feature = "volume-leveling")]
             self.headroom_manager
-                .process(&mut self.stereo_conversion_buffer[..samples_read]);
+                .process(&mut self.channel_conversion_buffer[..samples_read]);
+
+            map_channels(
+                &self.channel_conversion_buffer[..samples_read],
+                src_channels,
+                &mut output[..samples_out],
+                out_channels,
+            );
 
-            // Apply effects to stereo buffer (if feature enabled)
+            // Apply effects in the output layout (if feature enabled)
             #[cfg(feature = "effects")]
-            self.effect_chain.process(
-                &mut self.stereo_conversion_buffer[..samples_read],
+            self.effect_chain.process_layout(
+                &mut output[..samples_out],
                 self.sample_rate,
+                ChannelLayout::from_channel_count(self.output_channels),
             );
 
-            // Apply volume to stereo buffer
-            self.volume
-                .apply(&mut self.stereo_conversion_buffer[..samples_read]);
+            #[cfg(feature = "effects")]
+            if let Some(tap) = &mut self.analysis_tap {
+                tap.process(&output[..samples_out]);
+            }
+
+            // Apply volume
+            self.volume.apply(&mut output[..samples_out]);
 
             // Apply output limiter AFTER volume to catch ALL peaks
             #[cfg(feature = "volume-leveling")]
-            self.output_limiter
-                .process(&mut self.stereo_conversion_buffer[..samples_read]);
-
-            // Upmix stereo to multi-channel: put L/R in first two channels, silence in rest
-            for frame in 0..frames_read {
-                let left = self.stereo_conversion_buffer[frame * 2];
-                let right = self.stereo_conversion_buffer[frame * 2 + 1];
-                let out_offset = frame * self.output_channels as usize;
-
-                // First two channels get stereo audio
-                output[out_offset] = left;
-                output[out_offset + 1] = right;
-
-                // Remaining channels get silence
-                for ch in 2..self.output_channels as usize {
-                    output[out_offset + ch] = 0.0;
-                }
-            }
+            self.output_limiter.process(&mut output[..samples_out]);
+
+            Ok(samples_out)
+        }
+    }
+
+    /// Apply the gain offset, headroom attenuation and effects in the output layout
+    fn process_track_dsp(&mut self, buffer: &mut [f32]) {
+        if buffer.is_empty() {
+            return;
+        }
+
+        self.gain_offset.process(buffer, self.output_channels as usize);
+
+        // Apply headroom attenuation BEFORE effects to prevent clipping in DSP chain
+        #[cfg(feature = "volume-leveling")]
+        self.headroom_manager.process(buffer);
+
+        // Apply effects (if feature enabled)
+        #[cfg(feature = "effects")]
+        self.effect_chain.process_layout(
+            buffer,
+            self.sample_rate,
+            ChannelLayout::from_channel_count(self.output_channels),
+        );
+    }
+
+    /// Switch to the gain and effects prepared for the next track
+    fn switch_to_next_dsp(&mut self) {
+        if let Some(dsp) = self.next_dsp.take() {
+            self.install_dsp(dsp);
+        }
+    }
 
-            Ok(frames_read * self.output_channels as usize)
+    /// Install a track's gain offset and chain
+    ///
+    /// The replaced chain is kept until the platform takes it with
+    /// [`Self::take_retired_chain`], so it isn't freed on the audio thread.
+    fn install_dsp(&mut self, dsp: TrackDsp) {
+        self.gain_offset.set_db(dsp.gain_offset_db);
+        #[cfg(feature = "effects")]
+        if let Some(chain) = dsp.effect_chain {
+            self.retired_chain = Some(std::mem::replace(&mut self.effect_chain, chain));
         }
     }
 
-    /// Process stereo audio with crossfade support
+    /// Process audio in the output layout with crossfade support
     ///
     /// Handles:
     /// - Normal playback (no crossfade)
     /// - Crossfade initiation (when approaching end of track)
     /// - Crossfade mixing (when active)
     /// - Gapless transition (0ms crossfade)
-    fn process_stereo_with_crossfade(&mut self, output: &mut [f32]) -> Result<usize> {
+    fn process_native_with_crossfade(&mut self, output: &mut [f32]) -> Result<usize> {
         // Check if crossfade is currently active
         if self.crossfade.is_active() {
             return self.process_active_crossfade(output);
         }
 
-        // Normal playback - check if we should start crossfade
+        // Normal playback - check if we're approaching the crossfade window
+        // (in real time, so time-stretched tracks still fade over the configured duration)
+        let (remaining, crossfade_duration) = self
+            .crossfade_window()
+            .ok_or(PlaybackError::NoTrackLoaded)?;
+        let crossfade_duration_ms = crossfade_duration.as_millis() as u32;
+
         let source = self
             .audio_source
             .as_mut()
             .ok_or(PlaybackError::NoTrackLoaded)?;
 
-        // Check if we're approaching the crossfade window
-        let position = source.position();
-        let duration = source.duration();
-        let crossfade_duration_ms = self.crossfade.settings().duration_ms;
-        let crossfade_duration = Duration::from_millis(crossfade_duration_ms as u64);
-        let remaining = duration.saturating_sub(position);
-
         // Should we start crossfade?
+        // Bitstream sources can't be mixed, so they fall back to gapless,
+        // as do tracks in a different channel layout
+        let can_mix = !source.is_bitstream()
+            && !self
+                .next_source
+                .as_ref()
+                .is_some_and(|n| n.is_bitstream() || n.channels() != source.channels());
         let should_crossfade = self.crossfade.settings().enabled
+            && can_mix
             && self.next_source.is_some()
             && remaining <= crossfade_duration;
 
         if should_crossfade {
             // Start crossfade
-            let started = self.crossfade.start(self.is_manual_skip);
+            let started = self
+                .crossfade
+                .start_with_duration(self.is_manual_skip, crossfade_duration_ms);
             if started {
+                // The incoming track's DSP applies to the whole crossfade
+                if self.next_dsp.is_some() {
+                    self.dsp_switch_at = Some(0);
+                }
+
                 // Initialize crossfade progress tracker
                 let from_track_id = self
                     .current_track
@@ -1115,23 +1371,38 @@ impl PlaybackManager {
         }
 
         // Check for gapless transition (crossfade disabled but gapless enabled)
-        let should_gapless = !self.crossfade.settings().enabled
+        let should_gapless = (!self.crossfade.settings().enabled || !can_mix)
             && self.gapless_enabled
             && self.next_source.is_some();
 
         // Normal playback
         let samples_read = source.read_samples(output)?;
 
-        if samples_read == 0 {
-            // Track finished
-            if should_gapless {
-                // Seamless transition to next track
-                self.transition_to_next_track()?;
-                // Try to read from new source
-                if let Some(ref mut new_source) = self.audio_source {
-                    return new_source.read_samples(output);
+        // Track finished, possibly part way through the buffer
+        let track_finished =
+            samples_read == 0 || (samples_read < output.len() && source.is_finished());
+
+        if track_finished && should_gapless {
+            if self.next_dsp.is_some() {
+                self.dsp_switch_at = Some(samples_read);
+            }
+
+            // Seamless transition to next track
+            self.transition_to_next_track()?;
+            // Fill the rest of the buffer from the new source
+            if let Some(ref mut new_source) = self.audio_source {
+                if new_source.channels().max(1) != self.output_channels {
+                    // Layout changed - converted from the next callback on
+                    output[samples_read..].fill(0.0);
+                    return Ok(output.len());
                 }
+                let next_read = new_source.read_samples(&mut output[samples_read..])?;
+                return Ok(samples_read + next_read);
             }
+        }
+
+        if samples_read == 0 {
+            // Track finished
             return Ok(0);
         }
 
@@ -1260,6 +1531,29 @@ impl PlaybackManager {
         self.sample_rate = sample_rate;
         self.crossfade.set_sample_rate(sample_rate);
         self.start_fade.set_sample_rate(sample_rate);
+        let results = [
+            self.audio_source
+                .as_mut()
+                .map_or(Ok(()), |source| source.set_sample_rate(sample_rate)),
+            self.next_source
+                .as_mut()
+                .map_or(Ok(()), |source| source.set_sample_rate(sample_rate)),
+        ];
+        for result in results {
+            if let Err(e) = result {
+                self.emit_error(format!("Failed to resync after sample rate change: {}", e));
+            }
+        }
+        // Effects design filters and resample IRs here, not on the audio thread
+        #[cfg(feature = "effects")]
+        self.effect_chain.set_sample_rate(sample_rate);
+        if let Some(dsp) = &mut self.next_dsp {
+            dsp.prepare(sample_rate);
+        }
+        #[cfg(feature = "effects")]
+        if let Some(tap) = &mut self.analysis_tap {
+            tap.set_format(sample_rate, self.output_channels);
+        }
     }
 
     /// Get sample rate
@@ -1269,7 +1563,20 @@ impl PlaybackManager {
 
     /// Set output channels (called by platform)
     pub fn set_output_channels(&mut self, channels: u16) {
+        let channels = channels.max(1);
         self.output_channels = channels;
+        self.crossfade.set_channels(channels);
+        #[cfg(feature = "volume-leveling")]
+        self.output_limiter.set_channels(channels as usize);
+        #[cfg(feature = "effects")]
+        if let Some(tap) = &mut self.analysis_tap {
+            tap.set_format(self.sample_rate, channels);
+        }
+    }
+
+    /// Get output channels
+    pub fn get_output_channels(&self) -> u16 {
+        self.output_channels
     }
 
     /// Get effect chain (for adding/configuring effects)
@@ -1278,6 +1585,87 @@ impl PlaybackManager {
         &mut self.effect_chain
     }
 
+    /// Get the processing delay of the enabled effects in frames
+    ///
+    /// Non-zero when e.g. an EQ runs in linear-phase mode.
+    #[cfg(feature = "effects")]
+    pub fn get_effect_latency(&self) -> usize {
+        self.effect_chain.latency_samples()
+    }
+
+    /// Get the processing delay of the enabled effects in frames
+    #[cfg(not(feature = "effects"))]
+    pub fn get_effect_latency(&self) -> usize {
+        0
+    }
+
+    // ===== Gain Offset =====
+
+    /// Set the gain offset in dB for the current track
+    ///
+    /// Applied after loudness normalization and before the headroom
+    /// manager and effects. Changes are ramped over one buffer.
+    pub fn set_gain_offset_db(&mut self, gain_db: f64) {
+        self.gain_offset.set_db(gain_db);
+    }
+
+    /// Get the gain offset in dB
+    pub fn get_gain_offset_db(&self) -> f64 {
+        self.gain_offset.db()
+    }
+
+    /// Apply a track's gain offset and effect chain right away
+    ///
+    /// For the current track, e.g. after it was loaded directly rather than
+    /// prepared as the next track. Use [`Self::set_next_source_with_dsp`]
+    /// for the next track.
+    pub fn apply_track_dsp(&mut self, mut dsp: TrackDsp) {
+        dsp.prepare(self.sample_rate);
+        self.install_dsp(dsp);
+    }
+
+    /// Take the effect chain replaced at the last switch of track DSP
+    ///
+    /// Chains are switched on the audio thread; the platform frees the
+    /// replaced one elsewhere.
+    #[cfg(feature = "effects")]
+    pub fn take_retired_chain(&mut self) -> Option<EffectChain> {
+        self.retired_chain.take()
+    }
+
+    // ===== Audio Analysis =====
+
+    /// Enable or disable the level/spectrum analysis tap
+    ///
+    /// When enabled, peak/RMS, momentary/short-term LUFS and a band spectrum
+    /// of the post-effects signal are published ~30 times per second. Poll
+    /// them through [`analysis_reader`](Self::analysis_reader).
+    #[cfg(feature = "effects")]
+    pub fn set_analysis_enabled(&mut self, enabled: bool) {
+        if enabled {
+            if self.analysis_tap.is_none() {
+                self.analysis_tap = Some(AnalysisTap::new(self.sample_rate, self.output_channels));
+            }
+        } else {
+            self.analysis_tap = None;
+        }
+    }
+
+    /// Check if the analysis tap is enabled
+    #[cfg(feature = "effects")]
+    pub fn is_analysis_enabled(&self) -> bool {
+        self.analysis_tap.is_some()
+    }
+
+    /// Get a lock-free reader for the analysis tap (None if disabled)
+    ///
+    /// The reader stays valid until analysis is disabled; it can be polled
+    /// from the UI thread without locking the manager.
+    #[cfg(feature = "effects")]
+    pub fn analysis_reader(&self) -> Option<AnalysisReader> {
+        self.analysis_tap.as_ref().map(AnalysisTap::reader)
+    }
+
     // ===== Volume Leveling =====
 
     /// Set volume leveling mode (ReplayGain track/album, EBU R128, etc.)
@@ -1487,7 +1875,8 @@ impl PlaybackManager {
         // where audio callback reads samples before fade is active
         self.start_fade.start();
 
-        self.audio_source = Some(source);
+        let rate = self.rate_for(self.current_track.as_ref());
+        self.audio_source = Some(StretchedSource::new(source, self.sample_rate, rate));
         self.state = PlaybackState::Playing;
         self.is_manual_skip = false;
 
@@ -1575,11 +1964,62 @@ impl PlaybackManager {
     /// Called by platform when pre-decoding the next track
     pub fn set_next_source(&mut self, source: Box<dyn AudioSource>, track: QueueTrack) {
         let track_id = track.id.clone();
-        self.next_source = Some(source);
+        let rate = self.rate_for(Some(&track));
+        let mut next_source = StretchedSource::new(source, self.sample_rate, rate);
+        if let Some(cue_in) = self.incoming_cue_in(&track) {
+            // Plays from the start if the source can't seek
+            let _ = next_source.seek(cue_in);
+        }
+        self.next_source = Some(next_source);
         self.next_track = Some(track);
+        #[cfg(feature = "effects")]
+        if let Some(chain) = self.next_dsp.take().and_then(|dsp| dsp.effect_chain) {
+            self.retired_chain = Some(chain);
+        }
+        self.next_dsp = None;
         self.emit_next_track_prepared(track_id);
     }
 
+    /// Set the next audio source along with the gain and effects it plays with
+    ///
+    /// The DSP takes over at the sample where the transition to the track
+    /// starts: the start of the crossfade, or the track boundary for gapless
+    /// playback. Until then the current track keeps its own.
+    pub fn set_next_source_with_dsp(
+        &mut self,
+        source: Box<dyn AudioSource>,
+        track: QueueTrack,
+        mut dsp: TrackDsp,
+    ) {
+        self.set_next_source(source, track);
+        dsp.prepare(self.sample_rate);
+        self.next_dsp = Some(dsp);
+    }
+
+    /// Replace the gain and effects prepared for the next track
+    ///
+    /// For when the settings they were resolved from change before the
+    /// transition. Returns false if `track_id` isn't the prepared next track
+    /// or the transition to it has already started.
+    pub fn set_next_track_dsp(&mut self, track_id: &str, mut dsp: TrackDsp) -> bool {
+        let pending = self.next_track.as_ref().is_some_and(|t| t.id == track_id)
+            && !self.crossfade.is_active();
+        if pending {
+            dsp.prepare(self.sample_rate);
+            self.next_dsp = Some(dsp);
+        }
+        pending
+    }
+
+    /// Check if the current source is a bitstream (e.g. DSD over PCM)
+    ///
+    /// Output stages must write bitstream samples bit-exact (no dithering).
+    pub fn is_bitstream(&self) -> bool {
+        self.audio_source
+            .as_ref()
+            .is_some_and(|source| source.is_bitstream())
+    }
+
     /// Check if next source is ready
     pub fn has_next_source(&self) -> bool {
         self.next_source.is_some()
@@ -1599,20 +2039,65 @@ impl PlaybackManager {
             return None;
         }
 
+        // Crossfade starts when: remaining_time <= crossfade_duration
+        let (remaining, crossfade_duration) = self.crossfade_window()?;
+        Some(remaining.saturating_sub(crossfade_duration))
+    }
+
+    /// Time until the crossfade out of the current track ends, and its length
+    ///
+    /// Both in real (output) time. Without cue points the crossfade ends at the
+    /// end of the track and lasts the configured duration. With them it ends at
+    /// the cue-out point and starts at the natural fade-out or the configured
+    /// duration before cue-out, whichever is earlier.
+    fn crossfade_window(&self) -> Option<(Duration, Duration)> {
         let source = self.audio_source.as_ref()?;
         let position = source.position();
-        let duration = source.duration();
-        let crossfade_duration =
-            Duration::from_millis(self.crossfade.settings().duration_ms as u64);
+        let speed = source.playback_speed();
+        let configured = Duration::from_millis(self.crossfade.settings().duration_ms as u64);
 
-        // Crossfade starts when: remaining_time <= crossfade_duration
-        let remaining = duration.saturating_sub(position);
+        let Some(cue_points) = self.transition_cue_points() else {
+            let remaining = real_time(source.duration().saturating_sub(position), speed);
+            return Some((remaining, configured));
+        };
 
-        if remaining <= crossfade_duration {
-            Some(Duration::ZERO)
+        let remaining = real_time(cue_points.cue_out.saturating_sub(position), speed);
+        // A 0ms crossfade stays gapless, it just skips the silent tail
+        let length = if configured.is_zero() {
+            Duration::ZERO
         } else {
-            Some(remaining - crossfade_duration)
+            let natural_fade = cue_points.fade_out.map_or(Duration::ZERO, |fade_out| {
+                real_time(cue_points.cue_out.saturating_sub(fade_out), speed)
+            });
+            configured.max(natural_fade).min(MAX_CROSSFADE)
+        };
+
+        // Still end at cue-out when starting late (e.g. after a seek)
+        Some((remaining, length.min(remaining)))
+    }
+
+    /// Cue points of the current track, if the transition out of it uses them
+    fn transition_cue_points(&self) -> Option<CuePoints> {
+        let current = self.current_track.as_ref()?;
+        let cue_points = current.cue_points?;
+        let album_continues = self
+            .next_track
+            .as_ref()
+            .is_some_and(|next| next.follows_on_album(current));
+        (!album_continues).then_some(cue_points)
+    }
+
+    /// Where a crossfade into `next` starts it (None = from the beginning)
+    fn incoming_cue_in(&self, next: &QueueTrack) -> Option<Duration> {
+        if !self.crossfade.settings().enabled {
+            return None;
         }
+        let cue_in = next.cue_points?.cue_in;
+        let album_continues = self
+            .current_track
+            .as_ref()
+            .is_some_and(|current| next.follows_on_album(current));
+        (!cue_in.is_zero() && !album_continues).then_some(cue_in)
     }
 
     /// Check if we should start preparing the next track for crossfade
@@ -1642,7 +2127,10 @@ impl PlaybackManager {
         } else if self.gapless_enabled {
             // For gapless without crossfade, prepare when within 2 seconds
             if let Some(ref source) = self.audio_source {
-                let remaining = source.duration().saturating_sub(source.position());
+                let remaining = real_time(
+                    source.duration().saturating_sub(source.position()),
+                    source.playback_speed(),
+                );
                 remaining <= Duration::from_secs(2)
             } else {
                 false
@@ -1793,6 +2281,7 @@ mod tests {
             duration: Duration::from_secs(180),
             track_number: Some(1),
             source: TrackSource::Single,
+            ..Default::default()
         }
     }
 
@@ -1895,4 +2384,435 @@ mod tests {
 
         assert_eq!(manager.get_state(), PlaybackState::Playing);
     }
+
+    /// Source emitting a fixed pattern, flagged as bitstream
+    struct PatternSource {
+        pattern: Vec<f32>,
+        bitstream: bool,
+    }
+
+    impl AudioSource for PatternSource {
+        fn read_samples(&mut self, buffer: &mut [f32]) -> Result<usize> {
+            for (i, sample) in buffer.iter_mut().enumerate() {
+                *sample = self.pattern[i % self.pattern.len()];
+            }
+            Ok(buffer.len())
+        }
+
+        fn seek(&mut self, _position: Duration) -> Result<()> {
+            Ok(())
+        }
+
+        fn duration(&self) -> Duration {
+            Duration::from_secs(10)
+        }
+
+        fn position(&self) -> Duration {
+            Duration::ZERO
+        }
+
+        fn is_finished(&self) -> bool {
+            false
+        }
+
+        fn is_bitstream(&self) -> bool {
+            self.bitstream
+        }
+    }
+
+    #[test]
+    fn bitstream_source_bypasses_processing() {
+        // DoP-style words: marker byte plus DSD payload, scaled to f32
+        let pattern = vec![
+            0x05_69_96_00u32 as i32 as f32 / 2_147_483_648.0,
+            0xFA_A5_5A_00u32 as i32 as f32 / 2_147_483_648.0,
+        ];
+        let mut manager = PlaybackManager::default();
+        manager.set_volume(30);
+        manager.set_audio_source(Box::new(PatternSource {
+            pattern: pattern.clone(),
+            bitstream: true,
+        }));
+        assert!(manager.is_bitstream());
+
+        let mut buffer = [0.0f32; 512];
+        let written = manager.process_audio(&mut buffer).unwrap();
+        assert_eq!(written, 512);
+        for (i, sample) in buffer.iter().enumerate() {
+            assert_eq!(sample.to_bits(), pattern[i % 2].to_bits(), "sample {}", i);
+        }
+
+        // Regular sources still go through fade and volume
+        manager.set_audio_source(Box::new(PatternSource {
+            pattern: pattern.clone(),
+            bitstream: false,
+        }));
+        assert!(!manager.is_bitstream());
+        manager.process_audio(&mut buffer).unwrap();
+        assert_ne!(buffer[0].to_bits(), pattern[0].to_bits());
+    }
+
+    /// Source emitting an alternating signal on every channel
+    struct LayoutSource {
+        channels: u16,
+    }
+
+    impl AudioSource for LayoutSource {
+        fn read_samples(&mut self, buffer: &mut [f32]) -> Result<usize> {
+            let channels = self.channels as usize;
+            for (i, frame) in buffer.chunks_exact_mut(channels).enumerate() {
+                frame.fill(if i % 2 == 0 { 0.5 } else { -0.5 });
+            }
+            Ok(buffer.len() / channels * channels)
+        }
+
+        fn seek(&mut self, _position: Duration) -> Result<()> {
+            Ok(())
+        }
+
+        fn duration(&self) -> Duration {
+            Duration::from_secs(10)
+        }
+
+        fn position(&self) -> Duration {
+            Duration::ZERO
+        }
+
+        fn is_finished(&self) -> bool {
+            false
+        }
+
+        fn channels(&self) -> u16 {
+            self.channels
+        }
+    }
+
+    #[test]
+    fn surround_source_plays_natively() {
+        let mut manager = PlaybackManager::default();
+        manager.set_output_channels(6);
+        manager.set_audio_source(Box::new(LayoutSource { channels: 6 }));
+
+        let mut buffer = vec![0.0f32; 6 * 4096];
+        let written = manager.process_audio(&mut buffer).unwrap();
+        assert_eq!(written, buffer.len());
+
+        // Every channel carries audio once the start fade has finished
+        let last = &buffer[buffer.len() - 6..];
+        assert!(last.iter().all(|s| s.abs() > 0.1), "{:?}", last);
+        assert!(last.iter().all(|s| (s - last[0]).abs() < 1e-6));
+    }
+
+    #[cfg(feature = "effects")]
+    #[test]
+    fn analysis_tap_meters_output() {
+        let mut manager = PlaybackManager::default();
+        assert!(manager.analysis_reader().is_none());
+
+        manager.set_analysis_enabled(true);
+        let reader = manager.analysis_reader().unwrap();
+        manager.set_audio_source(Box::new(LayoutSource { channels: 2 }));
+
+        let mut buffer = vec![0.0f32; 2 * 4096];
+        manager.process_audio(&mut buffer).unwrap();
+
+        let levels = reader.latest().expect("analysis published");
+        assert_eq!(levels.channels, 2);
+        assert!(levels.peak[0] > 0.1 && levels.peak[1] > 0.1);
+
+        manager.set_analysis_enabled(false);
+        assert!(!manager.is_analysis_enabled());
+    }
+
+    #[test]
+    fn stereo_source_maps_to_front_pair() {
+        let mut manager = PlaybackManager::default();
+        manager.set_output_channels(6);
+        manager.set_audio_source(Box::new(LayoutSource { channels: 2 }));
+
+        let mut buffer = vec![1.0f32; 6 * 4096];
+        let written = manager.process_audio(&mut buffer).unwrap();
+        assert_eq!(written, buffer.len());
+
+        let last = &buffer[buffer.len() - 6..];
+        assert!(last[0].abs() > 0.1 && last[1].abs() > 0.1);
+        assert!(last[2..].iter().all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn crossfade_timing_follows_playback_speed() {
+        let mut manager =
+            PlaybackManager::new(PlaybackConfig::with_crossfade(2000, FadeCurve::EqualPower));
+        manager.current_track = Some(create_test_track("slow"));
+        manager.set_audio_source(Box::new(DummyAudioSource::new(
+            Duration::from_secs(10),
+            44100,
+        )));
+        assert_eq!(manager.time_until_crossfade(), Some(Duration::from_secs(8)));
+
+        // 10s of track at 2x is 5s of real time; the fade still lasts 2s
+        manager.set_playback_rate(PlaybackRate::with_speed(2.0));
+        assert_eq!(manager.get_current_playback_rate().speed, 2.0);
+        assert_eq!(manager.time_until_crossfade(), Some(Duration::from_secs(3)));
+
+        // Position advances in source time
+        let mut buffer = vec![0.0f32; 2 * 44100];
+        manager.process_audio(&mut buffer).unwrap();
+        assert!((manager.get_position().as_secs_f64() - 2.0).abs() < 0.01);
+
+        // A per-track override wins over the default
+        manager.set_current_track_playback_rate(Some(PlaybackRate::NORMAL));
+        assert!(manager.get_current_playback_rate().is_normal());
+    }
+
+    /// Source that plays but cannot seek
+    struct UnseekableSource(DummyAudioSource);
+
+    impl AudioSource for UnseekableSource {
+        fn read_samples(&mut self, buffer: &mut [f32]) -> Result<usize> {
+            self.0.read_samples(buffer)
+        }
+
+        fn seek(&mut self, _position: Duration) -> Result<()> {
+            Err(PlaybackError::AudioSource("Seek failed".to_string()))
+        }
+
+        fn duration(&self) -> Duration {
+            self.0.duration()
+        }
+
+        fn position(&self) -> Duration {
+            self.0.position()
+        }
+
+        fn is_finished(&self) -> bool {
+            self.0.is_finished()
+        }
+    }
+
+    #[test]
+    fn failed_resync_after_rate_change_emits_error() {
+        let mut manager = PlaybackManager::default();
+        manager.current_track = Some(create_test_track("1"));
+        manager.set_playback_rate(PlaybackRate::with_speed(1.5));
+        manager.set_audio_source(Box::new(UnseekableSource(DummyAudioSource::new(
+            Duration::from_secs(10),
+            44100,
+        ))));
+        manager.drain_events();
+
+        // Leaving stretch mode seeks the source back to the audible position
+        manager.set_playback_rate(PlaybackRate::NORMAL);
+        assert!(manager
+            .drain_events()
+            .iter()
+            .any(|event| matches!(event, PlaybackEvent::Error { .. })));
+    }
+
+    #[test]
+    fn crossfade_timing_follows_cue_points() {
+        let mut manager =
+            PlaybackManager::new(PlaybackConfig::with_crossfade(2000, FadeCurve::EqualPower));
+        let mut current = create_test_track("faded");
+        current.cue_points = Some(CuePoints {
+            cue_in: Duration::ZERO,
+            cue_out: Duration::from_secs(8),
+            fade_out: Some(Duration::from_secs(4)),
+        });
+        manager.current_track = Some(current.clone());
+        manager.set_audio_source(Box::new(DummyAudioSource::new(
+            Duration::from_secs(10),
+            44100,
+        )));
+
+        // The crossfade covers the 4s natural fade and ends at cue-out
+        assert_eq!(manager.time_until_crossfade(), Some(Duration::from_secs(4)));
+
+        // Without a fade it is the configured 2s before cue-out
+        current.cue_points = Some(CuePoints {
+            fade_out: None,
+            ..current.cue_points.unwrap()
+        });
+        manager.current_track = Some(current);
+        assert_eq!(manager.time_until_crossfade(), Some(Duration::from_secs(6)));
+
+        // The next track on the same album plays on gaplessly: cue points ignored
+        let mut next = create_test_track("next");
+        next.track_number = Some(2);
+        next.cue_points = Some(CuePoints {
+            cue_in: Duration::from_millis(1500),
+            cue_out: Duration::from_secs(9),
+            fade_out: None,
+        });
+        manager.set_next_source(
+            Box::new(DummyAudioSource::new(Duration::from_secs(10), 44100)),
+            next.clone(),
+        );
+        assert_eq!(manager.time_until_crossfade(), Some(Duration::from_secs(8)));
+        assert_eq!(
+            manager.next_source.as_ref().unwrap().position(),
+            Duration::ZERO
+        );
+
+        // A track from elsewhere starts at its cue-in
+        next.album = Some("Other Album".to_string());
+        manager.set_next_source(
+            Box::new(DummyAudioSource::new(Duration::from_secs(10), 44100)),
+            next,
+        );
+        assert_eq!(manager.time_until_crossfade(), Some(Duration::from_secs(6)));
+        assert_eq!(
+            manager.next_source.as_ref().unwrap().position(),
+            Duration::from_millis(1500)
+        );
+    }
+
+    #[test]
+    fn map_channels_downmixes_to_mono() {
+        let input = [0.25, 0.75, -0.5, 0.0];
+        let mut output = [0.0; 2];
+        map_channels(&input, 2, &mut output, 1);
+        assert_eq!(output, [0.5, -0.25]);
+    }
+
+    #[test]
+    fn gain_offset_ramps_to_target_without_jumps() {
+        let mut manager = PlaybackManager::default();
+        assert_eq!(manager.get_gain_offset_db(), 0.0);
+
+        manager.set_gain_offset_db(-6.0);
+        assert_eq!(manager.get_gain_offset_db(), -6.0);
+
+        // First buffer ramps from unity towards the target, frame by frame
+        let mut buffer = vec![1.0f32; 8];
+        manager.gain_offset.process(&mut buffer, 2);
+        let target = 10.0_f32.powf(-6.0 / 20.0);
+        for frame in buffer.chunks_exact(2) {
+            assert_eq!(frame[0], frame[1]);
+        }
+        assert!(buffer.windows(2).all(|w| w[1] <= w[0]));
+        assert!(buffer[0] < 1.0 && buffer[0] > target);
+        assert!((buffer[7] - target).abs() < 1e-6);
+
+        // Following buffers get the steady gain
+        let mut buffer = vec![1.0f32; 8];
+        manager.gain_offset.process(&mut buffer, 2);
+        assert!(buffer.iter().all(|&s| (s - target).abs() < 1e-6));
+    }
+
+    /// Source playing a fixed number of samples at a constant level
+    struct FiniteSource {
+        level: f32,
+        remaining: usize,
+    }
+
+    impl AudioSource for FiniteSource {
+        fn read_samples(&mut self, buffer: &mut [f32]) -> Result<usize> {
+            let n = buffer.len().min(self.remaining);
+            buffer[..n].fill(self.level);
+            buffer[n..].fill(0.0);
+            self.remaining -= n;
+            Ok(n)
+        }
+
+        fn seek(&mut self, _position: Duration) -> Result<()> {
+            Ok(())
+        }
+
+        fn duration(&self) -> Duration {
+            Duration::from_secs(10)
+        }
+
+        fn position(&self) -> Duration {
+            Duration::ZERO
+        }
+
+        fn is_finished(&self) -> bool {
+            self.remaining == 0
+        }
+    }
+
+    #[test]
+    fn gapless_transition_continues_in_the_same_buffer() {
+        let mut manager = PlaybackManager::default();
+        manager.set_volume(100);
+        manager.set_audio_source(Box::new(FiniteSource {
+            level: 0.25,
+            remaining: 4 * 1024 + 476,
+        }));
+        manager.set_next_source(
+            Box::new(FiniteSource {
+                level: 0.5,
+                remaining: 4096,
+            }),
+            create_test_track("2"),
+        );
+
+        // Play past the start fade
+        let mut buffer = vec![0.0f32; 1024];
+        for _ in 0..4 {
+            manager.process_audio(&mut buffer).unwrap();
+        }
+
+        // The output limiter's lookahead delays everything (in frames)
+        #[cfg(feature = "volume-leveling")]
+        let boundary = 476 + 2 * manager.get_output_limiter_latency();
+        #[cfg(not(feature = "volume-leveling"))]
+        let boundary = 476;
+
+        // The first track ends part way through: the next one fills the rest
+        let written = manager.process_audio(&mut buffer).unwrap();
+        assert_eq!(written, buffer.len());
+        assert!(buffer[..boundary].iter().all(|&s| (s - 0.25).abs() < 1e-3));
+        assert!(buffer[boundary..].iter().all(|&s| (s - 0.5).abs() < 1e-3));
+        assert!(!manager.has_next_source());
+    }
+
+    #[test]
+    fn next_track_dsp_switches_at_the_gapless_boundary() {
+        let mut manager = PlaybackManager::default();
+        manager.set_volume(100);
+        manager.set_audio_source(Box::new(FiniteSource {
+            level: 0.25,
+            remaining: 4 * 1024 + 476,
+        }));
+        manager.set_next_source_with_dsp(
+            Box::new(FiniteSource {
+                level: 0.5,
+                remaining: 4096,
+            }),
+            create_test_track("2"),
+            TrackDsp {
+                gain_offset_db: -12.0,
+                ..TrackDsp::default()
+            },
+        );
+
+        let mut buffer = vec![0.0f32; 1024];
+        for _ in 0..4 {
+            manager.process_audio(&mut buffer).unwrap();
+        }
+        assert_eq!(manager.get_gain_offset_db(), 0.0);
+
+        #[cfg(feature = "volume-leveling")]
+        let boundary = 476 + 2 * manager.get_output_limiter_latency();
+        #[cfg(not(feature = "volume-leveling"))]
+        let boundary = 476;
+
+        // The first track plays to its end with its own gain, the next one
+        // ramps to its offset from the boundary on
+        manager.process_audio(&mut buffer).unwrap();
+        assert_eq!(manager.get_gain_offset_db(), -12.0);
+        assert!(buffer[..boundary].iter().all(|&s| (s - 0.25).abs() < 1e-3));
+        let target = 0.5 * 10.0_f32.powf(-12.0 / 20.0);
+        assert!(buffer[boundary..]
+            .iter()
+            .all(|&s| s < 0.5 && s > target - 1e-3));
+
+        // Steady once the ramp has passed the limiter's lookahead
+        for _ in 0..2 {
+            manager.process_audio(&mut buffer).unwrap();
+        }
+        assert!(buffer.iter().all(|&s| (s - target).abs() < 1e-3));
+    }
 }
@@ -120,6 +120,7 @@ mod tests {
             duration: Duration::from_secs(180),
             track_number: Some(1),
             source: TrackSource::Single,
+            ..Default::default()
         }
     }
 
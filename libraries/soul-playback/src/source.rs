@@ -14,10 +14,11 @@ pub trait AudioSource: Send {
     /// Read next chunk of audio samples
     ///
     /// Returns number of samples read (can be less than buffer length at end of track).
-    /// Samples are interleaved stereo f32 in [-1.0, 1.0] range.
+    /// Samples are interleaved f32 in [-1.0, 1.0] range, with `channels()`
+    /// samples per frame.
     ///
     /// # Arguments
-    /// * `buffer` - Output buffer for samples (length must be even for stereo)
+    /// * `buffer` - Output buffer for samples (length must be a multiple of `channels()`)
     ///
     /// # Returns
     /// * `Ok(n)` - Number of samples read (0 = end of track)
@@ -51,6 +52,32 @@ pub trait AudioSource: Send {
     fn reset(&mut self) -> Result<()> {
         self.seek(Duration::ZERO)
     }
+
+    /// Number of interleaved channels produced by `read_samples`
+    ///
+    /// Defaults to stereo. PlaybackManager maps other channel counts to the
+    /// output channel count.
+    fn channels(&self) -> u16 {
+        2
+    }
+
+    /// Check if the samples are an encoded bitstream rather than audio
+    ///
+    /// Bitstream sources (e.g. DSD over PCM) must reach the device bit-exact:
+    /// PlaybackManager skips fades, crossfades, loudness, effects, volume and
+    /// the limiter for them, and output stages must not dither.
+    fn is_bitstream(&self) -> bool {
+        false
+    }
+
+    /// Seconds of source time played per second of output
+    ///
+    /// Above 1.0 when the source is time-stretched to play faster.
+    /// PlaybackManager divides remaining source time by this to schedule
+    /// crossfades in real time.
+    fn playback_speed(&self) -> f32 {
+        1.0
+    }
 }
 
 /// Dummy audio source for testing
@@ -0,0 +1,780 @@
+//! Time stretching for variable playback speed
+//!
+//! Uses WSOLA (waveform similarity overlap-add): ~40ms grains are taken from
+//! the input at the playback speed and spliced where the waveform lines up
+//! best with the previous grain, then cross-faded with a Hann window. This
+//! changes speed without the "chipmunk" pitch change.
+//!
+//! Pitch shifting stretches by the pitch ratio as well and resamples the
+//! result back to length, so speed and pitch can be set independently.
+//!
+//! [`StretchedSource`] wraps an [`AudioSource`] so PlaybackManager can switch
+//! stretching on per track while positions stay in source time.
+
+use crate::error::Result;
+use crate::source::AudioSource;
+use crate::types::PlaybackRate;
+use std::f64::consts::PI;
+use std::time::Duration;
+
+/// Synthesis hop in milliseconds (grains are twice this long)
+const HOP_MS: usize = 20;
+
+/// Input buffer length in hops
+///
+/// One grain needs up to ~9 hops of input at 3x speed with a -12 semitone
+/// shift (6 hops of advance, 2 of grain, 1 of search tolerance).
+const INPUT_HOPS: usize = 16;
+
+/// Stretched (pre-resampling) buffer length in hops
+const STRETCHED_HOPS: usize = 4;
+
+/// WSOLA time stretcher with optional pitch shift
+///
+/// Push interleaved input with [`push_input`](Self::push_input) and pull
+/// stretched output with [`pull_output`](Self::pull_output). All buffers are
+/// allocated up front, so both are safe to call from the audio thread.
+pub struct TimeStretcher {
+    channels: usize,
+
+    /// Synthesis hop in frames (grain length is `2 * hop`)
+    hop: usize,
+
+    /// Search radius around the nominal grain position (frames)
+    tolerance: usize,
+
+    /// Offset step of the coarse similarity search (frames)
+    search_step: usize,
+
+    /// Periodic Hann window of `2 * hop` frames (halves sum to unity)
+    window: Vec<f32>,
+
+    /// Buffered input (interleaved), `input_frames` valid
+    input: Vec<f32>,
+    input_frames: usize,
+
+    /// No more input will arrive; input past `end_frame` is padding
+    input_ended: bool,
+    end_frame: usize,
+
+    /// Whether the first grain has been emitted
+    started: bool,
+
+    /// Start frame (in `input`) of the last grain
+    prev_start: usize,
+
+    /// Nominal start frame (in `input`) of the next grain
+    analysis_pos: f64,
+
+    /// Input frames advanced per grain (hop * speed / pitch ratio)
+    grain_advance: f64,
+
+    /// Stretched output waiting to be resampled (interleaved)
+    stretched: Vec<f32>,
+    stretched_frames: usize,
+
+    /// Fractional read position in `stretched`
+    read_pos: f64,
+
+    /// Resampling step (1.0 = no pitch shift)
+    pitch_ratio: f64,
+}
+
+impl TimeStretcher {
+    /// Create a stretcher at normal speed
+    pub fn new(sample_rate: u32, channels: u16) -> Self {
+        let channels = (channels as usize).max(1);
+        let hop = (sample_rate as usize * HOP_MS / 1000).max(16);
+        let grain = hop * 2;
+
+        let window = (0..grain)
+            .map(|n| (0.5 - 0.5 * (2.0 * PI * n as f64 / grain as f64).cos()) as f32)
+            .collect();
+
+        Self {
+            channels,
+            hop,
+            tolerance: hop / 2,
+            // Keep the search cost independent of sample rate (~4 frames at 44.1 kHz)
+            search_step: (sample_rate as usize / 11025).max(1),
+            window,
+            input: vec![0.0; INPUT_HOPS * hop * channels],
+            input_frames: 0,
+            input_ended: false,
+            end_frame: 0,
+            started: false,
+            prev_start: 0,
+            analysis_pos: 0.0,
+            grain_advance: hop as f64,
+            stretched: vec![0.0; STRETCHED_HOPS * hop * channels],
+            stretched_frames: 0,
+            read_pos: 0.0,
+            pitch_ratio: 1.0,
+        }
+    }
+
+    /// Set speed and pitch
+    ///
+    /// Takes effect from the next grain, so it can be changed while playing.
+    pub fn set_rate(&mut self, rate: PlaybackRate) {
+        self.pitch_ratio = rate.pitch_ratio() as f64;
+        self.grain_advance = self.hop as f64 * rate.speed as f64 / self.pitch_ratio;
+        if self.pitch_ratio == 1.0 {
+            self.read_pos = self.read_pos.floor();
+        }
+    }
+
+    /// Number of interleaved channels
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// Free input space in samples
+    pub fn input_space(&self) -> usize {
+        if self.input_ended {
+            return 0;
+        }
+        (self.input.len() / self.channels - self.input_frames) * self.channels
+    }
+
+    /// Buffer interleaved input samples
+    ///
+    /// Returns the number of samples accepted (whole frames, up to
+    /// [`input_space`](Self::input_space)).
+    pub fn push_input(&mut self, samples: &[f32]) -> usize {
+        let ch = self.channels;
+        let count = samples.len().min(self.input_space()) / ch * ch;
+        let start = self.input_frames * ch;
+        self.input[start..start + count].copy_from_slice(&samples[..count]);
+        self.input_frames += count / ch;
+        count
+    }
+
+    /// Signal end of input so the buffered tail is played out
+    pub fn finish_input(&mut self) {
+        if !self.input_ended {
+            self.input_ended = true;
+            self.end_frame = self.input_frames;
+        }
+    }
+
+    /// Pull stretched output into `output` (interleaved)
+    ///
+    /// Returns the number of samples written. Less than `output.len()` means
+    /// more input is needed, or the stretcher is drained.
+    pub fn pull_output(&mut self, output: &mut [f32]) -> usize {
+        let len = output.len() / self.channels * self.channels;
+        let mut written = 0;
+
+        while written < len {
+            written += self.read_stretched(&mut output[written..len]);
+            if written == len || !self.process_grain() {
+                break;
+            }
+        }
+
+        written
+    }
+
+    /// Check if all input has been played out after [`finish_input`](Self::finish_input)
+    pub fn is_drained(&self) -> bool {
+        self.grains_done() && self.read_pos >= self.stretched_frames as f64
+    }
+
+    /// Clear all buffered audio (e.g. after a seek)
+    pub fn reset(&mut self) {
+        self.input_frames = 0;
+        self.input_ended = false;
+        self.end_frame = 0;
+        self.started = false;
+        self.prev_start = 0;
+        self.analysis_pos = 0.0;
+        self.stretched_frames = 0;
+        self.read_pos = 0.0;
+    }
+
+    /// Check if every grain of real input has been emitted
+    fn grains_done(&self) -> bool {
+        self.input_ended
+            && (self.end_frame == 0
+                || (self.started && self.prev_start + self.hop >= self.end_frame))
+    }
+
+    /// Input frames needed in the buffer to emit the next grain
+    fn grain_needed_frames(&self) -> usize {
+        if !self.started {
+            return self.hop * 2;
+        }
+        let target = self.analysis_pos.round() as usize;
+        (self.prev_start + self.hop * 2).max(target + self.tolerance + self.hop * 2)
+    }
+
+    /// Emit one hop of stretched output, if enough input is buffered
+    fn process_grain(&mut self) -> bool {
+        if self.grains_done() {
+            return false;
+        }
+
+        let ch = self.channels;
+        let hop = self.hop;
+        let needed = self.grain_needed_frames();
+        if needed > self.input_frames {
+            if !self.input_ended {
+                return false;
+            }
+            // Pad the tail with silence
+            self.input[self.input_frames * ch..needed * ch].fill(0.0);
+            self.input_frames = needed;
+        }
+
+        self.compact_stretched();
+        if self.stretched_frames + hop > self.stretched.len() / ch {
+            return false;
+        }
+
+        let dst = self.stretched_frames * ch;
+        if !self.started {
+            // First grain: nothing to overlap with yet
+            self.stretched[dst..dst + hop * ch].copy_from_slice(&self.input[..hop * ch]);
+            self.prev_start = 0;
+            self.analysis_pos = self.grain_advance;
+            self.started = true;
+        } else {
+            // Cross-fade the tail of the last grain into the best-matching new grain
+            let template = self.prev_start + hop;
+            let start = self.best_offset(template, self.analysis_pos.round() as usize);
+
+            let (fade_ins, fade_outs) = self.window.split_at(hop);
+            for (i, (&fade_in, &fade_out)) in fade_ins.iter().zip(fade_outs).enumerate() {
+                for c in 0..ch {
+                    self.stretched[dst + i * ch + c] = self.input[(template + i) * ch + c]
+                        * fade_out
+                        + self.input[(start + i) * ch + c] * fade_in;
+                }
+            }
+
+            self.prev_start = start;
+            self.analysis_pos += self.grain_advance;
+        }
+
+        self.stretched_frames += hop;
+        self.compact_input();
+        true
+    }
+
+    /// Find the grain start near `target` that best continues `template`
+    ///
+    /// Coarse search over the tolerance window on a decimated signal, then
+    /// a full-resolution search around the coarse winner.
+    fn best_offset(&self, template: usize, target: usize) -> usize {
+        let lo = target.saturating_sub(self.tolerance);
+        let hi = target + self.tolerance;
+        let step = self.search_step;
+
+        let mut best = target;
+        let mut best_score = self.similarity(template, target, step);
+        for candidate in (lo..=hi).step_by(step) {
+            let score = self.similarity(template, candidate, step);
+            if score > best_score {
+                best = candidate;
+                best_score = score;
+            }
+        }
+
+        let fine_lo = best.saturating_sub(step - 1).max(lo);
+        let fine_hi = (best + step - 1).min(hi);
+        let mut fine_best = best;
+        let mut fine_score = self.similarity(template, best, 1);
+        for candidate in fine_lo..=fine_hi {
+            let score = self.similarity(template, candidate, 1);
+            if score > fine_score {
+                fine_best = candidate;
+                fine_score = score;
+            }
+        }
+
+        fine_best
+    }
+
+    /// Normalized cross-correlation of the mono sums of two hop-length segments
+    fn similarity(&self, template: usize, candidate: usize, stride: usize) -> f32 {
+        let ch = self.channels;
+        let mut dot = 0.0f32;
+        let mut energy = 0.0f32;
+
+        for i in (0..self.hop).step_by(stride) {
+            let a: f32 = self.input[(template + i) * ch..][..ch].iter().sum();
+            let b: f32 = self.input[(candidate + i) * ch..][..ch].iter().sum();
+            dot += a * b;
+            energy += b * b;
+        }
+
+        dot / (energy + 1e-9).sqrt()
+    }
+
+    /// Resample stretched frames into `output` at the pitch ratio
+    fn read_stretched(&mut self, output: &mut [f32]) -> usize {
+        let ch = self.channels;
+        let frames = output.len() / ch;
+
+        if self.pitch_ratio == 1.0 {
+            let pos = self.read_pos as usize;
+            let count = self.stretched_frames.saturating_sub(pos).min(frames);
+            output[..count * ch].copy_from_slice(&self.stretched[pos * ch..(pos + count) * ch]);
+            self.read_pos += count as f64;
+            return count * ch;
+        }
+
+        let done = self.grains_done();
+        let mut produced = 0;
+        while produced < frames {
+            let index = self.read_pos.floor() as usize;
+            // Cubic interpolation needs two frames ahead until the tail
+            if index >= self.stretched_frames || (index + 2 >= self.stretched_frames && !done) {
+                break;
+            }
+
+            let t = (self.read_pos - index as f64) as f32;
+            for c in 0..ch {
+                output[produced * ch + c] = hermite(
+                    self.stretched_sample(index as isize - 1, c),
+                    self.stretched_sample(index as isize, c),
+                    self.stretched_sample(index as isize + 1, c),
+                    self.stretched_sample(index as isize + 2, c),
+                    t,
+                );
+            }
+
+            produced += 1;
+            self.read_pos += self.pitch_ratio;
+        }
+
+        produced * ch
+    }
+
+    /// Stretched sample at `frame` (silence outside the buffer)
+    fn stretched_sample(&self, frame: isize, channel: usize) -> f32 {
+        if frame < 0 || frame as usize >= self.stretched_frames {
+            0.0
+        } else {
+            self.stretched[frame as usize * self.channels + channel]
+        }
+    }
+
+    /// Drop input no longer reachable by the next grain
+    fn compact_input(&mut self) {
+        let target = self.analysis_pos as usize;
+        let drop = self
+            .prev_start
+            .min(target.saturating_sub(self.tolerance))
+            .min(self.input_frames);
+        if drop == 0 {
+            return;
+        }
+
+        let ch = self.channels;
+        self.input.copy_within(drop * ch..self.input_frames * ch, 0);
+        self.input_frames -= drop;
+        self.prev_start -= drop;
+        self.analysis_pos -= drop as f64;
+        self.end_frame = self.end_frame.saturating_sub(drop);
+    }
+
+    /// Drop stretched frames already read (keeping one frame of history)
+    fn compact_stretched(&mut self) {
+        let drop = (self.read_pos as usize)
+            .saturating_sub(1)
+            .min(self.stretched_frames);
+        if drop == 0 {
+            return;
+        }
+
+        let ch = self.channels;
+        self.stretched
+            .copy_within(drop * ch..self.stretched_frames * ch, 0);
+        self.stretched_frames -= drop;
+        self.read_pos -= drop as f64;
+    }
+}
+
+/// 4-point cubic Hermite interpolation between `p1` and `p2`
+#[inline]
+fn hermite(p0: f32, p1: f32, p2: f32, p3: f32, t: f32) -> f32 {
+    let c1 = 0.5 * (p2 - p0);
+    let c2 = p0 - 2.5 * p1 + 2.0 * p2 - 0.5 * p3;
+    let c3 = 0.5 * (p3 - p0) + 1.5 * (p1 - p2);
+    ((c3 * t + c2) * t + c1) * t + p1
+}
+
+/// Audio source played back at a [`PlaybackRate`]
+///
+/// Passes samples through untouched at normal rate. Otherwise reads the
+/// inner source through a [`TimeStretcher`]. `position()` and `duration()`
+/// stay in source time, and `playback_speed()` reports how fast source time
+/// advances relative to output time.
+pub struct StretchedSource {
+    inner: Box<dyn AudioSource>,
+    rate: PlaybackRate,
+    sample_rate: u32,
+
+    /// Present while stretching (rate is not normal)
+    stretcher: Option<TimeStretcher>,
+
+    /// Read buffer for the inner source
+    scratch: Vec<f32>,
+
+    /// Source position where stretching last (re)started
+    base_position: Duration,
+
+    /// Source frames played out since `base_position`
+    source_frames: f64,
+
+    /// Inner source has returned its last samples
+    inner_finished: bool,
+}
+
+impl StretchedSource {
+    /// Wrap `inner`, producing output at `sample_rate`
+    pub fn new(inner: Box<dyn AudioSource>, sample_rate: u32, rate: PlaybackRate) -> Self {
+        let stretch = !rate.is_normal() && !inner.is_bitstream();
+        let mut source = Self {
+            inner,
+            rate,
+            sample_rate,
+            stretcher: None,
+            scratch: Vec::new(),
+            base_position: Duration::ZERO,
+            source_frames: 0.0,
+            inner_finished: false,
+        };
+        if stretch {
+            source.start_stretching();
+        }
+        source
+    }
+
+    /// Requested playback rate
+    pub fn rate(&self) -> PlaybackRate {
+        self.rate
+    }
+
+    /// Check if samples currently go through the time stretcher
+    pub fn is_stretching(&self) -> bool {
+        self.stretcher.is_some()
+    }
+
+    /// Change the playback rate mid-track
+    ///
+    /// Bitstream sources always play at normal rate. Allocates when
+    /// stretching starts, so call from the control thread.
+    ///
+    /// Returning to normal rate seeks the inner source back to the audible
+    /// position; if that fails, playback would continue from the
+    /// stretcher's read-ahead, so the error is returned.
+    pub fn set_rate(&mut self, rate: PlaybackRate) -> Result<()> {
+        self.rate = rate;
+        let stretch = !rate.is_normal() && !self.inner.is_bitstream();
+
+        if !stretch {
+            if self.stretcher.is_some() {
+                // Resume direct reads where the listener is, not where the
+                // stretcher's read-ahead left the decoder
+                let position = self.position();
+                self.stretcher = None;
+                self.scratch = Vec::new();
+                self.inner.seek(position)?;
+            }
+        } else if let Some(stretcher) = &mut self.stretcher {
+            stretcher.set_rate(rate);
+        } else {
+            self.start_stretching();
+        }
+        Ok(())
+    }
+
+    /// Update the output sample rate
+    ///
+    /// While stretching, the stretcher is rebuilt and the inner source
+    /// seeks back to the audible position; a failed seek is returned.
+    pub fn set_sample_rate(&mut self, sample_rate: u32) -> Result<()> {
+        if sample_rate == self.sample_rate {
+            return Ok(());
+        }
+
+        if self.stretcher.is_some() {
+            let position = self.position();
+            self.sample_rate = sample_rate;
+            self.start_stretching();
+            self.seek(position)
+        } else {
+            self.sample_rate = sample_rate;
+            Ok(())
+        }
+    }
+
+    /// Route reads through a new stretcher from the current source position
+    fn start_stretching(&mut self) {
+        let mut stretcher = TimeStretcher::new(self.sample_rate, self.inner.channels());
+        stretcher.set_rate(self.rate);
+        self.scratch = vec![0.0; stretcher.input_space()];
+        self.stretcher = Some(stretcher);
+        self.base_position = self.inner.position();
+        self.source_frames = 0.0;
+        self.inner_finished = false;
+    }
+}
+
+impl AudioSource for StretchedSource {
+    fn read_samples(&mut self, buffer: &mut [f32]) -> Result<usize> {
+        let Some(stretcher) = &mut self.stretcher else {
+            return self.inner.read_samples(buffer);
+        };
+
+        let ch = stretcher.channels();
+        let mut written = 0;
+        while written < buffer.len() {
+            written += stretcher.pull_output(&mut buffer[written..]);
+            if written == buffer.len() || stretcher.is_drained() {
+                break;
+            }
+
+            // Refill the stretcher from the inner source
+            let space = stretcher.input_space().min(self.scratch.len()) / ch * ch;
+            if self.inner_finished || space == 0 {
+                break;
+            }
+            let read = self.inner.read_samples(&mut self.scratch[..space])?;
+            if read == 0 {
+                self.inner_finished = true;
+                stretcher.finish_input();
+            } else {
+                stretcher.push_input(&self.scratch[..read]);
+            }
+        }
+
+        self.source_frames += (written / ch) as f64 * self.rate.speed as f64;
+        Ok(written)
+    }
+
+    fn seek(&mut self, position: Duration) -> Result<()> {
+        self.inner.seek(position)?;
+        if let Some(stretcher) = &mut self.stretcher {
+            stretcher.reset();
+            self.base_position = self.inner.position();
+            self.source_frames = 0.0;
+            self.inner_finished = false;
+        }
+        Ok(())
+    }
+
+    fn duration(&self) -> Duration {
+        self.inner.duration()
+    }
+
+    fn position(&self) -> Duration {
+        if self.stretcher.is_none() {
+            return self.inner.position();
+        }
+
+        let played = Duration::from_secs_f64(self.source_frames / self.sample_rate.max(1) as f64);
+        let position = self.base_position + played;
+        let duration = self.inner.duration();
+        if duration > Duration::ZERO {
+            position.min(duration)
+        } else {
+            position
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        match &self.stretcher {
+            Some(stretcher) => stretcher.is_drained(),
+            None => self.inner.is_finished(),
+        }
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn is_bitstream(&self) -> bool {
+        self.inner.is_bitstream()
+    }
+
+    fn playback_speed(&self) -> f32 {
+        if self.stretcher.is_some() {
+            self.rate.speed
+        } else {
+            1.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::DummyAudioSource;
+
+    const SAMPLE_RATE: u32 = 44100;
+
+    /// Stereo sine test source
+    struct SineSource {
+        frequency: f32,
+        frames: usize,
+        position: usize,
+    }
+
+    impl SineSource {
+        fn new(frequency: f32, seconds: f32) -> Self {
+            Self {
+                frequency,
+                frames: (seconds * SAMPLE_RATE as f32) as usize,
+                position: 0,
+            }
+        }
+    }
+
+    impl AudioSource for SineSource {
+        fn read_samples(&mut self, buffer: &mut [f32]) -> Result<usize> {
+            let frames = (buffer.len() / 2).min(self.frames - self.position);
+            for frame in buffer.chunks_exact_mut(2).take(frames) {
+                let t = self.position as f32 / SAMPLE_RATE as f32;
+                let value = (2.0 * std::f32::consts::PI * self.frequency * t).sin() * 0.5;
+                frame.fill(value);
+                self.position += 1;
+            }
+            Ok(frames * 2)
+        }
+
+        fn seek(&mut self, position: Duration) -> Result<()> {
+            self.position =
+                ((position.as_secs_f64() * SAMPLE_RATE as f64) as usize).min(self.frames);
+            Ok(())
+        }
+
+        fn duration(&self) -> Duration {
+            Duration::from_secs_f64(self.frames as f64 / SAMPLE_RATE as f64)
+        }
+
+        fn position(&self) -> Duration {
+            Duration::from_secs_f64(self.position as f64 / SAMPLE_RATE as f64)
+        }
+
+        fn is_finished(&self) -> bool {
+            self.position >= self.frames
+        }
+    }
+
+    /// Read a source to the end, returning the left channel
+    fn render(source: &mut StretchedSource) -> Vec<f32> {
+        let mut buffer = vec![0.0; 1024];
+        let mut left = Vec::new();
+        loop {
+            let read = source.read_samples(&mut buffer).unwrap();
+            if read == 0 {
+                break;
+            }
+            left.extend(buffer[..read].iter().step_by(2));
+        }
+        left
+    }
+
+    /// Estimate frequency from rising zero crossings (skipping the edges)
+    fn estimate_frequency(samples: &[f32]) -> f32 {
+        let body = &samples[samples.len() / 4..samples.len() * 3 / 4];
+        let crossings = body
+            .windows(2)
+            .filter(|w| w[0] < 0.0 && w[1] >= 0.0)
+            .count();
+        crossings as f32 * SAMPLE_RATE as f32 / body.len() as f32
+    }
+
+    #[test]
+    fn normal_rate_is_bit_exact() {
+        let mut source = StretchedSource::new(
+            Box::new(SineSource::new(440.0, 0.5)),
+            SAMPLE_RATE,
+            PlaybackRate::NORMAL,
+        );
+        let mut reference = SineSource::new(440.0, 0.5);
+        assert!(!source.is_stretching());
+
+        let mut a = vec![0.0; 512];
+        let mut b = vec![0.0; 512];
+        source.read_samples(&mut a).unwrap();
+        reference.read_samples(&mut b).unwrap();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn speed_changes_length_not_pitch() {
+        for speed in [0.5, 1.5, 2.0, 3.0] {
+            let mut source = StretchedSource::new(
+                Box::new(SineSource::new(440.0, 2.0)),
+                SAMPLE_RATE,
+                PlaybackRate::with_speed(speed),
+            );
+            let output = render(&mut source);
+
+            // Within a couple of grains of the ideal length
+            let expected = 2.0 * SAMPLE_RATE as f32 / speed;
+            let error = (output.len() as f32 - expected).abs() / SAMPLE_RATE as f32;
+            assert!(
+                error < 0.05,
+                "speed {}: {} frames, expected {}",
+                speed,
+                output.len(),
+                expected
+            );
+
+            let frequency = estimate_frequency(&output);
+            assert!(
+                (frequency - 440.0).abs() < 10.0,
+                "speed {}: pitch drifted to {} Hz",
+                speed,
+                frequency
+            );
+        }
+    }
+
+    #[test]
+    fn pitch_shift_keeps_length() {
+        let mut source = StretchedSource::new(
+            Box::new(SineSource::new(440.0, 2.0)),
+            SAMPLE_RATE,
+            PlaybackRate::new(1.0, 12.0),
+        );
+        let output = render(&mut source);
+
+        let expected = 2.0 * SAMPLE_RATE as f32;
+        assert!(((output.len() as f32 - expected).abs() / SAMPLE_RATE as f32) < 0.05);
+
+        let frequency = estimate_frequency(&output);
+        assert!((frequency - 880.0).abs() < 20.0, "got {} Hz", frequency);
+    }
+
+    #[test]
+    fn position_stays_in_source_time() {
+        let mut source = StretchedSource::new(
+            Box::new(DummyAudioSource::new(Duration::from_secs(10), SAMPLE_RATE)),
+            SAMPLE_RATE,
+            PlaybackRate::with_speed(2.0),
+        );
+        assert_eq!(source.playback_speed(), 2.0);
+
+        // One second of output covers two seconds of the track
+        let mut buffer = vec![0.0; SAMPLE_RATE as usize * 2];
+        source.read_samples(&mut buffer).unwrap();
+        assert!((source.position().as_secs_f64() - 2.0).abs() < 0.01);
+
+        source.seek(Duration::from_secs(5)).unwrap();
+        assert_eq!(source.position(), Duration::from_secs(5));
+        source.read_samples(&mut buffer).unwrap();
+        assert!((source.position().as_secs_f64() - 7.0).abs() < 0.01);
+
+        // Back to normal resumes at the listener's position
+        source.set_rate(PlaybackRate::NORMAL).unwrap();
+        assert!(!source.is_stretching());
+        assert!((source.position().as_secs_f64() - 7.0).abs() < 0.01);
+    }
+}
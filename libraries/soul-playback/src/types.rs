@@ -12,7 +12,10 @@ pub use crate::crossfade::{CrossfadeSettings as CrossfadeConfig, FadeCurve};
 ///
 /// Contains all metadata needed for playback and display.
 /// This is eagerly loaded from storage to avoid I/O during playback.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+///
+/// Optional playback settings default to `None`, so constructors can fill
+/// in the metadata and end with `..Default::default()`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct QueueTrack {
     /// Unique track identifier from storage
     pub id: String,
@@ -37,12 +40,171 @@ pub struct QueueTrack {
 
     /// Source context for shuffle scope
     pub source: TrackSource,
+
+    /// Sub-range of the file to play (None = whole file)
+    ///
+    /// Set for virtual tracks from CUE sheets, where one file holds a whole disc.
+    #[serde(default)]
+    pub range: Option<TrackRange>,
+
+    /// Playback speed/pitch for this track (None = manager default)
+    ///
+    /// Lets audiobooks and podcasts play faster than music in the same queue.
+    #[serde(default)]
+    pub playback_rate: Option<PlaybackRate>,
+
+    /// Analysed cue points for smart crossfades (None = not analysed)
+    #[serde(default)]
+    pub cue_points: Option<CuePoints>,
+}
+
+impl QueueTrack {
+    /// Check if this track directly follows `previous` on the same album
+    ///
+    /// Transitions between such tracks are part of the recording, so they
+    /// play as usual and ignore cue points.
+    pub fn follows_on_album(&self, previous: &QueueTrack) -> bool {
+        self.album.is_some()
+            && self.album == previous.album
+            && matches!(
+                (previous.track_number, self.track_number),
+                (Some(prev), Some(next)) if next == prev + 1
+            )
+    }
+}
+
+/// Analysed transition points of a track, in track time
+///
+/// Crossfades out of a track end at its cue-out point (skipping a silent
+/// tail) and start no later than its natural fade-out; crossfades into a
+/// track start at its cue-in point (skipping leading silence).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CuePoints {
+    /// Where audio starts after leading silence
+    pub cue_in: Duration,
+
+    /// Where audio ends before trailing silence
+    pub cue_out: Duration,
+
+    /// Where the natural fade-out starts (None = the track doesn't fade out)
+    pub fade_out: Option<Duration>,
+}
+
+/// Sub-range of an audio file that makes up a track
+///
+/// Offsets are converted to sample positions by rounding, so boundaries on
+/// CD frames (1/75 s) land on exact samples at all common sample rates and
+/// consecutive ranges of the same file play back gaplessly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrackRange {
+    /// Start offset in the file
+    pub start: Duration,
+
+    /// End offset in the file (None = end of file)
+    pub end: Option<Duration>,
+}
+
+impl TrackRange {
+    /// Create a new range
+    pub fn new(start: Duration, end: Option<Duration>) -> Self {
+        Self { start, end }
+    }
+
+    /// Start offset in sample frames at the given sample rate
+    pub fn start_frame(&self, sample_rate: u32) -> u64 {
+        duration_to_frames(self.start, sample_rate)
+    }
+
+    /// End offset in sample frames at the given sample rate
+    pub fn end_frame(&self, sample_rate: u32) -> Option<u64> {
+        self.end.map(|end| duration_to_frames(end, sample_rate))
+    }
+
+    /// Length of the range, given the length of the whole file
+    pub fn length(&self, file_duration: Duration) -> Duration {
+        self.end
+            .unwrap_or(file_duration)
+            .min(file_duration)
+            .saturating_sub(self.start)
+    }
+}
+
+/// Convert a duration to the nearest sample frame
+fn duration_to_frames(duration: Duration, sample_rate: u32) -> u64 {
+    (duration.as_secs_f64() * sample_rate as f64).round() as u64
+}
+
+/// Playback speed and pitch
+///
+/// Speed changes are time-stretched, so pitch is preserved unless a pitch
+/// shift is set explicitly. Positions and durations stay in source time.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PlaybackRate {
+    /// Speed multiplier (0.5-3.0)
+    pub speed: f32,
+
+    /// Pitch shift in semitones (-12 to +12)
+    pub pitch_semitones: f32,
+}
+
+impl PlaybackRate {
+    /// Slowest supported speed
+    pub const MIN_SPEED: f32 = 0.5;
+
+    /// Fastest supported speed
+    pub const MAX_SPEED: f32 = 3.0;
+
+    /// Largest supported pitch shift in either direction (semitones)
+    pub const MAX_PITCH_SEMITONES: f32 = 12.0;
+
+    /// Normal speed, no pitch shift
+    pub const NORMAL: Self = Self {
+        speed: 1.0,
+        pitch_semitones: 0.0,
+    };
+
+    /// Create a rate, clamping to the supported ranges
+    pub fn new(speed: f32, pitch_semitones: f32) -> Self {
+        Self {
+            speed: if speed.is_finite() {
+                speed.clamp(Self::MIN_SPEED, Self::MAX_SPEED)
+            } else {
+                1.0
+            },
+            pitch_semitones: if pitch_semitones.is_finite() {
+                pitch_semitones.clamp(-Self::MAX_PITCH_SEMITONES, Self::MAX_PITCH_SEMITONES)
+            } else {
+                0.0
+            },
+        }
+    }
+
+    /// Create a rate that only changes speed
+    pub fn with_speed(speed: f32) -> Self {
+        Self::new(speed, 0.0)
+    }
+
+    /// Check if this rate leaves the audio untouched
+    pub fn is_normal(&self) -> bool {
+        self.speed == 1.0 && self.pitch_semitones == 0.0
+    }
+
+    /// Frequency ratio of the pitch shift (2.0 = one octave up)
+    pub fn pitch_ratio(&self) -> f32 {
+        2.0f32.powf(self.pitch_semitones / 12.0)
+    }
+}
+
+impl Default for PlaybackRate {
+    fn default() -> Self {
+        Self::NORMAL
+    }
 }
 
 /// Source context for a track
 ///
 /// Used to determine shuffle scope (e.g., shuffle within album only)
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub enum TrackSource {
     /// Track from a playlist
     Playlist { id: String, name: String },
@@ -54,6 +216,7 @@ pub enum TrackSource {
     Artist { id: String, name: String },
 
     /// Individual track (no context)
+    #[default]
     Single,
 }
 
@@ -185,9 +348,63 @@ mod tests {
                 id: "album1".to_string(),
                 name: "Test Album".to_string(),
             },
+            ..Default::default()
         };
 
         assert_eq!(track.id, "track1");
         assert_eq!(track.title, "Test Song");
     }
+
+    #[test]
+    fn follows_on_album_needs_consecutive_track_numbers() {
+        let track = |album: Option<&str>, number: Option<u32>| QueueTrack {
+            id: "t".to_string(),
+            path: PathBuf::from("/music/t.flac"),
+            title: "T".to_string(),
+            artist: "A".to_string(),
+            album: album.map(String::from),
+            duration: Duration::from_secs(180),
+            track_number: number,
+            source: TrackSource::Single,
+            ..Default::default()
+        };
+
+        let second = track(Some("Album"), Some(2));
+        assert!(track(Some("Album"), Some(3)).follows_on_album(&second));
+        assert!(!track(Some("Album"), Some(4)).follows_on_album(&second));
+        assert!(!track(Some("Album"), Some(1)).follows_on_album(&second));
+        assert!(!track(Some("Other"), Some(3)).follows_on_album(&second));
+        assert!(!track(None, Some(3)).follows_on_album(&track(None, Some(2))));
+        assert!(!track(Some("Album"), None).follows_on_album(&second));
+    }
+
+    #[test]
+    fn playback_rate_clamps_to_supported_range() {
+        let rate = PlaybackRate::new(5.0, -20.0);
+        assert_eq!(rate.speed, PlaybackRate::MAX_SPEED);
+        assert_eq!(rate.pitch_semitones, -PlaybackRate::MAX_PITCH_SEMITONES);
+        assert!((rate.pitch_ratio() - 0.5).abs() < 1e-6);
+
+        assert!(PlaybackRate::new(f32::NAN, 0.0).is_normal());
+        assert!(!PlaybackRate::with_speed(1.25).is_normal());
+    }
+
+    #[test]
+    fn track_range_frames_are_sample_accurate() {
+        // 04:00:01 in CD frames (1/75 s) is 588 samples past 4 minutes at 44.1 kHz
+        let start = Duration::from_nanos((240 * 75 + 1) * 1_000_000_000 / 75);
+        let range = TrackRange::new(start, Some(Duration::from_secs(300)));
+
+        assert_eq!(range.start_frame(44100), 240 * 44100 + 588);
+        assert_eq!(range.start_frame(48000), 240 * 48000 + 640);
+        assert_eq!(range.end_frame(44100), Some(300 * 44100));
+        assert_eq!(
+            range.length(Duration::from_secs(600)),
+            Duration::from_secs(300).saturating_sub(start)
+        );
+
+        // Open-ended range runs to the end of the file
+        let tail = TrackRange::new(Duration::from_secs(500), None);
+        assert_eq!(tail.length(Duration::from_secs(600)), Duration::from_secs(100));
+    }
 }
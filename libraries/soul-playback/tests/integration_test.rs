@@ -95,6 +95,7 @@ fn create_test_track(id: &str, title: &str, artist: &str, duration_secs: u64) ->
         duration: Duration::from_secs(duration_secs),
         track_number: Some(1),
         source: TrackSource::Single,
+        ..Default::default()
     }
 }
 
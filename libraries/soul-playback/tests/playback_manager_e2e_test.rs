@@ -151,6 +151,7 @@ fn create_track(id: &str, title: &str, artist: &str, duration_secs: u64) -> Queu
         duration: Duration::from_secs(duration_secs),
         track_number: Some(1),
         source: TrackSource::Single,
+        ..Default::default()
     }
 }
 
@@ -174,6 +175,7 @@ fn create_track_from_album(
             id: album_id.to_string(),
             name: album_name.to_string(),
         },
+        ..Default::default()
     }
 }
 
@@ -1117,6 +1119,7 @@ mod error_handling {
             duration: Duration::from_secs(180),
             track_number: None,
             source: TrackSource::Single,
+            ..Default::default()
         });
 
         // Play should enter loading state (actual file loading is platform-specific)
@@ -1506,6 +1509,7 @@ mod edge_cases {
             duration: Duration::from_secs(180),
             track_number: Some(1),
             source: TrackSource::Single,
+            ..Default::default()
         });
 
         let queue = manager.get_queue();
@@ -1530,6 +1534,7 @@ mod edge_cases {
             duration: Duration::from_nanos(123456789),
             track_number: None,
             source: TrackSource::Single,
+            ..Default::default()
         });
 
         assert_eq!(manager.queue_len(), 2);
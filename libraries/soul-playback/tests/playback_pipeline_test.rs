@@ -183,6 +183,7 @@ fn create_test_track(id: &str, title: &str, artist: &str, duration_secs: u64) ->
         duration: Duration::from_secs(duration_secs),
         track_number: Some(1),
         source: TrackSource::Single,
+        ..Default::default()
     }
 }
 
@@ -205,6 +206,7 @@ fn create_album_track(
             id: album.to_lowercase().replace(' ', "_"),
             name: album.to_string(),
         },
+        ..Default::default()
     }
 }
 
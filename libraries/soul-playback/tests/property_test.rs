@@ -30,6 +30,7 @@ fn arbitrary_track() -> impl Strategy<Value = QueueTrack> {
             duration: Duration::from_secs(duration_secs),
             track_number: Some(1),
             source: TrackSource::Single,
+            ..Default::default()
         })
 }
 
@@ -61,6 +62,7 @@ fn arbitrary_unique_tracks() -> impl Strategy<Value = Vec<QueueTrack>> {
                     duration: Duration::from_secs(duration_secs),
                     track_number: Some(1),
                     source: TrackSource::Single,
+                    ..Default::default()
                 })
                 .collect()
         })
@@ -171,6 +173,7 @@ proptest! {
                 duration: Duration::from_secs(180),
                 track_number: Some(1),
                 source: TrackSource::Single,
+                ..Default::default()
             });
         }
 
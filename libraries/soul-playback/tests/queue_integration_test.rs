@@ -19,6 +19,7 @@ fn create_track(id: &str, title: &str, artist: &str, duration_secs: u64) -> Queu
         duration: Duration::from_secs(duration_secs),
         track_number: Some(id.parse().unwrap_or(1)),
         source: TrackSource::Single,
+        ..Default::default()
     }
 }
 
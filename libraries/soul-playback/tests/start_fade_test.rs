@@ -141,6 +141,7 @@ fn create_test_track(id: &str) -> QueueTrack {
         duration: Duration::from_secs(10),
         track_number: Some(1),
         source: TrackSource::Single,
+        ..Default::default()
     }
 }
 
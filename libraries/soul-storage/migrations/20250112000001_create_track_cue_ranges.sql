@@ -0,0 +1,19 @@
+-- Virtual tracks from CUE sheets
+-- Album images (one FLAC/WAV/APE per disc + .cue) are imported as one track
+-- row per CUE track. Each row here marks its track as a sub-range of the file
+-- referenced by track_sources.local_file_path.
+--
+-- Offsets are stored in CD frames (1/75 second) exactly as written in the
+-- sheet, so boundaries convert to exact sample positions at playback time.
+
+CREATE TABLE IF NOT EXISTS track_cue_ranges (
+    track_id INTEGER PRIMARY KEY NOT NULL REFERENCES tracks(id) ON DELETE CASCADE,
+    cue_path TEXT NOT NULL,                  -- Path to the .cue sheet
+    cue_track_number INTEGER NOT NULL,       -- TRACK number in the sheet
+    start_frames INTEGER NOT NULL,           -- INDEX 01 of this track
+    end_frames INTEGER,                      -- INDEX 01 of the next track (NULL = end of file)
+    pregap_frames INTEGER NOT NULL DEFAULT 0 -- Length of the INDEX 00 pregap
+);
+
+-- Index for re-scanning / removing all tracks of a sheet
+CREATE INDEX IF NOT EXISTS idx_track_cue_ranges_cue_path ON track_cue_ranges(cue_path);
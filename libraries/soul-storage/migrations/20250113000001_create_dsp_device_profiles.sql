@@ -0,0 +1,21 @@
+-- DSP chain profiles bound to output devices
+-- Each row binds a saved DSP chain preset to one output device, identified by
+-- backend ("default", "asio", "jack") and device name. When playback switches
+-- to a device, the bound preset is loaded automatically.
+--
+-- The row with backend = '*' and device_name = '*' is the fallback profile,
+-- used for devices without a binding of their own.
+
+CREATE TABLE IF NOT EXISTS dsp_device_profiles (
+    user_id TEXT NOT NULL,
+    backend TEXT NOT NULL,
+    device_name TEXT NOT NULL,
+    preset_id INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    PRIMARY KEY (user_id, backend, device_name),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (preset_id) REFERENCES dsp_presets(id) ON DELETE CASCADE
+);
+
+-- Index for cleaning up bindings when a preset is deleted
+CREATE INDEX IF NOT EXISTS idx_dsp_device_profiles_preset ON dsp_device_profiles(preset_id);
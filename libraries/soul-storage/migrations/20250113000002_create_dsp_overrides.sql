@@ -0,0 +1,24 @@
+-- DSP and gain overrides for tracks, albums and genres
+-- An override attaches a saved DSP chain preset and/or a gain offset to one
+-- track, album or genre. The effective chain for a track is resolved with
+-- precedence track > album > genre > device profile > global chain; the
+-- preset and the gain offset are resolved independently.
+--
+-- target_id refers to tracks(id), albums(id) or genres(id) depending on
+-- scope, so it carries no foreign key; overrides of deleted items are
+-- simply never matched.
+
+CREATE TABLE IF NOT EXISTS dsp_overrides (
+    user_id TEXT NOT NULL,
+    scope TEXT NOT NULL CHECK (scope IN ('track', 'album', 'genre')),
+    target_id INTEGER NOT NULL,
+    preset_id INTEGER,                       -- NULL = keep the chain from the next level
+    gain_db REAL,                            -- NULL = no gain offset at this level
+    updated_at INTEGER NOT NULL,
+    PRIMARY KEY (user_id, scope, target_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (preset_id) REFERENCES dsp_presets(id) ON DELETE SET NULL
+);
+
+-- Index for clearing preset references when a preset is deleted
+CREATE INDEX IF NOT EXISTS idx_dsp_overrides_preset ON dsp_overrides(preset_id);
@@ -0,0 +1,15 @@
+-- Add dynamic range columns next to the loudness metadata on tracks
+-- Populated by the background loudness analysis, used to find brickwalled masters
+
+ALTER TABLE tracks ADD COLUMN dr_track REAL;  -- TT dynamic range in dB (unrounded, display rounded as "DR12")
+ALTER TABLE tracks ADD COLUMN dr_album REAL;  -- Album DR in dB (mean of the album's track DRs)
+ALTER TABLE tracks ADD COLUMN plr REAL;       -- Peak-to-loudness ratio in dB (true peak - integrated LUFS)
+
+-- Index for finding the least dynamic tracks
+CREATE INDEX IF NOT EXISTS idx_tracks_dr_track
+    ON tracks(dr_track) WHERE dr_track IS NOT NULL;
+
+-- Queue tracks analyzed before DR metering for re-analysis; their existing
+-- loudness values stay in place (and in use) until then
+UPDATE tracks SET loudness_analyzed_at = NULL
+    WHERE loudness_analyzed_at IS NOT NULL AND dr_track IS NULL;
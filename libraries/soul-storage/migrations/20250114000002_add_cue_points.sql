@@ -0,0 +1,10 @@
+-- Add analysed cue points to tracks for smart crossfades
+-- Populated by the background loudness analysis; all NULL when unknown
+
+ALTER TABLE tracks ADD COLUMN cue_in_seconds REAL;    -- Where audio starts after leading silence
+ALTER TABLE tracks ADD COLUMN cue_out_seconds REAL;   -- Where audio ends before trailing silence
+ALTER TABLE tracks ADD COLUMN fade_out_seconds REAL;  -- Where the natural fade-out starts (NULL = hard ending)
+
+-- Queue tracks analyzed before cue detection for re-analysis
+UPDATE tracks SET loudness_analyzed_at = NULL
+    WHERE loudness_analyzed_at IS NOT NULL AND cue_out_seconds IS NULL;
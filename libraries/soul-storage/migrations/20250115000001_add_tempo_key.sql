@@ -0,0 +1,32 @@
+-- Add tempo and musical key to tracks for filtering and sorting
+-- Read from BPM/key tags on import, otherwise filled in by background analysis
+
+ALTER TABLE tracks ADD COLUMN bpm REAL;                    -- Beats per minute
+ALTER TABLE tracks ADD COLUMN bpm_confidence REAL;         -- Detection confidence (0.0-1.0, NULL = from tags)
+ALTER TABLE tracks ADD COLUMN musical_key TEXT;            -- Camelot notation, e.g. '8A'
+ALTER TABLE tracks ADD COLUMN key_confidence REAL;         -- Detection confidence (0.0-1.0, NULL = from tags)
+ALTER TABLE tracks ADD COLUMN tempo_key_source TEXT;       -- 'tag' or 'analysis'
+ALTER TABLE tracks ADD COLUMN tempo_key_analyzed_at INTEGER; -- Timestamp of analysis (Unix epoch)
+
+CREATE INDEX IF NOT EXISTS idx_tracks_bpm ON tracks(bpm) WHERE bpm IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_tracks_musical_key ON tracks(musical_key) WHERE musical_key IS NOT NULL;
+
+-- Background tempo/key analysis queue
+CREATE TABLE IF NOT EXISTS tempo_key_queue (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    track_id INTEGER NOT NULL,
+    -- Higher priority = processed first (0 = normal, positive = higher priority)
+    priority INTEGER NOT NULL DEFAULT 0,
+    -- Number of processing attempts (for retry logic)
+    attempts INTEGER NOT NULL DEFAULT 0,
+    -- Last error message if processing failed
+    last_error TEXT,
+    created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+    FOREIGN KEY (track_id) REFERENCES tracks(id) ON DELETE CASCADE,
+    UNIQUE(track_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_tempo_key_queue_priority
+    ON tempo_key_queue(priority DESC, created_at ASC);
+CREATE INDEX IF NOT EXISTS idx_tempo_key_queue_attempts
+    ON tempo_key_queue(attempts) WHERE attempts < 3;
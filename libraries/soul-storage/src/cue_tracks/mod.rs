@@ -0,0 +1,165 @@
+//! CUE sheet virtual track storage
+//!
+//! Database operations for tracks that are a sub-range of a larger audio
+//! file (single-file album images described by a `.cue` sheet).
+
+use soul_core::error::Result;
+use sqlx::SqlitePool;
+use std::time::Duration;
+
+/// CD frames per second (CUE sheet time unit)
+const FRAMES_PER_SECOND: i64 = 75;
+
+/// File range of a virtual track
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CueRange {
+    /// Track ID
+    pub track_id: i64,
+    /// Path to the .cue sheet the track came from
+    pub cue_path: String,
+    /// TRACK number in the sheet
+    pub cue_track_number: i32,
+    /// Start offset in CD frames (INDEX 01)
+    pub start_frames: i64,
+    /// End offset in CD frames (None = end of file)
+    pub end_frames: Option<i64>,
+    /// Length of the INDEX 00 pregap in CD frames
+    pub pregap_frames: i64,
+}
+
+impl CueRange {
+    /// Start offset in the file
+    pub fn start(&self) -> Duration {
+        frames_to_duration(self.start_frames)
+    }
+
+    /// End offset in the file (None = end of file)
+    pub fn end(&self) -> Option<Duration> {
+        self.end_frames.map(frames_to_duration)
+    }
+
+    /// Length of the pregap preceding the track
+    pub fn pregap(&self) -> Duration {
+        frames_to_duration(self.pregap_frames)
+    }
+}
+
+/// Convert CD frames to a duration (exact to the nanosecond)
+fn frames_to_duration(frames: i64) -> Duration {
+    let frames = frames.max(0) as u64;
+    Duration::from_nanos(frames * 1_000_000_000 / FRAMES_PER_SECOND as u64)
+}
+
+/// Store (or replace) the file range of a track
+pub async fn set_range(pool: &SqlitePool, range: &CueRange) -> Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO track_cue_ranges (
+            track_id, cue_path, cue_track_number, start_frames, end_frames, pregap_frames
+        ) VALUES (?, ?, ?, ?, ?, ?)
+        ON CONFLICT(track_id) DO UPDATE SET
+            cue_path = excluded.cue_path,
+            cue_track_number = excluded.cue_track_number,
+            start_frames = excluded.start_frames,
+            end_frames = excluded.end_frames,
+            pregap_frames = excluded.pregap_frames
+        "#,
+        range.track_id,
+        range.cue_path,
+        range.cue_track_number,
+        range.start_frames,
+        range.end_frames,
+        range.pregap_frames
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Get the file range of a track (None = track plays the whole file)
+pub async fn get_range(pool: &SqlitePool, track_id: i64) -> Result<Option<CueRange>> {
+    let row = sqlx::query!(
+        r#"
+        SELECT track_id, cue_path, cue_track_number, start_frames, end_frames, pregap_frames
+        FROM track_cue_ranges
+        WHERE track_id = ?
+        "#,
+        track_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|r| CueRange {
+        track_id: r.track_id,
+        cue_path: r.cue_path,
+        cue_track_number: r.cue_track_number as i32,
+        start_frames: r.start_frames,
+        end_frames: r.end_frames,
+        pregap_frames: r.pregap_frames,
+    }))
+}
+
+/// Get all virtual tracks imported from a CUE sheet, in sheet order
+pub async fn get_by_cue_path(pool: &SqlitePool, cue_path: &str) -> Result<Vec<CueRange>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT track_id, cue_path, cue_track_number, start_frames, end_frames, pregap_frames
+        FROM track_cue_ranges
+        WHERE cue_path = ?
+        ORDER BY cue_track_number
+        "#,
+        cue_path
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| CueRange {
+            track_id: r.track_id,
+            cue_path: r.cue_path,
+            cue_track_number: r.cue_track_number as i32,
+            start_frames: r.start_frames,
+            end_frames: r.end_frames,
+            pregap_frames: r.pregap_frames,
+        })
+        .collect())
+}
+
+/// Remove the file range of a track (it will play the whole file)
+pub async fn delete_range(pool: &SqlitePool, track_id: i64) -> Result<()> {
+    sqlx::query!(
+        r#"
+        DELETE FROM track_cue_ranges WHERE track_id = ?
+        "#,
+        track_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_offsets_convert_to_durations() {
+        let range = CueRange {
+            track_id: 1,
+            cue_path: "/music/album.cue".to_string(),
+            cue_track_number: 2,
+            start_frames: 240 * 75 + 1,
+            end_frames: None,
+            pregap_frames: 150,
+        };
+
+        assert_eq!(range.pregap(), Duration::from_secs(2));
+        assert_eq!(range.end(), None);
+        // One frame is 588 samples at 44.1 kHz
+        let start_samples = (range.start().as_secs_f64() * 44100.0).round() as u64;
+        assert_eq!(start_samples, 240 * 44100 + 588);
+    }
+}
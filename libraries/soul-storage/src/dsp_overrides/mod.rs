@@ -0,0 +1,288 @@
+//! Per-track, per-album and per-genre DSP overrides
+//!
+//! Attaches a saved DSP chain preset and/or a gain offset to a track, an
+//! album or a genre (e.g. a bass cut for one album, a treble lift for old
+//! masters). The player resolves the overrides of each track as it starts
+//! playing, with precedence track > album > genre; levels without an
+//! override fall through to the device profile and the global chain.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use soul_storage::dsp_overrides::{self, DspOverride, OverrideScope};
+//! # async fn example(pool: &sqlx::SqlitePool) -> Result<(), Box<dyn std::error::Error>> {
+//! // Bass cut preset and -1.5 dB for album 7
+//! dsp_overrides::set_override(
+//!     pool,
+//!     "1",
+//!     &DspOverride {
+//!         scope: OverrideScope::Album,
+//!         target_id: 7,
+//!         preset_id: Some(3),
+//!         gain_db: Some(-1.5),
+//!     },
+//! )
+//! .await?;
+//!
+//! // What applies to track 42?
+//! let resolved = dsp_overrides::resolve_for_track(pool, "1", 42).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::error::StorageError;
+
+pub type Result<T> = std::result::Result<T, StorageError>;
+
+/// What an override is attached to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverrideScope {
+    /// A single track (`tracks.id`)
+    Track,
+    /// Every track of an album (`albums.id`)
+    Album,
+    /// Every track tagged with a genre (`genres.id`)
+    Genre,
+}
+
+impl OverrideScope {
+    /// Database representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Track => "track",
+            Self::Album => "album",
+            Self::Genre => "genre",
+        }
+    }
+
+    /// Parse the database representation
+    #[must_use]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "track" => Some(Self::Track),
+            "album" => Some(Self::Album),
+            "genre" => Some(Self::Genre),
+            _ => None,
+        }
+    }
+}
+
+/// DSP preset and/or gain offset attached to a track, album or genre
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DspOverride {
+    /// What the override is attached to
+    pub scope: OverrideScope,
+    /// Track, album or genre ID (depending on `scope`)
+    pub target_id: i64,
+    /// DSP chain preset to use (None = keep the chain from the next level)
+    pub preset_id: Option<i64>,
+    /// Gain offset in dB (None = no offset at this level)
+    pub gain_db: Option<f64>,
+}
+
+/// Overrides in effect for one track
+///
+/// The preset and the gain offset are resolved independently, so an album
+/// gain offset still applies when the track has its own preset.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ResolvedOverride {
+    /// DSP chain preset to use (None = device profile / global chain)
+    pub preset_id: Option<i64>,
+    /// Level the preset came from
+    pub preset_scope: Option<OverrideScope>,
+    /// Gain offset in dB (None = no offset)
+    pub gain_db: Option<f64>,
+}
+
+/// Store (or replace) an override
+///
+/// # Errors
+///
+/// Returns an error if the database query fails or the preset does not exist
+pub async fn set_override(pool: &SqlitePool, user_id: &str, entry: &DspOverride) -> Result<()> {
+    let now = chrono::Utc::now().timestamp();
+    let scope = entry.scope.as_str();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO dsp_overrides (user_id, scope, target_id, preset_id, gain_db, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?)
+        ON CONFLICT(user_id, scope, target_id) DO UPDATE SET
+            preset_id = excluded.preset_id,
+            gain_db = excluded.gain_db,
+            updated_at = excluded.updated_at
+        "#,
+        user_id,
+        scope,
+        entry.target_id,
+        entry.preset_id,
+        entry.gain_db,
+        now
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Remove an override
+///
+/// # Errors
+///
+/// Returns an error if the database query fails
+pub async fn delete_override(
+    pool: &SqlitePool,
+    user_id: &str,
+    scope: OverrideScope,
+    target_id: i64,
+) -> Result<()> {
+    let scope = scope.as_str();
+
+    sqlx::query!(
+        r#"
+        DELETE FROM dsp_overrides
+        WHERE user_id = ? AND scope = ? AND target_id = ?
+        "#,
+        user_id,
+        scope,
+        target_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Get the override attached to a track, album or genre
+///
+/// # Errors
+///
+/// Returns an error if the database query fails
+pub async fn get_override(
+    pool: &SqlitePool,
+    user_id: &str,
+    scope: OverrideScope,
+    target_id: i64,
+) -> Result<Option<DspOverride>> {
+    let scope_str = scope.as_str();
+
+    let row = sqlx::query!(
+        r#"
+        SELECT preset_id, gain_db
+        FROM dsp_overrides
+        WHERE user_id = ? AND scope = ? AND target_id = ?
+        "#,
+        user_id,
+        scope_str,
+        target_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|r| DspOverride {
+        scope,
+        target_id,
+        preset_id: r.preset_id,
+        gain_db: r.gain_db,
+    }))
+}
+
+/// Get all overrides of a user
+///
+/// # Errors
+///
+/// Returns an error if the database query fails
+pub async fn list_overrides(pool: &SqlitePool, user_id: &str) -> Result<Vec<DspOverride>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT scope, target_id, preset_id, gain_db
+        FROM dsp_overrides
+        WHERE user_id = ?
+        ORDER BY scope, target_id
+        "#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|r| {
+            Some(DspOverride {
+                scope: OverrideScope::from_str(&r.scope)?,
+                target_id: r.target_id,
+                preset_id: r.preset_id,
+                gain_db: r.gain_db,
+            })
+        })
+        .collect())
+}
+
+/// Resolve the overrides in effect for a track
+///
+/// Looks at the track itself, its album and its genres (lowest genre ID
+/// first when several genres have overrides). Each of preset and gain comes
+/// from the most specific level that sets it.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails
+pub async fn resolve_for_track(
+    pool: &SqlitePool,
+    user_id: &str,
+    track_id: i64,
+) -> Result<ResolvedOverride> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT scope, preset_id, gain_db
+        FROM dsp_overrides
+        WHERE user_id = ?
+          AND (
+            (scope = 'track' AND target_id = ?)
+            OR (scope = 'album' AND target_id = (SELECT album_id FROM tracks WHERE id = ?))
+            OR (scope = 'genre' AND target_id IN (SELECT genre_id FROM track_genres WHERE track_id = ?))
+          )
+        ORDER BY CASE scope WHEN 'track' THEN 0 WHEN 'album' THEN 1 ELSE 2 END, target_id
+        "#,
+        user_id,
+        track_id,
+        track_id,
+        track_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut resolved = ResolvedOverride::default();
+    for row in rows {
+        if resolved.preset_id.is_none() && row.preset_id.is_some() {
+            resolved.preset_id = row.preset_id;
+            resolved.preset_scope = OverrideScope::from_str(&row.scope);
+        }
+        if resolved.gain_db.is_none() {
+            resolved.gain_db = row.gain_db;
+        }
+    }
+
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scope_round_trips_through_database_string() {
+        for scope in [
+            OverrideScope::Track,
+            OverrideScope::Album,
+            OverrideScope::Genre,
+        ] {
+            assert_eq!(OverrideScope::from_str(scope.as_str()), Some(scope));
+        }
+        assert_eq!(OverrideScope::from_str("artist"), None);
+    }
+}
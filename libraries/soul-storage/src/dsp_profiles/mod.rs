@@ -0,0 +1,215 @@
+//! Per-device DSP chain profiles
+//!
+//! Binds saved DSP chain presets (`dsp_presets`) to output devices so the
+//! player can load the right EQ/crossfeed/convolution chain whenever the
+//! output changes. Devices are identified by backend and device name; a
+//! fallback profile covers devices without a binding of their own.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use soul_storage::dsp_profiles;
+//! # async fn example(pool: &sqlx::SqlitePool) -> Result<(), Box<dyn std::error::Error>> {
+//! // Use preset 3 whenever the USB DAC is the output
+//! dsp_profiles::bind_device(pool, "1", "default", "USB DAC", 3).await?;
+//!
+//! // Use preset 1 everywhere else
+//! dsp_profiles::set_default_profile(pool, "1", Some(1)).await?;
+//!
+//! // Preset to load after switching to the laptop speakers (Some(1))
+//! let preset_id = dsp_profiles::resolve_profile(pool, "1", "default", "Speakers").await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::error::StorageError;
+
+pub type Result<T> = std::result::Result<T, StorageError>;
+
+/// Backend and device name of the fallback profile row
+const DEFAULT_PROFILE_KEY: &str = "*";
+
+/// A DSP chain preset bound to an output device
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceProfileBinding {
+    /// Audio backend ("default", "asio", "jack")
+    pub backend: String,
+    /// Device name as reported by the backend
+    pub device_name: String,
+    /// Bound DSP chain preset
+    pub preset_id: i64,
+}
+
+/// Bind a DSP chain preset to an output device
+///
+/// Replaces any existing binding for the device.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails or the preset does not exist
+pub async fn bind_device(
+    pool: &SqlitePool,
+    user_id: &str,
+    backend: &str,
+    device_name: &str,
+    preset_id: i64,
+) -> Result<()> {
+    let now = chrono::Utc::now().timestamp();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO dsp_device_profiles (user_id, backend, device_name, preset_id, updated_at)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT(user_id, backend, device_name) DO UPDATE SET
+            preset_id = excluded.preset_id,
+            updated_at = excluded.updated_at
+        "#,
+        user_id,
+        backend,
+        device_name,
+        preset_id,
+        now
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Remove the binding of an output device (it falls back to the default profile)
+///
+/// # Errors
+///
+/// Returns an error if the database query fails
+pub async fn unbind_device(
+    pool: &SqlitePool,
+    user_id: &str,
+    backend: &str,
+    device_name: &str,
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        DELETE FROM dsp_device_profiles
+        WHERE user_id = ? AND backend = ? AND device_name = ?
+        "#,
+        user_id,
+        backend,
+        device_name
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Get all device bindings for a user (the fallback profile is not included)
+///
+/// # Errors
+///
+/// Returns an error if the database query fails
+pub async fn list_device_bindings(
+    pool: &SqlitePool,
+    user_id: &str,
+) -> Result<Vec<DeviceProfileBinding>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT backend, device_name, preset_id
+        FROM dsp_device_profiles
+        WHERE user_id = ? AND backend != '*'
+        ORDER BY backend, device_name
+        "#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| DeviceProfileBinding {
+            backend: r.backend,
+            device_name: r.device_name,
+            preset_id: r.preset_id,
+        })
+        .collect())
+}
+
+/// Set (or clear with `None`) the fallback profile for unbound devices
+///
+/// # Errors
+///
+/// Returns an error if the database query fails or the preset does not exist
+pub async fn set_default_profile(
+    pool: &SqlitePool,
+    user_id: &str,
+    preset_id: Option<i64>,
+) -> Result<()> {
+    match preset_id {
+        Some(preset_id) => {
+            bind_device(
+                pool,
+                user_id,
+                DEFAULT_PROFILE_KEY,
+                DEFAULT_PROFILE_KEY,
+                preset_id,
+            )
+            .await
+        }
+        None => unbind_device(pool, user_id, DEFAULT_PROFILE_KEY, DEFAULT_PROFILE_KEY).await,
+    }
+}
+
+/// Get the fallback profile for unbound devices
+///
+/// # Errors
+///
+/// Returns an error if the database query fails
+pub async fn get_default_profile(pool: &SqlitePool, user_id: &str) -> Result<Option<i64>> {
+    let row = sqlx::query!(
+        r#"
+        SELECT preset_id
+        FROM dsp_device_profiles
+        WHERE user_id = ? AND backend = '*' AND device_name = '*'
+        "#,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|r| r.preset_id))
+}
+
+/// Get the preset to load for an output device
+///
+/// Returns the device's own binding if it has one, otherwise the fallback
+/// profile, or `None` if neither is set.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails
+pub async fn resolve_profile(
+    pool: &SqlitePool,
+    user_id: &str,
+    backend: &str,
+    device_name: &str,
+) -> Result<Option<i64>> {
+    let row = sqlx::query!(
+        r#"
+        SELECT preset_id
+        FROM dsp_device_profiles
+        WHERE user_id = ?
+          AND ((backend = ? AND device_name = ?) OR (backend = '*' AND device_name = '*'))
+        ORDER BY backend = '*'
+        LIMIT 1
+        "#,
+        user_id,
+        backend,
+        device_name
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|r| r.preset_id))
+}
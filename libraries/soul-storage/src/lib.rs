@@ -38,6 +38,7 @@ mod error;
 // Vertical slices
 pub mod albums;
 pub mod artists;
+pub mod cue_tracks;
 pub mod genres;
 pub mod playlists;
 pub mod sources;
@@ -45,6 +46,8 @@ pub mod tracks;
 pub mod users;
 
 // User preferences and state
+pub mod dsp_overrides;
+pub mod dsp_profiles;
 pub mod external_file_settings;
 pub mod managed_library_settings;
 pub mod settings;
@@ -57,6 +60,7 @@ pub mod scan_progress;
 
 // Background processing
 pub mod fingerprint_queue;
+pub mod tempo_key_queue;
 
 // Multi-device sync
 pub mod devices;
@@ -65,6 +69,7 @@ pub mod playback_state;
 
 // Audio analysis
 pub mod loudness;
+pub mod tempo_key;
 
 pub use context::LocalStorageContext;
 pub use error::StorageError;
@@ -1,7 +1,7 @@
 //! Loudness metadata storage
 //!
 //! Database operations for storing and retrieving loudness analysis results
-//! including ReplayGain values and EBU R128 measurements.
+//! including ReplayGain values, EBU R128 measurements and dynamic range.
 
 use soul_core::error::Result;
 use sqlx::SqlitePool;
@@ -25,6 +25,12 @@ pub struct TrackLoudness {
     pub lufs_range: Option<f64>,
     /// True peak in dBFS
     pub true_peak_dbfs: Option<f64>,
+    /// TT dynamic range in dB
+    pub dr_track: Option<f64>,
+    /// Album dynamic range in dB
+    pub dr_album: Option<f64>,
+    /// Peak-to-loudness ratio in dB
+    pub plr: Option<f64>,
     /// Analysis timestamp (Unix epoch)
     pub analyzed_at: Option<i64>,
     /// Analysis algorithm version
@@ -43,6 +49,30 @@ impl TrackLoudness {
     }
 }
 
+/// Dynamic range of an analyzed track
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackDynamicRange {
+    /// Track ID
+    pub track_id: i64,
+    /// TT dynamic range in dB
+    pub dr_track: f64,
+    /// Album dynamic range in dB
+    pub dr_album: Option<f64>,
+    /// Peak-to-loudness ratio in dB
+    pub plr: Option<f64>,
+}
+
+/// Analysed cue points of a track, in seconds from its start
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrackCuePoints {
+    /// Where audio starts after leading silence
+    pub cue_in: f64,
+    /// Where audio ends before trailing silence
+    pub cue_out: f64,
+    /// Where the natural fade-out starts (None = hard ending)
+    pub fade_out: Option<f64>,
+}
+
 /// Album loudness metadata
 #[derive(Debug, Clone)]
 pub struct AlbumLoudness {
@@ -121,6 +151,9 @@ pub async fn get_track_loudness(pool: &SqlitePool, track_id: i64) -> Result<Opti
             lufs_integrated,
             lufs_range,
             true_peak_dbfs,
+            dr_track,
+            dr_album,
+            plr,
             loudness_analyzed_at,
             loudness_version
         FROM tracks
@@ -140,6 +173,9 @@ pub async fn get_track_loudness(pool: &SqlitePool, track_id: i64) -> Result<Opti
         lufs_integrated: r.lufs_integrated,
         lufs_range: r.lufs_range,
         true_peak_dbfs: r.true_peak_dbfs,
+        dr_track: r.dr_track,
+        dr_album: r.dr_album,
+        plr: r.plr,
         analyzed_at: r.loudness_analyzed_at,
         version: r.loudness_version,
     }))
@@ -162,6 +198,8 @@ pub async fn update_track_loudness(
             lufs_integrated = ?,
             lufs_range = ?,
             true_peak_dbfs = ?,
+            dr_track = ?,
+            plr = ?,
             loudness_analyzed_at = ?,
             loudness_version = ?
         WHERE id = ?
@@ -171,6 +209,8 @@ pub async fn update_track_loudness(
         loudness.lufs_integrated,
         loudness.lufs_range,
         loudness.true_peak_dbfs,
+        loudness.dr_track,
+        loudness.plr,
         now,
         version,
         track_id
@@ -205,6 +245,134 @@ pub async fn update_album_loudness(
     Ok(())
 }
 
+/// Get the dynamic range of every analyzed track in an album
+pub async fn get_album_track_dynamic_ranges(pool: &SqlitePool, album_id: i64) -> Result<Vec<f64>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT dr_track as "dr_track!: f64"
+        FROM tracks
+        WHERE album_id = ? AND dr_track IS NOT NULL
+        "#,
+        album_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| r.dr_track).collect())
+}
+
+/// Update album dynamic range for all tracks in an album
+pub async fn update_album_dynamic_range(
+    pool: &SqlitePool,
+    album_id: i64,
+    dr_album: f64,
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE tracks SET
+            dr_album = ?
+        WHERE album_id = ?
+        "#,
+        dr_album,
+        album_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Get analyzed tracks with a dynamic range of at most `max_dr` dB,
+/// least dynamic first
+///
+/// Brickwalled masters typically measure DR7 or below.
+pub async fn get_tracks_by_dynamic_range(
+    pool: &SqlitePool,
+    max_dr: f64,
+    limit: i32,
+) -> Result<Vec<TrackDynamicRange>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT id, dr_track as "dr_track!: f64", dr_album, plr
+        FROM tracks
+        WHERE dr_track IS NOT NULL AND dr_track <= ?
+        ORDER BY dr_track ASC, plr ASC
+        LIMIT ?
+        "#,
+        max_dr,
+        limit
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| TrackDynamicRange {
+            track_id: r.id,
+            dr_track: r.dr_track,
+            dr_album: r.dr_album,
+            plr: r.plr,
+        })
+        .collect())
+}
+
+/// Get the analysed cue points of a track
+///
+/// Returns `None` if the track doesn't exist or has no cue points.
+pub async fn get_track_cue_points(
+    pool: &SqlitePool,
+    track_id: i64,
+) -> Result<Option<TrackCuePoints>> {
+    let row = sqlx::query!(
+        r#"
+        SELECT
+            cue_in_seconds as "cue_in_seconds!: f64",
+            cue_out_seconds as "cue_out_seconds!: f64",
+            fade_out_seconds
+        FROM tracks
+        WHERE id = ? AND cue_in_seconds IS NOT NULL AND cue_out_seconds IS NOT NULL
+        "#,
+        track_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|r| TrackCuePoints {
+        cue_in: r.cue_in_seconds,
+        cue_out: r.cue_out_seconds,
+        fade_out: r.fade_out_seconds,
+    }))
+}
+
+/// Update (or clear, with `None`) the cue points of a track
+pub async fn update_track_cue_points(
+    pool: &SqlitePool,
+    track_id: i64,
+    cue_points: Option<&TrackCuePoints>,
+) -> Result<()> {
+    let cue_in = cue_points.map(|c| c.cue_in);
+    let cue_out = cue_points.map(|c| c.cue_out);
+    let fade_out = cue_points.and_then(|c| c.fade_out);
+
+    sqlx::query!(
+        r#"
+        UPDATE tracks SET
+            cue_in_seconds = ?,
+            cue_out_seconds = ?,
+            fade_out_seconds = ?
+        WHERE id = ?
+        "#,
+        cue_in,
+        cue_out,
+        fade_out,
+        track_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
 /// Get tracks without loudness analysis
 pub async fn get_tracks_without_analysis(pool: &SqlitePool, limit: i32) -> Result<Vec<i64>> {
     let rows = sqlx::query!(
@@ -0,0 +1,317 @@
+//! Tempo and musical key storage
+//!
+//! Database operations for the BPM and key of tracks. Values come either
+//! from BPM/key tags read on import or from background analysis; tag values
+//! take precedence and are only replaced by analysis when asked to.
+//!
+//! Keys are stored in Camelot notation ("8A", "11B"), so sorting by key
+//! walks the Camelot wheel.
+
+use serde::{Deserialize, Serialize};
+use soul_core::error::Result;
+use sqlx::SqlitePool;
+
+/// Where a track's tempo and key came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TempoKeySource {
+    /// Read from the file's tags
+    Tag,
+    /// Detected by background analysis
+    Analysis,
+}
+
+impl TempoKeySource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TempoKeySource::Tag => "tag",
+            TempoKeySource::Analysis => "analysis",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "tag" => Some(TempoKeySource::Tag),
+            "analysis" => Some(TempoKeySource::Analysis),
+            _ => None,
+        }
+    }
+}
+
+/// Tempo and key of a track
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct TrackTempoKey {
+    /// Track ID
+    pub track_id: i64,
+    /// Beats per minute
+    pub bpm: Option<f64>,
+    /// Tempo detection confidence (None = from tags)
+    pub bpm_confidence: Option<f64>,
+    /// Key in Camelot notation
+    pub musical_key: Option<String>,
+    /// Key detection confidence (None = from tags)
+    pub key_confidence: Option<f64>,
+    /// Where the values came from
+    pub source: Option<TempoKeySource>,
+    /// Analysis timestamp (Unix epoch)
+    pub analyzed_at: Option<i64>,
+}
+
+/// Result of tempo/key analysis for one track
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TempoKeyAnalysis {
+    /// Detected BPM and confidence
+    pub bpm: Option<(f64, f64)>,
+    /// Detected Camelot key and confidence
+    pub musical_key: Option<(String, f64)>,
+}
+
+/// Sort order for [`find_tracks`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TempoKeySort {
+    /// Slowest first
+    #[default]
+    Bpm,
+    /// Around the Camelot wheel (1A, 1B, 2A, ...), then by BPM
+    Key,
+}
+
+/// Filter for [`find_tracks`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TempoKeyFilter {
+    /// Minimum BPM (inclusive)
+    pub min_bpm: Option<f64>,
+    /// Maximum BPM (inclusive)
+    pub max_bpm: Option<f64>,
+    /// Camelot keys to match (empty = any key)
+    #[serde(default)]
+    pub keys: Vec<String>,
+    #[serde(default)]
+    pub sort: TempoKeySort,
+}
+
+/// Get the tempo and key of a track
+///
+/// Returns `None` if the track doesn't exist.
+pub async fn get_track_tempo_key(
+    pool: &SqlitePool,
+    track_id: i64,
+) -> Result<Option<TrackTempoKey>> {
+    let row = sqlx::query!(
+        r#"
+        SELECT
+            id,
+            bpm,
+            bpm_confidence,
+            musical_key,
+            key_confidence,
+            tempo_key_source,
+            tempo_key_analyzed_at
+        FROM tracks
+        WHERE id = ?
+        "#,
+        track_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|r| TrackTempoKey {
+        track_id: r.id,
+        bpm: r.bpm,
+        bpm_confidence: r.bpm_confidence,
+        musical_key: r.musical_key,
+        key_confidence: r.key_confidence,
+        source: r
+            .tempo_key_source
+            .as_deref()
+            .and_then(TempoKeySource::parse),
+        analyzed_at: r.tempo_key_analyzed_at,
+    }))
+}
+
+/// Store BPM and key read from a file's tags
+///
+/// Values that are `None` leave the stored ones untouched.
+pub async fn set_from_tags(
+    pool: &SqlitePool,
+    track_id: i64,
+    bpm: Option<f64>,
+    musical_key: Option<&str>,
+) -> Result<()> {
+    if bpm.is_none() && musical_key.is_none() {
+        return Ok(());
+    }
+
+    sqlx::query!(
+        r#"
+        UPDATE tracks SET
+            bpm = COALESCE(?, bpm),
+            bpm_confidence = CASE WHEN ? IS NULL THEN bpm_confidence ELSE NULL END,
+            musical_key = COALESCE(?, musical_key),
+            key_confidence = CASE WHEN ? IS NULL THEN key_confidence ELSE NULL END,
+            tempo_key_source = 'tag'
+        WHERE id = ?
+        "#,
+        bpm,
+        bpm,
+        musical_key,
+        musical_key,
+        track_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Store the result of tempo/key analysis
+///
+/// Values read from tags are kept unless `overwrite_tags` is set; analysis
+/// then only fills in what the tags were missing. The track is marked as
+/// analyzed either way so it isn't queued again.
+pub async fn update_from_analysis(
+    pool: &SqlitePool,
+    track_id: i64,
+    analysis: &TempoKeyAnalysis,
+    overwrite_tags: bool,
+) -> Result<()> {
+    let Some(current) = get_track_tempo_key(pool, track_id).await? else {
+        return Ok(());
+    };
+    let keep_tags = current.source == Some(TempoKeySource::Tag) && !overwrite_tags;
+
+    let (bpm, bpm_confidence) = match analysis.bpm {
+        _ if keep_tags && current.bpm.is_some() => (current.bpm, None),
+        Some((bpm, confidence)) => (Some(bpm), Some(confidence)),
+        None => (None, None),
+    };
+    let (musical_key, key_confidence) = match &analysis.musical_key {
+        _ if keep_tags && current.musical_key.is_some() => (current.musical_key.clone(), None),
+        Some((key, confidence)) => (Some(key.clone()), Some(*confidence)),
+        None => (None, None),
+    };
+
+    let source = if keep_tags && (current.bpm.is_some() || current.musical_key.is_some()) {
+        TempoKeySource::Tag
+    } else {
+        TempoKeySource::Analysis
+    }
+    .as_str();
+    let now = chrono::Utc::now().timestamp();
+
+    sqlx::query!(
+        r#"
+        UPDATE tracks SET
+            bpm = ?,
+            bpm_confidence = ?,
+            musical_key = ?,
+            key_confidence = ?,
+            tempo_key_source = ?,
+            tempo_key_analyzed_at = ?
+        WHERE id = ?
+        "#,
+        bpm,
+        bpm_confidence,
+        musical_key,
+        key_confidence,
+        source,
+        now,
+        track_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Find tracks by BPM range and key
+///
+/// Only tracks with a known BPM or key are returned; a BPM bound excludes
+/// tracks without a BPM, and a key list excludes tracks without a key.
+pub async fn find_tracks(pool: &SqlitePool, filter: &TempoKeyFilter) -> Result<Vec<TrackTempoKey>> {
+    let keys = serde_json::to_string(&filter.keys)
+        .map_err(|e| soul_core::SoulError::Storage(e.to_string()))?;
+    let sort_by_key = filter.sort == TempoKeySort::Key;
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            id,
+            bpm,
+            bpm_confidence,
+            musical_key,
+            key_confidence,
+            tempo_key_source,
+            tempo_key_analyzed_at
+        FROM tracks
+        WHERE (bpm IS NOT NULL OR musical_key IS NOT NULL)
+          AND (? IS NULL OR bpm >= ?)
+          AND (? IS NULL OR bpm <= ?)
+          AND (? = '[]' OR musical_key IN (SELECT value FROM json_each(?)))
+        ORDER BY
+            CASE WHEN ? THEN CAST(musical_key AS INTEGER) END,
+            CASE WHEN ? THEN musical_key END,
+            bpm IS NULL, bpm, id
+        "#,
+        filter.min_bpm,
+        filter.min_bpm,
+        filter.max_bpm,
+        filter.max_bpm,
+        keys,
+        keys,
+        sort_by_key,
+        sort_by_key
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| TrackTempoKey {
+            track_id: r.id,
+            bpm: r.bpm,
+            bpm_confidence: r.bpm_confidence,
+            musical_key: r.musical_key,
+            key_confidence: r.key_confidence,
+            source: r
+                .tempo_key_source
+                .as_deref()
+                .and_then(TempoKeySource::parse),
+            analyzed_at: r.tempo_key_analyzed_at,
+        })
+        .collect())
+}
+
+/// Get tracks that still need tempo/key analysis
+///
+/// Tracks whose tags already provide both BPM and key are skipped.
+pub async fn get_tracks_without_analysis(pool: &SqlitePool, limit: i32) -> Result<Vec<i64>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT id
+        FROM tracks
+        WHERE tempo_key_analyzed_at IS NULL
+          AND (bpm IS NULL OR musical_key IS NULL)
+        LIMIT ?
+        "#,
+        limit
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| r.id).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_round_trip() {
+        for source in [TempoKeySource::Tag, TempoKeySource::Analysis] {
+            assert_eq!(TempoKeySource::parse(source.as_str()), Some(source));
+        }
+        assert_eq!(TempoKeySource::parse("manual"), None);
+    }
+}
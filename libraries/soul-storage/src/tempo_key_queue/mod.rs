@@ -0,0 +1,253 @@
+//! Tempo/key analysis queue storage
+//!
+//! Manages the background queue for BPM and musical key detection.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use soul_storage::tempo_key_queue;
+//!
+//! # async fn example(pool: &sqlx::SqlitePool) -> Result<(), Box<dyn std::error::Error>> {
+//! // Add a track to the tempo/key queue
+//! tempo_key_queue::enqueue(pool, 123, 0).await?;
+//!
+//! // Get next item to process
+//! if let Some(item) = tempo_key_queue::get_next(pool).await? {
+//!     // Detect tempo and key...
+//!     tempo_key_queue::complete(pool, item.id).await?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::StorageError;
+use sqlx::SqlitePool;
+
+type Result<T> = std::result::Result<T, StorageError>;
+
+/// A tempo/key queue item
+#[derive(Debug, Clone)]
+pub struct TempoKeyQueueItem {
+    pub id: i64,
+    pub track_id: i64,
+    pub priority: i32,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub created_at: i64,
+}
+
+/// Tempo/key queue statistics
+#[derive(Debug, Clone, Default)]
+pub struct TempoKeyQueueStats {
+    pub pending: i64,
+    pub failed: i64,
+    pub total_processed: i64,
+}
+
+/// Add a track to the tempo/key queue
+pub async fn enqueue(pool: &SqlitePool, track_id: i64, priority: i32) -> Result<i64> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO tempo_key_queue (track_id, priority)
+        VALUES (?, ?)
+        ON CONFLICT(track_id) DO UPDATE SET priority = MAX(priority, excluded.priority)
+        "#,
+        track_id,
+        priority
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+/// Add multiple tracks to the tempo/key queue
+pub async fn enqueue_batch(pool: &SqlitePool, track_ids: &[i64], priority: i32) -> Result<i64> {
+    let mut count = 0i64;
+
+    for track_id in track_ids {
+        sqlx::query!(
+            r#"
+            INSERT INTO tempo_key_queue (track_id, priority)
+            VALUES (?, ?)
+            ON CONFLICT(track_id) DO NOTHING
+            "#,
+            track_id,
+            priority
+        )
+        .execute(pool)
+        .await?;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// Queue every track that still needs tempo/key analysis
+///
+/// Tracks whose tags already provide both BPM and key are skipped.
+pub async fn enqueue_unanalyzed(pool: &SqlitePool, priority: i32) -> Result<u64> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO tempo_key_queue (track_id, priority)
+        SELECT id, ?
+        FROM tracks
+        WHERE tempo_key_analyzed_at IS NULL
+          AND (bpm IS NULL OR musical_key IS NULL)
+        ON CONFLICT(track_id) DO NOTHING
+        "#,
+        priority
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Get the next item to process (highest priority, oldest first)
+pub async fn get_next(pool: &SqlitePool) -> Result<Option<TempoKeyQueueItem>> {
+    let row = sqlx::query!(
+        r#"
+        SELECT id, track_id, priority, attempts, last_error, created_at
+        FROM tempo_key_queue
+        WHERE attempts < 3
+        ORDER BY priority DESC, created_at ASC
+        LIMIT 1
+        "#
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|r| TempoKeyQueueItem {
+        id: r.id.expect("id should not be null"),
+        track_id: r.track_id,
+        priority: r.priority as i32,
+        attempts: r.attempts as i32,
+        last_error: r.last_error,
+        created_at: r.created_at,
+    }))
+}
+
+/// Get a batch of items to process
+pub async fn get_batch(pool: &SqlitePool, limit: i32) -> Result<Vec<TempoKeyQueueItem>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT id, track_id, priority, attempts, last_error, created_at
+        FROM tempo_key_queue
+        WHERE attempts < 3
+        ORDER BY priority DESC, created_at ASC
+        LIMIT ?
+        "#,
+        limit
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| TempoKeyQueueItem {
+            id: r.id.expect("id should not be null"),
+            track_id: r.track_id,
+            priority: r.priority as i32,
+            attempts: r.attempts as i32,
+            last_error: r.last_error,
+            created_at: r.created_at,
+        })
+        .collect())
+}
+
+/// Mark an item as completed (remove from queue)
+pub async fn complete(pool: &SqlitePool, id: i64) -> Result<()> {
+    sqlx::query!("DELETE FROM tempo_key_queue WHERE id = ?", id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Mark an item as failed (increment attempts, record error)
+pub async fn fail(pool: &SqlitePool, id: i64, error: &str) -> Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE tempo_key_queue
+        SET attempts = attempts + 1, last_error = ?
+        WHERE id = ?
+        "#,
+        error,
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Remove a track from the queue (e.g., if track was deleted)
+pub async fn remove(pool: &SqlitePool, track_id: i64) -> Result<()> {
+    sqlx::query!("DELETE FROM tempo_key_queue WHERE track_id = ?", track_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Get queue statistics
+pub async fn get_stats(pool: &SqlitePool) -> Result<TempoKeyQueueStats> {
+    let pending =
+        sqlx::query_scalar!("SELECT COUNT(*) as count FROM tempo_key_queue WHERE attempts < 3")
+            .fetch_one(pool)
+            .await?;
+
+    let failed =
+        sqlx::query_scalar!("SELECT COUNT(*) as count FROM tempo_key_queue WHERE attempts >= 3")
+            .fetch_one(pool)
+            .await?;
+
+    Ok(TempoKeyQueueStats {
+        pending: pending as i64,
+        failed: failed as i64,
+        total_processed: 0, // Would need a separate counter table
+    })
+}
+
+/// Get count of pending items
+pub async fn pending_count(pool: &SqlitePool) -> Result<i64> {
+    let count =
+        sqlx::query_scalar!("SELECT COUNT(*) as count FROM tempo_key_queue WHERE attempts < 3")
+            .fetch_one(pool)
+            .await?;
+
+    Ok(count as i64)
+}
+
+/// Clear all failed items (attempts >= 3)
+pub async fn clear_failed(pool: &SqlitePool) -> Result<u64> {
+    let result = sqlx::query!("DELETE FROM tempo_key_queue WHERE attempts >= 3")
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Reset failed items to retry
+pub async fn retry_failed(pool: &SqlitePool) -> Result<u64> {
+    let result = sqlx::query!(
+        "UPDATE tempo_key_queue SET attempts = 0, last_error = NULL WHERE attempts >= 3"
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tempo_key_queue_stats_default() {
+        let stats = TempoKeyQueueStats::default();
+        assert_eq!(stats.pending, 0);
+        assert_eq!(stats.failed, 0);
+    }
+}
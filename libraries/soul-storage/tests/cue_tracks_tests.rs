@@ -0,0 +1,125 @@
+//! Integration tests for CUE sheet virtual track ranges
+//!
+//! Tests range storage including:
+//! - Storing and replacing the file range of a track
+//! - Listing all tracks of a CUE sheet in sheet order
+//! - Cascade delete with the track
+
+mod test_helpers;
+
+use soul_storage::cue_tracks::{self, CueRange};
+use std::time::Duration;
+use test_helpers::*;
+
+fn range(track_id: i64, number: i32, start_frames: i64, end_frames: Option<i64>) -> CueRange {
+    CueRange {
+        track_id,
+        cue_path: "/music/Album/album.cue".to_string(),
+        cue_track_number: number,
+        start_frames,
+        end_frames,
+        pregap_frames: 0,
+    }
+}
+
+#[tokio::test]
+async fn test_set_and_get_range() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+
+    let track_id = create_test_track(pool, "Track 2", None, None, 1, Some("/music/Album/album.flac"))
+        .await
+        .as_str()
+        .parse::<i64>()
+        .unwrap();
+
+    // No range stored yet: plays the whole file
+    assert!(cue_tracks::get_range(pool, track_id).await.unwrap().is_none());
+
+    let mut stored = range(track_id, 2, 4 * 60 * 75 + 1, Some(9 * 60 * 75));
+    stored.pregap_frames = 150;
+    cue_tracks::set_range(pool, &stored)
+        .await
+        .expect("Failed to store range");
+
+    let retrieved = cue_tracks::get_range(pool, track_id)
+        .await
+        .unwrap()
+        .expect("Range not found");
+    assert_eq!(retrieved, stored);
+    assert_eq!(retrieved.end(), Some(Duration::from_secs(9 * 60)));
+    assert_eq!(retrieved.pregap(), Duration::from_secs(2));
+
+    // Storing again replaces the range
+    let replaced = range(track_id, 2, 0, None);
+    cue_tracks::set_range(pool, &replaced).await.unwrap();
+    assert_eq!(
+        cue_tracks::get_range(pool, track_id).await.unwrap(),
+        Some(replaced)
+    );
+}
+
+#[tokio::test]
+async fn test_get_by_cue_path_in_sheet_order() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+
+    let mut ids = Vec::new();
+    for title in ["Track 1", "Track 2", "Track 3"] {
+        let id = create_test_track(pool, title, None, None, 1, Some("/music/Album/album.flac")).await;
+        ids.push(id.as_str().parse::<i64>().unwrap());
+    }
+
+    // Insert out of order
+    cue_tracks::set_range(pool, &range(ids[2], 3, 2000, None)).await.unwrap();
+    cue_tracks::set_range(pool, &range(ids[0], 1, 0, Some(1000))).await.unwrap();
+    cue_tracks::set_range(pool, &range(ids[1], 2, 1000, Some(2000))).await.unwrap();
+
+    let ranges = cue_tracks::get_by_cue_path(pool, "/music/Album/album.cue")
+        .await
+        .unwrap();
+    let numbers: Vec<i32> = ranges.iter().map(|r| r.cue_track_number).collect();
+    assert_eq!(numbers, vec![1, 2, 3]);
+
+    // Consecutive tracks share their boundary (gapless)
+    assert_eq!(ranges[0].end_frames, Some(ranges[1].start_frames));
+    assert_eq!(ranges[1].end_frames, Some(ranges[2].start_frames));
+
+    assert!(cue_tracks::get_by_cue_path(pool, "/music/other.cue")
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_range_deleted_with_track() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+
+    let track_id = create_test_track(pool, "Track 1", None, None, 1, Some("/music/Album/album.flac")).await;
+    let id = track_id.as_str().parse::<i64>().unwrap();
+    cue_tracks::set_range(pool, &range(id, 1, 0, None)).await.unwrap();
+
+    soul_storage::tracks::delete(pool, track_id)
+        .await
+        .expect("Failed to delete track");
+
+    assert!(cue_tracks::get_range(pool, id).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_delete_range() {
+    let test_db = TestDb::new().await;
+    let pool = test_db.pool();
+
+    let track_id = create_test_track(pool, "Track 1", None, None, 1, Some("/music/Album/album.flac"))
+        .await
+        .as_str()
+        .parse::<i64>()
+        .unwrap();
+    cue_tracks::set_range(pool, &range(track_id, 1, 0, None)).await.unwrap();
+
+    cue_tracks::delete_range(pool, track_id).await.unwrap();
+
+    assert!(cue_tracks::get_range(pool, track_id).await.unwrap().is_none());
+}
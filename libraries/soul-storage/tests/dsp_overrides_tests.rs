@@ -0,0 +1,254 @@
+use soul_storage::{
+    create_pool,
+    dsp_overrides::{self, DspOverride, OverrideScope, ResolvedOverride},
+    run_migrations,
+};
+use sqlx::SqlitePool;
+
+const TRACK_ID: i64 = 42;
+const ALBUM_ID: i64 = 7;
+const ROCK_ID: i64 = 3;
+const JAZZ_ID: i64 = 4;
+
+/// One track on album 7, tagged Rock and Jazz
+async fn setup() -> SqlitePool {
+    let pool = create_pool("sqlite::memory:").await.unwrap();
+    run_migrations(&pool).await.unwrap();
+
+    for sql in [
+        "INSERT INTO users (id, name, created_at) VALUES ('1', 'Test User', 1234567890)",
+        "INSERT INTO albums (id, title) VALUES (7, 'Old Master')",
+        "INSERT INTO genres (id, name, canonical_name) VALUES (3, 'Rock', 'rock'), (4, 'Jazz', 'jazz')",
+        "INSERT INTO tracks (id, title, album_id) VALUES (42, 'Track', 7)",
+        "INSERT INTO track_genres (track_id, genre_id) VALUES (42, 3), (42, 4)",
+    ] {
+        sqlx::query(sql).execute(&pool).await.unwrap();
+    }
+
+    pool
+}
+
+async fn create_preset(pool: &SqlitePool, name: &str) -> i64 {
+    sqlx::query(
+        "INSERT INTO dsp_presets (user_id, name, is_builtin, effect_chain, created_at, updated_at)
+         VALUES (1, ?, 0, '[]', 0, 0)",
+    )
+    .bind(name)
+    .execute(pool)
+    .await
+    .unwrap()
+    .last_insert_rowid()
+}
+
+async fn set(
+    pool: &SqlitePool,
+    scope: OverrideScope,
+    target_id: i64,
+    preset_id: Option<i64>,
+    gain_db: Option<f64>,
+) {
+    dsp_overrides::set_override(
+        pool,
+        "1",
+        &DspOverride {
+            scope,
+            target_id,
+            preset_id,
+            gain_db,
+        },
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_track_without_overrides_resolves_to_nothing() {
+    let pool = setup().await;
+
+    let resolved = dsp_overrides::resolve_for_track(&pool, "1", TRACK_ID)
+        .await
+        .unwrap();
+
+    assert_eq!(resolved, ResolvedOverride::default());
+}
+
+#[tokio::test]
+async fn test_precedence_track_over_album_over_genre() {
+    let pool = setup().await;
+    let genre_preset = create_preset(&pool, "Genre").await;
+    let album_preset = create_preset(&pool, "Album").await;
+    let track_preset = create_preset(&pool, "Track").await;
+
+    set(
+        &pool,
+        OverrideScope::Genre,
+        ROCK_ID,
+        Some(genre_preset),
+        None,
+    )
+    .await;
+    let resolved = dsp_overrides::resolve_for_track(&pool, "1", TRACK_ID)
+        .await
+        .unwrap();
+    assert_eq!(resolved.preset_id, Some(genre_preset));
+    assert_eq!(resolved.preset_scope, Some(OverrideScope::Genre));
+
+    set(
+        &pool,
+        OverrideScope::Album,
+        ALBUM_ID,
+        Some(album_preset),
+        None,
+    )
+    .await;
+    let resolved = dsp_overrides::resolve_for_track(&pool, "1", TRACK_ID)
+        .await
+        .unwrap();
+    assert_eq!(resolved.preset_id, Some(album_preset));
+    assert_eq!(resolved.preset_scope, Some(OverrideScope::Album));
+
+    set(
+        &pool,
+        OverrideScope::Track,
+        TRACK_ID,
+        Some(track_preset),
+        None,
+    )
+    .await;
+    let resolved = dsp_overrides::resolve_for_track(&pool, "1", TRACK_ID)
+        .await
+        .unwrap();
+    assert_eq!(resolved.preset_id, Some(track_preset));
+    assert_eq!(resolved.preset_scope, Some(OverrideScope::Track));
+}
+
+#[tokio::test]
+async fn test_preset_and_gain_resolve_independently() {
+    let pool = setup().await;
+    let track_preset = create_preset(&pool, "Track").await;
+
+    // Track sets only a preset, album sets only a gain offset
+    set(
+        &pool,
+        OverrideScope::Track,
+        TRACK_ID,
+        Some(track_preset),
+        None,
+    )
+    .await;
+    set(&pool, OverrideScope::Album, ALBUM_ID, None, Some(-1.5)).await;
+    set(&pool, OverrideScope::Genre, ROCK_ID, None, Some(3.0)).await;
+
+    let resolved = dsp_overrides::resolve_for_track(&pool, "1", TRACK_ID)
+        .await
+        .unwrap();
+
+    assert_eq!(resolved.preset_id, Some(track_preset));
+    assert_eq!(resolved.gain_db, Some(-1.5));
+}
+
+#[tokio::test]
+async fn test_lowest_genre_id_wins_between_genres() {
+    let pool = setup().await;
+    let rock = create_preset(&pool, "Rock").await;
+    let jazz = create_preset(&pool, "Jazz").await;
+
+    set(&pool, OverrideScope::Genre, JAZZ_ID, Some(jazz), Some(-2.0)).await;
+    set(&pool, OverrideScope::Genre, ROCK_ID, Some(rock), None).await;
+
+    let resolved = dsp_overrides::resolve_for_track(&pool, "1", TRACK_ID)
+        .await
+        .unwrap();
+
+    assert_eq!(resolved.preset_id, Some(rock));
+    // Rock sets no gain, so Jazz's gain still applies
+    assert_eq!(resolved.gain_db, Some(-2.0));
+}
+
+#[tokio::test]
+async fn test_overrides_of_other_items_are_ignored() {
+    let pool = setup().await;
+    let preset = create_preset(&pool, "Other").await;
+
+    set(&pool, OverrideScope::Track, 99, Some(preset), Some(1.0)).await;
+    set(&pool, OverrideScope::Album, 99, Some(preset), Some(1.0)).await;
+    // Same ID as the track, but an album
+    set(
+        &pool,
+        OverrideScope::Album,
+        TRACK_ID,
+        Some(preset),
+        Some(1.0),
+    )
+    .await;
+
+    let resolved = dsp_overrides::resolve_for_track(&pool, "1", TRACK_ID)
+        .await
+        .unwrap();
+
+    assert_eq!(resolved, ResolvedOverride::default());
+}
+
+#[tokio::test]
+async fn test_set_replaces_and_delete_removes() {
+    let pool = setup().await;
+    let preset = create_preset(&pool, "Bass Cut").await;
+
+    set(&pool, OverrideScope::Album, ALBUM_ID, Some(preset), None).await;
+    set(&pool, OverrideScope::Album, ALBUM_ID, None, Some(-3.0)).await;
+
+    let stored = dsp_overrides::get_override(&pool, "1", OverrideScope::Album, ALBUM_ID)
+        .await
+        .unwrap();
+    assert_eq!(
+        stored,
+        Some(DspOverride {
+            scope: OverrideScope::Album,
+            target_id: ALBUM_ID,
+            preset_id: None,
+            gain_db: Some(-3.0),
+        })
+    );
+    assert_eq!(
+        dsp_overrides::list_overrides(&pool, "1")
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    dsp_overrides::delete_override(&pool, "1", OverrideScope::Album, ALBUM_ID)
+        .await
+        .unwrap();
+    assert!(dsp_overrides::list_overrides(&pool, "1")
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_deleting_preset_keeps_gain_offset() {
+    let pool = setup().await;
+    let preset = create_preset(&pool, "Treble Lift").await;
+
+    set(
+        &pool,
+        OverrideScope::Album,
+        ALBUM_ID,
+        Some(preset),
+        Some(-1.0),
+    )
+    .await;
+
+    sqlx::query("DELETE FROM dsp_presets WHERE id = ?")
+        .bind(preset)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let resolved = dsp_overrides::resolve_for_track(&pool, "1", TRACK_ID)
+        .await
+        .unwrap();
+    assert_eq!(resolved.preset_id, None);
+    assert_eq!(resolved.gain_db, Some(-1.0));
+}
@@ -0,0 +1,208 @@
+use soul_storage::{
+    create_pool,
+    dsp_profiles::{self, DeviceProfileBinding},
+    run_migrations,
+};
+use sqlx::SqlitePool;
+
+async fn setup() -> SqlitePool {
+    let pool = create_pool("sqlite::memory:").await.unwrap();
+    run_migrations(&pool).await.unwrap();
+
+    sqlx::query("INSERT INTO users (id, name, created_at) VALUES ('1', 'Test User', 1234567890)")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    pool
+}
+
+async fn create_preset(pool: &SqlitePool, name: &str) -> i64 {
+    sqlx::query(
+        "INSERT INTO dsp_presets (user_id, name, is_builtin, effect_chain, created_at, updated_at)
+         VALUES (1, ?, 0, '[]', 0, 0)",
+    )
+    .bind(name)
+    .execute(pool)
+    .await
+    .unwrap()
+    .last_insert_rowid()
+}
+
+#[tokio::test]
+async fn test_unbound_device_resolves_to_none() {
+    let pool = setup().await;
+
+    let preset = dsp_profiles::resolve_profile(&pool, "1", "default", "Speakers")
+        .await
+        .unwrap();
+
+    assert_eq!(preset, None);
+}
+
+#[tokio::test]
+async fn test_bound_device_resolves_to_its_preset() {
+    let pool = setup().await;
+    let dac = create_preset(&pool, "DAC").await;
+    let speakers = create_preset(&pool, "Speakers").await;
+
+    dsp_profiles::bind_device(&pool, "1", "default", "USB DAC", dac)
+        .await
+        .unwrap();
+    dsp_profiles::bind_device(&pool, "1", "default", "Speakers", speakers)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        dsp_profiles::resolve_profile(&pool, "1", "default", "USB DAC")
+            .await
+            .unwrap(),
+        Some(dac)
+    );
+    assert_eq!(
+        dsp_profiles::resolve_profile(&pool, "1", "default", "Speakers")
+            .await
+            .unwrap(),
+        Some(speakers)
+    );
+
+    // Same device name on another backend is a different device
+    assert_eq!(
+        dsp_profiles::resolve_profile(&pool, "1", "asio", "USB DAC")
+            .await
+            .unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+async fn test_unbound_device_falls_back_to_default_profile() {
+    let pool = setup().await;
+    let dac = create_preset(&pool, "DAC").await;
+    let fallback = create_preset(&pool, "Fallback").await;
+
+    dsp_profiles::bind_device(&pool, "1", "default", "USB DAC", dac)
+        .await
+        .unwrap();
+    dsp_profiles::set_default_profile(&pool, "1", Some(fallback))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        dsp_profiles::get_default_profile(&pool, "1").await.unwrap(),
+        Some(fallback)
+    );
+    assert_eq!(
+        dsp_profiles::resolve_profile(&pool, "1", "default", "Headphone Amp")
+            .await
+            .unwrap(),
+        Some(fallback)
+    );
+    // A device binding takes precedence over the fallback
+    assert_eq!(
+        dsp_profiles::resolve_profile(&pool, "1", "default", "USB DAC")
+            .await
+            .unwrap(),
+        Some(dac)
+    );
+
+    dsp_profiles::set_default_profile(&pool, "1", None)
+        .await
+        .unwrap();
+    assert_eq!(
+        dsp_profiles::resolve_profile(&pool, "1", "default", "Headphone Amp")
+            .await
+            .unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+async fn test_rebinding_replaces_and_unbinding_removes() {
+    let pool = setup().await;
+    let first = create_preset(&pool, "First").await;
+    let second = create_preset(&pool, "Second").await;
+
+    dsp_profiles::bind_device(&pool, "1", "default", "USB DAC", first)
+        .await
+        .unwrap();
+    dsp_profiles::bind_device(&pool, "1", "default", "USB DAC", second)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        dsp_profiles::list_device_bindings(&pool, "1")
+            .await
+            .unwrap(),
+        vec![DeviceProfileBinding {
+            backend: "default".to_string(),
+            device_name: "USB DAC".to_string(),
+            preset_id: second,
+        }]
+    );
+
+    dsp_profiles::unbind_device(&pool, "1", "default", "USB DAC")
+        .await
+        .unwrap();
+    assert!(dsp_profiles::list_device_bindings(&pool, "1")
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_list_excludes_default_profile() {
+    let pool = setup().await;
+    let dac = create_preset(&pool, "DAC").await;
+    let fallback = create_preset(&pool, "Fallback").await;
+
+    dsp_profiles::bind_device(&pool, "1", "default", "USB DAC", dac)
+        .await
+        .unwrap();
+    dsp_profiles::set_default_profile(&pool, "1", Some(fallback))
+        .await
+        .unwrap();
+
+    let bindings = dsp_profiles::list_device_bindings(&pool, "1")
+        .await
+        .unwrap();
+    assert_eq!(bindings.len(), 1);
+    assert_eq!(bindings[0].device_name, "USB DAC");
+}
+
+#[tokio::test]
+async fn test_deleting_preset_removes_its_bindings() {
+    let pool = setup().await;
+    let dac = create_preset(&pool, "DAC").await;
+
+    dsp_profiles::bind_device(&pool, "1", "default", "USB DAC", dac)
+        .await
+        .unwrap();
+    dsp_profiles::set_default_profile(&pool, "1", Some(dac))
+        .await
+        .unwrap();
+
+    sqlx::query("DELETE FROM dsp_presets WHERE id = ?")
+        .bind(dac)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    assert!(dsp_profiles::list_device_bindings(&pool, "1")
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        dsp_profiles::get_default_profile(&pool, "1").await.unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+async fn test_binding_unknown_preset_fails() {
+    let pool = setup().await;
+
+    let result = dsp_profiles::bind_device(&pool, "1", "default", "USB DAC", 999).await;
+
+    assert!(result.is_err());
+}
@@ -0,0 +1,174 @@
+use soul_storage::{
+    create_pool,
+    loudness::{self, TrackCuePoints, TrackDynamicRange, TrackLoudness},
+    run_migrations,
+};
+use sqlx::SqlitePool;
+
+const ALBUM_ID: i64 = 7;
+
+/// Three tracks on album 7 and one single
+async fn setup() -> SqlitePool {
+    let pool = create_pool("sqlite::memory:").await.unwrap();
+    run_migrations(&pool).await.unwrap();
+
+    for sql in [
+        "INSERT INTO albums (id, title) VALUES (7, 'Loudness War')",
+        "INSERT INTO tracks (id, title, album_id) VALUES (1, 'Quiet', 7), (2, 'Loud', 7), (3, 'Louder', 7)",
+        "INSERT INTO tracks (id, title) VALUES (4, 'Single')",
+    ] {
+        sqlx::query(sql).execute(&pool).await.unwrap();
+    }
+
+    pool
+}
+
+async fn store(pool: &SqlitePool, track_id: i64, dr: f64, plr: f64) {
+    let track = TrackLoudness {
+        track_id,
+        lufs_integrated: Some(-10.0),
+        true_peak_dbfs: Some(plr - 10.0),
+        dr_track: Some(dr),
+        plr: Some(plr),
+        ..Default::default()
+    };
+    loudness::update_track_loudness(pool, track_id, &track, "test")
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_dynamic_range_round_trip() {
+    let pool = setup().await;
+    store(&pool, 1, 12.4, 14.2).await;
+
+    let loaded = loudness::get_track_loudness(&pool, 1)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(loaded.is_analyzed());
+    assert_eq!(loaded.dr_track, Some(12.4));
+    assert_eq!(loaded.plr, Some(14.2));
+    assert_eq!(loaded.dr_album, None);
+
+    // Not analyzed yet
+    let other = loudness::get_track_loudness(&pool, 2)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(other.dr_track, None);
+}
+
+#[tokio::test]
+async fn test_album_dynamic_range() {
+    let pool = setup().await;
+    store(&pool, 1, 12.0, 14.0).await;
+    store(&pool, 2, 6.0, 8.0).await;
+    store(&pool, 4, 3.0, 5.0).await;
+
+    // Only analyzed tracks of the album
+    let mut track_drs = loudness::get_album_track_dynamic_ranges(&pool, ALBUM_ID)
+        .await
+        .unwrap();
+    track_drs.sort_by(f64::total_cmp);
+    assert_eq!(track_drs, vec![6.0, 12.0]);
+
+    loudness::update_album_dynamic_range(&pool, ALBUM_ID, 9.0)
+        .await
+        .unwrap();
+    for track_id in [1, 2, 3] {
+        let loaded = loudness::get_track_loudness(&pool, track_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(loaded.dr_album, Some(9.0));
+    }
+    let single = loudness::get_track_loudness(&pool, 4)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(single.dr_album, None);
+}
+
+#[tokio::test]
+async fn test_find_brickwalled_tracks() {
+    let pool = setup().await;
+    store(&pool, 1, 12.0, 14.0).await;
+    store(&pool, 2, 6.0, 8.5).await;
+    store(&pool, 3, 4.6, 6.0).await;
+    store(&pool, 4, 6.0, 7.0).await;
+
+    // Least dynamic first, ties broken by PLR
+    let tracks = loudness::get_tracks_by_dynamic_range(&pool, 7.0, 10)
+        .await
+        .unwrap();
+    let ids: Vec<i64> = tracks.iter().map(|t| t.track_id).collect();
+    assert_eq!(ids, vec![3, 4, 2]);
+    assert_eq!(
+        tracks[0],
+        TrackDynamicRange {
+            track_id: 3,
+            dr_track: 4.6,
+            dr_album: None,
+            plr: Some(6.0),
+        }
+    );
+
+    let limited = loudness::get_tracks_by_dynamic_range(&pool, 7.0, 1)
+        .await
+        .unwrap();
+    assert_eq!(limited.len(), 1);
+
+    assert!(loudness::get_tracks_by_dynamic_range(&pool, 4.0, 10)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_cue_points_round_trip() {
+    let pool = setup().await;
+    assert_eq!(
+        loudness::get_track_cue_points(&pool, 1).await.unwrap(),
+        None
+    );
+
+    let cues = TrackCuePoints {
+        cue_in: 0.35,
+        cue_out: 212.8,
+        fade_out: Some(204.1),
+    };
+    loudness::update_track_cue_points(&pool, 1, Some(&cues))
+        .await
+        .unwrap();
+    assert_eq!(
+        loudness::get_track_cue_points(&pool, 1).await.unwrap(),
+        Some(cues)
+    );
+
+    // Hard ending
+    let hard = TrackCuePoints {
+        fade_out: None,
+        ..cues
+    };
+    loudness::update_track_cue_points(&pool, 2, Some(&hard))
+        .await
+        .unwrap();
+    assert_eq!(
+        loudness::get_track_cue_points(&pool, 2).await.unwrap(),
+        Some(hard)
+    );
+
+    // Cleared
+    loudness::update_track_cue_points(&pool, 1, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        loudness::get_track_cue_points(&pool, 1).await.unwrap(),
+        None
+    );
+    assert_eq!(
+        loudness::get_track_cue_points(&pool, 99).await.unwrap(),
+        None
+    );
+}
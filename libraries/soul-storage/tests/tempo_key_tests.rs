@@ -0,0 +1,160 @@
+use soul_storage::{
+    create_pool, run_migrations,
+    tempo_key::{self, TempoKeyAnalysis, TempoKeyFilter, TempoKeySort, TempoKeySource},
+    tempo_key_queue,
+};
+use sqlx::SqlitePool;
+
+async fn setup() -> SqlitePool {
+    let pool = create_pool("sqlite::memory:").await.unwrap();
+    run_migrations(&pool).await.unwrap();
+
+    sqlx::query(
+        "INSERT INTO tracks (id, title) VALUES (1, 'Slow'), (2, 'House'), (3, 'Jungle'), (4, 'Untagged')",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    pool
+}
+
+fn analysis(bpm: f64, key: &str) -> TempoKeyAnalysis {
+    TempoKeyAnalysis {
+        bpm: Some((bpm, 0.8)),
+        musical_key: Some((key.to_string(), 0.6)),
+    }
+}
+
+#[tokio::test]
+async fn test_tag_values_round_trip() {
+    let pool = setup().await;
+    tempo_key::set_from_tags(&pool, 1, Some(92.0), Some("8A"))
+        .await
+        .unwrap();
+
+    let stored = tempo_key::get_track_tempo_key(&pool, 1)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.bpm, Some(92.0));
+    assert_eq!(stored.musical_key.as_deref(), Some("8A"));
+    assert_eq!(stored.bpm_confidence, None);
+    assert_eq!(stored.source, Some(TempoKeySource::Tag));
+    assert_eq!(stored.analyzed_at, None);
+
+    assert!(tempo_key::get_track_tempo_key(&pool, 99)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_analysis_keeps_tag_values() {
+    let pool = setup().await;
+    tempo_key::set_from_tags(&pool, 1, Some(92.0), None)
+        .await
+        .unwrap();
+
+    // Analysis only fills in the missing key
+    tempo_key::update_from_analysis(&pool, 1, &analysis(184.0, "3B"), false)
+        .await
+        .unwrap();
+    let stored = tempo_key::get_track_tempo_key(&pool, 1)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.bpm, Some(92.0));
+    assert_eq!(stored.bpm_confidence, None);
+    assert_eq!(stored.musical_key.as_deref(), Some("3B"));
+    assert_eq!(stored.key_confidence, Some(0.6));
+    assert_eq!(stored.source, Some(TempoKeySource::Tag));
+    assert!(stored.analyzed_at.is_some());
+
+    // Unless asked to replace the tags
+    tempo_key::update_from_analysis(&pool, 1, &analysis(184.0, "3B"), true)
+        .await
+        .unwrap();
+    let stored = tempo_key::get_track_tempo_key(&pool, 1)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.bpm, Some(184.0));
+    assert_eq!(stored.bpm_confidence, Some(0.8));
+    assert_eq!(stored.source, Some(TempoKeySource::Analysis));
+}
+
+#[tokio::test]
+async fn test_find_tracks_filters_and_sorts() {
+    let pool = setup().await;
+    tempo_key::update_from_analysis(&pool, 1, &analysis(92.0, "10B"), false)
+        .await
+        .unwrap();
+    tempo_key::update_from_analysis(&pool, 2, &analysis(124.0, "8A"), false)
+        .await
+        .unwrap();
+    tempo_key::update_from_analysis(&pool, 3, &analysis(172.0, "1A"), false)
+        .await
+        .unwrap();
+
+    let ids = |tracks: Vec<tempo_key::TrackTempoKey>| -> Vec<i64> {
+        tracks.into_iter().map(|t| t.track_id).collect()
+    };
+
+    // Untagged, unanalyzed tracks never match
+    let all = tempo_key::find_tracks(&pool, &TempoKeyFilter::default())
+        .await
+        .unwrap();
+    assert_eq!(ids(all), vec![1, 2, 3]);
+
+    let by_key = TempoKeyFilter {
+        sort: TempoKeySort::Key,
+        ..Default::default()
+    };
+    let sorted = tempo_key::find_tracks(&pool, &by_key).await.unwrap();
+    assert_eq!(ids(sorted), vec![3, 2, 1]);
+
+    let range = TempoKeyFilter {
+        min_bpm: Some(100.0),
+        max_bpm: Some(180.0),
+        ..Default::default()
+    };
+    let in_range = tempo_key::find_tracks(&pool, &range).await.unwrap();
+    assert_eq!(ids(in_range), vec![2, 3]);
+
+    let keys = TempoKeyFilter {
+        keys: vec!["8A".to_string(), "10B".to_string()],
+        ..Default::default()
+    };
+    let matching = tempo_key::find_tracks(&pool, &keys).await.unwrap();
+    assert_eq!(ids(matching), vec![1, 2]);
+}
+
+#[tokio::test]
+async fn test_queue_skips_fully_tagged_tracks() {
+    let pool = setup().await;
+    tempo_key::set_from_tags(&pool, 1, Some(92.0), Some("8A"))
+        .await
+        .unwrap();
+    tempo_key::set_from_tags(&pool, 2, Some(124.0), None)
+        .await
+        .unwrap();
+
+    let queued = tempo_key_queue::enqueue_unanalyzed(&pool, 0).await.unwrap();
+    assert_eq!(queued, 3);
+    assert_eq!(tempo_key_queue::pending_count(&pool).await.unwrap(), 3);
+
+    // Re-queueing is a no-op
+    assert_eq!(
+        tempo_key_queue::enqueue_unanalyzed(&pool, 0).await.unwrap(),
+        0
+    );
+
+    // Priority items come first
+    tempo_key_queue::enqueue(&pool, 4, 100).await.unwrap();
+    let next = tempo_key_queue::get_next(&pool).await.unwrap().unwrap();
+    assert_eq!(next.track_id, 4);
+
+    tempo_key_queue::complete(&pool, next.id).await.unwrap();
+    assert_eq!(tempo_key_queue::pending_count(&pool).await.unwrap(), 2);
+}